@@ -39,7 +39,7 @@ fuzz_target!(|data: &[u8]| {
 	};
 
 	let password_hash = orion::pwhash::hash_password(&pwhash_password, c).unwrap();
-	assert!(orion::pwhash::hash_password_verify(&password_hash, &pwhash_password, c).unwrap());
+	assert!(orion::pwhash::hash_password_verify(&password_hash, &pwhash_password).unwrap());
 
 	// orion::kdf
 	let kdf_salt = orion::kdf::Salt::from_slice(&salt).unwrap();
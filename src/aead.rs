@@ -37,6 +37,26 @@
 //! - Uses XChaCha20Poly1305 with no additional data.
 //! - When using `seal` and `open` then the separation of tags, nonces and
 //!   ciphertext are automatically handled.
+//! - `seal_with_password` derives the key from a password with
+//!   PBKDF2-HMAC-SHA512 and a random 64-byte salt. The output starts with a
+//!   69-byte header, holding a format version byte, the iteration count as a
+//!   little-endian 32-bit integer and the salt, followed by the output of
+//!   `seal`. The header is authenticated as additional data. `open_with_password`
+//!   reads the parameters back from the header.
+//! - Data that is too large to be held in memory at once can be encrypted in
+//!   chunks with `orion::aead::streaming`.
+//! - The allowed plaintext lengths are given by `PLAINTEXT_LIMITS`.
+//!   `seal_with_limits` and `open_with_limits` apply stricter `InputLimits` on
+//!   top of them. `open_with_limits` rejects a ciphertext by its length before
+//!   authenticating it.
+//! - `seal_with_provider` and `open_with_provider` are `seal` and `open` with
+//!   the key requested from a `KeyProvider`, such as an OS keychain, for the
+//!   duration of the call.
+//! - `seal_deterministic` and `open_deterministic` use AES-SIV, with a
+//!   `DeterministicSecretKey` and no nonce. They return a vector where the
+//!   first 16 bytes are the synthetic IV, which is also the tag, and the rest
+//!   is the ciphertext. `ad` is authenticated but not encrypted, and the
+//!   plaintext may be empty.
 //!
 //! # Parameters:
 //! - `plaintext`:  The data to be encrypted.
@@ -44,16 +64,35 @@
 //! - `ciphertext_with_tag_and_nonce`:  The data to be decrypted with the first
 //!   24 bytes being the nonce and the last
 //! 16 bytes being the corresponding Poly1305 tag.
+//! - `password`: The password that the key is derived from.
+//! - `params`: The algorithm and iteration count used to derive the key. The
+//!   output length of `params` is ignored, as the key is always 32 bytes.
+//! - `sealed`: The output of `seal_with_password`.
+//! - `provider`: The `KeyProvider` that the secret key is requested from.
+//! - `limits`: Stricter limits on the length of the plaintext, such as a
+//!   maximum message size of a protocol.
+//! - `ad`: Additional data that a deterministic ciphertext is bound to, such
+//!   as the table and column name it is stored in.
+//! - `siv_with_ciphertext`: The output of `seal_deterministic`.
 //!
 //! # Errors:
 //! An error will be returned if:
 //! - `secret_key` is not 32 bytes.
-//! - `plaintext` is empty.
-//! - `plaintext` is longer than (2^32)-2.
+//! - The length of `plaintext`, or of the plaintext in
+//!   `ciphertext_with_tag_and_nonce` or `sealed`, is outside of
+//!   `PLAINTEXT_LIMITS`: it is empty or longer than (2^32-1) * 64 bytes.
+//! - The length of the plaintext is outside of `limits`.
 //! - `ciphertext_with_tag_and_nonce` is less than 41 bytes.
-//! - `ciphertext_with_tag_and_nonce` is longer than (2^32)-2.
 //! - The received tag does not match the calculated tag when calling
-//!   `aead::open()`.
+//!   `aead::open()` or `aead::open_with_password()`.
+//! - `sealed` is less than 110 bytes.
+//! - The header of `sealed` has an unknown format version or an iteration
+//!   count of 0.
+//! - The iteration count of `params`, or the one stored in `sealed`, is above
+//!   that of `Params::sensitive()`.
+//! - `siv_with_ciphertext` is less than 16 bytes, or its synthetic IV does not
+//!   match the calculated one when calling `aead::open_deterministic()`.
+//! - `provider` fails to return a key.
 //! - The `OsRng` fails to initialize or read from its source.
 //!
 //! # Security:
@@ -62,6 +101,18 @@
 //! the security of all data that has been encrypted with that given key is
 //! compromised.
 //! - To securely generate a strong key, use `SecretKey::default()`.
+//! - `open_with_password` runs the key derivation with the iteration count
+//!   stored in `sealed`, before the data can be authenticated. Iteration
+//!   counts above that of `Params::sensitive()` are rejected before deriving
+//!   the key, so data from an untrusted source cannot make it run for longer
+//!   than a key derivation with `Params::sensitive()`.
+//! - `seal_deterministic` is __**deterministic**__: the same plaintext and `ad`
+//!   encrypted with the same key always give the same ciphertext. Anyone who
+//!   can see the ciphertexts learns which plaintexts are equal, and how often
+//!   each one occurs. This is intended for equality-searchable data, such as
+//!   blind indexes of encrypted database columns, and should not be used for
+//!   anything else. A `DeterministicSecretKey` should only ever be used for
+//!   one such column, with `ad` naming it.
 //!
 //! # Example:
 //! ```
@@ -71,23 +122,130 @@
 //! let ciphertext = aead::seal(&secret_key, "Secret message".as_bytes()).unwrap();
 //! let decrypted_data = aead::open(&secret_key, &ciphertext).unwrap();
 //! ```
+//!
+//! Encrypting with a key derived from a password:
+//! ```
+//! use orion::{aead, kdf};
+//!
+//! let password = kdf::Password::from_slice(b"Secret password").unwrap();
+//! let sealed =
+//! 	aead::seal_with_password(&password, &kdf::Params::interactive(), b"Secret message")
+//! 		.unwrap();
+//! let decrypted_data = aead::open_with_password(&password, &sealed).unwrap();
+//! assert_eq!(decrypted_data, b"Secret message");
+//! ```
+//!
+//! Encrypting with a key from an external key store:
+//! ```
+//! use orion::{aead, errors::UnknownCryptoError};
+//!
+//! struct Keychain;
+//!
+//! impl aead::KeyProvider for Keychain {
+//! 	fn provide_key(&self) -> Result<aead::SecretKey, UnknownCryptoError> {
+//! 		// Read the key from the keychain here.
+//! 		aead::SecretKey::from_slice(&[0x42; 32])
+//! 	}
+//! }
+//!
+//! let ciphertext = aead::seal_with_provider(&Keychain, b"Secret message").unwrap();
+//! let decrypted_data = aead::open_with_provider(&Keychain, &ciphertext).unwrap();
+//! assert_eq!(decrypted_data, b"Secret message");
+//! ```
+//!
+//! Deterministic encryption of an indexed column:
+//! ```
+//! use orion::aead;
+//!
+//! let secret_key = aead::DeterministicSecretKey::generate().unwrap();
+//! let stored = aead::seal_deterministic(&secret_key, b"alice@example.com", b"users.email").unwrap();
+//!
+//! // Looking up the same value gives the same ciphertext.
+//! let lookup = aead::seal_deterministic(&secret_key, b"alice@example.com", b"users.email").unwrap();
+//! assert_eq!(stored, lookup);
+//!
+//! let decrypted_data = aead::open_deterministic(&secret_key, &stored, b"users.email").unwrap();
+//! assert_eq!(decrypted_data, b"alice@example.com");
+//! ```
 
-pub use crate::hltypes::SecretKey;
+pub use crate::hltypes::{InputLimits, KeyId, KeyProvider, SecretKey};
 use crate::{
 	errors::UnknownCryptoError,
 	hazardous::{
 		aead,
-		constants::{POLY1305_BLOCKSIZE, XCHACHA_NONCESIZE},
+		constants::{AES_SIV_TAGSIZE, POLY1305_BLOCKSIZE, XCHACHA_NONCESIZE},
 		stream::{chacha20, xchacha20::Nonce},
 	},
+	hltypes::{Algorithm, Params, Password, Salt},
+	kdf,
 };
 
+/// Authenticated secret-key encryption of data in chunks.
+pub mod streaming;
+
+/// A type to represent the secret key of `seal_deterministic` and
+/// `open_deterministic`. It is 64 bytes, and is a different type from
+/// `SecretKey` so that a key for deterministic encryption cannot be passed to
+/// `seal` by mistake, or the other way around.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 64 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type DeterministicSecretKey = aead::aes_siv::SecretKey;
+
+/// The format version of `seal_with_password` output that uses
+/// PBKDF2-HMAC-SHA512 and XChaCha20Poly1305.
+const PASSWORD_FORMAT_V1: u8 = 1;
+
+/// The size of the salt generated by `seal_with_password`.
+const PASSWORD_SALT_SIZE: usize = 64;
+
+/// The size of the header of `seal_with_password` output: the format version,
+/// the iteration count and the salt.
+const PASSWORD_HEADER_SIZE: usize = 1 + 4 + PASSWORD_SALT_SIZE;
+
+/// XChaCha20Poly1305 encrypts at most 2^32-1 blocks of 64 bytes with one
+/// nonce.
+const XCHACHA_MAX_PLAINTEXT: u64 = (u32::max_value() as u64) * 64;
+
+/// The lengths of `plaintext` that `seal()` accepts and `open()` returns: at
+/// least 1 byte, and at most the (2^32-1) * 64 bytes XChaCha20Poly1305 can
+/// encrypt with one nonce.
+pub const PLAINTEXT_LIMITS: InputLimits = InputLimits::new_unchecked(
+	1,
+	if (usize::max_value() as u64) < XCHACHA_MAX_PLAINTEXT {
+		usize::max_value()
+	} else {
+		XCHACHA_MAX_PLAINTEXT as usize
+	},
+);
+
 #[must_use]
 /// Authenticated encryption using XChaCha20Poly1305.
 pub fn seal(secret_key: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
-	if plaintext.is_empty() {
-		return Err(UnknownCryptoError);
-	}
+	seal_with_limits(secret_key, plaintext, &PLAINTEXT_LIMITS)
+}
+
+#[must_use]
+/// Authenticated decryption using XChaCha20Poly1305.
+pub fn open(
+	secret_key: &SecretKey,
+	ciphertext_with_tag_and_nonce: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	open_with_limits(secret_key, ciphertext_with_tag_and_nonce, &PLAINTEXT_LIMITS)
+}
+
+#[must_use]
+/// Authenticated encryption using XChaCha20Poly1305, of a `plaintext` whose
+/// length is within both `limits` and `PLAINTEXT_LIMITS`.
+pub fn seal_with_limits(
+	secret_key: &SecretKey,
+	plaintext: &[u8],
+	limits: &InputLimits,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	PLAINTEXT_LIMITS.check(plaintext.len())?;
+	limits.check(plaintext.len())?;
 
 	let nonce = Nonce::generate()?;
 
@@ -106,15 +264,137 @@ pub fn seal(secret_key: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, Unknown
 }
 
 #[must_use]
-/// Authenticated decryption using XChaCha20Poly1305.
-pub fn open(
+/// Authenticated decryption using XChaCha20Poly1305, of a ciphertext whose
+/// plaintext length is within both `limits` and `PLAINTEXT_LIMITS`. The length
+/// is checked before the ciphertext is authenticated.
+pub fn open_with_limits(
 	secret_key: &SecretKey,
 	ciphertext_with_tag_and_nonce: &[u8],
+	limits: &InputLimits,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let plaintext_len = ciphertext_with_tag_and_nonce
+		.len()
+		.checked_sub(XCHACHA_NONCESIZE + POLY1305_BLOCKSIZE)
+		.ok_or(UnknownCryptoError)?;
+	PLAINTEXT_LIMITS.check(plaintext_len)?;
+	limits.check(plaintext_len)?;
+
+	let mut dst_out = vec![0u8; plaintext_len];
+
+	aead::xchacha20poly1305::open(
+		&chacha20::SecretKey::from_slice(&secret_key.unprotected_as_bytes())?,
+		&Nonce::from_slice(&ciphertext_with_tag_and_nonce[..XCHACHA_NONCESIZE])?,
+		&ciphertext_with_tag_and_nonce[XCHACHA_NONCESIZE..],
+		None,
+		&mut dst_out,
+	)?;
+
+	Ok(dst_out)
+}
+
+#[must_use]
+/// Authenticated encryption using XChaCha20Poly1305, with the key requested
+/// from `provider`.
+pub fn seal_with_provider(
+	provider: &impl KeyProvider,
+	plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	seal(&provider.provide_key()?, plaintext)
+}
+
+#[must_use]
+/// Authenticated decryption using XChaCha20Poly1305, with the key requested
+/// from `provider`.
+pub fn open_with_provider(
+	provider: &impl KeyProvider,
+	ciphertext_with_tag_and_nonce: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	open(&provider.provide_key()?, ciphertext_with_tag_and_nonce)
+}
+
+#[must_use]
+/// Derive the 32-byte key used by `seal_with_password` and
+/// `open_with_password`.
+fn password_key(
+	password: &Password,
+	salt: &Salt,
+	params: &Params,
+) -> Result<SecretKey, UnknownCryptoError> {
+	kdf::derive_key_with_params(password, salt, &params.with_output_length(32)?)
+}
+
+#[must_use]
+/// Authenticated encryption using XChaCha20Poly1305, with a key derived from
+/// `password` using `params`.
+pub fn seal_with_password(
+	password: &Password,
+	params: &Params,
+	plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	PLAINTEXT_LIMITS.check(plaintext.len())?;
+	if params.iterations() > Params::sensitive().iterations() {
+		return Err(UnknownCryptoError);
+	}
+
+	let salt = Salt::generate(PASSWORD_SALT_SIZE)?;
+	let mut header = [0u8; PASSWORD_HEADER_SIZE];
+	header[0] = match params.algorithm() {
+		Algorithm::Pbkdf2Sha512 => PASSWORD_FORMAT_V1,
+	};
+	// `Params` ensures that the iteration count fits in a u32
+	header[1..5].copy_from_slice(&(params.iterations() as u32).to_le_bytes());
+	header[5..].copy_from_slice(salt.as_bytes());
+
+	let secret_key = password_key(password, &salt, params)?;
+	let nonce = Nonce::generate()?;
+
+	let sealed_len =
+		PASSWORD_HEADER_SIZE + plaintext.len() + (XCHACHA_NONCESIZE + POLY1305_BLOCKSIZE);
+	let mut dst_out = vec![0u8; sealed_len];
+	dst_out[..PASSWORD_HEADER_SIZE].copy_from_slice(&header);
+	dst_out[PASSWORD_HEADER_SIZE..(PASSWORD_HEADER_SIZE + XCHACHA_NONCESIZE)]
+		.copy_from_slice(&nonce.as_bytes());
+
+	aead::xchacha20poly1305::seal(
+		&chacha20::SecretKey::from_slice(&secret_key.unprotected_as_bytes())?,
+		&nonce,
+		plaintext,
+		Some(&header),
+		&mut dst_out[(PASSWORD_HEADER_SIZE + XCHACHA_NONCESIZE)..],
+	)?;
+
+	Ok(dst_out)
+}
+
+#[must_use]
+/// Authenticated decryption using XChaCha20Poly1305, with a key derived from
+/// `password` using the parameters stored in `sealed`.
+pub fn open_with_password(
+	password: &Password,
+	sealed: &[u8],
 ) -> Result<Vec<u8>, UnknownCryptoError> {
-	// `+ 1` to avoid empty ciphertexts
-	if ciphertext_with_tag_and_nonce.len() < (XCHACHA_NONCESIZE + POLY1305_BLOCKSIZE + 1) {
+	let plaintext_len = sealed
+		.len()
+		.checked_sub(PASSWORD_HEADER_SIZE + XCHACHA_NONCESIZE + POLY1305_BLOCKSIZE)
+		.ok_or(UnknownCryptoError)?;
+	PLAINTEXT_LIMITS.check(plaintext_len)?;
+
+	let (header, ciphertext_with_tag_and_nonce) = sealed.split_at(PASSWORD_HEADER_SIZE);
+	let algorithm = match header[0] {
+		PASSWORD_FORMAT_V1 => Algorithm::Pbkdf2Sha512,
+		_ => return Err(UnknownCryptoError),
+	};
+	let mut iterations = [0u8; 4];
+	iterations.copy_from_slice(&header[1..5]);
+	let iterations = u32::from_le_bytes(iterations) as usize;
+	// The header is not authenticated until after the key is derived
+	if iterations > Params::sensitive().iterations() {
 		return Err(UnknownCryptoError);
 	}
+	let params = Params::with_algorithm(algorithm, iterations)?;
+	let salt = Salt::from_slice(&header[5..])?;
+
+	let secret_key = password_key(password, &salt, &params)?;
 
 	let mut dst_out =
 		vec![0u8; ciphertext_with_tag_and_nonce.len() - (XCHACHA_NONCESIZE + POLY1305_BLOCKSIZE)];
@@ -123,13 +403,47 @@ pub fn open(
 		&chacha20::SecretKey::from_slice(&secret_key.unprotected_as_bytes())?,
 		&Nonce::from_slice(&ciphertext_with_tag_and_nonce[..XCHACHA_NONCESIZE])?,
 		&ciphertext_with_tag_and_nonce[XCHACHA_NONCESIZE..],
-		None,
+		Some(header),
 		&mut dst_out,
 	)?;
 
 	Ok(dst_out)
 }
 
+#[must_use]
+/// Deterministic authenticated encryption using AES-SIV, with the ciphertext
+/// bound to `ad`. The same `plaintext` and `ad` always give the same output
+/// with the same key.
+pub fn seal_deterministic(
+	secret_key: &DeterministicSecretKey,
+	plaintext: &[u8],
+	ad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let mut dst_out = vec![0u8; plaintext.len() + AES_SIV_TAGSIZE];
+	aead::aes_siv::seal(secret_key, plaintext, Some(ad), &mut dst_out)?;
+
+	Ok(dst_out)
+}
+
+#[must_use]
+/// Authenticated decryption using AES-SIV, of the output of
+/// `seal_deterministic` with the same `ad`.
+pub fn open_deterministic(
+	secret_key: &DeterministicSecretKey,
+	siv_with_ciphertext: &[u8],
+	ad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let plaintext_len = siv_with_ciphertext
+		.len()
+		.checked_sub(AES_SIV_TAGSIZE)
+		.ok_or(UnknownCryptoError)?;
+
+	let mut dst_out = vec![0u8; plaintext_len];
+	aead::aes_siv::open(secret_key, siv_with_ciphertext, Some(ad), &mut dst_out)?;
+
+	Ok(dst_out)
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -221,6 +535,293 @@ mod public {
 		}
 	}
 
+	mod test_seal_open_with_limits {
+		use super::*;
+
+		#[test]
+		fn test_plaintext_limits() {
+			assert_eq!(PLAINTEXT_LIMITS.min_len(), 1);
+			if cfg!(target_pointer_width = "64") {
+				assert_eq!(PLAINTEXT_LIMITS.max_len() as u64, 274_877_906_880);
+			}
+			assert!(PLAINTEXT_LIMITS.check(0).is_err());
+		}
+
+		#[test]
+		fn test_within_limits() {
+			let key = SecretKey::default();
+			let limits = InputLimits::new(4, 8).unwrap();
+
+			for len in 4..=8 {
+				let plaintext = vec![1u8; len];
+				let ciphertext = seal_with_limits(&key, &plaintext, &limits).unwrap();
+				assert_eq!(
+					open_with_limits(&key, &ciphertext, &limits).unwrap(),
+					plaintext
+				);
+				assert_eq!(open(&key, &ciphertext).unwrap(), plaintext);
+			}
+		}
+
+		#[test]
+		fn test_outside_limits_err() {
+			let key = SecretKey::default();
+			let limits = InputLimits::new(4, 8).unwrap();
+
+			assert!(seal_with_limits(&key, &[1u8; 3], &limits).is_err());
+			assert!(seal_with_limits(&key, &[1u8; 9], &limits).is_err());
+
+			let short = seal(&key, &[1u8; 3]).unwrap();
+			let long = seal(&key, &[1u8; 9]).unwrap();
+			assert!(open_with_limits(&key, &short, &limits).is_err());
+			assert!(open_with_limits(&key, &long, &limits).is_err());
+		}
+
+		#[test]
+		fn test_limits_cannot_allow_empty() {
+			let key = SecretKey::default();
+			let limits = InputLimits::new(0, 8).unwrap();
+
+			assert!(seal_with_limits(&key, &[], &limits).is_err());
+			assert!(open_with_limits(&key, &[0u8; 40], &limits).is_err());
+			assert!(open_with_limits(&key, &[0u8; 39], &limits).is_err());
+			assert!(open_with_limits(&key, &[], &limits).is_err());
+		}
+	}
+
+	mod test_seal_open_with_password {
+		use super::*;
+
+		fn password() -> Password { Password::from_slice(b"Secret password").unwrap() }
+
+		fn params() -> Params { Params::new(100).unwrap() }
+
+		#[test]
+		fn test_seal_open() {
+			let sealed = seal_with_password(&password(), &params(), b"Secret message").unwrap();
+			assert_eq!(sealed.len(), 69 + 24 + 14 + 16);
+			assert_eq!(
+				open_with_password(&password(), &sealed).unwrap(),
+				b"Secret message".to_vec()
+			);
+		}
+
+		#[test]
+		fn test_header() {
+			let sealed = seal_with_password(&password(), &params(), b"Secret message").unwrap();
+			assert_eq!(sealed[0], 1);
+			assert_eq!(sealed[1..5], 100u32.to_le_bytes());
+
+			// A fresh salt and nonce are used every time.
+			let sealed_again =
+				seal_with_password(&password(), &params(), b"Secret message").unwrap();
+			assert_ne!(sealed[5..69], sealed_again[5..69]);
+			assert_ne!(sealed[69..93], sealed_again[69..93]);
+		}
+
+		#[test]
+		fn test_output_length_of_params_ignored() {
+			let params = params().with_output_length(10).unwrap();
+			let sealed = seal_with_password(&password(), &params, b"Secret message").unwrap();
+			assert!(open_with_password(&password(), &sealed).is_ok());
+		}
+
+		#[test]
+		fn test_plaintext_empty_err() {
+			assert!(seal_with_password(&password(), &params(), b"").is_err());
+		}
+
+		#[test]
+		fn test_iterations_above_sensitive_err() {
+			let max = Params::sensitive().iterations();
+			let params = Params::new(max + 1).unwrap();
+			assert!(seal_with_password(&password(), &params, b"Secret message").is_err());
+		}
+
+		#[test]
+		fn test_max_iterations_in_header_err() {
+			use std::time::{Duration, Instant};
+
+			let mut sealed = seal_with_password(&password(), &params(), b"Secret message").unwrap();
+			sealed[1..5].copy_from_slice(&u32::max_value().to_le_bytes());
+
+			// Rejected before deriving a key with 2^32-1 iterations.
+			let start = Instant::now();
+			assert!(open_with_password(&password(), &sealed).is_err());
+			assert!(start.elapsed() < Duration::from_secs(1));
+
+			let max = Params::sensitive().iterations() as u32;
+			sealed[1..5].copy_from_slice(&(max + 1).to_le_bytes());
+			assert!(open_with_password(&password(), &sealed).is_err());
+		}
+
+		#[test]
+		fn test_sealed_too_short_err() {
+			assert!(open_with_password(&password(), &[1u8; 109]).is_err());
+		}
+
+		#[test]
+		fn test_diff_password_err() {
+			let sealed = seal_with_password(&password(), &params(), b"Secret message").unwrap();
+			let bad_password = Password::from_slice(b"Wrong password").unwrap();
+			assert!(open_with_password(&bad_password, &sealed).is_err());
+		}
+
+		#[test]
+		fn test_modified_header_err() {
+			let sealed = seal_with_password(&password(), &params(), b"Secret message").unwrap();
+
+			// Unknown format version
+			let mut modified = sealed.clone();
+			modified[0] = 2;
+			assert!(open_with_password(&password(), &modified).is_err());
+
+			// Zero iterations
+			let mut modified = sealed.clone();
+			modified[1..5].copy_from_slice(&[0u8; 4]);
+			assert!(open_with_password(&password(), &modified).is_err());
+
+			// Different iterations
+			let mut modified = sealed.clone();
+			modified[1] ^= 1;
+			assert!(open_with_password(&password(), &modified).is_err());
+
+			// Modified salt
+			let mut modified = sealed.clone();
+			modified[40] ^= 1;
+			assert!(open_with_password(&password(), &modified).is_err());
+		}
+
+		#[test]
+		fn test_modified_ciphertext_and_tag_err() {
+			let sealed = seal_with_password(&password(), &params(), b"Secret message").unwrap();
+			let sealed_len = sealed.len();
+
+			let mut modified = sealed.clone();
+			modified[80] ^= 1;
+			assert!(open_with_password(&password(), &modified).is_err());
+
+			let mut modified = sealed.clone();
+			modified[sealed_len - 6] ^= 1;
+			assert!(open_with_password(&password(), &modified).is_err());
+		}
+
+		#[test]
+		fn test_not_compatible_with_open() {
+			let sealed = seal_with_password(&password(), &params(), b"Secret message").unwrap();
+			let key = SecretKey::default();
+			assert!(open(&key, &sealed).is_err());
+		}
+	}
+
+	/// A `KeyProvider` that holds the key bytes, like an external key store.
+	struct StoredKey([u8; 32]);
+
+	impl KeyProvider for StoredKey {
+		fn provide_key(&self) -> Result<SecretKey, UnknownCryptoError> {
+			SecretKey::from_slice(&self.0)
+		}
+	}
+
+	/// A `KeyProvider` that refuses to give out its key.
+	struct LockedKey;
+
+	impl KeyProvider for LockedKey {
+		fn provide_key(&self) -> Result<SecretKey, UnknownCryptoError> { Err(UnknownCryptoError) }
+	}
+
+	mod test_seal_open_deterministic {
+		use super::*;
+
+		#[test]
+		fn test_seal_open_deterministic() {
+			let key = DeterministicSecretKey::generate().unwrap();
+
+			let ciphertext = seal_deterministic(&key, b"Secret message", b"ad").unwrap();
+			assert_eq!(ciphertext.len(), 14 + 16);
+			assert_eq!(
+				ciphertext,
+				seal_deterministic(&key, b"Secret message", b"ad").unwrap()
+			);
+			assert_eq!(
+				open_deterministic(&key, &ciphertext, b"ad").unwrap(),
+				b"Secret message"
+			);
+		}
+
+		#[test]
+		fn test_empty_plaintext() {
+			let key = DeterministicSecretKey::generate().unwrap();
+
+			let ciphertext = seal_deterministic(&key, b"", b"ad").unwrap();
+			assert_eq!(ciphertext.len(), 16);
+			assert!(open_deterministic(&key, &ciphertext, b"ad")
+				.unwrap()
+				.is_empty());
+		}
+
+		#[test]
+		fn test_ad_is_bound() {
+			let key = DeterministicSecretKey::generate().unwrap();
+
+			let ciphertext = seal_deterministic(&key, b"Secret message", b"users.email").unwrap();
+			assert_ne!(
+				ciphertext,
+				seal_deterministic(&key, b"Secret message", b"users.name").unwrap()
+			);
+			assert!(open_deterministic(&key, &ciphertext, b"users.name").is_err());
+			assert!(open_deterministic(&key, &ciphertext, b"").is_err());
+		}
+
+		#[test]
+		fn test_bad_input() {
+			let key = DeterministicSecretKey::generate().unwrap();
+			let other_key = DeterministicSecretKey::generate().unwrap();
+
+			let mut ciphertext = seal_deterministic(&key, b"Secret message", b"ad").unwrap();
+			assert!(open_deterministic(&other_key, &ciphertext, b"ad").is_err());
+			assert!(open_deterministic(&key, &ciphertext[..15], b"ad").is_err());
+			assert!(open_deterministic(&key, &ciphertext[..16], b"ad").is_err());
+
+			ciphertext[20] ^= 1;
+			assert!(open_deterministic(&key, &ciphertext, b"ad").is_err());
+		}
+	}
+
+	mod test_seal_open_with_provider {
+		use super::*;
+
+		#[test]
+		fn test_seal_open_with_provider() {
+			let provider = StoredKey([0x42; 32]);
+			let ciphertext = seal_with_provider(&provider, b"Secret message").unwrap();
+
+			assert_eq!(
+				open_with_provider(&provider, &ciphertext).unwrap(),
+				b"Secret message"
+			);
+			// The key the provider gives out is the one used.
+			let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+			assert_eq!(open(&secret_key, &ciphertext).unwrap(), b"Secret message");
+		}
+
+		#[test]
+		fn test_secret_key_is_provider() {
+			let secret_key = SecretKey::default();
+			let ciphertext = seal_with_provider(&secret_key, b"Secret message").unwrap();
+			assert_eq!(open(&secret_key, &ciphertext).unwrap(), b"Secret message");
+		}
+
+		#[test]
+		fn test_provider_err() {
+			let ciphertext = seal(&SecretKey::default(), b"Secret message").unwrap();
+
+			assert!(seal_with_provider(&LockedKey, b"Secret message").is_err());
+			assert!(open_with_provider(&LockedKey, &ciphertext).is_err());
+			assert!(open_with_provider(&StoredKey([0x42; 32]), &ciphertext).is_err());
+		}
+	}
+
 	// Proptests. Only exectued when NOT testing no_std.
 	#[cfg(feature = "safe_api")]
 	mod proptest {
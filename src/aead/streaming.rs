@@ -0,0 +1,886 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Authenticated secret-key encryption of data in chunks.
+//!
+//! # Use case:
+//! `orion::aead::streaming` can be used to encrypt data that is too large to
+//! be held in memory at once, such as files or backups, in a way that detects
+//! if any chunk has been tampered with, reordered, removed or if the data has
+//! been truncated.
+//!
+//! # About:
+//! - Uses the STREAM construction from [Online Authenticated-Encryption and
+//!   its Nonce-Reuse Misuse-Resistance](https://eprint.iacr.org/2015/189.pdf)
+//!   with XChaCha20Poly1305 and no additional data.
+//! - `StreamSealer::new()` generates a random 19-byte `Header`, which must be
+//!   passed to `StreamOpener::new()`. The `Header` is not secret.
+//! - The nonce of each chunk is the `Header`, followed by the chunk counter as
+//!   a big-endian 32-bit integer and a byte that is `1` for the last chunk and
+//!   `0` otherwise. A chunk that is opened at a different position, or as the
+//!   last chunk when it was not sealed as one, fails authentication.
+//! - Each sealed chunk is the ciphertext with the 16-byte Poly1305 tag
+//!   appended to it.
+//! - The tag of a chunk is verified before any of it is decrypted. No
+//!   plaintext of a chunk is returned unless its tag is valid.
+//! - After a chunk fails to open, or the last chunk has been opened, a
+//!   `StreamOpener` refuses all further chunks.
+//! - A `StreamOpener` can be cloned to branch the stream, for example to open
+//!   the same chunks in two places. `StreamSealer` does not implement `Clone`,
+//!   as sealing different chunks with a copy of it would reuse nonces.
+//! - Each chunk is encrypted independently of the others, so
+//!   `seal_chunks_parallel()` and `seal_last_chunks_parallel()` seal a batch
+//!   of chunks on several threads. The output is the same as sealing the
+//!   chunks one by one, and is opened with `StreamOpener` as usual.
+//! - `seal_slice()` and `open_slice()` process a large slice, such as a
+//!   memory-mapped file, in chunks of `SLICE_CHUNK_SIZE` (1 MiB) and write
+//!   each result to `dst` as it is produced. The slice is read in order and
+//!   never copied into an intermediate buffer.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key used to encrypt and decrypt the chunks.
+//! - `header`: The `Header` returned by `StreamSealer::new()`.
+//! - `plaintext`: The chunk of data to be encrypted.
+//! - `chunk`: The sealed chunk to be decrypted.
+//! - `chunks`: The chunks of data to be encrypted on several threads.
+//! - `threads`: The maximum number of threads to use.
+//! - `data`: All data to be encrypted as one stream.
+//! - `sealed`: All sealed chunks of a stream written by `seal_slice()`.
+//! - `dst`: The writer that the sealed chunks or the plaintext are written to.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is not 32 bytes.
+//! - `plaintext`, `chunks` or any chunk in `chunks` is empty.
+//! - `threads` is 0.
+//! - `data` or `sealed` is empty.
+//! - Writing to `dst` fails.
+//! - `chunk` is less than 17 bytes.
+//! - The received tag does not match the calculated tag when opening a chunk.
+//! - A chunk is sealed or opened after the last chunk.
+//! - A chunk is opened after a previous chunk failed to open.
+//! - More than 2^32-1 chunks are sealed or opened.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - The data is only complete once `open_last_chunk()` has succeeded. If the
+//!   stream ends before that, the data has been truncated and everything
+//!   decrypted from it should be discarded.
+//! - Whether a sealed chunk is the last one must be known to the receiver.
+//!   Using chunks of a fixed size, where the last may be shorter, makes this
+//!   possible without trusting the length of the data.
+//! - `open_slice()` writes the plaintext of each chunk to `dst` once that
+//!   chunk is authenticated, before later chunks are checked. If it returns an
+//!   error, everything written to `dst` should be discarded.
+//! - A memory-mapped file that is modified while it is being sealed or opened
+//!   gives unpredictable results. Map files that no other process writes to.
+//! - To securely generate a strong key, use `SecretKey::default()`.
+//!
+//! # Example:
+//! ```
+//! use orion::aead::{streaming, SecretKey};
+//!
+//! let secret_key = SecretKey::default();
+//!
+//! let (mut sealer, header) = streaming::StreamSealer::new(&secret_key).unwrap();
+//! let chunk_1 = sealer.seal_chunk(b"Secret ").unwrap();
+//! let chunk_2 = sealer.seal_last_chunk(b"message").unwrap();
+//!
+//! let mut opener = streaming::StreamOpener::new(&secret_key, &header).unwrap();
+//! assert_eq!(opener.open_chunk(&chunk_1).unwrap(), b"Secret ");
+//! assert_eq!(opener.open_last_chunk(&chunk_2).unwrap(), b"message");
+//! assert!(opener.is_finished());
+//! ```
+
+pub use crate::hltypes::SecretKey;
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		aead,
+		constants::{POLY1305_BLOCKSIZE, XCHACHA_NONCESIZE},
+		stream::{chacha20, xchacha20::Nonce},
+	},
+};
+use std::io::Write;
+
+/// The size of the `Header` generated by `StreamSealer::new()`.
+const HEADER_SIZE: usize = 19;
+
+construct_nonce_with_generator! {
+	/// A type that represents the random `Header` of a stream.
+	///
+	/// # Exceptions:
+	/// An exception will be thrown if:
+	/// - `slice` is not 19 bytes.
+	/// - The `OsRng` fails to initialize or read from its source.
+	(Header, HEADER_SIZE)
+}
+
+#[must_use]
+/// Build the nonce of the chunk at `counter`.
+fn chunk_nonce(
+	header: &[u8; HEADER_SIZE],
+	counter: u32,
+	is_last: bool,
+) -> Result<Nonce, UnknownCryptoError> {
+	let mut nonce = [0u8; XCHACHA_NONCESIZE];
+	nonce[..HEADER_SIZE].copy_from_slice(header);
+	nonce[HEADER_SIZE..(HEADER_SIZE + 4)].copy_from_slice(&counter.to_be_bytes());
+	nonce[XCHACHA_NONCESIZE - 1] = is_last as u8;
+
+	Nonce::from_slice(&nonce)
+}
+
+#[must_use]
+/// Seal `plaintext` as the chunk at `counter`.
+fn seal_at(
+	secret_key: &chacha20::SecretKey,
+	header: &[u8; HEADER_SIZE],
+	counter: u32,
+	is_last: bool,
+	plaintext: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let nonce = chunk_nonce(header, counter, is_last)?;
+	let mut dst_out = vec![0u8; plaintext.len() + POLY1305_BLOCKSIZE];
+	aead::xchacha20poly1305::seal(secret_key, &nonce, plaintext, None, &mut dst_out)?;
+
+	Ok(dst_out)
+}
+
+/// Encryption of a stream of chunks.
+pub struct StreamSealer {
+	secret_key: chacha20::SecretKey,
+	header: [u8; HEADER_SIZE],
+	counter: u32,
+	is_finished: bool,
+}
+
+impl core::fmt::Debug for StreamSealer {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(
+			f,
+			"StreamSealer {{ secret_key: [***OMITTED***], header: {:?}, counter: {:?}, \
+			 is_finished: {:?} }}",
+			self.header, self.counter, self.is_finished
+		)
+	}
+}
+
+impl StreamSealer {
+	#[must_use]
+	/// Start a new stream, returning the sealer and the `Header` the opener
+	/// needs.
+	pub fn new(secret_key: &SecretKey) -> Result<(Self, Header), UnknownCryptoError> {
+		let header = Header::generate()?;
+		let sealer = Self {
+			secret_key: chacha20::SecretKey::from_slice(&secret_key.unprotected_as_bytes())?,
+			header: header.value,
+			counter: 0,
+			is_finished: false,
+		};
+
+		Ok((sealer, header))
+	}
+
+	#[must_use]
+	/// Seal the chunk at the current position.
+	fn seal(&mut self, plaintext: &[u8], is_last: bool) -> Result<Vec<u8>, UnknownCryptoError> {
+		if self.is_finished || plaintext.is_empty() {
+			return Err(UnknownCryptoError);
+		}
+
+		let dst_out = seal_at(
+			&self.secret_key,
+			&self.header,
+			self.counter,
+			is_last,
+			plaintext,
+		)?;
+
+		if is_last {
+			self.is_finished = true;
+		} else {
+			self.counter = self.counter.checked_add(1).ok_or(UnknownCryptoError)?;
+		}
+
+		Ok(dst_out)
+	}
+
+	#[must_use]
+	/// Seal `chunks` at consecutive positions, starting at the current one,
+	/// spread over at most `threads` threads.
+	fn seal_parallel(
+		&mut self,
+		chunks: Vec<Vec<u8>>,
+		is_last: bool,
+		threads: usize,
+	) -> Result<Vec<Vec<u8>>, UnknownCryptoError> {
+		if self.is_finished || threads == 0 || chunks.is_empty() {
+			return Err(UnknownCryptoError);
+		}
+		if chunks.iter().any(|plaintext| plaintext.is_empty()) {
+			return Err(UnknownCryptoError);
+		}
+		// The position after the last chunk must fit in the counter, as with
+		// sealing the chunks one by one.
+		let count = chunks.len();
+		let advance = if is_last { count - 1 } else { count };
+		let next_counter = u64::from(self.counter) + advance as u64;
+		if next_counter > u64::from(u32::max_value()) {
+			return Err(UnknownCryptoError);
+		}
+
+		let per_thread = (count + threads - 1) / threads;
+		let mut chunks = chunks.into_iter();
+		let mut handles = Vec::with_capacity(threads);
+		let mut grouped = 0;
+		while grouped < count {
+			let group: Vec<Vec<u8>> = chunks.by_ref().take(per_thread).collect();
+			let start = self.counter + grouped as u32;
+			grouped += group.len();
+			let group_has_last = is_last && grouped == count;
+			let secret_key = self.secret_key.duplicate();
+			let header = self.header;
+
+			handles.push(std::thread::spawn(move || {
+				let last_index = group.len() - 1;
+				group
+					.iter()
+					.enumerate()
+					.map(|(idx, plaintext)| {
+						let is_last_chunk = group_has_last && idx == last_index;
+						seal_at(
+							&secret_key,
+							&header,
+							start + idx as u32,
+							is_last_chunk,
+							plaintext,
+						)
+					})
+					.collect::<Result<Vec<Vec<u8>>, UnknownCryptoError>>()
+			}));
+		}
+
+		let mut sealed = Vec::with_capacity(count);
+		for handle in handles {
+			sealed.extend(handle.join().map_err(|_| UnknownCryptoError)??);
+		}
+
+		if is_last {
+			self.is_finished = true;
+		} else {
+			self.counter = next_counter as u32;
+		}
+
+		Ok(sealed)
+	}
+
+	#[must_use]
+	/// Seal a chunk that is not the last one of the stream.
+	pub fn seal_chunk(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+		self.seal(plaintext, false)
+	}
+
+	#[must_use]
+	/// Seal the last chunk of the stream. No chunks can be sealed after it.
+	pub fn seal_last_chunk(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+		self.seal(plaintext, true)
+	}
+
+	#[must_use]
+	/// Seal `chunks`, none of which is the last one of the stream, using at
+	/// most `threads` threads. The result is the same as sealing each chunk in
+	/// order with `seal_chunk()`.
+	pub fn seal_chunks_parallel(
+		&mut self,
+		chunks: Vec<Vec<u8>>,
+		threads: usize,
+	) -> Result<Vec<Vec<u8>>, UnknownCryptoError> {
+		self.seal_parallel(chunks, false, threads)
+	}
+
+	#[must_use]
+	/// Seal `chunks`, ending with the last one of the stream, using at most
+	/// `threads` threads. The result is the same as sealing each chunk in
+	/// order with `seal_chunk()`, and the final one with `seal_last_chunk()`.
+	/// No chunks can be sealed after it.
+	pub fn seal_last_chunks_parallel(
+		&mut self,
+		chunks: Vec<Vec<u8>>,
+		threads: usize,
+	) -> Result<Vec<Vec<u8>>, UnknownCryptoError> {
+		self.seal_parallel(chunks, true, threads)
+	}
+
+	/// Return true if the last chunk has been sealed.
+	pub fn is_finished(&self) -> bool { self.is_finished }
+}
+
+/// Decryption of a stream of chunks.
+pub struct StreamOpener {
+	secret_key: chacha20::SecretKey,
+	header: [u8; HEADER_SIZE],
+	counter: u32,
+	is_finished: bool,
+	is_failed: bool,
+}
+
+impl Clone for StreamOpener {
+	fn clone(&self) -> Self {
+		Self {
+			secret_key: self.secret_key.duplicate(),
+			header: self.header,
+			counter: self.counter,
+			is_finished: self.is_finished,
+			is_failed: self.is_failed,
+		}
+	}
+}
+
+impl core::fmt::Debug for StreamOpener {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(
+			f,
+			"StreamOpener {{ secret_key: [***OMITTED***], header: {:?}, counter: {:?}, \
+			 is_finished: {:?}, is_failed: {:?} }}",
+			self.header, self.counter, self.is_finished, self.is_failed
+		)
+	}
+}
+
+impl StreamOpener {
+	#[must_use]
+	/// Start opening the stream that begins with `header`.
+	pub fn new(secret_key: &SecretKey, header: &Header) -> Result<Self, UnknownCryptoError> {
+		Ok(Self {
+			secret_key: chacha20::SecretKey::from_slice(&secret_key.unprotected_as_bytes())?,
+			header: header.value,
+			counter: 0,
+			is_finished: false,
+			is_failed: false,
+		})
+	}
+
+	#[must_use]
+	/// Open the chunk at the current position.
+	fn open(&mut self, chunk: &[u8], is_last: bool) -> Result<Vec<u8>, UnknownCryptoError> {
+		if self.is_finished || self.is_failed {
+			return Err(UnknownCryptoError);
+		}
+		// Any failure from here on aborts the stream.
+		self.is_failed = true;
+
+		// `+ 1` to avoid empty ciphertexts
+		if chunk.len() < (POLY1305_BLOCKSIZE + 1) {
+			return Err(UnknownCryptoError);
+		}
+
+		let nonce = chunk_nonce(&self.header, self.counter, is_last)?;
+		let mut dst_out = vec![0u8; chunk.len() - POLY1305_BLOCKSIZE];
+		// The tag is verified before anything is written to `dst_out`.
+		aead::xchacha20poly1305::open(&self.secret_key, &nonce, chunk, None, &mut dst_out)?;
+
+		if is_last {
+			self.is_finished = true;
+		} else {
+			self.counter = self.counter.checked_add(1).ok_or(UnknownCryptoError)?;
+		}
+		self.is_failed = false;
+
+		Ok(dst_out)
+	}
+
+	#[must_use]
+	/// Open a chunk that is not the last one of the stream.
+	pub fn open_chunk(&mut self, chunk: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+		self.open(chunk, false)
+	}
+
+	#[must_use]
+	/// Open the last chunk of the stream. No chunks can be opened after it.
+	pub fn open_last_chunk(&mut self, chunk: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+		self.open(chunk, true)
+	}
+
+	/// Return true if the last chunk has been opened, meaning that the stream
+	/// was not truncated.
+	pub fn is_finished(&self) -> bool { self.is_finished }
+}
+
+/// The size of the plaintext of each chunk sealed by `seal_slice()`, except
+/// for the last chunk, which may be shorter.
+pub const SLICE_CHUNK_SIZE: usize = 1 << 20;
+
+#[must_use]
+/// Seal all of `data` as one stream, in chunks of `SLICE_CHUNK_SIZE` bytes,
+/// writing each sealed chunk to `dst` as soon as it is sealed.
+pub fn seal_slice<W: Write>(
+	secret_key: &SecretKey,
+	data: &[u8],
+	dst: &mut W,
+) -> Result<Header, UnknownCryptoError> {
+	if data.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let (mut sealer, header) = StreamSealer::new(secret_key)?;
+	let mut chunks = data.chunks(SLICE_CHUNK_SIZE).peekable();
+	while let Some(plaintext) = chunks.next() {
+		let sealed = if chunks.peek().is_some() {
+			sealer.seal_chunk(plaintext)?
+		} else {
+			sealer.seal_last_chunk(plaintext)?
+		};
+		dst.write_all(&sealed).map_err(|_| UnknownCryptoError)?;
+	}
+
+	Ok(header)
+}
+
+#[must_use]
+/// Open all of `sealed`, written by `seal_slice()`, writing the plaintext of
+/// each chunk to `dst` as soon as it is opened.
+pub fn open_slice<W: Write>(
+	secret_key: &SecretKey,
+	header: &Header,
+	sealed: &[u8],
+	dst: &mut W,
+) -> Result<(), UnknownCryptoError> {
+	if sealed.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut opener = StreamOpener::new(secret_key, header)?;
+	let mut chunks = sealed
+		.chunks(SLICE_CHUNK_SIZE + POLY1305_BLOCKSIZE)
+		.peekable();
+	while let Some(chunk) = chunks.next() {
+		let plaintext = if chunks.peek().is_some() {
+			opener.open_chunk(chunk)?
+		} else {
+			opener.open_last_chunk(chunk)?
+		};
+		dst.write_all(&plaintext).map_err(|_| UnknownCryptoError)?;
+	}
+
+	Ok(())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	fn seal_stream(secret_key: &SecretKey, chunks: &[&[u8]]) -> (Header, Vec<Vec<u8>>) {
+		let (mut sealer, header) = StreamSealer::new(secret_key).unwrap();
+		let mut sealed = Vec::new();
+		for (idx, chunk) in chunks.iter().enumerate() {
+			if idx == chunks.len() - 1 {
+				sealed.push(sealer.seal_last_chunk(chunk).unwrap());
+			} else {
+				sealed.push(sealer.seal_chunk(chunk).unwrap());
+			}
+		}
+		assert!(sealer.is_finished());
+
+		(header, sealed)
+	}
+
+	mod test_slice {
+		use super::*;
+
+		#[test]
+		fn test_seal_open_slice() {
+			let secret_key = SecretKey::default();
+			for &len in &[
+				1,
+				SLICE_CHUNK_SIZE - 1,
+				SLICE_CHUNK_SIZE,
+				SLICE_CHUNK_SIZE + 1,
+				2 * SLICE_CHUNK_SIZE + 10,
+			] {
+				let data: Vec<u8> = (0..len).map(|idx| idx as u8).collect();
+				let mut sealed = Vec::new();
+				let header = seal_slice(&secret_key, &data, &mut sealed).unwrap();
+				let chunks = (len + SLICE_CHUNK_SIZE - 1) / SLICE_CHUNK_SIZE;
+				assert_eq!(sealed.len(), len + chunks * POLY1305_BLOCKSIZE);
+
+				let mut opened = Vec::new();
+				open_slice(&secret_key, &header, &sealed, &mut opened).unwrap();
+				assert_eq!(opened, data);
+
+				// The chunks are ordinary stream chunks.
+				let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+				let sealed_chunks: Vec<&[u8]> = sealed
+					.chunks(SLICE_CHUNK_SIZE + POLY1305_BLOCKSIZE)
+					.collect();
+				for chunk in &sealed_chunks[..chunks - 1] {
+					opener.open_chunk(chunk).unwrap();
+				}
+				opener.open_last_chunk(sealed_chunks[chunks - 1]).unwrap();
+			}
+		}
+
+		#[test]
+		fn test_open_slice_truncated_err() {
+			let secret_key = SecretKey::default();
+			let data = vec![1u8; SLICE_CHUNK_SIZE + 5];
+			let mut sealed = Vec::new();
+			let header = seal_slice(&secret_key, &data, &mut sealed).unwrap();
+
+			let first_chunk = &sealed[..SLICE_CHUNK_SIZE + POLY1305_BLOCKSIZE];
+			assert!(open_slice(&secret_key, &header, first_chunk, &mut Vec::new()).is_err());
+			let mut modified = sealed.clone();
+			modified[SLICE_CHUNK_SIZE + POLY1305_BLOCKSIZE] ^= 1;
+			assert!(open_slice(&secret_key, &header, &modified, &mut Vec::new()).is_err());
+		}
+
+		#[test]
+		fn test_empty_err() {
+			let secret_key = SecretKey::default();
+			let header = Header::generate().unwrap();
+			assert!(seal_slice(&secret_key, &[], &mut Vec::new()).is_err());
+			assert!(open_slice(&secret_key, &header, &[], &mut Vec::new()).is_err());
+		}
+	}
+
+	mod test_stream_sealer {
+		use super::*;
+
+		#[test]
+		fn test_seal_empty_err() {
+			let (mut sealer, _) = StreamSealer::new(&SecretKey::default()).unwrap();
+			assert!(sealer.seal_chunk(&[]).is_err());
+			assert!(sealer.seal_last_chunk(&[]).is_err());
+		}
+
+		#[test]
+		fn test_seal_parallel_same_as_sequential() {
+			let secret_key = SecretKey::default();
+			let chunks: Vec<Vec<u8>> = (0..10u8).map(|idx| vec![idx; 100 + idx as usize]).collect();
+
+			for &threads in &[1, 3, 4, 10, 16] {
+				let (mut sealer, header) = StreamSealer::new(&secret_key).unwrap();
+				let mut sequential = StreamSealer {
+					secret_key: sealer.secret_key.duplicate(),
+					header: sealer.header,
+					counter: 0,
+					is_finished: false,
+				};
+
+				let mut expected = Vec::new();
+				for chunk in &chunks[..5] {
+					expected.push(sequential.seal_chunk(chunk).unwrap());
+				}
+				for chunk in &chunks[5..9] {
+					expected.push(sequential.seal_chunk(chunk).unwrap());
+				}
+				expected.push(sequential.seal_last_chunk(&chunks[9]).unwrap());
+
+				let mut sealed = Vec::new();
+				for chunk in &chunks[..5] {
+					sealed.push(sealer.seal_chunk(chunk).unwrap());
+				}
+				sealed.extend(
+					sealer
+						.seal_chunks_parallel(chunks[5..9].to_vec(), threads)
+						.unwrap(),
+				);
+				assert_eq!(sealer.counter, 9);
+				sealed.extend(
+					sealer
+						.seal_last_chunks_parallel(chunks[9..].to_vec(), threads)
+						.unwrap(),
+				);
+				assert!(sealer.is_finished());
+				assert_eq!(sealed, expected);
+
+				let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+				for (chunk, plaintext) in sealed[..9].iter().zip(chunks.iter()) {
+					assert_eq!(&opener.open_chunk(chunk).unwrap(), plaintext);
+				}
+				assert_eq!(opener.open_last_chunk(&sealed[9]).unwrap(), chunks[9]);
+				assert!(opener.is_finished());
+			}
+		}
+
+		#[test]
+		fn test_seal_parallel_last_chunk() {
+			let secret_key = SecretKey::default();
+			let chunks = vec![b"Secret ".to_vec(), b"message".to_vec()];
+			let (mut sealer, header) = StreamSealer::new(&secret_key).unwrap();
+			let sealed = sealer.seal_last_chunks_parallel(chunks, 2).unwrap();
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert_eq!(opener.open_chunk(&sealed[0]).unwrap(), b"Secret ");
+			assert_eq!(opener.open_last_chunk(&sealed[1]).unwrap(), b"message");
+		}
+
+		#[test]
+		fn test_seal_parallel_err() {
+			let (mut sealer, _) = StreamSealer::new(&SecretKey::default()).unwrap();
+			assert!(sealer.seal_chunks_parallel(vec![], 2).is_err());
+			assert!(sealer.seal_last_chunks_parallel(vec![], 2).is_err());
+			assert!(sealer.seal_chunks_parallel(vec![b"a".to_vec()], 0).is_err());
+			assert!(sealer
+				.seal_chunks_parallel(vec![b"a".to_vec(), vec![]], 2)
+				.is_err());
+			// Failed batches do not move the stream forward.
+			assert_eq!(sealer.counter, 0);
+			assert!(!sealer.is_finished());
+
+			sealer.counter = u32::max_value() - 1;
+			assert!(sealer
+				.seal_chunks_parallel(vec![b"a".to_vec(), b"b".to_vec()], 2)
+				.is_err());
+			assert!(sealer
+				.seal_last_chunks_parallel(vec![b"a".to_vec(), b"b".to_vec()], 2)
+				.is_ok());
+
+			assert!(sealer
+				.seal_chunks_parallel(vec![b"After".to_vec()], 1)
+				.is_err());
+			assert!(sealer
+				.seal_last_chunks_parallel(vec![b"After".to_vec()], 1)
+				.is_err());
+		}
+
+		#[test]
+		fn test_seal_after_last_err() {
+			let (mut sealer, _) = StreamSealer::new(&SecretKey::default()).unwrap();
+			assert!(!sealer.is_finished());
+			assert!(sealer.seal_last_chunk(b"Last").is_ok());
+			assert!(sealer.is_finished());
+			assert!(sealer.seal_chunk(b"After").is_err());
+			assert!(sealer.seal_last_chunk(b"After").is_err());
+		}
+
+		#[test]
+		fn test_seal_bad_key_err() {
+			let secret_key = SecretKey::from_slice(&[0u8; 64]).unwrap();
+			assert!(StreamSealer::new(&secret_key).is_err());
+		}
+
+		#[test]
+		fn test_same_chunk_differs_by_position() {
+			let (mut sealer, _) = StreamSealer::new(&SecretKey::default()).unwrap();
+			let chunk_1 = sealer.seal_chunk(b"Same").unwrap();
+			let chunk_2 = sealer.seal_chunk(b"Same").unwrap();
+			let chunk_3 = sealer.seal_last_chunk(b"Same").unwrap();
+			assert_ne!(chunk_1, chunk_2);
+			assert_ne!(chunk_2, chunk_3);
+		}
+
+		#[test]
+		fn test_header_differs_between_streams() {
+			let secret_key = SecretKey::default();
+			let (_, header_1) = StreamSealer::new(&secret_key).unwrap();
+			let (_, header_2) = StreamSealer::new(&secret_key).unwrap();
+			assert_ne!(header_1, header_2);
+		}
+
+		#[test]
+		fn test_debug_omits_key() {
+			let (sealer, _) = StreamSealer::new(&SecretKey::default()).unwrap();
+			assert!(format!("{:?}", sealer).contains("secret_key: [***OMITTED***]"));
+		}
+	}
+
+	mod test_stream_opener {
+		use super::*;
+
+		#[test]
+		fn test_seal_open_roundtrip() {
+			let secret_key = SecretKey::default();
+			let (header, sealed) = seal_stream(&secret_key, &[b"Chunk 1", b"Chunk 2", b"Chunk 3"]);
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert_eq!(opener.open_chunk(&sealed[0]).unwrap(), b"Chunk 1");
+			assert_eq!(opener.open_chunk(&sealed[1]).unwrap(), b"Chunk 2");
+			assert!(!opener.is_finished());
+			assert_eq!(opener.open_last_chunk(&sealed[2]).unwrap(), b"Chunk 3");
+			assert!(opener.is_finished());
+		}
+
+		#[test]
+		fn test_single_chunk() {
+			let secret_key = SecretKey::default();
+			let (header, sealed) = seal_stream(&secret_key, &[b"Only chunk"]);
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&sealed[0]).is_err());
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert_eq!(opener.open_last_chunk(&sealed[0]).unwrap(), b"Only chunk");
+		}
+
+		#[test]
+		fn test_truncation_detected() {
+			let secret_key = SecretKey::default();
+			let (header, sealed) = seal_stream(&secret_key, &[b"Chunk 1", b"Chunk 2", b"Chunk 3"]);
+
+			// Dropping the last chunk means no chunk can be opened as the last one.
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&sealed[0]).is_ok());
+			assert!(opener.open_last_chunk(&sealed[1]).is_err());
+			assert!(!opener.is_finished());
+
+			// Without a last chunk, the stream is never finished.
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&sealed[0]).is_ok());
+			assert!(opener.open_chunk(&sealed[1]).is_ok());
+			assert!(!opener.is_finished());
+		}
+
+		#[test]
+		fn test_reordering_detected() {
+			let secret_key = SecretKey::default();
+			let (header, sealed) = seal_stream(&secret_key, &[b"Chunk 1", b"Chunk 2", b"Chunk 3"]);
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&sealed[1]).is_err());
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&sealed[0]).is_ok());
+			assert!(opener.open_chunk(&sealed[0]).is_err());
+		}
+
+		#[test]
+		fn test_chunk_from_other_stream_err() {
+			let secret_key = SecretKey::default();
+			let (header_1, _) = seal_stream(&secret_key, &[b"Chunk 1", b"Chunk 2"]);
+			let (_, sealed_2) = seal_stream(&secret_key, &[b"Chunk 1", b"Chunk 2"]);
+
+			let mut opener = StreamOpener::new(&secret_key, &header_1).unwrap();
+			assert!(opener.open_chunk(&sealed_2[0]).is_err());
+		}
+
+		#[test]
+		fn test_abort_after_failure() {
+			let secret_key = SecretKey::default();
+			let (header, mut sealed) = seal_stream(&secret_key, &[b"Chunk 1", b"Chunk 2"]);
+			let valid = sealed[0].clone();
+			sealed[0][0] ^= 1;
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&sealed[0]).is_err());
+			// The untampered chunk is refused too, once the stream has failed.
+			assert!(opener.open_chunk(&valid).is_err());
+			assert!(opener.open_last_chunk(&sealed[1]).is_err());
+		}
+
+		#[test]
+		fn test_open_after_last_err() {
+			let secret_key = SecretKey::default();
+			let (header, sealed) = seal_stream(&secret_key, &[b"Chunk 1"]);
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_last_chunk(&sealed[0]).is_ok());
+			assert!(opener.open_chunk(&sealed[0]).is_err());
+			assert!(opener.open_last_chunk(&sealed[0]).is_err());
+		}
+
+		#[test]
+		fn test_open_short_chunk_err() {
+			let secret_key = SecretKey::default();
+			let (header, _) = seal_stream(&secret_key, &[b"Chunk 1"]);
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&[0u8; POLY1305_BLOCKSIZE]).is_err());
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_last_chunk(&[]).is_err());
+		}
+
+		#[test]
+		fn test_open_wrong_key_err() {
+			let (header, sealed) = seal_stream(&SecretKey::default(), &[b"Chunk 1"]);
+
+			let mut opener = StreamOpener::new(&SecretKey::default(), &header).unwrap();
+			assert!(opener.open_last_chunk(&sealed[0]).is_err());
+		}
+
+		#[test]
+		fn test_clone_branches_stream() {
+			let secret_key = SecretKey::default();
+			let (header, sealed) = seal_stream(&secret_key, &[b"Chunk 1", b"Chunk 2", b"Chunk 3"]);
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&sealed[0]).is_ok());
+			let mut branch = opener.clone();
+
+			assert_eq!(opener.open_chunk(&sealed[1]).unwrap(), b"Chunk 2");
+			assert_eq!(opener.open_last_chunk(&sealed[2]).unwrap(), b"Chunk 3");
+			assert!(opener.is_finished());
+			// The branch is unaffected by the original finishing.
+			assert!(!branch.is_finished());
+			assert_eq!(branch.open_chunk(&sealed[1]).unwrap(), b"Chunk 2");
+			assert_eq!(branch.open_last_chunk(&sealed[2]).unwrap(), b"Chunk 3");
+		}
+
+		#[test]
+		fn test_clone_keeps_failure() {
+			let secret_key = SecretKey::default();
+			let (header, sealed) = seal_stream(&secret_key, &[b"Chunk 1", b"Chunk 2"]);
+
+			let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+			assert!(opener.open_chunk(&sealed[1]).is_err());
+			let mut branch = opener.clone();
+			assert!(branch.open_chunk(&sealed[0]).is_err());
+		}
+
+		#[test]
+		fn test_debug_omits_key() {
+			let opener =
+				StreamOpener::new(&SecretKey::default(), &Header::generate().unwrap()).unwrap();
+			assert!(format!("{:?}", opener).contains("secret_key: [***OMITTED***]"));
+		}
+	}
+
+	// Proptests. Only exectued when NOT testing no_std.
+	#[cfg(feature = "safe_api")]
+	mod proptest {
+		use super::*;
+
+		quickcheck! {
+			fn prop_seal_open_chunks(data: Vec<u8>, chunk_size: usize) -> bool {
+				if data.is_empty() {
+					return true;
+				}
+
+				let secret_key = SecretKey::default();
+				let chunks: Vec<&[u8]> = data.chunks((chunk_size % 64) + 1).collect();
+				let (header, sealed) = seal_stream(&secret_key, &chunks);
+
+				let mut opener = StreamOpener::new(&secret_key, &header).unwrap();
+				let mut opened = Vec::new();
+				for (idx, chunk) in sealed.iter().enumerate() {
+					if idx == sealed.len() - 1 {
+						opened.extend(opener.open_last_chunk(chunk).unwrap());
+					} else {
+						opened.extend(opener.open_chunk(chunk).unwrap());
+					}
+				}
+
+				opener.is_finished() && opened == data
+			}
+		}
+	}
+}
@@ -32,16 +32,25 @@
 //!
 //! # About:
 //! - Uses HMAC-SHA512.
+//! - `orion::auth::blake2b` offers the same interface using keyed BLAKE2b.
+//! - `orion::auth::nacl` offers tags compatible with NaCl and libsodium.
+//! - `authenticate_with_provider` and `authenticate_verify_with_provider`
+//!   request the key from a `KeyProvider`, such as an OS keychain, for the
+//!   duration of the call.
 //!
 //! # Parameters:
 //! - `secret_key`: Secret key used to authenticate `data`.
 //! - `data`: Data to be authenticated.
 //! - `expected`: The expected authentication tag.
+//! - `provider`: The `KeyProvider` that the secret key is requested from.
 //!
 //! # Errors:
 //! An error will be returned if:
 //! - The calculated `Tag` does not match the expected.
 //!
+//! `data` may be empty and has no maximum length, as given by `DATA_LIMITS`.
+//! - `provider` fails to return a key.
+//!
 //! # Security:
 //! - The secret key should always be generated using a CSPRNG.
 //!   `SecretKey::default()` can be used for
@@ -57,13 +66,77 @@
 //!
 //! let expected_tag = auth::authenticate(&key, msg).unwrap();
 //! assert!(auth::authenticate_verify(&expected_tag, &key, &msg).unwrap());
+//!
+//! // Authenticating a message that arrives in chunks.
+//! let mut authenticator = auth::Authenticator::new(&key).unwrap();
+//! authenticator.update(b"Some ").unwrap();
+//! authenticator.update(b"message.").unwrap();
+//! assert!(authenticator.verify(&expected_tag).unwrap());
 //! ```
 
 use crate::{
 	errors::{UnknownCryptoError, ValidationCryptoError},
 	hazardous::mac::hmac,
 };
-pub use crate::{hazardous::mac::hmac::Tag, hltypes::SecretKey};
+pub use crate::{
+	hazardous::mac::hmac::Tag,
+	hltypes::{InputLimits, KeyId, KeyProvider, SecretKey},
+};
+
+/// The lengths of `data` that `authenticate()` and `authenticate_verify()`
+/// accept: `data` may be empty, and has no maximum length.
+pub const DATA_LIMITS: InputLimits = InputLimits::new_unchecked(0, usize::max_value());
+
+/// Message authentication using keyed BLAKE2b.
+pub mod blake2b;
+
+/// Message authentication compatible with `crypto_auth` and
+/// `crypto_onetimeauth` from NaCl and libsodium.
+pub mod nacl;
+
+#[must_use]
+#[derive(Debug, Clone)]
+/// Streaming message authentication using HMAC-SHA512.
+///
+/// `finalize()` and `verify()` consume the `Authenticator`, so it cannot be
+/// updated or finalized again afterwards.
+pub struct Authenticator {
+	state: hmac::Hmac,
+}
+
+impl Authenticator {
+	#[must_use]
+	/// Initialize a new `Authenticator` with `secret_key`.
+	pub fn new(secret_key: &SecretKey) -> Result<Self, UnknownCryptoError> {
+		Ok(Self {
+			state: hmac::init(&hmac::SecretKey::from_slice(
+				&secret_key.unprotected_as_bytes(),
+			)?),
+		})
+	}
+
+	#[must_use]
+	/// Update the `Authenticator` with `data`. This can be called multiple
+	/// times.
+	pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+		Ok(self.state.update(data)?)
+	}
+
+	#[must_use]
+	/// Return the `Tag` of all data the `Authenticator` has been updated with.
+	pub fn finalize(mut self) -> Result<Tag, UnknownCryptoError> { Ok(self.state.finalize()?) }
+
+	#[must_use]
+	/// Verify in constant time that `expected` is the `Tag` of all data the
+	/// `Authenticator` has been updated with.
+	pub fn verify(mut self, expected: &Tag) -> Result<bool, ValidationCryptoError> {
+		if expected == &self.state.finalize()? {
+			Ok(true)
+		} else {
+			Err(ValidationCryptoError)
+		}
+	}
+}
 
 #[must_use]
 /// Authenticate a message using HMAC-SHA512.
@@ -90,6 +163,27 @@ pub fn authenticate_verify(
 	Ok(true)
 }
 
+#[must_use]
+/// Authenticate a message using HMAC-SHA512, with the key requested from
+/// `provider`.
+pub fn authenticate_with_provider(
+	provider: &impl KeyProvider,
+	data: &[u8],
+) -> Result<Tag, UnknownCryptoError> {
+	authenticate(&provider.provide_key()?, data)
+}
+
+#[must_use]
+/// Authenticate and verify a message using HMAC-SHA512, with the key
+/// requested from `provider`.
+pub fn authenticate_verify_with_provider(
+	expected: &Tag,
+	provider: &impl KeyProvider,
+	data: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+	authenticate_verify(expected, &provider.provide_key()?, data)
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -127,6 +221,106 @@ mod public {
 		}
 	}
 
+	mod test_data_limits {
+		use super::*;
+
+		#[test]
+		fn test_empty_data() {
+			assert!(DATA_LIMITS.check(0).is_ok());
+			assert!(DATA_LIMITS.check(usize::max_value()).is_ok());
+
+			let key = SecretKey::default();
+			let tag = authenticate(&key, &[]).unwrap();
+			assert!(authenticate_verify(&tag, &key, &[]).unwrap());
+		}
+	}
+
+	mod test_authenticator {
+		use super::*;
+
+		#[test]
+		fn test_same_as_authenticate() {
+			let sec_key = SecretKey::default();
+			let mut authenticator = Authenticator::new(&sec_key).unwrap();
+			authenticator.update(b"what do ya ").unwrap();
+			authenticator.update(b"want for nothing?").unwrap();
+
+			assert_eq!(
+				authenticator.finalize().unwrap(),
+				authenticate(&sec_key, b"what do ya want for nothing?").unwrap()
+			);
+		}
+
+		#[test]
+		fn test_verify() {
+			let sec_key = SecretKey::default();
+			let tag = authenticate(&sec_key, b"what do ya want for nothing?").unwrap();
+
+			let mut authenticator = Authenticator::new(&sec_key).unwrap();
+			authenticator
+				.update(b"what do ya want for nothing?")
+				.unwrap();
+			assert!(authenticator.verify(&tag).unwrap());
+
+			let mut authenticator = Authenticator::new(&sec_key).unwrap();
+			authenticator.update(b"bad msg").unwrap();
+			assert!(authenticator.verify(&tag).is_err());
+
+			let mut authenticator = Authenticator::new(&SecretKey::default()).unwrap();
+			authenticator
+				.update(b"what do ya want for nothing?")
+				.unwrap();
+			assert!(authenticator.verify(&tag).is_err());
+		}
+	}
+
+	/// A `KeyProvider` that holds the key bytes, like an external key store.
+	struct StoredKey([u8; 32]);
+
+	impl KeyProvider for StoredKey {
+		fn provide_key(&self) -> Result<SecretKey, UnknownCryptoError> {
+			SecretKey::from_slice(&self.0)
+		}
+	}
+
+	/// A `KeyProvider` that refuses to give out its key.
+	struct LockedKey;
+
+	impl KeyProvider for LockedKey {
+		fn provide_key(&self) -> Result<SecretKey, UnknownCryptoError> { Err(UnknownCryptoError) }
+	}
+
+	mod test_auth_with_provider {
+		use super::*;
+
+		#[test]
+		fn test_auth_and_verify_with_provider() {
+			let provider = StoredKey([0x42; 32]);
+			let tag = authenticate_with_provider(&provider, b"Some message.").unwrap();
+
+			assert!(authenticate_verify_with_provider(&tag, &provider, b"Some message.").unwrap());
+			let secret_key = SecretKey::from_slice(&[0x42; 32]).unwrap();
+			assert_eq!(tag, authenticate(&secret_key, b"Some message.").unwrap());
+			assert!(
+				authenticate_verify_with_provider(&tag, &secret_key, b"Some message.").unwrap()
+			);
+		}
+
+		#[test]
+		fn test_provider_err() {
+			let tag = authenticate(&SecretKey::default(), b"Some message.").unwrap();
+
+			assert!(authenticate_with_provider(&LockedKey, b"Some message.").is_err());
+			assert!(authenticate_verify_with_provider(&tag, &LockedKey, b"Some message.").is_err());
+			assert!(authenticate_verify_with_provider(
+				&tag,
+				&StoredKey([0x42; 32]),
+				b"Some message."
+			)
+			.is_err());
+		}
+	}
+
 	// Proptests. Only exectued when NOT testing no_std.
 	#[cfg(feature = "safe_api")]
 	mod proptest {
@@ -0,0 +1,199 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Message authentication compatible with NaCl and libsodium.
+//!
+//! # Use case:
+//! `orion::auth::nacl` can be used to create and verify tags that are
+//! byte-for-byte compatible with `crypto_auth` and `crypto_onetimeauth` from
+//! NaCl and libsodium, for example when exchanging messages with existing
+//! services built on them.
+//!
+//! # About:
+//! - `auth()` and `auth_verify()` match `crypto_auth()` and
+//!   `crypto_auth_verify()`. They use HMAC-SHA512-256, which is HMAC-SHA512
+//!   truncated to 32 bytes, with a 32-byte key.
+//! - `onetimeauth()` and `onetimeauth_verify()` match `crypto_onetimeauth()`
+//!   and `crypto_onetimeauth_verify()`. They use Poly1305 with a 32-byte
+//!   one-time key.
+//!
+//! # Parameters:
+//! - `secret_key`: Secret key used to authenticate `data`.
+//! - `one_time_key`: One-time key used to authenticate `data`.
+//! - `data`: Data to be authenticated.
+//! - `expected`: The expected authentication tag.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The calculated tag does not match the expected.
+//!
+//! # Security:
+//! - The secret key should always be generated using a CSPRNG.
+//!   `SecretKey::generate()` can be used for this.
+//! - A `OneTimeKey` must only be used to authenticate a single message.
+//!   Authenticating two messages with the same `OneTimeKey` allows forging
+//!   tags.
+//! - Unless compatibility with NaCl or libsodium is needed, use
+//!   `orion::auth` instead.
+//!
+//! # Example:
+//! ```
+//! use orion::auth::nacl;
+//!
+//! let key = nacl::SecretKey::generate().unwrap();
+//! let tag = nacl::auth(&key, b"Some message.").unwrap();
+//! assert!(nacl::auth_verify(&tag, &key, b"Some message.").unwrap());
+//!
+//! let one_time_key = nacl::OneTimeKey::generate().unwrap();
+//! let tag = nacl::onetimeauth(&one_time_key, b"Some message.").unwrap();
+//! assert!(nacl::onetimeauth_verify(&tag, &one_time_key, b"Some message.").unwrap());
+//! ```
+
+pub use crate::hazardous::mac::{
+	hmac::sha512_256::Tag,
+	poly1305::{OneTimeKey, Tag as OneTimeTag},
+};
+use crate::{
+	errors::{UnknownCryptoError, ValidationCryptoError},
+	hazardous::{
+		mac::{hmac, poly1305},
+		types,
+	},
+};
+
+/// The size of a `SecretKey` for `crypto_auth`.
+const KEY_SIZE: usize = 32;
+
+/// A type to represent the `SecretKey` that `crypto_auth` uses.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 32 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type SecretKey = types::SecretKey<types::tag::NaclAuthKey, KEY_SIZE>;
+
+#[must_use]
+/// Authenticate a message using HMAC-SHA512-256, like `crypto_auth()`.
+pub fn auth(secret_key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+	hmac::sha512_256::hmac(
+		&hmac::SecretKey::from_slice(secret_key.unprotected_as_bytes())?,
+		data,
+	)
+}
+
+#[must_use]
+/// Verify a HMAC-SHA512-256 tag in constant time, like
+/// `crypto_auth_verify()`.
+pub fn auth_verify(
+	expected: &Tag,
+	secret_key: &SecretKey,
+	data: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+	if &auth(secret_key, data)? == expected {
+		Ok(true)
+	} else {
+		Err(ValidationCryptoError)
+	}
+}
+
+#[must_use]
+/// Authenticate a message using Poly1305, like `crypto_onetimeauth()`.
+pub fn onetimeauth(
+	one_time_key: &OneTimeKey,
+	data: &[u8],
+) -> Result<OneTimeTag, UnknownCryptoError> {
+	poly1305::poly1305(one_time_key, data)
+}
+
+#[must_use]
+/// Verify a Poly1305 tag in constant time, like
+/// `crypto_onetimeauth_verify()`.
+pub fn onetimeauth_verify(
+	expected: &OneTimeTag,
+	one_time_key: &OneTimeKey,
+	data: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+	poly1305::verify(expected, one_time_key, data)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_auth {
+		use super::*;
+
+		#[test]
+		fn test_libsodium_vector() {
+			// From libsodium test/default/auth.c.
+			let mut key = [0u8; 32];
+			key[..4].copy_from_slice(b"Jefe");
+			let key = SecretKey::from_slice(&key).unwrap();
+			let expected = Tag::from_slice(&[
+				0x16, 0x4b, 0x7a, 0x7b, 0xfc, 0xf8, 0x19, 0xe2, 0xe3, 0x95, 0xfb, 0xe7, 0x3b, 0x56,
+				0xe0, 0xa3, 0x87, 0xbd, 0x64, 0x22, 0x2e, 0x83, 0x1f, 0xd6, 0x10, 0x27, 0x0c, 0xd7,
+				0xea, 0x25, 0x05, 0x54,
+			])
+			.unwrap();
+
+			let data = b"what do ya want for nothing?";
+			assert!(auth(&key, data).unwrap() == expected);
+			assert!(auth_verify(&expected, &key, data).unwrap());
+		}
+
+		#[test]
+		fn test_verify_bad_input_err() {
+			let key = SecretKey::generate().unwrap();
+			let tag = auth(&key, b"Data").unwrap();
+
+			assert!(auth_verify(&tag, &key, b"Data").unwrap());
+			assert!(auth_verify(&tag, &key, b"Other data").is_err());
+			assert!(auth_verify(&tag, &SecretKey::generate().unwrap(), b"Data").is_err());
+		}
+	}
+
+	mod test_onetimeauth {
+		use super::*;
+
+		#[test]
+		fn test_rfc8439_vector() {
+			// From RFC 8439, section 2.5.2.
+			let one_time_key = OneTimeKey::from_slice(&[
+				0x85, 0xd6, 0xbe, 0x78, 0x57, 0x55, 0x6d, 0x33, 0x7f, 0x44, 0x52, 0xfe, 0x42, 0xd5,
+				0x06, 0xa8, 0x01, 0x03, 0x80, 0x8a, 0xfb, 0x0d, 0xb2, 0xfd, 0x4a, 0xbf, 0xf6, 0xaf,
+				0x41, 0x49, 0xf5, 0x1b,
+			])
+			.unwrap();
+			let expected = OneTimeTag::from_slice(&[
+				0xa8, 0x06, 0x1d, 0xc1, 0x30, 0x51, 0x36, 0xc6, 0xc2, 0x2b, 0x8b, 0xaf, 0x0c, 0x01,
+				0x27, 0xa9,
+			])
+			.unwrap();
+
+			let data = b"Cryptographic Forum Research Group";
+			assert!(onetimeauth(&one_time_key, data).unwrap() == expected);
+			assert!(onetimeauth_verify(&expected, &one_time_key, data).unwrap());
+			assert!(onetimeauth_verify(&expected, &one_time_key, b"Other data").is_err());
+		}
+	}
+}
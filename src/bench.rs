@@ -0,0 +1,380 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Throughput measurements.
+//!
+//! # Use case:
+//! `orion::bench` can be used to measure, at runtime, how fast orion's
+//! primitives are on the machine an application is deployed to.
+//!
+//! An example of this could be a server that supports several AEADs, and
+//! wants to prefer the fastest one on its hardware, much like TLS stacks
+//! choose their cipher suite preference.
+//!
+//! # About:
+//! - Each `Algorithm` is run once on every input size before it is timed, and
+//!   then repeatedly for at least `MEASUREMENT_TIME`.
+//! - Inputs and keys are random, and prepared before any measurement is taken.
+//!   The AEADs use a new nonce for every run.
+//! - The throughput of a `Measurement` is the number of input bytes processed
+//!   per second. For the AEADs, this is the plaintext length.
+//! - `bench::fastest()` returns the `Algorithm` with the highest throughput at
+//!   a given input size.
+//!
+//! # Parameters:
+//! - `algorithm`: The algorithm to measure.
+//! - `input_sizes`: The input lengths, in bytes, to measure `algorithm` with.
+//! - `candidates`: The algorithms to choose between.
+//! - `input_size`: The input length, in bytes, to compare `candidates` at.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `input_sizes` or `candidates` is empty.
+//! - An input size is 0 for `Algorithm::ChaCha20Poly1305` or
+//!   `Algorithm::XChaCha20Poly1305`.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - Measurements depend on the compiler, its optimization level and the load
+//!   of the machine. They should only be used to choose between algorithms
+//!   that are all acceptable, never to decide whether to encrypt at all.
+//! - Measuring takes time. It is best done once, such as at startup, with the
+//!   result kept for the lifetime of the application.
+//!
+//! # Example:
+//! ```rust
+//! use orion::bench::{self, Algorithm};
+//!
+//! let measurements = bench::measure(Algorithm::XChaCha20Poly1305, &[64, 1024])?;
+//! for measurement in measurements.iter() {
+//!     println!(
+//!         "{} bytes: {} bytes/s",
+//!         measurement.input_size(),
+//!         measurement.bytes_per_second()
+//!     );
+//! }
+//!
+//! let candidates = [Algorithm::ChaCha20Poly1305, Algorithm::XChaCha20Poly1305];
+//! let preferred = bench::fastest(&candidates, 1024)?;
+//! assert!(candidates.contains(&preferred));
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		aead::{chacha20poly1305, xchacha20poly1305},
+		constants::{IETF_CHACHA_NONCESIZE, POLY1305_OUTSIZE, XCHACHA_NONCESIZE},
+		hash::{blake2b, sha256, sha512},
+		mac::hmac,
+		stream::chacha20,
+	},
+	util,
+};
+use std::time::{Duration, Instant};
+
+/// The minimum time each input size of an `Algorithm` is run for.
+pub const MEASUREMENT_TIME: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An algorithm whose throughput can be measured.
+pub enum Algorithm {
+	/// AEAD ChaCha20Poly1305 encryption.
+	ChaCha20Poly1305,
+	/// AEAD XChaCha20Poly1305 encryption, as used by `orion::aead`.
+	XChaCha20Poly1305,
+	/// BLAKE2b with a 64-byte digest, as used by `orion::hash`.
+	Blake2b512,
+	/// SHA256.
+	Sha256,
+	/// SHA512.
+	Sha512,
+	/// HMAC-SHA512, as used by `orion::auth`.
+	HmacSha512,
+}
+
+impl Algorithm {
+	/// Return the name of the algorithm.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Algorithm::ChaCha20Poly1305 => "ChaCha20Poly1305",
+			Algorithm::XChaCha20Poly1305 => "XChaCha20Poly1305",
+			Algorithm::Blake2b512 => "BLAKE2b-512",
+			Algorithm::Sha256 => "SHA256",
+			Algorithm::Sha512 => "SHA512",
+			Algorithm::HmacSha512 => "HMAC-SHA512",
+		}
+	}
+
+	/// Return true if the algorithm rejects empty inputs.
+	fn requires_input(&self) -> bool {
+		match *self {
+			Algorithm::ChaCha20Poly1305 | Algorithm::XChaCha20Poly1305 => true,
+			_ => false,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The result of measuring an `Algorithm` at one input size.
+pub struct Measurement {
+	algorithm: Algorithm,
+	input_size: usize,
+	iterations: u64,
+	elapsed: Duration,
+}
+
+impl Measurement {
+	/// Return the algorithm that was measured.
+	pub fn algorithm(&self) -> Algorithm { self.algorithm }
+
+	/// Return the input length, in bytes, that was measured.
+	pub fn input_size(&self) -> usize { self.input_size }
+
+	/// Return the number of times the algorithm was run.
+	pub fn iterations(&self) -> u64 { self.iterations }
+
+	/// Return the total time of all runs.
+	pub fn elapsed(&self) -> Duration { self.elapsed }
+
+	/// Return the number of input bytes processed per second.
+	pub fn bytes_per_second(&self) -> f64 {
+		let seconds = self.elapsed.as_secs() as f64 + f64::from(self.elapsed.subsec_nanos()) * 1e-9;
+		if seconds == 0.0 {
+			return 0.0;
+		}
+
+		(self.input_size as f64) * (self.iterations as f64) / seconds
+	}
+}
+
+/// The keys, input and output buffer of an `Algorithm` at one input size.
+struct Runner {
+	algorithm: Algorithm,
+	chacha_key: chacha20::SecretKey,
+	hmac_key: hmac::SecretKey,
+	input: Vec<u8>,
+	dst_out: Vec<u8>,
+	counter: u64,
+}
+
+impl Runner {
+	/// Prepare random keys and a random input of `input_size` bytes.
+	fn new(algorithm: Algorithm, input_size: usize) -> Result<Self, UnknownCryptoError> {
+		if algorithm.requires_input() && input_size == 0 {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut input = vec![0u8; input_size];
+		if input_size > 0 {
+			util::secure_rand_bytes(&mut input)?;
+		}
+
+		Ok(Self {
+			algorithm,
+			chacha_key: chacha20::SecretKey::generate()?,
+			hmac_key: hmac::SecretKey::generate()?,
+			input,
+			dst_out: vec![0u8; input_size + POLY1305_OUTSIZE],
+			counter: 0,
+		})
+	}
+
+	/// Run the algorithm once. For the AEADs, a nonce is never used twice with
+	/// the same key.
+	fn run(&mut self) -> Result<(), UnknownCryptoError> {
+		self.counter += 1;
+
+		match self.algorithm {
+			Algorithm::ChaCha20Poly1305 => {
+				let mut nonce = [0u8; IETF_CHACHA_NONCESIZE];
+				nonce[..8].copy_from_slice(&self.counter.to_le_bytes());
+				chacha20poly1305::seal(
+					&self.chacha_key,
+					&chacha20poly1305::Nonce::from_slice(&nonce)?,
+					&self.input,
+					None,
+					&mut self.dst_out,
+				)
+			}
+			Algorithm::XChaCha20Poly1305 => {
+				let mut nonce = [0u8; XCHACHA_NONCESIZE];
+				nonce[..8].copy_from_slice(&self.counter.to_le_bytes());
+				xchacha20poly1305::seal(
+					&self.chacha_key,
+					&xchacha20poly1305::Nonce::from_slice(&nonce)?,
+					&self.input,
+					None,
+					&mut self.dst_out,
+				)
+			}
+			Algorithm::Blake2b512 => blake2b::digest(None, 64, &self.input).map(|_| ()),
+			Algorithm::Sha256 => sha256::digest(&self.input).map(|_| ()),
+			Algorithm::Sha512 => sha512::digest(&self.input).map(|_| ()),
+			Algorithm::HmacSha512 => hmac::hmac(&self.hmac_key, &self.input).map(|_| ()),
+		}
+	}
+}
+
+#[must_use]
+/// Measure the throughput of `algorithm` at each of `input_sizes`. The
+/// returned measurements are in the order of `input_sizes`.
+pub fn measure(
+	algorithm: Algorithm,
+	input_sizes: &[usize],
+) -> Result<Vec<Measurement>, UnknownCryptoError> {
+	if input_sizes.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut measurements = Vec::with_capacity(input_sizes.len());
+	for input_size in input_sizes.iter() {
+		let mut runner = Runner::new(algorithm, *input_size)?;
+		runner.run()?;
+
+		let mut iterations = 0u64;
+		let start = Instant::now();
+		let mut elapsed = Duration::from_secs(0);
+		while elapsed < MEASUREMENT_TIME {
+			runner.run()?;
+			iterations += 1;
+			elapsed = start.elapsed();
+		}
+
+		measurements.push(Measurement {
+			algorithm,
+			input_size: *input_size,
+			iterations,
+			elapsed,
+		});
+	}
+
+	Ok(measurements)
+}
+
+#[must_use]
+/// Measure each of `candidates` at `input_size` and return the one with the
+/// highest throughput. Ties are won by the candidate listed first.
+pub fn fastest(
+	candidates: &[Algorithm],
+	input_size: usize,
+) -> Result<Algorithm, UnknownCryptoError> {
+	if candidates.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut best: Option<Measurement> = None;
+	for candidate in candidates.iter() {
+		let measurement = measure(*candidate, &[input_size])?[0];
+		best = match best {
+			Some(current) if current.bytes_per_second() >= measurement.bytes_per_second() => {
+				Some(current)
+			}
+			_ => Some(measurement),
+		};
+	}
+
+	// `candidates` is not empty, so `best` is always set here.
+	Ok(best.unwrap().algorithm())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_measure {
+		use super::*;
+
+		#[test]
+		fn test_measure_sizes_in_order() {
+			let measurements = measure(Algorithm::XChaCha20Poly1305, &[1, 64, 1024]).unwrap();
+			assert_eq!(measurements.len(), 3);
+			for (measurement, size) in measurements.iter().zip([1usize, 64, 1024].iter()) {
+				assert_eq!(measurement.algorithm(), Algorithm::XChaCha20Poly1305);
+				assert_eq!(measurement.input_size(), *size);
+				assert!(measurement.iterations() > 0);
+				assert!(measurement.elapsed() >= MEASUREMENT_TIME);
+				assert!(measurement.bytes_per_second() > 0.0);
+			}
+		}
+
+		#[test]
+		fn test_measure_all_algorithms() {
+			for algorithm in [
+				Algorithm::ChaCha20Poly1305,
+				Algorithm::XChaCha20Poly1305,
+				Algorithm::Blake2b512,
+				Algorithm::Sha256,
+				Algorithm::Sha512,
+				Algorithm::HmacSha512,
+			]
+			.iter()
+			{
+				let measurements = measure(*algorithm, &[128]).unwrap();
+				assert_eq!(measurements.len(), 1);
+				assert!(measurements[0].bytes_per_second() > 0.0);
+			}
+		}
+
+		#[test]
+		fn test_measure_empty_sizes() {
+			assert!(measure(Algorithm::Sha512, &[]).is_err());
+		}
+
+		#[test]
+		fn test_measure_empty_input() {
+			assert!(measure(Algorithm::ChaCha20Poly1305, &[0]).is_err());
+			assert!(measure(Algorithm::XChaCha20Poly1305, &[64, 0]).is_err());
+
+			let measurements = measure(Algorithm::Blake2b512, &[0]).unwrap();
+			assert_eq!(measurements[0].input_size(), 0);
+			assert_eq!(measurements[0].bytes_per_second(), 0.0);
+		}
+	}
+
+	mod test_fastest {
+		use super::*;
+
+		#[test]
+		fn test_fastest_is_candidate() {
+			let candidates = [Algorithm::ChaCha20Poly1305, Algorithm::XChaCha20Poly1305];
+			let preferred = fastest(&candidates, 256).unwrap();
+			assert!(candidates.contains(&preferred));
+
+			assert_eq!(
+				fastest(&[Algorithm::Sha256], 256).unwrap(),
+				Algorithm::Sha256
+			);
+		}
+
+		#[test]
+		fn test_fastest_no_candidates() {
+			assert!(fastest(&[], 256).is_err());
+		}
+
+		#[test]
+		fn test_fastest_empty_input() {
+			assert!(fastest(&[Algorithm::Sha256, Algorithm::ChaCha20Poly1305], 0).is_err());
+		}
+	}
+}
@@ -0,0 +1,190 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Hash-based commitments.
+//!
+//! # Use case:
+//! `orion::commit` can be used to commit to a message without revealing it,
+//! and later reveal the message in a way that proves it was not changed
+//! after committing to it.
+//!
+//! An example of this could be a sealed-bid auction, where each party
+//! publishes a commitment to their bid, and only reveals the bid once all
+//! commitments have been received.
+//!
+//! # About:
+//! - `commit()` generates a random 32-byte `Opening`, and returns it along
+//!   with the `Commitment`.
+//! - The `Commitment` is BLAKE2b-256 of the domain separator
+//!   `"orion-commit-v1"`, the `Opening` and the message, in that order.
+//! - To reveal the message, both the message and the `Opening` are sent to the
+//!   party that holds the `Commitment`, who checks them with `verify()`.
+//!
+//! # Parameters:
+//! - `message`: The message to commit to.
+//! - `commitment`: The `Commitment` received before the message was revealed.
+//! - `opening`: The `Opening` received along with the revealed message.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The `Commitment` computed from `message` and `opening` does not match
+//!   `commitment`.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - The `Opening` must be kept secret until the message is revealed. Anyone
+//!   who knows it can check guesses of the message against the `Commitment`.
+//! - An `Opening` must never be re-used. Use `commit()`, which generates a new
+//!   one every time.
+//! - A `Commitment` does not hide the length of the message.
+//!
+//! # Example:
+//! ```
+//! use orion::commit;
+//!
+//! let (commitment, opening) = commit::commit(b"My bid is 100").unwrap();
+//! // Publish `commitment`, and later reveal the message and `opening`.
+//! assert!(commit::verify(&commitment, b"My bid is 100", &opening).unwrap());
+//! assert!(commit::verify(&commitment, b"My bid is 200", &opening).is_err());
+//! ```
+
+use crate::{
+	errors::{UnknownCryptoError, ValidationCryptoError},
+	hazardous::{hash::blake2b, types},
+	util,
+};
+
+/// The size of a `Commitment`.
+const COMMITMENT_SIZE: usize = 32;
+
+/// The size of an `Opening`.
+const OPENING_SIZE: usize = 32;
+
+/// The domain separator hashed before the `Opening` and the message.
+const COMMIT_DOMAIN: &[u8] = b"orion-commit-v1";
+
+/// A type to represent the `Commitment` to a message.
+pub type Commitment = types::PublicData<types::tag::Commitment, COMMITMENT_SIZE>;
+
+/// A type to represent the secret `Opening` of a `Commitment`.
+pub type Opening = types::SecretKey<types::tag::Opening, OPENING_SIZE>;
+
+/// Compute the `Commitment` to `message` with `opening`.
+fn compute(message: &[u8], opening: &Opening) -> Result<Commitment, UnknownCryptoError> {
+	let mut state = blake2b::init(None, COMMITMENT_SIZE)?;
+	state.update(COMMIT_DOMAIN)?;
+	state.update(opening.unprotected_as_bytes())?;
+	state.update(message)?;
+
+	Commitment::from_slice(state.finalize()?.as_bytes())
+}
+
+#[must_use]
+/// Commit to `message` with a randomly generated `Opening`.
+pub fn commit(message: &[u8]) -> Result<(Commitment, Opening), UnknownCryptoError> {
+	let opening = Opening::generate()?;
+	let commitment = compute(message, &opening)?;
+
+	Ok((commitment, opening))
+}
+
+#[must_use]
+/// Verify in constant time that `commitment` is the `Commitment` to `message`
+/// with `opening`.
+pub fn verify(
+	commitment: &Commitment,
+	message: &[u8],
+	opening: &Opening,
+) -> Result<bool, ValidationCryptoError> {
+	let computed = compute(message, opening)?;
+
+	if util::secure_cmp(commitment.as_bytes(), computed.as_bytes())? {
+		Ok(true)
+	} else {
+		Err(ValidationCryptoError)
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_commit {
+		use super::*;
+
+		#[test]
+		fn test_known_commitment() {
+			let opening = Opening::from_slice(&[0u8; 32]).unwrap();
+			let expected: Commitment =
+				"2a9b816800b7ecadeb51a63ee88eeff2e7e2cb07800233d4e091000f9a120e8c"
+					.parse()
+					.unwrap();
+
+			assert_eq!(compute(b"orion", &opening).unwrap(), expected);
+			assert!(verify(&expected, b"orion", &opening).unwrap());
+		}
+
+		#[test]
+		fn test_commit_hiding() {
+			// The same message gives different commitments.
+			let (first, _) = commit(b"orion").unwrap();
+			let (second, _) = commit(b"orion").unwrap();
+			assert!(first != second);
+		}
+
+		#[test]
+		fn test_commit_empty_message() {
+			let (commitment, opening) = commit(b"").unwrap();
+			assert!(verify(&commitment, b"", &opening).unwrap());
+			assert!(verify(&commitment, b"\0", &opening).is_err());
+		}
+	}
+
+	mod test_verify {
+		use super::*;
+
+		#[test]
+		fn test_verify_bad_message() {
+			let (commitment, opening) = commit(b"orion").unwrap();
+			assert!(verify(&commitment, b"orion", &opening).unwrap());
+			assert!(verify(&commitment, b"orioN", &opening).is_err());
+		}
+
+		#[test]
+		fn test_verify_bad_opening() {
+			let (commitment, opening) = commit(b"orion").unwrap();
+			let (_, other_opening) = commit(b"orion").unwrap();
+			assert!(verify(&commitment, b"orion", &opening).unwrap());
+			assert!(verify(&commitment, b"orion", &other_opening).is_err());
+		}
+
+		#[test]
+		fn test_verify_bad_commitment() {
+			let (commitment, opening) = commit(b"orion").unwrap();
+			let mut bytes: [u8; 32] = commitment.into();
+			bytes[0] ^= 1;
+			assert!(verify(&Commitment::from(bytes), b"orion", &opening).is_err());
+		}
+	}
+}
@@ -0,0 +1,347 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The subset of CBOR ([RFC 8949](https://tools.ietf.org/html/rfc8949))
+//! that COSE structures are made of. Only definite lengths are supported.
+
+use crate::errors::UnknownCryptoError;
+
+/// The maximum nesting of arrays, maps and tags when decoding.
+const MAX_DEPTH: usize = 16;
+
+/// Major type of unsigned integers.
+pub(crate) const MAJOR_UINT: u8 = 0;
+/// Major type of negative integers.
+pub(crate) const MAJOR_NINT: u8 = 1;
+/// Major type of byte strings.
+pub(crate) const MAJOR_BYTES: u8 = 2;
+/// Major type of text strings.
+pub(crate) const MAJOR_TEXT: u8 = 3;
+/// Major type of arrays.
+pub(crate) const MAJOR_ARRAY: u8 = 4;
+/// Major type of maps.
+pub(crate) const MAJOR_MAP: u8 = 5;
+/// Major type of tags.
+pub(crate) const MAJOR_TAG: u8 = 6;
+/// Major type of simple values and floats.
+const MAJOR_SIMPLE: u8 = 7;
+
+#[derive(Debug, Clone, PartialEq)]
+/// A decoded CBOR data item.
+pub(crate) enum Value {
+	/// An integer, which is negative for major type 1.
+	Int(i128),
+	/// A byte string.
+	Bytes(Vec<u8>),
+	/// A text string.
+	Text(String),
+	/// An array.
+	Array(Vec<Value>),
+	/// A map, in the order it was encoded.
+	Map(Vec<(Value, Value)>),
+	/// A tagged data item.
+	Tag(u64, Box<Value>),
+	/// A simple value or float, with its argument.
+	Simple(u64),
+}
+
+impl Value {
+	/// Return the value stored under the integer `label` in a map.
+	pub(crate) fn map_get(&self, label: i64) -> Option<&Value> {
+		match self {
+			Value::Map(entries) => entries
+				.iter()
+				.find(|(key, _)| *key == Value::Int(i128::from(label)))
+				.map(|(_, value)| value),
+			_ => None,
+		}
+	}
+}
+
+/// Append the head of a data item with major type `major` and argument
+/// `argument` to `out`, in its shortest form.
+pub(crate) fn encode_head(out: &mut Vec<u8>, major: u8, argument: u64) {
+	let major = major << 5;
+
+	if argument < 24 {
+		out.push(major | argument as u8);
+	} else if argument <= u64::from(u8::MAX) {
+		out.push(major | 24);
+		out.push(argument as u8);
+	} else if argument <= u64::from(u16::MAX) {
+		out.push(major | 25);
+		out.extend_from_slice(&(argument as u16).to_be_bytes());
+	} else if argument <= u64::from(u32::MAX) {
+		out.push(major | 26);
+		out.extend_from_slice(&(argument as u32).to_be_bytes());
+	} else {
+		out.push(major | 27);
+		out.extend_from_slice(&argument.to_be_bytes());
+	}
+}
+
+/// Append the byte string `bytes` to `out`.
+pub(crate) fn encode_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+	encode_head(out, MAJOR_BYTES, bytes.len() as u64);
+	out.extend_from_slice(bytes);
+}
+
+/// Append the text string `text` to `out`.
+pub(crate) fn encode_text(out: &mut Vec<u8>, text: &str) {
+	encode_head(out, MAJOR_TEXT, text.len() as u64);
+	out.extend_from_slice(text.as_bytes());
+}
+
+/// Append the integer `value` to `out`.
+pub(crate) fn encode_int(out: &mut Vec<u8>, value: i64) {
+	if value < 0 {
+		encode_head(out, MAJOR_NINT, !(value as u64));
+	} else {
+		encode_head(out, MAJOR_UINT, value as u64);
+	}
+}
+
+/// Read `bytes` as a big-endian unsigned integer.
+fn be_uint(bytes: &[u8]) -> u64 {
+	bytes
+		.iter()
+		.fold(0, |acc, byte| (acc << 8) | u64::from(*byte))
+}
+
+/// A decoder that reads data items from the start of a buffer.
+struct Decoder<'a> {
+	data: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Decoder<'a> {
+	/// Read `len` bytes.
+	fn take(&mut self, len: usize) -> Result<&'a [u8], UnknownCryptoError> {
+		if len > self.data.len() - self.position {
+			return Err(UnknownCryptoError);
+		}
+
+		let bytes = &self.data[self.position..(self.position + len)];
+		self.position += len;
+
+		Ok(bytes)
+	}
+
+	/// Read the head of a data item, returning its major type and argument.
+	fn head(&mut self) -> Result<(u8, u64), UnknownCryptoError> {
+		let initial = self.take(1)?[0];
+		let major = initial >> 5;
+
+		let argument = match initial & 0x1f {
+			n @ 0..=23 => u64::from(n),
+			24 => u64::from(self.take(1)?[0]),
+			25 => be_uint(self.take(2)?),
+			26 => be_uint(self.take(4)?),
+			27 => be_uint(self.take(8)?),
+			// Reserved values and indefinite lengths.
+			_ => return Err(UnknownCryptoError),
+		};
+
+		Ok((major, argument))
+	}
+
+	/// Read a length, which cannot be more than the number of bytes left.
+	fn length(&self, argument: u64) -> Result<usize, UnknownCryptoError> {
+		if argument > (self.data.len() - self.position) as u64 {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(argument as usize)
+	}
+
+	/// Read one data item.
+	fn value(&mut self, depth: usize) -> Result<Value, UnknownCryptoError> {
+		if depth > MAX_DEPTH {
+			return Err(UnknownCryptoError);
+		}
+
+		let (major, argument) = self.head()?;
+		match major {
+			MAJOR_UINT => Ok(Value::Int(i128::from(argument))),
+			MAJOR_NINT => Ok(Value::Int(-1 - i128::from(argument))),
+			MAJOR_BYTES => {
+				let len = self.length(argument)?;
+				Ok(Value::Bytes(self.take(len)?.to_vec()))
+			}
+			MAJOR_TEXT => {
+				let len = self.length(argument)?;
+				let text = core::str::from_utf8(self.take(len)?).map_err(|_| UnknownCryptoError)?;
+				Ok(Value::Text(String::from(text)))
+			}
+			MAJOR_ARRAY => {
+				// Every item is at least one byte.
+				let len = self.length(argument)?;
+				let mut items = Vec::with_capacity(len);
+				for _ in 0..len {
+					items.push(self.value(depth + 1)?);
+				}
+				Ok(Value::Array(items))
+			}
+			MAJOR_MAP => {
+				let len = self.length(argument)?;
+				let mut entries = Vec::with_capacity(len);
+				for _ in 0..len {
+					let key = self.value(depth + 1)?;
+					let value = self.value(depth + 1)?;
+					entries.push((key, value));
+				}
+				Ok(Value::Map(entries))
+			}
+			MAJOR_TAG => Ok(Value::Tag(argument, Box::new(self.value(depth + 1)?))),
+			MAJOR_SIMPLE => Ok(Value::Simple(argument)),
+			_ => unreachable!(),
+		}
+	}
+}
+
+/// Decode `data`, which must be exactly one data item.
+pub(crate) fn decode(data: &[u8]) -> Result<Value, UnknownCryptoError> {
+	let mut decoder = Decoder { data, position: 0 };
+	let value = decoder.value(0)?;
+
+	if decoder.position != data.len() {
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(value)
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_encode_head {
+		use super::*;
+
+		#[test]
+		fn test_rfc8949_examples() {
+			let encode = |value: i64| {
+				let mut out = Vec::new();
+				encode_int(&mut out, value);
+				out
+			};
+
+			assert_eq!(encode(0), [0x00]);
+			assert_eq!(encode(23), [0x17]);
+			assert_eq!(encode(24), [0x18, 0x18]);
+			assert_eq!(encode(1000), [0x19, 0x03, 0xe8]);
+			assert_eq!(encode(1_000_000), [0x1a, 0x00, 0x0f, 0x42, 0x40]);
+			assert_eq!(
+				encode(1_000_000_000_000),
+				[0x1b, 0x00, 0x00, 0x00, 0xe8, 0xd4, 0xa5, 0x10, 0x00]
+			);
+			assert_eq!(encode(-1), [0x20]);
+			assert_eq!(encode(-1000), [0x39, 0x03, 0xe7]);
+
+			let mut out = Vec::new();
+			encode_text(&mut out, "IETF");
+			encode_bytes(&mut out, &[1, 2, 3, 4]);
+			assert_eq!(
+				out,
+				[0x64, 0x49, 0x45, 0x54, 0x46, 0x44, 0x01, 0x02, 0x03, 0x04]
+			);
+		}
+	}
+
+	mod test_decode {
+		use super::*;
+
+		#[test]
+		fn test_rfc8949_examples() {
+			assert_eq!(
+				decode(&[0x1a, 0x00, 0x0f, 0x42, 0x40]).unwrap(),
+				Value::Int(1_000_000)
+			);
+			assert_eq!(
+				decode(&[0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).unwrap(),
+				Value::Int(-18_446_744_073_709_551_616)
+			);
+			assert_eq!(decode(&[0xf6]).unwrap(), Value::Simple(22));
+			assert_eq!(
+				decode(&[0x83, 0x01, 0x82, 0x02, 0x03, 0x82, 0x04, 0x05]).unwrap(),
+				Value::Array(vec![
+					Value::Int(1),
+					Value::Array(vec![Value::Int(2), Value::Int(3)]),
+					Value::Array(vec![Value::Int(4), Value::Int(5)]),
+				])
+			);
+			assert_eq!(
+				decode(&[0xa2, 0x61, 0x61, 0x01, 0x61, 0x62, 0x82, 0x02, 0x03]).unwrap(),
+				Value::Map(vec![
+					(Value::Text(String::from("a")), Value::Int(1)),
+					(
+						Value::Text(String::from("b")),
+						Value::Array(vec![Value::Int(2), Value::Int(3)])
+					),
+				])
+			);
+			assert_eq!(
+				decode(&[0xd8, 0x20, 0x40]).unwrap(),
+				Value::Tag(32, Box::new(Value::Bytes(vec![])))
+			);
+		}
+
+		#[test]
+		fn test_map_get() {
+			let map = decode(&[0xa2, 0x01, 0x05, 0x20, 0x41, 0xff]).unwrap();
+			assert_eq!(map.map_get(1), Some(&Value::Int(5)));
+			assert_eq!(map.map_get(-1), Some(&Value::Bytes(vec![0xff])));
+			assert_eq!(map.map_get(2), None);
+			assert_eq!(Value::Int(1).map_get(1), None);
+		}
+
+		#[test]
+		fn test_malformed_err() {
+			// Empty, truncated and trailing data.
+			assert!(decode(&[]).is_err());
+			assert!(decode(&[0x19, 0x03]).is_err());
+			assert!(decode(&[0x44, 0x01, 0x02, 0x03]).is_err());
+			assert!(decode(&[0x82, 0x01]).is_err());
+			assert!(decode(&[0x01, 0x02]).is_err());
+			// Indefinite lengths and reserved values.
+			assert!(decode(&[0x5f, 0x41, 0x01, 0xff]).is_err());
+			assert!(decode(&[0x1c]).is_err());
+			// Invalid UTF-8.
+			assert!(decode(&[0x61, 0xff]).is_err());
+			// A huge length is rejected before allocating.
+			assert!(decode(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err());
+		}
+
+		#[test]
+		fn test_depth_limit() {
+			let mut nested = vec![0x81; MAX_DEPTH];
+			nested.push(0x00);
+			assert!(decode(&nested).is_ok());
+
+			let mut nested = vec![0x81; MAX_DEPTH + 1];
+			nested.push(0x00);
+			assert!(decode(&nested).is_err());
+		}
+	}
+}
@@ -0,0 +1,504 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! COSE (CBOR Object Signing and Encryption) structures.
+//!
+//! # Use case:
+//! `orion::cose` can be used to create and verify `COSE_Mac0` and
+//! `COSE_Encrypt0` messages, for example when exchanging data with
+//! constrained devices that share a secret key.
+//!
+//! # About:
+//! - Implements `COSE_Mac0` with HMAC 256/256 and HMAC 512/512, and
+//!   `COSE_Encrypt0` with ChaCha20/Poly1305, as specified in
+//!   [RFC 8152](https://tools.ietf.org/html/rfc8152).
+//! - The algorithm is chosen by the caller, and never read from a message to
+//!   decide how to verify it. The protected header of a message must contain
+//!   only the chosen algorithm, encoded the way orion encodes it.
+//! - Messages are created with the CBOR tag of their type, and are accepted
+//!   with or without it.
+//! - A random 12 byte IV is generated for each `COSE_Encrypt0` message and
+//!   stored in its unprotected header.
+//! - Detached payloads, countersignatures and partial IVs are not supported.
+//! - This module is only available with the `cose` feature, which requires
+//!   `safe_api`.
+//!
+//! # Parameters:
+//! - `algorithm`: The MAC algorithm to create or verify a message with.
+//! - `secret_key`: The secret key.
+//! - `payload`: The payload of a `COSE_Mac0` message.
+//! - `plaintext`: The data to encrypt into a `COSE_Encrypt0` message.
+//! - `external_aad`: Additional data that is authenticated, but not stored in
+//!   the message. The same `external_aad` must be given when verifying.
+//! - `message`: The encoded message to verify or decrypt.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is shorter than the output of the hash function (32 bytes
+//!   for HMAC 256/256, 64 bytes for HMAC 512/512) for `COSE_Mac0`.
+//! - `secret_key` is not 32 bytes for `COSE_Encrypt0`.
+//! - `plaintext` is empty.
+//! - `message` is not a validly encoded message of the expected type.
+//! - The protected header of `message` is not that of the chosen algorithm.
+//! - `message` fails authentication, for example because it was modified,
+//!   or because `secret_key` or `external_aad` is not the one used when
+//!   creating it.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - The secret key should always be generated using a CSPRNG.
+//!   `SecretKey::generate()` can be used for this.
+//! - The `secret_key` must only be used with one algorithm.
+//! - Because the IV is random, no more than 2^32 messages should be
+//!   encrypted with the same `secret_key`.
+//!
+//! # Example:
+//! ```
+//! use orion::cose::{self, MacAlgorithm};
+//!
+//! let key = cose::SecretKey::default();
+//!
+//! let message = cose::mac0_create(MacAlgorithm::Hmac256, &key, b"Reading", b"").unwrap();
+//! let payload = cose::mac0_verify(MacAlgorithm::Hmac256, &key, &message, b"").unwrap();
+//! assert_eq!(payload, b"Reading");
+//!
+//! let message = cose::encrypt0_seal(&key, b"Reading", b"device-1").unwrap();
+//! let plaintext = cose::encrypt0_open(&key, &message, b"device-1").unwrap();
+//! assert_eq!(plaintext, b"Reading");
+//! ```
+
+/// A minimal CBOR encoder and decoder.
+mod cbor;
+
+pub use crate::hltypes::SecretKey;
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		aead::chacha20poly1305,
+		constants::{IETF_CHACHA_NONCESIZE, POLY1305_OUTSIZE, SHA256_OUTSIZE, SHA512_OUTSIZE},
+		mac::hmac,
+	},
+	util,
+};
+use cbor::Value;
+
+/// The CBOR tag of `COSE_Mac0`.
+const TAG_MAC0: u64 = 17;
+/// The CBOR tag of `COSE_Encrypt0`.
+const TAG_ENCRYPT0: u64 = 16;
+/// The header label of the IV.
+const LABEL_IV: i64 = 5;
+/// The algorithm identifier of ChaCha20/Poly1305.
+const ALG_CHACHA20_POLY1305: i64 = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The MAC algorithm that a `COSE_Mac0` message is created and verified with.
+pub enum MacAlgorithm {
+	/// HMAC 256/256, which is HMAC-SHA256 with an untruncated tag.
+	Hmac256,
+	/// HMAC 512/512, which is HMAC-SHA512 with an untruncated tag.
+	Hmac512,
+}
+
+impl MacAlgorithm {
+	/// The COSE algorithm identifier.
+	fn identifier(self) -> i64 {
+		match self {
+			MacAlgorithm::Hmac256 => 5,
+			MacAlgorithm::Hmac512 => 7,
+		}
+	}
+
+	/// The HMAC of `data` with `secret_key`.
+	fn mac(self, secret_key: &SecretKey, data: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+		let key = secret_key.unprotected_as_bytes();
+
+		match self {
+			MacAlgorithm::Hmac256 => {
+				if key.len() < SHA256_OUTSIZE {
+					return Err(UnknownCryptoError);
+				}
+				let tag = hmac::sha256::hmac(&hmac::sha256::SecretKey::from_slice(key)?, data)?;
+				Ok(tag.unprotected_as_bytes().to_vec())
+			}
+			MacAlgorithm::Hmac512 => {
+				if key.len() < SHA512_OUTSIZE {
+					return Err(UnknownCryptoError);
+				}
+				let tag = hmac::hmac(&hmac::SecretKey::from_slice(key)?, data)?;
+				Ok(tag.unprotected_as_bytes().to_vec())
+			}
+		}
+	}
+}
+
+/// The serialized protected header that contains only the algorithm
+/// `identifier`.
+fn protected_header(identifier: i64) -> Vec<u8> {
+	let mut header = Vec::new();
+	cbor::encode_head(&mut header, cbor::MAJOR_MAP, 1);
+	cbor::encode_int(&mut header, 1);
+	cbor::encode_int(&mut header, identifier);
+
+	header
+}
+
+/// Encode a `context` structure with the byte strings `fields`, which is
+/// `MAC_structure` or `Enc_structure`.
+fn structure(context: &str, fields: &[&[u8]]) -> Vec<u8> {
+	let mut encoded = Vec::new();
+	cbor::encode_head(&mut encoded, cbor::MAJOR_ARRAY, 1 + fields.len() as u64);
+	cbor::encode_text(&mut encoded, context);
+	for field in fields.iter() {
+		cbor::encode_bytes(&mut encoded, field);
+	}
+
+	encoded
+}
+
+/// Decode `message` as an array of `len` items, optionally tagged with `tag`,
+/// whose protected header is `expected_protected`.
+fn decode_message(
+	message: &[u8],
+	tag: u64,
+	len: usize,
+	expected_protected: &[u8],
+) -> Result<Vec<Value>, UnknownCryptoError> {
+	let items = match cbor::decode(message)? {
+		Value::Tag(found, inner) if found == tag => *inner,
+		Value::Tag(_, _) => return Err(UnknownCryptoError),
+		untagged => untagged,
+	};
+	let items = match items {
+		Value::Array(items) if items.len() == len => items,
+		_ => return Err(UnknownCryptoError),
+	};
+
+	let is_expected = match (&items[0], &items[1]) {
+		(Value::Bytes(protected), Value::Map(_)) => protected[..] == expected_protected[..],
+		_ => false,
+	};
+	if !is_expected {
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(items)
+}
+
+#[must_use]
+/// Create a tagged `COSE_Mac0` message of `payload`.
+pub fn mac0_create(
+	algorithm: MacAlgorithm,
+	secret_key: &SecretKey,
+	payload: &[u8],
+	external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let protected = protected_header(algorithm.identifier());
+	let tag = algorithm.mac(
+		secret_key,
+		&structure("MAC0", &[&protected, external_aad, payload]),
+	)?;
+
+	let mut message = Vec::new();
+	cbor::encode_head(&mut message, cbor::MAJOR_TAG, TAG_MAC0);
+	cbor::encode_head(&mut message, cbor::MAJOR_ARRAY, 4);
+	cbor::encode_bytes(&mut message, &protected);
+	cbor::encode_head(&mut message, cbor::MAJOR_MAP, 0);
+	cbor::encode_bytes(&mut message, payload);
+	cbor::encode_bytes(&mut message, &tag);
+
+	Ok(message)
+}
+
+#[must_use]
+/// Verify a `COSE_Mac0` `message`, returning its payload.
+pub fn mac0_verify(
+	algorithm: MacAlgorithm,
+	secret_key: &SecretKey,
+	message: &[u8],
+	external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let protected = protected_header(algorithm.identifier());
+	let items = decode_message(message, TAG_MAC0, 4, &protected)?;
+	let (payload, tag) = match (&items[2], &items[3]) {
+		(Value::Bytes(payload), Value::Bytes(tag)) => (payload, tag),
+		_ => return Err(UnknownCryptoError),
+	};
+
+	let expected = algorithm.mac(
+		secret_key,
+		&structure("MAC0", &[&protected, external_aad, payload]),
+	)?;
+	util::secure_cmp(&expected, tag)?;
+
+	Ok(payload.clone())
+}
+
+/// Create a tagged `COSE_Encrypt0` message of `plaintext` using a given `iv`.
+fn encrypt0_seal_with_iv(
+	secret_key: &SecretKey,
+	iv: &[u8],
+	plaintext: &[u8],
+	external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let key = chacha20poly1305::SecretKey::from_slice(secret_key.unprotected_as_bytes())?;
+	let protected = protected_header(ALG_CHACHA20_POLY1305);
+
+	let mut ciphertext = vec![0u8; plaintext.len() + POLY1305_OUTSIZE];
+	chacha20poly1305::seal(
+		&key,
+		&chacha20poly1305::Nonce::from_slice(iv)?,
+		plaintext,
+		Some(&structure("Encrypt0", &[&protected, external_aad])),
+		&mut ciphertext,
+	)?;
+
+	let mut message = Vec::new();
+	cbor::encode_head(&mut message, cbor::MAJOR_TAG, TAG_ENCRYPT0);
+	cbor::encode_head(&mut message, cbor::MAJOR_ARRAY, 3);
+	cbor::encode_bytes(&mut message, &protected);
+	cbor::encode_head(&mut message, cbor::MAJOR_MAP, 1);
+	cbor::encode_int(&mut message, LABEL_IV);
+	cbor::encode_bytes(&mut message, iv);
+	cbor::encode_bytes(&mut message, &ciphertext);
+
+	Ok(message)
+}
+
+#[must_use]
+/// Encrypt `plaintext` into a tagged `COSE_Encrypt0` message.
+pub fn encrypt0_seal(
+	secret_key: &SecretKey,
+	plaintext: &[u8],
+	external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let mut iv = [0u8; IETF_CHACHA_NONCESIZE];
+	util::secure_rand_bytes(&mut iv)?;
+
+	encrypt0_seal_with_iv(secret_key, &iv, plaintext, external_aad)
+}
+
+#[must_use]
+/// Authenticate and decrypt a `COSE_Encrypt0` `message`, returning its
+/// plaintext.
+pub fn encrypt0_open(
+	secret_key: &SecretKey,
+	message: &[u8],
+	external_aad: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let key = chacha20poly1305::SecretKey::from_slice(secret_key.unprotected_as_bytes())?;
+	let protected = protected_header(ALG_CHACHA20_POLY1305);
+	let items = decode_message(message, TAG_ENCRYPT0, 3, &protected)?;
+
+	let iv = match items[1].map_get(LABEL_IV) {
+		Some(Value::Bytes(iv)) => chacha20poly1305::Nonce::from_slice(iv)?,
+		_ => return Err(UnknownCryptoError),
+	};
+	let ciphertext = match &items[2] {
+		Value::Bytes(ciphertext) if ciphertext.len() > POLY1305_OUTSIZE => ciphertext,
+		_ => return Err(UnknownCryptoError),
+	};
+
+	let mut plaintext = vec![0u8; ciphertext.len() - POLY1305_OUTSIZE];
+	chacha20poly1305::open(
+		&key,
+		&iv,
+		ciphertext,
+		Some(&structure("Encrypt0", &[&protected, external_aad])),
+		&mut plaintext,
+	)?;
+
+	Ok(plaintext)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use crate::encoding::hex_decode_into;
+
+	fn key(len: usize) -> SecretKey {
+		SecretKey::from_slice(&(0..len).map(|n| n as u8).collect::<Vec<u8>>()).unwrap()
+	}
+
+	fn from_hex(hex: &str) -> Vec<u8> {
+		let mut bytes = vec![0u8; hex.len() / 2];
+		hex_decode_into(hex, &mut bytes).unwrap();
+		bytes
+	}
+
+	const PAYLOAD: &[u8] = b"This is the content.";
+
+	// Computed with an independent implementation of RFC 8152.
+	const MAC0_HMAC256: &str = "d18443a10105a054546869732069732074686520636f6e74656e742e58205a989ca3b8f01a1dbcd1c6b54729c92bf105191d40584f12833abaf79d768a19";
+	const MAC0_HMAC512: &str = "d18443a10107a054546869732069732074686520636f6e74656e742e5840fcb92072b50eff39a01ac93406b236168b00c34908449868cdd552761ecfe8a80620176d43c444377ccf10457ed62651637342e784c1a581b12b2c696457cbc1";
+	const ENCRYPT0: &str = "d08344a1011818a1054c404142434445464748494a4b5824ac3c15f252239d212ca79320d88003b9d1a21736f6631042ea2de985d3ee63fddf98dad9";
+
+	mod test_mac0 {
+		use super::*;
+
+		#[test]
+		fn test_known_messages() {
+			assert_eq!(
+				mac0_create(MacAlgorithm::Hmac256, &key(32), PAYLOAD, b"aad").unwrap(),
+				from_hex(MAC0_HMAC256)
+			);
+			assert_eq!(
+				mac0_create(MacAlgorithm::Hmac512, &key(64), PAYLOAD, b"").unwrap(),
+				from_hex(MAC0_HMAC512)
+			);
+			assert_eq!(
+				mac0_verify(
+					MacAlgorithm::Hmac256,
+					&key(32),
+					&from_hex(MAC0_HMAC256),
+					b"aad"
+				)
+				.unwrap(),
+				PAYLOAD
+			);
+			assert_eq!(
+				mac0_verify(
+					MacAlgorithm::Hmac512,
+					&key(64),
+					&from_hex(MAC0_HMAC512),
+					b""
+				)
+				.unwrap(),
+				PAYLOAD
+			);
+		}
+
+		#[test]
+		fn test_untagged_accepted() {
+			let message = from_hex(MAC0_HMAC256);
+			assert_eq!(
+				mac0_verify(MacAlgorithm::Hmac256, &key(32), &message[1..], b"aad").unwrap(),
+				PAYLOAD
+			);
+
+			// Tagged as COSE_Encrypt0 instead.
+			let mut wrong_tag = message.clone();
+			wrong_tag[0] = 0xd0;
+			assert!(mac0_verify(MacAlgorithm::Hmac256, &key(32), &wrong_tag, b"aad").is_err());
+		}
+
+		#[test]
+		fn test_algorithm_is_pinned() {
+			let key = key(64);
+			let message = mac0_create(MacAlgorithm::Hmac256, &key, PAYLOAD, b"").unwrap();
+			assert!(mac0_verify(MacAlgorithm::Hmac512, &key, &message, b"").is_err());
+			let message = mac0_create(MacAlgorithm::Hmac512, &key, PAYLOAD, b"").unwrap();
+			assert!(mac0_verify(MacAlgorithm::Hmac256, &key, &message, b"").is_err());
+		}
+
+		#[test]
+		fn test_mismatch_err() {
+			let message = from_hex(MAC0_HMAC256);
+			assert!(mac0_verify(MacAlgorithm::Hmac256, &key(32), &message, b"").is_err());
+			assert!(mac0_verify(MacAlgorithm::Hmac256, &key(33), &message, b"aad").is_err());
+			for idx in 0..message.len() {
+				let mut modified = message.clone();
+				modified[idx] ^= 1;
+				assert!(mac0_verify(MacAlgorithm::Hmac256, &key(32), &modified, b"aad").is_err());
+			}
+		}
+
+		#[test]
+		fn test_short_key_err() {
+			assert!(mac0_create(MacAlgorithm::Hmac256, &key(31), PAYLOAD, b"").is_err());
+			assert!(mac0_create(MacAlgorithm::Hmac512, &key(63), PAYLOAD, b"").is_err());
+		}
+
+		#[test]
+		fn test_empty_payload() {
+			let message = mac0_create(MacAlgorithm::Hmac256, &key(32), b"", b"").unwrap();
+			assert!(mac0_verify(MacAlgorithm::Hmac256, &key(32), &message, b"")
+				.unwrap()
+				.is_empty());
+		}
+
+		#[test]
+		fn test_detached_payload_err() {
+			// The payload replaced with nil.
+			let mut message = from_hex("d18443a10105a0f6");
+			message.extend_from_slice(&from_hex(MAC0_HMAC256)[27..]);
+			assert!(mac0_verify(MacAlgorithm::Hmac256, &key(32), &message, b"aad").is_err());
+		}
+	}
+
+	mod test_encrypt0 {
+		use super::*;
+
+		#[test]
+		fn test_known_message() {
+			let iv: Vec<u8> = (0x40..0x4c).collect();
+			assert_eq!(
+				encrypt0_seal_with_iv(&key(32), &iv, PAYLOAD, b"aad").unwrap(),
+				from_hex(ENCRYPT0)
+			);
+			assert_eq!(
+				encrypt0_open(&key(32), &from_hex(ENCRYPT0), b"aad").unwrap(),
+				PAYLOAD
+			);
+			assert_eq!(
+				encrypt0_open(&key(32), &from_hex(ENCRYPT0)[1..], b"aad").unwrap(),
+				PAYLOAD
+			);
+		}
+
+		#[test]
+		fn test_roundtrip() {
+			let key = SecretKey::default();
+			let message = encrypt0_seal(&key, b"Reading", b"").unwrap();
+			assert_eq!(encrypt0_open(&key, &message, b"").unwrap(), b"Reading");
+			assert_ne!(message, encrypt0_seal(&key, b"Reading", b"").unwrap());
+		}
+
+		#[test]
+		fn test_mismatch_err() {
+			let message = from_hex(ENCRYPT0);
+			assert!(encrypt0_open(&key(32), &message, b"").is_err());
+			assert!(encrypt0_open(&SecretKey::default(), &message, b"aad").is_err());
+			for idx in 0..message.len() {
+				let mut modified = message.clone();
+				modified[idx] ^= 1;
+				assert!(encrypt0_open(&key(32), &modified, b"aad").is_err());
+			}
+		}
+
+		#[test]
+		fn test_bad_input_err() {
+			assert!(encrypt0_seal(&key(31), b"Reading", b"").is_err());
+			assert!(encrypt0_seal(&key(64), b"Reading", b"").is_err());
+			assert!(encrypt0_seal(&key(32), b"", b"").is_err());
+			assert!(encrypt0_open(&key(64), &from_hex(ENCRYPT0), b"aad").is_err());
+
+			// A COSE_Mac0 message is not a COSE_Encrypt0 message.
+			assert!(encrypt0_open(&key(32), &from_hex(MAC0_HMAC256), b"aad").is_err());
+			assert!(encrypt0_open(&key(32), &[], b"").is_err());
+			// No IV in the unprotected header.
+			let no_iv = from_hex(&format!("8344a1011818a051{}", "00".repeat(17)));
+			assert!(encrypt0_open(&key(32), &no_iv, b"").is_err());
+		}
+	}
+}
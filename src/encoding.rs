@@ -0,0 +1,368 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::errors::UnknownCryptoError;
+
+#[cfg(feature = "safe_api")]
+#[inline]
+/// Encode the 6 least-significant bits of `value` as a Base64 character in
+/// constant time.
+fn encode_base64_char(value: u8, url_safe: bool) -> u8 {
+	let value = i16::from(value & 0x3f);
+	let (diff_62, diff_63) = if url_safe { (13, 49) } else { (15, 3) };
+
+	let mut diff: i16 = 0x41;
+	// 26..=51: 'a'..='z'
+	diff += ((25 - value) >> 8) & 6;
+	// 52..=61: '0'..='9'
+	diff -= ((51 - value) >> 8) & 75;
+	// 62: '+' or '-'
+	diff -= ((61 - value) >> 8) & diff_62;
+	// 63: '/' or '_'
+	diff += ((62 - value) >> 8) & diff_63;
+
+	(value + diff) as u8
+}
+
+#[cfg(feature = "safe_api")]
+#[inline]
+/// Decode a Base64 character in constant time. Returns -1 if `chr` is not in
+/// the alphabet.
+fn decode_base64_char(chr: u8, url_safe: bool) -> i16 {
+	let chr = i16::from(chr);
+	let (chr_62, chr_63) = if url_safe { (0x2d, 0x5f) } else { (0x2b, 0x2f) };
+
+	let mut value: i16 = -1;
+	// 'A'..='Z'
+	value += (((0x40 - chr) & (chr - 0x5b)) >> 8) & (chr - 64);
+	// 'a'..='z'
+	value += (((0x60 - chr) & (chr - 0x7b)) >> 8) & (chr - 70);
+	// '0'..='9'
+	value += (((0x2f - chr) & (chr - 0x3a)) >> 8) & (chr + 5);
+	value += (((chr_62 - 1 - chr) & (chr - chr_62 - 1)) >> 8) & 63;
+	value += (((chr_63 - 1 - chr) & (chr - chr_63 - 1)) >> 8) & 64;
+
+	value
+}
+
+#[cfg(feature = "safe_api")]
+/// Encode `src` as Base64 without padding, using either the standard or the
+/// URL-safe alphabet from [RFC 4648](https://tools.ietf.org/html/rfc4648).
+pub(crate) fn base64_encode(src: &[u8], url_safe: bool) -> String {
+	let mut encoded = Vec::with_capacity((src.len() * 4 + 2) / 3);
+
+	for chunk in src.chunks(3) {
+		let mut block = [0u8; 3];
+		block[..chunk.len()].copy_from_slice(chunk);
+
+		let chars = [
+			block[0] >> 2,
+			(block[0] << 4) | (block[1] >> 4),
+			(block[1] << 2) | (block[2] >> 6),
+			block[2],
+		];
+		for value in chars.iter().take(chunk.len() + 1) {
+			encoded.push(encode_base64_char(*value, url_safe));
+		}
+	}
+
+	// .unwrap() cannot panic since all encoded characters are ASCII
+	String::from_utf8(encoded).unwrap()
+}
+
+#[cfg(feature = "safe_api")]
+/// Decode unpadded Base64 in `src`, using either the standard or the URL-safe
+/// alphabet from [RFC 4648](https://tools.ietf.org/html/rfc4648).
+///
+/// An error is returned if `src` contains characters outside the alphabet or
+/// is not canonically encoded.
+pub(crate) fn base64_decode(src: &str, url_safe: bool) -> Result<Vec<u8>, UnknownCryptoError> {
+	let src = src.as_bytes();
+	if src.len() % 4 == 1 {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut decoded = Vec::with_capacity(src.len() * 3 / 4);
+	let mut invalid: i16 = 0;
+
+	for chunk in src.chunks(4) {
+		let mut values = [0i16; 4];
+		for (value, chr) in values.iter_mut().zip(chunk.iter()) {
+			*value = decode_base64_char(*chr, url_safe);
+			invalid |= *value;
+		}
+
+		let block = [
+			((values[0] << 2) | (values[1] >> 4)) as u8,
+			((values[1] << 4) | (values[2] >> 2)) as u8,
+			((values[2] << 6) | values[3]) as u8,
+		];
+		decoded.extend_from_slice(&block[..(chunk.len() - 1)]);
+
+		// Unused bits of the last character must be zero.
+		match chunk.len() {
+			2 => invalid |= -i16::from(values[1] & 0x0f != 0),
+			3 => invalid |= -i16::from(values[2] & 0x03 != 0),
+			_ => (),
+		}
+	}
+
+	if invalid < 0 {
+		Err(UnknownCryptoError)
+	} else {
+		Ok(decoded)
+	}
+}
+
+#[inline]
+/// Encode the 4 least-significant bits of `value` as a lowercase hex
+/// character in constant time.
+fn encode_hex_char(value: u8) -> char {
+	let value = i16::from(value & 0x0f);
+	// 10..=15: 'a'..='f'
+	(value + 0x30 + (((9 - value) >> 8) & 0x27)) as u8 as char
+}
+
+#[inline]
+/// Decode a hex character in constant time. Both lowercase and uppercase
+/// characters are accepted. Returns -1 if `chr` is not a hex character.
+fn decode_hex_char(chr: u8) -> i16 {
+	let chr = i16::from(chr);
+
+	let mut value: i16 = -1;
+	// '0'..='9'
+	value += (((0x2f - chr) & (chr - 0x3a)) >> 8) & (chr - 0x2f);
+	// 'a'..='f'
+	value += (((0x60 - chr) & (chr - 0x67)) >> 8) & (chr - 0x56);
+	// 'A'..='F'
+	value += (((0x40 - chr) & (chr - 0x47)) >> 8) & (chr - 0x36);
+
+	value
+}
+
+/// Write `src` to `f` as lowercase hex.
+pub(crate) fn hex_encode_fmt(src: &[u8], f: &mut core::fmt::Formatter) -> core::fmt::Result {
+	use core::fmt::Write;
+
+	for byte in src.iter() {
+		f.write_char(encode_hex_char(byte >> 4))?;
+		f.write_char(encode_hex_char(*byte))?;
+	}
+
+	Ok(())
+}
+
+/// Decode the hex in `src` into `dst`.
+///
+/// An error is returned if `src` is not exactly twice the length of `dst` or
+/// contains non-hex characters.
+pub(crate) fn hex_decode_into(src: &str, dst: &mut [u8]) -> Result<(), UnknownCryptoError> {
+	let src = src.as_bytes();
+	if src.len() != dst.len() * 2 {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut invalid: i16 = 0;
+	for (byte, chars) in dst.iter_mut().zip(src.chunks(2)) {
+		let high = decode_hex_char(chars[0]);
+		let low = decode_hex_char(chars[1]);
+		invalid |= high | low;
+		*byte = ((high << 4) | low) as u8;
+	}
+
+	if invalid < 0 {
+		Err(UnknownCryptoError)
+	} else {
+		Ok(())
+	}
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	// One function tested per submodule.
+	#[cfg(feature = "safe_api")]
+	mod test_base64_encode {
+		use super::*;
+
+		#[test]
+		fn test_rfc4648_vectors() {
+			let vectors: [(&[u8], &str); 7] = [
+				(b"", ""),
+				(b"f", "Zg"),
+				(b"fo", "Zm8"),
+				(b"foo", "Zm9v"),
+				(b"foob", "Zm9vYg"),
+				(b"fooba", "Zm9vYmE"),
+				(b"foobar", "Zm9vYmFy"),
+			];
+
+			for (data, expected) in vectors.iter() {
+				assert_eq!(&base64_encode(data, false), expected);
+				assert_eq!(&base64_encode(data, true), expected);
+			}
+		}
+
+		#[test]
+		fn test_alphabets() {
+			assert_eq!(base64_encode(&[0xfb, 0xff, 0xbf], false), "+/+/");
+			assert_eq!(base64_encode(&[0xfb, 0xff, 0xbf], true), "-_-_");
+		}
+
+		#[test]
+		fn test_all_characters() {
+			let mut data = Vec::new();
+			for value in 0u8..64 {
+				data.push(value);
+			}
+			// Pack the 6-bit values into bytes.
+			let mut packed = Vec::new();
+			for values in data.chunks(4) {
+				packed.push((values[0] << 2) | (values[1] >> 4));
+				packed.push((values[1] << 4) | (values[2] >> 2));
+				packed.push((values[2] << 6) | values[3]);
+			}
+
+			assert_eq!(
+				base64_encode(&packed, false),
+				"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+			);
+			assert_eq!(
+				base64_encode(&packed, true),
+				"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+			);
+		}
+	}
+
+	#[cfg(feature = "safe_api")]
+	mod test_base64_decode {
+		use super::*;
+
+		#[test]
+		fn test_rfc4648_vectors() {
+			let vectors: [(&[u8], &str); 7] = [
+				(b"", ""),
+				(b"f", "Zg"),
+				(b"fo", "Zm8"),
+				(b"foo", "Zm9v"),
+				(b"foob", "Zm9vYg"),
+				(b"fooba", "Zm9vYmE"),
+				(b"foobar", "Zm9vYmFy"),
+			];
+
+			for (expected, data) in vectors.iter() {
+				assert_eq!(&base64_decode(data, false).unwrap()[..], *expected);
+				assert_eq!(&base64_decode(data, true).unwrap()[..], *expected);
+			}
+		}
+
+		#[test]
+		fn test_alphabets() {
+			assert_eq!(base64_decode("+/+/", false).unwrap(), [0xfb, 0xff, 0xbf]);
+			assert_eq!(base64_decode("-_-_", true).unwrap(), [0xfb, 0xff, 0xbf]);
+			assert!(base64_decode("-_-_", false).is_err());
+			assert!(base64_decode("+/+/", true).is_err());
+		}
+
+		#[test]
+		fn test_invalid_err() {
+			// Padding is not accepted.
+			assert!(base64_decode("Zg==", false).is_err());
+			// Invalid length.
+			assert!(base64_decode("Zm9vY", false).is_err());
+			// Non-canonical trailing bits.
+			assert!(base64_decode("Zh", false).is_err());
+			assert!(base64_decode("Zm9", false).is_err());
+			// Characters outside the alphabet.
+			for chr in [b' ', b'.', b'*', b'=', b'@', b'[', b'`', b'{', 0x80, 0xff].iter() {
+				let mut encoded = b"Zm9v".to_vec();
+				encoded[1] = *chr;
+				if let Ok(encoded) = String::from_utf8(encoded) {
+					assert!(base64_decode(&encoded, false).is_err());
+					assert!(base64_decode(&encoded, true).is_err());
+				}
+			}
+		}
+
+		// Proptests. Only exectued when NOT testing no_std.
+		#[cfg(feature = "safe_api")]
+		mod proptest {
+			use super::*;
+
+			quickcheck! {
+				/// Decoding encoded data should always return the same data.
+				fn prop_encode_decode(input: Vec<u8>, url_safe: bool) -> bool {
+					base64_decode(&base64_encode(&input, url_safe), url_safe).unwrap() == input
+				}
+			}
+		}
+	}
+	mod test_hex_decode_into {
+		use super::*;
+
+		#[test]
+		fn test_all_bytes() {
+			let chars = b"0123456789abcdef";
+			for value in 0u8..=255 {
+				let encoded = [chars[(value >> 4) as usize], chars[(value & 0x0f) as usize]];
+				let mut dst = [0u8; 1];
+				hex_decode_into(core::str::from_utf8(&encoded).unwrap(), &mut dst).unwrap();
+				assert_eq!(dst[0], value);
+			}
+		}
+
+		#[test]
+		fn test_uppercase() {
+			let mut dst = [0u8; 4];
+			hex_decode_into("DEADbeef", &mut dst).unwrap();
+			assert_eq!(dst, [0xde, 0xad, 0xbe, 0xef]);
+		}
+
+		#[test]
+		fn test_invalid_err() {
+			let mut dst = [0u8; 2];
+			assert!(hex_decode_into("abc", &mut dst).is_err());
+			assert!(hex_decode_into("abcdef", &mut dst).is_err());
+			assert!(hex_decode_into("", &mut dst).is_err());
+			for chr in ["/", ":", "@", "G", "`", "g", " ", "\u{e9}"].iter() {
+				let mut encoded = [b'a'; 4];
+				encoded[..chr.len()].copy_from_slice(chr.as_bytes());
+				let encoded = core::str::from_utf8(&encoded[..]).unwrap();
+				assert!(hex_decode_into(encoded, &mut dst).is_err());
+			}
+		}
+	}
+
+	mod test_encode_hex_char {
+		use super::*;
+
+		#[test]
+		fn test_all_values() {
+			let chars = "0123456789abcdef";
+			for (value, expected) in chars.chars().enumerate() {
+				assert_eq!(encode_hex_char(value as u8), expected);
+			}
+		}
+	}
+}
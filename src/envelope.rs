@@ -0,0 +1,363 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Envelope encryption with a key-encryption key.
+//!
+//! # Use case:
+//! `orion::envelope` can be used to encrypt data with a fresh data-encryption
+//! key (DEK), which is itself encrypted with a long-term key-encryption key
+//! (KEK).
+//!
+//! An example of this could be a storage service where the KEK is kept in a
+//! key management system, and only the small encrypted DEK needs to be sent to
+//! it. Rotating the KEK then only requires re-encrypting the DEK with
+//! `rewrap()`, not the data itself.
+//!
+//! # About:
+//! - A random 32-byte DEK is generated for each call to `seal()`.
+//! - The DEK is encrypted with the KEK, and the plaintext is encrypted with
+//!   the DEK, both using `orion::aead`.
+//! - Returns a vector where the first 72 bytes are the encrypted DEK, and the
+//!   rest is the encrypted plaintext as returned by `aead::seal()`.
+//! - `seal_many()` encrypts the plaintext once, and the DEK to each of up to
+//!   255 KEKs. The first byte of the returned vector is the number of KEKs,
+//!   followed by 72 bytes of encrypted DEK for each of them, and the encrypted
+//!   plaintext. Any one of the KEKs can decrypt it with `open_many()`.
+//! - Wrapping the DEK to an X25519 public key is not supported, since orion
+//!   does not yet provide X25519.
+//!
+//! # Parameters:
+//! - `kek`: The key-encryption key used to encrypt the DEK.
+//! - `keks`: The key-encryption keys used to encrypt the DEK with `seal_many()`.
+//! - `new_kek`: The key-encryption key to re-encrypt the DEK with.
+//! - `plaintext`: The data to be encrypted.
+//! - `envelope`: The data to be decrypted, as returned by `seal()` or
+//!   `seal_many()`.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `kek`, `new_kek` or any of `keks` is not 32 bytes.
+//! - `keks` is empty or contains more than 255 keys.
+//! - `plaintext` is empty.
+//! - `envelope` is less than 113 bytes, or 114 bytes for `open_many()`.
+//! - `envelope` was not sealed with `kek`, or has been modified.
+//! - Any of the errors of `aead::seal()` and `aead::open()` occur.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - A new DEK is used for every envelope, so a nonce is never re-used with a
+//!   given DEK.
+//! - `seal_many()` does not hide how many KEKs an envelope was sealed with.
+//!   Each KEK holder can decrypt the DEK, and so all of the data.
+//! - `rewrap()` does not change the DEK. Anyone who has learned the DEK of an
+//!   envelope can still decrypt it after the KEK has been rotated.
+//!
+//! # Example:
+//! ```
+//! use orion::envelope;
+//!
+//! let kek = envelope::SecretKey::default();
+//! let sealed = envelope::seal(&kek, b"Secret message").unwrap();
+//! assert_eq!(envelope::open(&kek, &sealed).unwrap(), b"Secret message");
+//!
+//! // Rotate the key-encryption key without re-encrypting the message.
+//! let new_kek = envelope::SecretKey::default();
+//! let rewrapped = envelope::rewrap(&kek, &new_kek, &sealed).unwrap();
+//! assert_eq!(envelope::open(&new_kek, &rewrapped).unwrap(), b"Secret message");
+//! assert!(envelope::open(&kek, &rewrapped).is_err());
+//!
+//! // Encrypt once, for several key-encryption keys.
+//! let sealed = envelope::seal_many(&[&kek, &new_kek], b"Secret message").unwrap();
+//! assert_eq!(envelope::open_many(&new_kek, &sealed).unwrap(), b"Secret message");
+//! ```
+
+pub use crate::hltypes::SecretKey;
+use crate::{
+	aead,
+	errors::UnknownCryptoError,
+	hazardous::constants::{POLY1305_BLOCKSIZE, XCHACHA_NONCESIZE},
+};
+use zeroize::Zeroize;
+
+/// The length of the data-encryption key.
+const DEK_SIZE: usize = 32;
+
+/// The length of the encrypted data-encryption key.
+const WRAPPED_DEK_SIZE: usize = XCHACHA_NONCESIZE + DEK_SIZE + POLY1305_BLOCKSIZE;
+
+/// Decrypt the data-encryption key at the start of `envelope` with `kek`.
+fn unwrap_dek(kek: &SecretKey, envelope: &[u8]) -> Result<SecretKey, UnknownCryptoError> {
+	// `+ 1` to avoid empty ciphertexts
+	if envelope.len() < (WRAPPED_DEK_SIZE + XCHACHA_NONCESIZE + POLY1305_BLOCKSIZE + 1) {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut dek_bytes = aead::open(kek, &envelope[..WRAPPED_DEK_SIZE])?;
+	let dek = SecretKey::from_slice(&dek_bytes);
+	dek_bytes.zeroize();
+
+	dek
+}
+
+#[must_use]
+/// Encrypt `plaintext` with a new data-encryption key, which is encrypted with
+/// `kek`.
+pub fn seal(kek: &SecretKey, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+	if plaintext.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let dek = SecretKey::generate(DEK_SIZE)?;
+	let mut envelope = aead::seal(kek, dek.unprotected_as_bytes())?;
+	envelope.extend_from_slice(&aead::seal(&dek, plaintext)?);
+
+	Ok(envelope)
+}
+
+#[must_use]
+/// Encrypt `plaintext` with a new data-encryption key, which is encrypted with
+/// each of `keks`.
+pub fn seal_many(keks: &[&SecretKey], plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+	if keks.is_empty() || keks.len() > usize::from(u8::max_value()) || plaintext.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let dek = SecretKey::generate(DEK_SIZE)?;
+	let mut envelope = vec![keks.len() as u8];
+	for kek in keks.iter() {
+		envelope.extend_from_slice(&aead::seal(kek, dek.unprotected_as_bytes())?);
+	}
+	envelope.extend_from_slice(&aead::seal(&dek, plaintext)?);
+
+	Ok(envelope)
+}
+
+#[must_use]
+/// Decrypt the data-encryption key of `envelope` with `kek`, and use it to
+/// decrypt the rest of `envelope`.
+pub fn open(kek: &SecretKey, envelope: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+	let dek = unwrap_dek(kek, envelope)?;
+
+	aead::open(&dek, &envelope[WRAPPED_DEK_SIZE..])
+}
+
+#[must_use]
+/// Find the data-encryption key of an envelope from `seal_many()` that was
+/// encrypted with `kek`, and use it to decrypt the rest of `envelope`.
+pub fn open_many(kek: &SecretKey, envelope: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+	if envelope.is_empty() || envelope[0] == 0 {
+		return Err(UnknownCryptoError);
+	}
+
+	let header_len = 1 + usize::from(envelope[0]) * WRAPPED_DEK_SIZE;
+	// `+ 1` to avoid empty ciphertexts
+	if envelope.len() < (header_len + XCHACHA_NONCESIZE + POLY1305_BLOCKSIZE + 1) {
+		return Err(UnknownCryptoError);
+	}
+
+	let payload = &envelope[header_len..];
+	for wrapped_dek in envelope[1..header_len].chunks(WRAPPED_DEK_SIZE) {
+		let mut dek_bytes = match aead::open(kek, wrapped_dek) {
+			Ok(dek_bytes) => dek_bytes,
+			Err(_) => continue,
+		};
+		let dek = SecretKey::from_slice(&dek_bytes);
+		dek_bytes.zeroize();
+
+		return aead::open(&dek?, payload);
+	}
+
+	Err(UnknownCryptoError)
+}
+
+#[must_use]
+/// Re-encrypt the data-encryption key of `envelope` from `kek` to `new_kek`,
+/// leaving the encrypted data unchanged.
+pub fn rewrap(
+	kek: &SecretKey,
+	new_kek: &SecretKey,
+	envelope: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let dek = unwrap_dek(kek, envelope)?;
+	// Make sure the data decrypts before committing to the new envelope.
+	let mut plaintext = aead::open(&dek, &envelope[WRAPPED_DEK_SIZE..])?;
+	plaintext.zeroize();
+
+	let mut new_envelope = aead::seal(new_kek, dek.unprotected_as_bytes())?;
+	new_envelope.extend_from_slice(&envelope[WRAPPED_DEK_SIZE..]);
+
+	Ok(new_envelope)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_seal_open {
+		use super::*;
+
+		#[test]
+		fn test_seal_open() {
+			let kek = SecretKey::default();
+			let envelope = seal(&kek, b"Data").unwrap();
+
+			assert_eq!(envelope.len(), WRAPPED_DEK_SIZE + 24 + 4 + 16);
+			assert_eq!(open(&kek, &envelope).unwrap(), b"Data");
+		}
+
+		#[test]
+		fn test_seal_empty_err() {
+			assert!(seal(&SecretKey::default(), b"").is_err());
+		}
+
+		#[test]
+		fn test_kek_length() {
+			assert!(seal(&SecretKey::generate(31).unwrap(), b"Data").is_err());
+			assert!(seal(&SecretKey::generate(33).unwrap(), b"Data").is_err());
+
+			let envelope = seal(&SecretKey::default(), b"Data").unwrap();
+			assert!(open(&SecretKey::generate(31).unwrap(), &envelope).is_err());
+		}
+
+		#[test]
+		fn test_open_wrong_kek_err() {
+			let envelope = seal(&SecretKey::default(), b"Data").unwrap();
+			assert!(open(&SecretKey::default(), &envelope).is_err());
+		}
+
+		#[test]
+		fn test_open_modified_err() {
+			let kek = SecretKey::default();
+			let mut envelope = seal(&kek, b"Data").unwrap();
+
+			assert!(open(&kek, &envelope[..WRAPPED_DEK_SIZE + 40]).is_err());
+			assert!(open(&kek, &envelope[..WRAPPED_DEK_SIZE]).is_err());
+
+			for idx in 0..envelope.len() {
+				envelope[idx] ^= 1;
+				assert!(open(&kek, &envelope).is_err());
+				envelope[idx] ^= 1;
+			}
+			assert_eq!(open(&kek, &envelope).unwrap(), b"Data");
+		}
+
+		#[test]
+		fn test_swapped_payload_err() {
+			// A payload cannot be moved to the header of another envelope.
+			let kek = SecretKey::default();
+			let first = seal(&kek, b"Data").unwrap();
+			let second = seal(&kek, b"Data").unwrap();
+
+			let mut swapped = first[..WRAPPED_DEK_SIZE].to_vec();
+			swapped.extend_from_slice(&second[WRAPPED_DEK_SIZE..]);
+			assert!(open(&kek, &swapped).is_err());
+		}
+	}
+
+	mod test_seal_many_open_many {
+		use super::*;
+
+		#[test]
+		fn test_seal_many_open_many() {
+			let keks: Vec<SecretKey> = (0..3).map(|_| SecretKey::default()).collect();
+			let kek_refs: Vec<&SecretKey> = keks.iter().collect();
+			let envelope = seal_many(&kek_refs, b"Data").unwrap();
+
+			assert_eq!(envelope[0], 3);
+			assert_eq!(envelope.len(), 1 + 3 * WRAPPED_DEK_SIZE + 24 + 4 + 16);
+			for kek in keks.iter() {
+				assert_eq!(open_many(kek, &envelope).unwrap(), b"Data");
+			}
+			assert!(open_many(&SecretKey::default(), &envelope).is_err());
+		}
+
+		#[test]
+		fn test_seal_many_kek_count() {
+			let kek = SecretKey::default();
+			assert!(seal_many(&[], b"Data").is_err());
+			assert!(seal_many(&[&kek; 255], b"Data").is_ok());
+			assert!(seal_many(&[&kek; 256], b"Data").is_err());
+			assert!(seal_many(&[&kek], b"").is_err());
+		}
+
+		#[test]
+		fn test_open_many_modified_err() {
+			let first = SecretKey::default();
+			let second = SecretKey::default();
+			let mut envelope = seal_many(&[&first, &second], b"Data").unwrap();
+
+			assert!(open_many(&first, &[]).is_err());
+			assert!(open_many(&first, &envelope[..envelope.len() - 4]).is_err());
+
+			// Only the encrypted DEK of `first` and the payload are checked when
+			// opening with `first`.
+			for idx in (1..=WRAPPED_DEK_SIZE).chain(1 + 2 * WRAPPED_DEK_SIZE..envelope.len()) {
+				envelope[idx] ^= 1;
+				assert!(open_many(&first, &envelope).is_err());
+				envelope[idx] ^= 1;
+			}
+
+			envelope[0] = 0;
+			assert!(open_many(&first, &envelope).is_err());
+			envelope[0] = 3;
+			assert!(open_many(&first, &envelope).is_err());
+			envelope[0] = 1;
+			assert!(open_many(&first, &envelope).is_err());
+			envelope[0] = 2;
+			assert_eq!(open_many(&second, &envelope).unwrap(), b"Data");
+		}
+	}
+
+	mod test_rewrap {
+		use super::*;
+
+		#[test]
+		fn test_rewrap() {
+			let kek = SecretKey::default();
+			let new_kek = SecretKey::default();
+			let envelope = seal(&kek, b"Data").unwrap();
+			let rewrapped = rewrap(&kek, &new_kek, &envelope).unwrap();
+
+			assert_eq!(&rewrapped[WRAPPED_DEK_SIZE..], &envelope[WRAPPED_DEK_SIZE..]);
+			assert_eq!(open(&new_kek, &rewrapped).unwrap(), b"Data");
+			assert!(open(&kek, &rewrapped).is_err());
+		}
+
+		#[test]
+		fn test_rewrap_wrong_kek_err() {
+			let envelope = seal(&SecretKey::default(), b"Data").unwrap();
+			assert!(rewrap(&SecretKey::default(), &SecretKey::default(), &envelope).is_err());
+		}
+
+		#[test]
+		fn test_rewrap_modified_payload_err() {
+			let kek = SecretKey::default();
+			let mut envelope = seal(&kek, b"Data").unwrap();
+			let last = envelope.len() - 1;
+			envelope[last] ^= 1;
+
+			assert!(rewrap(&kek, &SecretKey::default(), &envelope).is_err());
+		}
+	}
+}
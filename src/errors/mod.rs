@@ -92,6 +92,73 @@ impl From<FinalizationCryptoError> for ValidationCryptoError {
 	fn from(_: FinalizationCryptoError) -> Self { ValidationCryptoError }
 }
 
+/// Error for an input that is shorter or longer than allowed.
+#[derive(PartialEq)]
+pub struct InputSizeCryptoError;
+
+impl fmt::Display for InputSizeCryptoError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "InputSizeCryptoError - Input size out of bounds")
+	}
+}
+
+impl fmt::Debug for InputSizeCryptoError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "InputSizeCryptoError - Input size out of bounds")
+	}
+}
+
+impl From<InputSizeCryptoError> for UnknownCryptoError {
+	fn from(_: InputSizeCryptoError) -> Self { UnknownCryptoError }
+}
+
+impl From<InputSizeCryptoError> for ValidationCryptoError {
+	fn from(_: InputSizeCryptoError) -> Self { ValidationCryptoError }
+}
+
+/// Error for a long-running operation that was cancelled by its caller.
+#[derive(PartialEq)]
+pub struct CancelledCryptoError;
+
+impl fmt::Display for CancelledCryptoError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CancelledCryptoError - Cancelled by caller")
+	}
+}
+
+impl fmt::Debug for CancelledCryptoError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "CancelledCryptoError - Cancelled by caller")
+	}
+}
+
+impl From<CancelledCryptoError> for UnknownCryptoError {
+	fn from(_: CancelledCryptoError) -> Self { UnknownCryptoError }
+}
+
+#[cfg(feature = "safe_api")]
+impl std::error::Error for UnknownCryptoError {}
+
+#[cfg(feature = "safe_api")]
+impl std::error::Error for ValidationCryptoError {}
+
+#[cfg(feature = "safe_api")]
+impl std::error::Error for FinalizationCryptoError {}
+
+#[cfg(feature = "safe_api")]
+impl std::error::Error for CancelledCryptoError {}
+
+#[cfg(feature = "safe_api")]
+impl std::error::Error for InputSizeCryptoError {}
+
+#[cfg(feature = "safe_api")]
+// Required for `std::io::Write` on the streaming states
+impl From<FinalizationCryptoError> for std::io::Error {
+	fn from(err: FinalizationCryptoError) -> Self {
+		std::io::Error::new(std::io::ErrorKind::Other, err)
+	}
+}
+
 #[test]
 #[cfg(feature = "safe_api")]
 // format! is only available with std
@@ -161,3 +228,44 @@ fn test_unknown_crypto_error_from() {
 	let err = format!("{:?}", UnknownCryptoError::from(FinalizationCryptoError));
 	assert_eq!(err, "UnknownCryptoError");
 }
+
+#[test]
+#[cfg(feature = "safe_api")]
+// format! is only available with std
+fn test_cancelled_crypto_error_debug_display() {
+	// Tests Debug impl though "{:?}"
+	let err = format!("{:?}", CancelledCryptoError);
+	assert_eq!(err, "CancelledCryptoError - Cancelled by caller");
+	// Tests Display impl though "{:?}"
+	let err = format!("{}", CancelledCryptoError);
+	assert_eq!(err, "CancelledCryptoError - Cancelled by caller");
+	let err = format!("{:?}", UnknownCryptoError::from(CancelledCryptoError));
+	assert_eq!(err, "UnknownCryptoError");
+}
+
+#[test]
+#[cfg(feature = "safe_api")]
+// format! is only available with std
+fn test_input_size_crypto_error_debug_display() {
+	// Tests Debug impl though "{:?}"
+	let err = format!("{:?}", InputSizeCryptoError);
+	assert_eq!(err, "InputSizeCryptoError - Input size out of bounds");
+	// Tests Display impl though "{:?}"
+	let err = format!("{}", InputSizeCryptoError);
+	assert_eq!(err, "InputSizeCryptoError - Input size out of bounds");
+	let err = format!("{:?}", UnknownCryptoError::from(InputSizeCryptoError));
+	assert_eq!(err, "UnknownCryptoError");
+	let err = format!("{:?}", ValidationCryptoError::from(InputSizeCryptoError));
+	assert_eq!(err, "ValidationCryptoError - Failed verification");
+}
+
+#[test]
+#[cfg(feature = "safe_api")]
+fn test_finalization_crypto_error_into_io_error() {
+	let err: std::io::Error = FinalizationCryptoError.into();
+	assert_eq!(err.kind(), std::io::ErrorKind::Other);
+	assert_eq!(
+		format!("{}", err),
+		"FinalizationCryptoError - Missing reset"
+	);
+}
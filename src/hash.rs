@@ -31,18 +31,40 @@
 //!
 //! # About:
 //! - Uses BLAKE2b with an output size of 32 bytes (i.e BLAKE2b-256).
+//! - `digest_with_size()` and `Hasher::with_size()` can be used to select
+//!   another output size between 1 and 64 bytes.
+//! - `with_context()` hashes `data` bound to a `context` string, so that the
+//!   same data hashed for different purposes gives unrelated digests. It uses
+//!   BLAKE2b-256 keyed with the BLAKE2b-512 hash of the domain separator
+//!   `"orion-hash-context-v1"` and `context`.
+//! - `digest()` and `Hasher::update()` hash a slice in place, so a very large
+//!   file can be hashed by passing a memory map of it, such as one created
+//!   with the `memmap2` crate, instead of reading it through `digest_file()`.
 //!
 //! # Parameters:
 //! - `data`:  The data to be hashed.
+//! - `context`: A string that identifies what `data` is being hashed for.
+//! - `size`: The desired output size of the digest.
+//! - `reader`: A reader from which the data to be hashed is read.
+//! - `path`: The path of a file to be hashed.
 //!
 //! # Errors:
 //! An error will be returned if:
+//! - Reading from `reader` or the file at `path` fails.
+//! - `size` is 0.
+//! - `size` is greater than 64.
+//! - `context` is empty.
+//!
+//! `data` may be empty and has no maximum length, as given by `DATA_LIMITS`.
 //!
 //! # Security:
 //! - This interface does not support supplying BLAKE2b with a secret key, and
 //!   the hashes retrieved
 //! from using `orion::hash` are therefore not suitable as MACs.
 //! - BLAKE2b is not suitable for password hashing. See `orion::pwhash` instead.
+//! - The recommended minimum output size is 32.
+//! - `context` is not secret, and `with_context()` is not a MAC. Use
+//!   `orion::auth` if the data needs to be authenticated.
 //!
 //! # Example:
 //! ```
@@ -50,9 +72,56 @@
 //!
 //! let hash: Digest = digest(b"Some data").unwrap();
 //! ```
+//!
+//! Hashing data that arrives in chunks, using the streaming `Hasher`:
+//! ```
+//! use orion::hash::{digest, Hasher};
+//!
+//! let mut hasher = Hasher::new();
+//! hasher.update(b"Some ").unwrap();
+//! hasher.update(b"data").unwrap();
+//! assert_eq!(hasher.finalize().unwrap(), digest(b"Some data").unwrap());
+//! ```
+//!
+//! Hashing the contents of a reader, without reading it all into memory first:
+//! ```
+//! use orion::hash::{digest, digest_reader};
+//!
+//! let mut reader: &[u8] = b"Some data";
+//! assert_eq!(digest_reader(&mut reader).unwrap(), digest(b"Some data").unwrap());
+//! ```
+//!
+//! Selecting a different output size:
+//! ```
+//! use orion::hash::{digest_with_size, Hasher};
+//!
+//! let hash = digest_with_size(b"Some data", 64).unwrap();
+//! assert_eq!(hash.get_length(), 64);
+//!
+//! let mut hasher = Hasher::with_size(64).unwrap();
+//! hasher.update(b"Some data").unwrap();
+//! assert_eq!(hasher.finalize().unwrap(), hash);
+//! ```
+//!
+//! Hashing the same data for two different purposes:
+//! ```
+//! use orion::hash::with_context;
+//!
+//! let for_dedup = with_context(b"MyApp deduplication", b"Some data").unwrap();
+//! let for_cache = with_context(b"MyApp cache key", b"Some data").unwrap();
+//! assert_ne!(for_dedup, for_cache);
+//! ```
 
-pub use crate::hazardous::hash::blake2b::Digest;
 use crate::{errors::UnknownCryptoError, hazardous::hash::blake2b};
+pub use crate::{hazardous::hash::blake2b::Digest, hltypes::InputLimits};
+use std::{fs::File, io::Read, path::Path};
+
+/// The lengths of `data` that the functions in `hash` accept: `data` may be
+/// empty, and has no maximum length.
+pub const DATA_LIMITS: InputLimits = InputLimits::new_unchecked(0, usize::max_value());
+
+/// The domain separator hashed before the `context` in `with_context()`.
+const CONTEXT_DOMAIN: &[u8] = b"orion-hash-context-v1";
 
 #[must_use]
 /// Hashing using BLAKE2b-256.
@@ -60,6 +129,89 @@ pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
 	Ok(blake2b::Hasher::Blake2b256.digest(data)?)
 }
 
+#[must_use]
+/// Hashing using BLAKE2b with an output size of `size` bytes.
+pub fn digest_with_size(data: &[u8], size: usize) -> Result<Digest, UnknownCryptoError> {
+	let mut hasher = Hasher::with_size(size)?;
+	hasher.update(data)?;
+
+	hasher.finalize()
+}
+
+#[must_use]
+/// Hashing using BLAKE2b-256, of `data` bound to `context`.
+pub fn with_context(context: &[u8], data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+	if context.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut state = blake2b::init(None, 64)?;
+	state.update(CONTEXT_DOMAIN)?;
+	state.update(context)?;
+	let context_key = blake2b::SecretKey::from_slice(state.finalize()?.as_bytes())?;
+
+	blake2b::digest(Some(&context_key), 32, data)
+}
+
+#[must_use]
+#[derive(Debug, Clone)]
+/// Streaming hashing using BLAKE2b-256, or BLAKE2b with the output size given
+/// to `with_size()`.
+///
+/// `finalize()` consumes the `Hasher`, so it cannot be updated or finalized
+/// again afterwards.
+pub struct Hasher {
+	state: blake2b::Blake2b,
+}
+
+impl Hasher {
+	/// Initialize a new `Hasher`.
+	pub fn new() -> Self {
+		Self {
+			// .unwrap() cannot panic since the size is valid
+			state: blake2b::init(None, 32).unwrap(),
+		}
+	}
+
+	#[must_use]
+	/// Initialize a new `Hasher` with an output size of `size` bytes.
+	pub fn with_size(size: usize) -> Result<Self, UnknownCryptoError> {
+		Ok(Self {
+			state: blake2b::init(None, size)?,
+		})
+	}
+
+	#[must_use]
+	/// Update the `Hasher` with `data`. This can be called multiple times.
+	pub fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+		Ok(self.state.update(data)?)
+	}
+
+	#[must_use]
+	/// Return the `Digest` of all data the `Hasher` has been updated with.
+	pub fn finalize(mut self) -> Result<Digest, UnknownCryptoError> { Ok(self.state.finalize()?) }
+}
+
+impl Default for Hasher {
+	fn default() -> Self { Self::new() }
+}
+
+#[must_use]
+/// Hashing using BLAKE2b-256, of all data read from `reader`.
+pub fn digest_reader(reader: &mut impl Read) -> Result<Digest, UnknownCryptoError> {
+	let mut hasher = Hasher::new();
+	std::io::copy(reader, &mut hasher.state).map_err(|_| UnknownCryptoError)?;
+
+	hasher.finalize()
+}
+
+#[must_use]
+/// Hashing using BLAKE2b-256, of the contents of the file at `path`.
+pub fn digest_file<P: AsRef<Path>>(path: P) -> Result<Digest, UnknownCryptoError> {
+	let mut file = File::open(path).map_err(|_| UnknownCryptoError)?;
+	digest_reader(&mut file)
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -68,7 +220,9 @@ mod public {
 	mod test_digest {
 		use super::*;
 		#[test]
-		fn basic_test() { let _digest = digest(b"Some data").unwrap(); }
+		fn basic_test() {
+			let _digest = digest(b"Some data").unwrap();
+		}
 
 		// Proptests. Only exectued when NOT testing no_std.
 		#[cfg(feature = "safe_api")]
@@ -90,4 +244,207 @@ mod public {
 			}
 		}
 	}
+
+	mod test_data_limits {
+		use super::*;
+
+		#[test]
+		fn test_empty_data() {
+			assert!(DATA_LIMITS.check(0).is_ok());
+			assert!(DATA_LIMITS.check(usize::max_value()).is_ok());
+			assert!(digest(&[]).is_ok());
+			assert!(with_context(b"context", &[]).is_ok());
+		}
+	}
+
+	mod test_digest_with_size {
+		use super::*;
+
+		#[test]
+		fn test_size() {
+			assert!(digest_with_size(b"Some data", 0).is_err());
+			assert!(digest_with_size(b"Some data", 65).is_err());
+			assert_eq!(digest_with_size(b"Some data", 1).unwrap().get_length(), 1);
+			assert_eq!(digest_with_size(b"Some data", 64).unwrap().get_length(), 64);
+		}
+
+		#[test]
+		fn test_same_as_digest() {
+			assert_eq!(
+				digest_with_size(b"Some data", 32).unwrap(),
+				digest(b"Some data").unwrap()
+			);
+		}
+
+		#[test]
+		fn test_same_as_hazardous() {
+			assert_eq!(
+				digest_with_size(b"Some data", 64).unwrap(),
+				blake2b::Hasher::Blake2b512.digest(b"Some data").unwrap()
+			);
+		}
+
+		#[test]
+		fn test_diff_sizes_diff_result() {
+			// A shorter output size is not a truncation of a longer one.
+			let short = digest_with_size(b"Some data", 32).unwrap();
+			let long = digest_with_size(b"Some data", 64).unwrap();
+			assert_ne!(short.as_bytes(), &long.as_bytes()[..32]);
+		}
+	}
+
+	mod test_with_context {
+		use super::*;
+
+		#[test]
+		fn test_known_digest() {
+			// Generated with Python's hashlib.blake2b.
+			let expected =
+				hex::decode("8a85c8c7eeb11d4f56c08493782c010c9dca97f840b317715098fe920cc98ce7")
+					.unwrap();
+			let res = with_context(b"MyApp file integrity", b"Some data").unwrap();
+			assert_eq!(res.as_bytes(), &expected[..]);
+		}
+
+		#[test]
+		fn test_empty_context_err() {
+			assert!(with_context(b"", b"Some data").is_err());
+			assert!(with_context(b"Context", b"").is_ok());
+		}
+
+		#[test]
+		fn test_diff_context_diff_result() {
+			assert_ne!(
+				with_context(b"Context A", b"Some data").unwrap(),
+				with_context(b"Context B", b"Some data").unwrap()
+			);
+			assert_ne!(
+				with_context(b"Context", b"Some data").unwrap(),
+				digest(b"Some data").unwrap()
+			);
+		}
+
+		#[test]
+		fn test_no_ambiguous_split() {
+			// Moving bytes between `context` and `data` must change the digest.
+			assert_ne!(
+				with_context(b"Context", b"Some data").unwrap(),
+				with_context(b"ContextSome", b" data").unwrap()
+			);
+		}
+
+		// Proptests. Only exectued when NOT testing no_std.
+		#[cfg(feature = "safe_api")]
+		mod proptest {
+			use super::*;
+
+			quickcheck! {
+				/// Hashing with a context should never produce the same output as without.
+				fn prop_with_context_diff_from_digest(input: Vec<u8>) -> bool {
+					(with_context(b"Context", &input[..]).unwrap() != digest(&input[..]).unwrap())
+				}
+			}
+		}
+	}
+
+	mod test_hasher {
+		use super::*;
+
+		#[test]
+		fn test_with_size() {
+			assert!(Hasher::with_size(0).is_err());
+			assert!(Hasher::with_size(65).is_err());
+
+			let mut hasher = Hasher::with_size(48).unwrap();
+			hasher.update(b"Some data").unwrap();
+			assert_eq!(
+				hasher.finalize().unwrap(),
+				digest_with_size(b"Some data", 48).unwrap()
+			);
+		}
+
+		#[test]
+		fn test_same_as_digest() {
+			let mut hasher = Hasher::default();
+			hasher.update(b"Some ").unwrap();
+			hasher.update(b"").unwrap();
+			hasher.update(b"data").unwrap();
+			assert_eq!(hasher.finalize().unwrap(), digest(b"Some data").unwrap());
+		}
+
+		#[test]
+		fn test_no_update() {
+			assert_eq!(Hasher::new().finalize().unwrap(), digest(b"").unwrap());
+		}
+
+		// Proptests. Only exectued when NOT testing no_std.
+		#[cfg(feature = "safe_api")]
+		mod proptest {
+			use super::*;
+
+			quickcheck! {
+				/// Hashing in two chunks should produce the same output as hashing once.
+				fn prop_hasher_same_as_digest(input: Vec<u8>, split: usize) -> bool {
+					let split = if input.is_empty() { 0 } else { split % input.len() };
+					let mut hasher = Hasher::new();
+					hasher.update(&input[..split]).unwrap();
+					hasher.update(&input[split..]).unwrap();
+
+					(hasher.finalize().unwrap() == digest(&input[..]).unwrap())
+				}
+			}
+		}
+	}
+
+	mod test_digest_reader {
+		use super::*;
+
+		#[test]
+		fn test_same_as_digest() {
+			let data = vec![0u8; 256 * 1024 + 5];
+			let mut reader: &[u8] = &data;
+			assert_eq!(digest_reader(&mut reader).unwrap(), digest(&data).unwrap());
+		}
+
+		#[test]
+		fn test_empty_reader() {
+			let mut reader: &[u8] = &[];
+			assert_eq!(digest_reader(&mut reader).unwrap(), digest(&[]).unwrap());
+		}
+
+		// Proptests. Only exectued when NOT testing no_std.
+		#[cfg(feature = "safe_api")]
+		mod proptest {
+			use super::*;
+
+			quickcheck! {
+				/// Hashing from a reader should produce the same output as hashing a slice.
+				fn prop_digest_reader_same_as_digest(input: Vec<u8>) -> bool {
+					let mut reader: &[u8] = &input;
+					(digest_reader(&mut reader).unwrap() == digest(&input[..]).unwrap())
+				}
+			}
+		}
+	}
+
+	mod test_digest_file {
+		use super::*;
+		use std::io::Write;
+
+		#[test]
+		fn test_same_as_digest() {
+			let path = std::env::temp_dir().join("orion_test_digest_file");
+			let data = vec![1u8; 9000];
+			File::create(&path).unwrap().write_all(&data).unwrap();
+
+			let res = digest_file(&path).unwrap();
+			std::fs::remove_file(&path).unwrap();
+			assert_eq!(res, digest(&data).unwrap());
+		}
+
+		#[test]
+		fn test_file_not_found_err() {
+			assert!(digest_file("this/file/does/not/exist").is_err());
+		}
+	}
 }
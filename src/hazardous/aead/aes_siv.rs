@@ -0,0 +1,428 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! ### **Deterministic**:
+//! AES-SIV takes no nonce. Encrypting the same plaintext with the same key and
+//! `ad` always produces the same ciphertext. This is what makes it usable for
+//! equality-searchable data, such as blind indexes of encrypted database
+//! columns, and it is also what it reveals.
+//!
+//! # About:
+//! - This is AEAD_AES_SIV_CMAC_512 from the [RFC 5297](https://tools.ietf.org/html/rfc5297):
+//!   the first 32 bytes of the 64-byte `SecretKey` are the AES-256 key of
+//!   CMAC, and the last 32 bytes the AES-256 key of CTR mode.
+//! - The synthetic IV (SIV) is computed with S2V over `ad` and the plaintext,
+//!   and doubles as the tag and as the initial counter of CTR mode. It is
+//!   prepended to the ciphertext.
+//! - `ad` of `None` passes no associated data to S2V, while `Some(&[])` passes
+//!   one empty string. As in the RFC, the two produce different outputs.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `ad`: Additional data to authenticate (this is not encrypted and can be
+//!   `None`).
+//! - `siv_with_ciphertext`: The 16-byte SIV followed by the encrypted data.
+//! - `plaintext`: The data to be encrypted. This may be empty.
+//! - `dst_out`: Destination array that will hold the
+//!   `siv_with_ciphertext`/`plaintext` after encryption/decryption.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `dst_out` is less than `plaintext + 16` when encrypting.
+//! - The length of `dst_out` is less than `siv_with_ciphertext - 16` when
+//!   decrypting.
+//! - The length of `siv_with_ciphertext` is less than `16`.
+//! - The received SIV does not match the calculated SIV when decrypting.
+//!
+//! # Security:
+//! - Equal plaintexts encrypted with the same key and `ad` give equal
+//!   ciphertexts, so anyone who sees the ciphertexts learns which plaintexts
+//!   are equal and how often each occurs. Only use this where that is
+//!   intended, such as for equality lookups, and use a randomized AEAD
+//!   everywhere else.
+//! - Bind the ciphertext to where it is stored through `ad`, such as the table
+//!   and column name, so that it cannot be moved elsewhere undetected.
+//! - Other than equality, nothing is revealed even when the same input is
+//!   encrypted many times. A nonce can be passed as part of `ad` to make the
+//!   output randomized.
+//! - To securely generate a strong key, use `SecretKey::generate()`.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::aead::aes_siv;
+//!
+//! let secret_key = aes_siv::SecretKey::generate().unwrap();
+//! let ad = b"users.email";
+//! let plaintext = b"alice@example.com";
+//!
+//! let mut dst_out_ct = [0u8; 17 + 16];
+//! let mut dst_out_ct_again = [0u8; 17 + 16];
+//! let mut dst_out_pt = [0u8; 17];
+//! aes_siv::seal(&secret_key, plaintext, Some(ad), &mut dst_out_ct).unwrap();
+//! aes_siv::seal(&secret_key, plaintext, Some(ad), &mut dst_out_ct_again).unwrap();
+//! assert_eq!(dst_out_ct, dst_out_ct_again);
+//!
+//! aes_siv::open(&secret_key, &dst_out_ct, Some(ad), &mut dst_out_pt).unwrap();
+//! assert_eq!(dst_out_pt.as_ref(), plaintext.as_ref());
+//! ```
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		cipher::aes::{Aes, SecretKey256},
+		constants::{AES256_KEYSIZE, AES_BLOCKSIZE, AES_SIV_KEYSIZE, AES_SIV_TAGSIZE},
+		types,
+	},
+	util,
+};
+use zeroize::Zeroize;
+
+/// A type to represent the `SecretKey` that AES-SIV uses.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 64 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type SecretKey = types::SecretKey<types::tag::AesSivKey, AES_SIV_KEYSIZE>;
+
+/// Multiply `block` by x in GF(2^128), without branching on its value.
+fn dbl(block: &mut [u8; AES_BLOCKSIZE]) {
+	let carry = block[0] >> 7;
+	for i in 0..(AES_BLOCKSIZE - 1) {
+		block[i] = (block[i] << 1) | (block[i + 1] >> 7);
+	}
+	block[AES_BLOCKSIZE - 1] = (block[AES_BLOCKSIZE - 1] << 1) ^ (0x87 & 0u8.wrapping_sub(carry));
+}
+
+/// XOR `src` into the start of `dst`.
+fn xor_into(dst: &mut [u8], src: &[u8]) {
+	for (d, s) in dst.iter_mut().zip(src.iter()) {
+		*d ^= s;
+	}
+}
+
+/// CMAC of a message given in one or more parts.
+struct Cmac<'a> {
+	cipher: &'a Aes,
+	state: [u8; AES_BLOCKSIZE],
+	buffer: [u8; AES_BLOCKSIZE],
+	leftover: usize,
+}
+
+impl<'a> Drop for Cmac<'a> {
+	fn drop(&mut self) {
+		self.state.zeroize();
+		self.buffer.zeroize();
+	}
+}
+
+impl<'a> Cmac<'a> {
+	fn new(cipher: &'a Aes) -> Self {
+		Self {
+			cipher,
+			state: [0u8; AES_BLOCKSIZE],
+			buffer: [0u8; AES_BLOCKSIZE],
+			leftover: 0,
+		}
+	}
+
+	/// Process `data`. A full buffer is only processed once more data
+	/// follows, since the last block is processed differently.
+	fn update(&mut self, mut data: &[u8]) -> Result<(), UnknownCryptoError> {
+		while !data.is_empty() {
+			if self.leftover == AES_BLOCKSIZE {
+				xor_into(&mut self.state, &self.buffer);
+				self.cipher.encrypt_block(&mut self.state)?;
+				self.leftover = 0;
+			}
+
+			let take = core::cmp::min(AES_BLOCKSIZE - self.leftover, data.len());
+			self.buffer[self.leftover..(self.leftover + take)].copy_from_slice(&data[..take]);
+			self.leftover += take;
+			data = &data[take..];
+		}
+
+		Ok(())
+	}
+
+	/// Process the last block and return the tag.
+	fn finalize(mut self) -> Result<[u8; AES_BLOCKSIZE], UnknownCryptoError> {
+		let mut subkey = [0u8; AES_BLOCKSIZE];
+		self.cipher.encrypt_block(&mut subkey)?;
+		dbl(&mut subkey);
+
+		if self.leftover == AES_BLOCKSIZE {
+			xor_into(&mut self.buffer, &subkey);
+		} else {
+			dbl(&mut subkey);
+			self.buffer[self.leftover] = 0x80;
+			for byte in self.buffer[(self.leftover + 1)..].iter_mut() {
+				*byte = 0;
+			}
+			xor_into(&mut self.buffer, &subkey);
+		}
+		subkey.zeroize();
+
+		xor_into(&mut self.state, &self.buffer);
+		self.cipher.encrypt_block(&mut self.state)?;
+
+		Ok(self.state)
+	}
+}
+
+/// Return the CMAC of `data`.
+fn cmac(cipher: &Aes, data: &[u8]) -> Result<[u8; AES_BLOCKSIZE], UnknownCryptoError> {
+	let mut state = Cmac::new(cipher);
+	state.update(data)?;
+
+	state.finalize()
+}
+
+/// S2V of `ad`, if any, and `plaintext`.
+fn s2v(
+	cipher: &Aes,
+	ad: Option<&[u8]>,
+	plaintext: &[u8],
+) -> Result<[u8; AES_BLOCKSIZE], UnknownCryptoError> {
+	let mut d = cmac(cipher, &[0u8; AES_BLOCKSIZE])?;
+	if let Some(ad) = ad {
+		dbl(&mut d);
+		xor_into(&mut d, &cmac(cipher, ad)?);
+	}
+
+	let mut state = Cmac::new(cipher);
+	if plaintext.len() >= AES_BLOCKSIZE {
+		let (head, last) = plaintext.split_at(plaintext.len() - AES_BLOCKSIZE);
+		state.update(head)?;
+		xor_into(&mut d, last);
+	} else {
+		dbl(&mut d);
+		xor_into(&mut d, plaintext);
+		d[plaintext.len()] ^= 0x80;
+	}
+	state.update(&d)?;
+	d.zeroize();
+
+	state.finalize()
+}
+
+/// Encrypt or decrypt `src` into `dst_out` with AES-CTR, starting at the
+/// counter given by `siv`.
+fn ctr(
+	cipher: &Aes,
+	siv: &[u8; AES_BLOCKSIZE],
+	src: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	// Two bits of the SIV are cleared, so that implementations with 32 or 64-bit
+	// counters can be used as well.
+	let mut counter = u128::from_be_bytes(*siv);
+	counter &= !((1u128 << 63) | (1u128 << 31));
+
+	let mut keystream = [0u8; AES_BLOCKSIZE];
+	for (src_block, dst_block) in src
+		.chunks(AES_BLOCKSIZE)
+		.zip(dst_out.chunks_mut(AES_BLOCKSIZE))
+	{
+		keystream = counter.to_be_bytes();
+		cipher.encrypt_block(&mut keystream)?;
+		dst_block[..src_block.len()].copy_from_slice(src_block);
+		xor_into(&mut dst_block[..src_block.len()], &keystream);
+		counter = counter.wrapping_add(1);
+	}
+	keystream.zeroize();
+
+	Ok(())
+}
+
+/// Expand the CMAC and CTR halves of `secret_key`.
+fn expand(secret_key: &SecretKey) -> Result<(Aes, Aes), UnknownCryptoError> {
+	let key = secret_key.unprotected_as_bytes();
+
+	Ok((
+		Aes::new(&SecretKey256::from_slice(&key[..AES256_KEYSIZE])?)?,
+		Aes::new(&SecretKey256::from_slice(&key[AES256_KEYSIZE..])?)?,
+	))
+}
+
+#[must_use]
+/// Deterministic AEAD AES-SIV encryption and authentication as specified in the [RFC 5297](https://tools.ietf.org/html/rfc5297).
+pub fn seal(
+	secret_key: &SecretKey,
+	plaintext: &[u8],
+	ad: Option<&[u8]>,
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if dst_out.len() < plaintext.len() + AES_SIV_TAGSIZE {
+		return Err(UnknownCryptoError);
+	}
+
+	let (mac, cipher) = expand(secret_key)?;
+
+	let siv = s2v(&mac, ad, plaintext)?;
+	ctr(
+		&cipher,
+		&siv,
+		plaintext,
+		&mut dst_out[AES_SIV_TAGSIZE..(AES_SIV_TAGSIZE + plaintext.len())],
+	)?;
+	dst_out[..AES_SIV_TAGSIZE].copy_from_slice(&siv);
+
+	Ok(())
+}
+
+#[must_use]
+/// Deterministic AEAD AES-SIV decryption and authentication as specified in the [RFC 5297](https://tools.ietf.org/html/rfc5297).
+pub fn open(
+	secret_key: &SecretKey,
+	siv_with_ciphertext: &[u8],
+	ad: Option<&[u8]>,
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if siv_with_ciphertext.len() < AES_SIV_TAGSIZE {
+		return Err(UnknownCryptoError);
+	}
+	if dst_out.len() < siv_with_ciphertext.len() - AES_SIV_TAGSIZE {
+		return Err(UnknownCryptoError);
+	}
+
+	let (mac, cipher) = expand(secret_key)?;
+
+	let mut siv = [0u8; AES_SIV_TAGSIZE];
+	siv.copy_from_slice(&siv_with_ciphertext[..AES_SIV_TAGSIZE]);
+	let ciphertext = &siv_with_ciphertext[AES_SIV_TAGSIZE..];
+	let plaintext = &mut dst_out[..ciphertext.len()];
+
+	// The SIV is computed over the plaintext, so it can only be checked after
+	// decrypting. The plaintext is zeroed out again if it does not match.
+	ctr(&cipher, &siv, ciphertext, plaintext)?;
+	let expected = s2v(&mac, ad, plaintext)?;
+	if util::secure_cmp(&expected, &siv).is_err() {
+		plaintext.zeroize();
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_seal_open {
+		use super::*;
+
+		#[test]
+		fn test_dst_out_length() {
+			let secret_key = SecretKey::from_slice(&[0u8; 64]).unwrap();
+
+			let mut dst_out_ct = [0u8; 32];
+			assert!(seal(&secret_key, &[0u8; 17], None, &mut dst_out_ct).is_err());
+			assert!(seal(&secret_key, &[0u8; 16], None, &mut dst_out_ct).is_ok());
+
+			let mut dst_out_pt = [0u8; 15];
+			assert!(open(&secret_key, &dst_out_ct, None, &mut dst_out_pt).is_err());
+			let mut dst_out_pt = [0u8; 17];
+			assert!(open(&secret_key, &dst_out_ct, None, &mut dst_out_pt).is_ok());
+			assert!(open(&secret_key, &[0u8; 15], None, &mut dst_out_pt).is_err());
+		}
+
+		#[test]
+		fn test_empty_plaintext() {
+			let secret_key = SecretKey::from_slice(&[0u8; 64]).unwrap();
+
+			let mut dst_out_ct = [0u8; 16];
+			seal(&secret_key, &[], Some(b"ad"), &mut dst_out_ct).unwrap();
+			assert!(open(&secret_key, &dst_out_ct, Some(b"ad"), &mut []).is_ok());
+			assert!(open(&secret_key, &dst_out_ct, Some(b"other"), &mut []).is_err());
+		}
+
+		#[test]
+		fn test_deterministic() {
+			let secret_key = SecretKey::from_slice(&[1u8; 64]).unwrap();
+
+			let mut dst_out_ct_1 = [0u8; 64 + 16];
+			let mut dst_out_ct_2 = [0u8; 64 + 16];
+			seal(&secret_key, &[2u8; 64], Some(b"ad"), &mut dst_out_ct_1).unwrap();
+			seal(&secret_key, &[2u8; 64], Some(b"ad"), &mut dst_out_ct_2).unwrap();
+			assert_eq!(dst_out_ct_1[..], dst_out_ct_2[..]);
+
+			seal(&secret_key, &[2u8; 64], Some(b"other"), &mut dst_out_ct_2).unwrap();
+			assert_ne!(dst_out_ct_1[..], dst_out_ct_2[..]);
+		}
+
+		#[test]
+		fn test_none_ad_differs_from_empty_ad() {
+			let secret_key = SecretKey::from_slice(&[1u8; 64]).unwrap();
+
+			let mut dst_out_ct_1 = [0u8; 32 + 16];
+			let mut dst_out_ct_2 = [0u8; 32 + 16];
+			seal(&secret_key, &[2u8; 32], None, &mut dst_out_ct_1).unwrap();
+			seal(&secret_key, &[2u8; 32], Some(&[]), &mut dst_out_ct_2).unwrap();
+			assert_ne!(dst_out_ct_1[..], dst_out_ct_2[..]);
+
+			let mut dst_out_pt = [0u8; 32];
+			assert!(open(&secret_key, &dst_out_ct_1, Some(&[]), &mut dst_out_pt).is_err());
+			assert!(open(&secret_key, &dst_out_ct_2, None, &mut dst_out_pt).is_err());
+		}
+
+		#[test]
+		fn test_modified_input_is_zeroed() {
+			let secret_key = SecretKey::from_slice(&[1u8; 64]).unwrap();
+
+			let mut dst_out_ct = [0u8; 32 + 16];
+			seal(&secret_key, &[2u8; 32], None, &mut dst_out_ct).unwrap();
+
+			for idx in 0..dst_out_ct.len() {
+				let mut modified = dst_out_ct;
+				modified[idx] ^= 1;
+				let mut dst_out_pt = [0xffu8; 32];
+				assert!(open(&secret_key, &modified, None, &mut dst_out_pt).is_err());
+				assert_eq!(dst_out_pt, [0u8; 32]);
+			}
+
+			let wrong_key = SecretKey::from_slice(&[3u8; 64]).unwrap();
+			let mut dst_out_pt = [0u8; 32];
+			assert!(open(&wrong_key, &dst_out_ct, None, &mut dst_out_pt).is_err());
+		}
+	}
+
+	// Proptests. Only exectued when NOT testing no_std.
+	#[cfg(feature = "safe_api")]
+	mod proptest {
+		use super::*;
+
+		quickcheck! {
+			/// Opening a sealed input should always yield the input.
+			fn prop_seal_open_same_input(input: Vec<u8>, ad: Vec<u8>) -> bool {
+				let secret_key = SecretKey::generate().unwrap();
+
+				let mut dst_out_ct = vec![0u8; input.len() + AES_SIV_TAGSIZE];
+				let mut dst_out_pt = vec![0u8; input.len()];
+				seal(&secret_key, &input, Some(&ad), &mut dst_out_ct).unwrap();
+				open(&secret_key, &dst_out_ct, Some(&ad), &mut dst_out_pt).unwrap();
+
+				dst_out_pt == input
+			}
+		}
+	}
+}
@@ -103,6 +103,7 @@ pub use crate::hazardous::stream::chacha20::{Nonce, SecretKey};
 use crate::{
 	errors::UnknownCryptoError,
 	hazardous::{
+		aead::Aead,
 		constants::{POLY1305_KEYSIZE, POLY1305_OUTSIZE},
 		mac::poly1305::{self, OneTimeKey},
 		stream::chacha20,
@@ -188,6 +189,9 @@ pub fn seal(
 		return Err(UnknownCryptoError);
 	}
 
+	#[cfg(feature = "nonce_reuse_detection")]
+	util::record_nonce_use(&secret_key.unprotected_as_bytes(), &nonce.as_bytes());
+
 	let optional_ad = match ad {
 		Some(ref n_val) => *n_val,
 		None => &[0u8; 0],
@@ -258,6 +262,35 @@ pub fn open(
 	Ok(())
 }
 
+#[derive(Debug, Clone, Copy)]
+/// AEAD ChaCha20Poly1305, for use through the `Aead` trait.
+pub struct ChaCha20Poly1305;
+
+impl Aead for ChaCha20Poly1305 {
+	type SecretKey = SecretKey;
+	type Nonce = Nonce;
+
+	fn seal(
+		secret_key: &SecretKey,
+		nonce: &Nonce,
+		plaintext: &[u8],
+		ad: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		seal(secret_key, nonce, plaintext, ad, dst_out)
+	}
+
+	fn open(
+		secret_key: &SecretKey,
+		nonce: &Nonce,
+		ciphertext_with_tag: &[u8],
+		ad: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		open(secret_key, nonce, ciphertext_with_tag, ad, dst_out)
+	}
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
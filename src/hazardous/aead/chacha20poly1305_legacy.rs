@@ -0,0 +1,460 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! ### **Interoperability only**:
+//! This is the original ChaCha20Poly1305 construction with a 64-bit nonce,
+//! from [draft-agl-tls-chacha20poly1305-04](https://tools.ietf.org/html/draft-agl-tls-chacha20poly1305-04),
+//! which predates RFC 8439. It is provided for interoperability with older
+//! protocols that use it. New designs should use `xchacha20poly1305`.
+//!
+//! # About:
+//! - Uses ChaCha20 with a 64-bit nonce and a 64-bit block counter. The
+//!   Poly1305 key is the start of the keystream block at counter 0, and the
+//!   plaintext is encrypted from counter 1.
+//! - The tag is computed over `ad`, the length of `ad` as a little-endian
+//!   64-bit integer, the ciphertext and the length of the ciphertext as a
+//!   little-endian 64-bit integer, with no padding in between. Both the
+//!   nonce size and this layout differ from RFC 8439, so tags from this module
+//!   and `chacha20poly1305` are not interchangeable.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `nonce`: The nonce value.
+//! - `ad`: Additional data to authenticate (this is not encrypted and can be
+//!   `None`).
+//! - `ciphertext_with_tag`: The encrypted data with the corresponding 16 byte
+//!   Poly1305 tag appended to it.
+//! - `plaintext`: The data to be encrypted.
+//! - `dst_out`: Destination array that will hold the
+//!   `ciphertext_with_tag`/`plaintext` after encryption/decryption.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `dst_out` is less than `plaintext + 16` when encrypting.
+//! - The length of `dst_out` is less than `ciphertext_with_tag - 16` when
+//!   decrypting.
+//! - The length of `ciphertext_with_tag` is not greater than `16`.
+//! - `plaintext` or `ciphertext_with_tag` are empty.
+//! - The received tag does not match the calculated tag when decrypting.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - More than 2^32-1 * 64 bytes of data are processed.
+//!
+//! # Security:
+//! - It is critical for security that a given nonce is not re-used with a given
+//!   key. A 64-bit nonce is too small to be randomly generated, so it should
+//!   be a counter, such as a record sequence number.
+//! - The keystream for a nonce in this module is the same as the keystream of
+//!   `chacha20poly1305` for that nonce prefixed with four zero bytes. A key
+//!   must therefore never be used with both.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::aead::chacha20poly1305_legacy;
+//!
+//! let secret_key = chacha20poly1305_legacy::SecretKey::generate().unwrap();
+//! let nonce = chacha20poly1305_legacy::Nonce::from_slice(&[0u8; 8]).unwrap();
+//! let ad = b"Header";
+//! let plaintext = b"Legacy protocol record";
+//!
+//! let mut dst_out_ct = [0u8; 22 + 16];
+//! let mut dst_out_pt = [0u8; 22];
+//! chacha20poly1305_legacy::seal(&secret_key, &nonce, plaintext, Some(ad), &mut dst_out_ct)
+//! 	.unwrap();
+//! chacha20poly1305_legacy::open(&secret_key, &nonce, &dst_out_ct, Some(ad), &mut dst_out_pt)
+//! 	.unwrap();
+//!
+//! assert_eq!(dst_out_pt.as_ref(), plaintext.as_ref());
+//! ```
+pub use crate::hazardous::stream::chacha20::SecretKey;
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		aead::Aead,
+		constants::{
+			IETF_CHACHA_NONCESIZE, LEGACY_CHACHA_NONCESIZE, POLY1305_KEYSIZE, POLY1305_OUTSIZE,
+		},
+		mac::poly1305::{self, OneTimeKey},
+		stream::chacha20,
+		types,
+	},
+	util,
+};
+
+/// A type that represents a `Nonce` that the legacy ChaCha20Poly1305 uses.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 8 bytes.
+pub type Nonce = types::PublicData<types::tag::LegacyChaCha20Nonce, LEGACY_CHACHA_NONCESIZE>;
+
+#[must_use]
+/// Return the IETF ChaCha20 nonce that gives the same keystream as `nonce`
+/// with a 64-bit counter, as long as the counter fits in 32 bits.
+fn ietf_nonce(nonce: &Nonce) -> Result<chacha20::Nonce, UnknownCryptoError> {
+	let mut ietf_nonce = [0u8; IETF_CHACHA_NONCESIZE];
+	ietf_nonce[(IETF_CHACHA_NONCESIZE - LEGACY_CHACHA_NONCESIZE)..]
+		.copy_from_slice(&nonce.as_bytes());
+
+	chacha20::Nonce::from_slice(&ietf_nonce)
+}
+
+#[must_use]
+/// Compute the tag of `ad` and `ciphertext`.
+fn authenticate(
+	secret_key: &SecretKey,
+	nonce: &chacha20::Nonce,
+	ad: &[u8],
+	ciphertext: &[u8],
+) -> Result<poly1305::Tag, UnknownCryptoError> {
+	let one_time_key = OneTimeKey::from_slice(
+		&chacha20::keystream_block(secret_key, nonce, 0)?[..POLY1305_KEYSIZE],
+	)?;
+
+	let mut poly1305_state = poly1305::init(&one_time_key);
+	poly1305_state.update(ad)?;
+	poly1305_state.update(&(ad.len() as u64).to_le_bytes())?;
+	poly1305_state.update(ciphertext)?;
+	poly1305_state.update(&(ciphertext.len() as u64).to_le_bytes())?;
+
+	Ok(poly1305_state.finalize()?)
+}
+
+#[must_use]
+/// AEAD ChaCha20Poly1305 encryption and authentication with a 64-bit nonce,
+/// as specified in [draft-agl-tls-chacha20poly1305-04](https://tools.ietf.org/html/draft-agl-tls-chacha20poly1305-04).
+pub fn seal(
+	secret_key: &SecretKey,
+	nonce: &Nonce,
+	plaintext: &[u8],
+	ad: Option<&[u8]>,
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if dst_out.len() < plaintext.len() + POLY1305_OUTSIZE {
+		return Err(UnknownCryptoError);
+	}
+	if plaintext.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let ietf_nonce = ietf_nonce(nonce)?;
+
+	#[cfg(feature = "nonce_reuse_detection")]
+	util::record_nonce_use(&secret_key.unprotected_as_bytes(), &ietf_nonce.as_bytes());
+
+	chacha20::encrypt(
+		secret_key,
+		&ietf_nonce,
+		1,
+		plaintext,
+		&mut dst_out[..plaintext.len()],
+	)?;
+	let tag = authenticate(
+		secret_key,
+		&ietf_nonce,
+		ad.unwrap_or(&[]),
+		&dst_out[..plaintext.len()],
+	)?;
+	dst_out[plaintext.len()..(plaintext.len() + POLY1305_OUTSIZE)]
+		.copy_from_slice(&tag.unprotected_as_bytes());
+
+	Ok(())
+}
+
+#[must_use]
+/// AEAD ChaCha20Poly1305 decryption and authentication with a 64-bit nonce,
+/// as specified in [draft-agl-tls-chacha20poly1305-04](https://tools.ietf.org/html/draft-agl-tls-chacha20poly1305-04).
+pub fn open(
+	secret_key: &SecretKey,
+	nonce: &Nonce,
+	ciphertext_with_tag: &[u8],
+	ad: Option<&[u8]>,
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if ciphertext_with_tag.len() <= POLY1305_OUTSIZE {
+		return Err(UnknownCryptoError);
+	}
+	if dst_out.len() < ciphertext_with_tag.len() - POLY1305_OUTSIZE {
+		return Err(UnknownCryptoError);
+	}
+
+	let ciphertext_len = ciphertext_with_tag.len() - POLY1305_OUTSIZE;
+	let ietf_nonce = ietf_nonce(nonce)?;
+
+	let tag = authenticate(
+		secret_key,
+		&ietf_nonce,
+		ad.unwrap_or(&[]),
+		&ciphertext_with_tag[..ciphertext_len],
+	)?;
+	util::secure_cmp(
+		&tag.unprotected_as_bytes(),
+		&ciphertext_with_tag[ciphertext_len..],
+	)?;
+
+	chacha20::decrypt(
+		secret_key,
+		&ietf_nonce,
+		1,
+		&ciphertext_with_tag[..ciphertext_len],
+		dst_out,
+	)?;
+
+	Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Legacy AEAD ChaCha20Poly1305 with a 64-bit nonce, for use through the
+/// `Aead` trait.
+pub struct ChaCha20Poly1305Legacy;
+
+impl Aead for ChaCha20Poly1305Legacy {
+	type SecretKey = SecretKey;
+	type Nonce = Nonce;
+
+	fn seal(
+		secret_key: &SecretKey,
+		nonce: &Nonce,
+		plaintext: &[u8],
+		ad: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		seal(secret_key, nonce, plaintext, ad, dst_out)
+	}
+
+	fn open(
+		secret_key: &SecretKey,
+		nonce: &Nonce,
+		ciphertext_with_tag: &[u8],
+		ad: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		open(secret_key, nonce, ciphertext_with_tag, ad, dst_out)
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	// From draft-agl-tls-chacha20poly1305-04, section 7.
+	const KEY: [u8; 32] = [
+		0x42, 0x90, 0xbc, 0xb1, 0x54, 0x17, 0x35, 0x31, 0xf3, 0x14, 0xaf, 0x57, 0xf3, 0xbe, 0x3b,
+		0x50, 0x06, 0xda, 0x37, 0x1e, 0xce, 0x27, 0x2a, 0xfa, 0x1b, 0x5d, 0xbd, 0xd1, 0x10, 0x0a,
+		0x10, 0x07,
+	];
+	const NONCE: [u8; 8] = [0xcd, 0x7c, 0xf6, 0x7b, 0xe3, 0x9c, 0x79, 0x4a];
+	const AD: [u8; 10] = [0x87, 0xe2, 0x29, 0xd4, 0x50, 0x08, 0x45, 0xa0, 0x79, 0xc0];
+	const PLAINTEXT: [u8; 10] = [0x86, 0xd0, 0x99, 0x74, 0x84, 0x0b, 0xde, 0xd2, 0xa5, 0xca];
+	const CIPHERTEXT_WITH_TAG: [u8; 26] = [
+		0xe3, 0xe4, 0x46, 0xf7, 0xed, 0xe9, 0xa1, 0x9b, 0x62, 0xa4, 0x67, 0x7d, 0xab, 0xf4, 0xe3,
+		0xd2, 0x4b, 0x87, 0x6b, 0xb2, 0x84, 0x75, 0x38, 0x96, 0xe1, 0xd6,
+	];
+
+	fn secret_key() -> SecretKey { SecretKey::from_slice(&KEY).unwrap() }
+
+	fn nonce() -> Nonce { Nonce::from_slice(&NONCE).unwrap() }
+
+	mod test_seal {
+		use super::*;
+
+		#[test]
+		fn test_draft_vector() {
+			let mut dst_out = [0u8; 26];
+			seal(&secret_key(), &nonce(), &PLAINTEXT, Some(&AD), &mut dst_out).unwrap();
+			assert_eq!(dst_out, CIPHERTEXT_WITH_TAG);
+		}
+
+		#[test]
+		fn test_dst_out_length() {
+			let mut dst_out_less = [0u8; 25];
+			let mut dst_out_more = [0u8; 27];
+			assert!(seal(
+				&secret_key(),
+				&nonce(),
+				&PLAINTEXT,
+				Some(&AD),
+				&mut dst_out_less
+			)
+			.is_err());
+			assert!(seal(
+				&secret_key(),
+				&nonce(),
+				&PLAINTEXT,
+				Some(&AD),
+				&mut dst_out_more
+			)
+			.is_ok());
+			assert_eq!(dst_out_more[..26], CIPHERTEXT_WITH_TAG);
+		}
+
+		#[test]
+		fn test_empty_plaintext_err() {
+			let mut dst_out = [0u8; 16];
+			assert!(seal(&secret_key(), &nonce(), &[], None, &mut dst_out).is_err());
+		}
+
+		#[test]
+		fn test_differs_from_rfc8439() {
+			use crate::hazardous::aead::chacha20poly1305;
+
+			let mut ietf_nonce = [0u8; 12];
+			ietf_nonce[4..].copy_from_slice(&NONCE);
+			let mut dst_out_legacy = [0u8; 26];
+			let mut dst_out_ietf = [0u8; 26];
+			seal(
+				&secret_key(),
+				&nonce(),
+				&PLAINTEXT,
+				Some(&AD),
+				&mut dst_out_legacy,
+			)
+			.unwrap();
+			chacha20poly1305::seal(
+				&secret_key(),
+				&chacha20poly1305::Nonce::from_slice(&ietf_nonce).unwrap(),
+				&PLAINTEXT,
+				Some(&AD),
+				&mut dst_out_ietf,
+			)
+			.unwrap();
+
+			// Same keystream, different tag.
+			assert_eq!(dst_out_legacy[..10], dst_out_ietf[..10]);
+			assert_ne!(dst_out_legacy[10..], dst_out_ietf[10..]);
+		}
+	}
+
+	mod test_open {
+		use super::*;
+
+		#[test]
+		fn test_draft_vector() {
+			let mut dst_out = [0u8; 10];
+			open(
+				&secret_key(),
+				&nonce(),
+				&CIPHERTEXT_WITH_TAG,
+				Some(&AD),
+				&mut dst_out,
+			)
+			.unwrap();
+			assert_eq!(dst_out, PLAINTEXT);
+		}
+
+		#[test]
+		fn test_modified_ad_err() {
+			let mut dst_out = [0u8; 10];
+			assert!(open(
+				&secret_key(),
+				&nonce(),
+				&CIPHERTEXT_WITH_TAG,
+				None,
+				&mut dst_out
+			)
+			.is_err());
+			assert!(open(
+				&secret_key(),
+				&nonce(),
+				&CIPHERTEXT_WITH_TAG,
+				Some(&AD[..9]),
+				&mut dst_out
+			)
+			.is_err());
+		}
+
+		#[test]
+		fn test_modified_ciphertext_or_tag_err() {
+			for idx in 0..CIPHERTEXT_WITH_TAG.len() {
+				let mut modified = CIPHERTEXT_WITH_TAG;
+				modified[idx] ^= 1;
+				let mut dst_out = [0u8; 10];
+				assert!(open(&secret_key(), &nonce(), &modified, Some(&AD), &mut dst_out).is_err());
+				// Nothing is decrypted when the tag does not match.
+				assert_eq!(dst_out, [0u8; 10]);
+			}
+		}
+
+		#[test]
+		fn test_bad_lengths_err() {
+			let mut dst_out = [0u8; 10];
+			let mut dst_out_less = [0u8; 9];
+			assert!(open(
+				&secret_key(),
+				&nonce(),
+				&CIPHERTEXT_WITH_TAG[..16],
+				None,
+				&mut dst_out
+			)
+			.is_err());
+			assert!(open(&secret_key(), &nonce(), &[], None, &mut dst_out).is_err());
+			assert!(open(
+				&secret_key(),
+				&nonce(),
+				&CIPHERTEXT_WITH_TAG,
+				Some(&AD),
+				&mut dst_out_less
+			)
+			.is_err());
+		}
+
+		#[test]
+		fn test_aead_trait() {
+			let mut dst_out = [0u8; 10];
+			<ChaCha20Poly1305Legacy as Aead>::open(
+				&secret_key(),
+				&nonce(),
+				&CIPHERTEXT_WITH_TAG,
+				Some(&AD),
+				&mut dst_out,
+			)
+			.unwrap();
+			assert_eq!(dst_out, PLAINTEXT);
+		}
+	}
+
+	// Proptests. Only exectued when NOT testing no_std.
+	#[cfg(feature = "safe_api")]
+	mod proptest {
+		use super::*;
+
+		quickcheck! {
+			// Sealing input, and then opening should always yield the same input.
+			fn prop_seal_open_same_input(input: Vec<u8>, ad: Vec<u8>) -> bool {
+				let pt = if input.is_empty() {
+					vec![1u8; 10]
+				} else {
+					input
+				};
+
+				let mut dst_out_ct = vec![0u8; pt.len() + POLY1305_OUTSIZE];
+				let mut dst_out_pt = vec![0u8; pt.len()];
+				seal(&secret_key(), &nonce(), &pt, Some(&ad), &mut dst_out_ct).unwrap();
+				open(&secret_key(), &nonce(), &dst_out_ct, Some(&ad), &mut dst_out_pt).unwrap();
+
+				dst_out_pt == pt
+			}
+		}
+	}
+}
@@ -0,0 +1,440 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! ### **Interoperability only**:
+//! This is the `chacha20-poly1305@openssh.com` cipher of the SSH transport
+//! protocol, as specified in [PROTOCOL.chacha20poly1305](https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL.chacha20poly1305?annotate=HEAD).
+//! It is provided so that SSH implementations can be built on orion, and
+//! should not be used outside of SSH.
+//!
+//! # About:
+//! - The 64 byte `secret_key` is split into two ChaCha20 keys. The first 32
+//!   bytes are the main key `K_2` and the last 32 bytes are the header key
+//!   `K_1`.
+//! - The nonce is the packet sequence number as a big-endian 64-bit integer,
+//!   used with the original 64-bit nonce ChaCha20.
+//! - The 4 byte packet length is encrypted on its own with `K_1`, so that a
+//!   receiver can learn the length of a packet before the rest of it has
+//!   arrived. The rest of the packet is encrypted with `K_2`, starting at
+//!   block counter 1.
+//! - The Poly1305 key is the start of the `K_2` keystream block at counter 0,
+//!   and the tag is computed over the encrypted length followed by the
+//!   encrypted rest of the packet.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `sequence_number`: The SSH packet sequence number.
+//! - `packet`: The packet to be encrypted, starting with the 4 byte packet
+//!   length.
+//! - `packet_with_tag`: The encrypted packet with the corresponding 16 byte
+//!   Poly1305 tag appended to it.
+//! - `encrypted_length`: The first 4 bytes of an encrypted packet.
+//! - `dst_out`: Destination array that will hold the encrypted/decrypted
+//!   packet after encryption/decryption.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `dst_out` is less than `packet + 16` when encrypting.
+//! - The length of `dst_out` is less than `packet_with_tag - 16` when
+//!   decrypting.
+//! - `packet` is not longer than `4` bytes.
+//! - `packet_with_tag` is not longer than `4 + 16` bytes.
+//! - `encrypted_length` is not `4` bytes.
+//! - The received tag does not match the calculated tag when decrypting.
+//!
+//! # Panics:
+//! A panic will occur if:
+//! - More than 2^32-1 * 64 bytes of data are processed.
+//!
+//! # Security:
+//! - A sequence number must never be used twice with the same key. SSH
+//!   requires rekeying before the 32-bit sequence number wraps around.
+//! - The length returned by [`decrypt_length()`] has not been authenticated.
+//!   It may only be used to decide how many bytes to read before calling
+//!   [`open()`], which verifies it along with the rest of the packet.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::aead::chacha20poly1305_openssh;
+//!
+//! let secret_key = chacha20poly1305_openssh::SecretKey::generate().unwrap();
+//! let sequence_number = 3;
+//! // Packet length, padding length, payload and padding.
+//! let packet = [0, 0, 0, 12, 4, 21, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0];
+//!
+//! let mut encrypted = [0u8; 16 + 16];
+//! chacha20poly1305_openssh::seal(&secret_key, sequence_number, &packet, &mut encrypted)
+//! 	.unwrap();
+//!
+//! // The receiver first reads 4 bytes to learn how much more to read.
+//! let length =
+//! 	chacha20poly1305_openssh::decrypt_length(&secret_key, sequence_number, &encrypted[..4])
+//! 		.unwrap();
+//! assert_eq!(length, 12);
+//!
+//! let mut decrypted = [0u8; 16];
+//! chacha20poly1305_openssh::open(&secret_key, sequence_number, &encrypted, &mut decrypted)
+//! 	.unwrap();
+//! assert_eq!(decrypted, packet);
+//! ```
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		constants::{
+			CHACHA_KEYSIZE, IETF_CHACHA_NONCESIZE, OPENSSH_CHACHA_KEYSIZE, POLY1305_KEYSIZE,
+			POLY1305_OUTSIZE,
+		},
+		mac::poly1305::{self, OneTimeKey},
+		stream::chacha20,
+		types,
+	},
+	util,
+};
+
+/// A type to represent the `SecretKey` that `chacha20-poly1305@openssh.com`
+/// uses.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 64 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type SecretKey = types::SecretKey<types::tag::OpenSshChaChaKey, OPENSSH_CHACHA_KEYSIZE>;
+
+/// The size of the packet length field at the start of each packet.
+pub const PACKET_LENGTH_SIZE: usize = 4;
+
+#[must_use]
+/// Split `secret_key` into the main key `K_2` and the header key `K_1`.
+fn split_key(
+	secret_key: &SecretKey,
+) -> Result<(chacha20::SecretKey, chacha20::SecretKey), UnknownCryptoError> {
+	let key = secret_key.unprotected_as_bytes();
+
+	Ok((
+		chacha20::SecretKey::from_slice(&key[..CHACHA_KEYSIZE])?,
+		chacha20::SecretKey::from_slice(&key[CHACHA_KEYSIZE..])?,
+	))
+}
+
+#[must_use]
+/// Return the IETF ChaCha20 nonce for `sequence_number`, which gives the same
+/// keystream as the 64-bit nonce ChaCha20 that OpenSSH uses.
+fn nonce(sequence_number: u32) -> Result<chacha20::Nonce, UnknownCryptoError> {
+	let mut nonce = [0u8; IETF_CHACHA_NONCESIZE];
+	nonce[4..].copy_from_slice(&u64::from(sequence_number).to_be_bytes());
+
+	chacha20::Nonce::from_slice(&nonce)
+}
+
+#[must_use]
+/// Compute the tag of an encrypted packet.
+fn authenticate(
+	main_key: &chacha20::SecretKey,
+	nonce: &chacha20::Nonce,
+	encrypted_packet: &[u8],
+) -> Result<poly1305::Tag, UnknownCryptoError> {
+	let one_time_key = OneTimeKey::from_slice(
+		&chacha20::keystream_block(main_key, nonce, 0)?[..POLY1305_KEYSIZE],
+	)?;
+
+	poly1305::poly1305(&one_time_key, encrypted_packet)
+}
+
+#[must_use]
+/// Encrypt and authenticate an SSH packet with `chacha20-poly1305@openssh.com`.
+pub fn seal(
+	secret_key: &SecretKey,
+	sequence_number: u32,
+	packet: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if packet.len() <= PACKET_LENGTH_SIZE {
+		return Err(UnknownCryptoError);
+	}
+	if dst_out.len() < packet.len() + POLY1305_OUTSIZE {
+		return Err(UnknownCryptoError);
+	}
+
+	let (main_key, header_key) = split_key(secret_key)?;
+	let nonce = nonce(sequence_number)?;
+
+	#[cfg(feature = "nonce_reuse_detection")]
+	util::record_nonce_use(&secret_key.unprotected_as_bytes(), &nonce.as_bytes());
+
+	chacha20::encrypt(
+		&header_key,
+		&nonce,
+		0,
+		&packet[..PACKET_LENGTH_SIZE],
+		&mut dst_out[..PACKET_LENGTH_SIZE],
+	)?;
+	chacha20::encrypt(
+		&main_key,
+		&nonce,
+		1,
+		&packet[PACKET_LENGTH_SIZE..],
+		&mut dst_out[PACKET_LENGTH_SIZE..packet.len()],
+	)?;
+	let tag = authenticate(&main_key, &nonce, &dst_out[..packet.len()])?;
+	dst_out[packet.len()..(packet.len() + POLY1305_OUTSIZE)]
+		.copy_from_slice(&tag.unprotected_as_bytes());
+
+	Ok(())
+}
+
+#[must_use]
+/// Decrypt the packet length from the first 4 bytes of an encrypted packet,
+/// without authenticating it.
+pub fn decrypt_length(
+	secret_key: &SecretKey,
+	sequence_number: u32,
+	encrypted_length: &[u8],
+) -> Result<u32, UnknownCryptoError> {
+	if encrypted_length.len() != PACKET_LENGTH_SIZE {
+		return Err(UnknownCryptoError);
+	}
+
+	let (_, header_key) = split_key(secret_key)?;
+	let mut length = [0u8; PACKET_LENGTH_SIZE];
+	chacha20::decrypt(
+		&header_key,
+		&nonce(sequence_number)?,
+		0,
+		encrypted_length,
+		&mut length,
+	)?;
+
+	Ok(u32::from_be_bytes(length))
+}
+
+#[must_use]
+/// Authenticate and decrypt an SSH packet with `chacha20-poly1305@openssh.com`.
+pub fn open(
+	secret_key: &SecretKey,
+	sequence_number: u32,
+	packet_with_tag: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if packet_with_tag.len() <= PACKET_LENGTH_SIZE + POLY1305_OUTSIZE {
+		return Err(UnknownCryptoError);
+	}
+	if dst_out.len() < packet_with_tag.len() - POLY1305_OUTSIZE {
+		return Err(UnknownCryptoError);
+	}
+
+	let packet_len = packet_with_tag.len() - POLY1305_OUTSIZE;
+	let (main_key, header_key) = split_key(secret_key)?;
+	let nonce = nonce(sequence_number)?;
+
+	let tag = authenticate(&main_key, &nonce, &packet_with_tag[..packet_len])?;
+	util::secure_cmp(&tag.unprotected_as_bytes(), &packet_with_tag[packet_len..])?;
+
+	chacha20::decrypt(
+		&header_key,
+		&nonce,
+		0,
+		&packet_with_tag[..PACKET_LENGTH_SIZE],
+		&mut dst_out[..PACKET_LENGTH_SIZE],
+	)?;
+	chacha20::decrypt(
+		&main_key,
+		&nonce,
+		1,
+		&packet_with_tag[PACKET_LENGTH_SIZE..packet_len],
+		&mut dst_out[PACKET_LENGTH_SIZE..packet_len],
+	)?;
+
+	Ok(())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use crate::encoding::hex_decode_into;
+
+	// Computed with an independent implementation of
+	// PROTOCOL.chacha20poly1305, with the key 0x00..0x3f and sequence number 7.
+	const PACKET: &str = "0000001c0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c";
+	const PACKET_WITH_TAG: &str = "a39afcb6294416474b852d566567b0fcda81dc3c25a023226bf52a9b597000c3e53320e2ee3a4d7f7c76231327862b1c";
+
+	fn secret_key() -> SecretKey {
+		let mut key = [0u8; 64];
+		for (idx, byte) in key.iter_mut().enumerate() {
+			*byte = idx as u8;
+		}
+
+		SecretKey::from_slice(&key).unwrap()
+	}
+
+	fn packet() -> [u8; 32] {
+		let mut packet = [0u8; 32];
+		hex_decode_into(PACKET, &mut packet).unwrap();
+		packet
+	}
+
+	fn packet_with_tag() -> [u8; 48] {
+		let mut packet_with_tag = [0u8; 48];
+		hex_decode_into(PACKET_WITH_TAG, &mut packet_with_tag).unwrap();
+		packet_with_tag
+	}
+
+	mod test_seal {
+		use super::*;
+
+		#[test]
+		fn test_vector() {
+			let mut dst_out = [0u8; 48];
+			seal(&secret_key(), 7, &packet(), &mut dst_out).unwrap();
+			assert_eq!(dst_out, packet_with_tag());
+		}
+
+		#[test]
+		fn test_vector_multiple_blocks() {
+			// Key 0xff..0xc0, sequence number 2^32-1 and a 140 byte payload.
+			let mut key = [0u8; 64];
+			for (idx, byte) in key.iter_mut().enumerate() {
+				*byte = 255 - idx as u8;
+			}
+			let mut packet = [0u8; 144];
+			packet[..4].copy_from_slice(&140u32.to_be_bytes());
+			for (idx, byte) in packet[4..].iter_mut().enumerate() {
+				*byte = (idx * 3) as u8;
+			}
+			let mut expected = [0u8; 160];
+			hex_decode_into(
+				"8e769000867655bb9fb480a2143f957b5783e21973401ff6c8e3dec30edf3bd0\
+				 b2049b550530d843348f76688cade68918af102715e5e3c02861636e445cacb1\
+				 01e8339c438ff4ad83db6eca37b21fe7f63ea8a276ddf1febd0b7f5739d7b9d3\
+				 9387e8842ced8edb96a8aaabb4a009cd48ce1b7b8b3d8b1a129d12233640997\
+				 383fbf08b030fed360666d0788cf93b5aa36e1ecb1569cbbb775d82c416ad7690",
+				&mut expected,
+			)
+			.unwrap();
+
+			let mut dst_out = [0u8; 160];
+			seal(
+				&SecretKey::from_slice(&key).unwrap(),
+				u32::MAX,
+				&packet,
+				&mut dst_out,
+			)
+			.unwrap();
+			assert_eq!(dst_out[..], expected[..]);
+		}
+
+		#[test]
+		fn test_bad_lengths_err() {
+			let mut dst_out = [0u8; 48];
+			let mut dst_out_less = [0u8; 47];
+			assert!(seal(&secret_key(), 7, &packet(), &mut dst_out_less).is_err());
+			assert!(seal(&secret_key(), 7, &packet()[..4], &mut dst_out).is_err());
+			assert!(seal(&secret_key(), 7, &[], &mut dst_out).is_err());
+			assert!(seal(&secret_key(), 7, &packet()[..5], &mut dst_out).is_ok());
+		}
+	}
+
+	mod test_decrypt_length {
+		use super::*;
+
+		#[test]
+		fn test_vector() {
+			assert_eq!(
+				decrypt_length(&secret_key(), 7, &packet_with_tag()[..4]).unwrap(),
+				28
+			);
+			assert_ne!(
+				decrypt_length(&secret_key(), 8, &packet_with_tag()[..4]).unwrap(),
+				28
+			);
+		}
+
+		#[test]
+		fn test_bad_length_err() {
+			assert!(decrypt_length(&secret_key(), 7, &packet_with_tag()[..3]).is_err());
+			assert!(decrypt_length(&secret_key(), 7, &packet_with_tag()[..5]).is_err());
+		}
+	}
+
+	mod test_open {
+		use super::*;
+
+		#[test]
+		fn test_vector() {
+			let mut dst_out = [0u8; 32];
+			open(&secret_key(), 7, &packet_with_tag(), &mut dst_out).unwrap();
+			assert_eq!(dst_out, packet());
+		}
+
+		#[test]
+		fn test_wrong_sequence_number_err() {
+			let mut dst_out = [0u8; 32];
+			assert!(open(&secret_key(), 6, &packet_with_tag(), &mut dst_out).is_err());
+		}
+
+		#[test]
+		fn test_modified_packet_or_tag_err() {
+			for idx in 0..48 {
+				let mut modified = packet_with_tag();
+				modified[idx] ^= 1;
+				let mut dst_out = [0u8; 32];
+				assert!(open(&secret_key(), 7, &modified, &mut dst_out).is_err());
+				// Nothing is decrypted when the tag does not match.
+				assert_eq!(dst_out, [0u8; 32]);
+			}
+		}
+
+		#[test]
+		fn test_bad_lengths_err() {
+			let mut dst_out = [0u8; 32];
+			let mut dst_out_less = [0u8; 31];
+			assert!(open(&secret_key(), 7, &packet_with_tag(), &mut dst_out_less).is_err());
+			assert!(open(&secret_key(), 7, &packet_with_tag()[..20], &mut dst_out).is_err());
+			assert!(open(&secret_key(), 7, &[], &mut dst_out).is_err());
+		}
+	}
+
+	// Proptests. Only exectued when NOT testing no_std.
+	#[cfg(feature = "safe_api")]
+	mod proptest {
+		use super::*;
+
+		quickcheck! {
+			// Sealing a packet, and then opening should always yield the same
+			// packet and the length should be decrypted on its own.
+			fn prop_seal_open_same_input(input: Vec<u8>, sequence_number: u32) -> bool {
+				let mut packet = (input.len() as u32 + 1).to_be_bytes().to_vec();
+				packet.push(0);
+				packet.extend_from_slice(&input);
+
+				let mut dst_out_ct = vec![0u8; packet.len() + POLY1305_OUTSIZE];
+				let mut dst_out_pt = vec![0u8; packet.len()];
+				seal(&secret_key(), sequence_number, &packet, &mut dst_out_ct).unwrap();
+				let length = decrypt_length(&secret_key(), sequence_number, &dst_out_ct[..4])
+					.unwrap();
+				open(&secret_key(), sequence_number, &dst_out_ct, &mut dst_out_pt).unwrap();
+
+				dst_out_pt == packet && length as usize == input.len() + 1
+			}
+		}
+	}
+}
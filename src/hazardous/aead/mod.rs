@@ -25,3 +25,117 @@ pub mod chacha20poly1305;
 
 /// AEAD XChaCha20Poly1305 as specified in the [draft RFC](https://github.com/bikeshedders/xchacha-rfc).
 pub mod xchacha20poly1305;
+
+/// Deterministic AEAD AES-SIV as specified in the [RFC 5297](https://tools.ietf.org/html/rfc5297).
+pub mod aes_siv;
+
+/// AEAD ChaCha20Poly1305 with a 64-bit nonce, as specified in [draft-agl-tls-chacha20poly1305-04](https://tools.ietf.org/html/draft-agl-tls-chacha20poly1305-04).
+/// For interoperability only.
+pub mod chacha20poly1305_legacy;
+
+/// The `chacha20-poly1305@openssh.com` cipher of the SSH transport protocol,
+/// as specified in [PROTOCOL.chacha20poly1305](https://cvsweb.openbsd.org/src/usr.bin/ssh/PROTOCOL.chacha20poly1305?annotate=HEAD).
+/// For interoperability only.
+pub mod chacha20poly1305_openssh;
+
+use crate::errors::UnknownCryptoError;
+
+/// Authenticated Encryption with Associated Data.
+///
+/// This is implemented by `chacha20poly1305::ChaCha20Poly1305`,
+/// `xchacha20poly1305::XChaCha20Poly1305` and
+/// `chacha20poly1305_legacy::ChaCha20Poly1305Legacy`, so that code can be
+/// written generically over which AEAD is used. The functions behave exactly
+/// like the `seal()` and `open()` functions of the respective modules.
+pub trait Aead {
+	/// The secret key used for encryption and authentication.
+	type SecretKey;
+	/// The nonce used for encryption and authentication.
+	type Nonce;
+
+	#[must_use]
+	/// Encrypt and authenticate `plaintext` and `ad`, placing the ciphertext
+	/// with the tag appended in `dst_out`.
+	fn seal(
+		secret_key: &Self::SecretKey,
+		nonce: &Self::Nonce,
+		plaintext: &[u8],
+		ad: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError>;
+
+	#[must_use]
+	/// Verify the tag of `ciphertext_with_tag` and `ad`, and if it is valid,
+	/// place the decrypted plaintext in `dst_out`.
+	fn open(
+		secret_key: &Self::SecretKey,
+		nonce: &Self::Nonce,
+		ciphertext_with_tag: &[u8],
+		ad: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError>;
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	fn roundtrip_generic<A: Aead>(secret_key: &A::SecretKey, nonce: &A::Nonce) -> bool {
+		let plaintext = b"Secret data";
+		let mut dst_out_ct = [0u8; 11 + 16];
+		let mut dst_out_pt = [0u8; 11];
+
+		A::seal(secret_key, nonce, plaintext, Some(b"Additional"), &mut dst_out_ct).unwrap();
+		if A::open(secret_key, nonce, &dst_out_ct, None, &mut dst_out_pt).is_ok() {
+			return false;
+		}
+		A::open(secret_key, nonce, &dst_out_ct, Some(b"Additional"), &mut dst_out_pt).unwrap();
+
+		dst_out_pt.as_ref() == plaintext.as_ref()
+	}
+
+	mod test_seal_open {
+		use super::*;
+
+		#[test]
+		fn test_roundtrip_chacha20poly1305() {
+			let secret_key = chacha20poly1305::SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let nonce = chacha20poly1305::Nonce::from_slice(&[0u8; 12]).unwrap();
+			assert!(roundtrip_generic::<chacha20poly1305::ChaCha20Poly1305>(
+				&secret_key,
+				&nonce
+			));
+		}
+
+		#[test]
+		fn test_roundtrip_xchacha20poly1305() {
+			let secret_key = xchacha20poly1305::SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let nonce = xchacha20poly1305::Nonce::from_slice(&[0u8; 24]).unwrap();
+			assert!(roundtrip_generic::<xchacha20poly1305::XChaCha20Poly1305>(
+				&secret_key,
+				&nonce
+			));
+		}
+
+		#[test]
+		fn test_same_as_module_functions() {
+			let secret_key = chacha20poly1305::SecretKey::from_slice(&[1u8; 32]).unwrap();
+			let nonce = xchacha20poly1305::Nonce::from_slice(&[2u8; 24]).unwrap();
+			let mut dst_trait = [0u8; 32];
+			let mut dst_fn = [0u8; 32];
+
+			<xchacha20poly1305::XChaCha20Poly1305 as Aead>::seal(
+				&secret_key,
+				&nonce,
+				&[0u8; 16],
+				None,
+				&mut dst_trait,
+			)
+			.unwrap();
+			xchacha20poly1305::seal(&secret_key, &nonce, &[0u8; 16], None, &mut dst_fn).unwrap();
+
+			assert_eq!(dst_trait.as_ref(), dst_fn.as_ref());
+		}
+	}
+}
@@ -94,7 +94,7 @@ pub use crate::hazardous::stream::{chacha20::SecretKey, xchacha20::Nonce};
 use crate::{
 	errors::UnknownCryptoError,
 	hazardous::{
-		aead::chacha20poly1305,
+		aead::{chacha20poly1305, Aead},
 		constants::IETF_CHACHA_NONCESIZE,
 		stream::chacha20::{self, Nonce as IETFNonce},
 	},
@@ -156,6 +156,35 @@ pub fn open(
 // now I'm keeping them as they don't seem to bring any disadvantages.
 //
 
+#[derive(Debug, Clone, Copy)]
+/// AEAD XChaCha20Poly1305, for use through the `Aead` trait.
+pub struct XChaCha20Poly1305;
+
+impl Aead for XChaCha20Poly1305 {
+	type SecretKey = SecretKey;
+	type Nonce = Nonce;
+
+	fn seal(
+		secret_key: &SecretKey,
+		nonce: &Nonce,
+		plaintext: &[u8],
+		ad: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		seal(secret_key, nonce, plaintext, ad, dst_out)
+	}
+
+	fn open(
+		secret_key: &SecretKey,
+		nonce: &Nonce,
+		ciphertext_with_tag: &[u8],
+		ad: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		open(secret_key, nonce, ciphertext_with_tag, ad, dst_out)
+	}
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
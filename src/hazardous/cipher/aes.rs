@@ -0,0 +1,611 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `secret_key`: The secret key, of 16, 24 or 32 bytes for AES-128, AES-192
+//!   or AES-256.
+//! - `block`: The block to encrypt or decrypt in place.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `slice` when calling `SecretKey::from_slice()` is not the size of the
+//!   `SecretKey` type.
+//! - The `OsRng` fails to initialize or read from its source when calling
+//!   `SecretKey::generate()`.
+//! - The `SecretKey` passed to `Aes::new()` is not 16, 24 or 32 bytes.
+//! - The length of `block` is not `AES_BLOCKSIZE`.
+//!
+//! # Security:
+//! - This is the raw block cipher, meant for building modes that orion does
+//!   not provide, such as format-preserving encryption. Encrypting data one
+//!   block at a time with the same key (ECB mode) reveals which blocks are
+//!   equal. For encryption of data, use an AEAD instead. See orions `aead`
+//!   module for this.
+//! - The S-box is computed with a bitsliced circuit instead of a table lookup,
+//!   so no memory access depends on the key or the data.
+//! - To securely generate a strong key, use `SecretKey256::generate()`.
+//!
+//! # Recommendation:
+//! - It is recommended to use AES-256 when possible.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::cipher::aes;
+//!
+//! let secret_key = aes::SecretKey256::generate()?;
+//! let cipher = aes::Aes::new(&secret_key)?;
+//!
+//! let mut block = [0u8; aes::AES_BLOCKSIZE];
+//! block.copy_from_slice(b"Sixteen bytes!!!");
+//!
+//! cipher.encrypt_block(&mut block)?;
+//! assert_ne!(&block, b"Sixteen bytes!!!");
+//!
+//! cipher.decrypt_block(&mut block)?;
+//! assert_eq!(&block, b"Sixteen bytes!!!");
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		constants::{AES128_KEYSIZE, AES192_KEYSIZE, AES256_KEYSIZE},
+		types::{tag::AesKey, SecretKey},
+	},
+};
+use zeroize::Zeroize;
+
+pub use crate::hazardous::constants::AES_BLOCKSIZE;
+
+/// A type to represent an AES-128 `SecretKey`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 16 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type SecretKey128 = SecretKey<AesKey, AES128_KEYSIZE>;
+/// A type to represent an AES-192 `SecretKey`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 24 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type SecretKey192 = SecretKey<AesKey, AES192_KEYSIZE>;
+/// A type to represent an AES-256 `SecretKey`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 32 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type SecretKey256 = SecretKey<AesKey, AES256_KEYSIZE>;
+
+/// The number of rounds of AES-256, which has the most rounds.
+const MAX_ROUNDS: usize = 14;
+
+/// The round constants of the key expansion.
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// Transpose a block so that `q[i]` holds bit `i` of every byte, with byte
+/// `j` of the block in bit `j` of each `q[i]`.
+fn bitslice(block: &[u8; AES_BLOCKSIZE]) -> [u16; 8] {
+	let mut q = [0u16; 8];
+	for (j, byte) in block.iter().enumerate() {
+		for (i, plane) in q.iter_mut().enumerate() {
+			*plane |= u16::from((byte >> i) & 1) << j;
+		}
+	}
+
+	q
+}
+
+/// Reverse `bitslice()`.
+fn unbitslice(q: &[u16; 8], block: &mut [u8; AES_BLOCKSIZE]) {
+	for (j, byte) in block.iter_mut().enumerate() {
+		*byte = 0;
+		for (i, plane) in q.iter().enumerate() {
+			*byte |= (((plane >> j) & 1) as u8) << i;
+		}
+	}
+}
+
+/// The AES S-box on 16 bytes at once, computed with the circuit of Boyar and
+/// Peralta instead of a table lookup.
+fn sbox_bitsliced(q: &mut [u16; 8]) {
+	let x0 = q[7];
+	let x1 = q[6];
+	let x2 = q[5];
+	let x3 = q[4];
+	let x4 = q[3];
+	let x5 = q[2];
+	let x6 = q[1];
+	let x7 = q[0];
+
+	// Top linear transformation.
+	let y14 = x3 ^ x5;
+	let y13 = x0 ^ x6;
+	let y9 = x0 ^ x3;
+	let y8 = x0 ^ x5;
+	let t0 = x1 ^ x2;
+	let y1 = t0 ^ x7;
+	let y4 = y1 ^ x3;
+	let y12 = y13 ^ y14;
+	let y2 = y1 ^ x0;
+	let y5 = y1 ^ x6;
+	let y3 = y5 ^ y8;
+	let t1 = x4 ^ y12;
+	let y15 = t1 ^ x5;
+	let y20 = t1 ^ x1;
+	let y6 = y15 ^ x7;
+	let y10 = y15 ^ t0;
+	let y11 = y20 ^ y9;
+	let y7 = x7 ^ y11;
+	let y17 = y10 ^ y11;
+	let y19 = y10 ^ y8;
+	let y16 = t0 ^ y11;
+	let y21 = y13 ^ y16;
+	let y18 = x0 ^ y16;
+
+	// Non-linear section.
+	let t2 = y12 & y15;
+	let t3 = y3 & y6;
+	let t4 = t3 ^ t2;
+	let t5 = y4 & x7;
+	let t6 = t5 ^ t2;
+	let t7 = y13 & y16;
+	let t8 = y5 & y1;
+	let t9 = t8 ^ t7;
+	let t10 = y2 & y7;
+	let t11 = t10 ^ t7;
+	let t12 = y9 & y11;
+	let t13 = y14 & y17;
+	let t14 = t13 ^ t12;
+	let t15 = y8 & y10;
+	let t16 = t15 ^ t12;
+	let t17 = t4 ^ t14;
+	let t18 = t6 ^ t16;
+	let t19 = t9 ^ t14;
+	let t20 = t11 ^ t16;
+	let t21 = t17 ^ y20;
+	let t22 = t18 ^ y19;
+	let t23 = t19 ^ y21;
+	let t24 = t20 ^ y18;
+
+	let t25 = t21 ^ t22;
+	let t26 = t21 & t23;
+	let t27 = t24 ^ t26;
+	let t28 = t25 & t27;
+	let t29 = t28 ^ t22;
+	let t30 = t23 ^ t24;
+	let t31 = t22 ^ t26;
+	let t32 = t31 & t30;
+	let t33 = t32 ^ t24;
+	let t34 = t23 ^ t33;
+	let t35 = t27 ^ t33;
+	let t36 = t24 & t35;
+	let t37 = t36 ^ t34;
+	let t38 = t27 ^ t36;
+	let t39 = t29 & t38;
+	let t40 = t25 ^ t39;
+
+	let t41 = t40 ^ t37;
+	let t42 = t29 ^ t33;
+	let t43 = t29 ^ t40;
+	let t44 = t33 ^ t37;
+	let t45 = t42 ^ t41;
+	let z0 = t44 & y15;
+	let z1 = t37 & y6;
+	let z2 = t33 & x7;
+	let z3 = t43 & y16;
+	let z4 = t40 & y1;
+	let z5 = t29 & y7;
+	let z6 = t42 & y11;
+	let z7 = t45 & y17;
+	let z8 = t41 & y10;
+	let z9 = t44 & y12;
+	let z10 = t37 & y3;
+	let z11 = t33 & y4;
+	let z12 = t43 & y13;
+	let z13 = t40 & y5;
+	let z14 = t29 & y2;
+	let z15 = t42 & y9;
+	let z16 = t45 & y14;
+	let z17 = t41 & y8;
+
+	// Bottom linear transformation.
+	let t46 = z15 ^ z16;
+	let t47 = z10 ^ z11;
+	let t48 = z5 ^ z13;
+	let t49 = z9 ^ z10;
+	let t50 = z2 ^ z12;
+	let t51 = z2 ^ z5;
+	let t52 = z7 ^ z8;
+	let t53 = z0 ^ z3;
+	let t54 = z6 ^ z7;
+	let t55 = z16 ^ z17;
+	let t56 = z12 ^ t48;
+	let t57 = t50 ^ t53;
+	let t58 = z4 ^ t46;
+	let t59 = z3 ^ t54;
+	let t60 = t46 ^ t57;
+	let t61 = z14 ^ t57;
+	let t62 = t52 ^ t58;
+	let t63 = t49 ^ t58;
+	let t64 = z4 ^ t59;
+	let t65 = t61 ^ t62;
+	let t66 = z1 ^ t63;
+	let s0 = t59 ^ t63;
+	let s6 = t56 ^ !t62;
+	let s7 = t48 ^ !t60;
+	let t67 = t64 ^ t65;
+	let s3 = t53 ^ t66;
+	let s4 = t51 ^ t66;
+	let s5 = t47 ^ t65;
+	let s1 = t64 ^ !s3;
+	let s2 = t55 ^ !t67;
+
+	q[7] = s0;
+	q[6] = s1;
+	q[5] = s2;
+	q[4] = s3;
+	q[3] = s4;
+	q[2] = s5;
+	q[1] = s6;
+	q[0] = s7;
+}
+
+/// SubBytes.
+fn sub_bytes(state: &mut [u8; AES_BLOCKSIZE]) {
+	let mut q = bitslice(state);
+	sbox_bitsliced(&mut q);
+	unbitslice(&q, state);
+	q.zeroize();
+}
+
+/// The inverse of the affine transformation of the S-box.
+fn inv_affine(state: &mut [u8; AES_BLOCKSIZE]) {
+	for byte in state.iter_mut() {
+		*byte = byte.rotate_left(1) ^ byte.rotate_left(3) ^ byte.rotate_left(6) ^ 0x05;
+	}
+}
+
+/// InvSubBytes. The inverse S-box is the S-box surrounded by the inverse of
+/// its affine transformation, since the field inversion is its own inverse.
+fn inv_sub_bytes(state: &mut [u8; AES_BLOCKSIZE]) {
+	inv_affine(state);
+	sub_bytes(state);
+	inv_affine(state);
+}
+
+/// ShiftRows.
+fn shift_rows(state: &mut [u8; AES_BLOCKSIZE]) {
+	let old = *state;
+	for c in 0..4 {
+		for r in 1..4 {
+			state[r + 4 * c] = old[r + 4 * ((c + r) % 4)];
+		}
+	}
+}
+
+/// InvShiftRows.
+fn inv_shift_rows(state: &mut [u8; AES_BLOCKSIZE]) {
+	let old = *state;
+	for c in 0..4 {
+		for r in 1..4 {
+			state[r + 4 * ((c + r) % 4)] = old[r + 4 * c];
+		}
+	}
+}
+
+/// Multiply by x in GF(2^8), without branching on the value.
+fn xtime(x: u8) -> u8 { (x << 1) ^ (0x1b & 0u8.wrapping_sub(x >> 7)) }
+
+/// MixColumns.
+fn mix_columns(state: &mut [u8; AES_BLOCKSIZE]) {
+	for column in state.chunks_exact_mut(4) {
+		let (a0, a1, a2, a3) = (column[0], column[1], column[2], column[3]);
+		let all = a0 ^ a1 ^ a2 ^ a3;
+		column[0] ^= all ^ xtime(a0 ^ a1);
+		column[1] ^= all ^ xtime(a1 ^ a2);
+		column[2] ^= all ^ xtime(a2 ^ a3);
+		column[3] ^= all ^ xtime(a3 ^ a0);
+	}
+}
+
+/// InvMixColumns, computed as a multiplication by `{04}x^2 + {05}` followed
+/// by MixColumns.
+fn inv_mix_columns(state: &mut [u8; AES_BLOCKSIZE]) {
+	for column in state.chunks_exact_mut(4) {
+		let u = xtime(xtime(column[0] ^ column[2]));
+		let v = xtime(xtime(column[1] ^ column[3]));
+		column[0] ^= u;
+		column[1] ^= v;
+		column[2] ^= u;
+		column[3] ^= v;
+	}
+	mix_columns(state);
+}
+
+/// AddRoundKey.
+fn add_round_key(state: &mut [u8; AES_BLOCKSIZE], round_key: &[u8; AES_BLOCKSIZE]) {
+	for (s, k) in state.iter_mut().zip(round_key.iter()) {
+		*s ^= k;
+	}
+}
+
+/// Expand `key` into `round_keys` and return the number of rounds.
+fn expand_key(key: &[u8], round_keys: &mut [[u8; AES_BLOCKSIZE]; MAX_ROUNDS + 1]) -> usize {
+	let nk = key.len() / 4;
+	let rounds = nk + 6;
+	let total_words = 4 * (rounds + 1);
+
+	let mut words = [0u8; AES_BLOCKSIZE * (MAX_ROUNDS + 1)];
+	words[..key.len()].copy_from_slice(key);
+
+	// Only the first four bytes of `temp` are used, the rest is there so that
+	// `sub_bytes()` can be reused.
+	let mut temp = [0u8; AES_BLOCKSIZE];
+	for i in nk..total_words {
+		temp[..4].copy_from_slice(&words[4 * (i - 1)..4 * i]);
+		if i % nk == 0 {
+			temp[..4].rotate_left(1);
+			sub_bytes(&mut temp);
+			temp[0] ^= RCON[i / nk - 1];
+		} else if nk > 6 && i % nk == 4 {
+			sub_bytes(&mut temp);
+		}
+
+		for j in 0..4 {
+			words[4 * i + j] = words[4 * (i - nk) + j] ^ temp[j];
+		}
+	}
+
+	for (round_key, word) in round_keys
+		.iter_mut()
+		.zip(words[..4 * total_words].chunks_exact(AES_BLOCKSIZE))
+	{
+		round_key.copy_from_slice(word);
+	}
+
+	temp.zeroize();
+	words.zeroize();
+
+	rounds
+}
+
+/// The AES block cipher with an expanded key.
+pub struct Aes {
+	round_keys: [[u8; AES_BLOCKSIZE]; MAX_ROUNDS + 1],
+	rounds: usize,
+}
+
+impl core::fmt::Debug for Aes {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(
+			f,
+			"Aes {{ round_keys: [***OMITTED***], rounds: {} }}",
+			self.rounds
+		)
+	}
+}
+
+impl Drop for Aes {
+	fn drop(&mut self) {
+		for round_key in self.round_keys.iter_mut() {
+			round_key.zeroize();
+		}
+	}
+}
+
+impl Aes {
+	#[must_use]
+	/// Expand a 16, 24 or 32 byte `secret_key` for AES-128, AES-192 or AES-256.
+	pub fn new<const N: usize>(
+		secret_key: &SecretKey<AesKey, N>,
+	) -> Result<Self, UnknownCryptoError> {
+		if N != AES128_KEYSIZE && N != AES192_KEYSIZE && N != AES256_KEYSIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut round_keys = [[0u8; AES_BLOCKSIZE]; MAX_ROUNDS + 1];
+		let rounds = expand_key(secret_key.unprotected_as_bytes(), &mut round_keys);
+
+		Ok(Self { round_keys, rounds })
+	}
+
+	/// Return the number of rounds: 10, 12 or 14 for AES-128, AES-192 or
+	/// AES-256.
+	pub fn rounds(&self) -> usize { self.rounds }
+
+	#[must_use]
+	/// Encrypt `block` in place.
+	pub fn encrypt_block(&self, block: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		if block.len() != AES_BLOCKSIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut state = [0u8; AES_BLOCKSIZE];
+		state.copy_from_slice(block);
+
+		add_round_key(&mut state, &self.round_keys[0]);
+		for round_key in self.round_keys[1..self.rounds].iter() {
+			sub_bytes(&mut state);
+			shift_rows(&mut state);
+			mix_columns(&mut state);
+			add_round_key(&mut state, round_key);
+		}
+		sub_bytes(&mut state);
+		shift_rows(&mut state);
+		add_round_key(&mut state, &self.round_keys[self.rounds]);
+
+		block.copy_from_slice(&state);
+		state.zeroize();
+
+		Ok(())
+	}
+
+	#[must_use]
+	/// Decrypt `block` in place.
+	pub fn decrypt_block(&self, block: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		if block.len() != AES_BLOCKSIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut state = [0u8; AES_BLOCKSIZE];
+		state.copy_from_slice(block);
+
+		add_round_key(&mut state, &self.round_keys[self.rounds]);
+		for round_key in self.round_keys[1..self.rounds].iter().rev() {
+			inv_shift_rows(&mut state);
+			inv_sub_bytes(&mut state);
+			add_round_key(&mut state, round_key);
+			inv_mix_columns(&mut state);
+		}
+		inv_shift_rows(&mut state);
+		inv_sub_bytes(&mut state);
+		add_round_key(&mut state, &self.round_keys[0]);
+
+		block.copy_from_slice(&state);
+		state.zeroize();
+
+		Ok(())
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_new {
+		use super::*;
+
+		#[test]
+		fn test_rounds() {
+			assert_eq!(
+				Aes::new(&SecretKey128::from_slice(&[0u8; 16]).unwrap())
+					.unwrap()
+					.rounds(),
+				10
+			);
+			assert_eq!(
+				Aes::new(&SecretKey192::from_slice(&[0u8; 24]).unwrap())
+					.unwrap()
+					.rounds(),
+				12
+			);
+			assert_eq!(
+				Aes::new(&SecretKey256::from_slice(&[0u8; 32]).unwrap())
+					.unwrap()
+					.rounds(),
+				14
+			);
+		}
+
+		#[test]
+		fn test_bad_key_size() {
+			assert!(Aes::new(&SecretKey::<AesKey, 0>::from_slice(&[]).unwrap()).is_err());
+			assert!(Aes::new(&SecretKey::<AesKey, 15>::from_slice(&[0u8; 15]).unwrap()).is_err());
+			assert!(Aes::new(&SecretKey::<AesKey, 17>::from_slice(&[0u8; 17]).unwrap()).is_err());
+			assert!(Aes::new(&SecretKey::<AesKey, 64>::from_slice(&[0u8; 64]).unwrap()).is_err());
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		// format! is only available with std
+		fn test_debug_impl() {
+			let cipher = Aes::new(&SecretKey256::from_slice(&[0u8; 32]).unwrap()).unwrap();
+			let debug = format!("{:?}", cipher);
+			assert_eq!(debug, "Aes { round_keys: [***OMITTED***], rounds: 14 }");
+		}
+	}
+
+	mod test_encrypt_decrypt {
+		use super::*;
+
+		#[test]
+		fn test_block_length() {
+			let cipher = Aes::new(&SecretKey256::from_slice(&[0u8; 32]).unwrap()).unwrap();
+
+			assert!(cipher.encrypt_block(&mut [0u8; 0]).is_err());
+			assert!(cipher.encrypt_block(&mut [0u8; 15]).is_err());
+			assert!(cipher.encrypt_block(&mut [0u8; 17]).is_err());
+			assert!(cipher.encrypt_block(&mut [0u8; 16]).is_ok());
+			assert!(cipher.decrypt_block(&mut [0u8; 0]).is_err());
+			assert!(cipher.decrypt_block(&mut [0u8; 15]).is_err());
+			assert!(cipher.decrypt_block(&mut [0u8; 17]).is_err());
+			assert!(cipher.decrypt_block(&mut [0u8; 16]).is_ok());
+		}
+
+		#[test]
+		fn test_roundtrip() {
+			let keys: [&[u8]; 3] = [&[1u8; 16], &[2u8; 24], &[3u8; 32]];
+			let ciphers = [
+				Aes::new(&SecretKey128::from_slice(keys[0]).unwrap()).unwrap(),
+				Aes::new(&SecretKey192::from_slice(keys[1]).unwrap()).unwrap(),
+				Aes::new(&SecretKey256::from_slice(keys[2]).unwrap()).unwrap(),
+			];
+
+			for cipher in ciphers.iter() {
+				let mut block = [0x42u8; AES_BLOCKSIZE];
+				cipher.encrypt_block(&mut block).unwrap();
+				assert_ne!(block, [0x42u8; AES_BLOCKSIZE]);
+				cipher.decrypt_block(&mut block).unwrap();
+				assert_eq!(block, [0x42u8; AES_BLOCKSIZE]);
+			}
+		}
+
+		#[test]
+		fn test_diff_keys_diff_output() {
+			let cipher_1 = Aes::new(&SecretKey256::from_slice(&[0u8; 32]).unwrap()).unwrap();
+			let cipher_2 = Aes::new(&SecretKey256::from_slice(&[1u8; 32]).unwrap()).unwrap();
+
+			let mut block_1 = [0u8; AES_BLOCKSIZE];
+			let mut block_2 = [0u8; AES_BLOCKSIZE];
+			cipher_1.encrypt_block(&mut block_1).unwrap();
+			cipher_2.encrypt_block(&mut block_2).unwrap();
+			assert_ne!(block_1, block_2);
+		}
+	}
+
+	// Proptests. Only exectued when NOT testing no_std.
+	#[cfg(feature = "safe_api")]
+	mod proptest {
+		use super::*;
+
+		quickcheck! {
+			/// Decrypting an encrypted block should always yield the block.
+			fn prop_encrypt_decrypt_same_input(input: Vec<u8>) -> bool {
+				let mut block = [0u8; AES_BLOCKSIZE];
+				for (b, i) in block.iter_mut().zip(input.iter()) {
+					*b = *i;
+				}
+				let original = block;
+
+				let cipher = Aes::new(&SecretKey256::generate().unwrap()).unwrap();
+				cipher.encrypt_block(&mut block).unwrap();
+				cipher.decrypt_block(&mut block).unwrap();
+
+				block == original
+			}
+		}
+	}
+}
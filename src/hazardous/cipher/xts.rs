@@ -0,0 +1,508 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `data_key`: The secret key that encrypts the data (Key1).
+//! - `tweak_key`: The secret key that encrypts the sector number (Key2).
+//! - `sector`: The sector number, i.e. the data unit sequence number.
+//! - `first_sector`: The sector number of the first sector in `data`.
+//! - `sector_size`: The size of each sector in `data`, such as
+//!   `SECTOR_SIZE_512` or `SECTOR_SIZE_4096`.
+//! - `data`: The data to encrypt or decrypt in place.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `data_key` and `tweak_key` are not both 16 bytes, for XTS-AES-128, or both
+//!   32 bytes, for XTS-AES-256.
+//! - `data_key` and `tweak_key` are equal.
+//! - The length of a sector is less than `AES_BLOCKSIZE` or greater than
+//!   `MAX_SECTOR_SIZE`.
+//! - The length of `data` is not a multiple of `sector_size`, or is 0.
+//! - A sector number in `data` would overflow.
+//!
+//! Sectors whose length is not a multiple of `AES_BLOCKSIZE` are encrypted
+//! with ciphertext stealing, so the ciphertext is always of the same length as
+//! the plaintext.
+//!
+//! # Security:
+//! - XTS is meant for encrypting storage where the ciphertext cannot be longer
+//!   than the plaintext. It provides no data integrity: a modified ciphertext
+//!   decrypts to unpredictable data instead of being rejected. If you need data
+//!   integrity, use an AEAD instead. See orions `aead` module for this.
+//! - Encrypting the same data at the same sector with the same keys produces
+//!   the same ciphertext. Someone who sees a disk at several points in time can
+//!   therefore tell which blocks of a sector have changed.
+//! - Every sector must have its own sector number. Two sectors encrypted with
+//!   the same sector number and keys can be swapped undetected, and reveal
+//!   which of their blocks are equal.
+//! - `data_key` and `tweak_key` should be generated independently, with
+//!   `SecretKey256::generate()`.
+//!
+//! # Recommendation:
+//! - It is recommended to use XTS-AES-256 when possible.
+//!
+//! # Example:
+//! ```rust
+//! use orion::hazardous::cipher::{aes, xts};
+//!
+//! let data_key = aes::SecretKey256::generate()?;
+//! let tweak_key = aes::SecretKey256::generate()?;
+//! let cipher = xts::Xts::new(&data_key, &tweak_key)?;
+//!
+//! // Two 4K sectors, starting at sector 8.
+//! let mut data = vec![0u8; 2 * xts::SECTOR_SIZE_4096];
+//! cipher.encrypt_sectors(8, xts::SECTOR_SIZE_4096, &mut data)?;
+//! assert_ne!(&data[..xts::SECTOR_SIZE_4096], &data[xts::SECTOR_SIZE_4096..]);
+//!
+//! cipher.decrypt_sectors(8, xts::SECTOR_SIZE_4096, &mut data)?;
+//! assert_eq!(data, vec![0u8; 2 * xts::SECTOR_SIZE_4096]);
+//! # Ok::<(), orion::errors::UnknownCryptoError>(())
+//! ```
+
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		cipher::aes::Aes,
+		constants::{AES128_KEYSIZE, AES256_KEYSIZE, AES_BLOCKSIZE},
+		types::{tag::AesKey, SecretKey},
+	},
+};
+use zeroize::Zeroize;
+
+/// The size of a 512-byte sector.
+pub const SECTOR_SIZE_512: usize = 512;
+
+/// The size of a 4K sector.
+pub const SECTOR_SIZE_4096: usize = 4096;
+
+/// The maximum size of a sector, which is 2^20 blocks of `AES_BLOCKSIZE`.
+pub const MAX_SECTOR_SIZE: usize = (1 << 20) * AES_BLOCKSIZE;
+
+/// Multiply `tweak` by the primitive element of GF(2^128), without branching
+/// on its value.
+fn mul_alpha(tweak: &mut [u8; AES_BLOCKSIZE]) {
+	let carry = tweak[AES_BLOCKSIZE - 1] >> 7;
+	for i in (1..AES_BLOCKSIZE).rev() {
+		tweak[i] = (tweak[i] << 1) | (tweak[i - 1] >> 7);
+	}
+	tweak[0] = (tweak[0] << 1) ^ (0x87 & 0u8.wrapping_sub(carry));
+}
+
+/// XOR `tweak` into `block`.
+fn xor_tweak(block: &mut [u8], tweak: &[u8; AES_BLOCKSIZE]) {
+	for (b, t) in block.iter_mut().zip(tweak.iter()) {
+		*b ^= t;
+	}
+}
+
+/// XTS-AES with a data key and a tweak key.
+pub struct Xts {
+	data: Aes,
+	tweak: Aes,
+}
+
+impl core::fmt::Debug for Xts {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "Xts {{ data: [***OMITTED***], tweak: [***OMITTED***] }}")
+	}
+}
+
+impl Xts {
+	#[must_use]
+	/// Expand two 16-byte keys for XTS-AES-128, or two 32-byte keys for
+	/// XTS-AES-256.
+	pub fn new<const N: usize>(
+		data_key: &SecretKey<AesKey, N>,
+		tweak_key: &SecretKey<AesKey, N>,
+	) -> Result<Self, UnknownCryptoError> {
+		if N != AES128_KEYSIZE && N != AES256_KEYSIZE {
+			return Err(UnknownCryptoError);
+		}
+		if data_key == tweak_key {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(Self {
+			data: Aes::new(data_key)?,
+			tweak: Aes::new(tweak_key)?,
+		})
+	}
+
+	/// Return the tweak of the first block of `sector`.
+	fn initial_tweak(&self, sector: u128) -> Result<[u8; AES_BLOCKSIZE], UnknownCryptoError> {
+		let mut tweak = sector.to_le_bytes();
+		self.tweak.encrypt_block(&mut tweak)?;
+
+		Ok(tweak)
+	}
+
+	/// Encrypt one full `block` with `tweak`.
+	fn encrypt_block(
+		&self,
+		block: &mut [u8],
+		tweak: &[u8; AES_BLOCKSIZE],
+	) -> Result<(), UnknownCryptoError> {
+		xor_tweak(block, tweak);
+		self.data.encrypt_block(block)?;
+		xor_tweak(block, tweak);
+
+		Ok(())
+	}
+
+	/// Decrypt one full `block` with `tweak`.
+	fn decrypt_block(
+		&self,
+		block: &mut [u8],
+		tweak: &[u8; AES_BLOCKSIZE],
+	) -> Result<(), UnknownCryptoError> {
+		xor_tweak(block, tweak);
+		self.data.decrypt_block(block)?;
+		xor_tweak(block, tweak);
+
+		Ok(())
+	}
+
+	#[must_use]
+	/// Encrypt one sector in place.
+	pub fn encrypt_sector(&self, sector: u128, data: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		if data.len() < AES_BLOCKSIZE || data.len() > MAX_SECTOR_SIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let partial = data.len() % AES_BLOCKSIZE;
+		// With ciphertext stealing, the last full block is handled on its own.
+		let full_len = if partial == 0 {
+			data.len()
+		} else {
+			data.len() - partial - AES_BLOCKSIZE
+		};
+
+		let mut tweak = self.initial_tweak(sector)?;
+		for block in data[..full_len].chunks_exact_mut(AES_BLOCKSIZE) {
+			self.encrypt_block(block, &tweak)?;
+			mul_alpha(&mut tweak);
+		}
+
+		if partial != 0 {
+			let (last_block, tail) = data[full_len..].split_at_mut(AES_BLOCKSIZE);
+			self.encrypt_block(last_block, &tweak)?;
+			mul_alpha(&mut tweak);
+			// The partial block takes the head of the encrypted last full block,
+			// which is then encrypted again with the partial block in its place.
+			last_block[..partial].swap_with_slice(tail);
+			self.encrypt_block(last_block, &tweak)?;
+		}
+
+		tweak.zeroize();
+
+		Ok(())
+	}
+
+	#[must_use]
+	/// Decrypt one sector in place.
+	pub fn decrypt_sector(&self, sector: u128, data: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		if data.len() < AES_BLOCKSIZE || data.len() > MAX_SECTOR_SIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let partial = data.len() % AES_BLOCKSIZE;
+		// With ciphertext stealing, the last full block is handled on its own.
+		let full_len = if partial == 0 {
+			data.len()
+		} else {
+			data.len() - partial - AES_BLOCKSIZE
+		};
+
+		let mut tweak = self.initial_tweak(sector)?;
+		for block in data[..full_len].chunks_exact_mut(AES_BLOCKSIZE) {
+			self.decrypt_block(block, &tweak)?;
+			mul_alpha(&mut tweak);
+		}
+
+		if partial != 0 {
+			// The last full block was encrypted last, with the next tweak.
+			let mut next_tweak = tweak;
+			mul_alpha(&mut next_tweak);
+
+			let (last_block, tail) = data[full_len..].split_at_mut(AES_BLOCKSIZE);
+			self.decrypt_block(last_block, &next_tweak)?;
+			last_block[..partial].swap_with_slice(tail);
+			self.decrypt_block(last_block, &tweak)?;
+
+			next_tweak.zeroize();
+		}
+
+		tweak.zeroize();
+
+		Ok(())
+	}
+
+	/// Call `process` on each sector of `data`, with its sector number.
+	fn process_sectors<F>(
+		first_sector: u128,
+		sector_size: usize,
+		data: &mut [u8],
+		mut process: F,
+	) -> Result<(), UnknownCryptoError>
+	where
+		F: FnMut(u128, &mut [u8]) -> Result<(), UnknownCryptoError>,
+	{
+		if sector_size < AES_BLOCKSIZE || sector_size > MAX_SECTOR_SIZE {
+			return Err(UnknownCryptoError);
+		}
+		if data.is_empty() || data.len() % sector_size != 0 {
+			return Err(UnknownCryptoError);
+		}
+
+		let last_sector = (data.len() / sector_size - 1) as u128;
+		if first_sector.checked_add(last_sector).is_none() {
+			return Err(UnknownCryptoError);
+		}
+
+		for (idx, sector) in data.chunks_exact_mut(sector_size).enumerate() {
+			process(first_sector + idx as u128, sector)?;
+		}
+
+		Ok(())
+	}
+
+	#[must_use]
+	/// Encrypt consecutive sectors of `sector_size` bytes in place, starting at
+	/// sector `first_sector`.
+	pub fn encrypt_sectors(
+		&self,
+		first_sector: u128,
+		sector_size: usize,
+		data: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		Self::process_sectors(first_sector, sector_size, data, |sector, sector_data| {
+			self.encrypt_sector(sector, sector_data)
+		})
+	}
+
+	#[must_use]
+	/// Decrypt consecutive sectors of `sector_size` bytes in place, starting at
+	/// sector `first_sector`.
+	pub fn decrypt_sectors(
+		&self,
+		first_sector: u128,
+		sector_size: usize,
+		data: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		Self::process_sectors(first_sector, sector_size, data, |sector, sector_data| {
+			self.decrypt_sector(sector, sector_data)
+		})
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use crate::hazardous::cipher::aes::{SecretKey128, SecretKey192, SecretKey256};
+
+	fn test_cipher() -> Xts {
+		Xts::new(
+			&SecretKey256::from_slice(&[1u8; 32]).unwrap(),
+			&SecretKey256::from_slice(&[2u8; 32]).unwrap(),
+		)
+		.unwrap()
+	}
+
+	mod test_new {
+		use super::*;
+
+		#[test]
+		fn test_key_sizes() {
+			assert!(Xts::new(
+				&SecretKey128::from_slice(&[1u8; 16]).unwrap(),
+				&SecretKey128::from_slice(&[2u8; 16]).unwrap()
+			)
+			.is_ok());
+			assert!(Xts::new(
+				&SecretKey192::from_slice(&[1u8; 24]).unwrap(),
+				&SecretKey192::from_slice(&[2u8; 24]).unwrap()
+			)
+			.is_err());
+			assert!(Xts::new(
+				&SecretKey::<AesKey, 64>::from_slice(&[1u8; 64]).unwrap(),
+				&SecretKey::<AesKey, 64>::from_slice(&[2u8; 64]).unwrap()
+			)
+			.is_err());
+		}
+
+		#[test]
+		fn test_equal_keys() {
+			assert!(Xts::new(
+				&SecretKey256::from_slice(&[1u8; 32]).unwrap(),
+				&SecretKey256::from_slice(&[1u8; 32]).unwrap()
+			)
+			.is_err());
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		// format! is only available with std
+		fn test_debug_impl() {
+			let debug = format!("{:?}", test_cipher());
+			assert_eq!(
+				debug,
+				"Xts { data: [***OMITTED***], tweak: [***OMITTED***] }"
+			);
+		}
+	}
+
+	mod test_sector {
+		use super::*;
+
+		#[test]
+		fn test_sector_length() {
+			let cipher = test_cipher();
+
+			assert!(cipher.encrypt_sector(0, &mut [0u8; 0]).is_err());
+			assert!(cipher.encrypt_sector(0, &mut [0u8; 15]).is_err());
+			assert!(cipher.encrypt_sector(0, &mut [0u8; 16]).is_ok());
+			assert!(cipher.encrypt_sector(0, &mut [0u8; 17]).is_ok());
+			assert!(cipher.decrypt_sector(0, &mut [0u8; 0]).is_err());
+			assert!(cipher.decrypt_sector(0, &mut [0u8; 15]).is_err());
+			assert!(cipher.decrypt_sector(0, &mut [0u8; 16]).is_ok());
+			assert!(cipher.decrypt_sector(0, &mut [0u8; 17]).is_ok());
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		// vec! is only available with std
+		fn test_sector_too_long() {
+			let cipher = test_cipher();
+
+			let mut too_long = vec![0u8; MAX_SECTOR_SIZE + 1];
+			assert!(cipher.encrypt_sector(0, &mut too_long).is_err());
+			assert!(cipher.decrypt_sector(0, &mut too_long).is_err());
+		}
+
+		#[test]
+		fn test_roundtrip_all_partial_lengths() {
+			let cipher = test_cipher();
+
+			let mut plaintext = [0u8; 4 * AES_BLOCKSIZE];
+			for (i, byte) in plaintext.iter_mut().enumerate() {
+				*byte = i as u8;
+			}
+
+			for len in AES_BLOCKSIZE..(4 * AES_BLOCKSIZE) {
+				let mut data = plaintext;
+
+				cipher.encrypt_sector(5, &mut data[..len]).unwrap();
+				assert_ne!(data[..len], plaintext[..len]);
+				cipher.decrypt_sector(5, &mut data[..len]).unwrap();
+				assert_eq!(data[..], plaintext[..]);
+			}
+		}
+
+		#[test]
+		fn test_diff_sector_diff_output() {
+			let cipher = test_cipher();
+
+			let mut sector_1 = [0u8; SECTOR_SIZE_512];
+			let mut sector_2 = [0u8; SECTOR_SIZE_512];
+			cipher.encrypt_sector(1, &mut sector_1).unwrap();
+			cipher.encrypt_sector(2, &mut sector_2).unwrap();
+			assert_ne!(&sector_1[..], &sector_2[..]);
+		}
+
+		#[test]
+		fn test_max_sector_number() {
+			let cipher = test_cipher();
+
+			let mut data = [0u8; SECTOR_SIZE_512];
+			cipher.encrypt_sector(u128::max_value(), &mut data).unwrap();
+			cipher.decrypt_sector(u128::max_value(), &mut data).unwrap();
+			assert_eq!(&data[..], &[0u8; SECTOR_SIZE_512][..]);
+		}
+	}
+
+	mod test_sectors {
+		use super::*;
+
+		#[test]
+		fn test_same_as_single_sectors() {
+			let cipher = test_cipher();
+
+			let mut buffer = [0u8; 3 * SECTOR_SIZE_4096];
+			for (i, byte) in buffer.iter_mut().enumerate() {
+				*byte = i as u8;
+			}
+
+			for sector_size in [SECTOR_SIZE_512, SECTOR_SIZE_4096].iter() {
+				let plaintext = &buffer[..(3 * sector_size)];
+
+				let mut expected = [0u8; 3 * SECTOR_SIZE_4096];
+				let expected = &mut expected[..(3 * sector_size)];
+				expected.copy_from_slice(plaintext);
+				for (idx, sector) in expected.chunks_exact_mut(*sector_size).enumerate() {
+					cipher.encrypt_sector(10 + idx as u128, sector).unwrap();
+				}
+
+				let mut data = [0u8; 3 * SECTOR_SIZE_4096];
+				let data = &mut data[..(3 * sector_size)];
+				data.copy_from_slice(plaintext);
+				cipher.encrypt_sectors(10, *sector_size, data).unwrap();
+				assert_eq!(&data[..], &expected[..]);
+
+				cipher.decrypt_sectors(10, *sector_size, data).unwrap();
+				assert_eq!(&data[..], plaintext);
+			}
+		}
+
+		#[test]
+		fn test_bad_sector_size() {
+			let cipher = test_cipher();
+			let mut data = [0u8; 2 * SECTOR_SIZE_512];
+
+			assert!(cipher.encrypt_sectors(0, 0, &mut data).is_err());
+			assert!(cipher.encrypt_sectors(0, 15, &mut data).is_err());
+			assert!(cipher.encrypt_sectors(0, 1000, &mut data).is_err());
+			assert!(cipher
+				.encrypt_sectors(0, MAX_SECTOR_SIZE + 1, &mut data)
+				.is_err());
+			assert!(cipher.decrypt_sectors(0, 0, &mut data).is_err());
+			assert!(cipher.decrypt_sectors(0, 1000, &mut data).is_err());
+			assert!(cipher.encrypt_sectors(0, SECTOR_SIZE_512, &mut []).is_err());
+		}
+
+		#[test]
+		fn test_sector_number_overflow() {
+			let cipher = test_cipher();
+			let mut data = [0u8; 2 * SECTOR_SIZE_512];
+
+			assert!(cipher
+				.encrypt_sectors(u128::max_value(), SECTOR_SIZE_512, &mut data)
+				.is_err());
+			assert!(cipher
+				.decrypt_sectors(u128::max_value(), SECTOR_SIZE_512, &mut data)
+				.is_err());
+			assert!(cipher
+				.encrypt_sectors(u128::max_value() - 1, SECTOR_SIZE_512, &mut data)
+				.is_ok());
+		}
+	}
+}
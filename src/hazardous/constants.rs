@@ -20,10 +20,16 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+/// The blocksize for the hash function SHA256.
+pub const SHA256_BLOCKSIZE: usize = 64;
+/// The output size for the hash function SHA256.
+pub const SHA256_OUTSIZE: usize = 32;
 /// The blocksize for the hash function SHA512.
 pub const SHA512_BLOCKSIZE: usize = 128;
 /// The output size for the hash function SHA512.
 pub const SHA512_OUTSIZE: usize = 64;
+/// The minimum size of a truncated HMAC-SHA512 tag.
+pub const HMAC_MIN_TRUNCATED_OUTSIZE: usize = 16;
 /// The blocksize which ChaCha20 operates on.
 pub const CHACHA_BLOCKSIZE: usize = 64;
 /// The key size for ChaCha20.
@@ -32,6 +38,11 @@ pub const CHACHA_KEYSIZE: usize = 32;
 pub const HCHACHA_OUTSIZE: usize = 32;
 /// The nonce size for IETF ChaCha20.
 pub const IETF_CHACHA_NONCESIZE: usize = 12;
+/// The nonce size for the original ChaCha20 with a 64-bit nonce.
+pub const LEGACY_CHACHA_NONCESIZE: usize = 8;
+/// The key size for `chacha20-poly1305@openssh.com`, which uses two ChaCha20
+/// keys.
+pub const OPENSSH_CHACHA_KEYSIZE: usize = 64;
 /// The nonce size for HChaCha20.
 pub const HCHACHA_NONCESIZE: usize = 16;
 /// The nonce size for XChaCha20.
@@ -48,6 +59,18 @@ pub const BLAKE2B_BLOCKSIZE: usize = 128;
 pub const BLAKE2B_KEYSIZE: usize = 64;
 /// The output size for the hash function BLAKE2b.
 pub const BLAKE2B_OUTSIZE: usize = 64;
+/// The blocksize which AES operates on.
+pub const AES_BLOCKSIZE: usize = 16;
+/// The key size for AES-128.
+pub const AES128_KEYSIZE: usize = 16;
+/// The key size for AES-192.
+pub const AES192_KEYSIZE: usize = 24;
+/// The key size for AES-256.
+pub const AES256_KEYSIZE: usize = 32;
+/// The key size for AES-SIV, which is two AES-256 keys.
+pub const AES_SIV_KEYSIZE: usize = 64;
+/// The size of the synthetic IV of AES-SIV.
+pub const AES_SIV_TAGSIZE: usize = 16;
 
 /// Type for an array of length `SHA512_BLOCKSIZE`.
 pub type BlocksizeArray = [u8; SHA512_BLOCKSIZE];
@@ -36,6 +36,8 @@
 //!   with `None`.
 //! - `reset()` is called with `None` as `secret_key` but the struct was
 //!   initialized with `Some(secret_key)`.
+//! - `from_state_bytes()` is called with bytes that are not a state serialized
+//!   by `to_state_bytes()`.
 //!
 //! # Panics:
 //! A panic will occur if:
@@ -51,6 +53,9 @@
 //! intention, __**avoid using**__ `as_bytes()` to compare such MACs and use
 //! instead `verify()`, which will compare the MAC in constant time.
 //! - The recommended minimum output size is 32.
+//! - The bytes returned by `to_state_bytes()` contain all data that has not
+//!   yet been processed, which for a keyed state may include the secret key.
+//!   They must be protected the same way as the secret key.
 //!
 //! # Example:
 //! ```
@@ -68,14 +73,39 @@
 //! let mac = state_keyed.finalize().unwrap();
 //! assert!(blake2b::verify(&mac, &secret_key, 64, b"Some data").unwrap());
 //!
+//! // Using the one-shot function.
+//! assert_eq!(blake2b::digest(Some(&secret_key), 64, b"Some data").unwrap(), mac);
+//!
 //! // Using the `Hasher` for convenience functions.
 //! let digest = blake2b::Hasher::Blake2b512.digest(b"Some data").unwrap();
 //! ```
+//!
+//! With the `serializable_state` feature, a state can be saved and resumed
+//! later:
+//! ```
+//! # #[cfg(feature = "serializable_state")] {
+//! use orion::hazardous::hash::blake2b;
+//!
+//! let mut state = blake2b::init(None, 64).unwrap();
+//! state.update(b"Some ").unwrap();
+//! let checkpoint = state.to_state_bytes();
+//!
+//! let mut resumed = blake2b::Blake2b::from_state_bytes(&checkpoint).unwrap();
+//! resumed.update(b"data").unwrap();
+//! assert_eq!(
+//! 	resumed.finalize().unwrap(),
+//! 	blake2b::Hasher::Blake2b512.digest(b"Some data").unwrap()
+//! );
+//! # }
+//! ```
 
 use crate::{
 	endianness::{load_u64_into_le, store_u64_into_le},
 	errors::{FinalizationCryptoError, UnknownCryptoError, ValidationCryptoError},
-	hazardous::constants::{BLAKE2B_BLOCKSIZE, BLAKE2B_OUTSIZE},
+	hazardous::{
+		constants::{BLAKE2B_BLOCKSIZE, BLAKE2B_OUTSIZE},
+		mac::Mac,
+	},
 };
 
 construct_blake2b_key! {
@@ -155,10 +185,7 @@ impl Hasher {
 			Hasher::Blake2b512 => 64,
 		};
 
-		let mut state = init(None, size)?;
-		state.update(data)?;
-
-		Ok(state.finalize()?)
+		digest(None, size, data)
 	}
 
 	#[must_use]
@@ -172,6 +199,18 @@ impl Hasher {
 	}
 }
 
+#[cfg(feature = "serializable_state")]
+/// Version of the format produced by `to_state_bytes()`.
+const STATE_VERSION: u8 = 1;
+
+#[cfg(feature = "serializable_state")]
+/// Identifier of BLAKE2b in the format produced by `to_state_bytes()`.
+const STATE_ID: u8 = 2;
+
+#[cfg(feature = "serializable_state")]
+/// Length of the format produced by `to_state_bytes()`.
+const STATE_BYTES_LEN: usize = 2 + 64 + 64 + BLAKE2B_BLOCKSIZE + 1 + 16 + 16 + 3;
+
 #[must_use]
 #[derive(Clone)]
 /// BLAKE2b streaming state.
@@ -309,6 +348,78 @@ impl Blake2b {
 		self.internal_state[7] ^= w_vec[7] ^ w_vec[15];
 	}
 
+	#[must_use]
+	#[cfg(feature = "serializable_state")]
+	/// Serialize the state, so that it can be resumed with `from_state_bytes()`.
+	///
+	/// The format starts with a version byte and an identifier byte for
+	/// BLAKE2b. It is followed by the initial state and the internal state as
+	/// little-endian `u64`s, the buffer, the leftover as a single byte, the
+	/// `t` and `f` values as little-endian `u64`s and then single bytes for
+	/// whether the state is finalized, whether it is keyed and the output size.
+	pub fn to_state_bytes(&self) -> Vec<u8> {
+		let mut state_bytes = vec![0u8; STATE_BYTES_LEN];
+		state_bytes[0] = STATE_VERSION;
+		state_bytes[1] = STATE_ID;
+		store_u64_into_le(&self.init_state, &mut state_bytes[2..66]);
+		store_u64_into_le(&self.internal_state, &mut state_bytes[66..130]);
+		state_bytes[130..258].copy_from_slice(&self.buffer);
+		state_bytes[258] = self.leftover as u8;
+		store_u64_into_le(&self.t, &mut state_bytes[259..275]);
+		store_u64_into_le(&self.f, &mut state_bytes[275..291]);
+		state_bytes[291] = self.is_finalized as u8;
+		state_bytes[292] = self.is_keyed as u8;
+		state_bytes[293] = self.size as u8;
+
+		state_bytes
+	}
+
+	#[must_use]
+	#[cfg(feature = "serializable_state")]
+	/// Resume a state serialized with `to_state_bytes()`.
+	pub fn from_state_bytes(state_bytes: &[u8]) -> Result<Blake2b, UnknownCryptoError> {
+		if state_bytes.len() != STATE_BYTES_LEN
+			|| state_bytes[0] != STATE_VERSION
+			|| state_bytes[1] != STATE_ID
+			|| state_bytes[291] > 1
+			|| state_bytes[292] > 1
+		{
+			return Err(UnknownCryptoError);
+		}
+
+		let mut state = init(None, BLAKE2B_OUTSIZE)?;
+		load_u64_into_le(&state_bytes[2..66], &mut state.init_state);
+		load_u64_into_le(&state_bytes[66..130], &mut state.internal_state);
+		state.buffer.copy_from_slice(&state_bytes[130..258]);
+		state.leftover = state_bytes[258] as usize;
+		load_u64_into_le(&state_bytes[259..275], &mut state.t);
+		load_u64_into_le(&state_bytes[275..291], &mut state.f);
+		state.is_finalized = state_bytes[291] == 1;
+		state.is_keyed = state_bytes[292] == 1;
+		state.size = state_bytes[293] as usize;
+
+		// The parameter block in the initial state must match the output size
+		// and whether a key is used.
+		let param = state.init_state[0] ^ IV[0];
+		if state.size < 1
+			|| state.size > BLAKE2B_OUTSIZE
+			|| state.leftover > BLAKE2B_BLOCKSIZE
+			|| state.init_state[1..] != IV[1..]
+			|| param >> 16 != 0x0101
+			|| param & 0xff != state.size as u64
+			|| ((param >> 8) & 0xff != 0) != state.is_keyed
+		{
+			return Err(UnknownCryptoError);
+		}
+		// A state that is not finalized has only processed full blocks.
+		if !state.is_finalized && (state.f != [0u64; 2] || state.t[0] % BLAKE2B_BLOCKSIZE as u64 != 0)
+		{
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(state)
+	}
+
 	#[must_use]
 	/// Reset to `init()` state.
 	pub fn reset(&mut self, secret_key: Option<&SecretKey>) -> Result<(), UnknownCryptoError> {
@@ -446,6 +557,20 @@ pub fn init(secret_key: Option<&SecretKey>, size: usize) -> Result<Blake2b, Unkn
 	Ok(context)
 }
 
+#[must_use]
+/// One-shot function for generating a BLAKE2b digest of `data` with a given
+/// size and an optional key.
+pub fn digest(
+	secret_key: Option<&SecretKey>,
+	size: usize,
+	data: &[u8],
+) -> Result<Digest, UnknownCryptoError> {
+	let mut state = init(secret_key, size)?;
+	state.update(data)?;
+
+	Ok(state.finalize()?)
+}
+
 #[must_use]
 /// Verify a Blake2b Digest in constant time.
 pub fn verify(
@@ -464,6 +589,45 @@ pub fn verify(
 	}
 }
 
+#[cfg(feature = "safe_api")]
+/// Update the state with the data written. This makes it possible to use
+/// `std::io::copy()` to process a reader, such as a file.
+///
+/// An error is returned if writing to a finalized state.
+impl std::io::Write for Blake2b {
+	fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+		self.update(data)?;
+		Ok(data.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+impl Mac for Blake2b {
+	type Tag = Digest;
+
+	fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
+		Blake2b::update(self, data)
+	}
+
+	fn finalize(&mut self) -> Result<Digest, FinalizationCryptoError> { Blake2b::finalize(self) }
+
+	/// Finalize the state and compare the result with `expected` in constant
+	/// time. An error is returned if the state was initialized without a
+	/// secret key.
+	fn verify(&mut self, expected: &Digest) -> Result<bool, ValidationCryptoError> {
+		if !self.is_keyed {
+			return Err(ValidationCryptoError);
+		}
+
+		if expected == &Blake2b::finalize(self)? {
+			Ok(true)
+		} else {
+			Err(ValidationCryptoError)
+		}
+	}
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -564,6 +728,37 @@ mod public {
 			);
 		}
 
+		#[test]
+		fn verify_shortened_expected_err() {
+			let secret_key = SecretKey::from_slice("Jefe".as_bytes()).unwrap();
+			let data = "what do ya want for nothing?".as_bytes();
+
+			let mut state = init(Some(&secret_key), 64).unwrap();
+			state.update(data).unwrap();
+			let tag = state.finalize().unwrap();
+			assert!(verify(&tag, &secret_key, 64, data).unwrap());
+
+			// A 1-byte digest computed with size 1 must not pass when 64 is expected.
+			let mut state = init(Some(&secret_key), 1).unwrap();
+			state.update(data).unwrap();
+			let short = state.finalize().unwrap();
+			assert!(verify(&short, &secret_key, 64, data).is_err());
+
+			// Neither must a prefix of the correct 64-byte digest.
+			let truncated = Digest::from_slice(&tag.as_bytes()[..32]).unwrap();
+			assert!(verify(&truncated, &secret_key, 64, data).is_err());
+		}
+
+		#[test]
+		fn verify_wrong_data_false() {
+			let secret_key = SecretKey::from_slice("Jefe".as_bytes()).unwrap();
+
+			let mut state = init(Some(&secret_key), 64).unwrap();
+			state.update(b"Some data").unwrap();
+			let tag = state.finalize().unwrap();
+			assert!(verify(&tag, &secret_key, 64, b"Other data").is_err());
+		}
+
 		// Proptests. Only exectued when NOT testing no_std.
 		#[cfg(feature = "safe_api")]
 		mod proptest {
@@ -606,6 +801,38 @@ mod public {
 		}
 	}
 
+	mod test_digest {
+		use super::*;
+
+		#[test]
+		fn test_same_as_streaming() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			for size in [1, 32, 48, 64].iter() {
+				let mut state = init(Some(&sk), *size).unwrap();
+				state.update(b"Some data").unwrap();
+				assert_eq!(digest(Some(&sk), *size, b"Some data").unwrap(), state.finalize().unwrap());
+
+				let mut state = init(None, *size).unwrap();
+				state.update(b"Some data").unwrap();
+				assert_eq!(digest(None, *size, b"Some data").unwrap(), state.finalize().unwrap());
+			}
+		}
+
+		#[test]
+		fn test_same_as_hasher() {
+			assert_eq!(
+				digest(None, 32, b"Some data").unwrap(),
+				Hasher::Blake2b256.digest(b"Some data").unwrap()
+			);
+		}
+
+		#[test]
+		fn test_size_err() {
+			assert!(digest(None, 0, b"Some data").is_err());
+			assert!(digest(None, 65, b"Some data").is_err());
+		}
+	}
+
 	mod test_hasher {
 		use super::*;
 
@@ -800,6 +1027,88 @@ mod public {
 		}
 	}
 
+	#[cfg(feature = "safe_api")]
+	mod test_io_write {
+		use super::*;
+		use std::io::{copy, Write};
+
+		#[test]
+		fn test_write_same_as_update() {
+			let data = [0u8; 356];
+
+			let mut state = init(None, 64).unwrap();
+			let mut reader: &[u8] = &data;
+			assert_eq!(copy(&mut reader, &mut state).unwrap(), 356);
+			state.flush().unwrap();
+
+			let mut state_update = init(None, 64).unwrap();
+			state_update.update(&data).unwrap();
+
+			assert_eq!(state.finalize().unwrap(), state_update.finalize().unwrap());
+		}
+
+		#[test]
+		fn test_write_after_finalize_err() {
+			let mut state = init(None, 64).unwrap();
+			let _ = state.finalize().unwrap();
+			assert!(state.write(b"Some data").is_err());
+		}
+	}
+
+	#[cfg(feature = "serializable_state")]
+	mod test_state_bytes {
+		use super::*;
+
+		#[test]
+		fn test_resume_same_digest() {
+			let data = [0u8; 300];
+			let secret_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+
+			for key in [None, Some(&secret_key)].iter() {
+				let mut expected_state = init(*key, 32).unwrap();
+				expected_state.update(&data).unwrap();
+				let expected = expected_state.finalize().unwrap();
+
+				for split in [0, 1, 127, 128, 129, 256, 300].iter() {
+					let mut state = init(*key, 32).unwrap();
+					state.update(&data[..*split]).unwrap();
+					let mut resumed = Blake2b::from_state_bytes(&state.to_state_bytes()).unwrap();
+					resumed.update(&data[*split..]).unwrap();
+					assert_eq!(resumed.finalize().unwrap(), expected);
+				}
+			}
+		}
+
+		#[test]
+		fn test_resume_keyed_reset() {
+			let secret_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+			let state = init(Some(&secret_key), 64).unwrap();
+			let mut resumed = Blake2b::from_state_bytes(&state.to_state_bytes()).unwrap();
+			assert!(resumed.reset(None).is_err());
+			assert!(resumed.reset(Some(&secret_key)).is_ok());
+			resumed.update(b"Some data").unwrap();
+			assert!(verify(&resumed.finalize().unwrap(), &secret_key, 64, b"Some data").unwrap());
+		}
+
+		#[test]
+		fn test_invalid_state_bytes_err() {
+			let mut state = init(None, 32).unwrap();
+			state.update(b"Some data").unwrap();
+			let state_bytes = state.to_state_bytes();
+			assert!(Blake2b::from_state_bytes(&state_bytes).is_ok());
+
+			assert!(Blake2b::from_state_bytes(&state_bytes[..state_bytes.len() - 1]).is_err());
+			assert!(Blake2b::from_state_bytes(&[]).is_err());
+			// Version, identifier, init_state, leftover, t, f, is_finalized,
+			// is_keyed and size.
+			for idx in [0, 1, 2, 10, 258, 259, 275, 291, 292, 293].iter() {
+				let mut modified = state_bytes.clone();
+				modified[*idx] ^= 0x81;
+				assert!(Blake2b::from_state_bytes(&modified).is_err());
+			}
+		}
+	}
+
 	mod test_streaming_interface {
 		use super::*;
 
@@ -0,0 +1,309 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `seed`: The secret value at the start of the hash chain.
+//! - `length`: The number of links in the hash chain after the `seed`.
+//! - `anchor`: The last value of the hash chain, which is published in advance.
+//! - `link`: A value of the hash chain revealed by its owner.
+//! - `max_steps`: The maximum number of hash applications between `link` and
+//!   the last verified value.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `length` is 0.
+//! - `next_link()` is called after all links of the hash chain have been
+//!   revealed.
+//! - `max_steps` is 0.
+//! - `link` does not hash to the last verified value within the allowed
+//!   number of steps.
+//!
+//! # Security:
+//! - The `seed` must be generated using a CSPRNG, and should be deleted once
+//!   all links have been revealed.
+//! - The links are revealed in the reverse order of how they are computed, so
+//!   knowing a revealed link does not make it possible to compute any of the
+//!   links that are yet to be revealed. Verified links, in the other direction,
+//!   can be recomputed by anyone.
+//! - Each link must only be accepted once. `ChainVerifier` takes care of this by
+//!   only accepting links that come before the last verified one.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::hash::chain::{ChainVerifier, HashChain, Seed};
+//!
+//! let mut chain = HashChain::new(Seed::generate().unwrap(), 100).unwrap();
+//! // The anchor is published in advance.
+//! let mut verifier = ChainVerifier::new(&chain.anchor().unwrap());
+//!
+//! // Each link is revealed once, to be used as a one-time token.
+//! let first = chain.next_link().unwrap();
+//! assert!(verifier.verify(&first).unwrap());
+//! assert!(verifier.verify(&first).is_err());
+//!
+//! // Links that were lost can be skipped.
+//! let _lost = chain.next_link().unwrap();
+//! let third = chain.next_link().unwrap();
+//! assert!(verifier.verify(&third).is_err());
+//! assert!(verifier.verify_with_skip(&third, 2).unwrap());
+//! ```
+
+use crate::{
+	errors::{UnknownCryptoError, ValidationCryptoError},
+	hazardous::{hash::blake2b, types},
+	util,
+};
+
+/// The size of each value in a hash chain.
+pub const CHAIN_LINK_SIZE: usize = 32;
+
+/// A type to represent the secret `Seed` that a hash chain starts from.
+pub type Seed = types::SecretKey<types::tag::ChainSeed, CHAIN_LINK_SIZE>;
+
+/// A type to represent a `Link` of a hash chain.
+pub type Link = types::PublicData<types::tag::ChainLink, CHAIN_LINK_SIZE>;
+
+/// Return BLAKE2b-256 of `bytes`.
+fn hash_link(bytes: &[u8]) -> Result<Link, UnknownCryptoError> {
+	let mut state = blake2b::init(None, CHAIN_LINK_SIZE)?;
+	state.update(bytes)?;
+
+	Link::from_slice(state.finalize()?.as_bytes())
+}
+
+#[must_use]
+/// Return the value of the hash chain from `seed` after `n` hash applications.
+/// If `n` is 0, the `seed` itself is returned.
+pub fn iterate(seed: &Seed, n: usize) -> Result<Link, UnknownCryptoError> {
+	let mut link = Link::from_slice(seed.unprotected_as_bytes())?;
+	for _ in 0..n {
+		link = hash_link(link.as_bytes())?;
+	}
+
+	Ok(link)
+}
+
+#[must_use]
+#[derive(Debug)]
+/// The owner of a hash chain, which reveals its links in reverse order.
+pub struct HashChain {
+	seed: Seed,
+	length: usize,
+	revealed: usize,
+}
+
+impl HashChain {
+	#[must_use]
+	/// Initialize a `HashChain` of `length` links from `seed`.
+	pub fn new(seed: Seed, length: usize) -> Result<Self, UnknownCryptoError> {
+		if length == 0 {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(Self {
+			seed,
+			length,
+			revealed: 0,
+		})
+	}
+
+	#[must_use]
+	/// Return the anchor of the hash chain, which is the `seed` after `length`
+	/// hash applications.
+	pub fn anchor(&self) -> Result<Link, UnknownCryptoError> { iterate(&self.seed, self.length) }
+
+	#[must_use]
+	/// Return the next link to be revealed. The last link is the `seed`.
+	pub fn next_link(&mut self) -> Result<Link, UnknownCryptoError> {
+		if self.revealed == self.length {
+			return Err(UnknownCryptoError);
+		}
+
+		self.revealed += 1;
+		iterate(&self.seed, self.length - self.revealed)
+	}
+
+	/// Return the number of links that are yet to be revealed.
+	pub fn remaining(&self) -> usize { self.length - self.revealed }
+}
+
+#[must_use]
+#[derive(Debug, Clone)]
+/// Verification of the successive links of a hash chain, starting from its
+/// anchor.
+pub struct ChainVerifier {
+	current: Link,
+}
+
+impl ChainVerifier {
+	/// Initialize a `ChainVerifier` with the published `anchor`.
+	pub fn new(anchor: &Link) -> Self { Self { current: *anchor } }
+
+	/// Return the last verified link, or the anchor if no link has been
+	/// verified.
+	pub fn current(&self) -> Link { self.current }
+
+	#[must_use]
+	/// Verify that `link` is the link directly before the last verified one.
+	pub fn verify(&mut self, link: &Link) -> Result<bool, ValidationCryptoError> {
+		self.verify_with_skip(link, 1)
+	}
+
+	#[must_use]
+	/// Verify that `link` hashes to the last verified link in at most
+	/// `max_steps` hash applications, allowing for up to `max_steps - 1` links
+	/// that were never received.
+	pub fn verify_with_skip(
+		&mut self,
+		link: &Link,
+		max_steps: usize,
+	) -> Result<bool, ValidationCryptoError> {
+		if max_steps == 0 {
+			return Err(ValidationCryptoError);
+		}
+
+		let mut hashed = *link;
+		for _ in 0..max_steps {
+			hashed = hash_link(hashed.as_bytes())?;
+			if util::secure_cmp(hashed.as_bytes(), self.current.as_bytes()).is_ok() {
+				self.current = *link;
+				return Ok(true);
+			}
+		}
+
+		Err(ValidationCryptoError)
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	fn test_seed() -> Seed {
+		let mut seed = [0u8; 32];
+		for (i, byte) in seed.iter_mut().enumerate() {
+			*byte = i as u8;
+		}
+
+		Seed::from_slice(&seed).unwrap()
+	}
+
+	// One function tested per submodule.
+	mod test_iterate {
+		use super::*;
+
+		#[test]
+		fn test_known_values() {
+			let expected: [Link; 3] = [
+				"cb2f5160fc1f7e05a55ef49d340b48da2e5a78099d53393351cd579dd42503d6"
+					.parse()
+					.unwrap(),
+				"bff30b9a1aac661d7a3aa6c5508cb844999bb39600e7b97e7cebd9bcbfdf329d"
+					.parse()
+					.unwrap(),
+				"21ee4cbff968ce3cfb4379dc4a545359903b84e7b9d9ca22d88652dfe4fc76b0"
+					.parse()
+					.unwrap(),
+			];
+
+			let seed = test_seed();
+			assert_eq!(iterate(&seed, 0).unwrap().as_bytes(), seed.unprotected_as_bytes());
+			for (n, link) in expected.iter().enumerate() {
+				assert_eq!(&iterate(&seed, n + 1).unwrap(), link);
+			}
+		}
+	}
+
+	mod test_hash_chain {
+		use super::*;
+
+		#[test]
+		fn test_zero_length_err() {
+			assert!(HashChain::new(test_seed(), 0).is_err());
+		}
+
+		#[test]
+		fn test_links_in_reverse_order() {
+			let mut chain = HashChain::new(test_seed(), 3).unwrap();
+			assert_eq!(chain.anchor().unwrap(), iterate(&test_seed(), 3).unwrap());
+
+			for n in (0..3).rev() {
+				assert_eq!(chain.remaining(), n + 1);
+				assert_eq!(chain.next_link().unwrap(), iterate(&test_seed(), n).unwrap());
+			}
+			assert_eq!(chain.remaining(), 0);
+			assert!(chain.next_link().is_err());
+		}
+	}
+
+	mod test_chain_verifier {
+		use super::*;
+
+		#[test]
+		fn test_verify_all_links() {
+			let mut chain = HashChain::new(test_seed(), 10).unwrap();
+			let mut verifier = ChainVerifier::new(&chain.anchor().unwrap());
+
+			while chain.remaining() > 0 {
+				let link = chain.next_link().unwrap();
+				assert!(verifier.verify(&link).unwrap());
+				assert_eq!(verifier.current(), link);
+				// A link cannot be used twice.
+				assert!(verifier.verify(&link).is_err());
+			}
+		}
+
+		#[test]
+		fn test_verify_wrong_link_err() {
+			let mut chain = HashChain::new(test_seed(), 10).unwrap();
+			let anchor = chain.anchor().unwrap();
+			let mut verifier = ChainVerifier::new(&anchor);
+
+			// The anchor itself, and links from another chain, are rejected.
+			assert!(verifier.verify(&anchor).is_err());
+			let other = HashChain::new(Seed::from_slice(&[0u8; 32]).unwrap(), 10).unwrap();
+			assert!(verifier.verify(&other.anchor().unwrap()).is_err());
+
+			let _ = chain.next_link().unwrap();
+			let second = chain.next_link().unwrap();
+			assert!(verifier.verify(&second).is_err());
+			assert_eq!(verifier.current(), anchor);
+		}
+
+		#[test]
+		fn test_verify_with_skip() {
+			let mut chain = HashChain::new(test_seed(), 10).unwrap();
+			let mut verifier = ChainVerifier::new(&chain.anchor().unwrap());
+			for _ in 0..3 {
+				let _ = chain.next_link().unwrap();
+			}
+			let fourth = chain.next_link().unwrap();
+
+			assert!(verifier.verify_with_skip(&fourth, 0).is_err());
+			assert!(verifier.verify_with_skip(&fourth, 3).is_err());
+			assert!(verifier.verify_with_skip(&fourth, 4).unwrap());
+			assert!(verifier.verify_with_skip(&fourth, 4).is_err());
+			assert!(verifier.verify(&chain.next_link().unwrap()).unwrap());
+		}
+	}
+}
@@ -0,0 +1,620 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `data`: The data of a leaf to be appended to the tree.
+//! - `index`: The position of a leaf in the tree, starting at 0.
+//! - `size`: The number of leaves in the tree the proof is for.
+//! - `old_size`, `new_size`: The number of leaves in the two trees that a
+//!   consistency proof is for.
+//! - `leaf_hash`: The hash of the leaf, as returned by `leaf_hash()`.
+//! - `proof`: An inclusion or consistency proof.
+//! - `root`, `old_root`, `new_root`: The root hashes the proof is checked
+//!   against.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `index` is not less than `size`.
+//! - `size` or `new_size` is greater than the number of leaves in the tree.
+//! - `old_size` is 0 or greater than `new_size`.
+//! - The proof does not prove inclusion of `leaf_hash` in, or consistency
+//!   between, the given root hashes.
+//!
+//! # Security:
+//! - Leaf and node hashes are domain separated as specified in RFC 6962, so a
+//!   node hash cannot be presented as a leaf hash.
+//! - A consistency proof only shows that the tree of `old_size` leaves is a
+//!   prefix of the tree of `new_size` leaves. The root hashes themselves must
+//!   be obtained from a trusted source, such as a signed tree head.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::hash::merkle;
+//!
+//! let mut tree = merkle::MerkleTree::new();
+//! tree.append(b"first entry").unwrap();
+//! tree.append(b"second entry").unwrap();
+//! let old_root = tree.root().unwrap();
+//! let index = tree.append(b"third entry").unwrap();
+//! let new_root = tree.root().unwrap();
+//!
+//! // Prove that the third entry is in the tree.
+//! let proof = tree.inclusion_proof(index, 3).unwrap();
+//! let leaf_hash = merkle::leaf_hash(b"third entry").unwrap();
+//! assert!(merkle::verify_inclusion(&leaf_hash, index, 3, &proof, &new_root).unwrap());
+//!
+//! // Prove that the tree has only been appended to.
+//! let proof = tree.consistency_proof(2, 3).unwrap();
+//! assert!(merkle::verify_consistency(2, 3, &old_root, &new_root, &proof).unwrap());
+//! ```
+
+use crate::{
+	errors::{UnknownCryptoError, ValidationCryptoError},
+	hazardous::hash::sha256,
+};
+
+pub use crate::hazardous::hash::sha256::Digest;
+
+/// The prefix of a leaf hash.
+const LEAF_PREFIX: u8 = 0x00;
+
+/// The prefix of a node hash.
+const NODE_PREFIX: u8 = 0x01;
+
+#[must_use]
+/// Return the hash of a leaf with `data`.
+pub fn leaf_hash(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+	let mut state = sha256::init();
+	state.update(&[LEAF_PREFIX])?;
+	state.update(data)?;
+
+	Ok(state.finalize()?)
+}
+
+/// Return the hash of a node with the children `left` and `right`.
+fn node_hash(left: &Digest, right: &Digest) -> Result<Digest, UnknownCryptoError> {
+	let mut state = sha256::init();
+	state.update(&[NODE_PREFIX])?;
+	state.update(left.as_bytes())?;
+	state.update(right.as_bytes())?;
+
+	Ok(state.finalize()?)
+}
+
+/// Return the largest power of two that is less than `n`. `n` must be
+/// greater than 1.
+fn split_point(n: usize) -> usize {
+	debug_assert!(n > 1);
+	let mut k = 1;
+	while k << 1 < n {
+		k <<= 1;
+	}
+
+	k
+}
+
+/// Return the root hash of the non-empty tree with the leaf hashes `leaves`.
+fn subtree_root(leaves: &[Digest]) -> Result<Digest, UnknownCryptoError> {
+	if leaves.len() == 1 {
+		return Ok(leaves[0]);
+	}
+
+	let k = split_point(leaves.len());
+	node_hash(&subtree_root(&leaves[..k])?, &subtree_root(&leaves[k..])?)
+}
+
+/// Append the inclusion proof of leaf `index` in the tree with the leaf hashes
+/// `leaves` to `proof`.
+fn inclusion_path(
+	index: usize,
+	leaves: &[Digest],
+	proof: &mut Vec<Digest>,
+) -> Result<(), UnknownCryptoError> {
+	if leaves.len() == 1 {
+		return Ok(());
+	}
+
+	let k = split_point(leaves.len());
+	if index < k {
+		inclusion_path(index, &leaves[..k], proof)?;
+		proof.push(subtree_root(&leaves[k..])?);
+	} else {
+		inclusion_path(index - k, &leaves[k..], proof)?;
+		proof.push(subtree_root(&leaves[..k])?);
+	}
+
+	Ok(())
+}
+
+/// Append the consistency proof between the first `old_size` leaves and the
+/// tree with the leaf hashes `leaves` to `proof`. `complete` is true if the
+/// subtree of `old_size` leaves is known to the verifier.
+fn consistency_path(
+	old_size: usize,
+	leaves: &[Digest],
+	complete: bool,
+	proof: &mut Vec<Digest>,
+) -> Result<(), UnknownCryptoError> {
+	if old_size == leaves.len() {
+		if !complete {
+			proof.push(subtree_root(leaves)?);
+		}
+		return Ok(());
+	}
+
+	let k = split_point(leaves.len());
+	if old_size <= k {
+		consistency_path(old_size, &leaves[..k], complete, proof)?;
+		proof.push(subtree_root(&leaves[k..])?);
+	} else {
+		consistency_path(old_size - k, &leaves[k..], false, proof)?;
+		proof.push(subtree_root(&leaves[..k])?);
+	}
+
+	Ok(())
+}
+
+#[must_use]
+#[derive(Debug, Clone, Default)]
+/// An append-only Merkle tree, which can produce inclusion proofs and
+/// consistency proofs for any of its earlier sizes.
+pub struct MerkleTree {
+	leaves: Vec<Digest>,
+}
+
+impl MerkleTree {
+	/// Initialize an empty `MerkleTree`.
+	pub fn new() -> Self { Self { leaves: Vec::new() } }
+
+	#[must_use]
+	/// Append a leaf with `data` to the tree, and return its index.
+	pub fn append(&mut self, data: &[u8]) -> Result<usize, UnknownCryptoError> {
+		self.leaves.push(leaf_hash(data)?);
+
+		Ok(self.leaves.len() - 1)
+	}
+
+	/// Return the number of leaves in the tree.
+	pub fn size(&self) -> usize { self.leaves.len() }
+
+	#[must_use]
+	/// Return the root hash of the tree.
+	pub fn root(&self) -> Result<Digest, UnknownCryptoError> { self.root_at(self.leaves.len()) }
+
+	#[must_use]
+	/// Return the root hash of the tree when it had `size` leaves.
+	pub fn root_at(&self, size: usize) -> Result<Digest, UnknownCryptoError> {
+		if size > self.leaves.len() {
+			return Err(UnknownCryptoError);
+		}
+		if size == 0 {
+			return sha256::digest(b"");
+		}
+
+		subtree_root(&self.leaves[..size])
+	}
+
+	#[must_use]
+	/// Return the proof that leaf `index` is included in the tree of `size`
+	/// leaves.
+	pub fn inclusion_proof(
+		&self,
+		index: usize,
+		size: usize,
+	) -> Result<Vec<Digest>, UnknownCryptoError> {
+		if index >= size || size > self.leaves.len() {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut proof = Vec::new();
+		inclusion_path(index, &self.leaves[..size], &mut proof)?;
+
+		Ok(proof)
+	}
+
+	#[must_use]
+	/// Return the proof that the tree of `old_size` leaves is a prefix of the
+	/// tree of `new_size` leaves.
+	pub fn consistency_proof(
+		&self,
+		old_size: usize,
+		new_size: usize,
+	) -> Result<Vec<Digest>, UnknownCryptoError> {
+		if old_size == 0 || old_size > new_size || new_size > self.leaves.len() {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut proof = Vec::new();
+		consistency_path(old_size, &self.leaves[..new_size], true, &mut proof)?;
+
+		Ok(proof)
+	}
+}
+
+#[must_use]
+/// Verify that `proof` proves that `leaf_hash` is leaf `index` of the tree of
+/// `size` leaves with the root hash `root`.
+pub fn verify_inclusion(
+	leaf_hash: &Digest,
+	index: usize,
+	size: usize,
+	proof: &[Digest],
+	root: &Digest,
+) -> Result<bool, ValidationCryptoError> {
+	if index >= size {
+		return Err(ValidationCryptoError);
+	}
+
+	let mut f_n = index;
+	let mut s_n = size - 1;
+	let mut r = *leaf_hash;
+
+	for p in proof.iter() {
+		if s_n == 0 {
+			return Err(ValidationCryptoError);
+		}
+
+		if f_n & 1 == 1 || f_n == s_n {
+			r = node_hash(p, &r)?;
+			while f_n & 1 == 0 && f_n != 0 {
+				f_n >>= 1;
+				s_n >>= 1;
+			}
+		} else {
+			r = node_hash(&r, p)?;
+		}
+
+		f_n >>= 1;
+		s_n >>= 1;
+	}
+
+	if s_n == 0 && &r == root {
+		Ok(true)
+	} else {
+		Err(ValidationCryptoError)
+	}
+}
+
+#[must_use]
+/// Verify that `proof` proves that the tree of `old_size` leaves with the root
+/// hash `old_root` is a prefix of the tree of `new_size` leaves with the root
+/// hash `new_root`.
+pub fn verify_consistency(
+	old_size: usize,
+	new_size: usize,
+	old_root: &Digest,
+	new_root: &Digest,
+	proof: &[Digest],
+) -> Result<bool, ValidationCryptoError> {
+	if old_size == 0 || old_size > new_size {
+		return Err(ValidationCryptoError);
+	}
+
+	if old_size == new_size {
+		if proof.is_empty() && old_root == new_root {
+			return Ok(true);
+		}
+		return Err(ValidationCryptoError);
+	}
+
+	// If the old tree is a complete subtree, its root is the first node of
+	// the proof.
+	let (first, rest) = if old_size.is_power_of_two() {
+		(old_root, proof)
+	} else {
+		match proof.split_first() {
+			Some(split) => split,
+			None => return Err(ValidationCryptoError),
+		}
+	};
+
+	let mut f_n = old_size - 1;
+	let mut s_n = new_size - 1;
+	while f_n & 1 == 1 {
+		f_n >>= 1;
+		s_n >>= 1;
+	}
+
+	let mut f_r = *first;
+	let mut s_r = *first;
+
+	for c in rest.iter() {
+		if s_n == 0 {
+			return Err(ValidationCryptoError);
+		}
+
+		if f_n & 1 == 1 || f_n == s_n {
+			f_r = node_hash(c, &f_r)?;
+			s_r = node_hash(c, &s_r)?;
+			while f_n & 1 == 0 && f_n != 0 {
+				f_n >>= 1;
+				s_n >>= 1;
+			}
+		} else {
+			s_r = node_hash(&s_r, c)?;
+		}
+
+		f_n >>= 1;
+		s_n >>= 1;
+	}
+
+	if s_n == 0 && &f_r == old_root && &s_r == new_root {
+		Ok(true)
+	} else {
+		Err(ValidationCryptoError)
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	/// The leaves of the RFC 6962 reference test tree.
+	fn reference_tree() -> MerkleTree {
+		let leaves: [&[u8]; 8] = [
+			b"",
+			b"\x00",
+			b"\x10",
+			b"\x20\x21",
+			b"\x30\x31",
+			b"\x40\x41\x42\x43",
+			b"\x50\x51\x52\x53\x54\x55\x56\x57",
+			b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+		];
+
+		let mut tree = MerkleTree::new();
+		for leaf in leaves.iter() {
+			tree.append(leaf).unwrap();
+		}
+
+		tree
+	}
+
+	fn digests(hex: &[&str]) -> Vec<Digest> { hex.iter().map(|h| h.parse().unwrap()).collect() }
+
+	// One function tested per submodule.
+	mod test_root {
+		use super::*;
+
+		#[test]
+		fn test_reference_roots() {
+			let expected = digests(&[
+				"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+				"6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+				"fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+				"aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+				"d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+				"4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+				"76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+				"ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+				"5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+			]);
+
+			let tree = reference_tree();
+			for (size, root) in expected.iter().enumerate() {
+				assert_eq!(&tree.root_at(size).unwrap(), root);
+			}
+			assert_eq!(&tree.root().unwrap(), &expected[8]);
+			assert!(tree.root_at(9).is_err());
+		}
+	}
+
+	mod test_inclusion_proof {
+		use super::*;
+
+		#[test]
+		fn test_reference_proofs() {
+			let tree = reference_tree();
+
+			assert_eq!(
+				tree.inclusion_proof(0, 8).unwrap(),
+				digests(&[
+					"96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+					"5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+					"6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+				])
+			);
+			assert_eq!(
+				tree.inclusion_proof(5, 8).unwrap(),
+				digests(&[
+					"bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+					"ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+					"d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+				])
+			);
+			assert_eq!(
+				tree.inclusion_proof(2, 3).unwrap(),
+				digests(&["fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125"])
+			);
+			assert_eq!(
+				tree.inclusion_proof(1, 5).unwrap(),
+				digests(&[
+					"6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+					"5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+					"bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+				])
+			);
+			assert!(tree.inclusion_proof(0, 1).unwrap().is_empty());
+		}
+
+		#[test]
+		fn test_bad_index_or_size_err() {
+			let tree = reference_tree();
+			assert!(tree.inclusion_proof(3, 3).is_err());
+			assert!(tree.inclusion_proof(0, 0).is_err());
+			assert!(tree.inclusion_proof(0, 9).is_err());
+		}
+	}
+
+	mod test_consistency_proof {
+		use super::*;
+
+		#[test]
+		fn test_reference_proofs() {
+			let tree = reference_tree();
+
+			assert_eq!(
+				tree.consistency_proof(1, 8).unwrap(),
+				digests(&[
+					"96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+					"5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+					"6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+				])
+			);
+			assert_eq!(
+				tree.consistency_proof(6, 8).unwrap(),
+				digests(&[
+					"0ebc5d3437fbe2db158b9f126a1d118e308181031d0a949f8dededebc558ef6a",
+					"ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+					"d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+				])
+			);
+			assert_eq!(
+				tree.consistency_proof(2, 5).unwrap(),
+				digests(&[
+					"5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+					"bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+				])
+			);
+			assert_eq!(
+				tree.consistency_proof(4, 8).unwrap(),
+				digests(&["6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4"])
+			);
+			assert!(tree.consistency_proof(8, 8).unwrap().is_empty());
+		}
+
+		#[test]
+		fn test_bad_sizes_err() {
+			let tree = reference_tree();
+			assert!(tree.consistency_proof(0, 8).is_err());
+			assert!(tree.consistency_proof(5, 4).is_err());
+			assert!(tree.consistency_proof(5, 9).is_err());
+		}
+	}
+
+	mod test_verify_inclusion {
+		use super::*;
+
+		#[test]
+		fn test_verify_all_proofs() {
+			let mut tree = MerkleTree::new();
+			for n in 0..33u8 {
+				tree.append(&[n]).unwrap();
+			}
+
+			for size in 1..=tree.size() {
+				let root = tree.root_at(size).unwrap();
+				for index in 0..size {
+					let leaf = leaf_hash(&[index as u8]).unwrap();
+					let proof = tree.inclusion_proof(index, size).unwrap();
+					assert!(verify_inclusion(&leaf, index, size, &proof, &root).unwrap());
+
+					// The proof is bound to the leaf and its index.
+					let other = leaf_hash(&[size as u8]).unwrap();
+					assert!(verify_inclusion(&other, index, size, &proof, &root).is_err());
+					assert!(verify_inclusion(&leaf, index + 1, size, &proof, &root).is_err());
+				}
+			}
+		}
+
+		#[test]
+		fn test_verify_modified_proof_err() {
+			let tree = reference_tree();
+			let root = tree.root().unwrap();
+			let leaf = leaf_hash(b"\x30\x31").unwrap();
+			let proof = tree.inclusion_proof(4, 8).unwrap();
+			assert!(verify_inclusion(&leaf, 4, 8, &proof, &root).unwrap());
+
+			assert!(verify_inclusion(&leaf, 4, 8, &proof[..2], &root).is_err());
+			let mut longer = proof.clone();
+			longer.push(root);
+			assert!(verify_inclusion(&leaf, 4, 8, &longer, &root).is_err());
+			let mut swapped = proof.clone();
+			swapped.swap(0, 1);
+			assert!(verify_inclusion(&leaf, 4, 8, &swapped, &root).is_err());
+			assert!(verify_inclusion(&root, 4, 8, &proof, &root).is_err());
+		}
+
+		#[test]
+		fn test_leaf_and_node_hash_differ() {
+			// The data of an inner node does not hash to that node as a leaf.
+			let tree = reference_tree();
+			let node = tree.root_at(2).unwrap();
+			let mut children = Vec::new();
+			children.extend_from_slice(tree.leaves[0].as_bytes());
+			children.extend_from_slice(tree.leaves[1].as_bytes());
+
+			assert_eq!(node_hash(&tree.leaves[0], &tree.leaves[1]).unwrap(), node);
+			assert!(leaf_hash(&children).unwrap() != node);
+		}
+	}
+
+	mod test_verify_consistency {
+		use super::*;
+
+		#[test]
+		fn test_verify_all_proofs() {
+			let mut tree = MerkleTree::new();
+			for n in 0..33u8 {
+				tree.append(&[n]).unwrap();
+			}
+
+			for new_size in 1..=tree.size() {
+				let new_root = tree.root_at(new_size).unwrap();
+				for old_size in 1..=new_size {
+					let old_root = tree.root_at(old_size).unwrap();
+					let proof = tree.consistency_proof(old_size, new_size).unwrap();
+					assert!(
+						verify_consistency(old_size, new_size, &old_root, &new_root, &proof)
+							.unwrap()
+					);
+					assert!(
+						verify_consistency(old_size, new_size, &new_root, &old_root, &proof)
+							.is_err()
+							|| old_root == new_root
+					);
+				}
+			}
+		}
+
+		#[test]
+		fn test_verify_bad_input_err() {
+			let tree = reference_tree();
+			let old = tree.root_at(3).unwrap();
+			let new = tree.root().unwrap();
+			let proof = tree.consistency_proof(3, 8).unwrap();
+			assert!(verify_consistency(3, 8, &old, &new, &proof).unwrap());
+
+			assert!(verify_consistency(0, 8, &old, &new, &proof).is_err());
+			assert!(verify_consistency(9, 8, &old, &new, &proof).is_err());
+			assert!(verify_consistency(2, 8, &old, &new, &proof).is_err());
+			assert!(verify_consistency(3, 4, &old, &new, &proof).is_err());
+			assert!(verify_consistency(3, 8, &old, &new, &[]).is_err());
+			assert!(verify_consistency(3, 8, &old, &new, &proof[1..]).is_err());
+			assert!(verify_consistency(3, 8, &new, &new, &proof).is_err());
+			assert!(verify_consistency(3, 8, &old, &old, &proof).is_err());
+			assert!(verify_consistency(8, 8, &new, &new, &proof).is_err());
+			assert!(verify_consistency(8, 8, &new, &new, &[]).unwrap());
+		}
+	}
+}
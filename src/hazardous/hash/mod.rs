@@ -23,5 +23,74 @@
 /// BLAKE2b as specified in the [RFC 7693](https://tools.ietf.org/html/rfc7693).
 pub mod blake2b;
 
+/// Forward-secure hash chains using BLAKE2b.
+pub mod chain;
+
+/// SHA256 as specified in the [FIPS PUB 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+pub mod sha256;
+
 /// SHA512 as specified in the [FIPS PUB 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
 pub mod sha512;
+
+#[cfg(feature = "safe_api")]
+/// Append-only Merkle trees as specified in the [RFC 6962](https://tools.ietf.org/html/rfc6962).
+pub mod merkle;
+
+use crate::errors::{FinalizationCryptoError, UnknownCryptoError};
+
+/// A streaming hash function with a fixed block and output size.
+///
+/// This is implemented by the streaming states of SHA256 and SHA512, so that
+/// constructions such as HMAC can be written once and instantiated with any
+/// of them.
+pub trait HashFunction: Clone {
+	/// The block size of the hash function in bytes.
+	const BLOCKSIZE: usize;
+	/// The output size of the hash function in bytes.
+	const OUTSIZE: usize;
+
+	/// Initialize a new state.
+	fn init() -> Self;
+
+	#[must_use]
+	/// Update state with `data`. This can be called multiple times.
+	fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError>;
+
+	#[must_use]
+	/// Write the digest of the data processed so far to `dst_out`, which must
+	/// be `OUTSIZE` bytes.
+	fn finalize_into(&mut self, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError>;
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	fn digest_generic<H: HashFunction>(data: &[u8], dst_out: &mut [u8]) {
+		let mut state = H::init();
+		state.update(data).unwrap();
+		state.finalize_into(dst_out).unwrap();
+	}
+
+	mod test_finalize_into {
+		use super::*;
+
+		#[test]
+		fn test_same_as_digest() {
+			let mut sha256_out = [0u8; sha256::Sha256::OUTSIZE];
+			digest_generic::<sha256::Sha256>(b"Data", &mut sha256_out);
+			assert_eq!(&sha256_out[..], sha256::digest(b"Data").unwrap().as_bytes());
+
+			let mut sha512_out = [0u8; sha512::Sha512::OUTSIZE];
+			digest_generic::<sha512::Sha512>(b"Data", &mut sha512_out);
+			assert_eq!(&sha512_out[..], sha512::digest(b"Data").unwrap().as_bytes());
+		}
+
+		#[test]
+		fn test_wrong_length_err() {
+			let mut state = <sha256::Sha256 as HashFunction>::init();
+			assert!(state.finalize_into(&mut [0u8; 64]).is_err());
+		}
+	}
+}
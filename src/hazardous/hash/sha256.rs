@@ -0,0 +1,750 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `data`: The data to be hashed.
+//! - `dst_out`: Destination buffer for the digest.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `finalize()` is called twice without a `reset()` in between.
+//! - `update()` is called after `finalize()` without a `reset()` in between.
+//! - The length of `dst_out` is not `SHA256_OUTSIZE`.
+//!
+//! # Security:
+//! - SHA256 is vulnerable to length extension attacks.
+//!
+//! # Recommendation:
+//! - It is recommended to use BLAKE2b when possible. SHA256 is provided for
+//!   interoperability with protocols that require it.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::hash::sha256;
+//!
+//! // Using the streaming interface
+//! let mut state = sha256::init();
+//! state.update(b"Hello world").unwrap();
+//! let hash = state.finalize().unwrap();
+//!
+//! // Using the one-shot function
+//! let hash_one_shot = sha256::digest(b"Hello world").unwrap();
+//!
+//! assert_eq!(hash, hash_one_shot);
+//! ```
+
+use crate::{
+	endianness::{load_u32_into_be, store_u32_into_be},
+	errors::{FinalizationCryptoError, UnknownCryptoError},
+	hazardous::{
+		constants::{SHA256_BLOCKSIZE, SHA256_OUTSIZE},
+		hash::HashFunction,
+		types,
+	},
+};
+
+/// A type to represent the `Digest` that SHA256 returns.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 32 bytes.
+pub type Digest = types::PublicData<types::tag::Sha256Digest, SHA256_OUTSIZE>;
+
+#[rustfmt::skip]
+#[allow(clippy::unreadable_literal)]
+/// The SHA256 constants as defined in the FIPS 180-4.
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+#[rustfmt::skip]
+#[allow(clippy::unreadable_literal)]
+/// The SHA256 initial hash value H(0) as defined in the FIPS 180-4.
+const H0: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+#[derive(Clone)]
+/// SHA256 streaming state.
+pub struct Sha256 {
+	working_state: [u32; 8],
+	buffer: [u8; SHA256_BLOCKSIZE],
+	leftover: usize,
+	message_len: u64,
+	is_finalized: bool,
+}
+
+impl Drop for Sha256 {
+	fn drop(&mut self) {
+		use zeroize::Zeroize;
+		self.working_state.zeroize();
+		self.buffer.zeroize();
+		self.message_len.zeroize();
+	}
+}
+
+impl core::fmt::Debug for Sha256 {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(
+			f,
+			"Sha256 {{ working_state: [***OMITTED***], buffer: [***OMITTED***], leftover: {:?}, \
+			 message_len: {:?}, is_finalized: {:?} }}",
+			self.leftover, self.message_len, self.is_finalized
+		)
+	}
+}
+
+impl Sha256 {
+	#[inline]
+	/// The Ch function as specified in FIPS 180-4 section 4.1.2.
+	fn ch(&self, x: u32, y: u32, z: u32) -> u32 { z ^ (x & (y ^ z)) }
+
+	#[inline]
+	/// The Maj function as specified in FIPS 180-4 section 4.1.2.
+	fn maj(&self, x: u32, y: u32, z: u32) -> u32 { (x & y) | (z & (x | y)) }
+
+	#[inline]
+	/// The Big Sigma 0 function as specified in FIPS 180-4 section 4.1.2.
+	fn big_sigma_0(&self, x: u32) -> u32 {
+		(x.rotate_right(2)) ^ x.rotate_right(13) ^ x.rotate_right(22)
+	}
+
+	#[inline]
+	/// The Big Sigma 1 function as specified in FIPS 180-4 section 4.1.2.
+	fn big_sigma_1(&self, x: u32) -> u32 {
+		(x.rotate_right(6)) ^ x.rotate_right(11) ^ x.rotate_right(25)
+	}
+
+	#[inline]
+	/// The Small Sigma 0 function as specified in FIPS 180-4 section 4.1.2.
+	fn small_sigma_0(&self, x: u32) -> u32 { (x.rotate_right(7)) ^ x.rotate_right(18) ^ (x >> 3) }
+
+	#[inline]
+	/// The Small Sigma 1 function as specified in FIPS 180-4 section 4.1.2.
+	fn small_sigma_1(&self, x: u32) -> u32 { (x.rotate_right(17)) ^ x.rotate_right(19) ^ (x >> 10) }
+
+	#[inline]
+	#[allow(clippy::many_single_char_names)]
+	#[allow(clippy::too_many_arguments)]
+	/// Message compression adopted from [mbed TLS](https://tls.mbed.org/sha-256-source-code).
+	fn compress(
+		&self,
+		a: u32,
+		b: u32,
+		c: u32,
+		d: &mut u32,
+		e: u32,
+		f: u32,
+		g: u32,
+		h: &mut u32,
+		x: u32,
+		ki: u32,
+	) {
+		let temp1 = h
+			.wrapping_add(self.big_sigma_1(e))
+			.wrapping_add(self.ch(e, f, g))
+			.wrapping_add(ki)
+			.wrapping_add(x);
+
+		let temp2 = self.big_sigma_0(a).wrapping_add(self.maj(a, b, c));
+
+		*d = d.wrapping_add(temp1);
+		*h = temp1.wrapping_add(temp2);
+	}
+
+	#[inline]
+	#[rustfmt::skip]
+	#[allow(clippy::many_single_char_names)]
+	/// Process data in `self.buffer`.
+	fn process(&mut self) {
+		let mut w = [0u32; 64];
+		load_u32_into_be(&self.buffer, &mut w[..16]);
+
+		for t in 16..64 {
+			w[t] = self
+				.small_sigma_1(w[t - 2])
+				.wrapping_add(w[t - 7])
+				.wrapping_add(self.small_sigma_0(w[t - 15]))
+				.wrapping_add(w[t - 16]);
+		}
+
+		// Initialize working variables
+		let mut a = self.working_state[0];
+		let mut b = self.working_state[1];
+		let mut c = self.working_state[2];
+		let mut d = self.working_state[3];
+		let mut e = self.working_state[4];
+		let mut f = self.working_state[5];
+		let mut g = self.working_state[6];
+		let mut h = self.working_state[7];
+
+		let mut t = 0;
+		while t < 64 {
+			self.compress(a, b, c, &mut d, e, f, g, &mut h, w[t], K[t]); t += 1;
+			self.compress(h, a, b, &mut c, d, e, f, &mut g, w[t], K[t]); t += 1;
+			self.compress(g, h, a, &mut b, c, d, e, &mut f, w[t], K[t]); t += 1;
+			self.compress(f, g, h, &mut a, b, c, d, &mut e, w[t], K[t]); t += 1;
+			self.compress(e, f, g, &mut h, a, b, c, &mut d, w[t], K[t]); t += 1;
+			self.compress(d, e, f, &mut g, h, a, b, &mut c, w[t], K[t]); t += 1;
+			self.compress(c, d, e, &mut f, g, h, a, &mut b, w[t], K[t]); t += 1;
+			self.compress(b, c, d, &mut e, f, g, h, &mut a, w[t], K[t]); t += 1;
+		}
+
+		self.working_state[0] = self.working_state[0].wrapping_add(a);
+		self.working_state[1] = self.working_state[1].wrapping_add(b);
+		self.working_state[2] = self.working_state[2].wrapping_add(c);
+		self.working_state[3] = self.working_state[3].wrapping_add(d);
+		self.working_state[4] = self.working_state[4].wrapping_add(e);
+		self.working_state[5] = self.working_state[5].wrapping_add(f);
+		self.working_state[6] = self.working_state[6].wrapping_add(g);
+		self.working_state[7] = self.working_state[7].wrapping_add(h);
+	}
+
+	/// Reset to `init()` state.
+	pub fn reset(&mut self) {
+		self.working_state = H0;
+		self.buffer = [0u8; SHA256_BLOCKSIZE];
+		self.leftover = 0;
+		self.message_len = 0;
+		self.is_finalized = false;
+	}
+
+	#[inline]
+	/// Increment the message length during processing of data.
+	fn increment_mlen(&mut self, length: u64) {
+		// Multiply to get bit-sized representation of length.
+		// If either of these panic, the size limit is reached.
+		let len = length.checked_mul(8).unwrap();
+		self.message_len = self.message_len.checked_add(len).unwrap();
+	}
+
+	#[must_use]
+	/// Update state with `data`. This can be called multiple times.
+	pub fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
+		if self.is_finalized {
+			return Err(FinalizationCryptoError);
+		}
+		if data.is_empty() {
+			return Ok(());
+		}
+
+		let mut bytes = data;
+		// First fill up if there is leftover space
+		if self.leftover > 0 {
+			// Using .unwrap() since overflow should not happen in practice
+			let fill = SHA256_BLOCKSIZE.checked_sub(self.leftover).unwrap();
+
+			if bytes.len() < fill {
+				self.buffer[self.leftover..(self.leftover + bytes.len())].copy_from_slice(&bytes);
+				// Using .unwrap() since overflow should not happen in practice
+				self.leftover = self.leftover.checked_add(bytes.len()).unwrap();
+				self.increment_mlen(bytes.len() as u64);
+				return Ok(());
+			}
+
+			self.buffer[self.leftover..(self.leftover + fill)].copy_from_slice(&bytes[..fill]);
+			// Process data
+			self.process();
+			self.increment_mlen(fill as u64);
+			self.leftover = 0;
+			// Reduce by slice
+			bytes = &bytes[fill..];
+		}
+
+		while bytes.len() >= SHA256_BLOCKSIZE {
+			// Process data
+			self.buffer.copy_from_slice(&bytes[..SHA256_BLOCKSIZE]);
+			self.process();
+			self.increment_mlen(SHA256_BLOCKSIZE as u64);
+			// Reduce by slice
+			bytes = &bytes[SHA256_BLOCKSIZE..];
+		}
+
+		if !bytes.is_empty() {
+			self.buffer[self.leftover..(self.leftover + bytes.len())].copy_from_slice(&bytes);
+			// Using .unwrap() since overflow should not happen in practice
+			self.leftover = self.leftover.checked_add(bytes.len()).unwrap();
+			self.increment_mlen(bytes.len() as u64);
+		}
+
+		Ok(())
+	}
+
+	/// Finalize the state and write the digest to `digest_dst`, which must be
+	/// `SHA256_OUTSIZE` bytes.
+	fn finalize_to_dst(&mut self, digest_dst: &mut [u8]) -> Result<(), FinalizationCryptoError> {
+		if self.is_finalized {
+			return Err(FinalizationCryptoError);
+		}
+
+		self.is_finalized = true;
+
+		// self.leftover should not be greater than SHA256_BLOCKSIZE
+		// as that would have been processed in the update call
+		assert!(self.leftover < SHA256_BLOCKSIZE);
+		self.buffer[self.leftover] = 0x80;
+		// Using .unwrap() since overflow should not happen in practice
+		self.leftover = self.leftover.checked_add(1).unwrap();
+
+		for itm in self.buffer.iter_mut().skip(self.leftover) {
+			*itm = 0;
+		}
+
+		// Check for available space for length padding
+		if (SHA256_BLOCKSIZE - self.leftover) < 8 {
+			self.process();
+			for itm in self.buffer.iter_mut().take(self.leftover) {
+				*itm = 0;
+			}
+		}
+
+		// Pad with length
+		self.buffer[SHA256_BLOCKSIZE - 8..SHA256_BLOCKSIZE]
+			.copy_from_slice(&self.message_len.to_be_bytes());
+
+		self.process();
+		store_u32_into_be(&self.working_state, digest_dst);
+
+		Ok(())
+	}
+
+	#[must_use]
+	/// Return a SHA256 digest.
+	pub fn finalize(&mut self) -> Result<Digest, FinalizationCryptoError> {
+		let mut digest = [0u8; SHA256_OUTSIZE];
+		self.finalize_to_dst(&mut digest)?;
+
+		Ok(Digest::from_slice(&digest)?)
+	}
+
+	#[must_use]
+	/// Write the SHA256 digest to `dst_out`, which must be `SHA256_OUTSIZE`
+	/// bytes.
+	pub fn finalize_into(&mut self, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		if dst_out.len() != SHA256_OUTSIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(self.finalize_to_dst(dst_out)?)
+	}
+}
+
+#[must_use]
+/// Initialize a `Sha256` struct.
+pub fn init() -> Sha256 {
+	Sha256 {
+		working_state: H0,
+		buffer: [0u8; SHA256_BLOCKSIZE],
+		leftover: 0,
+		message_len: 0,
+		is_finalized: false,
+	}
+}
+
+#[must_use]
+/// Calculate a SHA256 digest of some `data`.
+pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
+	let mut state = init();
+	state.update(data)?;
+
+	Ok(state.finalize()?)
+}
+
+impl HashFunction for Sha256 {
+	const BLOCKSIZE: usize = SHA256_BLOCKSIZE;
+	const OUTSIZE: usize = SHA256_OUTSIZE;
+
+	fn init() -> Self { init() }
+
+	fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
+		Sha256::update(self, data)
+	}
+
+	fn finalize_into(&mut self, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		Sha256::finalize_into(self, dst_out)
+	}
+}
+
+#[cfg(feature = "safe_api")]
+/// Update the state with the data written. This makes it possible to use
+/// `std::io::copy()` to process a reader, such as a file.
+///
+/// An error is returned if writing to a finalized state.
+impl std::io::Write for Sha256 {
+	fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+		self.update(data)?;
+		Ok(data.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+#[cfg(test)]
+/// Compare two Sha256 state objects to check if their fields
+/// are the same.
+pub fn compare_sha256_states(state_1: &Sha256, state_2: &Sha256) {
+	assert_eq!(state_1.working_state, state_2.working_state);
+	assert_eq!(state_1.buffer[..], state_2.buffer[..]);
+	assert_eq!(state_1.leftover, state_2.leftover);
+	assert_eq!(state_1.message_len, state_2.message_len);
+	assert_eq!(state_1.is_finalized, state_2.is_finalized);
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_reset {
+		use super::*;
+
+		#[test]
+		fn test_double_reset_ok() {
+			let data = "what do ya want for nothing?".as_bytes();
+
+			let mut state = init();
+			state.update(data).unwrap();
+			let _ = state.finalize().unwrap();
+			state.reset();
+			state.reset();
+		}
+	}
+
+	mod test_update {
+		use super::*;
+
+		#[test]
+		fn test_update_after_finalize_with_reset_ok() {
+			let data = "what do ya want for nothing?".as_bytes();
+
+			let mut state = init();
+			state.update(data).unwrap();
+			let _ = state.finalize().unwrap();
+			state.reset();
+			state.update(data).unwrap();
+		}
+
+		#[test]
+		/// Related bug: https://github.com/brycx/orion/issues/28
+		fn test_update_after_finalize_err() {
+			let data = "what do ya want for nothing?".as_bytes();
+
+			let mut state = init();
+			state.update(data).unwrap();
+			let _ = state.finalize().unwrap();
+			assert!(state.update(data).is_err());
+		}
+	}
+
+	mod test_finalize {
+		use super::*;
+
+		#[test]
+		fn test_digest_into_array() {
+			let data = b"what do ya want for nothing?";
+			let bytes: [u8; SHA256_OUTSIZE] = digest(data).unwrap().into();
+			assert_eq!(&bytes[..], digest(data).unwrap().as_bytes());
+		}
+
+		#[test]
+		fn test_double_finalize_with_reset_no_update_ok() {
+			let data = "what do ya want for nothing?".as_bytes();
+
+			let mut state = init();
+			state.update(data).unwrap();
+			let _ = state.finalize().unwrap();
+			state.reset();
+			let _ = state.finalize().unwrap();
+		}
+
+		#[test]
+		fn test_double_finalize_with_reset_ok() {
+			let data = "what do ya want for nothing?".as_bytes();
+
+			let mut state = init();
+			state.update(data).unwrap();
+			let one = state.finalize().unwrap();
+			state.reset();
+			state.update(data).unwrap();
+			let two = state.finalize().unwrap();
+			assert_eq!(one.as_bytes(), two.as_bytes());
+		}
+
+		#[test]
+		fn test_double_finalize_err() {
+			let data = "what do ya want for nothing?".as_bytes();
+
+			let mut state = init();
+			state.update(data).unwrap();
+			let _ = state.finalize().unwrap();
+			assert!(state.finalize().is_err());
+		}
+	}
+
+	#[cfg(feature = "safe_api")]
+	mod test_io_write {
+		use super::*;
+		use std::io::{copy, Write};
+
+		#[test]
+		fn test_write_same_as_update() {
+			let data = [0u8; 356];
+
+			let mut state = init();
+			let mut reader: &[u8] = &data;
+			assert_eq!(copy(&mut reader, &mut state).unwrap(), 356);
+			state.flush().unwrap();
+
+			let mut state_update = init();
+			state_update.update(&data).unwrap();
+
+			assert_eq!(state.finalize().unwrap(), state_update.finalize().unwrap());
+		}
+
+		#[test]
+		fn test_write_after_finalize_err() {
+			let mut state = init();
+			let _ = state.finalize().unwrap();
+			assert!(state.write(b"Some data").is_err());
+		}
+	}
+
+	mod test_streaming_interface {
+		use super::*;
+
+		/// Related bug: https://github.com/brycx/orion/issues/46
+		/// Testing different usage combinations of init(), update(),
+		/// finalize() and reset() produce the same Digest.
+		fn produces_same_hash(data: &[u8]) {
+			// init(), update(), finalize()
+			let mut state_1 = init();
+			state_1.update(data).unwrap();
+			let res_1 = state_1.finalize().unwrap();
+
+			// init(), reset(), update(), finalize()
+			let mut state_2 = init();
+			state_2.reset();
+			state_2.update(data).unwrap();
+			let res_2 = state_2.finalize().unwrap();
+
+			// init(), update(), reset(), update(), finalize()
+			let mut state_3 = init();
+			state_3.update(data).unwrap();
+			state_3.reset();
+			state_3.update(data).unwrap();
+			let res_3 = state_3.finalize().unwrap();
+
+			// init(), update(), finalize(), reset(), update(), finalize()
+			let mut state_4 = init();
+			state_4.update(data).unwrap();
+			let _ = state_4.finalize().unwrap();
+			state_4.reset();
+			state_4.update(data).unwrap();
+			let res_4 = state_4.finalize().unwrap();
+
+			assert_eq!(res_1, res_2);
+			assert_eq!(res_2, res_3);
+			assert_eq!(res_3, res_4);
+
+			// Tests for the assumption that returning Ok() on empty update() calls
+			// with streaming API's, gives the correct result. This is done by testing
+			// the reasoning that if update() is empty, returns Ok(), it is the same as
+			// calling init() -> finalize(). i.e not calling update() at all.
+			if data.is_empty() {
+				// init(), finalize()
+				let mut state_5 = init();
+				let res_5 = state_5.finalize().unwrap();
+
+				// init(), reset(), finalize()
+				let mut state_6 = init();
+				state_6.reset();
+				let res_6 = state_6.finalize().unwrap();
+
+				// init(), update(), reset(), finalize()
+				let mut state_7 = init();
+				state_7.update(b"Wrong data").unwrap();
+				state_7.reset();
+				let res_7 = state_7.finalize().unwrap();
+
+				assert_eq!(res_4, res_5);
+				assert_eq!(res_5, res_6);
+				assert_eq!(res_6, res_7);
+			}
+		}
+
+		/// Related bug: https://github.com/brycx/orion/issues/46
+		/// Testing different usage combinations of init(), update(),
+		/// finalize() and reset() produce the same Digest.
+		fn produces_same_state(data: &[u8]) {
+			// init()
+			let state_1 = init();
+
+			// init(), reset()
+			let mut state_2 = init();
+			state_2.reset();
+
+			// init(), update(), reset()
+			let mut state_3 = init();
+			state_3.update(data).unwrap();
+			state_3.reset();
+
+			// init(), update(), finalize(), reset()
+			let mut state_4 = init();
+			state_4.update(data).unwrap();
+			let _ = state_4.finalize().unwrap();
+			state_4.reset();
+
+			compare_sha256_states(&state_1, &state_2);
+			compare_sha256_states(&state_2, &state_3);
+			compare_sha256_states(&state_3, &state_4);
+		}
+
+		#[test]
+		/// Related bug: https://github.com/brycx/orion/issues/46
+		fn test_produce_same_state() { produces_same_state(b"Tests"); }
+
+		#[test]
+		/// Related bug: https://github.com/brycx/orion/issues/46
+		fn test_produce_same_hash() { produces_same_hash(b"Tests"); }
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		// Test for issues when incrementally processing data
+		// with leftover
+		fn test_streaming_consistency() {
+			for len in 0..SHA256_BLOCKSIZE * 4 {
+				let data = vec![0u8; len];
+				let mut state = init();
+				let mut other_data: Vec<u8> = Vec::new();
+
+				other_data.extend_from_slice(&data);
+				state.update(&data).unwrap();
+
+				if data.len() > SHA256_BLOCKSIZE {
+					other_data.extend_from_slice(b"");
+					state.update(b"").unwrap();
+				}
+				if data.len() > SHA256_BLOCKSIZE * 2 {
+					other_data.extend_from_slice(b"Extra");
+					state.update(b"Extra").unwrap();
+				}
+				if data.len() > SHA256_BLOCKSIZE * 3 {
+					other_data.extend_from_slice(&[0u8; 256]);
+					state.update(&[0u8; 256]).unwrap();
+				}
+
+				let digest_one_shot = digest(&other_data).unwrap();
+
+				assert!(state.finalize().unwrap().as_bytes() == digest_one_shot.as_bytes());
+			}
+		}
+		// Proptests. Only exectued when NOT testing no_std.
+		#[cfg(feature = "safe_api")]
+		mod proptest {
+			use super::*;
+
+			quickcheck! {
+				/// Related bug: https://github.com/brycx/orion/issues/46
+				/// Test different streaming state usage patterns.
+				fn prop_same_hash_different_usage(data: Vec<u8>) -> bool {
+					// Will panic on incorrect results.
+					produces_same_hash(&data[..]);
+
+					true
+				}
+			}
+
+			quickcheck! {
+				/// Related bug: https://github.com/brycx/orion/issues/46
+				/// Test different streaming state usage patterns.
+				fn prop_same_state_different_usage(data: Vec<u8>) -> bool {
+					// Will panic on incorrect results.
+					produces_same_state(&data[..]);
+
+					true
+				}
+			}
+
+			quickcheck! {
+				/// Using the one-shot function should always produce the
+				/// same result as when using the streaming interface.
+				fn prop_digest_same_as_streaming(data: Vec<u8>) -> bool {
+					let mut state = init();
+					state.update(&data[..]).unwrap();
+					let stream = state.finalize().unwrap();
+					let one_shot = digest(&data[..]).unwrap();
+
+					(one_shot == stream)
+				}
+			}
+		}
+	}
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+	// One function tested per submodule.
+
+	mod test_increment_mlen {
+		use super::*;
+
+		#[test]
+		fn test_mlen_increase_values() {
+			let mut context = init();
+
+			context.increment_mlen(1);
+			assert!(context.message_len == 8u64);
+			context.increment_mlen(17);
+			assert!(context.message_len == 144u64);
+			context.increment_mlen(12);
+			assert!(context.message_len == 240u64);
+		}
+
+		#[test]
+		#[should_panic]
+		fn test_panic_on_overflow() {
+			let mut context = init();
+			context.message_len = u64::max_value() - 7;
+			// u64::max_value() - 7, to leave so that the length represented
+			// in bites should overflow by exactly one.
+
+			context.increment_mlen(1);
+		}
+
+		#[test]
+		#[should_panic]
+		fn test_panic_on_shift_overflow() {
+			let mut context = init();
+			context.increment_mlen(u64::max_value());
+		}
+	}
+}
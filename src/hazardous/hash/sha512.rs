@@ -22,14 +22,20 @@
 
 //! # Parameters:
 //! - `data`: The data to be hashed.
+//! - `dst_out`: Destination buffer for the digest.
 //!
 //! # Errors:
 //! An error will be returned if:
 //! - `finalize()` is called twice without a `reset()` in between.
 //! - `update()` is called after `finalize()` without a `reset()` in between.
+//! - The length of `dst_out` is not `SHA512_OUTSIZE`.
+//! - `from_state_bytes()` is called with bytes that are not a state serialized
+//!   by `to_state_bytes()`.
 //!
 //! # Security:
 //! - SHA512 is vulnerable to length extension attacks.
+//! - The bytes returned by `to_state_bytes()` contain all data that has not
+//!   yet been processed, and must be protected the same way as that data.
 //!
 //! # Recommendation:
 //! - It is recommended to use BLAKE2b when possible.
@@ -47,22 +53,47 @@
 //! let hash_one_shot = sha512::digest(b"Hello world").unwrap();
 //!
 //! assert_eq!(hash, hash_one_shot);
+//!
+//! // Writing the digest to a caller-provided buffer
+//! let mut hash_buf = [0u8; 64];
+//! sha512::digest_into(b"Hello world", &mut hash_buf).unwrap();
+//! assert_eq!(&hash_buf[..], hash.as_bytes());
+//! ```
+//!
+//! With the `serializable_state` feature, a state can be saved and resumed
+//! later:
+//! ```
+//! # #[cfg(feature = "serializable_state")] {
+//! use orion::hazardous::hash::sha512;
+//!
+//! let mut state = sha512::init();
+//! state.update(b"Hello ").unwrap();
+//! let checkpoint = state.to_state_bytes();
+//!
+//! let mut resumed = sha512::Sha512::from_state_bytes(&checkpoint).unwrap();
+//! resumed.update(b"world").unwrap();
+//! assert_eq!(resumed.finalize().unwrap(), sha512::digest(b"Hello world").unwrap());
+//! # }
 //! ```
 
 use crate::{
 	endianness::{load_u64_into_be, store_u64_into_be},
 	errors::{FinalizationCryptoError, UnknownCryptoError},
-	hazardous::constants::{SHA512_BLOCKSIZE, SHA512_OUTSIZE},
+	hazardous::{
+		constants::{SHA512_BLOCKSIZE, SHA512_OUTSIZE},
+		hash::HashFunction,
+		types,
+	},
 };
+#[cfg(feature = "serializable_state")]
+use crate::endianness::{load_u64_into_le, store_u64_into_le};
 
-construct_nonce_no_generator! {
-	/// A type to represent the `Digest` that SHA512 returns.
-	///
-	/// # Exceptions:
-	/// An exception will be thrown if:
-	/// - `slice` is not 64 bytes.
-	(Digest, SHA512_OUTSIZE)
-}
+/// A type to represent the `Digest` that SHA512 returns.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 64 bytes.
+pub type Digest = types::PublicData<types::tag::Sha512Digest, SHA512_OUTSIZE>;
 
 #[rustfmt::skip]
 #[allow(clippy::unreadable_literal)]
@@ -98,6 +129,18 @@ const H0: [u64; 8] = [
     0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
 ];
 
+#[cfg(feature = "serializable_state")]
+/// Version of the format produced by `to_state_bytes()`.
+const STATE_VERSION: u8 = 1;
+
+#[cfg(feature = "serializable_state")]
+/// Identifier of SHA512 in the format produced by `to_state_bytes()`.
+const STATE_ID: u8 = 1;
+
+#[cfg(feature = "serializable_state")]
+/// Length of a serialized `Sha512`, without the version and identifier.
+pub(crate) const STATE_FIELDS_LEN: usize = 64 + SHA512_BLOCKSIZE + 1 + 16 + 1;
+
 #[derive(Clone)]
 /// SHA512 streaming state.
 pub struct Sha512 {
@@ -234,6 +277,79 @@ impl Sha512 {
 		self.working_state[7] = self.working_state[7].wrapping_add(h);
 	}
 
+	#[cfg(feature = "serializable_state")]
+	/// Append the fields of the state to `dst`. The fields are, in order:
+	/// the working state as little-endian `u64`s, the buffer, the leftover
+	/// as a single byte, the message length as little-endian `u64`s and
+	/// whether the state is finalized as a single byte.
+	pub(crate) fn append_state_fields(&self, dst: &mut Vec<u8>) {
+		let mut fields = [0u8; STATE_FIELDS_LEN];
+		store_u64_into_le(&self.working_state, &mut fields[..64]);
+		fields[64..192].copy_from_slice(&self.buffer);
+		fields[192] = self.leftover as u8;
+		store_u64_into_le(&self.message_len, &mut fields[193..209]);
+		fields[209] = self.is_finalized as u8;
+
+		dst.extend_from_slice(&fields);
+		use zeroize::Zeroize;
+		fields.zeroize();
+	}
+
+	#[cfg(feature = "serializable_state")]
+	/// Load a state from fields serialized with `append_state_fields()`.
+	pub(crate) fn from_state_fields(src: &[u8]) -> Result<Sha512, UnknownCryptoError> {
+		if src.len() != STATE_FIELDS_LEN || src[209] > 1 {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut state = init();
+		load_u64_into_le(&src[..64], &mut state.working_state);
+		state.buffer.copy_from_slice(&src[64..192]);
+		state.leftover = src[192] as usize;
+		load_u64_into_le(&src[193..209], &mut state.message_len);
+		state.is_finalized = src[209] == 1;
+
+		// A state that is not finalized always has its full blocks processed,
+		// and the leftover must then match the message length.
+		if state.leftover > SHA512_BLOCKSIZE
+			|| (!state.is_finalized
+				&& state.leftover != ((state.message_len[1] >> 3) % SHA512_BLOCKSIZE as u64) as usize)
+		{
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(state)
+	}
+
+	#[must_use]
+	#[cfg(feature = "serializable_state")]
+	/// Serialize the state, so that it can be resumed with `from_state_bytes()`.
+	///
+	/// The format starts with a version byte and an identifier byte for
+	/// SHA512, followed by the fields of the state.
+	pub fn to_state_bytes(&self) -> Vec<u8> {
+		let mut state_bytes = Vec::with_capacity(2 + STATE_FIELDS_LEN);
+		state_bytes.push(STATE_VERSION);
+		state_bytes.push(STATE_ID);
+		self.append_state_fields(&mut state_bytes);
+
+		state_bytes
+	}
+
+	#[must_use]
+	#[cfg(feature = "serializable_state")]
+	/// Resume a state serialized with `to_state_bytes()`.
+	pub fn from_state_bytes(state_bytes: &[u8]) -> Result<Sha512, UnknownCryptoError> {
+		if state_bytes.len() != 2 + STATE_FIELDS_LEN
+			|| state_bytes[0] != STATE_VERSION
+			|| state_bytes[1] != STATE_ID
+		{
+			return Err(UnknownCryptoError);
+		}
+
+		Sha512::from_state_fields(&state_bytes[2..])
+	}
+
 	/// Reset to `init()` state.
 	pub fn reset(&mut self) {
 		self.working_state = H0;
@@ -310,9 +426,9 @@ impl Sha512 {
 		Ok(())
 	}
 
-	#[must_use]
-	/// Return a SHA512 digest.
-	pub fn finalize(&mut self) -> Result<Digest, FinalizationCryptoError> {
+	/// Finalize the state and write the digest to `digest_dst`, which must be
+	/// `SHA512_OUTSIZE` bytes.
+	fn finalize_to_dst(&mut self, digest_dst: &mut [u8]) -> Result<(), FinalizationCryptoError> {
 		if self.is_finalized {
 			return Err(FinalizationCryptoError);
 		}
@@ -345,12 +461,30 @@ impl Sha512 {
 			.copy_from_slice(&self.message_len[1].to_be_bytes());
 
 		self.process();
+		store_u64_into_be(&self.working_state, digest_dst);
 
-		let mut digest = [0u8; 64];
-		store_u64_into_be(&self.working_state, &mut digest);
+		Ok(())
+	}
+
+	#[must_use]
+	/// Return a SHA512 digest.
+	pub fn finalize(&mut self) -> Result<Digest, FinalizationCryptoError> {
+		let mut digest = [0u8; SHA512_OUTSIZE];
+		self.finalize_to_dst(&mut digest)?;
 
 		Ok(Digest::from_slice(&digest)?)
 	}
+
+	#[must_use]
+	/// Write the SHA512 digest to `dst_out`, which must be `SHA512_OUTSIZE`
+	/// bytes.
+	pub fn finalize_into(&mut self, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		if dst_out.len() != SHA512_OUTSIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(self.finalize_to_dst(dst_out)?)
+	}
 }
 
 #[must_use]
@@ -374,6 +508,44 @@ pub fn digest(data: &[u8]) -> Result<Digest, UnknownCryptoError> {
 	Ok(state.finalize()?)
 }
 
+#[must_use]
+/// Calculate a SHA512 digest of some `data` and write it to `dst_out`, which
+/// must be `SHA512_OUTSIZE` bytes.
+pub fn digest_into(data: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+	let mut state = init();
+	state.update(data)?;
+	state.finalize_into(dst_out)
+}
+
+impl HashFunction for Sha512 {
+	const BLOCKSIZE: usize = SHA512_BLOCKSIZE;
+	const OUTSIZE: usize = SHA512_OUTSIZE;
+
+	fn init() -> Self { init() }
+
+	fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
+		Sha512::update(self, data)
+	}
+
+	fn finalize_into(&mut self, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		Sha512::finalize_into(self, dst_out)
+	}
+}
+
+#[cfg(feature = "safe_api")]
+/// Update the state with the data written. This makes it possible to use
+/// `std::io::copy()` to process a reader, such as a file.
+///
+/// An error is returned if writing to a finalized state.
+impl std::io::Write for Sha512 {
+	fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+		self.update(data)?;
+		Ok(data.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
 #[cfg(test)]
 /// Compare two Sha512 state objects to check if their fields
 /// are the same.
@@ -435,6 +607,13 @@ mod public {
 	mod test_finalize {
 		use super::*;
 
+		#[test]
+		fn test_digest_into_array() {
+			let data = b"what do ya want for nothing?";
+			let bytes: [u8; SHA512_OUTSIZE] = digest(data).unwrap().into();
+			assert_eq!(&bytes[..], digest(data).unwrap().as_bytes());
+		}
+
 		#[test]
 		fn test_double_finalize_with_reset_no_update_ok() {
 			let data = "what do ya want for nothing?".as_bytes();
@@ -471,6 +650,121 @@ mod public {
 
 	}
 
+	mod test_finalize_into {
+		use super::*;
+
+		#[test]
+		fn test_same_as_finalize() {
+			let data = b"what do ya want for nothing?";
+
+			let mut dst = [0u8; SHA512_OUTSIZE];
+			digest_into(data, &mut dst).unwrap();
+			assert_eq!(&dst[..], digest(data).unwrap().as_bytes());
+
+			let mut state = init();
+			state.update(data).unwrap();
+			let mut dst_state = [0u8; SHA512_OUTSIZE];
+			state.finalize_into(&mut dst_state).unwrap();
+			assert_eq!(dst, dst_state);
+		}
+
+		#[test]
+		fn test_length_err() {
+			assert!(digest_into(b"Data", &mut [0u8; SHA512_OUTSIZE - 1]).is_err());
+			assert!(digest_into(b"Data", &mut [0u8; SHA512_OUTSIZE + 1]).is_err());
+			assert!(digest_into(b"Data", &mut [0u8; 0]).is_err());
+		}
+
+		#[test]
+		fn test_length_err_does_not_finalize() {
+			let mut state = init();
+			assert!(state.finalize_into(&mut [0u8; 32]).is_err());
+			assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_ok());
+		}
+
+		#[test]
+		fn test_double_finalize_err() {
+			let mut state = init();
+			let _ = state.finalize().unwrap();
+			assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_err());
+		}
+	}
+
+	#[cfg(feature = "safe_api")]
+	mod test_io_write {
+		use super::*;
+		use std::io::{copy, Write};
+
+		#[test]
+		fn test_write_same_as_update() {
+			let data = [0u8; 356];
+
+			let mut state = init();
+			let mut reader: &[u8] = &data;
+			assert_eq!(copy(&mut reader, &mut state).unwrap(), 356);
+			state.flush().unwrap();
+
+			let mut state_update = init();
+			state_update.update(&data).unwrap();
+
+			assert_eq!(state.finalize().unwrap(), state_update.finalize().unwrap());
+		}
+
+		#[test]
+		fn test_write_after_finalize_err() {
+			let mut state = init();
+			let _ = state.finalize().unwrap();
+			assert!(state.write(b"Some data").is_err());
+		}
+	}
+
+	#[cfg(feature = "serializable_state")]
+	mod test_state_bytes {
+		use super::*;
+
+		#[test]
+		fn test_resume_same_digest() {
+			let data = [0u8; 300];
+			let expected = digest(&data).unwrap();
+
+			for split in [0, 1, 127, 128, 129, 256, 300].iter() {
+				let mut state = init();
+				state.update(&data[..*split]).unwrap();
+				let mut resumed = Sha512::from_state_bytes(&state.to_state_bytes()).unwrap();
+				compare_sha512_states(&state, &resumed);
+				resumed.update(&data[*split..]).unwrap();
+				assert_eq!(resumed.finalize().unwrap(), expected);
+			}
+		}
+
+		#[test]
+		fn test_resume_finalized() {
+			let mut state = init();
+			let _ = state.finalize().unwrap();
+			let mut resumed = Sha512::from_state_bytes(&state.to_state_bytes()).unwrap();
+			assert!(resumed.update(b"").is_err());
+			resumed.reset();
+			assert_eq!(resumed.finalize().unwrap(), digest(b"").unwrap());
+		}
+
+		#[test]
+		fn test_invalid_state_bytes_err() {
+			let mut state = init();
+			state.update(b"Some data").unwrap();
+			let state_bytes = state.to_state_bytes();
+			assert!(Sha512::from_state_bytes(&state_bytes).is_ok());
+
+			assert!(Sha512::from_state_bytes(&state_bytes[..state_bytes.len() - 1]).is_err());
+			assert!(Sha512::from_state_bytes(&[]).is_err());
+			// Version, identifier, leftover, message length and is_finalized.
+			for idx in [0, 1, 194, 203, 211].iter() {
+				let mut modified = state_bytes.clone();
+				modified[*idx] ^= 8;
+				assert!(Sha512::from_state_bytes(&modified).is_err());
+			}
+		}
+	}
+
 	mod test_streaming_interface {
 		use super::*;
 
@@ -33,6 +33,7 @@
 //! An error will be returned if:
 //! - The length of `dst_out` is less than 1.
 //! - The length of `dst_out` is greater than 255 * hash_output_size_in_bytes.
+//!   This is 16320 for SHA512 and 8160 for SHA256.
 //! - The derived key does not match the expected when verifying.
 //!
 //! # Security:
@@ -57,33 +58,46 @@
 //!
 //! assert!(hkdf::verify(&exp_okm, &salt, "IKM".as_bytes(), None, &mut okm_out).unwrap());
 //! ```
+//!
+//! The functions in `hkdf` use HMAC-SHA512. HKDF with HMAC-SHA256 is available
+//! in `hkdf::sha256`, and `derive_key_with()` can be used with any hash
+//! function that HMAC supports:
+//! ```
+//! use orion::hazardous::{hash::sha256::Sha256, kdf::hkdf};
+//!
+//! let mut okm_out = [0u8; 32];
+//! hkdf::sha256::derive_key(b"Salt", b"IKM", None, &mut okm_out).unwrap();
+//!
+//! let mut okm_generic = [0u8; 32];
+//! hkdf::derive_key_with::<Sha256>(b"Salt", b"IKM", None, &mut okm_generic).unwrap();
+//! assert_eq!(okm_out, okm_generic);
+//! ```
 
 use crate::{
 	errors::{UnknownCryptoError, ValidationCryptoError},
-	hazardous::{
-		constants::SHA512_OUTSIZE,
-		mac::hmac::{self, SecretKey},
-	},
+	hazardous::mac::hmac::{Hmac, HmacHash},
 	util,
 };
 
+pub use self::sha512::{derive_key, expand, extract, verify};
+
 #[must_use]
-/// The HKDF extract step.
-pub fn extract(salt: &[u8], ikm: &[u8]) -> Result<hmac::Tag, UnknownCryptoError> {
-	let mut prk = hmac::init(&SecretKey::from_slice(salt)?);
+/// The HKDF extract step, using HMAC with the hash function `H`.
+pub fn extract_with<H: HmacHash>(salt: &[u8], ikm: &[u8]) -> Result<H::Tag, UnknownCryptoError> {
+	let mut prk = Hmac::<H>::new(&H::key_from_slice(salt)?);
 	prk.update(ikm)?;
 
 	Ok(prk.finalize()?)
 }
 
 #[must_use]
-/// The HKDF expand step.
-pub fn expand(
-	prk: &hmac::Tag,
+/// The HKDF expand step, using HMAC with the hash function `H`.
+pub fn expand_with<H: HmacHash>(
+	prk: &H::Tag,
 	info: Option<&[u8]>,
 	dst_out: &mut [u8],
 ) -> Result<(), UnknownCryptoError> {
-	if dst_out.len() > 16320 {
+	if dst_out.len() > 255 * H::OUTSIZE {
 		return Err(UnknownCryptoError);
 	}
 	if dst_out.is_empty() {
@@ -95,18 +109,18 @@ pub fn expand(
 		None => &[0u8; 0],
 	};
 
-	let mut hmac = hmac::init(&hmac::SecretKey::from_slice(&prk.unprotected_as_bytes())?);
+	let mut hmac = Hmac::<H>::new(&H::key_from_slice(H::unprotected_tag_bytes(prk))?);
 	let okm_len = dst_out.len();
 
-	for (idx, hlen_block) in dst_out.chunks_mut(SHA512_OUTSIZE).enumerate() {
+	for (idx, hlen_block) in dst_out.chunks_mut(H::OUTSIZE).enumerate() {
 		let block_len = hlen_block.len();
 
 		hmac.update(optional_info)?;
 		hmac.update(&[idx as u8 + 1_u8])?;
-		hlen_block.copy_from_slice(&hmac.finalize()?.unprotected_as_bytes()[..block_len]);
+		hlen_block.copy_from_slice(&H::unprotected_tag_bytes(&hmac.finalize()?)[..block_len]);
 
 		// Check if it's the last iteration, if yes don't process anything
-		if block_len < SHA512_OUTSIZE || (block_len * (idx + 1) == okm_len) {
+		if block_len < H::OUTSIZE || (block_len * (idx + 1) == okm_len) {
 			break;
 		} else {
 			hmac.reset();
@@ -118,28 +132,30 @@ pub fn expand(
 }
 
 #[must_use]
-/// Combine `extract` and `expand` to return a derived key.
-pub fn derive_key(
+/// Combine `extract_with` and `expand_with` to return a derived key, using
+/// HMAC with the hash function `H`.
+pub fn derive_key_with<H: HmacHash>(
 	salt: &[u8],
 	ikm: &[u8],
 	info: Option<&[u8]>,
 	dst_out: &mut [u8],
 ) -> Result<(), UnknownCryptoError> {
-	expand(&extract(salt, ikm)?, info, dst_out)?;
+	expand_with::<H>(&extract_with::<H>(salt, ikm)?, info, dst_out)?;
 
 	Ok(())
 }
 
 #[must_use]
-/// Verify a derived key in constant time.
-pub fn verify(
+/// Verify a derived key in constant time, using HMAC with the hash function
+/// `H`.
+pub fn verify_with<H: HmacHash>(
 	expected: &[u8],
 	salt: &[u8],
 	ikm: &[u8],
 	info: Option<&[u8]>,
 	dst_out: &mut [u8],
 ) -> Result<bool, ValidationCryptoError> {
-	expand(&extract(salt, ikm)?, info, dst_out)?;
+	derive_key_with::<H>(salt, ikm, info, dst_out)?;
 
 	if util::secure_cmp(&dst_out, expected).is_err() {
 		Err(ValidationCryptoError)
@@ -148,7 +164,157 @@ pub fn verify(
 	}
 }
 
-// Testing public functions in the module.
+/// HKDF-HMAC-SHA512. These are also available directly in `hkdf`.
+pub mod sha512 {
+	use crate::{
+		errors::{UnknownCryptoError, ValidationCryptoError},
+		hazardous::{hash::sha512::Sha512, mac::hmac},
+	};
+
+	#[must_use]
+	/// The HKDF extract step.
+	pub fn extract(salt: &[u8], ikm: &[u8]) -> Result<hmac::Tag, UnknownCryptoError> {
+		super::extract_with::<Sha512>(salt, ikm)
+	}
+
+	#[must_use]
+	/// The HKDF expand step.
+	pub fn expand(
+		prk: &hmac::Tag,
+		info: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		super::expand_with::<Sha512>(prk, info, dst_out)
+	}
+
+	#[must_use]
+	/// Combine `extract` and `expand` to return a derived key.
+	pub fn derive_key(
+		salt: &[u8],
+		ikm: &[u8],
+		info: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		super::derive_key_with::<Sha512>(salt, ikm, info, dst_out)
+	}
+
+	#[must_use]
+	/// Verify a derived key in constant time.
+	pub fn verify(
+		expected: &[u8],
+		salt: &[u8],
+		ikm: &[u8],
+		info: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<bool, ValidationCryptoError> {
+		super::verify_with::<Sha512>(expected, salt, ikm, info, dst_out)
+	}
+}
+
+/// HKDF-HMAC-SHA256.
+pub mod sha256 {
+	use crate::{
+		errors::{UnknownCryptoError, ValidationCryptoError},
+		hazardous::{hash::sha256::Sha256, mac::hmac},
+	};
+
+	#[must_use]
+	/// The HKDF extract step.
+	pub fn extract(salt: &[u8], ikm: &[u8]) -> Result<hmac::sha256::Tag, UnknownCryptoError> {
+		super::extract_with::<Sha256>(salt, ikm)
+	}
+
+	#[must_use]
+	/// The HKDF expand step.
+	pub fn expand(
+		prk: &hmac::sha256::Tag,
+		info: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		super::expand_with::<Sha256>(prk, info, dst_out)
+	}
+
+	#[must_use]
+	/// Combine `extract` and `expand` to return a derived key.
+	pub fn derive_key(
+		salt: &[u8],
+		ikm: &[u8],
+		info: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		super::derive_key_with::<Sha256>(salt, ikm, info, dst_out)
+	}
+
+	#[must_use]
+	/// Verify a derived key in constant time.
+	pub fn verify(
+		expected: &[u8],
+		salt: &[u8],
+		ikm: &[u8],
+		info: Option<&[u8]>,
+		dst_out: &mut [u8],
+	) -> Result<bool, ValidationCryptoError> {
+		super::verify_with::<Sha256>(expected, salt, ikm, info, dst_out)
+	}
+
+	// Testing public functions in the module.
+	#[cfg(test)]
+	mod public {
+		use super::*;
+
+		#[test]
+		fn test_rfc5869_test_case_1() {
+			let ikm = [0x0b; 22];
+			let salt = [
+				0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+			];
+			let info = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+			let expected_prk = [
+				0x07, 0x77, 0x09, 0x36, 0x2c, 0x2e, 0x32, 0xdf, 0x0d, 0xdc, 0x3f, 0x0d, 0xc4, 0x7b,
+				0xba, 0x63, 0x90, 0xb6, 0xc7, 0x3b, 0xb5, 0x0f, 0x9c, 0x31, 0x22, 0xec, 0x84, 0x4a,
+				0xd7, 0xc2, 0xb3, 0xe5,
+			];
+			let expected_okm = [
+				0x3c, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0, 0x36,
+				0x2f, 0x2a, 0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0, 0x2d, 0x56,
+				0xec, 0xc4, 0xc5, 0xbf, 0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87, 0x18, 0x58, 0x65,
+			];
+
+			let prk = extract(&salt, &ikm).unwrap();
+			assert_eq!(prk.unprotected_as_bytes(), &expected_prk[..]);
+
+			let mut okm_out = [0u8; 42];
+			expand(&prk, Some(&info), &mut okm_out).unwrap();
+			assert_eq!(&okm_out[..], &expected_okm[..]);
+
+			let mut okm_out_verify = [0u8; 42];
+			assert!(verify(&expected_okm, &salt, &ikm, Some(&info), &mut okm_out_verify).unwrap());
+		}
+
+		#[test]
+		fn test_rfc5869_test_case_3_no_salt_no_info() {
+			let ikm = [0x0b; 22];
+			let expected_okm = [
+				0x8d, 0xa4, 0xe7, 0x75, 0xa5, 0x63, 0xc1, 0x8f, 0x71, 0x5f, 0x80, 0x2a, 0x06, 0x3c,
+				0x5a, 0x31, 0xb8, 0xa1, 0x1f, 0x5c, 0x5e, 0xe1, 0x87, 0x9e, 0xc3, 0x45, 0x4e, 0x5f,
+				0x3c, 0x73, 0x8d, 0x2d, 0x9d, 0x20, 0x13, 0x95, 0xfa, 0xa4, 0xb6, 0x1a, 0x96, 0xc8,
+			];
+
+			let mut okm_out = [0u8; 42];
+			derive_key(&[], &ikm, None, &mut okm_out).unwrap();
+			assert_eq!(&okm_out[..], &expected_okm[..]);
+		}
+
+		#[test]
+		fn hkdf_maximum_length() {
+			// Max allowed length here is 8160
+			let prk = extract(b"", b"").unwrap();
+			assert!(expand(&prk, None, &mut [0u8; 8160]).is_ok());
+			assert!(expand(&prk, None, &mut [0u8; 8161]).is_err());
+		}
+	}
+}
+
 #[cfg(test)]
 mod public {
 	use super::*;
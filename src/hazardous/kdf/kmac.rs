@@ -0,0 +1,438 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `secret_key`: The input key from which keying material is derived.
+//! - `label`: A string that identifies the purpose of the derived keying
+//!   material. This is used as the KMAC customization string.
+//! - `context`: Information related to the derived keying material, such as
+//!   the identities of the parties deriving it.
+//! - `dst_out`: Destination buffer for the derived key. The length of the
+//!   derived key is implied by the length of `dst_out`.
+//! - `expected`: The expected derived key.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `dst_out` is less than 1.
+//! - The length of `secret_key` is less than 16 for KMAC128 or less than 32
+//!   for KMAC256.
+//! - The derived key does not match the expected when verifying.
+//!
+//! # Security:
+//! - KMAC128 has a security strength of 128 bits and KMAC256 of 256 bits. The
+//!   length of `secret_key` must be at least the security strength.
+//! - Because the requested output length is an input to KMAC, derived keys of
+//!   different lengths are unrelated. A shorter derived key is not a prefix of
+//!   a longer one.
+//! - This KDF is not suitable for password storage.
+//!
+//! ### Note:
+//! The implementation relies on the `tiny-keccak` crate, the same as
+//! `hazardous::xof::cshake`, and therefore produces ***incorrect results on
+//! big-endian based systems***. See the [issue here](https://github.com/debris/tiny-keccak/issues/15).
+//!
+//! # Example:
+//! ```
+//! use orion::{hazardous::kdf::kmac, util};
+//!
+//! let mut secret_key = [0u8; 32];
+//! util::secure_rand_bytes(&mut secret_key).unwrap();
+//! let mut derived_key = [0u8; 32];
+//!
+//! kmac::derive_key_kmac128(&secret_key, b"Encryption key", b"Context", &mut derived_key)
+//! 	.unwrap();
+//!
+//! let expected = derived_key;
+//!
+//! assert!(kmac::verify_kmac128(
+//! 	&expected,
+//! 	&secret_key,
+//! 	b"Encryption key",
+//! 	b"Context",
+//! 	&mut derived_key
+//! )
+//! .unwrap());
+//! ```
+
+use crate::{
+	errors::{UnknownCryptoError, ValidationCryptoError},
+	hazardous::xof::cshake::left_encode,
+	util,
+};
+use tiny_keccak::Keccak;
+
+/// The rate of KMAC128 in bytes.
+const KMAC128_RATE: usize = 168;
+/// The rate of KMAC256 in bytes.
+const KMAC256_RATE: usize = 136;
+
+#[must_use]
+/// The right_encode function as specified in the NIST SP 800-185.
+fn right_encode(x: u64) -> ([u8; 9], usize) {
+	let (left, offset) = left_encode(x);
+	let len = 9 - offset;
+
+	let mut encoded = [0u8; 9];
+	encoded[..len].copy_from_slice(&left[offset..]);
+	encoded[len] = len as u8;
+
+	(encoded, len + 1)
+}
+
+#[must_use]
+/// Absorb the bytepad() of the encode_string() of each of `strings`, as
+/// specified in the NIST SP 800-185.
+fn absorb_bytepad(
+	hasher: &mut Keccak,
+	rate: usize,
+	strings: &[&[u8]],
+) -> Result<(), UnknownCryptoError> {
+	let (encoded, offset) = left_encode(rate as u64);
+	hasher.update(&encoded[(offset - 1)..]);
+	let mut block_offset = (9 - (offset - 1)) % rate;
+
+	for string in strings {
+		let bits = (string.len() as u64)
+			.checked_mul(8)
+			.ok_or(UnknownCryptoError)?;
+		let (encoded, offset) = left_encode(bits);
+		hasher.update(&encoded[(offset - 1)..]);
+		hasher.update(string);
+		block_offset = (block_offset + (9 - (offset - 1)) + (string.len() % rate)) % rate;
+	}
+
+	// Pad with zeroes to a multiple of the rate
+	let zeroes = [0u8; KMAC128_RATE];
+	hasher.update(&zeroes[..((rate - block_offset) % rate)]);
+
+	Ok(())
+}
+
+#[must_use]
+/// KMAC128 or KMAC256, depending on `rate`, as specified in the NIST SP
+/// 800-185.
+fn kmac(
+	rate: usize,
+	secret_key: &[u8],
+	data: &[u8],
+	custom: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if dst_out.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+	let out_bits = (dst_out.len() as u64)
+		.checked_mul(8)
+		.ok_or(UnknownCryptoError)?;
+
+	let mut hasher = Keccak::new(rate, 0x04);
+	absorb_bytepad(&mut hasher, rate, &[b"KMAC", custom])?;
+	absorb_bytepad(&mut hasher, rate, &[secret_key])?;
+	hasher.update(data);
+	let (encoded, len) = right_encode(out_bits);
+	hasher.update(&encoded[..len]);
+	hasher.finalize(dst_out);
+
+	Ok(())
+}
+
+#[must_use]
+/// Derive a key using the KDF in KMAC mode with KMAC128.
+pub fn derive_key_kmac128(
+	secret_key: &[u8],
+	label: &[u8],
+	context: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if secret_key.len() < 16 {
+		return Err(UnknownCryptoError);
+	}
+
+	kmac(KMAC128_RATE, secret_key, context, label, dst_out)
+}
+
+#[must_use]
+/// Derive a key using the KDF in KMAC mode with KMAC256.
+pub fn derive_key_kmac256(
+	secret_key: &[u8],
+	label: &[u8],
+	context: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if secret_key.len() < 32 {
+		return Err(UnknownCryptoError);
+	}
+
+	kmac(KMAC256_RATE, secret_key, context, label, dst_out)
+}
+
+#[must_use]
+/// Verify a key derived with KMAC128 in constant time.
+pub fn verify_kmac128(
+	expected: &[u8],
+	secret_key: &[u8],
+	label: &[u8],
+	context: &[u8],
+	dst_out: &mut [u8],
+) -> Result<bool, ValidationCryptoError> {
+	derive_key_kmac128(secret_key, label, context, dst_out)?;
+
+	if util::secure_cmp(&dst_out, expected).is_err() {
+		Err(ValidationCryptoError)
+	} else {
+		Ok(true)
+	}
+}
+
+#[must_use]
+/// Verify a key derived with KMAC256 in constant time.
+pub fn verify_kmac256(
+	expected: &[u8],
+	secret_key: &[u8],
+	label: &[u8],
+	context: &[u8],
+	dst_out: &mut [u8],
+) -> Result<bool, ValidationCryptoError> {
+	derive_key_kmac256(secret_key, label, context, dst_out)?;
+
+	if util::secure_cmp(&dst_out, expected).is_err() {
+		Err(ValidationCryptoError)
+	} else {
+		Ok(true)
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+#[cfg(target_endian = "little")]
+mod public {
+	use super::*;
+
+	// The KMAC sample key from NIST.
+	const SAMPLE_KEY: [u8; 32] = [
+		0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4A, 0x4B, 0x4C, 0x4D, 0x4E,
+		0x4F, 0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x5B, 0x5C, 0x5D,
+		0x5E, 0x5F,
+	];
+
+	// One function tested per submodule.
+	mod test_derive_key_kmac128 {
+		use super::*;
+
+		#[test]
+		fn test_nist_kmac_samples() {
+			// KMAC_samples.pdf, Sample #1 and #2.
+			let mut okm = [0u8; 32];
+			derive_key_kmac128(&SAMPLE_KEY, b"", b"\x00\x01\x02\x03", &mut okm).unwrap();
+			let expected =
+				hex::decode("e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e")
+					.unwrap();
+			assert_eq!(okm[..], expected[..]);
+
+			derive_key_kmac128(
+				&SAMPLE_KEY,
+				b"My Tagged Application",
+				b"\x00\x01\x02\x03",
+				&mut okm,
+			)
+			.unwrap();
+			let expected =
+				hex::decode("3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5")
+					.unwrap();
+			assert_eq!(okm[..], expected[..]);
+		}
+
+		#[test]
+		fn test_known_keys() {
+			// Generated with a Python implementation of KMAC over Keccak-f[1600].
+			let mut okm = [0u8; 200];
+			derive_key_kmac128(&[0x0b; 16], b"Label", b"Context", &mut okm).unwrap();
+			let expected = hex::decode(
+				"bc3ac8add7f9172e73d0ec0accaed33e6ae85a4cae69af4e33f82199c6a6926f2f0f9bc699cfae55e87c\
+				 976cbdba11a4980823a18a592820df753a019093df17f1c70a029775d5f44193bd9fad384ec2bf5342a5\
+				 11c7de3ad83e953cb3eeaff9daa228e4da440a3b3d3f46969286515d469953e4d2b55901f2647667832b\
+				 db56babedd48df85521a8c9b21b10525e9277a80d68ddc35d0522a7b34547354c44af9f0e44291d6e30e\
+				 8da746b9481ebfe9b243fe10555691e02df2adc617eefbb4ba93bcaf346fcdf6",
+			)
+			.unwrap();
+			assert_eq!(okm[..], expected[..]);
+
+			let mut okm = [0u8; 1];
+			derive_key_kmac128(&[0x0b; 16], b"", b"", &mut okm).unwrap();
+			assert_eq!(okm[..], [0xd0]);
+		}
+
+		#[test]
+		fn test_label_fills_block() {
+			// The encoded "KMAC" and label fill exactly one 168 byte block.
+			let mut okm = [0u8; 32];
+			derive_key_kmac128(&[0x0b; 16], &[0x5a; 157], b"Context", &mut okm).unwrap();
+			let expected =
+				hex::decode("2165244995e844c84ad932d3edbbcc592d45862588eefb1371adf551627d2491")
+					.unwrap();
+			assert_eq!(okm[..], expected[..]);
+		}
+
+		#[test]
+		fn test_length_not_prefix() {
+			let mut short = [0u8; 32];
+			let mut long = [0u8; 64];
+			derive_key_kmac128(&SAMPLE_KEY, b"Label", b"Context", &mut short).unwrap();
+			derive_key_kmac128(&SAMPLE_KEY, b"Label", b"Context", &mut long).unwrap();
+			assert_ne!(short[..], long[..32]);
+		}
+
+		#[test]
+		fn test_params_err() {
+			let mut okm = [0u8; 0];
+			assert!(derive_key_kmac128(&SAMPLE_KEY, b"Label", b"Context", &mut okm).is_err());
+
+			let mut okm = [0u8; 32];
+			assert!(derive_key_kmac128(&[0u8; 15], b"Label", b"Context", &mut okm).is_err());
+			assert!(derive_key_kmac128(&[0u8; 16], b"Label", b"Context", &mut okm).is_ok());
+		}
+	}
+
+	mod test_derive_key_kmac256 {
+		use super::*;
+
+		#[test]
+		fn test_nist_kmac_samples() {
+			// KMAC_samples.pdf, Sample #4.
+			let mut okm = [0u8; 64];
+			derive_key_kmac256(
+				&SAMPLE_KEY,
+				b"My Tagged Application",
+				b"\x00\x01\x02\x03",
+				&mut okm,
+			)
+			.unwrap();
+			let expected = hex::decode(
+				"20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7f69d4cc3de9d104a3516\
+				 89f27cf6f5951f0103f33f4f24871024d9c27773a8dd",
+			)
+			.unwrap();
+			assert_eq!(okm[..], expected[..]);
+		}
+
+		#[test]
+		fn test_known_keys() {
+			// Generated with a Python implementation of KMAC over Keccak-f[1600].
+			let mut okm = [0u8; 200];
+			derive_key_kmac256(&[0x0b; 32], b"Label", b"Context", &mut okm).unwrap();
+			let expected = hex::decode(
+				"435155cc87aeae25a7d887fa3852e73be1f877f37bf4e1e3e22629f94df6cc03830c0cc9ff3192661b97\
+				 fd6adf2d32773b0a4b28ddd4ce3553bcf72691735b1459dd7f7ef30faa007b1c4e1e92be1e9e141a748c\
+				 f3789783594937bce9de01d0247bdc1224c193854dee44ebab226470bc234336e06d59a57f7bb20e0ddb\
+				 e85104610602d4c690515e09a65eb7804c7815f876cdd14b311b6dc7ab8639309687fa0e1a5a54369de0\
+				 41862bb96a0db8185abb2b40280acfb17ab5b5e0674db9b364355338a4a5973a",
+			)
+			.unwrap();
+			assert_eq!(okm[..], expected[..]);
+		}
+
+		#[test]
+		fn test_diff_from_kmac128() {
+			let mut okm_128 = [0u8; 32];
+			let mut okm_256 = [0u8; 32];
+			derive_key_kmac128(&SAMPLE_KEY, b"Label", b"Context", &mut okm_128).unwrap();
+			derive_key_kmac256(&SAMPLE_KEY, b"Label", b"Context", &mut okm_256).unwrap();
+			assert_ne!(okm_128, okm_256);
+		}
+
+		#[test]
+		fn test_params_err() {
+			let mut okm = [0u8; 0];
+			assert!(derive_key_kmac256(&SAMPLE_KEY, b"Label", b"Context", &mut okm).is_err());
+
+			let mut okm = [0u8; 32];
+			assert!(derive_key_kmac256(&[0u8; 31], b"Label", b"Context", &mut okm).is_err());
+			assert!(derive_key_kmac256(&[0u8; 32], b"Label", b"Context", &mut okm).is_ok());
+		}
+	}
+
+	mod test_verify_kmac128 {
+		use super::*;
+
+		#[test]
+		fn test_verify() {
+			let mut okm = [0u8; 32];
+			derive_key_kmac128(&SAMPLE_KEY, b"Label", b"Context", &mut okm).unwrap();
+			let expected = okm;
+
+			assert!(
+				verify_kmac128(&expected, &SAMPLE_KEY, b"Label", b"Context", &mut okm).unwrap()
+			);
+			assert!(verify_kmac128(&expected, &SAMPLE_KEY, b"Label", b"Other", &mut okm).is_err());
+			assert!(
+				verify_kmac128(&expected, &SAMPLE_KEY, b"Other", b"Context", &mut okm).is_err()
+			);
+			assert!(
+				verify_kmac128(&expected[..31], &SAMPLE_KEY, b"Label", b"Context", &mut okm)
+					.is_err()
+			);
+		}
+	}
+
+	mod test_verify_kmac256 {
+		use super::*;
+
+		#[test]
+		fn test_verify() {
+			let mut okm = [0u8; 32];
+			derive_key_kmac256(&SAMPLE_KEY, b"Label", b"Context", &mut okm).unwrap();
+			let expected = okm;
+
+			assert!(
+				verify_kmac256(&expected, &SAMPLE_KEY, b"Label", b"Context", &mut okm).unwrap()
+			);
+			assert!(verify_kmac256(&expected, &SAMPLE_KEY, b"Label", b"Other", &mut okm).is_err());
+			assert!(
+				verify_kmac256(&expected, &SAMPLE_KEY, b"Other", b"Context", &mut okm).is_err()
+			);
+		}
+	}
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	mod test_right_encode {
+		use super::*;
+
+		#[test]
+		fn test_right_encode() {
+			let (test_1, len_1) = right_encode(0);
+			let (test_2, len_2) = right_encode(256);
+			let (test_3, len_3) = right_encode(u64::max_value());
+
+			assert_eq!(&test_1[..len_1], &[0, 1]);
+			assert_eq!(&test_2[..len_2], &[1, 0, 2]);
+			assert_eq!(
+				&test_3[..len_3],
+				&[255, 255, 255, 255, 255, 255, 255, 255, 8]
+			);
+		}
+	}
+}
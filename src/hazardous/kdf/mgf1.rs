@@ -0,0 +1,208 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `seed`: The seed from which the mask is generated.
+//! - `dst_out`: Destination buffer for the mask. The length of the mask is
+//!   implied by the length of `dst_out`.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `dst_out` is less than 1.
+//! - The length of `dst_out` is greater than 2^32 * hash_output_size_in_bytes.
+//!
+//! # Security:
+//! - MGF1 is not a key derivation function for secret keys and should only be
+//!   used where a construction requires it, such as RSA-OAEP and RSA-PSS. Use
+//!   HKDF to derive keys.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::kdf::mgf1;
+//!
+//! let mut mask = [0u8; 100];
+//! mgf1::mask_sha256(b"Seed", &mut mask).unwrap();
+//!
+//! let mut mask = [0u8; 100];
+//! mgf1::mask_sha512(b"Seed", &mut mask).unwrap();
+//! ```
+
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		constants::{SHA256_OUTSIZE, SHA512_OUTSIZE},
+		hash::{sha256, sha512},
+	},
+};
+
+/// Check that a mask of length `dst_len` can be generated with a hash
+/// function that has an output size of `hash_len`.
+fn check_mask_length(dst_len: usize, hash_len: usize) -> Result<(), UnknownCryptoError> {
+	if dst_len < 1 || (dst_len as u64) > (1u64 << 32) * (hash_len as u64) {
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(())
+}
+
+#[must_use]
+/// Generate a mask using MGF1 with SHA256.
+pub fn mask_sha256(seed: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+	check_mask_length(dst_out.len(), SHA256_OUTSIZE)?;
+
+	let mut hash = sha256::init();
+	for (counter, block) in dst_out.chunks_mut(SHA256_OUTSIZE).enumerate() {
+		hash.reset();
+		hash.update(seed)?;
+		hash.update(&(counter as u32).to_be_bytes())?;
+		block.copy_from_slice(&hash.finalize()?.as_bytes()[..block.len()]);
+	}
+
+	Ok(())
+}
+
+#[must_use]
+/// Generate a mask using MGF1 with SHA512.
+pub fn mask_sha512(seed: &[u8], dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+	check_mask_length(dst_out.len(), SHA512_OUTSIZE)?;
+
+	let mut hash = sha512::init();
+	for (counter, block) in dst_out.chunks_mut(SHA512_OUTSIZE).enumerate() {
+		hash.reset();
+		hash.update(seed)?;
+		hash.update(&(counter as u32).to_be_bytes())?;
+		block.copy_from_slice(&hash.finalize()?.as_bytes()[..block.len()]);
+	}
+
+	Ok(())
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_mask_sha256 {
+		use super::*;
+
+		#[test]
+		fn test_known_masks() {
+			// Generated with a Python implementation of MGF1 over hashlib.
+			let mut mask = [0u8; 3];
+			mask_sha256(b"foo", &mut mask).unwrap();
+			assert_eq!(mask[..], hex::decode("3bdaba").unwrap()[..]);
+
+			let mut mask = [0u8; 5];
+			mask_sha256(b"foo", &mut mask).unwrap();
+			assert_eq!(mask[..], hex::decode("3bdaba83cf").unwrap()[..]);
+
+			let mut mask = [0u8; 50];
+			mask_sha256(b"bar", &mut mask).unwrap();
+			let expected = hex::decode(
+				"382576a7841021cc28fc4c0948753fb8312090cea942ea4c4e735d10dc724b155f9f6069f289d61daca0cb814502ef04eae1",
+			)
+			.unwrap();
+			assert_eq!(mask[..], expected[..]);
+		}
+
+		#[test]
+		fn test_prefix_of_longer_mask() {
+			let mut short = [0u8; 33];
+			let mut long = [0u8; 200];
+			mask_sha256(b"bar", &mut short).unwrap();
+			mask_sha256(b"bar", &mut long).unwrap();
+			assert_eq!(short[..], long[..33]);
+		}
+
+		#[test]
+		fn test_zero_length_err() {
+			let mut mask = [0u8; 0];
+			assert!(mask_sha256(b"foo", &mut mask).is_err());
+			let mut mask = [0u8; 1];
+			assert!(mask_sha256(b"foo", &mut mask).is_ok());
+		}
+	}
+
+	mod test_mask_sha512 {
+		use super::*;
+
+		#[test]
+		fn test_known_masks() {
+			// Generated with a Python implementation of MGF1 over hashlib.
+			let mut mask = [0u8; 3];
+			mask_sha512(b"foo", &mut mask).unwrap();
+			assert_eq!(mask[..], hex::decode("e8ac84").unwrap()[..]);
+
+			let mut mask = [0u8; 5];
+			mask_sha512(b"foo", &mut mask).unwrap();
+			assert_eq!(mask[..], hex::decode("e8ac84d032").unwrap()[..]);
+
+			let mut mask = [0u8; 50];
+			mask_sha512(b"bar", &mut mask).unwrap();
+			let expected = hex::decode(
+				"8625c97145f50577911b25359975c8f942487e7aa0167e6db44239680d08547afcef6d3b7080cde5c1d9a8b17acfe7d95b9f",
+			)
+			.unwrap();
+			assert_eq!(mask[..], expected[..]);
+		}
+
+		#[test]
+		fn test_prefix_of_longer_mask() {
+			let mut short = [0u8; 65];
+			let mut long = [0u8; 200];
+			mask_sha512(b"bar", &mut short).unwrap();
+			mask_sha512(b"bar", &mut long).unwrap();
+			assert_eq!(short[..], long[..65]);
+		}
+
+		#[test]
+		fn test_zero_length_err() {
+			let mut mask = [0u8; 0];
+			assert!(mask_sha512(b"foo", &mut mask).is_err());
+			let mut mask = [0u8; 1];
+			assert!(mask_sha512(b"foo", &mut mask).is_ok());
+		}
+	}
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	mod test_check_mask_length {
+		use super::*;
+
+		#[test]
+		fn test_mask_length_limits() {
+			assert!(check_mask_length(0, SHA256_OUTSIZE).is_err());
+			assert!(check_mask_length(1, SHA256_OUTSIZE).is_ok());
+
+			if core::mem::size_of::<usize>() == 8 {
+				let max = ((1u64 << 32) * (SHA256_OUTSIZE as u64)) as usize;
+				assert!(check_mask_length(max, SHA256_OUTSIZE).is_ok());
+				assert!(check_mask_length(max + 1, SHA256_OUTSIZE).is_err());
+			}
+		}
+	}
+}
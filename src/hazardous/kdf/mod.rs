@@ -20,8 +20,17 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-/// HKDF-HMAC-SHA512 (HMAC-based Extract-and-Expand Key Derivation Function) as specified in the [RFC 5869](https://tools.ietf.org/html/rfc5869).
+/// HKDF-HMAC-SHA512 and HKDF-HMAC-SHA256 (HMAC-based Extract-and-Expand Key Derivation Function) as specified in the [RFC 5869](https://tools.ietf.org/html/rfc5869).
 pub mod hkdf;
 
-/// PBKDF2-HMAC-SHA512 (Password-Based Key Derivation Function 2) as specified in the [RFC 8018](https://tools.ietf.org/html/rfc8018).
+/// PBKDF2-HMAC-SHA512 and PBKDF2-HMAC-SHA256 (Password-Based Key Derivation Function 2) as specified in the [RFC 8018](https://tools.ietf.org/html/rfc8018).
 pub mod pbkdf2;
+
+/// MGF1 (Mask Generation Function 1) over SHA256 and SHA512 as specified in the [RFC 8017](https://tools.ietf.org/html/rfc8017#appendix-B.2.1).
+pub mod mgf1;
+
+/// SLIP-0010 hardened key derivation for Ed25519 and Curve25519 as specified in [SLIP-0010](https://github.com/satoshilabs/slips/blob/master/slip-0010.md).
+pub mod slip10;
+
+/// KDF in KMAC mode with KMAC128 and KMAC256 as specified in the [NIST SP 800-108r1](https://csrc.nist.gov/publications/detail/sp/800-108/rev-1/final) and [NIST SP 800-185](https://csrc.nist.gov/publications/detail/sp/800-185/final).
+pub mod kmac;
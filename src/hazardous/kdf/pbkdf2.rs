@@ -27,16 +27,24 @@
 //! - `dst_out`: Destination buffer for the derived key. The length of the
 //!   derived key is implied by the length of `dk_out`.
 //! - `expected`: The expected derived key.
+//! - `interval`: The number of iterations between calls to `progress`.
+//! - `progress`: A closure that is given the number of completed iterations
+//!   and the total, and returns `false` to cancel the derivation.
+//! - `iterations` (`Derivation::step()`): The maximum number of iterations to
+//!   do in this step.
 //!
 //! # Errors:
 //! An error will be returned if:
 //! - The length of `dst_out` is less than 1.
 //! - The specified iteration count is less than 1.
 //! - The hashed password does not match the expected when verifying.
+//! - `progress` returns `false`. `Derivation::run()` then returns
+//!   `CancelledCryptoError`, and `dst_out` is zeroed.
 //!
 //! # Panics:
 //! A panic will occur if:
-//! - The length of `dst_out` is greater than (2^32 - 1) * 64.
+//! - The length of `dst_out` is greater than (2^32 - 1) *
+//!   hash_output_size_in_bytes.
 //!
 //! # Security:
 //! - Use `Password::generate()` to randomly generate a password of 128 bytes.
@@ -62,15 +70,66 @@
 //!
 //! assert!(pbkdf2::verify(&exp_dk, &password, &salt, 10000, &mut dk_out).unwrap());
 //! ```
+//!
+//! The functions in `pbkdf2` use HMAC-SHA512. PBKDF2 with HMAC-SHA256 is
+//! available in `pbkdf2::sha256`, and `derive_key_with()` can be used with
+//! any hash function that HMAC supports:
+//! ```
+//! use orion::hazardous::kdf::pbkdf2;
+//!
+//! let password = pbkdf2::sha256::Password::from_slice(b"Secret password").unwrap();
+//! let mut dk_out = [0u8; 32];
+//!
+//! pbkdf2::sha256::derive_key(&password, b"Salt", 10000, &mut dk_out).unwrap();
+//! ```
+//!
+//! A `Derivation` reports its progress, for example to a progress bar, and can
+//! be cancelled:
+//! ```
+//! use orion::hazardous::kdf::pbkdf2;
+//!
+//! let password = pbkdf2::Password::from_slice(b"Secret password").unwrap();
+//! let mut dk_out = [0u8; 64];
+//!
+//! let derivation = pbkdf2::derivation(&password, b"Salt", 10000, &mut dk_out).unwrap();
+//! derivation
+//! 	.run(1000, |completed, total| {
+//! 		println!("{}%", completed * 100 / total);
+//! 		true
+//! 	})
+//! 	.unwrap();
+//! ```
+//!
+//! `Derivation::step()` instead does a bounded amount of work per call, so an
+//! event loop or GUI thread can derive a key between other tasks:
+//! ```
+//! use orion::hazardous::kdf::pbkdf2::{self, Status};
+//!
+//! let password = pbkdf2::Password::from_slice(b"Secret password").unwrap();
+//! let mut dk_out = [0u8; 64];
+//!
+//! let mut derivation = pbkdf2::derivation(&password, b"Salt", 10000, &mut dk_out).unwrap();
+//! while derivation.step(500).unwrap() == Status::Pending {
+//! 	// Handle other events here.
+//! }
+//! drop(derivation);
+//!
+//! let mut expected = [0u8; 64];
+//! pbkdf2::derive_key(&password, b"Salt", 10000, &mut expected).unwrap();
+//! assert_eq!(dk_out[..], expected[..]);
+//! ```
 
 use crate::{
-	errors::{UnknownCryptoError, ValidationCryptoError},
+	errors::{CancelledCryptoError, UnknownCryptoError, ValidationCryptoError},
 	hazardous::{
 		constants::{HLenArray, SHA512_BLOCKSIZE, SHA512_OUTSIZE},
-		mac::hmac,
+		mac::hmac::{Hmac, HmacHash},
 	},
 	util,
 };
+use zeroize::Zeroize;
+
+pub use self::sha512::{derivation, derive_key, verify};
 
 construct_hmac_key! {
 	/// A type to represent the `Password` that PBKDF2 hashes.
@@ -91,26 +150,27 @@ construct_hmac_key! {
 
 #[inline]
 /// The F function as described in the RFC.
-fn function_f(
+fn function_f<H: HmacHash>(
 	salt: &[u8],
 	iterations: usize,
 	index: u32,
 	dk_block: &mut [u8],
 	block_len: usize,
-	hmac: &mut hmac::Hmac,
+	hmac: &mut Hmac<H>,
 ) -> Result<(), UnknownCryptoError> {
-	let mut u_step: HLenArray = [0u8; 64];
+	let mut u_step_buf: HLenArray = [0u8; SHA512_OUTSIZE];
+	let u_step = &mut u_step_buf[..H::OUTSIZE];
 	hmac.update(salt)?;
 	hmac.update(&index.to_be_bytes())?;
 
-	u_step.copy_from_slice(&hmac.finalize()?.unprotected_as_bytes());
+	hmac.finalize_into(u_step)?;
 	dk_block.copy_from_slice(&u_step[..block_len]);
 
 	if iterations > 1 {
 		for _ in 1..iterations {
 			hmac.reset();
-			hmac.update(&u_step)?;
-			u_step.copy_from_slice(&hmac.finalize()?.unprotected_as_bytes());
+			hmac.update(&u_step[..])?;
+			hmac.finalize_into(u_step)?;
 			dk_block
 				.iter_mut()
 				.zip(u_step.iter())
@@ -122,10 +182,11 @@ fn function_f(
 }
 
 #[must_use]
-/// PBKDF2-SHA512 (Password-Based Key Derivation Function 2) as specified in the
-/// [RFC 8018](https://tools.ietf.org/html/rfc8018).
-pub fn derive_key(
-	password: &Password,
+/// PBKDF2 (Password-Based Key Derivation Function 2) as specified in the
+/// [RFC 8018](https://tools.ietf.org/html/rfc8018), using HMAC with the hash
+/// function `H`. `password` is used as the HMAC `SecretKey`.
+pub fn derive_key_with<H: HmacHash>(
+	password: &H::SecretKey,
 	salt: &[u8],
 	iterations: usize,
 	dst_out: &mut [u8],
@@ -137,11 +198,9 @@ pub fn derive_key(
 		return Err(UnknownCryptoError);
 	}
 
-	let mut hmac = hmac::init(&hmac::SecretKey::from_slice(
-		&password.unprotected_as_bytes(),
-	)?);
+	let mut hmac = Hmac::<H>::new(password);
 
-	for (idx, dk_block) in dst_out.chunks_mut(SHA512_OUTSIZE).enumerate() {
+	for (idx, dk_block) in dst_out.chunks_mut(H::OUTSIZE).enumerate() {
 		let block_len = dk_block.len();
 		let block_idx = (1u32).checked_add(idx as u32).unwrap();
 
@@ -153,15 +212,16 @@ pub fn derive_key(
 }
 
 #[must_use]
-/// Verify PBKDF2-HMAC-SHA512 derived key in constant time.
-pub fn verify(
+/// Verify a PBKDF2 derived key in constant time, using HMAC with the hash
+/// function `H`.
+pub fn verify_with<H: HmacHash>(
 	expected: &[u8],
-	password: &Password,
+	password: &H::SecretKey,
 	salt: &[u8],
 	iterations: usize,
 	dst_out: &mut [u8],
 ) -> Result<bool, ValidationCryptoError> {
-	derive_key(password, salt, iterations, dst_out)?;
+	derive_key_with::<H>(password, salt, iterations, dst_out)?;
 
 	if util::secure_cmp(&dst_out, expected).is_err() {
 		Err(ValidationCryptoError)
@@ -170,6 +230,348 @@ pub fn verify(
 	}
 }
 
+/// A PBKDF2 derivation, using HMAC with the hash function `H`, that reports
+/// its progress and can be cancelled.
+///
+/// # Note:
+/// The iterations of each block of the derived key are counted towards the
+/// total, so a derived key of two `H::OUTSIZE` blocks with 10000 iterations
+/// has a total of 20000 iterations.
+pub struct Derivation<'a, H: HmacHash> {
+	hmac: Hmac<H>,
+	salt: &'a [u8],
+	iterations: usize,
+	dst_out: &'a mut [u8],
+	// Index of the block of `dst_out` being derived.
+	block: usize,
+	// Iterations already done for `block`.
+	iteration: usize,
+	u_step: HLenArray,
+}
+
+impl<H: HmacHash> Drop for Derivation<'_, H> {
+	fn drop(&mut self) {
+		self.u_step.zeroize();
+		// A partially derived key must not be used.
+		if !self.is_done() {
+			self.dst_out.zeroize();
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The state of a `Derivation` after a call to `step()`.
+pub enum Status {
+	/// More iterations are needed.
+	Pending,
+	/// The key has been fully derived into `dst_out`.
+	Done,
+}
+
+impl<'a, H: HmacHash> Derivation<'a, H> {
+	#[must_use]
+	/// Prepare the derivation of a key into `dst_out`. No iterations are done
+	/// until `run()` is called.
+	pub fn new(
+		password: &H::SecretKey,
+		salt: &'a [u8],
+		iterations: usize,
+		dst_out: &'a mut [u8],
+	) -> Result<Self, UnknownCryptoError> {
+		if iterations < 1 {
+			return Err(UnknownCryptoError);
+		}
+		if dst_out.is_empty() {
+			return Err(UnknownCryptoError);
+		}
+		// The block index is a 32-bit counter.
+		if (dst_out.len() - 1) / H::OUTSIZE >= u32::max_value() as usize {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(Self {
+			hmac: Hmac::<H>::new(password),
+			salt,
+			iterations,
+			dst_out,
+			block: 0,
+			iteration: 0,
+			u_step: [0u8; SHA512_OUTSIZE],
+		})
+	}
+
+	/// Return the number of iterations that are done.
+	pub fn completed(&self) -> u64 {
+		(self.block as u64)
+			.saturating_mul(self.iterations as u64)
+			.saturating_add(self.iteration as u64)
+	}
+
+	/// Return the total number of iterations of the derivation.
+	pub fn total(&self) -> u64 {
+		let blocks = (self.dst_out.len() + H::OUTSIZE - 1) / H::OUTSIZE;
+		(blocks as u64).saturating_mul(self.iterations as u64)
+	}
+
+	/// Return `true` if the key has been fully derived.
+	fn is_done(&self) -> bool { self.block * H::OUTSIZE >= self.dst_out.len() }
+
+	/// Do at most `max_iterations` iterations.
+	fn advance(&mut self, max_iterations: usize) -> Result<(), UnknownCryptoError> {
+		let u_step = &mut self.u_step[..H::OUTSIZE];
+
+		for _ in 0..max_iterations {
+			let start = self.block * H::OUTSIZE;
+			if start >= self.dst_out.len() {
+				break;
+			}
+			let end = core::cmp::min(start + H::OUTSIZE, self.dst_out.len());
+			let dk_block = &mut self.dst_out[start..end];
+
+			self.hmac.reset();
+			if self.iteration == 0 {
+				self.hmac.update(self.salt)?;
+				self.hmac.update(&(self.block as u32 + 1).to_be_bytes())?;
+				self.hmac.finalize_into(u_step)?;
+				dk_block.copy_from_slice(&u_step[..dk_block.len()]);
+			} else {
+				self.hmac.update(u_step)?;
+				self.hmac.finalize_into(u_step)?;
+				dk_block
+					.iter_mut()
+					.zip(u_step.iter())
+					.for_each(|(a, b)| *a ^= b);
+			}
+
+			self.iteration += 1;
+			if self.iteration == self.iterations {
+				self.block += 1;
+				self.iteration = 0;
+			}
+		}
+
+		Ok(())
+	}
+
+	#[must_use]
+	/// Derive the key, calling `progress` with the number of completed
+	/// iterations and the total after every `interval` iterations and once the
+	/// key is derived. If `progress` returns `false`, the derivation stops,
+	/// `dst_out` is zeroed and `CancelledCryptoError` is returned.
+	///
+	/// An `interval` of 0 is treated as 1.
+	pub fn run<F>(mut self, interval: usize, mut progress: F) -> Result<(), CancelledCryptoError>
+	where
+		F: FnMut(u64, u64) -> bool,
+	{
+		let total = self.total();
+
+		// `dst_out` is zeroed when `self` is dropped before the key is derived.
+		loop {
+			// The parameters were checked in `new()`, so the HMAC operations
+			// cannot fail.
+			let status = self
+				.step(core::cmp::max(interval, 1))
+				.map_err(|_| CancelledCryptoError)?;
+			if !progress(self.completed(), total) {
+				return Err(CancelledCryptoError);
+			}
+			if status == Status::Done {
+				return Ok(());
+			}
+		}
+	}
+
+	#[must_use]
+	/// Do at most `iterations` iterations of the derivation, and return
+	/// whether the key is fully derived. Once `Status::Done` is returned, the
+	/// derived key can be read from `dst_out` after dropping the `Derivation`.
+	///
+	/// Dropping the `Derivation` before `Status::Done` is returned cancels
+	/// it, and zeroes `dst_out`.
+	pub fn step(&mut self, iterations: usize) -> Result<Status, UnknownCryptoError> {
+		self.advance(iterations)?;
+
+		if self.is_done() {
+			Ok(Status::Done)
+		} else {
+			Ok(Status::Pending)
+		}
+	}
+}
+
+/// PBKDF2-HMAC-SHA512. These are also available directly in `pbkdf2`.
+pub mod sha512 {
+	pub use super::Password;
+	use crate::{
+		errors::{UnknownCryptoError, ValidationCryptoError},
+		hazardous::{hash::sha512::Sha512, mac::hmac},
+	};
+
+	#[must_use]
+	/// PBKDF2-SHA512 (Password-Based Key Derivation Function 2) as specified in the
+	/// [RFC 8018](https://tools.ietf.org/html/rfc8018).
+	pub fn derive_key(
+		password: &Password,
+		salt: &[u8],
+		iterations: usize,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		let secret_key = hmac::SecretKey::from_slice(&password.unprotected_as_bytes())?;
+		super::derive_key_with::<Sha512>(&secret_key, salt, iterations, dst_out)
+	}
+
+	#[must_use]
+	/// Prepare a PBKDF2-HMAC-SHA512 derivation that reports its progress and
+	/// can be cancelled.
+	pub fn derivation<'a>(
+		password: &Password,
+		salt: &'a [u8],
+		iterations: usize,
+		dst_out: &'a mut [u8],
+	) -> Result<super::Derivation<'a, Sha512>, UnknownCryptoError> {
+		let secret_key = hmac::SecretKey::from_slice(&password.unprotected_as_bytes())?;
+		super::Derivation::new(&secret_key, salt, iterations, dst_out)
+	}
+
+	#[must_use]
+	/// Verify PBKDF2-HMAC-SHA512 derived key in constant time.
+	pub fn verify(
+		expected: &[u8],
+		password: &Password,
+		salt: &[u8],
+		iterations: usize,
+		dst_out: &mut [u8],
+	) -> Result<bool, ValidationCryptoError> {
+		let secret_key = hmac::SecretKey::from_slice(&password.unprotected_as_bytes())?;
+		super::verify_with::<Sha512>(expected, &secret_key, salt, iterations, dst_out)
+	}
+}
+
+/// PBKDF2-HMAC-SHA256.
+pub mod sha256 {
+	use crate::{
+		errors::{UnknownCryptoError, ValidationCryptoError},
+		hazardous::{constants::SHA256_BLOCKSIZE, hash::sha256::Sha256, mac::hmac},
+	};
+
+	construct_hmac_key! {
+		/// A type to represent the `Password` that PBKDF2-HMAC-SHA256 hashes.
+		///
+		/// # Note:
+		/// `Password` pads the given password to a length of 64, for use in HMAC-SHA256, when
+		/// initialized.
+		///
+		/// Using `unprotected_as_bytes()` will return the password with padding.
+		///
+		/// Using `get_length()` will return the length with padding (always 64).
+		///
+		/// # Exceptions:
+		/// An exception will be thrown if:
+		/// - The `OsRng` fails to initialize or read from its source.
+		(Password, SHA256_BLOCKSIZE, sha256, SHA256_OUTSIZE)
+	}
+
+	#[must_use]
+	/// PBKDF2-SHA256 (Password-Based Key Derivation Function 2) as specified in the
+	/// [RFC 8018](https://tools.ietf.org/html/rfc8018).
+	pub fn derive_key(
+		password: &Password,
+		salt: &[u8],
+		iterations: usize,
+		dst_out: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		let secret_key = hmac::sha256::SecretKey::from_slice(&password.unprotected_as_bytes())?;
+		super::derive_key_with::<Sha256>(&secret_key, salt, iterations, dst_out)
+	}
+
+	#[must_use]
+	/// Prepare a PBKDF2-HMAC-SHA256 derivation that reports its progress and
+	/// can be cancelled.
+	pub fn derivation<'a>(
+		password: &Password,
+		salt: &'a [u8],
+		iterations: usize,
+		dst_out: &'a mut [u8],
+	) -> Result<super::Derivation<'a, Sha256>, UnknownCryptoError> {
+		let secret_key = hmac::sha256::SecretKey::from_slice(&password.unprotected_as_bytes())?;
+		super::Derivation::new(&secret_key, salt, iterations, dst_out)
+	}
+
+	#[must_use]
+	/// Verify PBKDF2-HMAC-SHA256 derived key in constant time.
+	pub fn verify(
+		expected: &[u8],
+		password: &Password,
+		salt: &[u8],
+		iterations: usize,
+		dst_out: &mut [u8],
+	) -> Result<bool, ValidationCryptoError> {
+		let secret_key = hmac::sha256::SecretKey::from_slice(&password.unprotected_as_bytes())?;
+		super::verify_with::<Sha256>(expected, &secret_key, salt, iterations, dst_out)
+	}
+
+	// Testing public functions in the module.
+	#[cfg(test)]
+	mod public {
+		use super::*;
+
+		#[test]
+		fn test_rfc7914_test_vector_1() {
+			let password = Password::from_slice(b"passwd").unwrap();
+			let expected = [
+				0x55, 0xac, 0x04, 0x6e, 0x56, 0xe3, 0x08, 0x9f, 0xec, 0x16, 0x91, 0xc2, 0x25, 0x44,
+				0xb6, 0x05, 0xf9, 0x41, 0x85, 0x21, 0x6d, 0xde, 0x04, 0x65, 0xe6, 0x8b, 0x9d, 0x57,
+				0xc2, 0x0d, 0xac, 0xbc, 0x49, 0xca, 0x9c, 0xcc, 0xf1, 0x79, 0xb6, 0x45, 0x99, 0x16,
+				0x64, 0xb3, 0x9d, 0x77, 0xef, 0x31, 0x7c, 0x71, 0xb8, 0x45, 0xb1, 0xe3, 0x0b, 0xd5,
+				0x09, 0x11, 0x20, 0x41, 0xd3, 0xa1, 0x97, 0x83,
+			];
+
+			let mut dk_out = [0u8; 64];
+			derive_key(&password, b"salt", 1, &mut dk_out).unwrap();
+			assert_eq!(&dk_out[..], &expected[..]);
+
+			let mut dk_out_verify = [0u8; 64];
+			assert!(verify(&expected, &password, b"salt", 1, &mut dk_out_verify).unwrap());
+			assert!(verify(&expected, &password, b"salt", 2, &mut dk_out_verify).is_err());
+		}
+
+		#[test]
+		fn test_4096_iterations() {
+			let password = Password::from_slice(b"password").unwrap();
+			let expected = [
+				0xc5, 0xe4, 0x78, 0xd5, 0x92, 0x88, 0xc8, 0x41, 0xaa, 0x53, 0x0d, 0xb6, 0x84, 0x5c,
+				0x4c, 0x8d, 0x96, 0x28, 0x93, 0xa0, 0x01, 0xce, 0x4e, 0x11, 0xa4, 0x96, 0x38, 0x73,
+				0xaa, 0x98, 0x13, 0x4a,
+			];
+
+			let mut dk_out = [0u8; 32];
+			derive_key(&password, b"salt", 4096, &mut dk_out).unwrap();
+			assert_eq!(dk_out, expected);
+		}
+
+		#[test]
+		fn test_password_longer_than_blocksize() {
+			let password = Password::from_slice(&[b'p'; 70]).unwrap();
+			let expected = [
+				0x1f, 0x0b, 0x11, 0x33, 0x1f, 0x76, 0x0c, 0x54, 0x89, 0xd0, 0x0f, 0x41, 0xfe, 0xe4,
+				0xb4, 0xf9,
+			];
+
+			let mut dk_out = [0u8; 16];
+			derive_key(&password, b"salt", 1, &mut dk_out).unwrap();
+			assert_eq!(dk_out, expected);
+		}
+
+		#[test]
+		fn test_zero_iterations_and_dklen_err() {
+			let password = Password::from_slice(b"password").unwrap();
+			assert!(derive_key(&password, b"salt", 0, &mut [0u8; 32]).is_err());
+			assert!(derive_key(&password, b"salt", 1, &mut [0u8; 0]).is_err());
+		}
+	}
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -177,6 +579,121 @@ mod public {
 
 	// One function tested per submodule.
 
+	mod test_derivation {
+		use super::*;
+
+		#[test]
+		fn test_matches_derive_key() {
+			let password = Password::from_slice(b"password").unwrap();
+			for &(iterations, len) in &[(1, 1), (1, 64), (3, 65), (100, 200)] {
+				let mut expected = [0u8; 200];
+				derive_key(&password, b"salt", iterations, &mut expected[..len]).unwrap();
+
+				for &interval in &[0, 1, 7, 100, 1000] {
+					let mut dk_out = [0u8; 200];
+					derivation(&password, b"salt", iterations, &mut dk_out[..len])
+						.unwrap()
+						.run(interval, |_, _| true)
+						.unwrap();
+					assert_eq!(dk_out[..], expected[..]);
+				}
+			}
+		}
+
+		#[test]
+		fn test_sha256_matches_derive_key() {
+			let password = sha256::Password::from_slice(b"password").unwrap();
+			let mut expected = [0u8; 40];
+			sha256::derive_key(&password, b"salt", 10, &mut expected).unwrap();
+
+			let mut dk_out = [0u8; 40];
+			sha256::derivation(&password, b"salt", 10, &mut dk_out)
+				.unwrap()
+				.run(3, |_, _| true)
+				.unwrap();
+			assert_eq!(dk_out, expected);
+		}
+
+		#[test]
+		fn test_progress() {
+			let password = Password::from_slice(b"password").unwrap();
+			let mut dk_out = [0u8; 65];
+			let derivation = derivation(&password, b"salt", 10, &mut dk_out).unwrap();
+			assert_eq!(derivation.completed(), 0);
+			assert_eq!(derivation.total(), 20);
+
+			let mut calls = [(0, 0); 3];
+			let mut count = 0;
+			derivation
+				.run(8, |completed, total| {
+					calls[count] = (completed, total);
+					count += 1;
+					true
+				})
+				.unwrap();
+			assert_eq!(count, 3);
+			assert_eq!(calls, [(8, 20), (16, 20), (20, 20)]);
+		}
+
+		#[test]
+		fn test_cancel() {
+			let password = Password::from_slice(b"password").unwrap();
+			let mut dk_out = [0u8; 64];
+			let mut calls = 0;
+
+			let result = derivation(&password, b"salt", 100, &mut dk_out)
+				.unwrap()
+				.run(10, |completed, _| {
+					calls += 1;
+					completed < 30
+				});
+			assert_eq!(result, Err(CancelledCryptoError));
+			assert_eq!(calls, 3);
+			assert_eq!(&dk_out[..], &[0u8; 64][..]);
+		}
+
+		#[test]
+		fn test_step() {
+			let password = Password::from_slice(b"password").unwrap();
+			let mut expected = [0u8; 100];
+			derive_key(&password, b"salt", 50, &mut expected).unwrap();
+
+			let mut dk_out = [0u8; 100];
+			let mut derivation = derivation(&password, b"salt", 50, &mut dk_out).unwrap();
+			assert_eq!(derivation.step(0).unwrap(), Status::Pending);
+			assert_eq!(derivation.completed(), 0);
+			assert_eq!(derivation.step(49).unwrap(), Status::Pending);
+			assert_eq!(derivation.step(2).unwrap(), Status::Pending);
+			assert_eq!(derivation.completed(), 51);
+			assert_eq!(derivation.step(1000).unwrap(), Status::Done);
+			assert_eq!(derivation.completed(), 100);
+			// Stepping a finished derivation does nothing.
+			assert_eq!(derivation.step(1).unwrap(), Status::Done);
+			drop(derivation);
+
+			assert_eq!(&dk_out[..], &expected[..]);
+		}
+
+		#[test]
+		fn test_drop_pending_zeroes() {
+			let password = Password::from_slice(b"password").unwrap();
+			let mut dk_out = [0u8; 100];
+			let mut derivation = derivation(&password, b"salt", 50, &mut dk_out).unwrap();
+			// The first block is complete.
+			derivation.step(60).unwrap();
+			drop(derivation);
+
+			assert_eq!(&dk_out[..], &[0u8; 100][..]);
+		}
+
+		#[test]
+		fn test_new_err() {
+			let password = Password::from_slice(b"password").unwrap();
+			assert!(derivation(&password, b"salt", 0, &mut [0u8; 32]).is_err());
+			assert!(derivation(&password, b"salt", 1, &mut [0u8; 0]).is_err());
+		}
+	}
+
 	mod test_verify {
 		use super::*;
 
@@ -0,0 +1,342 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # Parameters:
+//! - `curve`: The curve the derived keys are for, which selects the master key
+//!   of the derivation.
+//! - `seed`: The seed the master key is derived from.
+//! - `index`: The index of a child key. Must be hardened, i.e. at least
+//!   `HARDENED_OFFSET`.
+//! - `path`: A derivation path, such as `m/44'/0'/1'`. Each index must be
+//!   hardened, which is marked with `'` or `H`.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The length of `seed` is less than 16 or greater than 64 bytes.
+//! - `index` is less than `HARDENED_OFFSET`.
+//! - `path` does not start with `m`, or contains an index that is not a
+//!   hardened decimal number less than 2^31.
+//! - A key deeper than 255 levels is derived.
+//!
+//! # Security:
+//! - Only hardened derivation is defined for Ed25519 and Curve25519 in
+//!   SLIP-0010, so a child key can never be derived from a public key, and a
+//!   leaked child key does not reveal its parent.
+//! - The `seed` should be generated using a CSPRNG, and be at least 32 bytes.
+//! - This module only derives the private keys and chain codes. Public keys
+//!   must be computed from the private keys with an Ed25519 or X25519
+//!   implementation.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::kdf::slip10::{derive_path, Curve, HARDENED_OFFSET};
+//!
+//! let seed = [0x42u8; 32];
+//! let device_key = derive_path(Curve::Ed25519, &seed, "m/0'/1'").unwrap();
+//!
+//! let account = derive_path(Curve::Ed25519, &seed, "m/0'").unwrap();
+//! let same_device_key = account.derive_child(HARDENED_OFFSET + 1).unwrap();
+//! assert!(device_key.private_key() == same_device_key.private_key());
+//! ```
+
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{mac::hmac, types},
+};
+
+/// The offset that marks an index as hardened.
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// The size of a private key and of a chain code.
+const EXTENDED_KEY_PART_SIZE: usize = 32;
+
+/// A type to represent the derived `PrivateKey` of an `ExtendedKey`.
+pub type PrivateKey = types::SecretKey<types::tag::Slip10PrivateKey, EXTENDED_KEY_PART_SIZE>;
+
+/// A type to represent the `ChainCode` of an `ExtendedKey`.
+pub type ChainCode = types::SecretKey<types::tag::Slip10ChainCode, EXTENDED_KEY_PART_SIZE>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The curves that SLIP-0010 defines derivation for in this module.
+pub enum Curve {
+	/// Ed25519, with the master key `"ed25519 seed"`.
+	Ed25519,
+	/// Curve25519 for X25519, with the master key `"curve25519 seed"`.
+	Curve25519,
+}
+
+impl Curve {
+	/// Return the HMAC key that the master key is derived with.
+	fn master_hmac_key(self) -> &'static [u8] {
+		match self {
+			Curve::Ed25519 => b"ed25519 seed",
+			Curve::Curve25519 => b"curve25519 seed",
+		}
+	}
+}
+
+#[derive(Debug)]
+/// A private key and chain code at some depth of a SLIP-0010 derivation tree.
+pub struct ExtendedKey {
+	curve: Curve,
+	depth: u8,
+	private_key: PrivateKey,
+	chain_code: ChainCode,
+}
+
+impl ExtendedKey {
+	/// Split the HMAC-SHA512 `tag` into a private key and chain code.
+	fn from_tag(curve: Curve, depth: u8, tag: &hmac::Tag) -> Result<Self, UnknownCryptoError> {
+		let bytes = tag.unprotected_as_bytes();
+
+		Ok(Self {
+			curve,
+			depth,
+			private_key: PrivateKey::from_slice(&bytes[..EXTENDED_KEY_PART_SIZE])?,
+			chain_code: ChainCode::from_slice(&bytes[EXTENDED_KEY_PART_SIZE..])?,
+		})
+	}
+
+	#[must_use]
+	/// Derive the master `ExtendedKey` of `curve` from `seed`.
+	pub fn master(curve: Curve, seed: &[u8]) -> Result<Self, UnknownCryptoError> {
+		if seed.len() < 16 || seed.len() > 64 {
+			return Err(UnknownCryptoError);
+		}
+
+		let hmac_key = hmac::SecretKey::from_slice(curve.master_hmac_key())?;
+
+		Self::from_tag(curve, 0, &hmac::hmac(&hmac_key, seed)?)
+	}
+
+	#[must_use]
+	/// Derive the hardened child `ExtendedKey` with `index`.
+	pub fn derive_child(&self, index: u32) -> Result<Self, UnknownCryptoError> {
+		if index < HARDENED_OFFSET || self.depth == u8::max_value() {
+			return Err(UnknownCryptoError);
+		}
+
+		let hmac_key = hmac::SecretKey::from_slice(self.chain_code.unprotected_as_bytes())?;
+		let mut state = hmac::init(&hmac_key);
+		state.update(&[0u8])?;
+		state.update(self.private_key.unprotected_as_bytes())?;
+		state.update(&index.to_be_bytes())?;
+
+		Self::from_tag(self.curve, self.depth + 1, &state.finalize()?)
+	}
+
+	/// Return the curve the `ExtendedKey` was derived for.
+	pub fn curve(&self) -> Curve { self.curve }
+
+	/// Return the depth of the `ExtendedKey`, where the master key is at 0.
+	pub fn depth(&self) -> u8 { self.depth }
+
+	/// Return the private key.
+	pub fn private_key(&self) -> &PrivateKey { &self.private_key }
+
+	/// Return the chain code.
+	pub fn chain_code(&self) -> &ChainCode { &self.chain_code }
+}
+
+/// Parse one hardened index of a derivation path, such as `44'` or `44H`.
+fn parse_hardened_index(segment: &str) -> Result<u32, UnknownCryptoError> {
+	let digits = match segment.as_bytes().last() {
+		Some(b'\'') | Some(b'H') => &segment[..segment.len() - 1],
+		_ => return Err(UnknownCryptoError),
+	};
+	if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+		return Err(UnknownCryptoError);
+	}
+
+	let index: u32 = digits.parse().map_err(|_| UnknownCryptoError)?;
+	if index >= HARDENED_OFFSET {
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(index + HARDENED_OFFSET)
+}
+
+#[must_use]
+/// Derive the `ExtendedKey` of `curve` at `path` from `seed`.
+pub fn derive_path(
+	curve: Curve,
+	seed: &[u8],
+	path: &str,
+) -> Result<ExtendedKey, UnknownCryptoError> {
+	let mut segments = path.split('/');
+	if segments.next() != Some("m") {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut key = ExtendedKey::master(curve, seed)?;
+	for segment in segments {
+		key = key.derive_child(parse_hardened_index(segment)?)?;
+	}
+
+	Ok(key)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	/// Check the chain code and private key of `key` against the hex strings
+	/// from the SLIP-0010 test vectors.
+	fn check(key: &ExtendedKey, chain_code: &str, private_key: &str) {
+		let mut expected = [0u8; 32];
+		crate::encoding::hex_decode_into(chain_code, &mut expected).unwrap();
+		assert_eq!(key.chain_code().unprotected_as_bytes(), &expected[..]);
+		crate::encoding::hex_decode_into(private_key, &mut expected).unwrap();
+		assert_eq!(key.private_key().unprotected_as_bytes(), &expected[..]);
+	}
+
+	const SEED_1: [u8; 16] = [
+		0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+		0x0f,
+	];
+
+	// One function tested per submodule.
+	mod test_master {
+		use super::*;
+
+		#[test]
+		fn test_seed_length() {
+			assert!(ExtendedKey::master(Curve::Ed25519, &[0u8; 15]).is_err());
+			assert!(ExtendedKey::master(Curve::Ed25519, &[0u8; 16]).is_ok());
+			assert!(ExtendedKey::master(Curve::Ed25519, &[0u8; 64]).is_ok());
+			assert!(ExtendedKey::master(Curve::Ed25519, &[0u8; 65]).is_err());
+		}
+
+		#[test]
+		fn test_vectors() {
+			let key = ExtendedKey::master(Curve::Ed25519, &SEED_1).unwrap();
+			assert_eq!(key.depth(), 0);
+			check(
+				&key,
+				"90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+				"2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+			);
+
+			let key = ExtendedKey::master(Curve::Curve25519, &SEED_1).unwrap();
+			assert_eq!(key.curve(), Curve::Curve25519);
+			check(
+				&key,
+				"77997ca3588a1a34f3589279ea2962247abfe5277d52770a44c706378c710768",
+				"d70a59c2e68b836cc4bbe8bcae425169b9e2384f3905091e3d60b890e90cd92c",
+			);
+		}
+	}
+
+	mod test_derive_child {
+		use super::*;
+
+		#[test]
+		fn test_non_hardened_err() {
+			let key = ExtendedKey::master(Curve::Ed25519, &SEED_1).unwrap();
+			assert!(key.derive_child(0).is_err());
+			assert!(key.derive_child(HARDENED_OFFSET - 1).is_err());
+			assert!(key.derive_child(HARDENED_OFFSET).is_ok());
+			assert!(key.derive_child(u32::max_value()).is_ok());
+		}
+
+		#[test]
+		fn test_max_depth() {
+			let mut key = ExtendedKey::master(Curve::Ed25519, &SEED_1).unwrap();
+			for _ in 0..255 {
+				key = key.derive_child(HARDENED_OFFSET).unwrap();
+			}
+			assert_eq!(key.depth(), 255);
+			assert!(key.derive_child(HARDENED_OFFSET).is_err());
+		}
+
+		#[test]
+		fn test_vectors() {
+			let master = ExtendedKey::master(Curve::Ed25519, &SEED_1).unwrap();
+			let child = master.derive_child(HARDENED_OFFSET).unwrap();
+			assert_eq!(child.depth(), 1);
+			check(
+				&child,
+				"8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+				"68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+			);
+		}
+	}
+
+	mod test_derive_path {
+		use super::*;
+
+		#[test]
+		fn test_ed25519_vector_1() {
+			let key = derive_path(Curve::Ed25519, &SEED_1, "m/0H/1H/2H/2H/1000000000H").unwrap();
+			assert_eq!(key.depth(), 5);
+			check(
+				&key,
+				"68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+				"8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+			);
+		}
+
+		#[test]
+		fn test_ed25519_vector_2() {
+			let mut seed = [0u8; 64];
+			for (i, byte) in seed.iter_mut().enumerate() {
+				*byte = 0xffu8.wrapping_sub((i as u8) * 3);
+			}
+			let key = derive_path(Curve::Ed25519, &seed, "m/0'/2147483647'/1'/2147483646'/2'");
+			check(
+				&key.unwrap(),
+				"5d70af781f3a37b829f0d060924d5e960bdc02e85423494afc0b1a41bbe196d4",
+				"551d333177df541ad876a60ea71f00447931c0a9da16f227c11ea080d7391b8d",
+			);
+		}
+
+		#[test]
+		fn test_curve25519_vector_1() {
+			let key = derive_path(Curve::Curve25519, &SEED_1, "m/0'/1'/2'/2'/1000000000'").unwrap();
+			check(
+				&key,
+				"8ccf15d55b1dda246b0c1bf3e979a471a82524c1bd0c1eaecccf00dde72168bb",
+				"7a59954d387abde3bc703f531f67d659ec2b8a12597ae82824547d7e27991e26",
+			);
+		}
+
+		#[test]
+		fn test_master_path() {
+			let key = derive_path(Curve::Ed25519, &SEED_1, "m").unwrap();
+			let master = ExtendedKey::master(Curve::Ed25519, &SEED_1).unwrap();
+			assert!(key.private_key() == master.private_key());
+		}
+
+		#[test]
+		fn test_bad_path_err() {
+			for path in [
+				"", "0'", "M/0'", "m/", "m/0", "m/0'/1", "m//0'", "m/'", "m/-1'", "m/+1'", "m/a'",
+				"m/2147483648'", "m/0''", "m/0h", " m/0'",
+			]
+			.iter()
+			{
+				assert!(derive_path(Curve::Ed25519, &SEED_1, path).is_err(), "{}", path);
+			}
+		}
+	}
+}
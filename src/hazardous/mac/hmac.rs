@@ -24,18 +24,47 @@
 //! - `secret_key`:  The authentication key.
 //! - `data`: Data to be authenticated.
 //! - `expected`: The expected authentication tag.
+//! - `dst_out`: Destination buffer for a full or truncated authentication tag.
+//! - `len`: The length of the expected truncated authentication tag.
+//! - `expected_hex`, `expected_base64`: The expected authentication tag,
+//!   encoded as hex or as standard Base64 with optional padding.
 //!
 //! # Errors:
 //! An error will be returned if:
 //! - `finalize()` is called twice without a `reset()` in between.
 //! - `update()` is called after `finalize()` without a `reset()` in between.
 //! - The HMAC does not match the expected when verifying.
+//! - The length of `dst_out` or `len` is less than
+//!   `HMAC_MIN_TRUNCATED_OUTSIZE` or greater than the output size of the hash
+//!   function (64 for SHA512, 32 for SHA256).
+//! - The length of `expected` is not `len` when verifying a truncated tag.
+//! - The length of `dst_out` is not the output size of the hash function when
+//!   writing a full tag with `finalize_into()` or `hmac_into()`.
+//! - `from_state_bytes()` is called with bytes that are not a state serialized
+//!   by `to_state_bytes()`.
+//! - `expected_hex` or `expected_base64` is not a valid encoding of a full
+//!   tag.
 //!
 //! # Security:
 //! - The secret key should always be generated using a CSPRNG.
 //!   `SecretKey::generate()` can be used
 //! for this. It generates a secret key of 128 bytes.
 //! - The minimum recommended size for a secret key is 64 bytes.
+//! - Truncating a tag lowers the security of the MAC. Only truncate tags when
+//!   a protocol requires it, and never below `HMAC_MIN_TRUNCATED_OUTSIZE`
+//!   (128 bits).
+//! - `len` must come from the protocol, not from the length of the received
+//!   tag, since an attacker may shorten the tag.
+//! - The bytes returned by `to_state_bytes()` can be used to authenticate
+//!   arbitrary data, and must be protected the same way as the secret key.
+//! - A tag written with `finalize_into()` or `hmac_into()` is a plain byte
+//!   buffer. Never compare it with `==`; use `verify_truncated()` with a
+//!   `len` of 64 instead.
+//! - `verify_hex()` and `verify_base64()` are meant for tags received from a
+//!   remote service, such as a webhook signature. Instead of comparing the
+//!   decoded tag directly, both tags are authenticated again with a random key
+//!   and those results are compared. Any prefix, such as `sha256=`, must be
+//!   removed by the caller.
 //!
 //! # Recommendation:
 //! - If you are unsure of whether to use HMAC or Poly1305, it is most often
@@ -53,14 +82,77 @@
 //! let tag = state.finalize().unwrap();
 //!
 //! assert!(hmac::verify(&tag, &key, b"Some message.").unwrap());
+//!
+//! // Verify a streamed message without handling the computed tag.
+//! let mut state = hmac::init(&key);
+//! state.update(b"Some message.").unwrap();
+//! assert!(state.finalize_verify(&tag).unwrap());
+//!
+//! // A tag truncated to 128 bits.
+//! let mut truncated = [0u8; 16];
+//! hmac::hmac_truncated(&key, b"Some message.", &mut truncated).unwrap();
+//! assert!(hmac::verify_truncated(&truncated, &key, b"Some message.", 16).unwrap());
+//!
+//! // A full tag written to a caller-provided buffer.
+//! let mut tag_buf = [0u8; 64];
+//! hmac::hmac_into(&key, b"Some message.", &mut tag_buf).unwrap();
+//! assert!(hmac::verify_truncated(&tag_buf, &key, b"Some message.", 64).unwrap());
+//! ```
+//!
+//! `Hmac` is generic over the hash function. HMAC-SHA256 is available in
+//! `hmac::sha256`:
+//! ```
+//! use orion::hazardous::mac::hmac;
+//!
+//! let key = hmac::sha256::SecretKey::generate().unwrap();
+//! let tag = hmac::sha256::hmac(&key, b"Some message.").unwrap();
+//! assert!(hmac::sha256::verify(&tag, &key, b"Some message.").unwrap());
+//!
+//! // A hex-encoded tag received from a webhook.
+//! let key = hmac::sha256::SecretKey::from_slice(b"Jefe").unwrap();
+//! let received = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+//! assert!(hmac::sha256::verify_hex(received, &key, b"what do ya want for nothing?").unwrap());
+//! ```
+//!
+//! HMAC-SHA512-256, HMAC-SHA512 with the tag truncated to 32 bytes, is
+//! available in `hmac::sha512_256`. It uses the same `SecretKey` as
+//! HMAC-SHA512, but returns its own 32-byte `Tag`:
+//! ```
+//! use orion::hazardous::mac::hmac;
+//!
+//! let key = hmac::SecretKey::generate().unwrap();
+//! let tag = hmac::sha512_256::hmac(&key, b"Some message.").unwrap();
+//! assert!(hmac::sha512_256::verify(&tag, &key, b"Some message.").unwrap());
+//! ```
+//!
+//! With the `serializable_state` feature, an HMAC-SHA512 state can be saved
+//! and resumed later:
+//! ```
+//! # #[cfg(feature = "serializable_state")] {
+//! use orion::hazardous::mac::hmac;
+//!
+//! let key = hmac::SecretKey::generate().unwrap();
+//!
+//! let mut state = hmac::init(&key);
+//! state.update(b"Some ").unwrap();
+//! let checkpoint = state.to_state_bytes();
+//!
+//! let mut resumed = hmac::Hmac::from_state_bytes(&checkpoint).unwrap();
+//! resumed.update(b"message.").unwrap();
+//! assert!(hmac::verify(&resumed.finalize().unwrap(), &key, b"Some message.").unwrap());
+//! # }
 //! ```
 
 use crate::{
 	errors::{FinalizationCryptoError, UnknownCryptoError, ValidationCryptoError},
 	hazardous::{
-		constants::{BlocksizeArray, SHA512_BLOCKSIZE, SHA512_OUTSIZE},
-		hash::sha512,
+		constants::{
+			BlocksizeArray, HLenArray, HMAC_MIN_TRUNCATED_OUTSIZE, SHA512_BLOCKSIZE, SHA512_OUTSIZE,
+		},
+		hash::{sha512, HashFunction},
+		mac::Mac,
 	},
+	util,
 };
 use zeroize::Zeroize;
 
@@ -89,17 +181,69 @@ construct_tag! {
 	(Tag, SHA512_OUTSIZE)
 }
 
+/// A hash function that HMAC can be instantiated with, together with the
+/// `SecretKey` and `Tag` types of the resulting HMAC.
+pub trait HmacHash: HashFunction {
+	/// The `SecretKey` type, padded to `BLOCKSIZE` bytes.
+	type SecretKey;
+	/// The `Tag` type, of `OUTSIZE` bytes.
+	type Tag: PartialEq;
+
+	#[must_use]
+	/// Make a `SecretKey` from `slice`, hashing it first if it is longer than
+	/// `BLOCKSIZE` bytes.
+	fn key_from_slice(slice: &[u8]) -> Result<Self::SecretKey, UnknownCryptoError>;
+
+	/// Return the padded bytes of `secret_key`.
+	fn unprotected_key_bytes(secret_key: &Self::SecretKey) -> &[u8];
+
+	#[must_use]
+	/// Make a `Tag` from a slice of `OUTSIZE` bytes.
+	fn tag_from_slice(slice: &[u8]) -> Result<Self::Tag, UnknownCryptoError>;
+
+	/// Return the bytes of `tag`.
+	fn unprotected_tag_bytes(tag: &Self::Tag) -> &[u8];
+}
+
+impl HmacHash for sha512::Sha512 {
+	type SecretKey = SecretKey;
+	type Tag = Tag;
+
+	fn key_from_slice(slice: &[u8]) -> Result<SecretKey, UnknownCryptoError> {
+		SecretKey::from_slice(slice)
+	}
+
+	fn unprotected_key_bytes(secret_key: &SecretKey) -> &[u8] { secret_key.unprotected_as_bytes() }
+
+	fn tag_from_slice(slice: &[u8]) -> Result<Tag, UnknownCryptoError> { Tag::from_slice(slice) }
+
+	fn unprotected_tag_bytes(tag: &Tag) -> &[u8] { tag.unprotected_as_bytes() }
+}
+
+#[cfg(feature = "serializable_state")]
+/// Version of the format produced by `to_state_bytes()`.
+const STATE_VERSION: u8 = 1;
+
+#[cfg(feature = "serializable_state")]
+/// Identifier of HMAC-SHA512 in the format produced by `to_state_bytes()`.
+const STATE_ID: u8 = 3;
+
+#[cfg(feature = "serializable_state")]
+/// Length of the format produced by `to_state_bytes()`.
+const STATE_BYTES_LEN: usize = 2 + (3 * sha512::STATE_FIELDS_LEN) + 1;
+
 #[must_use]
 #[derive(Clone)]
-/// HMAC-SHA512 streaming state.
-pub struct Hmac {
-	working_hasher: sha512::Sha512,
-	opad_hasher: sha512::Sha512,
-	ipad_hasher: sha512::Sha512,
+/// HMAC streaming state, generic over the hash function. `Hmac` on its own
+/// is HMAC-SHA512.
+pub struct Hmac<H: HmacHash = sha512::Sha512> {
+	working_hasher: H,
+	opad_hasher: H,
+	ipad_hasher: H,
 	is_finalized: bool,
 }
 
-impl core::fmt::Debug for Hmac {
+impl<H: HmacHash> core::fmt::Debug for Hmac<H> {
 	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
 		write!(
 			f,
@@ -110,30 +254,62 @@ impl core::fmt::Debug for Hmac {
 	}
 }
 
-impl Hmac {
+impl<H: HmacHash> Hmac<H> {
 	#[inline]
 	/// Pad `key` with `ipad` and `opad`.
-	fn pad_key_io(&mut self, key: &SecretKey) {
+	fn pad_key_io(&mut self, key: &[u8]) {
+		// The largest supported block size is that of SHA512.
 		let mut ipad: BlocksizeArray = [0x36; SHA512_BLOCKSIZE];
 		let mut opad: BlocksizeArray = [0x5C; SHA512_BLOCKSIZE];
-		// `key` has already been padded with zeroes to a length of SHA512_BLOCKSIZE
+		// `key` has already been padded with zeroes to a length of H::BLOCKSIZE
 		// in SecretKey::from_slice
-		assert_eq!(key.unprotected_as_bytes().len(), SHA512_BLOCKSIZE);
-		for (idx, itm) in key.unprotected_as_bytes().iter().enumerate() {
+		assert_eq!(key.len(), H::BLOCKSIZE);
+		for (idx, itm) in key.iter().enumerate() {
 			opad[idx] ^= itm;
 			ipad[idx] ^= itm;
 		}
 
-		// Due to opad_hasher and ipad_hasher being initialized in init()
+		// Due to opad_hasher and ipad_hasher being initialized in new()
 		// and the size of input to update() is known to be acceptable size,
 		// .unwrap() here should not be able to panic
-		self.ipad_hasher.update(ipad.as_ref()).unwrap();
-		self.opad_hasher.update(opad.as_ref()).unwrap();
+		self.ipad_hasher.update(&ipad[..H::BLOCKSIZE]).unwrap();
+		self.opad_hasher.update(&opad[..H::BLOCKSIZE]).unwrap();
 		self.working_hasher = self.ipad_hasher.clone();
 		ipad.zeroize();
 		opad.zeroize();
 	}
 
+	/// Finalize the state and write the `Tag` to `dst_out`, which must be
+	/// `H::OUTSIZE` bytes.
+	fn finalize_to_dst(&mut self, dst_out: &mut [u8]) -> Result<(), FinalizationCryptoError> {
+		if self.is_finalized {
+			return Err(FinalizationCryptoError);
+		}
+
+		self.is_finalized = true;
+		let mut outer_hasher = self.opad_hasher.clone();
+		// The inner digest is written directly into `dst_out` and then
+		// overwritten by the outer digest.
+		self.working_hasher.finalize_into(dst_out)?;
+		outer_hasher.update(dst_out)?;
+		outer_hasher.finalize_into(dst_out)?;
+
+		Ok(())
+	}
+
+	/// Initialize `Hmac` struct with a given key.
+	pub fn new(secret_key: &H::SecretKey) -> Self {
+		let mut state = Hmac {
+			working_hasher: H::init(),
+			opad_hasher: H::init(),
+			ipad_hasher: H::init(),
+			is_finalized: false,
+		};
+
+		state.pad_key_io(H::unprotected_key_bytes(secret_key));
+		state
+	}
+
 	/// Reset to `init()` state.
 	pub fn reset(&mut self) {
 		self.working_hasher = self.ipad_hasher.clone();
@@ -153,34 +329,122 @@ impl Hmac {
 
 	#[must_use]
 	/// Return a `Tag`.
-	pub fn finalize(&mut self) -> Result<Tag, FinalizationCryptoError> {
-		if self.is_finalized {
-			return Err(FinalizationCryptoError);
+	pub fn finalize(&mut self) -> Result<H::Tag, FinalizationCryptoError> {
+		let mut tag: HLenArray = [0u8; SHA512_OUTSIZE];
+		self.finalize_to_dst(&mut tag[..H::OUTSIZE])?;
+		let res = H::tag_from_slice(&tag[..H::OUTSIZE]);
+		tag.zeroize();
+
+		Ok(res?)
+	}
+
+	#[must_use]
+	/// Write the `Tag` to `dst_out`, which must be `H::OUTSIZE` bytes.
+	pub fn finalize_into(&mut self, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		if dst_out.len() != H::OUTSIZE {
+			return Err(UnknownCryptoError);
 		}
 
-		self.is_finalized = true;
-		let mut outer_hasher = self.opad_hasher.clone();
-		outer_hasher.update(self.working_hasher.finalize()?.as_bytes())?;
-		let tag = Tag::from_slice(outer_hasher.finalize()?.as_bytes())?;
+		Ok(self.finalize_to_dst(dst_out)?)
+	}
+
+	#[must_use]
+	/// Finalize the state and compare the result with `expected` in constant
+	/// time. The state is consumed, so the computed `Tag` is never exposed.
+	pub fn finalize_verify(mut self, expected: &H::Tag) -> Result<bool, ValidationCryptoError> {
+		if expected == &self.finalize()? {
+			Ok(true)
+		} else {
+			Err(ValidationCryptoError)
+		}
+	}
+
+	#[must_use]
+	/// Write the first `dst_out.len()` bytes of the `Tag` to `dst_out`.
+	pub fn finalize_truncated(&mut self, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+		if dst_out.len() < HMAC_MIN_TRUNCATED_OUTSIZE || dst_out.len() > H::OUTSIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut tag: HLenArray = [0u8; SHA512_OUTSIZE];
+		self.finalize_to_dst(&mut tag[..H::OUTSIZE])?;
+		dst_out.copy_from_slice(&tag[..dst_out.len()]);
+		tag.zeroize();
+
+		Ok(())
+	}
+
+	#[must_use]
+	/// Verify a `Tag` truncated to `len` bytes in constant time.
+	pub fn verify_truncated(
+		&mut self,
+		expected: &[u8],
+		len: usize,
+	) -> Result<bool, ValidationCryptoError> {
+		if expected.len() != len || len < HMAC_MIN_TRUNCATED_OUTSIZE || len > H::OUTSIZE {
+			return Err(ValidationCryptoError);
+		}
 
-		Ok(tag)
+		let mut tag: HLenArray = [0u8; SHA512_OUTSIZE];
+		self.finalize_truncated(&mut tag[..len])?;
+
+		if util::secure_cmp(expected, &tag[..len]).is_ok() {
+			Ok(true)
+		} else {
+			Err(ValidationCryptoError)
+		}
 	}
 }
 
-#[must_use]
-/// Initialize `Hmac` struct with a given key.
-pub fn init(secret_key: &SecretKey) -> Hmac {
-	let mut state = Hmac {
-		working_hasher: sha512::init(),
-		opad_hasher: sha512::init(),
-		ipad_hasher: sha512::init(),
-		is_finalized: false,
-	};
+#[cfg(feature = "serializable_state")]
+impl Hmac<sha512::Sha512> {
+	#[must_use]
+	/// Serialize the state, so that it can be resumed with `from_state_bytes()`.
+	///
+	/// The format starts with a version byte and an identifier byte for
+	/// HMAC-SHA512. It is followed by the fields of the three inner SHA512
+	/// states, as serialized by `Sha512::to_state_bytes()`, and a single byte
+	/// for whether the state is finalized.
+	pub fn to_state_bytes(&self) -> Vec<u8> {
+		let mut state_bytes = Vec::with_capacity(STATE_BYTES_LEN);
+		state_bytes.push(STATE_VERSION);
+		state_bytes.push(STATE_ID);
+		self.working_hasher.append_state_fields(&mut state_bytes);
+		self.opad_hasher.append_state_fields(&mut state_bytes);
+		self.ipad_hasher.append_state_fields(&mut state_bytes);
+		state_bytes.push(self.is_finalized as u8);
+
+		state_bytes
+	}
+
+	#[must_use]
+	/// Resume a state serialized with `to_state_bytes()`.
+	pub fn from_state_bytes(state_bytes: &[u8]) -> Result<Hmac, UnknownCryptoError> {
+		if state_bytes.len() != STATE_BYTES_LEN
+			|| state_bytes[0] != STATE_VERSION
+			|| state_bytes[1] != STATE_ID
+			|| state_bytes[STATE_BYTES_LEN - 1] > 1
+		{
+			return Err(UnknownCryptoError);
+		}
+
+		let fields = &state_bytes[2..(STATE_BYTES_LEN - 1)];
+		let (working, pads) = fields.split_at(sha512::STATE_FIELDS_LEN);
+		let (opad, ipad) = pads.split_at(sha512::STATE_FIELDS_LEN);
 
-	state.pad_key_io(secret_key);
-	state
+		Ok(Hmac {
+			working_hasher: sha512::Sha512::from_state_fields(working)?,
+			opad_hasher: sha512::Sha512::from_state_fields(opad)?,
+			ipad_hasher: sha512::Sha512::from_state_fields(ipad)?,
+			is_finalized: state_bytes[STATE_BYTES_LEN - 1] == 1,
+		})
+	}
 }
 
+#[must_use]
+/// Initialize `Hmac` struct with a given key.
+pub fn init(secret_key: &SecretKey) -> Hmac { Hmac::new(secret_key) }
+
 #[must_use]
 /// One-shot function for generating an HMAC-SHA512 tag of `data`.
 pub fn hmac(secret_key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
@@ -190,6 +454,19 @@ pub fn hmac(secret_key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoErr
 	Ok(hmac_state.finalize()?)
 }
 
+#[must_use]
+/// One-shot function for generating an HMAC-SHA512 tag of `data` and writing
+/// it to `dst_out`, which must be `SHA512_OUTSIZE` bytes.
+pub fn hmac_into(
+	secret_key: &SecretKey,
+	data: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	let mut hmac_state = init(secret_key);
+	hmac_state.update(data)?;
+	hmac_state.finalize_into(dst_out)
+}
+
 #[must_use]
 /// Verify a HMAC-SHA512 Tag in constant time.
 pub fn verify(
@@ -199,14 +476,537 @@ pub fn verify(
 ) -> Result<bool, ValidationCryptoError> {
 	let mut hmac_state = init(secret_key);
 	hmac_state.update(data)?;
+	hmac_state.finalize_verify(expected)
+}
 
-	if expected == &hmac_state.finalize()? {
+#[must_use]
+/// One-shot function for generating an HMAC-SHA512 tag of `data`, truncated
+/// to `dst_out.len()` bytes.
+pub fn hmac_truncated(
+	secret_key: &SecretKey,
+	data: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	let mut hmac_state = init(secret_key);
+	hmac_state.update(data)?;
+	hmac_state.finalize_truncated(dst_out)
+}
+
+#[must_use]
+/// Verify a HMAC-SHA512 Tag truncated to `len` bytes in constant time.
+pub fn verify_truncated(
+	expected: &[u8],
+	secret_key: &SecretKey,
+	data: &[u8],
+	len: usize,
+) -> Result<bool, ValidationCryptoError> {
+	let mut hmac_state = init(secret_key);
+	hmac_state.update(data)?;
+	hmac_state.verify_truncated(expected, len)
+}
+
+#[cfg(feature = "safe_api")]
+/// Compare `expected` and `tag` by authenticating both with a random key and
+/// comparing the results. Since the key is unknown to an attacker, timing of
+/// the comparison reveals nothing about `tag`.
+fn double_hmac_cmp(expected: &[u8], tag: &[u8]) -> Result<bool, ValidationCryptoError> {
+	let blinding_key = SecretKey::generate()?;
+
+	let mut expected_state = init(&blinding_key);
+	expected_state.update(expected)?;
+	let mut tag_state = init(&blinding_key);
+	tag_state.update(tag)?;
+
+	if expected_state.finalize()? == tag_state.finalize()? {
 		Ok(true)
 	} else {
 		Err(ValidationCryptoError)
 	}
 }
 
+#[cfg(feature = "safe_api")]
+/// Decode a standard Base64 tag of `len` bytes, with or without padding.
+fn decode_base64_tag(src: &str, len: usize) -> Result<Vec<u8>, UnknownCryptoError> {
+	let unpadded = src.trim_end_matches('=');
+	let padding = src.len() - unpadded.len();
+	if padding > 2 || (padding > 0 && src.len() % 4 != 0) {
+		return Err(UnknownCryptoError);
+	}
+
+	let decoded = crate::encoding::base64_decode(unpadded, false)?;
+	if decoded.len() != len {
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(decoded)
+}
+
+#[must_use]
+#[cfg(feature = "safe_api")]
+/// Verify a hex-encoded HMAC-SHA512 tag, such as one sent by a webhook. The
+/// comparison is done with a second HMAC under a random key.
+pub fn verify_hex(
+	expected_hex: &str,
+	secret_key: &SecretKey,
+	data: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+	let mut expected = [0u8; SHA512_OUTSIZE];
+	crate::encoding::hex_decode_into(expected_hex, &mut expected)?;
+
+	double_hmac_cmp(&expected, hmac(secret_key, data)?.unprotected_as_bytes())
+}
+
+#[must_use]
+#[cfg(feature = "safe_api")]
+/// Verify a Base64-encoded HMAC-SHA512 tag, such as one sent by a webhook.
+/// The comparison is done with a second HMAC under a random key.
+pub fn verify_base64(
+	expected_base64: &str,
+	secret_key: &SecretKey,
+	data: &[u8],
+) -> Result<bool, ValidationCryptoError> {
+	let expected = decode_base64_tag(expected_base64, SHA512_OUTSIZE)?;
+
+	double_hmac_cmp(&expected, hmac(secret_key, data)?.unprotected_as_bytes())
+}
+
+#[cfg(feature = "safe_api")]
+/// Update the state with the data written. This makes it possible to use
+/// `std::io::copy()` to process a reader, such as a file.
+///
+/// An error is returned if writing to a finalized state.
+impl<H: HmacHash> std::io::Write for Hmac<H> {
+	fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+		self.update(data)?;
+		Ok(data.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+impl<H: HmacHash> Mac for Hmac<H> {
+	type Tag = H::Tag;
+
+	fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
+		Hmac::update(self, data)
+	}
+
+	fn finalize(&mut self) -> Result<H::Tag, FinalizationCryptoError> { Hmac::finalize(self) }
+}
+
+/// HMAC-SHA256.
+pub mod sha256 {
+	use super::HmacHash;
+	use crate::{
+		errors::{UnknownCryptoError, ValidationCryptoError},
+		hazardous::{
+			constants::{SHA256_BLOCKSIZE, SHA256_OUTSIZE},
+			hash::sha256::Sha256,
+		},
+	};
+
+	construct_hmac_key! {
+		/// A type to represent the `SecretKey` that HMAC-SHA256 uses for authentication.
+		///
+		/// # Note:
+		/// `SecretKey` pads the secret key for use with HMAC-SHA256 to a length of 64, when initialized.
+		///
+		/// Using `unprotected_as_bytes()` will return the secret key with padding.
+		///
+		/// Using `get_length()` will return the length with padding (always 64).
+		///
+		/// # Exceptions:
+		/// An exception will be thrown if:
+		/// - The `OsRng` fails to initialize or read from its source.
+		(SecretKey, SHA256_BLOCKSIZE, sha256, SHA256_OUTSIZE)
+	}
+
+	construct_tag! {
+		/// A type to represent the `Tag` that HMAC-SHA256 returns.
+		///
+		/// # Exceptions:
+		/// An exception will be thrown if:
+		/// - `slice` is not 32 bytes.
+		(Tag, SHA256_OUTSIZE)
+	}
+
+	impl HmacHash for Sha256 {
+		type SecretKey = SecretKey;
+		type Tag = Tag;
+
+		fn key_from_slice(slice: &[u8]) -> Result<SecretKey, UnknownCryptoError> {
+			SecretKey::from_slice(slice)
+		}
+
+		fn unprotected_key_bytes(secret_key: &SecretKey) -> &[u8] {
+			secret_key.unprotected_as_bytes()
+		}
+
+		fn tag_from_slice(slice: &[u8]) -> Result<Tag, UnknownCryptoError> {
+			Tag::from_slice(slice)
+		}
+
+		fn unprotected_tag_bytes(tag: &Tag) -> &[u8] { tag.unprotected_as_bytes() }
+	}
+
+	/// HMAC-SHA256 streaming state.
+	pub type Hmac = super::Hmac<Sha256>;
+
+	#[must_use]
+	/// Initialize `Hmac` struct with a given key.
+	pub fn init(secret_key: &SecretKey) -> Hmac { Hmac::new(secret_key) }
+
+	#[must_use]
+	/// One-shot function for generating an HMAC-SHA256 tag of `data`.
+	pub fn hmac(secret_key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+		let mut hmac_state = init(secret_key);
+		hmac_state.update(data)?;
+
+		Ok(hmac_state.finalize()?)
+	}
+
+	#[must_use]
+	/// Verify a HMAC-SHA256 Tag in constant time.
+	pub fn verify(
+		expected: &Tag,
+		secret_key: &SecretKey,
+		data: &[u8],
+	) -> Result<bool, ValidationCryptoError> {
+		let mut hmac_state = init(secret_key);
+		hmac_state.update(data)?;
+		hmac_state.finalize_verify(expected)
+	}
+
+	#[must_use]
+	#[cfg(feature = "safe_api")]
+	/// Verify a hex-encoded HMAC-SHA256 tag, such as one sent by a webhook. The
+	/// comparison is done with a second HMAC under a random key.
+	pub fn verify_hex(
+		expected_hex: &str,
+		secret_key: &SecretKey,
+		data: &[u8],
+	) -> Result<bool, ValidationCryptoError> {
+		let mut expected = [0u8; SHA256_OUTSIZE];
+		crate::encoding::hex_decode_into(expected_hex, &mut expected)?;
+
+		super::double_hmac_cmp(&expected, hmac(secret_key, data)?.unprotected_as_bytes())
+	}
+
+	#[must_use]
+	#[cfg(feature = "safe_api")]
+	/// Verify a Base64-encoded HMAC-SHA256 tag, such as one sent by a webhook.
+	/// The comparison is done with a second HMAC under a random key.
+	pub fn verify_base64(
+		expected_base64: &str,
+		secret_key: &SecretKey,
+		data: &[u8],
+	) -> Result<bool, ValidationCryptoError> {
+		let expected = super::decode_base64_tag(expected_base64, SHA256_OUTSIZE)?;
+
+		super::double_hmac_cmp(&expected, hmac(secret_key, data)?.unprotected_as_bytes())
+	}
+
+	// Testing public functions in the module.
+	#[cfg(test)]
+	mod public {
+		use super::*;
+
+		#[test]
+		fn test_rfc4231_test_case_1() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let expected = Tag::from_slice(&[
+				0xb0, 0x34, 0x4c, 0x61, 0xd8, 0xdb, 0x38, 0x53, 0x5c, 0xa8, 0xaf, 0xce, 0xaf, 0x0b,
+				0xf1, 0x2b, 0x88, 0x1d, 0xc2, 0x00, 0xc9, 0x83, 0x3d, 0xa7, 0x26, 0xe9, 0x37, 0x6c,
+				0x2e, 0x32, 0xcf, 0xf7,
+			])
+			.unwrap();
+
+			assert_eq!(hmac(&sk, b"Hi There").unwrap(), expected);
+			assert!(verify(&expected, &sk, b"Hi There").unwrap());
+		}
+
+		#[test]
+		fn test_rfc4231_test_case_6_long_key() {
+			let sk = SecretKey::from_slice(&[0xaa; 131]).unwrap();
+			let expected = Tag::from_slice(&[
+				0x60, 0xe4, 0x31, 0x59, 0x1e, 0xe0, 0xb6, 0x7f, 0x0d, 0x8a, 0x26, 0xaa, 0xcb, 0xf5,
+				0xb7, 0x7f, 0x8e, 0x0b, 0xc6, 0x21, 0x37, 0x28, 0xc5, 0x14, 0x05, 0x46, 0x04, 0x0f,
+				0x0e, 0xe3, 0x7f, 0x54,
+			])
+			.unwrap();
+
+			assert_eq!(
+				hmac(
+					&sk,
+					b"Test Using Larger Than Block-Size Key - Hash Key First"
+				)
+				.unwrap(),
+				expected
+			);
+		}
+
+		#[test]
+		fn test_rfc4231_test_case_5_truncated() {
+			let sk = SecretKey::from_slice(&[0x0c; 20]).unwrap();
+			let expected = [
+				0xa3, 0xb6, 0x16, 0x74, 0x73, 0x10, 0x0e, 0xe0, 0x6e, 0x0c, 0x79, 0x6c, 0x29, 0x55,
+				0x55, 0x2b,
+			];
+
+			let mut state = init(&sk);
+			state.update(b"Test With Truncation").unwrap();
+			assert!(state.verify_truncated(&expected, 16).is_ok());
+
+			let mut state = init(&sk);
+			assert!(state
+				.finalize_truncated(&mut [0u8; SHA256_OUTSIZE + 1])
+				.is_err());
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		fn test_verify_hex_and_base64() {
+			let sk = SecretKey::from_slice(b"Jefe").unwrap();
+			let data = b"what do ya want for nothing?";
+			let hex = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+			assert!(verify_hex(hex, &sk, data).unwrap());
+			assert!(verify_hex(&hex.to_uppercase(), &sk, data).unwrap());
+			assert!(verify_hex(hex, &sk, b"what do ya want for nothing!").is_err());
+			assert!(verify_hex(&hex[..62], &sk, data).is_err());
+
+			let b64 = "W9zBRr9gdU5qBCQmCJV1x1oAPwidJzmDnexYuWTsOEM=";
+			assert!(verify_base64(b64, &sk, data).unwrap());
+			assert!(verify_base64(&b64[..43], &sk, data).unwrap());
+			assert!(verify_base64(&b64[..42], &sk, data).is_err());
+		}
+
+		#[test]
+		fn test_verify_wrong_data_err() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let tag = hmac(&sk, b"Hi There").unwrap();
+			assert!(verify(&tag, &sk, b"Hi there").is_err());
+		}
+
+		#[test]
+		fn test_finalize_into_length() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let mut dst = [0u8; SHA256_OUTSIZE];
+			init(&sk).finalize_into(&mut dst).unwrap();
+			assert_eq!(&dst[..], hmac(&sk, b"").unwrap().unprotected_as_bytes());
+			assert!(init(&sk).finalize_into(&mut [0u8; 64]).is_err());
+		}
+
+		#[test]
+		fn test_double_finalize_err() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let mut state = init(&sk);
+			let _ = state.finalize().unwrap();
+			assert!(state.finalize().is_err());
+			assert!(state.update(b"Data").is_err());
+			state.reset();
+			assert_eq!(state.finalize().unwrap(), hmac(&sk, b"").unwrap());
+		}
+
+		#[test]
+		fn test_finalize_verify() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let expected = hmac(&sk, b"Hi There").unwrap();
+
+			let mut state = init(&sk);
+			state.update(b"Hi There").unwrap();
+			assert!(state.finalize_verify(&expected).unwrap());
+
+			let mut state = init(&sk);
+			state.update(b"Hi there").unwrap();
+			assert!(state.finalize_verify(&expected).is_err());
+		}
+	}
+}
+
+/// HMAC-SHA512-256, which is HMAC-SHA512 with the tag truncated to 32 bytes.
+pub mod sha512_256 {
+	pub use super::SecretKey;
+	use crate::{
+		errors::{FinalizationCryptoError, UnknownCryptoError, ValidationCryptoError},
+		hazardous::{
+			constants::{HLenArray, SHA512_OUTSIZE},
+			hash::sha512::Sha512,
+			mac::Mac,
+		},
+	};
+	use zeroize::Zeroize;
+
+	/// The size of an HMAC-SHA512-256 tag.
+	pub const TAG_SIZE: usize = 32;
+
+	construct_tag! {
+		/// A type to represent the `Tag` that HMAC-SHA512-256 returns.
+		///
+		/// # Exceptions:
+		/// An exception will be thrown if:
+		/// - `slice` is not 32 bytes.
+		(Tag, TAG_SIZE)
+	}
+
+	#[must_use]
+	#[derive(Clone, Debug)]
+	/// HMAC-SHA512-256 streaming state.
+	pub struct Hmac(super::Hmac<Sha512>);
+
+	impl Hmac {
+		#[must_use]
+		/// Initialize `Hmac` struct with a given key.
+		pub fn new(secret_key: &SecretKey) -> Self { Hmac(super::Hmac::new(secret_key)) }
+
+		/// Reset to `new()` state.
+		pub fn reset(&mut self) { self.0.reset() }
+
+		#[must_use]
+		/// Update state with `data`. This can be called multiple times.
+		pub fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
+			self.0.update(data)
+		}
+
+		#[must_use]
+		/// Return the first 32 bytes of the HMAC-SHA512 `Tag`.
+		pub fn finalize(&mut self) -> Result<Tag, FinalizationCryptoError> {
+			let mut tag: HLenArray = [0u8; SHA512_OUTSIZE];
+			self.0.finalize_to_dst(&mut tag)?;
+			let truncated = Tag::from_slice(&tag[..TAG_SIZE]);
+			tag.zeroize();
+
+			Ok(truncated?)
+		}
+
+		#[must_use]
+		/// Finalize the state and compare the result with `expected` in
+		/// constant time. The state is consumed, so the computed `Tag` is never
+		/// exposed.
+		pub fn finalize_verify(mut self, expected: &Tag) -> Result<bool, ValidationCryptoError> {
+			if expected == &self.finalize()? {
+				Ok(true)
+			} else {
+				Err(ValidationCryptoError)
+			}
+		}
+	}
+
+	impl Mac for Hmac {
+		type Tag = Tag;
+
+		fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
+			Hmac::update(self, data)
+		}
+
+		fn finalize(&mut self) -> Result<Tag, FinalizationCryptoError> { Hmac::finalize(self) }
+	}
+
+	#[must_use]
+	/// Initialize `Hmac` struct with a given key.
+	pub fn init(secret_key: &SecretKey) -> Hmac { Hmac::new(secret_key) }
+
+	#[must_use]
+	/// One-shot function for generating an HMAC-SHA512-256 tag of `data`.
+	pub fn hmac(secret_key: &SecretKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
+		let mut hmac_state = init(secret_key);
+		hmac_state.update(data)?;
+
+		Ok(hmac_state.finalize()?)
+	}
+
+	#[must_use]
+	/// Verify a HMAC-SHA512-256 Tag in constant time.
+	pub fn verify(
+		expected: &Tag,
+		secret_key: &SecretKey,
+		data: &[u8],
+	) -> Result<bool, ValidationCryptoError> {
+		let mut hmac_state = init(secret_key);
+		hmac_state.update(data)?;
+		hmac_state.finalize_verify(expected)
+	}
+
+	// Testing public functions in the module.
+	#[cfg(test)]
+	mod public {
+		use super::*;
+
+		#[test]
+		fn test_rfc4231_test_case_1_truncated() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let expected = Tag::from_slice(&[
+				0x87, 0xaa, 0x7c, 0xde, 0xa5, 0xef, 0x61, 0x9d, 0x4f, 0xf0, 0xb4, 0x24, 0x1a, 0x1d,
+				0x6c, 0xb0, 0x23, 0x79, 0xf4, 0xe2, 0xce, 0x4e, 0xc2, 0x78, 0x7a, 0xd0, 0xb3, 0x05,
+				0x45, 0xe1, 0x7c, 0xde,
+			])
+			.unwrap();
+
+			assert_eq!(hmac(&sk, b"Hi There").unwrap(), expected);
+			assert!(verify(&expected, &sk, b"Hi There").unwrap());
+		}
+
+		#[test]
+		fn test_rfc4231_test_case_2_truncated() {
+			let sk = SecretKey::from_slice(b"Jefe").unwrap();
+			let expected = Tag::from_slice(&[
+				0x16, 0x4b, 0x7a, 0x7b, 0xfc, 0xf8, 0x19, 0xe2, 0xe3, 0x95, 0xfb, 0xe7, 0x3b, 0x56,
+				0xe0, 0xa3, 0x87, 0xbd, 0x64, 0x22, 0x2e, 0x83, 0x1f, 0xd6, 0x10, 0x27, 0x0c, 0xd7,
+				0xea, 0x25, 0x05, 0x54,
+			])
+			.unwrap();
+
+			let mut state = init(&sk);
+			state.update(b"what do ya ").unwrap();
+			state.update(b"want for nothing?").unwrap();
+			assert_eq!(state.finalize().unwrap(), expected);
+		}
+
+		#[test]
+		fn test_matches_truncated_sha512() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let mut truncated = [0u8; TAG_SIZE];
+			super::super::hmac_truncated(&sk, b"Data", &mut truncated).unwrap();
+			assert_eq!(
+				hmac(&sk, b"Data").unwrap().unprotected_as_bytes(),
+				&truncated[..]
+			);
+		}
+
+		#[test]
+		fn test_verify_wrong_data_err() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let tag = hmac(&sk, b"Hi There").unwrap();
+			assert!(verify(&tag, &sk, b"Hi there").is_err());
+		}
+
+		#[test]
+		fn test_double_finalize_err() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let mut state = init(&sk);
+			let _ = state.finalize().unwrap();
+			assert!(state.finalize().is_err());
+			assert!(state.update(b"Data").is_err());
+			state.reset();
+			assert_eq!(state.finalize().unwrap(), hmac(&sk, b"").unwrap());
+		}
+
+		#[test]
+		fn test_finalize_verify() {
+			let sk = SecretKey::from_slice(&[0x0b; 20]).unwrap();
+			let expected = hmac(&sk, b"Hi There").unwrap();
+
+			let mut state = init(&sk);
+			state.update(b"Hi There").unwrap();
+			assert!(state.finalize_verify(&expected).unwrap());
+
+			let mut state = init(&sk);
+			state.update(b"Hi there").unwrap();
+			assert!(state.finalize_verify(&expected).is_err());
+		}
+	}
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -373,7 +1173,303 @@ mod public {
 			let _ = state.finalize().unwrap();
 			assert!(state.finalize().is_err());
 		}
+	}
+
+	mod test_finalize_into {
+		use super::*;
+
+		#[test]
+		fn test_same_as_finalize() {
+			let sk = SecretKey::from_slice("Jefe".as_bytes()).unwrap();
+			let data = "what do ya want for nothing?".as_bytes();
+
+			let mut dst = [0u8; SHA512_OUTSIZE];
+			hmac_into(&sk, data, &mut dst).unwrap();
+			assert_eq!(&dst[..], hmac(&sk, data).unwrap().unprotected_as_bytes());
+		}
 
+		#[test]
+		fn test_length_err() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			assert!(hmac_into(&sk, b"Data", &mut [0u8; SHA512_OUTSIZE - 1]).is_err());
+			assert!(hmac_into(&sk, b"Data", &mut [0u8; SHA512_OUTSIZE + 1]).is_err());
+			assert!(hmac_into(&sk, b"Data", &mut [0u8; 0]).is_err());
+		}
+
+		#[test]
+		fn test_length_err_does_not_finalize() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let mut state = init(&sk);
+			assert!(state.finalize_into(&mut [0u8; 16]).is_err());
+			assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_ok());
+		}
+
+		#[test]
+		fn test_double_finalize_err() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let mut state = init(&sk);
+			let _ = state.finalize().unwrap();
+			assert!(state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).is_err());
+
+			let mut state = init(&sk);
+			state.finalize_into(&mut [0u8; SHA512_OUTSIZE]).unwrap();
+			assert!(state.finalize().is_err());
+		}
+	}
+
+	mod test_finalize_truncated {
+		use super::*;
+
+		#[test]
+		fn test_rfc4231_test_case_5() {
+			let sk = SecretKey::from_slice(&[0x0c; 20]).unwrap();
+			let expected = [
+				0x41, 0x5f, 0xad, 0x62, 0x71, 0x58, 0x0a, 0x53, 0x1d, 0x41, 0x79, 0xbc, 0x89, 0x1d,
+				0x87, 0xa6,
+			];
+
+			let mut state = init(&sk);
+			state.update(b"Test With Truncation").unwrap();
+			let mut tag = [0u8; 16];
+			state.finalize_truncated(&mut tag).unwrap();
+			assert_eq!(tag, expected);
+		}
+
+		#[test]
+		fn test_prefix_of_full_tag() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let full = hmac(&sk, b"Data").unwrap();
+
+			for len in HMAC_MIN_TRUNCATED_OUTSIZE..=SHA512_OUTSIZE {
+				let mut tag = [0u8; SHA512_OUTSIZE];
+				hmac_truncated(&sk, b"Data", &mut tag[..len]).unwrap();
+				assert_eq!(&tag[..len], &full.unprotected_as_bytes()[..len]);
+			}
+		}
+
+		#[test]
+		fn test_length_err() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let mut too_short = [0u8; HMAC_MIN_TRUNCATED_OUTSIZE - 1];
+			assert!(hmac_truncated(&sk, b"Data", &mut too_short).is_err());
+			assert!(hmac_truncated(&sk, b"Data", &mut [0u8; SHA512_OUTSIZE + 1]).is_err());
+			assert!(hmac_truncated(&sk, b"Data", &mut [0u8; 0]).is_err());
+		}
+
+		#[test]
+		fn test_double_finalize_err() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let mut state = init(&sk);
+			let _ = state.finalize().unwrap();
+			assert!(state.finalize_truncated(&mut [0u8; 16]).is_err());
+		}
+	}
+
+	mod test_verify_truncated {
+		use super::*;
+
+		#[test]
+		fn test_verify_truncated_ok() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let mut tag = [0u8; 20];
+			hmac_truncated(&sk, b"Data", &mut tag).unwrap();
+
+			assert!(verify_truncated(&tag, &sk, b"Data", 20).unwrap());
+			let mut state = init(&sk);
+			state.update(b"Data").unwrap();
+			assert!(state.verify_truncated(&tag, 20).unwrap());
+		}
+
+		#[test]
+		fn test_verify_truncated_err() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let mut tag = [0u8; 20];
+			hmac_truncated(&sk, b"Data", &mut tag).unwrap();
+
+			assert!(verify_truncated(&tag, &sk, b"Bad data", 20).is_err());
+			// The expected tag must be exactly `len` bytes.
+			assert!(verify_truncated(&tag[..16], &sk, b"Data", 20).is_err());
+			assert!(verify_truncated(&tag, &sk, b"Data", 16).is_err());
+			// Shorter than the minimum, even when it is a valid prefix.
+			assert!(verify_truncated(&tag[..8], &sk, b"Data", 8).is_err());
+
+			tag[0] ^= 1;
+			assert!(verify_truncated(&tag, &sk, b"Data", 20).is_err());
+		}
+
+		#[test]
+		fn test_verify_truncated_length_err() {
+			let sk = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let expected = [0u8; SHA512_OUTSIZE + 1];
+
+			for len in &[0, HMAC_MIN_TRUNCATED_OUTSIZE - 1, SHA512_OUTSIZE + 1] {
+				assert!(verify_truncated(&expected[..*len], &sk, b"Data", *len).is_err());
+				let mut state = init(&sk);
+				state.update(b"Data").unwrap();
+				assert!(state.verify_truncated(&expected[..*len], *len).is_err());
+			}
+		}
+	}
+
+	mod test_finalize_verify {
+		use super::*;
+
+		#[test]
+		fn test_finalize_verify_ok() {
+			let sk = SecretKey::from_slice(b"Jefe").unwrap();
+			let expected = hmac(&sk, b"what do ya want for nothing?").unwrap();
+
+			let mut state = init(&sk);
+			state.update(b"what do ya ").unwrap();
+			state.update(b"want for nothing?").unwrap();
+			assert!(state.finalize_verify(&expected).unwrap());
+		}
+
+		#[test]
+		fn test_finalize_verify_err() {
+			let sk = SecretKey::from_slice(b"Jefe").unwrap();
+			let expected = hmac(&sk, b"what do ya want for nothing?").unwrap();
+
+			let mut state = init(&sk);
+			state.update(b"what do ya want for something?").unwrap();
+			assert!(state.finalize_verify(&expected).is_err());
+
+			// An already finalized state cannot be verified.
+			let mut state = init(&sk);
+			state.update(b"what do ya want for nothing?").unwrap();
+			let _ = state.finalize().unwrap();
+			assert!(state.finalize_verify(&expected).is_err());
+		}
+	}
+
+	#[cfg(feature = "safe_api")]
+	mod test_verify_hex {
+		use super::*;
+
+		const JEFE_HEX: &str = "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737";
+
+		const JEFE_BASE64: &str =
+			"Fkt6e/z4GeLjlfvnO1bgo4e9ZCIugx/WECcM1+olBVSXWL91wFqZSm0DT2X48Ob9yuqxo01Ka0tjbgcKOLznNw==";
+
+		#[test]
+		fn test_verify_hex_ok() {
+			let sk = SecretKey::from_slice(b"Jefe").unwrap();
+			assert!(verify_hex(JEFE_HEX, &sk, b"what do ya want for nothing?").unwrap());
+		}
+
+		#[test]
+		fn test_verify_hex_err() {
+			let sk = SecretKey::from_slice(b"Jefe").unwrap();
+			let data = b"what do ya want for nothing?";
+
+			assert!(verify_hex(JEFE_HEX, &sk, b"what do ya want for something?").is_err());
+			// Truncated, too long and non-hex input.
+			assert!(verify_hex(&JEFE_HEX[..126], &sk, data).is_err());
+			assert!(verify_hex(&format!("{}00", JEFE_HEX), &sk, data).is_err());
+			assert!(verify_hex(&JEFE_HEX.replace("7", "g"), &sk, data).is_err());
+			assert!(verify_hex("", &sk, data).is_err());
+		}
+
+		#[test]
+		fn test_verify_base64_ok() {
+			let sk = SecretKey::from_slice(b"Jefe").unwrap();
+			let data = b"what do ya want for nothing?";
+
+			assert!(verify_base64(JEFE_BASE64, &sk, data).unwrap());
+			// Padding is optional.
+			assert!(verify_base64(JEFE_BASE64.trim_end_matches('='), &sk, data).unwrap());
+		}
+
+		#[test]
+		fn test_verify_base64_err() {
+			let sk = SecretKey::from_slice(b"Jefe").unwrap();
+			let data = b"what do ya want for nothing?";
+
+			assert!(verify_base64(JEFE_BASE64, &sk, b"Data").is_err());
+			assert!(verify_base64(&format!("{}=", JEFE_BASE64), &sk, data).is_err());
+			assert!(verify_base64(&JEFE_BASE64[..87], &sk, data).is_err());
+			assert!(verify_base64(&JEFE_BASE64.replace("+", "-"), &sk, data).is_err());
+			assert!(verify_base64(JEFE_HEX, &sk, data).is_err());
+		}
+	}
+
+	#[cfg(feature = "safe_api")]
+	mod test_io_write {
+		use super::*;
+		use std::io::{copy, Write};
+
+		#[test]
+		fn test_write_same_as_update() {
+			let data = [0u8; 356];
+
+			let mut state = init(&SecretKey::from_slice(&[0u8; 32]).unwrap());
+			let mut reader: &[u8] = &data;
+			assert_eq!(copy(&mut reader, &mut state).unwrap(), 356);
+			state.flush().unwrap();
+
+			let mut state_update = init(&SecretKey::from_slice(&[0u8; 32]).unwrap());
+			state_update.update(&data).unwrap();
+
+			assert_eq!(state.finalize().unwrap(), state_update.finalize().unwrap());
+		}
+
+		#[test]
+		fn test_write_after_finalize_err() {
+			let mut state = init(&SecretKey::from_slice(&[0u8; 32]).unwrap());
+			let _ = state.finalize().unwrap();
+			assert!(state.write(b"Some data").is_err());
+		}
+	}
+
+	#[cfg(feature = "serializable_state")]
+	mod test_state_bytes {
+		use super::*;
+
+		#[test]
+		fn test_resume_same_tag() {
+			let data = [0u8; 300];
+			let secret_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+			let expected = hmac(&secret_key, &data).unwrap();
+
+			for split in [0, 1, 127, 128, 129, 256, 300].iter() {
+				let mut state = init(&secret_key);
+				state.update(&data[..*split]).unwrap();
+				let mut resumed = Hmac::from_state_bytes(&state.to_state_bytes()).unwrap();
+				compare_hmac_states(&state, &resumed);
+				resumed.update(&data[*split..]).unwrap();
+				assert_eq!(resumed.finalize().unwrap(), expected);
+			}
+		}
+
+		#[test]
+		fn test_resume_finalized_reset() {
+			let secret_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+			let mut state = init(&secret_key);
+			let _ = state.finalize().unwrap();
+			let mut resumed = Hmac::from_state_bytes(&state.to_state_bytes()).unwrap();
+			assert!(resumed.update(b"").is_err());
+			resumed.reset();
+			assert_eq!(resumed.finalize().unwrap(), hmac(&secret_key, b"").unwrap());
+		}
+
+		#[test]
+		fn test_invalid_state_bytes_err() {
+			let state = init(&SecretKey::from_slice(&[1u8; 32]).unwrap());
+			let state_bytes = state.to_state_bytes();
+			assert!(Hmac::from_state_bytes(&state_bytes).is_ok());
+
+			assert!(Hmac::from_state_bytes(&state_bytes[..state_bytes.len() - 1]).is_err());
+			assert!(Hmac::from_state_bytes(&[]).is_err());
+			// Version, identifier, inner state leftovers and is_finalized.
+			for idx in [0, 1, 194, 404, 614, 632].iter() {
+				let mut modified = state_bytes.clone();
+				modified[*idx] ^= 0x81;
+				assert!(Hmac::from_state_bytes(&modified).is_err());
+			}
+
+			// A SHA512 state is not a HMAC state.
+			assert!(Hmac::from_state_bytes(&sha512::init().to_state_bytes()).is_err());
+		}
 	}
 
 	mod test_streaming_interface {
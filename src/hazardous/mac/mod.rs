@@ -20,8 +20,109 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-/// HMAC-SHA512 (Hash-based Message Authentication Code) as specified in the [RFC 2104](https://tools.ietf.org/html/rfc2104).
+/// HMAC-SHA512 and HMAC-SHA256 (Hash-based Message Authentication Code) as specified in the [RFC 2104](https://tools.ietf.org/html/rfc2104).
 pub mod hmac;
 
 /// Poly1305 as specified in the [RFC 8439](https://tools.ietf.org/html/rfc8439).
 pub mod poly1305;
+
+use crate::errors::{FinalizationCryptoError, ValidationCryptoError};
+
+/// A streaming Message Authentication Code.
+///
+/// This is implemented by the streaming states of HMAC, Poly1305 and keyed
+/// BLAKE2b, so that code can be written generically over which MAC is used.
+/// How a state is initialized is left to each primitive, since their keys and
+/// parameters differ.
+///
+/// # Security:
+/// - `Tag`s are compared in constant time by `verify()`.
+pub trait Mac {
+	/// The authentication tag produced by `finalize()`.
+	type Tag: PartialEq;
+
+	#[must_use]
+	/// Update state with `data`. This can be called multiple times.
+	fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError>;
+
+	#[must_use]
+	/// Return the authentication tag of the data processed so far.
+	fn finalize(&mut self) -> Result<Self::Tag, FinalizationCryptoError>;
+
+	#[must_use]
+	/// Finalize the state and compare the result with `expected` in constant
+	/// time.
+	fn verify(&mut self, expected: &Self::Tag) -> Result<bool, ValidationCryptoError> {
+		if expected == &self.finalize()? {
+			Ok(true)
+		} else {
+			Err(ValidationCryptoError)
+		}
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use crate::hazardous::hash::blake2b;
+
+	fn authenticate_generic<M: Mac>(state: &mut M, data: &[u8]) -> M::Tag {
+		state.update(data).unwrap();
+		state.finalize().unwrap()
+	}
+
+	mod test_verify {
+		use super::*;
+
+		#[test]
+		fn test_verify_hmac() {
+			let key = hmac::SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let tag = authenticate_generic(&mut hmac::init(&key), b"Data");
+
+			assert!(Mac::verify(&mut hmac::init(&key), &tag).is_err());
+			let mut state = hmac::init(&key);
+			state.update(b"Data").unwrap();
+			assert!(Mac::verify(&mut state, &tag).unwrap());
+			assert_eq!(tag, hmac::hmac(&key, b"Data").unwrap());
+		}
+
+		#[test]
+		fn test_verify_poly1305() {
+			let key = poly1305::OneTimeKey::from_slice(&[1u8; 32]).unwrap();
+			let tag = authenticate_generic(&mut poly1305::init(&key), b"Data");
+
+			let mut state = poly1305::init(&key);
+			state.update(b"Data").unwrap();
+			assert!(Mac::verify(&mut state, &tag).unwrap());
+			assert_eq!(tag, poly1305::poly1305(&key, b"Data").unwrap());
+		}
+
+		#[test]
+		fn test_verify_blake2b_keyed() {
+			let key = blake2b::SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let tag = authenticate_generic(&mut blake2b::init(Some(&key), 32).unwrap(), b"Data");
+
+			let mut state = blake2b::init(Some(&key), 32).unwrap();
+			state.update(b"Data").unwrap();
+			assert!(Mac::verify(&mut state, &tag).unwrap());
+			assert!(blake2b::verify(&tag, &key, 32, b"Data").unwrap());
+		}
+
+		#[test]
+		fn test_verify_blake2b_not_keyed_err() {
+			let mut state = blake2b::init(None, 32).unwrap();
+			let digest = authenticate_generic(&mut blake2b::init(None, 32).unwrap(), b"Data");
+			state.update(b"Data").unwrap();
+			assert!(Mac::verify(&mut state, &digest).is_err());
+		}
+
+		#[test]
+		fn test_verify_after_finalize_err() {
+			let key = hmac::SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let mut state = hmac::init(&key);
+			let tag = authenticate_generic(&mut state, b"Data");
+			assert!(Mac::verify(&mut state, &tag).is_err());
+		}
+	}
+}
@@ -28,6 +28,8 @@
 //! # Parameters:
 //! - `data`: Data to be authenticated.
 //! - `one_time_key`: One-time key used to authenticate.
+//! - `r`: The first 16 bytes of the one-time key, which are clamped.
+//! - `s`: The last 16 bytes of the one-time key.
 //! - `expected`: The expected tag that needs to be verified.
 //!
 //! # Errors:
@@ -35,10 +37,14 @@
 //! - `finalize()` is called twice without a `reset()` in between.
 //! - `update()` is called after `finalize()` without a `reset()` in between.
 //! - The calculated tag does not match the expected when verifying.
+//! - `r` or `s` is not 16 bytes.
 //!
 //! # Security:
 //! - The one-time key should be generated using a CSPRNG.
 //!   `OneTimeKey::generate()` can be used for this.
+//! - `init_from_parts()` is for constructions that derive `r` and `s`
+//!   separately, such as from different keystream offsets. Like the whole
+//!   one-time key, the pair of `r` and `s` must never be used twice.
 //!
 //! # Recommendation:
 //! - If you are unsure of whether to use HMAC or Poly1305, it is most often
@@ -64,18 +70,20 @@ extern crate core;
 use crate::{
 	endianness::{load_u32_le, store_u32_into_le},
 	errors::{FinalizationCryptoError, UnknownCryptoError, ValidationCryptoError},
-	hazardous::constants::{Poly1305Tag, POLY1305_BLOCKSIZE, POLY1305_KEYSIZE, POLY1305_OUTSIZE},
+	hazardous::{
+		constants::{Poly1305Tag, POLY1305_BLOCKSIZE, POLY1305_KEYSIZE, POLY1305_OUTSIZE},
+		mac::Mac,
+		types,
+	},
 };
 
-construct_secret_key! {
-	/// A type to represent the `OneTimeKey` that Poly1305 uses for authentication.
-	///
-	/// # Exceptions:
-	/// An exception will be thrown if:
-	/// - `slice` is not 32 bytes.
-	/// - The `OsRng` fails to initialize or read from its source.
-	(OneTimeKey, POLY1305_KEYSIZE)
-}
+/// A type to represent the `OneTimeKey` that Poly1305 uses for authentication.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 32 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type OneTimeKey = types::SecretKey<types::tag::Poly1305Key, POLY1305_KEYSIZE>;
 construct_tag! {
 	/// A type to represent the `Tag` that Poly1305 returns.
 	///
@@ -389,6 +397,25 @@ pub fn init(one_time_key: &OneTimeKey) -> Poly1305 {
 	poly_1305_state
 }
 
+#[must_use]
+/// Initialize a `Poly1305` struct with a one-time key given as its `r` and
+/// `s` parts. `r` is clamped, so it does not need to be clamped beforehand.
+pub fn init_from_parts(r: &[u8], s: &[u8]) -> Result<Poly1305, UnknownCryptoError> {
+	use zeroize::Zeroize;
+
+	if r.len() != POLY1305_KEYSIZE / 2 || s.len() != POLY1305_KEYSIZE / 2 {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut key = [0u8; POLY1305_KEYSIZE];
+	key[..POLY1305_KEYSIZE / 2].copy_from_slice(r);
+	key[POLY1305_KEYSIZE / 2..].copy_from_slice(s);
+	let one_time_key = OneTimeKey::from_slice(&key);
+	key.zeroize();
+
+	Ok(init(&one_time_key?))
+}
+
 #[must_use]
 /// One-shot function for generating a Poly1305 tag of `data`.
 pub fn poly1305(one_time_key: &OneTimeKey, data: &[u8]) -> Result<Tag, UnknownCryptoError> {
@@ -412,6 +439,16 @@ pub fn verify(
 	}
 }
 
+impl Mac for Poly1305 {
+	type Tag = Tag;
+
+	fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
+		Poly1305::update(self, data)
+	}
+
+	fn finalize(&mut self) -> Result<Tag, FinalizationCryptoError> { Poly1305::finalize(self) }
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -518,6 +555,67 @@ mod public {
 		}
 	}
 
+	mod test_init_from_parts {
+		use super::*;
+
+		// From RFC 8439, section 2.5.2.
+		const R: [u8; 16] = [
+			0x85, 0xd6, 0xbe, 0x78, 0x57, 0x55, 0x6d, 0x33, 0x7f, 0x44, 0x52, 0xfe, 0x42, 0xd5,
+			0x06, 0xa8,
+		];
+		const S: [u8; 16] = [
+			0x01, 0x03, 0x80, 0x8a, 0xfb, 0x0d, 0xb2, 0xfd, 0x4a, 0xbf, 0xf6, 0xaf, 0x41, 0x49,
+			0xf5, 0x1b,
+		];
+		const EXPECTED: [u8; 16] = [
+			0xa8, 0x06, 0x1d, 0xc1, 0x30, 0x51, 0x36, 0xc6, 0xc2, 0x2b, 0x8b, 0xaf, 0x0c, 0x01,
+			0x27, 0xa9,
+		];
+
+		#[test]
+		fn test_rfc_vector() {
+			let mut state = init_from_parts(&R, &S).unwrap();
+			state.update(b"Cryptographic Forum Research Group").unwrap();
+			assert_eq!(
+				state.finalize().unwrap(),
+				Tag::from_slice(&EXPECTED).unwrap()
+			);
+		}
+
+		#[test]
+		fn test_same_as_init() {
+			let mut key = [0u8; 32];
+			key[..16].copy_from_slice(&R);
+			key[16..].copy_from_slice(&S);
+			let state = init(&OneTimeKey::from_slice(&key).unwrap());
+			compare_poly1305_states(&init_from_parts(&R, &S).unwrap(), &state);
+		}
+
+		#[test]
+		fn test_r_is_clamped() {
+			let mut clamped = R;
+			for idx in [3, 7, 11, 15].iter() {
+				clamped[*idx] &= 0x0f;
+			}
+			for idx in [4, 8, 12].iter() {
+				clamped[*idx] &= 0xfc;
+			}
+			compare_poly1305_states(
+				&init_from_parts(&R, &S).unwrap(),
+				&init_from_parts(&clamped, &S).unwrap(),
+			);
+		}
+
+		#[test]
+		fn test_bad_part_length() {
+			assert!(init_from_parts(&R[..15], &S).is_err());
+			assert!(init_from_parts(&R, &S[..15]).is_err());
+			assert!(init_from_parts(&[0u8; 17], &S).is_err());
+			assert!(init_from_parts(&R, &[0u8; 32]).is_err());
+			assert!(init_from_parts(&[], &[]).is_err());
+		}
+	}
+
 	mod test_reset {
 		use super::*;
 
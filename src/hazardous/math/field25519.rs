@@ -0,0 +1,536 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! - Arithmetic in the field of integers modulo p = 2^255 - 19, the field
+//!   that Curve25519 and Ed25519 are defined over.
+//! - Elements are stored in five 51-bit limbs. All operations run in constant
+//!   time, with no branches or memory accesses that depend on the values.
+//! - `from_slice()` ignores the most significant bit, as specified for
+//!   Curve25519 in [RFC 7748](https://tools.ietf.org/html/rfc7748#section-5),
+//!   and reduces values that are not less than p. `to_bytes()` always returns
+//!   the canonical encoding.
+//! - `invert()` returns zero for zero.
+//! - `is_negative()` is true if the canonical encoding is odd, as used for the
+//!   sign of a coordinate in Ed25519.
+//!
+//! # Parameters:
+//! - `slice`: The little-endian encoding of a field element.
+//! - `rhs`: The right-hand side of an operation.
+//! - `choice`: Selects between two field elements, in constant time.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `slice` is not 32 bytes.
+//!
+//! # Security:
+//! - Only the functions of this module are constant time. Branching on a
+//!   `Choice` they return, or on the output of `to_bytes()`, is not.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::math::field25519::FieldElement;
+//!
+//! let a = FieldElement::from_slice(&[9u8; 32]).unwrap();
+//! let a_inv = a.invert();
+//!
+//! assert_eq!(a.mul(&a_inv), FieldElement::one());
+//! assert_eq!(a.sub(&a).to_bytes(), [0u8; 32]);
+//! ```
+
+use crate::errors::UnknownCryptoError;
+use subtle::{Choice, ConstantTimeEq};
+
+/// The mask of the 51 bits used in each limb.
+const LOW_51_BIT_MASK: u64 = (1u64 << 51) - 1;
+
+/// The size of an encoded field element.
+pub const FIELD_ELEMENT_SIZE: usize = 32;
+
+#[must_use]
+/// An element of the field of integers modulo 2^255 - 19.
+pub struct FieldElement {
+	limbs: [u64; 5],
+}
+
+impl Clone for FieldElement {
+	fn clone(&self) -> Self { Self { limbs: self.limbs } }
+}
+
+impl Drop for FieldElement {
+	fn drop(&mut self) {
+		use zeroize::Zeroize;
+		self.limbs.zeroize();
+	}
+}
+
+impl core::fmt::Debug for FieldElement {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "FieldElement {{ limbs: [***OMITTED***] }}")
+	}
+}
+
+impl ConstantTimeEq for FieldElement {
+	fn ct_eq(&self, other: &Self) -> Choice { self.to_bytes()[..].ct_eq(&other.to_bytes()[..]) }
+}
+
+impl PartialEq for FieldElement {
+	fn eq(&self, other: &Self) -> bool { self.ct_eq(other).unwrap_u8() == 1 }
+}
+
+impl Eq for FieldElement {}
+
+impl FieldElement {
+	/// The additive identity.
+	pub fn zero() -> Self { Self { limbs: [0u64; 5] } }
+
+	/// The multiplicative identity.
+	pub fn one() -> Self { Self { limbs: [1, 0, 0, 0, 0] } }
+
+	#[must_use]
+	/// Make a field element from its 32-byte little-endian encoding.
+	pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+		if slice.len() != FIELD_ELEMENT_SIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut limbs = [0u64; 5];
+		let mut acc = 0u128;
+		let mut acc_bits = 0;
+		let mut idx = 0;
+		for byte in slice.iter() {
+			acc |= u128::from(*byte) << acc_bits;
+			acc_bits += 8;
+			if acc_bits >= 51 && idx < 5 {
+				limbs[idx] = (acc as u64) & LOW_51_BIT_MASK;
+				acc >>= 51;
+				acc_bits -= 51;
+				idx += 1;
+			}
+		}
+		// The remaining bit is the most significant bit, which is ignored.
+
+		Ok(Self::reduce(limbs))
+	}
+
+	/// Return the canonical 32-byte little-endian encoding.
+	pub fn to_bytes(&self) -> [u8; FIELD_ELEMENT_SIZE] {
+		let mut limbs = Self::reduce(self.limbs).limbs;
+
+		// Find out whether the value is at least p, by checking if adding 19
+		// carries into bit 255.
+		let mut q = (limbs[0] + 19) >> 51;
+		q = (limbs[1] + q) >> 51;
+		q = (limbs[2] + q) >> 51;
+		q = (limbs[3] + q) >> 51;
+		q = (limbs[4] + q) >> 51;
+
+		// Subtract q * p, by adding 19 * q and dropping bit 255.
+		limbs[0] += 19 * q;
+		limbs[1] += limbs[0] >> 51;
+		limbs[0] &= LOW_51_BIT_MASK;
+		limbs[2] += limbs[1] >> 51;
+		limbs[1] &= LOW_51_BIT_MASK;
+		limbs[3] += limbs[2] >> 51;
+		limbs[2] &= LOW_51_BIT_MASK;
+		limbs[4] += limbs[3] >> 51;
+		limbs[3] &= LOW_51_BIT_MASK;
+		limbs[4] &= LOW_51_BIT_MASK;
+
+		let mut bytes = [0u8; FIELD_ELEMENT_SIZE];
+		let mut acc = 0u128;
+		let mut acc_bits = 0;
+		let mut idx = 0;
+		for limb in limbs.iter() {
+			acc |= u128::from(*limb) << acc_bits;
+			acc_bits += 51;
+			while acc_bits >= 8 {
+				bytes[idx] = acc as u8;
+				acc >>= 8;
+				acc_bits -= 8;
+				idx += 1;
+			}
+		}
+		bytes[idx] = acc as u8;
+
+		bytes
+	}
+
+	/// Carry the limbs so that each is at most 51 bits, plus a small carry
+	/// in the lowest limb.
+	fn reduce(mut limbs: [u64; 5]) -> Self {
+		let c0 = limbs[0] >> 51;
+		let c1 = limbs[1] >> 51;
+		let c2 = limbs[2] >> 51;
+		let c3 = limbs[3] >> 51;
+		let c4 = limbs[4] >> 51;
+
+		limbs[0] &= LOW_51_BIT_MASK;
+		limbs[1] &= LOW_51_BIT_MASK;
+		limbs[2] &= LOW_51_BIT_MASK;
+		limbs[3] &= LOW_51_BIT_MASK;
+		limbs[4] &= LOW_51_BIT_MASK;
+
+		// 2^255 = 19 mod p
+		limbs[0] += c4 * 19;
+		limbs[1] += c0;
+		limbs[2] += c1;
+		limbs[3] += c2;
+		limbs[4] += c3;
+
+		Self { limbs }
+	}
+
+	/// Return `self + rhs`.
+	pub fn add(&self, rhs: &Self) -> Self {
+		let mut limbs = [0u64; 5];
+		for (idx, limb) in limbs.iter_mut().enumerate() {
+			*limb = self.limbs[idx] + rhs.limbs[idx];
+		}
+
+		Self::reduce(limbs)
+	}
+
+	/// Return `self - rhs`.
+	pub fn sub(&self, rhs: &Self) -> Self {
+		// Add 16 * p first, so that no limb underflows.
+		Self::reduce([
+			(self.limbs[0] + 0x007f_ffff_ffff_fed0) - rhs.limbs[0],
+			(self.limbs[1] + 0x007f_ffff_ffff_fff0) - rhs.limbs[1],
+			(self.limbs[2] + 0x007f_ffff_ffff_fff0) - rhs.limbs[2],
+			(self.limbs[3] + 0x007f_ffff_ffff_fff0) - rhs.limbs[3],
+			(self.limbs[4] + 0x007f_ffff_ffff_fff0) - rhs.limbs[4],
+		])
+	}
+
+	/// Return `-self`.
+	pub fn neg(&self) -> Self { Self::zero().sub(self) }
+
+	/// Return `self * rhs`.
+	pub fn mul(&self, rhs: &Self) -> Self {
+		#[inline(always)]
+		fn m(x: u64, y: u64) -> u128 { u128::from(x) * u128::from(y) }
+
+		let a = &self.limbs;
+		let b = &rhs.limbs;
+
+		// 2^255 = 19 mod p, so the high half of the product wraps around
+		// multiplied by 19.
+		let b1_19 = b[1] * 19;
+		let b2_19 = b[2] * 19;
+		let b3_19 = b[3] * 19;
+		let b4_19 = b[4] * 19;
+
+		let c0 = m(a[0], b[0]) + m(a[4], b1_19) + m(a[3], b2_19) + m(a[2], b3_19) + m(a[1], b4_19);
+		let mut c1 =
+			m(a[1], b[0]) + m(a[0], b[1]) + m(a[4], b2_19) + m(a[3], b3_19) + m(a[2], b4_19);
+		let mut c2 =
+			m(a[2], b[0]) + m(a[1], b[1]) + m(a[0], b[2]) + m(a[4], b3_19) + m(a[3], b4_19);
+		let mut c3 = m(a[3], b[0]) + m(a[2], b[1]) + m(a[1], b[2]) + m(a[0], b[3]) + m(a[4], b4_19);
+		let mut c4 = m(a[4], b[0]) + m(a[3], b[1]) + m(a[2], b[2]) + m(a[1], b[3]) + m(a[0], b[4]);
+
+		let mut limbs = [0u64; 5];
+		c1 += c0 >> 51;
+		limbs[0] = (c0 as u64) & LOW_51_BIT_MASK;
+		c2 += c1 >> 51;
+		limbs[1] = (c1 as u64) & LOW_51_BIT_MASK;
+		c3 += c2 >> 51;
+		limbs[2] = (c2 as u64) & LOW_51_BIT_MASK;
+		c4 += c3 >> 51;
+		limbs[3] = (c3 as u64) & LOW_51_BIT_MASK;
+		let carry = (c4 >> 51) as u64;
+		limbs[4] = (c4 as u64) & LOW_51_BIT_MASK;
+
+		limbs[0] += carry * 19;
+		limbs[1] += limbs[0] >> 51;
+		limbs[0] &= LOW_51_BIT_MASK;
+
+		Self { limbs }
+	}
+
+	/// Return `self * self`.
+	pub fn square(&self) -> Self { self.mul(self) }
+
+	/// Return `self^(p - 2)`, which is the multiplicative inverse of `self`,
+	/// or zero if `self` is zero.
+	pub fn invert(&self) -> Self {
+		// p - 2 = 2^255 - 21, in little-endian bytes.
+		let mut exponent = [0xffu8; FIELD_ELEMENT_SIZE];
+		exponent[0] = 0xeb;
+		exponent[31] = 0x7f;
+
+		// The exponent is public, so branching on its bits is constant time.
+		let mut result = Self::one();
+		for bit in (0..255).rev() {
+			result = result.square();
+			if (exponent[bit / 8] >> (bit % 8)) & 1 == 1 {
+				result = result.mul(self);
+			}
+		}
+
+		result
+	}
+
+	/// Return whether `self` is zero.
+	pub fn is_zero(&self) -> Choice { self.ct_eq(&Self::zero()) }
+
+	/// Return whether the canonical encoding of `self` is odd.
+	pub fn is_negative(&self) -> Choice { Choice::from(self.to_bytes()[0] & 1) }
+
+	/// Return `a` if `choice` is 0, or `b` if `choice` is 1.
+	pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+		let mask = u64::from(choice.unwrap_u8()).wrapping_neg();
+		let mut limbs = [0u64; 5];
+		for (idx, limb) in limbs.iter_mut().enumerate() {
+			*limb = a.limbs[idx] ^ (mask & (a.limbs[idx] ^ b.limbs[idx]));
+		}
+
+		Self { limbs }
+	}
+
+	/// Swap `a` and `b` if `choice` is 1, and leave them if `choice` is 0.
+	pub fn conditional_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+		let mask = u64::from(choice.unwrap_u8()).wrapping_neg();
+		for idx in 0..5 {
+			let t = mask & (a.limbs[idx] ^ b.limbs[idx]);
+			a.limbs[idx] ^= t;
+			b.limbs[idx] ^= t;
+		}
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	fn bytes(hex_str: &str) -> [u8; 32] {
+		let mut bytes = [0u8; 32];
+		crate::encoding::hex_decode_into(hex_str, &mut bytes).unwrap();
+		bytes
+	}
+
+	fn fe(hex_str: &str) -> FieldElement { FieldElement::from_slice(&bytes(hex_str)).unwrap() }
+
+	fn a() -> FieldElement {
+		fe("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")
+	}
+
+	fn b() -> FieldElement {
+		fe("a925617886e8799cc5be05aee17f9b70fa3e91370a471e06c21277c10804d05c")
+	}
+
+	/// The encoding of p, which is not canonical.
+	const P: &str = "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f";
+
+	mod test_from_slice_to_bytes {
+		use super::*;
+
+		#[test]
+		fn test_bad_length() {
+			assert!(FieldElement::from_slice(&[0u8; 31]).is_err());
+			assert!(FieldElement::from_slice(&[0u8; 33]).is_err());
+			assert!(FieldElement::from_slice(&[]).is_err());
+		}
+
+		#[test]
+		fn test_roundtrip() {
+			let encoded = bytes("a925617886e8799cc5be05aee17f9b70fa3e91370a471e06c21277c10804d05c");
+			assert_eq!(b().to_bytes(), encoded);
+		}
+
+		#[test]
+		fn test_non_canonical_reduced() {
+			assert_eq!(fe(P).to_bytes(), [0u8; 32]);
+			let mut p_plus_one = bytes(P);
+			p_plus_one[0] += 1;
+			assert_eq!(
+				FieldElement::from_slice(&p_plus_one).unwrap(),
+				FieldElement::one()
+			);
+			// 2^255 - 1 = p + 18
+			let mut max = [0xffu8; 32];
+			max[31] = 0x7f;
+			let mut eighteen = [0u8; 32];
+			eighteen[0] = 18;
+			assert_eq!(FieldElement::from_slice(&max).unwrap().to_bytes(), eighteen);
+		}
+
+		#[test]
+		fn test_top_bit_ignored() {
+			let mut encoded =
+				bytes("a925617886e8799cc5be05aee17f9b70fa3e91370a471e06c21277c10804d05c");
+			encoded[31] |= 0x80;
+			assert_eq!(FieldElement::from_slice(&encoded).unwrap(), b());
+		}
+	}
+
+	mod test_arithmetic {
+		use super::*;
+
+		#[test]
+		fn test_add() {
+			assert_eq!(
+				a().add(&b()),
+				fe("a926637b8aed7fa3cdc70fb9ed8ca97f0a50a34a1e5c341dda2b91dc2421ee7b")
+			);
+			assert_eq!(a().add(&FieldElement::zero()), a());
+		}
+
+		#[test]
+		fn test_sub() {
+			assert_eq!(
+				a().sub(&b()),
+				fe("44dba08a7d1c8c6a424a045d2a8d729e15d280db09cef7105606a35913194e42")
+			);
+			assert_eq!(
+				b().sub(&a()),
+				fe("a9245f7582e37395bdb5fba2d5728d61ea2d7f24f63108efa9f95ca6ece6b13d")
+			);
+			assert!(bool::from(a().sub(&a()).is_zero()));
+		}
+
+		#[test]
+		fn test_neg() {
+			assert_eq!(
+				a().neg().to_bytes(),
+				bytes("edfefdfcfbfaf9f8f7f6f5f4f3f2f1f0efeeedecebeae9e8e7e6e5e4e3e2e160")
+			);
+			assert_eq!(FieldElement::zero().neg(), FieldElement::zero());
+			assert!(bool::from(a().add(&a().neg()).is_zero()));
+		}
+
+		#[test]
+		fn test_mul_square() {
+			assert_eq!(
+				a().mul(&b()),
+				fe("94c73743fa7ed65ce15212acbfbf14ad4a27241c766dbf72b672f9ed4ad1d806")
+			);
+			assert_eq!(
+				a().square(),
+				fe("d8ec2c5e5bff24a7602ce565892a24518cb0981f2075f987fb2efd40d5945a01")
+			);
+			assert_eq!(a().mul(&FieldElement::one()), a());
+			assert_eq!(a().mul(&FieldElement::zero()), FieldElement::zero());
+		}
+
+		#[test]
+		fn test_invert() {
+			assert_eq!(
+				a().invert(),
+				fe("4dcd88822d0589ded58c28d85290e85dcd88822d0589ded58c28d85290e85d73")
+			);
+			assert_eq!(b().mul(&b().invert()), FieldElement::one());
+			assert_eq!(FieldElement::zero().invert(), FieldElement::zero());
+		}
+
+		#[test]
+		fn test_p_minus_one_squared() {
+			let minus_one = FieldElement::one().neg();
+			assert_eq!(minus_one.square(), FieldElement::one());
+			assert_eq!(minus_one.mul(&minus_one).mul(&minus_one), minus_one);
+		}
+	}
+
+	mod test_constant_time_helpers {
+		use super::*;
+
+		#[test]
+		fn test_is_negative() {
+			assert!(!bool::from(FieldElement::zero().is_negative()));
+			assert!(bool::from(FieldElement::one().is_negative()));
+			// -1 = p - 1, which is even.
+			assert!(!bool::from(FieldElement::one().neg().is_negative()));
+		}
+
+		#[test]
+		fn test_conditional_select() {
+			assert_eq!(
+				FieldElement::conditional_select(&a(), &b(), Choice::from(0)),
+				a()
+			);
+			assert_eq!(
+				FieldElement::conditional_select(&a(), &b(), Choice::from(1)),
+				b()
+			);
+		}
+
+		#[test]
+		fn test_conditional_swap() {
+			let (mut x, mut y) = (a(), b());
+			FieldElement::conditional_swap(&mut x, &mut y, Choice::from(0));
+			assert!(x == a() && y == b());
+			FieldElement::conditional_swap(&mut x, &mut y, Choice::from(1));
+			assert!(x == b() && y == a());
+		}
+
+		#[test]
+		fn test_ct_eq() {
+			assert!(bool::from(a().ct_eq(&a())));
+			assert!(!bool::from(a().ct_eq(&b())));
+			assert!(bool::from(fe(P).ct_eq(&FieldElement::zero())));
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		// format! is only available with std
+		fn test_debug_omits_limbs() {
+			assert_eq!(
+				format!("{:?}", a()),
+				"FieldElement { limbs: [***OMITTED***] }"
+			);
+		}
+	}
+
+	// Proptests. Only exectued when NOT testing no_std.
+	#[cfg(feature = "safe_api")]
+	mod proptest {
+		use super::*;
+
+		quickcheck! {
+			fn prop_mul_invert_is_one(input: Vec<u8>) -> bool {
+				let mut bytes = [1u8; 32];
+				for (byte, input_byte) in bytes.iter_mut().zip(input.iter()) {
+					*byte = *input_byte;
+				}
+				let x = FieldElement::from_slice(&bytes).unwrap();
+
+				bool::from(x.is_zero()) || x.mul(&x.invert()) == FieldElement::one()
+			}
+
+			fn prop_add_sub_roundtrip(input_a: Vec<u8>, input_b: Vec<u8>) -> bool {
+				let mut bytes_a = [0u8; 32];
+				let mut bytes_b = [0u8; 32];
+				for (byte, input_byte) in bytes_a.iter_mut().zip(input_a.iter()) {
+					*byte = *input_byte;
+				}
+				for (byte, input_byte) in bytes_b.iter_mut().zip(input_b.iter()) {
+					*byte = *input_byte;
+				}
+				let x = FieldElement::from_slice(&bytes_a).unwrap();
+				let y = FieldElement::from_slice(&bytes_b).unwrap();
+
+				x.add(&y).sub(&y) == x && x.sub(&y).add(&y) == x
+			}
+		}
+	}
+}
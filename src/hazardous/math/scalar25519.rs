@@ -0,0 +1,572 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! - Arithmetic modulo the order ℓ = 2^252 + 27742317777372353535851937790883648493
+//!   of the prime-order subgroup of Curve25519, as used for Ed25519 secret
+//!   scalars and signatures.
+//! - Scalars are stored fully reduced, in five 52-bit limbs, and multiplied
+//!   using Montgomery reduction. All operations run in constant time, with no
+//!   branches or memory accesses that depend on the values.
+//! - `from_slice()` reduces any 32-byte value modulo ℓ, and `from_slice_wide()`
+//!   reduces a 64-byte value, such as a SHA512 digest, modulo ℓ.
+//!   `from_canonical_slice()` only accepts values that are less than ℓ, as
+//!   required when checking the `S` half of an Ed25519 signature.
+//! - `invert()` returns zero for zero.
+//!
+//! # Parameters:
+//! - `slice`: The little-endian encoding of a scalar.
+//! - `rhs`: The right-hand side of an operation.
+//! - `choice`: Selects between two scalars, in constant time.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `slice` is not 32 bytes, or 64 bytes for `from_slice_wide()`.
+//! - `slice` is not less than ℓ when calling `from_canonical_slice()`.
+//!
+//! # Security:
+//! - Only the functions of this module are constant time. Branching on a
+//!   `Choice` they return, or on the output of `to_bytes()`, is not.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::math::scalar25519::Scalar;
+//!
+//! let a = Scalar::from_slice_wide(&[0xab; 64]).unwrap();
+//! let b = Scalar::from_slice(&[0x12; 32]).unwrap();
+//!
+//! assert_eq!(a.mul(&b).mul(&b.invert()), a);
+//! assert_eq!(a.sub(&a), Scalar::zero());
+//! ```
+
+use crate::errors::UnknownCryptoError;
+use subtle::{Choice, ConstantTimeEq};
+
+/// The mask of the 52 bits used in each limb.
+const LOW_52_BIT_MASK: u64 = (1u64 << 52) - 1;
+
+/// The size of an encoded scalar.
+pub const SCALAR_SIZE: usize = 32;
+
+/// The size of the input to `Scalar::from_slice_wide()`.
+pub const SCALAR_WIDE_SIZE: usize = 64;
+
+/// ℓ, in 52-bit limbs.
+const L: [u64; 5] = [
+	0x0002_631a_5cf5_d3ed,
+	0x000d_ea2f_79cd_6581,
+	0x0000_0000_0014_def9,
+	0x0000_0000_0000_0000,
+	0x0000_1000_0000_0000,
+];
+
+/// -ℓ^-1 mod 2^52, used in Montgomery reduction.
+const L_FACTOR: u64 = 0x0005_1da3_1254_7e1b;
+
+/// R = 2^260 mod ℓ, in 52-bit limbs.
+const R: [u64; 5] = [
+	0x000f_48bd_6721_e6ed,
+	0x0003_bab5_ac67_e45a,
+	0x000f_ffff_eb35_e51b,
+	0x000f_ffff_ffff_ffff,
+	0x0000_0fff_ffff_ffff,
+];
+
+/// R^2 mod ℓ, in 52-bit limbs.
+const RR: [u64; 5] = [
+	0x0009_d265_e952_d13b,
+	0x000d_63c7_15be_a69f,
+	0x0005_be65_cb68_7604,
+	0x0003_dcee_c73d_217f,
+	0x0000_0941_1b7c_309a,
+];
+
+#[must_use]
+/// An integer modulo the order of the Curve25519 prime-order subgroup.
+pub struct Scalar {
+	limbs: [u64; 5],
+}
+
+impl Clone for Scalar {
+	fn clone(&self) -> Self { Self { limbs: self.limbs } }
+}
+
+impl Drop for Scalar {
+	fn drop(&mut self) {
+		use zeroize::Zeroize;
+		self.limbs.zeroize();
+	}
+}
+
+impl core::fmt::Debug for Scalar {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "Scalar {{ limbs: [***OMITTED***] }}")
+	}
+}
+
+impl ConstantTimeEq for Scalar {
+	fn ct_eq(&self, other: &Self) -> Choice { self.to_bytes()[..].ct_eq(&other.to_bytes()[..]) }
+}
+
+impl PartialEq for Scalar {
+	fn eq(&self, other: &Self) -> bool { self.ct_eq(other).unwrap_u8() == 1 }
+}
+
+impl Eq for Scalar {}
+
+#[inline(always)]
+/// Multiply two limbs into a 128-bit product.
+fn m(x: u64, y: u64) -> u128 { u128::from(x) * u128::from(y) }
+
+/// Return `a - b`, plus ℓ if the result is negative. The limbs of both must
+/// be at most 52 bits, and `a - b` must be greater than `-ℓ`.
+fn sub_limbs(a: &[u64; 5], b: &[u64; 5]) -> [u64; 5] {
+	let mut difference = [0u64; 5];
+	let mut borrow = 0u64;
+	for idx in 0..5 {
+		borrow = a[idx].wrapping_sub(b[idx] + (borrow >> 63));
+		difference[idx] = borrow & LOW_52_BIT_MASK;
+	}
+
+	// All ones if the subtraction underflowed, and zero otherwise.
+	let underflow_mask = ((borrow >> 63) ^ 1).wrapping_sub(1);
+	let mut carry = 0u64;
+	for idx in 0..5 {
+		carry = (carry >> 52) + difference[idx] + (L[idx] & underflow_mask);
+		difference[idx] = carry & LOW_52_BIT_MASK;
+	}
+
+	difference
+}
+
+/// Return the 9-limb product of `a` and `b`.
+fn mul_limbs(a: &[u64; 5], b: &[u64; 5]) -> [u128; 9] {
+	let mut product = [0u128; 9];
+	for i in 0..5 {
+		for j in 0..5 {
+			product[i + j] += m(a[i], b[j]);
+		}
+	}
+
+	product
+}
+
+/// Return `limbs / R mod ℓ`, for a 9-limb product of two values less than ℓ.
+fn montgomery_reduce(limbs: &[u128; 9]) -> [u64; 5] {
+	#[inline(always)]
+	fn part1(sum: u128) -> (u128, u64) {
+		let p = (sum as u64).wrapping_mul(L_FACTOR) & LOW_52_BIT_MASK;
+		((sum + m(p, L[0])) >> 52, p)
+	}
+
+	#[inline(always)]
+	fn part2(sum: u128) -> (u128, u64) {
+		let w = (sum as u64) & LOW_52_BIT_MASK;
+		(sum >> 52, w)
+	}
+
+	// Add multiples of ℓ that clear the low limbs. L[3] is zero.
+	let (carry, n0) = part1(limbs[0]);
+	let (carry, n1) = part1(carry + limbs[1] + m(n0, L[1]));
+	let (carry, n2) = part1(carry + limbs[2] + m(n0, L[2]) + m(n1, L[1]));
+	let (carry, n3) = part1(carry + limbs[3] + m(n1, L[2]) + m(n2, L[1]));
+	let (carry, n4) = part1(carry + limbs[4] + m(n0, L[4]) + m(n2, L[2]) + m(n3, L[1]));
+
+	// Divide by R = 2^260, by keeping the high limbs.
+	let (carry, r0) = part2(carry + limbs[5] + m(n1, L[4]) + m(n3, L[2]) + m(n4, L[1]));
+	let (carry, r1) = part2(carry + limbs[6] + m(n2, L[4]) + m(n4, L[2]));
+	let (carry, r2) = part2(carry + limbs[7] + m(n3, L[4]));
+	let (carry, r3) = part2(carry + limbs[8] + m(n4, L[4]));
+	let r4 = carry as u64;
+
+	// The result is less than 2ℓ.
+	sub_limbs(&[r0, r1, r2, r3, r4], &L)
+}
+
+/// Return `a * b / R mod ℓ`.
+fn montgomery_mul(a: &[u64; 5], b: &[u64; 5]) -> [u64; 5] { montgomery_reduce(&mul_limbs(a, b)) }
+
+impl Scalar {
+	/// The additive identity.
+	pub fn zero() -> Self { Self { limbs: [0u64; 5] } }
+
+	/// The multiplicative identity.
+	pub fn one() -> Self { Self { limbs: [1, 0, 0, 0, 0] } }
+
+	#[must_use]
+	/// Make a scalar from a 32-byte little-endian value, reduced modulo ℓ.
+	pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+		if slice.len() != SCALAR_SIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut wide = [0u8; SCALAR_WIDE_SIZE];
+		wide[..SCALAR_SIZE].copy_from_slice(slice);
+
+		Self::from_slice_wide(&wide)
+	}
+
+	#[must_use]
+	/// Make a scalar from a 64-byte little-endian value, reduced modulo ℓ.
+	pub fn from_slice_wide(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+		if slice.len() != SCALAR_WIDE_SIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		// Split the value into the low 260 bits and the high 252 bits.
+		let mut words = [0u64; 8];
+		for (word, chunk) in words.iter_mut().zip(slice.chunks(8)) {
+			let mut bytes = [0u8; 8];
+			bytes.copy_from_slice(chunk);
+			*word = u64::from_le_bytes(bytes);
+		}
+
+		let lo = [
+			words[0] & LOW_52_BIT_MASK,
+			((words[0] >> 52) | (words[1] << 12)) & LOW_52_BIT_MASK,
+			((words[1] >> 40) | (words[2] << 24)) & LOW_52_BIT_MASK,
+			((words[2] >> 28) | (words[3] << 36)) & LOW_52_BIT_MASK,
+			((words[3] >> 16) | (words[4] << 48)) & LOW_52_BIT_MASK,
+		];
+		let hi = [
+			(words[4] >> 4) & LOW_52_BIT_MASK,
+			((words[4] >> 56) | (words[5] << 8)) & LOW_52_BIT_MASK,
+			((words[5] >> 44) | (words[6] << 20)) & LOW_52_BIT_MASK,
+			((words[6] >> 32) | (words[7] << 32)) & LOW_52_BIT_MASK,
+			words[7] >> 20,
+		];
+
+		// lo * R / R = lo and hi * R^2 / R = hi * 2^260, both modulo ℓ.
+		let lo = montgomery_mul(&lo, &R);
+		let hi = montgomery_mul(&hi, &RR);
+
+		Ok(Self::from_limbs_sum(&hi, &lo))
+	}
+
+	#[must_use]
+	/// Make a scalar from a 32-byte little-endian value, which must be less
+	/// than ℓ.
+	pub fn from_canonical_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+		let scalar = Self::from_slice(slice)?;
+		// Only a value less than ℓ is unchanged by reduction.
+		if scalar.to_bytes()[..].ct_eq(slice).unwrap_u8() != 1 {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(scalar)
+	}
+
+	/// Return the canonical 32-byte little-endian encoding.
+	pub fn to_bytes(&self) -> [u8; SCALAR_SIZE] {
+		let mut bytes = [0u8; SCALAR_SIZE];
+		let mut acc = 0u128;
+		let mut acc_bits = 0;
+		let mut idx = 0;
+		for limb in self.limbs.iter() {
+			acc |= u128::from(*limb) << acc_bits;
+			acc_bits += 52;
+			while acc_bits >= 8 && idx < SCALAR_SIZE {
+				bytes[idx] = acc as u8;
+				acc >>= 8;
+				acc_bits -= 8;
+				idx += 1;
+			}
+		}
+
+		bytes
+	}
+
+	/// Return `a + b mod ℓ`, for limbs of values less than ℓ.
+	fn from_limbs_sum(a: &[u64; 5], b: &[u64; 5]) -> Self {
+		let mut sum = [0u64; 5];
+		let mut carry = 0u64;
+		for idx in 0..5 {
+			carry = a[idx] + b[idx] + (carry >> 52);
+			sum[idx] = carry & LOW_52_BIT_MASK;
+		}
+
+		// The sum is less than 2ℓ, so subtract ℓ once if needed.
+		Self {
+			limbs: sub_limbs(&sum, &L),
+		}
+	}
+
+	/// Return `self + rhs`.
+	pub fn add(&self, rhs: &Self) -> Self { Self::from_limbs_sum(&self.limbs, &rhs.limbs) }
+
+	/// Return `self - rhs`.
+	pub fn sub(&self, rhs: &Self) -> Self {
+		Self {
+			limbs: sub_limbs(&self.limbs, &rhs.limbs),
+		}
+	}
+
+	/// Return `-self`.
+	pub fn neg(&self) -> Self { Self::zero().sub(self) }
+
+	/// Return `self * rhs`.
+	pub fn mul(&self, rhs: &Self) -> Self {
+		// (a * b / R) * R^2 / R = a * b
+		Self {
+			limbs: montgomery_mul(&montgomery_mul(&self.limbs, &rhs.limbs), &RR),
+		}
+	}
+
+	/// Return `self * self`.
+	pub fn square(&self) -> Self { self.mul(self) }
+
+	/// Return `self^(ℓ - 2)`, which is the multiplicative inverse of `self`,
+	/// or zero if `self` is zero.
+	pub fn invert(&self) -> Self {
+		let mut exponent = L;
+		exponent[0] -= 2;
+
+		// Work in Montgomery form, where x is represented by x * R.
+		let base = montgomery_mul(&self.limbs, &RR);
+		let mut result = R;
+		// The exponent is public, so branching on its bits is constant time.
+		for bit in (0..253).rev() {
+			result = montgomery_mul(&result, &result);
+			if (exponent[bit / 52] >> (bit % 52)) & 1 == 1 {
+				result = montgomery_mul(&result, &base);
+			}
+		}
+
+		// Leave Montgomery form, by multiplying with 1.
+		Self {
+			limbs: montgomery_mul(&result, &[1, 0, 0, 0, 0]),
+		}
+	}
+
+	/// Return whether `self` is zero.
+	pub fn is_zero(&self) -> Choice { self.ct_eq(&Self::zero()) }
+
+	/// Return `a` if `choice` is 0, or `b` if `choice` is 1.
+	pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+		let mask = u64::from(choice.unwrap_u8()).wrapping_neg();
+		let mut limbs = [0u64; 5];
+		for (idx, limb) in limbs.iter_mut().enumerate() {
+			*limb = a.limbs[idx] ^ (mask & (a.limbs[idx] ^ b.limbs[idx]));
+		}
+
+		Self { limbs }
+	}
+
+	/// Swap `a` and `b` if `choice` is 1, and leave them if `choice` is 0.
+	pub fn conditional_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+		let mask = u64::from(choice.unwrap_u8()).wrapping_neg();
+		for idx in 0..5 {
+			let t = mask & (a.limbs[idx] ^ b.limbs[idx]);
+			a.limbs[idx] ^= t;
+			b.limbs[idx] ^= t;
+		}
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	fn bytes<const N: usize>(hex_str: &str) -> [u8; N] {
+		let mut bytes = [0u8; N];
+		crate::encoding::hex_decode_into(hex_str, &mut bytes).unwrap();
+		bytes
+	}
+
+	fn scalar(hex_str: &str) -> Scalar { Scalar::from_slice(&bytes::<32>(hex_str)).unwrap() }
+
+	fn a() -> Scalar {
+		Scalar::from_slice_wide(&bytes::<64>(
+			"93b9d77711e6aece04c390696b3ead1610ede16bd1cdf541904d567d56411ad0\
+			 39186408270aa99200510f99da599878fe4dfd065c68a563e35bd428cbf03f3d",
+		))
+		.unwrap()
+	}
+
+	fn b() -> Scalar {
+		Scalar::from_slice_wide(&bytes::<64>(
+			"5a33af5d760af67b4fa2e86c72f28065dee171fd98ed1da873805f3c83ba9cca\
+			 f09ba6f78a0f3f7954395c8f7cf5baefd50e2ab6e400d73c060bc1464226b1cd",
+		))
+		.unwrap()
+	}
+
+	/// The encoding of ℓ, which is not canonical.
+	const ORDER: &str = "edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010";
+
+	mod test_from_slice_to_bytes {
+		use super::*;
+
+		#[test]
+		fn test_bad_length() {
+			assert!(Scalar::from_slice(&[0u8; 31]).is_err());
+			assert!(Scalar::from_slice(&[0u8; 64]).is_err());
+			assert!(Scalar::from_slice_wide(&[0u8; 32]).is_err());
+			assert!(Scalar::from_slice_wide(&[0u8; 65]).is_err());
+			assert!(Scalar::from_canonical_slice(&[0u8; 31]).is_err());
+		}
+
+		#[test]
+		fn test_wide_reduced() {
+			assert_eq!(
+				a().to_bytes(),
+				bytes::<32>("b8f3cb666808c89a312695baef0af572c60427730d0a6c99345da2bd7d7f640b")
+			);
+			assert_eq!(
+				b().to_bytes(),
+				bytes::<32>("56e0d78303631cfd3a5f3d5cf76372a862f28d5a985633664c231be97f2fcf08")
+			);
+		}
+
+		#[test]
+		fn test_reduced() {
+			assert_eq!(scalar(ORDER), Scalar::zero());
+			assert_eq!(
+				Scalar::from_slice(&[0xff; 32]).unwrap().to_bytes(),
+				bytes::<32>("1c95988d7431ecd670cf7d73f45befc6feffffffffffffffffffffffffffff0f")
+			);
+		}
+
+		#[test]
+		fn test_canonical() {
+			assert!(Scalar::from_canonical_slice(&bytes::<32>(ORDER)).is_err());
+			assert!(Scalar::from_canonical_slice(&[0xff; 32]).is_err());
+			let mut order_minus_one = bytes::<32>(ORDER);
+			order_minus_one[0] -= 1;
+			assert_eq!(
+				Scalar::from_canonical_slice(&order_minus_one).unwrap(),
+				Scalar::one().neg()
+			);
+			assert_eq!(Scalar::from_canonical_slice(&a().to_bytes()).unwrap(), a());
+		}
+	}
+
+	mod test_arithmetic {
+		use super::*;
+
+		#[test]
+		fn test_add() {
+			assert_eq!(
+				a().add(&b()),
+				scalar("2100ae8d5108d23f96e8da730875880629f7b4cda5609fff8080bda6fdae3304")
+			);
+			assert_eq!(a().add(&Scalar::zero()), a());
+		}
+
+		#[test]
+		fn test_sub() {
+			assert_eq!(
+				a().sub(&b()),
+				scalar("6213f4e264a5ab9df6c6575ef8a682ca6312991875b33833e83987d4fd4f9502")
+			);
+			assert_eq!(a().sub(&b()).add(&b()), a());
+		}
+
+		#[test]
+		fn test_neg() {
+			assert_eq!(
+				a().neg(),
+				scalar("35e029f6b15a4abda47662e8eeeee9a139fbd88cf2f59366cba25d4282809b04")
+			);
+			assert_eq!(Scalar::zero().neg(), Scalar::zero());
+		}
+
+		#[test]
+		fn test_mul() {
+			assert_eq!(
+				a().mul(&b()),
+				scalar("fc4a2b5eebbebca748711b3322262eb20d53ed1ec74e762de62d89ea68150002")
+			);
+			assert_eq!(a().mul(&Scalar::one()), a());
+			assert_eq!(a().square(), a().mul(&a()));
+		}
+
+		#[test]
+		fn test_invert() {
+			assert_eq!(
+				a().invert(),
+				scalar("4c218733f7e30e17b3b872ef6dffdb91c52cb3b6ec284d9f250494df5bd38202")
+			);
+			assert_eq!(b().mul(&b().invert()), Scalar::one());
+			assert_eq!(Scalar::zero().invert(), Scalar::zero());
+		}
+	}
+
+	mod test_constant_time_helpers {
+		use super::*;
+
+		#[test]
+		fn test_conditional_select_swap() {
+			assert_eq!(Scalar::conditional_select(&a(), &b(), Choice::from(0)), a());
+			assert_eq!(Scalar::conditional_select(&a(), &b(), Choice::from(1)), b());
+
+			let (mut x, mut y) = (a(), b());
+			Scalar::conditional_swap(&mut x, &mut y, Choice::from(0));
+			assert!(x == a() && y == b());
+			Scalar::conditional_swap(&mut x, &mut y, Choice::from(1));
+			assert!(x == b() && y == a());
+		}
+
+		#[test]
+		fn test_is_zero() {
+			assert!(bool::from(Scalar::zero().is_zero()));
+			assert!(!bool::from(a().is_zero()));
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		// format! is only available with std
+		fn test_debug_omits_limbs() {
+			assert_eq!(format!("{:?}", a()), "Scalar { limbs: [***OMITTED***] }");
+		}
+	}
+
+	// Proptests. Only exectued when NOT testing no_std.
+	#[cfg(feature = "safe_api")]
+	mod proptest {
+		use super::*;
+
+		quickcheck! {
+			fn prop_mul_invert_is_one(input: Vec<u8>) -> bool {
+				let mut wide = [1u8; 64];
+				for (byte, input_byte) in wide.iter_mut().zip(input.iter()) {
+					*byte = *input_byte;
+				}
+				let x = Scalar::from_slice_wide(&wide).unwrap();
+
+				bool::from(x.is_zero()) || x.mul(&x.invert()) == Scalar::one()
+			}
+
+			fn prop_wide_matches_narrow(input: Vec<u8>) -> bool {
+				let mut narrow = [0u8; 32];
+				for (byte, input_byte) in narrow.iter_mut().zip(input.iter()) {
+					*byte = *input_byte;
+				}
+				let mut wide = [0u8; 64];
+				wide[..32].copy_from_slice(&narrow);
+
+				Scalar::from_slice(&narrow).unwrap() == Scalar::from_slice_wide(&wide).unwrap()
+			}
+		}
+	}
+}
@@ -47,3 +47,15 @@ pub mod constants;
 
 /// Stream ciphers.
 pub mod stream;
+
+/// Block ciphers.
+pub mod cipher;
+
+/// Constant-time field and scalar arithmetic for Curve25519.
+pub mod math;
+
+/// QUIC key derivation and header protection as specified in the [RFC 9001](https://tools.ietf.org/html/rfc9001).
+pub mod quic;
+
+/// Size-generic types that the fixed-size types in `hazardous` are aliases of.
+pub mod types;
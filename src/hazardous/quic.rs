@@ -0,0 +1,388 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! The key derivation and header protection parts of QUIC packet protection,
+//! as specified in the [RFC 9001](https://tools.ietf.org/html/rfc9001), for
+//! the cipher suites that use HKDF-SHA256. The packets themselves are
+//! encrypted with an AEAD, such as `chacha20poly1305`, using the derived key
+//! and IV.
+//!
+//! - `hkdf_expand_label()` is `HKDF-Expand-Label` from TLS 1.3.
+//! - `initial_secrets()` derives the client and server Initial secrets from
+//!   the Destination Connection ID of the first Initial packet, using the
+//!   QUIC version 1 salt.
+//! - `packet_key()`, `packet_iv()` and `header_protection_key()` derive the
+//!   packet protection keys from a secret.
+//! - `next_secret()` derives the secret for the next key phase.
+//! - `chacha20_header_protection_mask()` returns the header protection mask
+//!   for the ChaCha20 based cipher suite.
+//!
+//! # Parameters:
+//! - `secret`: A traffic secret or an Initial secret.
+//! - `label`: The label, without the `"tls13 "` prefix.
+//! - `context`: The context value.
+//! - `connection_id`: The Destination Connection ID.
+//! - `hp_key`: The header protection key.
+//! - `sample`: The 16 byte sample of the packet ciphertext.
+//! - `dst_out`: Destination buffer for the derived key.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `label` is longer than 249 bytes.
+//! - `context` is longer than 255 bytes.
+//! - The length of `dst_out` is less than 1 or greater than 8160.
+//! - `connection_id` is longer than 20 bytes.
+//! - `sample` is not 16 bytes.
+//!
+//! # Security:
+//! - The Initial secrets only depend on the Destination Connection ID, which
+//!   is sent in the clear. Initial packets are therefore not confidential,
+//!   and anyone who observes them can decrypt them.
+//! - Header protection does not authenticate anything. The header must
+//!   still be authenticated as the associated data of the packet AEAD.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::{quic, stream::chacha20};
+//!
+//! let connection_id = [0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08];
+//! let (client_secret, _server_secret) = quic::initial_secrets(&connection_id).unwrap();
+//!
+//! let mut key = [0u8; 16];
+//! quic::packet_key(&client_secret, &mut key).unwrap();
+//! let iv = quic::packet_iv(&client_secret).unwrap();
+//!
+//! // With the ChaCha20Poly1305 cipher suite, header protection uses ChaCha20.
+//! let mut hp_key = [0u8; 32];
+//! quic::header_protection_key(&client_secret, &mut hp_key).unwrap();
+//! let hp_key = chacha20::SecretKey::from_slice(&hp_key).unwrap();
+//! let sample = [0u8; 16];
+//! let mask = quic::chacha20_header_protection_mask(&hp_key, &sample).unwrap();
+//! ```
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		constants::{IETF_CHACHA_NONCESIZE, SHA256_OUTSIZE},
+		kdf::hkdf,
+		mac::hmac,
+		stream::chacha20,
+		types,
+	},
+};
+
+/// A type to represent a QUIC traffic secret, or an Initial secret.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 32 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type Secret = types::SecretKey<types::tag::QuicSecret, SHA256_OUTSIZE>;
+
+/// The salt that QUIC version 1 uses to derive the Initial secrets.
+pub const INITIAL_SALT_V1: [u8; 20] = [
+	0x38, 0x76, 0x2c, 0xf7, 0xf5, 0x59, 0x34, 0xb3, 0x4d, 0x17, 0x9a, 0xe6, 0xa4, 0xc8, 0x0c, 0xad,
+	0xcc, 0xbb, 0x7f, 0x0a,
+];
+
+/// The size of the ciphertext sample that header protection uses.
+pub const SAMPLE_SIZE: usize = 16;
+
+/// The size of the header protection mask.
+pub const MASK_SIZE: usize = 5;
+
+/// The size of the packet protection IV.
+pub const IV_SIZE: usize = 12;
+
+/// The maximum length of a connection ID in QUIC version 1.
+const MAX_CONNECTION_ID_SIZE: usize = 20;
+
+/// The prefix that TLS 1.3 adds to every label.
+const LABEL_PREFIX: &[u8] = b"tls13 ";
+
+#[must_use]
+/// `HKDF-Expand-Label` as specified in the [RFC 8446](https://tools.ietf.org/html/rfc8446#section-7.1),
+/// using HKDF-SHA256.
+pub fn hkdf_expand_label(
+	secret: &Secret,
+	label: &[u8],
+	context: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if label.len() > 255 - LABEL_PREFIX.len() {
+		return Err(UnknownCryptoError);
+	}
+	if context.len() > 255 {
+		return Err(UnknownCryptoError);
+	}
+	if dst_out.len() > usize::from(u16::MAX) {
+		return Err(UnknownCryptoError);
+	}
+
+	// struct { uint16 length; opaque label<7..255>; opaque context<0..255>; }
+	let mut info = [0u8; 2 + 1 + 255 + 1 + 255];
+	let mut info_len = 0;
+	for part in [
+		&(dst_out.len() as u16).to_be_bytes()[..],
+		&[(LABEL_PREFIX.len() + label.len()) as u8],
+		LABEL_PREFIX,
+		label,
+		&[context.len() as u8],
+		context,
+	]
+	.iter()
+	{
+		info[info_len..(info_len + part.len())].copy_from_slice(part);
+		info_len += part.len();
+	}
+
+	let prk = hmac::sha256::Tag::from_slice(&secret.unprotected_as_bytes())?;
+	hkdf::sha256::expand(&prk, Some(&info[..info_len]), dst_out)
+}
+
+#[must_use]
+/// Derive a `Secret` with `hkdf_expand_label()`.
+fn expand_secret(secret: &Secret, label: &[u8]) -> Result<Secret, UnknownCryptoError> {
+	use zeroize::Zeroize;
+
+	let mut dst_out = [0u8; SHA256_OUTSIZE];
+	hkdf_expand_label(secret, label, &[], &mut dst_out)?;
+	let next = Secret::from_slice(&dst_out);
+	dst_out.zeroize();
+
+	next
+}
+
+#[must_use]
+/// Derive the client and server Initial secrets from the Destination
+/// Connection ID, returned as `(client, server)`.
+pub fn initial_secrets(connection_id: &[u8]) -> Result<(Secret, Secret), UnknownCryptoError> {
+	if connection_id.len() > MAX_CONNECTION_ID_SIZE {
+		return Err(UnknownCryptoError);
+	}
+
+	let initial_secret = Secret::from_slice(
+		&hkdf::sha256::extract(&INITIAL_SALT_V1, connection_id)?.unprotected_as_bytes(),
+	)?;
+
+	Ok((
+		expand_secret(&initial_secret, b"client in")?,
+		expand_secret(&initial_secret, b"server in")?,
+	))
+}
+
+#[must_use]
+/// Derive the packet protection key from `secret`. The length of `dst_out`
+/// is the key size of the AEAD.
+pub fn packet_key(secret: &Secret, dst_out: &mut [u8]) -> Result<(), UnknownCryptoError> {
+	hkdf_expand_label(secret, b"quic key", &[], dst_out)
+}
+
+#[must_use]
+/// Derive the packet protection IV from `secret`.
+pub fn packet_iv(secret: &Secret) -> Result<[u8; IV_SIZE], UnknownCryptoError> {
+	let mut iv = [0u8; IV_SIZE];
+	hkdf_expand_label(secret, b"quic iv", &[], &mut iv)?;
+
+	Ok(iv)
+}
+
+#[must_use]
+/// Derive the header protection key from `secret`. The length of `dst_out`
+/// is the key size of the header protection cipher.
+pub fn header_protection_key(
+	secret: &Secret,
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	hkdf_expand_label(secret, b"quic hp", &[], dst_out)
+}
+
+#[must_use]
+/// Derive the secret of the next key phase from `secret`. The header
+/// protection key is not updated along with it.
+pub fn next_secret(secret: &Secret) -> Result<Secret, UnknownCryptoError> {
+	expand_secret(secret, b"quic ku")
+}
+
+#[must_use]
+/// Return the ChaCha20 header protection mask for `sample`, as specified in
+/// the [RFC 9001](https://tools.ietf.org/html/rfc9001#section-5.4.4).
+pub fn chacha20_header_protection_mask(
+	hp_key: &chacha20::SecretKey,
+	sample: &[u8],
+) -> Result<[u8; MASK_SIZE], UnknownCryptoError> {
+	if sample.len() != SAMPLE_SIZE {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut counter = [0u8; 4];
+	counter.copy_from_slice(&sample[..4]);
+	let nonce = chacha20::Nonce::from_slice(&sample[(SAMPLE_SIZE - IETF_CHACHA_NONCESIZE)..])?;
+
+	let mut mask = [0u8; MASK_SIZE];
+	mask.copy_from_slice(
+		&chacha20::keystream_block(hp_key, &nonce, u32::from_le_bytes(counter))?[..MASK_SIZE],
+	);
+
+	Ok(mask)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use crate::encoding::hex_decode_into;
+
+	fn secret_from_hex(hex: &str) -> Secret {
+		let mut secret = [0u8; 32];
+		hex_decode_into(hex, &mut secret).unwrap();
+		Secret::from_slice(&secret).unwrap()
+	}
+
+	fn assert_label(secret: &Secret, label: &[u8], expected: &str) {
+		let mut expected_bytes = [0u8; 32];
+		let expected_bytes = &mut expected_bytes[..(expected.len() / 2)];
+		hex_decode_into(expected, expected_bytes).unwrap();
+
+		let mut actual = [0u8; 32];
+		let actual = &mut actual[..expected_bytes.len()];
+		hkdf_expand_label(secret, label, &[], actual).unwrap();
+		assert_eq!(actual, expected_bytes);
+	}
+
+	// From RFC 9001, Appendix A.1.
+	const CONNECTION_ID: [u8; 8] = [0x83, 0x94, 0xc8, 0xf0, 0x3e, 0x51, 0x57, 0x08];
+
+	mod test_initial_secrets {
+		use super::*;
+
+		#[test]
+		fn test_rfc9001_vectors() {
+			let (client, server) = initial_secrets(&CONNECTION_ID).unwrap();
+			assert!(
+				client
+					== secret_from_hex(
+						"c00cf151ca5be075ed0ebfb5c80323c42d6b7db67881289af4008f1f6c357aea"
+					)
+			);
+			assert!(
+				server
+					== secret_from_hex(
+						"3c199828fd139efd216c155ad844cc81fb82fa8d7446fa7d78be803acdda951b"
+					)
+			);
+
+			assert_label(&client, b"quic key", "1f369613dd76d5467730efcbe3b1a22d");
+			assert_label(&client, b"quic iv", "fa044b2f42a3fd3b46fb255c");
+			assert_label(&client, b"quic hp", "9f50449e04a0e810283a1e9933adedd2");
+			assert_label(&server, b"quic key", "cf3a5331653c364c88f0f379b6067e37");
+			assert_label(&server, b"quic iv", "0ac1493ca1905853b0bba03e");
+			assert_label(&server, b"quic hp", "c206b8d9b9f0f37644430b490eeaa314");
+		}
+
+		#[test]
+		fn test_connection_id_length() {
+			assert!(initial_secrets(&[]).is_ok());
+			assert!(initial_secrets(&[0u8; 20]).is_ok());
+			assert!(initial_secrets(&[0u8; 21]).is_err());
+		}
+	}
+
+	mod test_hkdf_expand_label {
+		use super::*;
+
+		#[test]
+		fn test_bad_lengths() {
+			let secret = Secret::from_slice(&[0u8; 32]).unwrap();
+			let mut dst_out = [0u8; 32];
+			assert!(hkdf_expand_label(&secret, &[0u8; 249], &[0u8; 255], &mut dst_out).is_ok());
+			assert!(hkdf_expand_label(&secret, &[0u8; 250], &[], &mut dst_out).is_err());
+			assert!(hkdf_expand_label(&secret, b"quic key", &[0u8; 256], &mut dst_out).is_err());
+			assert!(hkdf_expand_label(&secret, b"quic key", &[], &mut []).is_err());
+		}
+
+		#[test]
+		fn test_length_is_part_of_info() {
+			let secret = Secret::from_slice(&[0u8; 32]).unwrap();
+			let mut dst_out_16 = [0u8; 16];
+			let mut dst_out_32 = [0u8; 32];
+			hkdf_expand_label(&secret, b"quic key", &[], &mut dst_out_16).unwrap();
+			hkdf_expand_label(&secret, b"quic key", &[], &mut dst_out_32).unwrap();
+			assert_ne!(dst_out_16[..], dst_out_32[..16]);
+		}
+	}
+
+	mod test_chacha20_suite {
+		use super::*;
+
+		// From RFC 9001, Appendix A.5.
+		const SECRET: &str = "9ac312a7f877468ebe69422748ad00a15443f18203a07d6060f688f30f21632b";
+
+		#[test]
+		fn test_rfc9001_keys() {
+			let secret = secret_from_hex(SECRET);
+			assert_label(
+				&secret,
+				b"quic key",
+				"c6d98ff3441c3fe1b2182094f69caa2ed4b716b65488960a7a984979fb23e1c8",
+			);
+			assert_label(
+				&secret,
+				b"quic hp",
+				"25a282b9e82f06f21f488917a4fc8f1b73573685608597d0efcb076b0ab7a7a4",
+			);
+
+			let mut expected_iv = [0u8; IV_SIZE];
+			hex_decode_into("e0459b3474bdd0e44a41c144", &mut expected_iv).unwrap();
+			assert_eq!(packet_iv(&secret).unwrap(), expected_iv);
+
+			assert!(
+				next_secret(&secret).unwrap()
+					== secret_from_hex(
+						"1223504755036d556342ee9361d253421a826c9ecdf3c7148684b36b714881f9"
+					)
+			);
+		}
+
+		#[test]
+		fn test_rfc9001_header_protection_mask() {
+			let mut hp_key = [0u8; 32];
+			header_protection_key(&secret_from_hex(SECRET), &mut hp_key).unwrap();
+			let hp_key = chacha20::SecretKey::from_slice(&hp_key).unwrap();
+
+			let mut sample = [0u8; SAMPLE_SIZE];
+			hex_decode_into("5e5cd55c41f69080575d7999c25a5bfb", &mut sample).unwrap();
+			assert_eq!(
+				chacha20_header_protection_mask(&hp_key, &sample).unwrap(),
+				[0xae, 0xfe, 0xfe, 0x7d, 0x03]
+			);
+		}
+
+		#[test]
+		fn test_sample_length() {
+			let hp_key = chacha20::SecretKey::from_slice(&[0u8; 32]).unwrap();
+			assert!(chacha20_header_protection_mask(&hp_key, &[0u8; 15]).is_err());
+			assert!(chacha20_header_protection_mask(&hp_key, &[0u8; 17]).is_err());
+			assert!(chacha20_header_protection_mask(&hp_key, &[0u8; 16]).is_ok());
+		}
+	}
+}
@@ -102,35 +102,34 @@
 use crate::{
 	endianness::{load_u32_into_le, store_u32_into_le},
 	errors::UnknownCryptoError,
-	hazardous::constants::{
-		ChaChaState,
-		CHACHA_BLOCKSIZE,
-		CHACHA_KEYSIZE,
-		HCHACHA_NONCESIZE,
-		HCHACHA_OUTSIZE,
-		IETF_CHACHA_NONCESIZE,
+	hazardous::{
+		constants::{
+			ChaChaState,
+			CHACHA_BLOCKSIZE,
+			CHACHA_KEYSIZE,
+			HCHACHA_NONCESIZE,
+			HCHACHA_OUTSIZE,
+			IETF_CHACHA_NONCESIZE,
+		},
+		types,
 	},
 };
 use zeroize::Zeroize;
 
-construct_secret_key! {
-	/// A type to represent the `SecretKey` that `chacha20`, `xchacha20`, `chacha20poly1305` and
-	/// `xchacha20poly1305` use.
-	///
-	/// # Exceptions:
-	/// An exception will be thrown if:
-	/// - `slice` is not 32 bytes.
-	/// - The `OsRng` fails to initialize or read from its source.
-	(SecretKey, CHACHA_KEYSIZE)
-}
-construct_nonce_no_generator! {
-	/// A type that represents a `Nonce` that ChaCha20 and ChaCha20Poly1305 use.
-	///
-	/// # Exceptions:
-	/// An exception will be thrown if:
-	/// - `slice` is not 12 bytes.
-	(Nonce, IETF_CHACHA_NONCESIZE)
-}
+/// A type to represent the `SecretKey` that `chacha20`, `xchacha20`, `chacha20poly1305` and
+/// `xchacha20poly1305` use.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 32 bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+pub type SecretKey = types::SecretKey<types::tag::ChaCha20Key, CHACHA_KEYSIZE>;
+/// A type that represents a `Nonce` that ChaCha20 and ChaCha20Poly1305 use.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 12 bytes.
+pub type Nonce = types::PublicData<types::tag::ChaCha20Nonce, IETF_CHACHA_NONCESIZE>;
 
 #[derive(Clone)]
 struct InternalState {
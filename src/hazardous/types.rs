@@ -0,0 +1,516 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! The size-generic types that the fixed-size types in `hazardous` are aliases
+//! of.
+//!
+//! # About:
+//! - `SecretKey<T, N>` holds `N` bytes of sensitive data. It implements
+//!   constant-time `PartialEq`, zeroes its memory on drop and omits its
+//!   contents from `Debug`.
+//! - `PublicData<T, N>` holds `N` bytes of non-sensitive data, such as a digest
+//!   or a nonce. It implements a normal `PartialEq` and `Debug`, as well as
+//!   `Ord` and `Hash` so that it can be used as a key in a `BTreeMap` or
+//!   `HashMap`. It is displayed as lowercase hex and can be parsed back with
+//!   `FromStr`.
+//!
+//! `T` is one of the zero-sized markers in [`tag`]. The algorithm-specific
+//! modules expose type aliases with their own marker, such as
+//! `chacha20::SecretKey` for `SecretKey<tag::ChaCha20Key, 32>` and
+//! `sha512::Digest` for `PublicData<tag::Sha512Digest, 64>`. Aliases with
+//! different markers are different types, so a `chacha20::SecretKey` is not
+//! accepted where a `poly1305::OneTimeKey` is expected, even though both are 32
+//! bytes. Code that must work with any alias can be generic over `T` and `N`.
+//!
+//! These types use const generics and require Rust 1.51 or later.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::types::PublicData;
+//! use orion::hazardous::hash::{sha256, sha512};
+//!
+//! fn first_byte<T, const N: usize>(data: &PublicData<T, N>) -> u8 {
+//! 	data.as_bytes()[0]
+//! }
+//!
+//! let digest256 = sha256::digest(b"Some data").unwrap();
+//! let digest512 = sha512::digest(b"Some data").unwrap();
+//!
+//! assert_eq!(first_byte(&digest256), digest256.as_bytes()[0]);
+//! assert_eq!(first_byte(&digest512), digest512.as_bytes()[0]);
+//! ```
+
+use crate::errors::UnknownCryptoError;
+use core::{convert::TryFrom, marker::PhantomData};
+
+/// Zero-sized markers for the `T` parameter of `SecretKey` and `PublicData`.
+///
+/// Each marker is an empty enum, so it can never be constructed and takes up no
+/// space. Two aliases with different markers are different types:
+///
+/// ```compile_fail
+/// use orion::hazardous::{mac::poly1305, stream::chacha20};
+///
+/// fn use_key(_: &poly1305::OneTimeKey) {}
+///
+/// let key = chacha20::SecretKey::from_slice(&[0u8; 32]).unwrap();
+/// use_key(&key);
+/// ```
+pub mod tag {
+	/// Marker of `chacha20::SecretKey`.
+	pub enum ChaCha20Key {}
+	/// Marker of `chacha20::Nonce`.
+	pub enum ChaCha20Nonce {}
+	/// Marker of `chacha20poly1305_legacy::Nonce`.
+	pub enum LegacyChaCha20Nonce {}
+	/// Marker of `chacha20poly1305_openssh::SecretKey`.
+	pub enum OpenSshChaChaKey {}
+	/// Marker of `poly1305::OneTimeKey`.
+	pub enum Poly1305Key {}
+	/// Marker of `aes::SecretKey128`, `aes::SecretKey192` and
+	/// `aes::SecretKey256`.
+	pub enum AesKey {}
+	/// Marker of `aes_siv::SecretKey`.
+	pub enum AesSivKey {}
+	/// Marker of `sha256::Digest`.
+	pub enum Sha256Digest {}
+	/// Marker of `sha512::Digest`.
+	pub enum Sha512Digest {}
+	/// Marker of `chain::Seed`.
+	pub enum ChainSeed {}
+	/// Marker of `chain::Link`.
+	pub enum ChainLink {}
+	/// Marker of `slip10::PrivateKey`.
+	pub enum Slip10PrivateKey {}
+	/// Marker of `slip10::ChainCode`.
+	pub enum Slip10ChainCode {}
+	/// Marker of `quic::Secret`.
+	pub enum QuicSecret {}
+	/// Marker of `orion::auth::nacl::SecretKey`.
+	pub enum NaclAuthKey {}
+	/// Marker of `orion::commit::Commitment`.
+	pub enum Commitment {}
+	/// Marker of `orion::commit::Opening`.
+	pub enum Opening {}
+	/// Marker of `orion::aead::KeyId`.
+	pub enum KeyId {}
+}
+
+#[must_use]
+/// A type to represent `N` bytes of sensitive data.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not `N` bytes.
+/// - The `OsRng` fails to initialize or read from its source.
+///
+/// # Security:
+/// - __**Avoid using**__ `unprotected_as_bytes()` whenever possible, as it
+///   breaks all protections that the type implements.
+pub struct SecretKey<T, const N: usize> {
+	value: [u8; N],
+	tag: PhantomData<T>,
+}
+
+impl<T, const N: usize> SecretKey<T, N> {
+	#[must_use]
+	/// Make an object from a given byte slice.
+	pub fn from_slice(slice: &[u8]) -> Result<SecretKey<T, N>, UnknownCryptoError> {
+		if slice.len() != N {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut value = [0u8; N];
+		value.copy_from_slice(slice);
+
+		Ok(SecretKey {
+			value,
+			tag: PhantomData,
+		})
+	}
+
+	#[must_use]
+	/// Return the object as byte slice. __**Warning**__: Should not be used
+	/// unless strictly needed. This __**breaks protections**__ that the type
+	/// implements.
+	pub fn unprotected_as_bytes(&self) -> &[u8] { self.value.as_ref() }
+
+	#[must_use]
+	#[cfg(feature = "safe_api")]
+	/// Randomly generate using a CSPRNG. Not available in `no_std` context.
+	pub fn generate() -> Result<SecretKey<T, N>, UnknownCryptoError> {
+		use crate::util;
+		let mut value = [0u8; N];
+		util::secure_rand_bytes(&mut value)?;
+
+		Ok(SecretKey {
+			value,
+			tag: PhantomData,
+		})
+	}
+
+	/// Return the length of the object.
+	pub fn get_length(&self) -> usize { self.value.len() }
+
+	#[must_use]
+	/// Return a copy of the object. Both copies are zeroed out when dropped.
+	/// __**Warning**__: Should not be used unless strictly needed, as every
+	/// copy is another place in memory that holds the secret data.
+	pub fn duplicate(&self) -> SecretKey<T, N> {
+		SecretKey {
+			value: self.value,
+			tag: PhantomData,
+		}
+	}
+}
+
+impl<T, const N: usize> core::fmt::Debug for SecretKey<T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "SecretKey {{***OMITTED***}}")
+	}
+}
+
+impl<T, const N: usize> Drop for SecretKey<T, N> {
+	fn drop(&mut self) {
+		use zeroize::Zeroize;
+		self.value.zeroize();
+	}
+}
+
+#[cfg(feature = "secrecy_interop")]
+impl<T, const N: usize> zeroize::Zeroize for SecretKey<T, N> {
+	fn zeroize(&mut self) { self.value.zeroize(); }
+}
+
+impl<T, const N: usize> PartialEq for SecretKey<T, N> {
+	fn eq(&self, other: &SecretKey<T, N>) -> bool {
+		use subtle::ConstantTimeEq;
+		self.unprotected_as_bytes()
+			.ct_eq(other.unprotected_as_bytes())
+			.unwrap_u8() == 1
+	}
+}
+
+impl<T, const N: usize> TryFrom<&[u8]> for SecretKey<T, N> {
+	type Error = UnknownCryptoError;
+
+	fn try_from(slice: &[u8]) -> Result<SecretKey<T, N>, UnknownCryptoError> {
+		SecretKey::from_slice(slice)
+	}
+}
+
+impl<T, const N: usize> From<[u8; N]> for SecretKey<T, N> {
+	fn from(bytes: [u8; N]) -> SecretKey<T, N> {
+		SecretKey {
+			value: bytes,
+			tag: PhantomData,
+		}
+	}
+}
+
+#[must_use]
+/// A type to represent `N` bytes of non-sensitive data.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not `N` bytes.
+pub struct PublicData<T, const N: usize> {
+	value: [u8; N],
+	tag: PhantomData<T>,
+}
+
+impl<T, const N: usize> Clone for PublicData<T, N> {
+	fn clone(&self) -> PublicData<T, N> { *self }
+}
+
+impl<T, const N: usize> Copy for PublicData<T, N> {}
+
+impl<T, const N: usize> PublicData<T, N> {
+	#[must_use]
+	/// Make an object from a given byte slice.
+	pub fn from_slice(slice: &[u8]) -> Result<PublicData<T, N>, UnknownCryptoError> {
+		if slice.len() != N {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut value = [0u8; N];
+		value.copy_from_slice(slice);
+
+		Ok(PublicData {
+			value,
+			tag: PhantomData,
+		})
+	}
+
+	#[must_use]
+	/// Return the object as byte slice.
+	pub fn as_bytes(&self) -> &[u8] { self.value.as_ref() }
+
+	/// Return the length of the object.
+	pub fn get_length(&self) -> usize { self.value.len() }
+}
+
+impl<T, const N: usize> core::fmt::Debug for PublicData<T, N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "PublicData {:?}", &self.value[..])
+	}
+}
+
+impl<T, const N: usize> PartialEq for PublicData<T, N> {
+	fn eq(&self, other: &PublicData<T, N>) -> bool { self.as_bytes() == other.as_bytes() }
+}
+
+impl<T, const N: usize> core::fmt::Display for PublicData<T, N> {
+	/// Write the object as lowercase hex.
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		crate::encoding::hex_encode_fmt(self.as_bytes(), f)
+	}
+}
+
+impl<T, const N: usize> core::str::FromStr for PublicData<T, N> {
+	type Err = UnknownCryptoError;
+
+	/// Make an object from its hex encoding. Both lowercase and uppercase hex
+	/// are accepted.
+	fn from_str(s: &str) -> Result<PublicData<T, N>, UnknownCryptoError> {
+		let mut value = [0u8; N];
+		crate::encoding::hex_decode_into(s, &mut value)?;
+
+		Ok(PublicData {
+			value,
+			tag: PhantomData,
+		})
+	}
+}
+
+impl<T, const N: usize> Eq for PublicData<T, N> {}
+
+impl<T, const N: usize> PartialOrd for PublicData<T, N> {
+	fn partial_cmp(&self, other: &PublicData<T, N>) -> Option<core::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl<T, const N: usize> Ord for PublicData<T, N> {
+	fn cmp(&self, other: &PublicData<T, N>) -> core::cmp::Ordering {
+		self.as_bytes().cmp(other.as_bytes())
+	}
+}
+
+impl<T, const N: usize> core::hash::Hash for PublicData<T, N> {
+	fn hash<H: core::hash::Hasher>(&self, state: &mut H) { self.as_bytes().hash(state); }
+}
+
+impl<T, const N: usize> TryFrom<&[u8]> for PublicData<T, N> {
+	type Error = UnknownCryptoError;
+
+	fn try_from(slice: &[u8]) -> Result<PublicData<T, N>, UnknownCryptoError> {
+		PublicData::from_slice(slice)
+	}
+}
+
+impl<T, const N: usize> From<[u8; N]> for PublicData<T, N> {
+	fn from(bytes: [u8; N]) -> PublicData<T, N> {
+		PublicData {
+			value: bytes,
+			tag: PhantomData,
+		}
+	}
+}
+
+impl<T, const N: usize> From<PublicData<T, N>> for [u8; N] {
+	fn from(data: PublicData<T, N>) -> [u8; N] { data.value }
+}
+
+#[cfg(feature = "arbitrary")]
+/// Made from the next `N` bytes of the input.
+impl<'a, T, const N: usize> arbitrary::Arbitrary<'a> for PublicData<T, N> {
+	fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+		PublicData::from_slice(u.bytes(N)?).map_err(|_| arbitrary::Error::IncorrectFormat)
+	}
+
+	fn size_hint(_depth: usize) -> (usize, Option<usize>) { (N, Some(N)) }
+}
+
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	/// Marker of the types under test.
+	enum TestTag {}
+
+	mod test_secret_key {
+		use super::*;
+
+		#[test]
+		fn test_from_slice_size() {
+			assert!(SecretKey::<TestTag, 32>::from_slice(&[0u8; 32]).is_ok());
+			assert!(SecretKey::<TestTag, 32>::from_slice(&[0u8; 0]).is_err());
+			assert!(SecretKey::<TestTag, 32>::from_slice(&[0u8; 31]).is_err());
+			assert!(SecretKey::<TestTag, 32>::from_slice(&[0u8; 33]).is_err());
+			assert!(SecretKey::<TestTag, 16>::from_slice(&[0u8; 16]).is_ok());
+			assert!(SecretKey::<TestTag, 16>::from_slice(&[0u8; 32]).is_err());
+		}
+
+		#[test]
+		fn test_unprotected_as_bytes_and_get_length() {
+			let test = SecretKey::<TestTag, 32>::from_slice(&[1u8; 32]).unwrap();
+			assert_eq!(test.unprotected_as_bytes(), &[1u8; 32]);
+			assert_eq!(test.get_length(), 32);
+			assert_eq!(test.unprotected_as_bytes().len(), test.get_length());
+		}
+
+		#[test]
+		fn test_try_from_and_from_array() {
+			assert!(SecretKey::<TestTag, 32>::try_from(&[0u8; 32][..]).is_ok());
+			assert!(SecretKey::<TestTag, 32>::try_from(&[0u8; 33][..]).is_err());
+			assert!(
+				SecretKey::<TestTag, 32>::from([1u8; 32])
+					== SecretKey::from_slice(&[1u8; 32]).unwrap()
+			);
+		}
+
+		#[test]
+		fn test_ct_partialeq() {
+			let a = SecretKey::<TestTag, 32>::from([1u8; 32]);
+			let b = SecretKey::<TestTag, 32>::from([2u8; 32]);
+			assert!(a == SecretKey::from([1u8; 32]));
+			assert!(a != b);
+		}
+
+		#[test]
+		fn test_duplicate() {
+			let test = SecretKey::<TestTag, 32>::from([1u8; 32]);
+			let duplicate = test.duplicate();
+			assert!(test == duplicate);
+			assert_eq!(test.unprotected_as_bytes(), duplicate.unprotected_as_bytes());
+		}
+
+		#[test]
+		#[cfg(feature = "secrecy_interop")]
+		fn test_zeroize() {
+			use zeroize::Zeroize;
+			let mut test = SecretKey::<TestTag, 32>::from([1u8; 32]);
+			test.zeroize();
+			assert_eq!(test.unprotected_as_bytes(), &[0u8; 32]);
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		fn test_generate() {
+			let test_zero = SecretKey::<TestTag, 32>::from([0u8; 32]);
+			// A random one should never be all 0's.
+			let test_rand = SecretKey::<TestTag, 32>::generate().unwrap();
+			assert!(test_zero != test_rand);
+			assert_eq!(test_rand.get_length(), 32);
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		// format! is only available with std
+		fn test_omitted_debug() {
+			let secret = format!("{:?}", [0u8; 32].as_ref());
+			let test_debug_contents = format!("{:?}", SecretKey::<TestTag, 32>::from([0u8; 32]));
+			assert_eq!(test_debug_contents.contains(&secret), false);
+		}
+	}
+
+	mod test_public_data {
+		use super::*;
+
+		#[test]
+		fn test_from_slice_size() {
+			assert!(PublicData::<TestTag, 12>::from_slice(&[0u8; 12]).is_ok());
+			assert!(PublicData::<TestTag, 12>::from_slice(&[0u8; 0]).is_err());
+			assert!(PublicData::<TestTag, 12>::from_slice(&[0u8; 11]).is_err());
+			assert!(PublicData::<TestTag, 12>::from_slice(&[0u8; 13]).is_err());
+			assert!(PublicData::<TestTag, 64>::from_slice(&[0u8; 64]).is_ok());
+		}
+
+		#[test]
+		fn test_as_bytes_and_get_length() {
+			let test = PublicData::<TestTag, 12>::from_slice(&[1u8; 12]).unwrap();
+			assert_eq!(test.as_bytes(), &[1u8; 12]);
+			assert_eq!(test.get_length(), 12);
+			assert_eq!(test.as_bytes().len(), test.get_length());
+		}
+
+		#[test]
+		fn test_try_from_and_array_conversions() {
+			assert!(PublicData::<TestTag, 12>::try_from(&[0u8; 12][..]).is_ok());
+			assert!(PublicData::<TestTag, 12>::try_from(&[0u8; 13][..]).is_err());
+
+			let test = PublicData::<TestTag, 12>::from([1u8; 12]);
+			assert!(test == PublicData::from_slice(&[1u8; 12]).unwrap());
+			let bytes: [u8; 12] = test.into();
+			assert_eq!(bytes, [1u8; 12]);
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		fn test_ord_and_hash() {
+			use std::collections::{BTreeSet, HashSet};
+			let a = PublicData::<TestTag, 12>::from([0u8; 12]);
+			let b = PublicData::<TestTag, 12>::from([1u8; 12]);
+			assert!(a < b);
+			assert_eq!(a.cmp(&PublicData::from([0u8; 12])), core::cmp::Ordering::Equal);
+
+			let mut hash_set = HashSet::new();
+			assert!(hash_set.insert(a));
+			assert!(!hash_set.insert(PublicData::from([0u8; 12])));
+			assert!(hash_set.insert(b));
+
+			let mut tree_set = BTreeSet::new();
+			tree_set.insert(b);
+			tree_set.insert(a);
+			assert_eq!(tree_set.iter().next(), Some(&a));
+		}
+
+		#[test]
+		#[cfg(feature = "safe_api")]
+		fn test_display_from_str_roundtrip() {
+			let test = PublicData::<TestTag, 4>::from([0xde, 0xad, 0xbe, 0xef]);
+			assert_eq!(test.to_string(), "deadbeef");
+			assert!("deadbeef".parse::<PublicData<TestTag, 4>>().unwrap() == test);
+			assert!("DEADBEEF".parse::<PublicData<TestTag, 4>>().unwrap() == test);
+		}
+
+		#[test]
+		fn test_from_str_err() {
+			use core::str::FromStr;
+			assert!(PublicData::<TestTag, 4>::from_str("deadbe").is_err());
+			assert!(PublicData::<TestTag, 4>::from_str("deadbeef00").is_err());
+			assert!(PublicData::<TestTag, 4>::from_str("deadbeeg").is_err());
+			assert!(PublicData::<TestTag, 4>::from_str("").is_err());
+		}
+
+		#[test]
+		#[cfg(feature = "arbitrary")]
+		fn test_arbitrary() {
+			use arbitrary::{Arbitrary, Unstructured};
+			let mut u = Unstructured::new(&[0xde, 0xad, 0xbe, 0xef, 0x00]);
+			let expected = PublicData::from([0xde, 0xad, 0xbe, 0xef]);
+			assert!(PublicData::<TestTag, 4>::arbitrary(&mut u).unwrap() == expected);
+			assert!(PublicData::<TestTag, 4>::arbitrary(&mut u).is_err());
+		}
+	}
+}
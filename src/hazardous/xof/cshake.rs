@@ -44,12 +44,14 @@
 //! # Errors:
 //! An error will be returned if:
 //! - The length of `dst_out` is zero.
-//! - The length of `dst_out` is greater than 65536.
+//! - The length of `dst_out` is greater than 65536 when calling `finalize()`.
 //! - `finalize()` is called twice in a row without calling `reset()` in
 //!   between.
-//! - `update()` is called after `finalize()` without a `reset()` in between.
+//! - `update()` is called after `finalize()` or `squeeze()` without a
+//!   `reset()` in between.
+//! - `finalize()` is called after `squeeze()`, or `squeeze()` after
+//!   `finalize()`, without a `reset()` in between.
 //! - Both `name` and `custom` are empty.
-//! - If the length of either `name` or `custom` is greater than 65536.
 //!
 //! The reason that `name` and `custom` cannot both be empty is because that
 //! would be equivalent to a SHAKE call.
@@ -70,27 +72,81 @@
 //! hash.update(input).unwrap();
 //!
 //! hash.finalize(&mut out).unwrap();
+//!
+//! // Using the one-shot function
+//! let mut out_one_shot = [0u8; 64];
+//! cshake::cshake256(custom, None, input, &mut out_one_shot).unwrap();
+//!
+//! assert_eq!(out[..], out_one_shot[..]);
+//!
+//! // Reading the output in several parts, which has no length limit
+//! let mut hash = cshake::init(custom, None).unwrap();
+//! hash.update(input).unwrap();
+//!
+//! let mut first = [0u8; 32];
+//! let mut second = [0u8; 32];
+//! hash.squeeze(&mut first).unwrap();
+//! hash.squeeze(&mut second).unwrap();
+//!
+//! assert_eq!(first[..], out[..32]);
+//! assert_eq!(second[..], out[32..]);
 //! ```
 extern crate core;
 
 use self::core::mem;
 use crate::errors::{FinalizationCryptoError, UnknownCryptoError};
-use tiny_keccak::Keccak;
+use tiny_keccak::{Keccak, XofReader};
 
 #[must_use]
-#[derive(Clone)]
 /// cSHAKE256 streaming state.
 pub struct CShake {
 	setup_hasher: Keccak,
 	hasher: Keccak,
+	// The absorbed state `reader` was created from and the number of bytes
+	// squeezed from it so far. `XofReader` is not `Clone`, so these are used
+	// to rebuild it.
+	absorbed: Option<Keccak>,
+	squeezed: u64,
+	reader: Option<XofReader>,
 	is_finalized: bool,
 }
 
+impl Clone for CShake {
+	fn clone(&self) -> CShake {
+		let reader = match self.absorbed {
+			Some(ref absorbed) => {
+				let mut reader = absorbed.clone().xof();
+				let mut skip = [0u8; 136];
+				let mut remaining = self.squeezed;
+				while remaining > 0 {
+					let len = core::cmp::min(remaining, 136) as usize;
+					reader.squeeze(&mut skip[..len]);
+					remaining -= len as u64;
+				}
+
+				Some(reader)
+			}
+			None => None,
+		};
+
+		CShake {
+			setup_hasher: self.setup_hasher.clone(),
+			hasher: self.hasher.clone(),
+			absorbed: self.absorbed.clone(),
+			squeezed: self.squeezed,
+			reader,
+			is_finalized: self.is_finalized,
+		}
+	}
+}
+
 impl core::fmt::Debug for CShake {
 	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
 		write!(
 			f,
-			"CShake {{ setup_hasher: Unknown, hasher: Unknown, is_finalized: {:?} }}",
+			"CShake {{ setup_hasher: Unknown, hasher: Unknown, reader: Unknown, is_squeezing: \
+			 {:?}, is_finalized: {:?} }}",
+			self.reader.is_some(),
 			self.is_finalized
 		)
 	}
@@ -103,9 +159,15 @@ impl CShake {
 			return Err(UnknownCryptoError);
 		}
 
-		if name.len() > 65536 || custom.len() > 65536 {
-			return Err(UnknownCryptoError);
-		}
+		// The bit lengths of `name` and `custom` are left encoded as a u64, which
+		// covers all lengths that can be represented by a usize on supported
+		// targets. The spec allows up to 2^2040 - 1 bits.
+		let name_bits = (name.len() as u64)
+			.checked_mul(8)
+			.ok_or(UnknownCryptoError)?;
+		let custom_bits = (custom.len() as u64)
+			.checked_mul(8)
+			.ok_or(UnknownCryptoError)?;
 
 		// Only append the left encoded rate, not the rate itself as with `name` and
 		// `custom`
@@ -113,11 +175,11 @@ impl CShake {
 		self.hasher.update(&encoded[(offset - 1)..]);
 
 		// The below two calls are equivalent to encode_string() from the spec
-		let (encoded, offset) = left_encode(name.len() as u64 * 8);
+		let (encoded, offset) = left_encode(name_bits);
 		self.hasher.update(&encoded[(offset - 1)..]);
 		self.hasher.update(&name);
 
-		let (encoded, offset) = left_encode(custom.len() as u64 * 8);
+		let (encoded, offset) = left_encode(custom_bits);
 		self.hasher.update(&encoded[(offset - 1)..]);
 		self.hasher.update(custom);
 
@@ -131,13 +193,16 @@ impl CShake {
 	/// Reset to `init()` state.
 	pub fn reset(&mut self) {
 		self.hasher = self.setup_hasher.clone();
+		self.absorbed = None;
+		self.squeezed = 0;
+		self.reader = None;
 		self.is_finalized = false;
 	}
 
 	#[must_use]
 	/// Set `data`. Can be called repeatedly.
 	pub fn update(&mut self, data: &[u8]) -> Result<(), FinalizationCryptoError> {
-		if self.is_finalized {
+		if self.is_finalized || self.reader.is_some() {
 			Err(FinalizationCryptoError)
 		} else {
 			self.hasher.update(data);
@@ -145,10 +210,38 @@ impl CShake {
 		}
 	}
 
+	#[must_use]
+	/// Fill `dst_out` with the next bytes of cSHAKE output. This can be called
+	/// repeatedly, and the output of consecutive calls is the same as that of
+	/// a single call with their combined length.
+	pub fn squeeze(&mut self, dst_out: &mut [u8]) -> Result<(), FinalizationCryptoError> {
+		if self.is_finalized {
+			return Err(FinalizationCryptoError);
+		}
+		if dst_out.is_empty() {
+			return Err(FinalizationCryptoError);
+		}
+
+		if self.reader.is_none() {
+			let mut hasher_new = Keccak::new(136, 0x04);
+			mem::swap(&mut self.hasher, &mut hasher_new);
+			self.absorbed = Some(hasher_new.clone());
+			self.reader = Some(hasher_new.xof());
+		}
+
+		// .unwrap() cannot panic since the reader was set above
+		self.reader.as_mut().unwrap().squeeze(dst_out);
+		self.squeezed += dst_out.len() as u64;
+
+		Ok(())
+	}
+
 	#[must_use]
 	/// Return a cSHAKE hash and copy into `dst_out`.
+	///
+	/// Unlike `squeeze()`, this can only be called once before `reset()`.
 	pub fn finalize(&mut self, dst_out: &mut [u8]) -> Result<(), FinalizationCryptoError> {
-		if self.is_finalized {
+		if self.is_finalized || self.reader.is_some() {
 			return Err(FinalizationCryptoError);
 		}
 
@@ -167,6 +260,20 @@ impl CShake {
 	}
 }
 
+#[cfg(feature = "safe_api")]
+/// Update the state with the data written. This makes it possible to use
+/// `std::io::copy()` to process a reader, such as a file.
+///
+/// An error is returned if writing to a finalized state.
+impl std::io::Write for CShake {
+	fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+		self.update(data)?;
+		Ok(data.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
 #[must_use]
 /// Initialize a `CShake` struct.
 pub fn init(custom: &[u8], name: Option<&[u8]>) -> Result<CShake, UnknownCryptoError> {
@@ -181,6 +288,9 @@ pub fn init(custom: &[u8], name: Option<&[u8]>) -> Result<CShake, UnknownCryptoE
 	let mut hash = CShake {
 		setup_hasher: Keccak::new(136, 0x04),
 		hasher: Keccak::new(136, 0x04),
+		absorbed: None,
+		squeezed: 0,
+		reader: None,
 		is_finalized: false,
 	};
 
@@ -189,9 +299,23 @@ pub fn init(custom: &[u8], name: Option<&[u8]>) -> Result<CShake, UnknownCryptoE
 	Ok(hash)
 }
 
+#[must_use]
+/// One-shot function for writing the cSHAKE256 hash of `data` to `dst_out`.
+pub fn cshake256(
+	custom: &[u8],
+	name: Option<&[u8]>,
+	data: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	let mut hash = init(custom, name)?;
+	hash.update(data)?;
+
+	Ok(hash.finalize(dst_out)?)
+}
+
 #[must_use]
 /// The left_encode function as specified in the NIST SP 800-185.
-fn left_encode(x: u64) -> ([u8; 9], usize) {
+pub(crate) fn left_encode(x: u64) -> ([u8; 9], usize) {
 	let mut input = [0u8; 9];
 	let offset: usize = if x == 0 {
 		8
@@ -334,28 +458,62 @@ mod public {
 		}
 
 		#[test]
-		fn err_on_name_max_length() {
+		fn name_above_65536_ok() {
 			let custom = b"";
 			let name = [0u8; 65537];
 
-			assert!(init(custom, Some(&name)).is_err());
+			assert!(init(custom, Some(&name)).is_ok());
 		}
 
 		#[test]
-		fn err_on_n_c_max_length() {
+		fn n_c_above_65536_ok() {
 			let custom = [0u8; 65537];
 			let name = [0u8; 65537];
 
-			assert!(init(&custom, Some(&name)).is_err());
+			assert!(init(&custom, Some(&name)).is_ok());
 		}
 
 		#[test]
-		fn err_on_custom_max_length() {
+		fn custom_above_65536_ok() {
 			let custom = [0u8; 65537];
 			let name = [0u8; 0];
 
-			assert!(init(&custom, Some(&name)).is_err());
-			assert!(init(&custom, None).is_err());
+			assert!(init(&custom, Some(&name)).is_ok());
+			assert!(init(&custom, None).is_ok());
+		}
+
+		#[test]
+		#[cfg(target_endian = "little")]
+		fn long_custom_result_ok() {
+			let input = b"\x00\x01\x02\x03";
+			let custom = [0xa5u8; 70000];
+			let mut out = [0u8; 32];
+
+			let mut cshake = init(&custom, None).unwrap();
+			cshake.update(input).unwrap();
+			cshake.finalize(&mut out).unwrap();
+
+			let expected = b"\x6C\xE4\xCB\xBF\x09\xFA\x41\xD5\x92\x02\x7D\x5B\x97\x16\x79\xE4\
+			                 \x1D\x95\x19\xA6\x8C\xC9\xDD\x89\xD8\x48\x60\x7E\x6C\x16\x40\x7B";
+
+			assert_eq!(out.as_ref(), expected.as_ref());
+		}
+
+		#[test]
+		#[cfg(target_endian = "little")]
+		fn long_name_result_ok() {
+			let input = b"\x00\x01\x02\x03";
+			let name = [0x5au8; 70000];
+			let mut out = [0u8; 32];
+
+			let mut cshake = init(b"", Some(&name)).unwrap();
+			cshake.update(input).unwrap();
+			cshake.finalize(&mut out).unwrap();
+
+			let expected = b"\x3F\x13\x85\xD3\x06\x5B\x94\xF8\x8B\x15\x74\x9B\xC9\x4F\xF4\x63\
+			                 \x71\x92\x00\xBA\xFD\x9B\xA0\xE1\xFD\xE6\x97\x06\x6E\x7F\x4D\x4B";
+
+			assert_eq!(out.as_ref(), expected.as_ref());
 		}
 	}
 
@@ -484,6 +642,134 @@ mod public {
 
 	}
 
+	mod test_squeeze {
+		use super::*;
+
+		#[test]
+		fn test_same_as_finalize() {
+			let mut out = [0u8; 64];
+			let mut state = init(b"Email signature", None).unwrap();
+			state.update(b"Data").unwrap();
+			state.finalize(&mut out).unwrap();
+
+			let mut out_squeeze = [0u8; 64];
+			let mut state = init(b"Email signature", None).unwrap();
+			state.update(b"Data").unwrap();
+			state.squeeze(&mut out_squeeze).unwrap();
+			assert_eq!(out[..], out_squeeze[..]);
+		}
+
+		#[test]
+		fn test_split_same_as_single() {
+			let mut single = [0u8; 700];
+			let mut state = init(b"Custom", None).unwrap();
+			state.update(b"Data").unwrap();
+			state.squeeze(&mut single).unwrap();
+
+			// Split at, inside and across the 136 byte rate.
+			for split in [1, 135, 136, 137, 272, 699].iter() {
+				let mut parts = [0u8; 700];
+				let mut state = init(b"Custom", None).unwrap();
+				state.update(b"Data").unwrap();
+				let (first, second) = parts.split_at_mut(*split);
+				state.squeeze(first).unwrap();
+				state.squeeze(second).unwrap();
+				assert_eq!(single[..], parts[..]);
+			}
+		}
+
+		#[test]
+		fn test_no_length_limit() {
+			let mut out = [0u8; 65537];
+			let mut state = init(b"Custom", None).unwrap();
+			state.squeeze(&mut out).unwrap();
+			state.squeeze(&mut out).unwrap();
+		}
+
+		#[test]
+		fn test_zero_length_err() {
+			let mut state = init(b"Custom", None).unwrap();
+			assert!(state.squeeze(&mut [0u8; 0]).is_err());
+		}
+
+		#[test]
+		fn test_update_after_squeeze_err() {
+			let mut state = init(b"Custom", None).unwrap();
+			state.squeeze(&mut [0u8; 32]).unwrap();
+			assert!(state.update(b"Data").is_err());
+			assert!(state.finalize(&mut [0u8; 32]).is_err());
+
+			state.reset();
+			state.update(b"Data").unwrap();
+			state.squeeze(&mut [0u8; 32]).unwrap();
+		}
+
+		#[test]
+		fn test_squeeze_after_finalize_err() {
+			let mut state = init(b"Custom", None).unwrap();
+			state.finalize(&mut [0u8; 32]).unwrap();
+			assert!(state.squeeze(&mut [0u8; 32]).is_err());
+		}
+
+		#[test]
+		fn test_reset_restarts_output() {
+			let mut first = [0u8; 32];
+			let mut state = init(b"Custom", None).unwrap();
+			state.squeeze(&mut first).unwrap();
+			state.reset();
+
+			let mut again = [0u8; 32];
+			state.squeeze(&mut again).unwrap();
+			assert_eq!(first, again);
+		}
+
+		#[test]
+		fn test_clone_mid_squeeze() {
+			let mut state = init(b"Custom", None).unwrap();
+			state.update(b"Data").unwrap();
+			// Leave the reader inside a block, past the first one.
+			state.squeeze(&mut [0u8; 200]).unwrap();
+
+			let mut cloned = state.clone();
+			let mut out = [0u8; 300];
+			let mut out_cloned = [0u8; 300];
+			state.squeeze(&mut out).unwrap();
+			cloned.squeeze(&mut out_cloned).unwrap();
+			assert_eq!(out[..], out_cloned[..]);
+
+			let mut single = [0u8; 500];
+			let mut state = init(b"Custom", None).unwrap();
+			state.update(b"Data").unwrap();
+			state.squeeze(&mut single).unwrap();
+			assert_eq!(single[200..], out_cloned[..]);
+		}
+	}
+
+	mod test_cshake256 {
+		use super::*;
+
+		#[test]
+		fn test_same_as_streaming() {
+			let mut out = [0u8; 64];
+			let mut state = init(b"Email signature", None).unwrap();
+			state.update(b"Data").unwrap();
+			state.finalize(&mut out).unwrap();
+
+			let mut out_one_shot = [0u8; 64];
+			cshake256(b"Email signature", None, b"Data", &mut out_one_shot).unwrap();
+			assert_eq!(out[..], out_one_shot[..]);
+		}
+
+		#[test]
+		fn test_params_err() {
+			let mut out = [0u8; 64];
+			assert!(cshake256(b"", None, b"Data", &mut out).is_err());
+			assert!(cshake256(b"Custom", None, b"Data", &mut [0u8; 0]).is_err());
+			assert!(cshake256(b"Custom", None, b"Data", &mut [0u8; 65537]).is_err());
+			assert!(cshake256(b"", Some(b"Name"), b"Data", &mut out).is_ok());
+		}
+	}
+
 	#[cfg(feature = "safe_api")]
 	// Mark safe_api because currently it only contains proptests and tests that
 	// need vec![].
@@ -598,6 +884,38 @@ mod public {
 			}
 		}
 	}
+
+	#[cfg(feature = "safe_api")]
+	mod test_io_write {
+		use super::*;
+		use std::io::{copy, Write};
+
+		#[test]
+		fn test_write_same_as_update() {
+			let data = [0u8; 356];
+
+			let mut state = init(b"", Some(b"Name")).unwrap();
+			let mut reader: &[u8] = &data;
+			assert_eq!(copy(&mut reader, &mut state).unwrap(), 356);
+			state.flush().unwrap();
+
+			let mut state_update = init(b"", Some(b"Name")).unwrap();
+			state_update.update(&data).unwrap();
+
+			let mut out = [0u8; 64];
+			let mut out_update = [0u8; 64];
+			state.finalize(&mut out).unwrap();
+			state_update.finalize(&mut out_update).unwrap();
+			assert_eq!(out.as_ref(), out_update.as_ref());
+		}
+
+		#[test]
+		fn test_write_after_finalize_err() {
+			let mut state = init(b"", Some(b"Name")).unwrap();
+			state.finalize(&mut [0u8; 64]).unwrap();
+			assert!(state.write(b"Some data").is_err());
+		}
+	}
 }
 
 // Testing private functions in the module.
@@ -617,6 +935,7 @@ mod private {
 			let (test_3, offset_3) = left_encode(0);
 			let (test_4, offset_4) = left_encode(64);
 			let (test_5, offset_5) = left_encode(u64::max_value());
+			let (test_6, offset_6) = left_encode(65537 * 8);
 
 			assert_eq!(&test_1[(offset_1 - 1)..], &[1, 32]);
 			assert_eq!(&test_2[(offset_2 - 1)..], &[1, 255]);
@@ -626,6 +945,7 @@ mod private {
 				&test_5[(offset_5 - 1)..],
 				&[8, 255, 255, 255, 255, 255, 255, 255, 255]
 			);
+			assert_eq!(&test_6[(offset_6 - 1)..], &[3, 8, 0, 8]);
 		}
 	}
 }
@@ -22,7 +22,11 @@
 
 /// These are the different types used by the high-level interface. They are not
 /// used in `hazardous`.
-use crate::errors::UnknownCryptoError;
+use crate::{
+	errors::{InputSizeCryptoError, UnknownCryptoError, ValidationCryptoError},
+	hazardous::{kdf::pbkdf2, types},
+};
+use std::time::{Duration, Instant};
 
 construct_secret_key_variable_size! {
 	/// A type to represent a secret key.
@@ -43,6 +47,66 @@ construct_secret_key_variable_size! {
 	(SecretKey, 32)
 }
 
+/// A short identifier of a `SecretKey`, returned by `SecretKey::id()`.
+///
+/// A `KeyId` is not secret. It is displayed as lowercase hex, and can be
+/// parsed back with `FromStr` or made from a slice with `from_slice()`.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is not 16 bytes.
+pub type KeyId = types::PublicData<types::tag::KeyId, KEY_ID_SIZE>;
+
+/// The size of a `KeyId`.
+pub(crate) const KEY_ID_SIZE: usize = 16;
+
+/// The domain separation string that `SecretKey::id()` hashes before the
+/// context and key.
+const KEY_ID_DOMAIN: &[u8] = b"orion-key-id-v1";
+
+impl SecretKey {
+	#[must_use]
+	/// Derive a short, non-reversible identifier of the key for `context`, so
+	/// that the key can be referred to in logs and headers without exposing
+	/// it.
+	///
+	/// The identifier is BLAKE2b-128 over a fixed domain separation string,
+	/// the length of `context`, `context` and the key. The same key has
+	/// unrelated identifiers in different contexts.
+	///
+	/// An exception will be thrown if `context` is empty.
+	pub fn id(&self, context: &str) -> Result<KeyId, UnknownCryptoError> {
+		use crate::hazardous::hash::blake2b;
+
+		if context.is_empty() {
+			return Err(UnknownCryptoError);
+		}
+
+		let mut state = blake2b::init(None, KEY_ID_SIZE)?;
+		state.update(KEY_ID_DOMAIN)?;
+		state.update(&(context.len() as u64).to_le_bytes())?;
+		state.update(context.as_bytes())?;
+		state.update(self.unprotected_as_bytes())?;
+
+		KeyId::from_slice(state.finalize()?.as_bytes())
+	}
+}
+
+/// A source of a `SecretKey` that is kept outside of the process, such as an
+/// OS keychain, a TPM or a PKCS#11 token.
+///
+/// APIs that accept a `KeyProvider` request the key once per call, and drop
+/// it, which zeroes it out, before returning. A `SecretKey` is itself a
+/// `KeyProvider`.
+pub trait KeyProvider {
+	/// Return the key, or an error if the provider cannot give it out.
+	fn provide_key(&self) -> Result<SecretKey, UnknownCryptoError>;
+}
+
+impl KeyProvider for SecretKey {
+	fn provide_key(&self) -> Result<SecretKey, UnknownCryptoError> { Ok(self.duplicate()) }
+}
+
 construct_salt_variable_size! {
 	/// A type to represent the `Salt` that PBKDF2 uses during key derivation.
 	///
@@ -62,18 +126,6 @@ construct_salt_variable_size! {
 	(Salt, 64)
 }
 
-construct_tag! {
-	/// A type to represent the `PasswordHash` that PBKDF2 returns when used for password hashing.
-	///
-	/// A `PasswordHash`'s first 64 bytes are the salt used to hash the password, and the last 64
-	/// bytes are the actual password hash.
-	///
-	/// # Exceptions:
-	/// An exception will be thrown if:
-	/// - `slice` is not 128 bytes.
-	(PasswordHash, 128)
-}
-
 construct_password_variable_size! {
 	/// A type to represent the `Password` that PBKDF2 hashes and uses for key derivation.
 	///
@@ -86,3 +138,605 @@ construct_password_variable_size! {
 	/// - `length` is not less than `u32::max_value()`.
 	(Password)
 }
+
+#[cfg(feature = "secrecy_interop")]
+impl core::convert::TryFrom<&secrecy::SecretString> for Password {
+	type Error = UnknownCryptoError;
+
+	/// Make a `Password` from a `secrecy::SecretString`. An exception will be
+	/// thrown if `secret` is empty.
+	fn try_from(secret: &secrecy::SecretString) -> Result<Password, UnknownCryptoError> {
+		use secrecy::ExposeSecret;
+		Password::from_slice(secret.expose_secret().as_bytes())
+	}
+}
+
+#[cfg(feature = "secrecy_interop")]
+impl core::convert::TryFrom<&secrecy::SecretVec<u8>> for Password {
+	type Error = UnknownCryptoError;
+
+	/// Make a `Password` from a `secrecy::SecretVec<u8>`. An exception will be
+	/// thrown if `secret` is empty.
+	fn try_from(secret: &secrecy::SecretVec<u8>) -> Result<Password, UnknownCryptoError> {
+		use secrecy::ExposeSecret;
+		Password::from_slice(secret.expose_secret())
+	}
+}
+
+#[cfg(feature = "secrecy_interop")]
+impl core::convert::TryFrom<&secrecy::SecretVec<u8>> for SecretKey {
+	type Error = UnknownCryptoError;
+
+	/// Make a `SecretKey` from a `secrecy::SecretVec<u8>`. An exception will be
+	/// thrown if `secret` is empty.
+	fn try_from(secret: &secrecy::SecretVec<u8>) -> Result<SecretKey, UnknownCryptoError> {
+		use secrecy::ExposeSecret;
+		SecretKey::from_slice(secret.expose_secret())
+	}
+}
+
+impl core::str::FromStr for Password {
+	type Err = UnknownCryptoError;
+
+	/// Make a `Password` from the UTF-8 bytes of `s`, without normalizing it.
+	/// An exception will be thrown if `s` is empty.
+	fn from_str(s: &str) -> Result<Password, UnknownCryptoError> {
+		Password::from_slice(s.as_bytes())
+	}
+}
+
+#[cfg(feature = "password_normalization")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The Unicode normalization form that `Password::from_str_normalized()`
+/// applies to a password.
+pub enum Normalization {
+	/// Use the UTF-8 bytes of the password as they are.
+	None,
+	/// Normalization Form C, canonical composition. Characters that can be
+	/// typed either precomposed or with combining marks, such as "é", become
+	/// the same.
+	Nfc,
+	/// Normalization Form KC, compatibility composition. In addition to NFC,
+	/// compatibility characters such as full-width letters and ligatures
+	/// become their plain equivalents. This is the form used by SASLprep and
+	/// PRECIS.
+	Nfkc,
+}
+
+#[cfg(feature = "password_normalization")]
+impl Password {
+	#[must_use]
+	/// Make a `Password` from `password`, normalized to `normalization`.
+	///
+	/// The intermediate buffer holding the normalized password is zeroed.
+	/// The normalization itself buffers a few characters at a time, in memory
+	/// that is not zeroed.
+	pub fn from_str_normalized(
+		password: &str,
+		normalization: Normalization,
+	) -> Result<Password, UnknownCryptoError> {
+		use unicode_normalization::UnicodeNormalization;
+
+		match normalization {
+			Normalization::None => Password::from_slice(password.as_bytes()),
+			Normalization::Nfc => password_from_chars(password.nfc(), password.len()),
+			Normalization::Nfkc => password_from_chars(password.nfkc(), password.len()),
+		}
+	}
+}
+
+#[cfg(feature = "password_normalization")]
+/// Make a `Password` from the UTF-8 encoding of `chars`. Every buffer that is
+/// outgrown, and the final one, is zeroed.
+pub(crate) fn password_from_chars(
+	chars: impl Iterator<Item = char>,
+	capacity: usize,
+) -> Result<Password, UnknownCryptoError> {
+	use zeroize::Zeroize;
+
+	let mut buf: Vec<u8> = Vec::with_capacity(capacity);
+	let mut utf8 = [0u8; 4];
+	for c in chars {
+		let len = c.encode_utf8(&mut utf8).len();
+		if buf.len() + len > buf.capacity() {
+			// Grow manually, so that the old allocation can be zeroed.
+			let mut grown = Vec::with_capacity(2 * buf.capacity() + len);
+			grown.extend_from_slice(&buf);
+			buf.zeroize();
+			buf = grown;
+		}
+		buf.extend_from_slice(&utf8[..len]);
+	}
+	utf8.zeroize();
+
+	let password = Password::from_slice(&buf);
+	buf.zeroize();
+
+	password
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The allowed lengths, in bytes, of an input to a high-level function.
+///
+/// Each module documents the limits of its inputs as constants, such as
+/// `aead::PLAINTEXT_LIMITS`. Functions that take `InputLimits` apply them in
+/// addition to the limits of the module, so they can only be made stricter.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `min_len` is greater than `max_len`.
+pub struct InputLimits {
+	min_len: usize,
+	max_len: usize,
+}
+
+impl InputLimits {
+	#[must_use]
+	/// Allow inputs of `min_len` up to and including `max_len` bytes.
+	pub fn new(min_len: usize, max_len: usize) -> Result<Self, UnknownCryptoError> {
+		if min_len > max_len {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(Self { min_len, max_len })
+	}
+
+	/// Make `InputLimits` in a constant. `min_len` must not be greater than
+	/// `max_len`.
+	pub(crate) const fn new_unchecked(min_len: usize, max_len: usize) -> Self {
+		Self { min_len, max_len }
+	}
+
+	/// Return the minimum allowed length.
+	pub fn min_len(&self) -> usize { self.min_len }
+
+	/// Return the maximum allowed length.
+	pub fn max_len(&self) -> usize { self.max_len }
+
+	#[must_use]
+	/// Check that `len` is within the limits.
+	pub fn check(&self, len: usize) -> Result<(), InputSizeCryptoError> {
+		if len < self.min_len || len > self.max_len {
+			return Err(InputSizeCryptoError);
+		}
+
+		Ok(())
+	}
+}
+
+/// The algorithm used when none is specified.
+pub(crate) const DEFAULT_ALGORITHM: Algorithm = Algorithm::Pbkdf2Sha512;
+
+/// The output length of `Params` when none is specified.
+pub(crate) const DEFAULT_OUTPUT_LENGTH: usize = 64;
+
+/// The iteration count that `Params::calibrate()` starts measuring with.
+const CALIBRATION_START_ITERATIONS: usize = 1000;
+
+/// The minimum duration of a measurement made by `Params::calibrate()`, before
+/// it is scaled to the target duration.
+const CALIBRATION_MIN_SAMPLE: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The algorithms that `kdf` and `pwhash` can derive keys and password hashes with.
+pub enum Algorithm {
+	/// PBKDF2-HMAC-SHA512, identified by `pbkdf2-sha512`.
+	Pbkdf2Sha512,
+}
+
+impl Algorithm {
+	/// Return the identifier of the algorithm in the PHC string format.
+	pub(crate) fn identifier(self) -> &'static str {
+		match self {
+			Algorithm::Pbkdf2Sha512 => "pbkdf2-sha512",
+		}
+	}
+
+	/// Return the algorithm with the PHC string format identifier `id`.
+	pub(crate) fn from_identifier(id: &str) -> Result<Algorithm, UnknownCryptoError> {
+		match id {
+			"pbkdf2-sha512" => Ok(Algorithm::Pbkdf2Sha512),
+			_ => Err(UnknownCryptoError),
+		}
+	}
+
+	/// Derive a key or password hash from `password` with this algorithm,
+	/// filling `dst`.
+	pub(crate) fn derive(
+		self,
+		password: &Password,
+		salt: &[u8],
+		iterations: usize,
+		dst: &mut [u8],
+	) -> Result<(), UnknownCryptoError> {
+		match self {
+			Algorithm::Pbkdf2Sha512 => pbkdf2::derive_key(
+				&pbkdf2::Password::from_slice(password.unprotected_as_bytes())?,
+				salt,
+				iterations,
+				dst,
+			),
+		}
+	}
+
+	/// Verify that `expected` is the key or password hash derived from
+	/// `password` with this algorithm.
+	pub(crate) fn verify(
+		self,
+		expected: &[u8],
+		password: &Password,
+		salt: &[u8],
+		iterations: usize,
+		dst: &mut [u8],
+	) -> Result<bool, ValidationCryptoError> {
+		match self {
+			Algorithm::Pbkdf2Sha512 => pbkdf2::verify(
+				expected,
+				&pbkdf2::Password::from_slice(password.unprotected_as_bytes())?,
+				salt,
+				iterations,
+				dst,
+			),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// A type to represent the parameters used for key derivation in `kdf` and
+/// password hashing in `pwhash`.
+///
+/// `Params` are validated when they are constructed, so a `Params` always holds
+/// a valid combination of algorithm, iteration count and output length.
+///
+/// # Presets:
+/// - `Params::interactive()`: 100000 iterations, for operations that a user is
+///   waiting on, such as logging in.
+/// - `Params::moderate()`: 300000 iterations.
+/// - `Params::sensitive()`: 1000000 iterations, for highly sensitive data that
+///   is derived or verified rarely.
+///
+/// All presets use the default algorithm, PBKDF2-HMAC-SHA512, and an output
+/// length of 64 bytes.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `iterations` is 0 or greater than `u32::max_value()`.
+/// - `output_length` is 0 or not less than `u32::max_value()`.
+/// - `target_duration` is zero, when calibrating.
+pub struct Params {
+	algorithm: Algorithm,
+	iterations: usize,
+	output_length: usize,
+}
+
+impl Params {
+	#[must_use]
+	/// Construct `Params` with the default algorithm, the given iteration
+	/// count and an output length of 64 bytes.
+	pub fn new(iterations: usize) -> Result<Params, UnknownCryptoError> {
+		Params::with_algorithm(DEFAULT_ALGORITHM, iterations)
+	}
+
+	#[must_use]
+	/// Construct `Params` with the given algorithm and iteration count, and an
+	/// output length of 64 bytes.
+	pub fn with_algorithm(
+		algorithm: Algorithm,
+		iterations: usize,
+	) -> Result<Params, UnknownCryptoError> {
+		if iterations < 1 || iterations > (u32::max_value() as usize) {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(Params {
+			algorithm,
+			iterations,
+			output_length: DEFAULT_OUTPUT_LENGTH,
+		})
+	}
+
+	#[must_use]
+	/// Return these `Params` with the given output length.
+	pub fn with_output_length(self, output_length: usize) -> Result<Params, UnknownCryptoError> {
+		if output_length < 1 || output_length >= (u32::max_value() as usize) {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(Params {
+			output_length,
+			..self
+		})
+	}
+
+	/// Recommended `Params` for interactive use.
+	pub fn interactive() -> Params {
+		Params {
+			algorithm: DEFAULT_ALGORITHM,
+			iterations: 100_000,
+			output_length: DEFAULT_OUTPUT_LENGTH,
+		}
+	}
+
+	/// Recommended `Params` for moderately sensitive use.
+	pub fn moderate() -> Params {
+		Params {
+			algorithm: DEFAULT_ALGORITHM,
+			iterations: 300_000,
+			output_length: DEFAULT_OUTPUT_LENGTH,
+		}
+	}
+
+	/// Recommended `Params` for highly sensitive use.
+	pub fn sensitive() -> Params {
+		Params {
+			algorithm: DEFAULT_ALGORITHM,
+			iterations: 1_000_000,
+			output_length: DEFAULT_OUTPUT_LENGTH,
+		}
+	}
+
+	/// Return the algorithm.
+	pub fn algorithm(&self) -> Algorithm { self.algorithm }
+
+	/// Return the iteration count.
+	pub fn iterations(&self) -> usize { self.iterations }
+
+	/// Return the output length.
+	pub fn output_length(&self) -> usize { self.output_length }
+
+	#[must_use]
+	/// Construct `Params` for the default algorithm, with an iteration count
+	/// that makes deriving a key take approximately `target_duration` on the
+	/// current machine. The memory usage of the returned parameters will not
+	/// exceed `max_memory` bytes.
+	///
+	/// PBKDF2 uses a small, constant amount of memory and has no memory cost
+	/// parameter, so `max_memory` currently does not affect the result.
+	///
+	/// The iteration count will be at most `u32::max_value()`.
+	pub fn calibrate(
+		target_duration: Duration,
+		max_memory: usize,
+	) -> Result<Params, UnknownCryptoError> {
+		if target_duration == Duration::from_secs(0) {
+			return Err(UnknownCryptoError);
+		}
+
+		let max_iterations = u32::max_value() as usize;
+		let password = Password::from_slice(&[0u8; 32])?;
+		let salt = [0u8; 16];
+		let mut buffer = [0u8; DEFAULT_OUTPUT_LENGTH];
+
+		match DEFAULT_ALGORITHM {
+			Algorithm::Pbkdf2Sha512 => {
+				// PBKDF2 has no memory cost, so there is nothing to restrict.
+				let _ = max_memory;
+			}
+		}
+
+		let mut iterations = CALIBRATION_START_ITERATIONS;
+		let elapsed = loop {
+			let start = Instant::now();
+			DEFAULT_ALGORITHM.derive(&password, &salt, iterations, &mut buffer)?;
+			let elapsed = start.elapsed();
+
+			if elapsed >= CALIBRATION_MIN_SAMPLE
+				|| elapsed >= target_duration
+				|| iterations >= max_iterations / 2
+			{
+				break elapsed;
+			}
+			iterations *= 2;
+		};
+
+		// Scale the measured iteration count linearly to the target duration.
+		let elapsed_nanos = core::cmp::max(elapsed.as_nanos(), 1);
+		let scaled = (iterations as u128) * target_duration.as_nanos() / elapsed_nanos;
+		let scaled = core::cmp::min(core::cmp::max(scaled, 1), max_iterations as u128);
+
+		Params::new(scaled as usize)
+	}
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Algorithm {
+	fn arbitrary(_u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+		Ok(Algorithm::Pbkdf2Sha512)
+	}
+
+	fn size_hint(_depth: usize) -> (usize, Option<usize>) { (0, Some(0)) }
+}
+
+#[cfg(feature = "arbitrary")]
+/// Only valid `Params` are made. The iteration count and output length can be
+/// up to `u32::max_value()`, so fuzz targets that derive keys should bound
+/// them.
+impl<'a> arbitrary::Arbitrary<'a> for Params {
+	fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+		let algorithm: Algorithm = u.arbitrary()?;
+		let iterations = u.int_in_range(1..=u32::max_value())? as usize;
+		let output_length = u.int_in_range(1..=(u32::max_value() - 1))? as usize;
+
+		Params::with_algorithm(algorithm, iterations)
+			.and_then(|params| params.with_output_length(output_length))
+			.map_err(|_| arbitrary::Error::IncorrectFormat)
+	}
+
+	fn size_hint(_depth: usize) -> (usize, Option<usize>) { (8, Some(8)) }
+}
+
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_input_limits {
+		use super::*;
+
+		#[test]
+		fn test_new() {
+			let limits = InputLimits::new(1, 10).unwrap();
+			assert_eq!(limits.min_len(), 1);
+			assert_eq!(limits.max_len(), 10);
+			assert!(InputLimits::new(5, 5).is_ok());
+			assert!(InputLimits::new(6, 5).is_err());
+		}
+
+		#[test]
+		fn test_check() {
+			let limits = InputLimits::new(1, 10).unwrap();
+			assert_eq!(limits.check(0), Err(InputSizeCryptoError));
+			assert!(limits.check(1).is_ok());
+			assert!(limits.check(10).is_ok());
+			assert_eq!(limits.check(11), Err(InputSizeCryptoError));
+
+			let unbounded = InputLimits::new(0, usize::max_value()).unwrap();
+			assert!(unbounded.check(0).is_ok());
+			assert!(unbounded.check(usize::max_value()).is_ok());
+		}
+	}
+
+	mod test_password_from_str {
+		use super::*;
+		use core::str::FromStr;
+
+		#[test]
+		fn test_from_str() {
+			let password = Password::from_str("Secret password").unwrap();
+			assert_eq!(password.unprotected_as_bytes(), b"Secret password");
+			assert!(Password::from_str("").is_err());
+			// Not normalized.
+			let decomposed = Password::from_str("e\u{301}").unwrap();
+			assert_eq!(decomposed.unprotected_as_bytes(), "e\u{301}".as_bytes());
+		}
+
+		#[test]
+		#[cfg(feature = "password_normalization")]
+		fn test_from_str_normalized() {
+			let precomposed = Password::from_str("caf\u{e9}").unwrap();
+			let decomposed = "cafe\u{301}";
+
+			let none = Password::from_str_normalized(decomposed, Normalization::None).unwrap();
+			assert!(none != precomposed);
+			let nfc = Password::from_str_normalized(decomposed, Normalization::Nfc).unwrap();
+			assert!(nfc == precomposed);
+			let nfkc = Password::from_str_normalized(decomposed, Normalization::Nfkc).unwrap();
+			assert!(nfkc == precomposed);
+
+			// Compatibility characters are only mapped by NFKC.
+			let ligature = "\u{fb01}le";
+			let nfc = Password::from_str_normalized(ligature, Normalization::Nfc).unwrap();
+			assert_eq!(nfc.unprotected_as_bytes(), ligature.as_bytes());
+			let nfkc = Password::from_str_normalized(ligature, Normalization::Nfkc).unwrap();
+			assert_eq!(nfkc.unprotected_as_bytes(), b"file");
+
+			// U+FDFA expands to 18 characters under NFKC, outgrowing the buffer.
+			let expanded = Password::from_str_normalized("\u{fdfa}", Normalization::Nfkc).unwrap();
+			assert!(expanded.get_length() > "\u{fdfa}".len());
+
+			assert!(Password::from_str_normalized("", Normalization::Nfc).is_err());
+		}
+	}
+
+	mod test_secret_key_id {
+		use super::*;
+
+		#[test]
+		fn test_known_id() {
+			let key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			assert_eq!(
+				key.id("orion-test").unwrap().to_string(),
+				"0a9f18887555e9b6011baa7274aca2dc"
+			);
+		}
+
+		#[test]
+		fn test_id_is_deterministic() {
+			let key = SecretKey::default();
+			assert!(key.id("context").unwrap() == key.id("context").unwrap());
+		}
+
+		#[test]
+		fn test_id_differs() {
+			let key = SecretKey::default();
+			assert!(key.id("context").unwrap() != key.id("other context").unwrap());
+			assert!(key.id("context").unwrap() != SecretKey::default().id("context").unwrap());
+		}
+
+		#[test]
+		fn test_empty_context_err() {
+			assert!(SecretKey::default().id("").is_err());
+		}
+	}
+
+	#[cfg(feature = "arbitrary")]
+	mod test_arbitrary {
+		use super::*;
+		use arbitrary::{Arbitrary, Unstructured};
+
+		#[test]
+		fn test_params_valid() {
+			for byte in [0x00u8, 0x01, 0x7f, 0xff].iter() {
+				let data = [*byte; 8];
+				let params = Params::arbitrary(&mut Unstructured::new(&data)).unwrap();
+				assert!(params.iterations() >= 1);
+				assert!(params.iterations() <= u32::max_value() as usize);
+				assert!(params.output_length() >= 1);
+				assert!(params.output_length() < u32::max_value() as usize);
+				assert_eq!(params.algorithm(), Algorithm::Pbkdf2Sha512);
+			}
+		}
+
+		#[test]
+		fn test_params_diff_input() {
+			let a = Params::arbitrary(&mut Unstructured::new(&[0x00; 8])).unwrap();
+			let b = Params::arbitrary(&mut Unstructured::new(&[0x01; 8])).unwrap();
+			assert_ne!(a, b);
+		}
+	}
+
+	#[cfg(feature = "secrecy_interop")]
+	mod test_secrecy_try_from {
+		use super::*;
+		use core::convert::TryFrom;
+		use secrecy::{SecretString, SecretVec};
+
+		#[test]
+		fn test_password_from_secret_string() {
+			let secret = SecretString::new("Secret password".to_string());
+			let password = Password::try_from(&secret).unwrap();
+			assert_eq!(password.unprotected_as_bytes(), b"Secret password");
+
+			assert!(Password::try_from(&SecretString::new(String::new())).is_err());
+		}
+
+		#[test]
+		fn test_password_and_secret_key_from_secret_vec() {
+			let secret = SecretVec::new(vec![1u8; 32]);
+			let password = Password::try_from(&secret).unwrap();
+			let secret_key = SecretKey::try_from(&secret).unwrap();
+			assert_eq!(password.unprotected_as_bytes(), &[1u8; 32][..]);
+			assert_eq!(secret_key.unprotected_as_bytes(), &[1u8; 32][..]);
+
+			assert!(Password::try_from(&SecretVec::new(Vec::new())).is_err());
+			assert!(SecretKey::try_from(&SecretVec::new(Vec::new())).is_err());
+		}
+	}
+
+	#[cfg(feature = "secrecy_interop")]
+	mod test_zeroize {
+		use super::*;
+		use zeroize::Zeroize;
+
+		#[test]
+		fn test_zeroize_secret_types() {
+			let mut secret_key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+			secret_key.zeroize();
+			assert!(secret_key.unprotected_as_bytes().iter().all(|b| *b == 0));
+
+			let mut password = Password::from_slice(&[1u8; 32]).unwrap();
+			password.zeroize();
+			assert!(password.unprotected_as_bytes().iter().all(|b| *b == 0));
+		}
+	}
+}
@@ -0,0 +1,341 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Adapters for the RustCrypto traits.
+//!
+//! # Use case:
+//! `orion::interop` can be used to plug orion into libraries that are generic
+//! over the [`digest`](https://docs.rs/digest) and [`aead`](https://docs.rs/aead)
+//! traits from the RustCrypto ecosystem.
+//!
+//! # About:
+//! - `Sha512` implements `digest::Digest` through `Update`, `FixedOutputDirty`
+//!   and `Reset`.
+//! - `Blake2b` implements `digest::VariableOutput`, `Update` and `Reset` using
+//!   unkeyed BLAKE2b.
+//! - `ChaCha20Poly1305` and `XChaCha20Poly1305` implement `aead::NewAead` and
+//!   `aead::AeadInPlace`, which in turn gives `aead::Aead`.
+//! - orion has no signature schemes, so nothing implements the `signature`
+//!   traits.
+//!
+//! This module is only available with the `interop` feature, which requires
+//! `safe_api`.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The plaintext or ciphertext passed to the AEADs is empty.
+//! - The received tag does not match the calculated tag when decrypting.
+//!
+//! # Security:
+//! - The security considerations of the wrapped primitives in `hazardous`
+//!   apply. It is critical that a nonce is never re-used with a given key.
+//!
+//! # Example:
+//! ```
+//! use aead::{generic_array::GenericArray, Aead, NewAead};
+//! use digest::Digest;
+//! use orion::interop::{Sha512, XChaCha20Poly1305};
+//!
+//! let hash = Sha512::digest(b"Some data");
+//!
+//! let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(&[0u8; 32]));
+//! let nonce = GenericArray::from_slice(&[0u8; 24]);
+//! let ciphertext = cipher.encrypt(nonce, b"Secret data".as_ref()).unwrap();
+//! assert_eq!(
+//! 	cipher.decrypt(nonce, ciphertext.as_ref()).unwrap(),
+//! 	b"Secret data"
+//! );
+//! ```
+
+use crate::hazardous::{
+	aead::{chacha20poly1305, xchacha20poly1305},
+	constants::POLY1305_OUTSIZE,
+	hash::{blake2b, sha512},
+	stream::chacha20,
+};
+use aead::{
+	consts::{U0, U12, U16, U24, U32},
+	generic_array::GenericArray,
+	AeadInPlace, NewAead,
+};
+use digest::{consts::U128, BlockInput, FixedOutputDirty, InvalidOutputSize, Reset, Update};
+
+#[derive(Debug, Clone)]
+/// SHA512 for use through the `digest` traits.
+pub struct Sha512 {
+	state: sha512::Sha512,
+}
+
+impl Default for Sha512 {
+	fn default() -> Self {
+		Self {
+			state: sha512::init(),
+		}
+	}
+}
+
+impl BlockInput for Sha512 {
+	type BlockSize = U128;
+}
+
+impl Update for Sha512 {
+	fn update(&mut self, data: impl AsRef<[u8]>) {
+		// .unwrap() cannot panic since the state is never left finalized
+		self.state.update(data.as_ref()).unwrap();
+	}
+}
+
+impl FixedOutputDirty for Sha512 {
+	type OutputSize = digest::consts::U64;
+
+	fn finalize_into_dirty(&mut self, out: &mut GenericArray<u8, Self::OutputSize>) {
+		// .unwrap() cannot panic since the state is never left finalized
+		out.copy_from_slice(self.state.finalize().unwrap().as_bytes());
+	}
+}
+
+impl Reset for Sha512 {
+	fn reset(&mut self) { self.state.reset(); }
+}
+
+#[derive(Debug, Clone)]
+/// Unkeyed BLAKE2b for use through the `digest` traits.
+pub struct Blake2b {
+	state: blake2b::Blake2b,
+	size: usize,
+}
+
+impl digest::VariableOutput for Blake2b {
+	fn new(output_size: usize) -> Result<Self, InvalidOutputSize> {
+		match blake2b::init(None, output_size) {
+			Ok(state) => Ok(Self {
+				state,
+				size: output_size,
+			}),
+			Err(_) => Err(InvalidOutputSize),
+		}
+	}
+
+	fn output_size(&self) -> usize { self.size }
+
+	fn finalize_variable(mut self, f: impl FnOnce(&[u8])) {
+		// .unwrap() cannot panic since the state is never left finalized
+		f(self.state.finalize().unwrap().as_bytes());
+	}
+
+	fn finalize_variable_reset(&mut self, f: impl FnOnce(&[u8])) {
+		// .unwrap() cannot panic since the state is never left finalized
+		f(self.state.finalize().unwrap().as_bytes());
+		Reset::reset(self);
+	}
+}
+
+impl BlockInput for Blake2b {
+	type BlockSize = U128;
+}
+
+impl Update for Blake2b {
+	fn update(&mut self, data: impl AsRef<[u8]>) {
+		// .unwrap() cannot panic since the state is never left finalized
+		self.state.update(data.as_ref()).unwrap();
+	}
+}
+
+impl Reset for Blake2b {
+	fn reset(&mut self) {
+		// .unwrap() cannot panic since the state is always unkeyed
+		self.state.reset(None).unwrap();
+	}
+}
+
+/// Implement `NewAead` and `AeadInPlace` for an AEAD from `hazardous::aead`,
+/// by sealing/opening into a temporary buffer.
+macro_rules! impl_aead_in_place (($name:ident, $module:ident, $nonce_size:ty) => (
+    impl NewAead for $name {
+        type KeySize = U32;
+
+        fn new(key: &GenericArray<u8, U32>) -> Self {
+            // .unwrap() cannot panic since the key is always 32 bytes
+            Self { secret_key: chacha20::SecretKey::from_slice(key).unwrap() }
+        }
+    }
+
+    impl AeadInPlace for $name {
+        type NonceSize = $nonce_size;
+        type TagSize = U16;
+        type CiphertextOverhead = U0;
+
+        fn encrypt_in_place_detached(
+            &self,
+            nonce: &GenericArray<u8, Self::NonceSize>,
+            associated_data: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<GenericArray<u8, U16>, aead::Error> {
+            let nonce = $module::Nonce::from_slice(nonce).map_err(|_| aead::Error)?;
+            let mut dst_out = vec![0u8; buffer.len() + POLY1305_OUTSIZE];
+            $module::seal(&self.secret_key, &nonce, buffer, Some(associated_data), &mut dst_out)
+                .map_err(|_| aead::Error)?;
+
+            buffer.copy_from_slice(&dst_out[..buffer.len()]);
+            Ok(GenericArray::clone_from_slice(&dst_out[buffer.len()..]))
+        }
+
+        fn decrypt_in_place_detached(
+            &self,
+            nonce: &GenericArray<u8, Self::NonceSize>,
+            associated_data: &[u8],
+            buffer: &mut [u8],
+            tag: &GenericArray<u8, U16>,
+        ) -> Result<(), aead::Error> {
+            let nonce = $module::Nonce::from_slice(nonce).map_err(|_| aead::Error)?;
+            let mut ciphertext_with_tag = Vec::with_capacity(buffer.len() + POLY1305_OUTSIZE);
+            ciphertext_with_tag.extend_from_slice(buffer);
+            ciphertext_with_tag.extend_from_slice(tag);
+
+            $module::open(
+                &self.secret_key,
+                &nonce,
+                &ciphertext_with_tag,
+                Some(associated_data),
+                buffer,
+            )
+            .map_err(|_| aead::Error)
+        }
+    }
+));
+
+#[derive(Debug)]
+/// AEAD ChaCha20Poly1305 for use through the `aead` traits.
+pub struct ChaCha20Poly1305 {
+	secret_key: chacha20::SecretKey,
+}
+
+#[derive(Debug)]
+/// AEAD XChaCha20Poly1305 for use through the `aead` traits.
+pub struct XChaCha20Poly1305 {
+	secret_key: chacha20::SecretKey,
+}
+
+impl_aead_in_place!(ChaCha20Poly1305, chacha20poly1305, U12);
+impl_aead_in_place!(XChaCha20Poly1305, xchacha20poly1305, U24);
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use aead::Aead;
+	use digest::{Digest, VariableOutput};
+
+	mod test_sha512 {
+		use super::*;
+
+		#[test]
+		fn test_same_as_hazardous() {
+			let expected = sha512::digest(b"Some data").unwrap();
+			assert_eq!(Sha512::digest(b"Some data").as_slice(), expected.as_bytes());
+
+			let mut state = Sha512::new();
+			Digest::update(&mut state, b"Some ");
+			Digest::update(&mut state, b"data");
+			assert_eq!(state.finalize().as_slice(), expected.as_bytes());
+		}
+
+		#[test]
+		fn test_finalize_reset() {
+			let mut state = Sha512::new();
+			Digest::update(&mut state, b"Some data");
+			let first = state.finalize_reset();
+			Digest::update(&mut state, b"Some data");
+			assert_eq!(first, state.finalize_reset());
+		}
+	}
+
+	mod test_blake2b {
+		use super::*;
+
+		#[test]
+		fn test_invalid_size() {
+			assert!(<Blake2b as VariableOutput>::new(0).is_err());
+			assert!(<Blake2b as VariableOutput>::new(65).is_err());
+			assert!(<Blake2b as VariableOutput>::new(64).is_ok());
+		}
+
+		#[test]
+		fn test_same_as_hazardous() {
+			let expected = blake2b::Hasher::Blake2b256.digest(b"Some data").unwrap();
+
+			let mut state = <Blake2b as VariableOutput>::new(32).unwrap();
+			Update::update(&mut state, b"Some data");
+			state.finalize_variable_reset(|res| assert_eq!(res, expected.as_bytes()));
+			Update::update(&mut state, b"Some data");
+			state.finalize_variable(|res| assert_eq!(res, expected.as_bytes()));
+		}
+	}
+
+	mod test_aead {
+		use super::*;
+
+		#[test]
+		fn test_same_as_hazardous() {
+			let secret_key = chacha20::SecretKey::from_slice(&[1u8; 32]).unwrap();
+			let nonce = chacha20poly1305::Nonce::from_slice(&[2u8; 12]).unwrap();
+			let mut expected = [0u8; 16 + 16];
+			chacha20poly1305::seal(&secret_key, &nonce, &[0u8; 16], Some(b"ad"), &mut expected)
+				.unwrap();
+
+			let cipher = ChaCha20Poly1305::new(GenericArray::from_slice(&[1u8; 32]));
+			let ciphertext = cipher
+				.encrypt(
+					GenericArray::from_slice(&[2u8; 12]),
+					aead::Payload {
+						msg: &[0u8; 16],
+						aad: b"ad",
+					},
+				)
+				.unwrap();
+
+			assert_eq!(ciphertext.as_slice(), expected.as_ref());
+		}
+
+		#[test]
+		fn test_roundtrip_and_modified_ciphertext() {
+			let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(&[1u8; 32]));
+			let nonce = GenericArray::from_slice(&[2u8; 24]);
+			let mut ciphertext = cipher.encrypt(nonce, b"Secret data".as_ref()).unwrap();
+			assert_eq!(
+				cipher.decrypt(nonce, ciphertext.as_ref()).unwrap(),
+				b"Secret data"
+			);
+
+			ciphertext[0] ^= 1;
+			assert!(cipher.decrypt(nonce, ciphertext.as_ref()).is_err());
+		}
+
+		#[test]
+		fn test_empty_plaintext_err() {
+			let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(&[1u8; 32]));
+			assert!(cipher
+				.encrypt(GenericArray::from_slice(&[2u8; 24]), b"".as_ref())
+				.is_err());
+		}
+	}
+}
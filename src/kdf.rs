@@ -33,8 +33,17 @@
 //! VeraCrypt volume.
 //!
 //!
+//! `orion::kdf` can also derive independent subkeys from a single
+//! high-entropy master key, with `kdf::derive_subkey`. Each subkey is
+//! identified by a context string and an index, so that, for example, an
+//! application can derive separate keys for encryption and authentication
+//! without storing more than one key. This should not be used with passwords.
+//!
 //! # About:
-//! - Uses PBKDF2-HMAC-SHA512.
+//! - `derive_key` uses PBKDF2-HMAC-SHA512.
+//! - `derive_subkey` uses HKDF-HMAC-SHA512, with no salt and the `subkey_id`
+//!   encoded as little-endian 64-bit integer, followed by the `context`, as
+//!   info.
 //!
 //! # Parameters:
 //! - `password`: The low-entropy input key to be used in key derivation.
@@ -43,13 +52,25 @@
 //! - `iterations`: The number of iterations performed by PBKDF2, i.e. the cost
 //!   parameter.
 //! - `length`: The desired length of the derived key.
+//! - `params`: The algorithm, iteration count and length of the derived key.
+//!   `Params::interactive()`, `Params::moderate()` and `Params::sensitive()` are
+//!   recommended presets.
+//! - `master_key`: The high-entropy key to derive subkeys from.
+//! - `context`: A description of what the subkey is used for.
+//! - `subkey_id`: The index of the subkey within `context`.
 //!
 //! # Errors:
 //! An error will be returned if:
-//! - `iterations` is 0.
+//! - `iterations` is 0 or greater than `u32::max_value()`.
 //! - `length` is 0.
 //! - `length` is not less than `u32::max_value()`.
 //! - The `expected` does not match the derived key.
+//! - The length of `expected` does not match the output length of `params`.
+//!
+//! An error will be returned by `derive_subkey` if:
+//! - `master_key` is less than 32 bytes.
+//! - `context` is empty.
+//! - `length` is 0 or greater than 16320.
 //!
 //!
 //! # Security:
@@ -69,12 +90,27 @@
 //! let derived_key = kdf::derive_key(&user_password, &salt, 100000, 64).unwrap();
 //!
 //! assert!(kdf::derive_key_verify(&derived_key, &user_password, &salt, 100000).unwrap());
+//!
+//! let params = kdf::Params::interactive().with_output_length(32).unwrap();
+//! let derived_key = kdf::derive_key_with_params(&user_password, &salt, &params).unwrap();
+//!
+//! assert!(
+//! 	kdf::derive_key_verify_with_params(&derived_key, &user_password, &salt, &params).unwrap()
+//! );
+//!
+//! let master_key = kdf::SecretKey::default();
+//! let encryption_key = kdf::derive_subkey(&master_key, "file encryption", 0, 32).unwrap();
+//! let authentication_key = kdf::derive_subkey(&master_key, "file authentication", 0, 32).unwrap();
+//!
+//! assert!(encryption_key != authentication_key);
 //! ```
 
-pub use crate::hltypes::{Password, Salt, SecretKey};
+pub use crate::hltypes::{Algorithm, Params, Password, Salt, SecretKey};
+#[cfg(feature = "password_normalization")]
+pub use crate::hltypes::Normalization;
 use crate::{
 	errors::{UnknownCryptoError, ValidationCryptoError},
-	hazardous::kdf::pbkdf2,
+	hazardous::kdf::hkdf,
 };
 use zeroize::Zeroize;
 
@@ -86,18 +122,42 @@ pub fn derive_key(
 	iterations: usize,
 	length: usize,
 ) -> Result<SecretKey, UnknownCryptoError> {
-	if length < 1 || length >= (u32::max_value() as usize) {
-		return Err(UnknownCryptoError);
-	}
+	derive_key_with_params(
+		password,
+		salt,
+		&Params::new(iterations)?.with_output_length(length)?,
+	)
+}
 
-	let mut buffer = vec![0u8; length];
+#[must_use]
+/// Derive and verify a key using PBKDF2-HMAC-SHA512.
+pub fn derive_key_verify(
+	expected: &SecretKey,
+	password: &Password,
+	salt: &Salt,
+	iterations: usize,
+) -> Result<bool, ValidationCryptoError> {
+	derive_key_verify_with_params(
+		expected,
+		password,
+		salt,
+		&Params::new(iterations)?.with_output_length(expected.get_length())?,
+	)
+}
 
-	pbkdf2::derive_key(
-		&pbkdf2::Password::from_slice(password.unprotected_as_bytes())?,
-		&salt.as_bytes(),
-		iterations,
-		&mut buffer,
-	)?;
+#[must_use]
+/// Derive a key using the algorithm, iteration count and output length of
+/// `params`.
+pub fn derive_key_with_params(
+	password: &Password,
+	salt: &Salt,
+	params: &Params,
+) -> Result<SecretKey, UnknownCryptoError> {
+	let mut buffer = vec![0u8; params.output_length()];
+
+	params
+		.algorithm()
+		.derive(password, &salt.as_bytes(), params.iterations(), &mut buffer)?;
 
 	let dk = SecretKey::from_slice(&buffer)?;
 	buffer.zeroize();
@@ -106,20 +166,25 @@ pub fn derive_key(
 }
 
 #[must_use]
-/// Derive and verify a key using PBKDF2-HMAC-SHA512.
-pub fn derive_key_verify(
+/// Derive and verify a key using the algorithm, iteration count and output
+/// length of `params`.
+pub fn derive_key_verify_with_params(
 	expected: &SecretKey,
 	password: &Password,
 	salt: &Salt,
-	iterations: usize,
+	params: &Params,
 ) -> Result<bool, ValidationCryptoError> {
+	if expected.get_length() != params.output_length() {
+		return Err(ValidationCryptoError);
+	}
+
 	let mut buffer = vec![0u8; expected.get_length()];
 
-	let is_good = pbkdf2::verify(
+	let is_good = params.algorithm().verify(
 		&expected.unprotected_as_bytes(),
-		&pbkdf2::Password::from_slice(password.unprotected_as_bytes())?,
+		password,
 		&salt.as_bytes(),
-		iterations,
+		params.iterations(),
 		&mut buffer,
 	)?;
 
@@ -128,6 +193,39 @@ pub fn derive_key_verify(
 	Ok(is_good)
 }
 
+#[must_use]
+/// Derive a subkey from a master key, using HKDF-HMAC-SHA512.
+pub fn derive_subkey(
+	master_key: &SecretKey,
+	context: &str,
+	subkey_id: u64,
+	length: usize,
+) -> Result<SecretKey, UnknownCryptoError> {
+	if master_key.get_length() < 32 || context.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+	if length < 1 || length > 16320 {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut info = Vec::with_capacity(8 + context.len());
+	info.extend_from_slice(&subkey_id.to_le_bytes());
+	info.extend_from_slice(context.as_bytes());
+
+	let mut buffer = vec![0u8; length];
+	hkdf::derive_key(
+		&[0u8; 0],
+		master_key.unprotected_as_bytes(),
+		Some(&info),
+		&mut buffer,
+	)?;
+
+	let subkey = SecretKey::from_slice(&buffer)?;
+	buffer.zeroize();
+
+	Ok(subkey)
+}
+
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
@@ -166,6 +264,88 @@ mod public {
 		}
 	}
 
+	mod test_derive_key_with_params {
+		use super::*;
+
+		#[test]
+		fn test_derive_key_with_params_and_verify() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let salt = Salt::from_slice(&[0u8; 64]).unwrap();
+			let params = Params::new(100).unwrap().with_output_length(32).unwrap();
+
+			let dk = derive_key_with_params(&password, &salt, &params).unwrap();
+			assert_eq!(dk.get_length(), 32);
+			assert!(dk == derive_key(&password, &salt, 100, 32).unwrap());
+			assert!(derive_key_verify_with_params(&dk, &password, &salt, &params).unwrap());
+		}
+
+		#[test]
+		fn test_derive_key_verify_with_params_err() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let salt = Salt::from_slice(&[0u8; 64]).unwrap();
+			let params = Params::new(100).unwrap();
+
+			let dk = derive_key_with_params(&password, &salt, &params).unwrap();
+
+			let iterations = Params::new(50).unwrap();
+			assert!(derive_key_verify_with_params(&dk, &password, &salt, &iterations).is_err());
+			let length = params.with_output_length(32).unwrap();
+			assert!(derive_key_verify_with_params(&dk, &password, &salt, &length).is_err());
+		}
+	}
+
+	mod test_derive_subkey {
+		use super::*;
+
+		#[test]
+		fn test_derive_subkey_known() {
+			// Computed with HKDF-SHA512 from Python's cryptography package.
+			let master_key = SecretKey::from_slice(&(0u8..32).collect::<Vec<u8>>()).unwrap();
+			let expected = SecretKey::from_slice(
+				&hex::decode("b2c047118947f2b5ab702e9c374899d86a81d15f93d247c559fc14d8a852608d")
+					.unwrap(),
+			)
+			.unwrap();
+
+			assert!(derive_subkey(&master_key, "orion-test", 1, 32).unwrap() == expected);
+		}
+
+		#[test]
+		fn test_derive_subkey_deterministic() {
+			let master_key = SecretKey::default();
+
+			let first = derive_subkey(&master_key, "context", 0, 32).unwrap();
+			let second = derive_subkey(&master_key, "context", 0, 32).unwrap();
+			assert!(first == second);
+		}
+
+		#[test]
+		fn test_derive_subkey_independent() {
+			let master_key = SecretKey::default();
+
+			let subkey = derive_subkey(&master_key, "context", 0, 32).unwrap();
+			assert!(subkey != derive_subkey(&master_key, "context", 1, 32).unwrap());
+			assert!(subkey != derive_subkey(&master_key, "context2", 0, 32).unwrap());
+			assert!(subkey != derive_subkey(&SecretKey::default(), "context", 0, 32).unwrap());
+		}
+
+		#[test]
+		fn test_derive_subkey_bad_params() {
+			let master_key = SecretKey::default();
+
+			assert!(derive_subkey(&master_key, "", 0, 32).is_err());
+			assert!(derive_subkey(&master_key, "context", 0, 0).is_err());
+			assert!(derive_subkey(&master_key, "context", 0, 1).is_ok());
+			assert!(derive_subkey(&master_key, "context", 0, 16320).is_ok());
+			assert!(derive_subkey(&master_key, "context", 0, 16321).is_err());
+
+			let short_key = SecretKey::from_slice(&[0u8; 31]).unwrap();
+			assert!(derive_subkey(&short_key, "context", 0, 32).is_err());
+			let key = SecretKey::from_slice(&[0u8; 32]).unwrap();
+			assert!(derive_subkey(&key, "context", 0, 32).is_ok());
+		}
+	}
+
 	// Proptests. Only exectued when NOT testing no_std.
 	#[cfg(feature = "safe_api")]
 	mod proptest {
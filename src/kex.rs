@@ -0,0 +1,423 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Session keys from a Diffie-Hellman shared secret.
+//!
+//! # Use case:
+//! `orion::kex` can be used to turn the output of a Diffie-Hellman key
+//! exchange, such as X25519, into a pair of keys for encrypting traffic in
+//! each direction, for example with `orion::aead`.
+//!
+//! # About:
+//! - The session keys are derived with HKDF-HMAC-SHA512, with an empty salt
+//!   and the shared secret as input keying material.
+//! - The info is a fixed domain separation string followed by the client's
+//!   public key, the server's public key and the transcript. Each of these is
+//!   prefixed with its length as a little-endian 64-bit integer.
+//! - 64 bytes are derived. The first 32 bytes are the key for traffic from
+//!   the client to the server and the last 32 bytes the key for traffic from
+//!   the server to the client.
+//! - `client_session_keys()` and `server_session_keys()` return the same two
+//!   keys, with the transmit and receive keys swapped.
+//! - The output of the Diffie-Hellman function is held in a `SharedSecret`,
+//!   which can only be used to derive keys. `derive_key()` can derive a key
+//!   with HKDF-HMAC-SHA512 and a caller-chosen salt and info, for protocols
+//!   that need a different key schedule.
+//!
+//! # Parameters:
+//! - `shared_secret`: The output of the Diffie-Hellman function.
+//! - `salt`: Salt value for HKDF. This may be empty.
+//! - `info`: Context and application-specific information for HKDF.
+//! - `length`: The length of the key derived by `derive_key()`.
+//! - `client_public_key`: The public key of the client.
+//! - `server_public_key`: The public key of the server.
+//! - `transcript`: Any other data both parties have agreed on, such as the
+//!   handshake messages and protocol name. This may be empty.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The slice given to `SharedSecret::from_slice()` is less than 32 bytes or
+//!   all zeros.
+//! - `client_public_key` or `server_public_key` is empty.
+//! - `length` is 0 or greater than 16320.
+//!
+//! # Security:
+//! - The output of a Diffie-Hellman function should never be used directly
+//!   as a key. It is not uniformly random and is the same in both
+//!   directions.
+//! - Binding both public keys and the transcript to the session keys means
+//!   that the parties only end up with the same keys if they agree on who
+//!   took part in the exchange and what was said.
+//! - __**Avoid using**__ `SharedSecret::dangerous_unprotected_as_bytes()`
+//!   unless the shared secret must be passed to a key derivation that orion
+//!   does not offer.
+//! - An all-zero shared secret is the result of X25519 with a low-order
+//!   public key, and is rejected.
+//! - The public keys are not authenticated here. Without authentication of
+//!   at least one public key, the exchange is open to man-in-the-middle
+//!   attacks.
+//!
+//! # Example:
+//! ```
+//! use orion::kex;
+//!
+//! // The Diffie-Hellman output and public keys come from the key exchange.
+//! let shared_secret = kex::SharedSecret::from_slice(&[0x42u8; 32]).unwrap();
+//! let client_public_key = [0xaau8; 32];
+//! let server_public_key = [0xbbu8; 32];
+//!
+//! let client = kex::client_session_keys(
+//! 	&shared_secret,
+//! 	&client_public_key,
+//! 	&server_public_key,
+//! 	b"example protocol v1",
+//! )
+//! .unwrap();
+//! let server = kex::server_session_keys(
+//! 	&shared_secret,
+//! 	&client_public_key,
+//! 	&server_public_key,
+//! 	b"example protocol v1",
+//! )
+//! .unwrap();
+//!
+//! assert!(client.transmit_key() == server.receive_key());
+//! assert!(server.transmit_key() == client.receive_key());
+//!
+//! let key = kex::derive_key(&shared_secret, b"", b"custom key schedule", 32).unwrap();
+//! ```
+
+pub use crate::hltypes::SecretKey;
+use crate::{errors::UnknownCryptoError, hazardous::kdf::hkdf, util};
+use zeroize::Zeroize;
+
+/// The domain separation string at the start of the info.
+const KEX_DOMAIN: &[u8] = b"orion-kex-v1";
+
+/// The minimum size of a shared secret.
+const MIN_SHARED_SECRET_SIZE: usize = 32;
+
+/// The size of each session key.
+const SESSION_KEY_SIZE: usize = 32;
+
+#[must_use]
+/// A type to represent the output of a Diffie-Hellman function.
+///
+/// A `SharedSecret` cannot be compared, cloned or read as bytes, other than
+/// with `dangerous_unprotected_as_bytes()`. It is meant to be passed to the
+/// key derivation functions in this module.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - `slice` is less than 32 bytes.
+/// - `slice` is all zeros.
+pub struct SharedSecret {
+	value: Vec<u8>,
+}
+
+impl_omitted_debug_trait!(SharedSecret);
+impl_drop_trait!(SharedSecret);
+impl_zeroize_trait!(SharedSecret);
+
+impl SharedSecret {
+	#[must_use]
+	/// Make a `SharedSecret` from the output of a Diffie-Hellman function.
+	pub fn from_slice(slice: &[u8]) -> Result<Self, UnknownCryptoError> {
+		if slice.len() < MIN_SHARED_SECRET_SIZE
+			|| util::secure_cmp(slice, &vec![0u8; slice.len()]).is_ok()
+		{
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(Self {
+			value: slice.to_vec(),
+		})
+	}
+
+	/// Return the length of the shared secret.
+	pub fn get_length(&self) -> usize { self.value.len() }
+
+	/// Return the raw shared secret.
+	///
+	/// # Security:
+	/// - __**Avoid using**__ this. The raw shared secret must never be used
+	///   directly as a key.
+	pub fn dangerous_unprotected_as_bytes(&self) -> &[u8] { &self.value }
+}
+
+#[derive(Debug)]
+/// A pair of session keys, one for each direction of traffic.
+pub struct SessionKeys {
+	transmit: SecretKey,
+	receive: SecretKey,
+}
+
+impl SessionKeys {
+	/// Return the key for encrypting traffic sent to the other party.
+	pub fn transmit_key(&self) -> &SecretKey { &self.transmit }
+
+	/// Return the key for decrypting traffic received from the other party.
+	pub fn receive_key(&self) -> &SecretKey { &self.receive }
+}
+
+#[must_use]
+/// Derive the client-to-server and server-to-client keys, in that order.
+fn derive_directional_keys(
+	shared_secret: &SharedSecret,
+	client_public_key: &[u8],
+	server_public_key: &[u8],
+	transcript: &[u8],
+) -> Result<(SecretKey, SecretKey), UnknownCryptoError> {
+	if client_public_key.is_empty() || server_public_key.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut info = KEX_DOMAIN.to_vec();
+	for field in [client_public_key, server_public_key, transcript].iter() {
+		info.extend_from_slice(&(field.len() as u64).to_le_bytes());
+		info.extend_from_slice(field);
+	}
+
+	let mut okm = [0u8; 2 * SESSION_KEY_SIZE];
+	hkdf::derive_key(&[0u8; 0], &shared_secret.value, Some(&info), &mut okm)?;
+
+	let keys = (
+		SecretKey::from_slice(&okm[..SESSION_KEY_SIZE]),
+		SecretKey::from_slice(&okm[SESSION_KEY_SIZE..]),
+	);
+	okm.zeroize();
+
+	Ok((keys.0?, keys.1?))
+}
+
+#[must_use]
+/// Derive the session keys of the client.
+pub fn client_session_keys(
+	shared_secret: &SharedSecret,
+	client_public_key: &[u8],
+	server_public_key: &[u8],
+	transcript: &[u8],
+) -> Result<SessionKeys, UnknownCryptoError> {
+	let (client_to_server, server_to_client) = derive_directional_keys(
+		shared_secret,
+		client_public_key,
+		server_public_key,
+		transcript,
+	)?;
+
+	Ok(SessionKeys {
+		transmit: client_to_server,
+		receive: server_to_client,
+	})
+}
+
+#[must_use]
+/// Derive the session keys of the server.
+pub fn server_session_keys(
+	shared_secret: &SharedSecret,
+	client_public_key: &[u8],
+	server_public_key: &[u8],
+	transcript: &[u8],
+) -> Result<SessionKeys, UnknownCryptoError> {
+	let (client_to_server, server_to_client) = derive_directional_keys(
+		shared_secret,
+		client_public_key,
+		server_public_key,
+		transcript,
+	)?;
+
+	Ok(SessionKeys {
+		transmit: server_to_client,
+		receive: client_to_server,
+	})
+}
+
+#[must_use]
+/// Derive a key of `length` bytes from `shared_secret` using
+/// HKDF-HMAC-SHA512.
+pub fn derive_key(
+	shared_secret: &SharedSecret,
+	salt: &[u8],
+	info: &[u8],
+	length: usize,
+) -> Result<SecretKey, UnknownCryptoError> {
+	if length < 1 || length > 16320 {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut buffer = vec![0u8; length];
+	hkdf::derive_key(salt, &shared_secret.value, Some(info), &mut buffer)?;
+
+	let key = SecretKey::from_slice(&buffer)?;
+	buffer.zeroize();
+
+	Ok(key)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	const SHARED_SECRET_BYTES: [u8; 32] = [
+		0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+		0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e,
+		0x1f, 0x20,
+	];
+
+	const CLIENT_PK: [u8; 32] = [0xaa; 32];
+
+	const SERVER_PK: [u8; 32] = [0xbb; 32];
+
+	fn shared_secret() -> SharedSecret { SharedSecret::from_slice(&SHARED_SECRET_BYTES).unwrap() }
+
+	mod test_shared_secret {
+		use super::*;
+
+		#[test]
+		fn test_from_slice() {
+			assert!(SharedSecret::from_slice(&SHARED_SECRET_BYTES).is_ok());
+			assert!(SharedSecret::from_slice(&SHARED_SECRET_BYTES[..31]).is_err());
+			assert!(SharedSecret::from_slice(&[0u8; 32]).is_err());
+			assert!(SharedSecret::from_slice(&[0u8; 0]).is_err());
+
+			let mut long_secret = [0u8; 56];
+			long_secret[55] = 1;
+			assert_eq!(
+				SharedSecret::from_slice(&long_secret).unwrap().get_length(),
+				56
+			);
+		}
+
+		#[test]
+		fn test_dangerous_unprotected_as_bytes() {
+			assert_eq!(
+				shared_secret().dangerous_unprotected_as_bytes(),
+				&SHARED_SECRET_BYTES[..]
+			);
+		}
+
+		#[test]
+		fn test_omitted_debug() {
+			let debug = format!("{:?}", shared_secret());
+			assert_eq!(debug, "SharedSecret {***OMITTED***}");
+		}
+	}
+
+	mod test_session_keys {
+		use super::*;
+
+		#[test]
+		fn test_known_answer() {
+			let expected_client_to_server = [
+				0xe0, 0x91, 0x8a, 0xf9, 0x34, 0xd4, 0x23, 0x46, 0xa6, 0xa5, 0xcb, 0x0e, 0x0e, 0xfb,
+				0x5b, 0x57, 0xd9, 0x4f, 0x1e, 0x88, 0x30, 0x39, 0x05, 0x76, 0xba, 0x66, 0x8b, 0x80,
+				0x03, 0x46, 0xfc, 0xb1,
+			];
+			let expected_server_to_client = [
+				0x08, 0x06, 0xbb, 0x7a, 0xc9, 0x4b, 0x21, 0xdf, 0x41, 0x02, 0xd2, 0x14, 0x9b, 0x4c,
+				0x4b, 0x86, 0xbb, 0xd0, 0x0b, 0x23, 0x10, 0x6f, 0x9f, 0x8e, 0x08, 0xee, 0x66, 0x97,
+				0xb1, 0x88, 0x31, 0xc5,
+			];
+
+			let client = client_session_keys(
+				&shared_secret(),
+				&CLIENT_PK,
+				&SERVER_PK,
+				b"handshake transcript",
+			)
+			.unwrap();
+			assert_eq!(
+				client.transmit_key().unprotected_as_bytes(),
+				&expected_client_to_server[..]
+			);
+			assert_eq!(
+				client.receive_key().unprotected_as_bytes(),
+				&expected_server_to_client[..]
+			);
+		}
+
+		#[test]
+		fn test_client_and_server_agree() {
+			let client =
+				client_session_keys(&shared_secret(), &CLIENT_PK, &SERVER_PK, b"").unwrap();
+			let server =
+				server_session_keys(&shared_secret(), &CLIENT_PK, &SERVER_PK, b"").unwrap();
+
+			assert!(client.transmit_key() == server.receive_key());
+			assert!(client.receive_key() == server.transmit_key());
+			assert!(client.transmit_key() != client.receive_key());
+		}
+
+		#[test]
+		fn test_binds_public_keys_and_transcript() {
+			let keys = client_session_keys(&shared_secret(), &CLIENT_PK, &SERVER_PK, b"").unwrap();
+
+			let swapped =
+				client_session_keys(&shared_secret(), &SERVER_PK, &CLIENT_PK, b"").unwrap();
+			assert!(keys.transmit_key() != swapped.transmit_key());
+
+			let other_transcript =
+				client_session_keys(&shared_secret(), &CLIENT_PK, &SERVER_PK, b"transcript")
+					.unwrap();
+			assert!(keys.transmit_key() != other_transcript.transmit_key());
+
+			// Moving bytes between fields must change the keys.
+			let moved =
+				client_session_keys(&shared_secret(), &CLIENT_PK[..31], &[0xaa; 33], b"").unwrap();
+			let moved_back =
+				client_session_keys(&shared_secret(), &CLIENT_PK, &[0xaa; 32], b"").unwrap();
+			assert!(moved.transmit_key() != moved_back.transmit_key());
+		}
+
+		#[test]
+		fn test_empty_public_key_err() {
+			assert!(client_session_keys(&shared_secret(), &[], &SERVER_PK, b"").is_err());
+			assert!(client_session_keys(&shared_secret(), &CLIENT_PK, &[], b"").is_err());
+			assert!(server_session_keys(&shared_secret(), &[], &SERVER_PK, b"").is_err());
+		}
+	}
+
+	mod test_derive_key {
+		use super::*;
+
+		#[test]
+		fn test_known_answer() {
+			let expected = [
+				0x7f, 0x57, 0xf7, 0xdb, 0x0f, 0x2b, 0xba, 0xc9, 0x29, 0x38, 0x79, 0x10, 0x0b, 0x9c,
+				0x4b, 0xa2, 0x3c, 0xa5, 0xec, 0x0b, 0x82, 0x1f, 0x5f, 0xf3, 0x09, 0xa3, 0x80, 0x32,
+				0xc9, 0xdc, 0x73, 0xed,
+			];
+
+			let key = derive_key(&shared_secret(), b"salt", b"custom key schedule", 32).unwrap();
+			assert_eq!(key.unprotected_as_bytes(), &expected[..]);
+		}
+
+		#[test]
+		fn test_length() {
+			assert!(derive_key(&shared_secret(), b"", b"", 0).is_err());
+			assert!(derive_key(&shared_secret(), b"", b"", 1).is_ok());
+			assert!(derive_key(&shared_secret(), b"", b"", 16320).is_ok());
+			assert!(derive_key(&shared_secret(), b"", b"", 16321).is_err());
+		}
+	}
+}
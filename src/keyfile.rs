@@ -0,0 +1,750 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Password-protected key files.
+//!
+//! # Use case:
+//! `orion::keyfile` can be used to keep a `SecretKey` on disk, encrypted
+//! under a password, so that it can be loaded again when an application
+//! restarts.
+//!
+//! An example of this could be a desktop application that encrypts local
+//! data with a key that is unlocked by the user's password at startup.
+//!
+//! `KeyStore` can be used when an application has several keys, to keep them
+//! together with their metadata in a single key store file.
+//!
+//! # About:
+//! - A key file starts with the 8-byte magic value `ORIONKEY` and a format
+//!   version byte, followed by the key encrypted with
+//!   `aead::seal_with_password()`.
+//! - Format version 1 derives the key-encryption key with PBKDF2-HMAC-SHA512
+//!   using `params`, and encrypts with XChaCha20Poly1305. The iteration count
+//!   and salt are stored in the file, so `open()` needs only the password.
+//! - `store()` writes the file to a temporary path next to `path` and renames
+//!   it over `path`, so an existing key file is never left half-written. On
+//!   Unix, the file is only readable and writable by its owner.
+//! - A key store file starts with the magic value `ORIONKST` and a format
+//!   version byte. All entries are encoded together and encrypted as one
+//!   ciphertext, so entries cannot be added, removed, reordered or modified
+//!   without `KeyStore::open()` failing.
+//! - Each `KeyEntry` has a unique name, the name of the algorithm the key is
+//!   intended for, the time it was inserted in seconds since the Unix epoch,
+//!   and the `KeyId` of the key.
+//! - Entries are encoded ordered by name, so the same entries always encode to
+//!   the same plaintext. Only the salt and nonce make two seals differ.
+//!
+//! # Parameters:
+//! - `secret_key`: The key to protect.
+//! - `password`: The password that the key-encryption key is derived from.
+//! - `params`: The algorithm and iteration count used to derive the
+//!   key-encryption key. The output length of `params` is ignored.
+//! - `key_file`: The contents of a key file.
+//! - `path`: The location of the key file.
+//! - `name`: The name of a `KeyEntry`.
+//! - `algorithm`: The algorithm the key of a `KeyEntry` is intended for, such
+//!   as `"xchacha20poly1305"`.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `key_file` does not start with the magic value or has an unknown format
+//!   version.
+//! - `key_file` was modified or `password` is not the one used when sealing.
+//! - Any of the errors of `aead::seal_with_password()` and
+//!   `aead::open_with_password()` occur.
+//! - The file at `path` cannot be read or written.
+//! - `name` or `algorithm` is empty or longer than 65535 bytes.
+//! - `name` is already used by an entry, when inserting.
+//! - No entry is named `name`, when removing.
+//!
+//! # Security:
+//! - The key is only as strong as the password protecting it. Use
+//!   `Params::sensitive()`, or an iteration count calibrated with
+//!   `Params::calibrate()`, for key files. Iteration counts above that of
+//!   `Params::sensitive()` are rejected.
+//! - `open()` runs the key derivation with the iteration count stored in the
+//!   key file, which is at most that of `Params::sensitive()`.
+//!
+//! # Example:
+//! ```
+//! use orion::keyfile;
+//!
+//! let secret_key = keyfile::SecretKey::default();
+//! let password = keyfile::Password::from_slice(b"Secret password").unwrap();
+//! let params = keyfile::Params::interactive();
+//!
+//! let key_file = keyfile::seal(&secret_key, &password, &params).unwrap();
+//! assert!(keyfile::open(&key_file, &password).unwrap() == secret_key);
+//!
+//! let path = std::env::temp_dir().join("orion_doc_keyfile");
+//! keyfile::store(&path, &secret_key, &password, &params).unwrap();
+//! assert!(keyfile::load(&path, &password).unwrap() == secret_key);
+//! # std::fs::remove_file(&path).unwrap();
+//!
+//! let mut key_store = keyfile::KeyStore::new();
+//! key_store.insert("backup", "xchacha20poly1305", secret_key).unwrap();
+//!
+//! let sealed = key_store.seal(&password, &params).unwrap();
+//! let opened = keyfile::KeyStore::open(&sealed, &password).unwrap();
+//! assert_eq!(opened.get("backup").unwrap().algorithm(), "xchacha20poly1305");
+//! ```
+
+pub use crate::hltypes::{KeyId, Params, Password, SecretKey};
+use crate::{aead, errors::UnknownCryptoError, hltypes::KEY_ID_SIZE};
+use std::{
+	collections::BTreeMap,
+	fs::{self, OpenOptions},
+	io::Write,
+	path::Path,
+	time::{SystemTime, UNIX_EPOCH},
+};
+use zeroize::Zeroize;
+
+/// The magic value at the start of a key file.
+const KEY_FILE_MAGIC: &[u8; 8] = b"ORIONKEY";
+
+/// The magic value at the start of a key store file.
+const KEY_STORE_MAGIC: &[u8; 8] = b"ORIONKST";
+
+/// The format version of key files and key store files that use `aead::seal_with_password()`
+/// with PBKDF2-HMAC-SHA512 and XChaCha20Poly1305.
+const KEY_FILE_FORMAT_V1: u8 = 1;
+
+/// The size of the magic value and format version.
+const KEY_FILE_HEADER_SIZE: usize = KEY_FILE_MAGIC.len() + 1;
+
+/// Write `contents` to a temporary file next to `path` and rename it over
+/// `path`. On Unix, the file is only readable and writable by its owner.
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), UnknownCryptoError> {
+	let mut tmp_name = path.file_name().ok_or(UnknownCryptoError)?.to_os_string();
+	tmp_name.push(".tmp");
+	let tmp_path = path.with_file_name(tmp_name);
+
+	let mut options = OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::OpenOptionsExt;
+		options.mode(0o600);
+	}
+
+	let written = options.open(&tmp_path).and_then(|mut file| {
+		file.write_all(contents)?;
+		file.sync_all()
+	});
+	if written.and_then(|_| fs::rename(&tmp_path, path)).is_err() {
+		let _ = fs::remove_file(&tmp_path);
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(())
+}
+
+/// Check the magic value and format version at the start of `sealed`, and
+/// decrypt the rest with `password`.
+fn open_sealed(
+	magic: &[u8; 8],
+	sealed: &[u8],
+	password: &Password,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	if sealed.len() < KEY_FILE_HEADER_SIZE || !sealed.starts_with(magic) {
+		return Err(UnknownCryptoError);
+	}
+
+	match sealed[magic.len()] {
+		KEY_FILE_FORMAT_V1 => aead::open_with_password(password, &sealed[KEY_FILE_HEADER_SIZE..]),
+		_ => Err(UnknownCryptoError),
+	}
+}
+
+/// Encrypt `plaintext` with `password` and prefix it with `magic` and the
+/// format version.
+fn seal_with_magic(
+	magic: &[u8; 8],
+	plaintext: &[u8],
+	password: &Password,
+	params: &Params,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	let sealed = aead::seal_with_password(password, params, plaintext)?;
+
+	let mut out = Vec::with_capacity(KEY_FILE_HEADER_SIZE + sealed.len());
+	out.extend_from_slice(magic);
+	out.push(KEY_FILE_FORMAT_V1);
+	out.extend_from_slice(&sealed);
+
+	Ok(out)
+}
+
+#[must_use]
+/// Encrypt `secret_key` under `password` into the contents of a key file.
+pub fn seal(
+	secret_key: &SecretKey,
+	password: &Password,
+	params: &Params,
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	seal_with_magic(
+		KEY_FILE_MAGIC,
+		secret_key.unprotected_as_bytes(),
+		password,
+		params,
+	)
+}
+
+#[must_use]
+/// Decrypt the `SecretKey` in the contents of a key file.
+pub fn open(key_file: &[u8], password: &Password) -> Result<SecretKey, UnknownCryptoError> {
+	let mut key_bytes = open_sealed(KEY_FILE_MAGIC, key_file, password)?;
+	let secret_key = SecretKey::from_slice(&key_bytes);
+	key_bytes.zeroize();
+
+	secret_key
+}
+
+#[must_use]
+/// Encrypt `secret_key` under `password` and write it to a key file at
+/// `path`, replacing any existing file.
+pub fn store<P: AsRef<Path>>(
+	path: P,
+	secret_key: &SecretKey,
+	password: &Password,
+	params: &Params,
+) -> Result<(), UnknownCryptoError> {
+	write_atomic(path.as_ref(), &seal(secret_key, password, params)?)
+}
+
+#[must_use]
+/// Read the key file at `path` and decrypt the `SecretKey` in it.
+pub fn load<P: AsRef<Path>>(path: P, password: &Password) -> Result<SecretKey, UnknownCryptoError> {
+	let key_file = fs::read(path).map_err(|_| UnknownCryptoError)?;
+	open(&key_file, password)
+}
+
+/// The context used to compute the `KeyId` of each entry in a key store.
+const KEY_STORE_ID_CONTEXT: &str = "orion-keystore";
+
+/// Return the current time in seconds since the Unix epoch.
+fn now() -> Result<u64, UnknownCryptoError> {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|since_epoch| since_epoch.as_secs())
+		.map_err(|_| UnknownCryptoError)
+}
+
+/// Split the first `len` bytes off `src`.
+fn take<'a>(src: &mut &'a [u8], len: usize) -> Result<&'a [u8], UnknownCryptoError> {
+	if src.len() < len {
+		return Err(UnknownCryptoError);
+	}
+	let (head, tail) = src.split_at(len);
+	*src = tail;
+
+	Ok(head)
+}
+
+/// Split a `u16` length-prefixed, non-empty UTF-8 string off `src`.
+fn take_str(src: &mut &[u8]) -> Result<String, UnknownCryptoError> {
+	let mut len = [0u8; 2];
+	len.copy_from_slice(take(src, 2)?);
+	let bytes = take(src, usize::from(u16::from_le_bytes(len)))?;
+	if bytes.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	String::from_utf8(bytes.to_vec()).map_err(|_| UnknownCryptoError)
+}
+
+/// Append `field` to `dst`, prefixed by its length as a little-endian `u16`.
+fn put_str(dst: &mut Vec<u8>, field: &str) {
+	debug_assert!(field.len() <= usize::from(u16::MAX));
+	dst.extend_from_slice(&(field.len() as u16).to_le_bytes());
+	dst.extend_from_slice(field.as_bytes());
+}
+
+/// Check that `field` can be stored as the name or algorithm of an entry.
+fn validate_field(field: &str) -> Result<(), UnknownCryptoError> {
+	if field.is_empty() || field.len() > usize::from(u16::MAX) {
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(())
+}
+
+#[derive(Debug)]
+/// A named key in a `KeyStore`, with its metadata.
+pub struct KeyEntry {
+	name: String,
+	algorithm: String,
+	created_at: u64,
+	key_id: KeyId,
+	secret_key: SecretKey,
+}
+
+impl KeyEntry {
+	/// Return the name of the entry.
+	pub fn name(&self) -> &str { &self.name }
+
+	/// Return the algorithm the key is intended for.
+	pub fn algorithm(&self) -> &str { &self.algorithm }
+
+	/// Return the time the entry was inserted, in seconds since the Unix
+	/// epoch.
+	pub fn created_at(&self) -> u64 { self.created_at }
+
+	/// Return the `KeyId` of the key.
+	pub fn key_id(&self) -> KeyId { self.key_id }
+
+	/// Return the key.
+	pub fn secret_key(&self) -> &SecretKey { &self.secret_key }
+}
+
+#[derive(Debug, Default)]
+/// A set of named keys that is stored as a single key file.
+pub struct KeyStore {
+	entries: BTreeMap<String, KeyEntry>,
+}
+
+impl KeyStore {
+	/// Create an empty `KeyStore`.
+	pub fn new() -> Self { Self::default() }
+
+	/// Return the number of entries.
+	pub fn len(&self) -> usize { self.entries.len() }
+
+	/// Return `true` if there are no entries.
+	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+	#[must_use]
+	/// Add `secret_key` under `name`, recording `algorithm` and the current
+	/// time.
+	pub fn insert(
+		&mut self,
+		name: &str,
+		algorithm: &str,
+		secret_key: SecretKey,
+	) -> Result<&KeyEntry, UnknownCryptoError> {
+		self.insert_at(name, algorithm, secret_key, now()?)
+	}
+
+	/// Add `secret_key` under `name`, created at `created_at`.
+	fn insert_at(
+		&mut self,
+		name: &str,
+		algorithm: &str,
+		secret_key: SecretKey,
+		created_at: u64,
+	) -> Result<&KeyEntry, UnknownCryptoError> {
+		validate_field(name)?;
+		validate_field(algorithm)?;
+		if self.entries.contains_key(name) {
+			return Err(UnknownCryptoError);
+		}
+
+		let entry = KeyEntry {
+			name: name.to_string(),
+			algorithm: algorithm.to_string(),
+			created_at,
+			key_id: secret_key.id(KEY_STORE_ID_CONTEXT)?,
+			secret_key,
+		};
+
+		Ok(self.entries.entry(name.to_string()).or_insert(entry))
+	}
+
+	/// Return the entry named `name`, if any.
+	pub fn get(&self, name: &str) -> Option<&KeyEntry> { self.entries.get(name) }
+
+	#[must_use]
+	/// Remove and return the entry named `name`.
+	pub fn remove(&mut self, name: &str) -> Result<KeyEntry, UnknownCryptoError> {
+		self.entries.remove(name).ok_or(UnknownCryptoError)
+	}
+
+	/// Return the entries, ordered by name.
+	pub fn entries(&self) -> impl Iterator<Item = &KeyEntry> { self.entries.values() }
+
+	/// Encode the entries, ordered by name.
+	fn encode(&self) -> Vec<u8> {
+		let mut out = Vec::new();
+		out.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+		for entry in self.entries.values() {
+			put_str(&mut out, &entry.name);
+			put_str(&mut out, &entry.algorithm);
+			out.extend_from_slice(&entry.created_at.to_le_bytes());
+			out.extend_from_slice(entry.key_id.as_bytes());
+			let key = entry.secret_key.unprotected_as_bytes();
+			out.extend_from_slice(&(key.len() as u32).to_le_bytes());
+			out.extend_from_slice(key);
+		}
+
+		out
+	}
+
+	/// Decode entries written by `encode()`.
+	fn decode(mut src: &[u8]) -> Result<Self, UnknownCryptoError> {
+		let mut count = [0u8; 4];
+		count.copy_from_slice(take(&mut src, 4)?);
+
+		let mut key_store = Self::new();
+		for _ in 0..u32::from_le_bytes(count) {
+			let name = take_str(&mut src)?;
+			let algorithm = take_str(&mut src)?;
+			// Names must be strictly increasing, so there is only one encoding
+			// of a given key store.
+			if let Some(last) = key_store.entries.keys().next_back() {
+				if name.as_str() <= last.as_str() {
+					return Err(UnknownCryptoError);
+				}
+			}
+
+			let mut created_at = [0u8; 8];
+			created_at.copy_from_slice(take(&mut src, 8)?);
+			let key_id = KeyId::from_slice(take(&mut src, KEY_ID_SIZE)?)?;
+			let mut key_len = [0u8; 4];
+			key_len.copy_from_slice(take(&mut src, 4)?);
+			let secret_key =
+				SecretKey::from_slice(take(&mut src, u32::from_le_bytes(key_len) as usize)?)?;
+
+			let entry = key_store.insert_at(
+				&name,
+				&algorithm,
+				secret_key,
+				u64::from_le_bytes(created_at),
+			)?;
+			if entry.key_id != key_id {
+				return Err(UnknownCryptoError);
+			}
+		}
+
+		if !src.is_empty() {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(key_store)
+	}
+
+	#[must_use]
+	/// Encrypt all entries under `password` into the contents of a key store
+	/// file.
+	pub fn seal(
+		&self,
+		password: &Password,
+		params: &Params,
+	) -> Result<Vec<u8>, UnknownCryptoError> {
+		let mut plaintext = self.encode();
+		let sealed = seal_with_magic(KEY_STORE_MAGIC, &plaintext, password, params);
+		plaintext.zeroize();
+
+		sealed
+	}
+
+	#[must_use]
+	/// Decrypt the entries in the contents of a key store file.
+	pub fn open(key_store: &[u8], password: &Password) -> Result<Self, UnknownCryptoError> {
+		let mut plaintext = open_sealed(KEY_STORE_MAGIC, key_store, password)?;
+		let decoded = Self::decode(&plaintext);
+		plaintext.zeroize();
+
+		decoded
+	}
+
+	#[must_use]
+	/// Encrypt all entries under `password` and write them to a key store
+	/// file at `path`, replacing any existing file.
+	pub fn store<P: AsRef<Path>>(
+		&self,
+		path: P,
+		password: &Password,
+		params: &Params,
+	) -> Result<(), UnknownCryptoError> {
+		write_atomic(path.as_ref(), &self.seal(password, params)?)
+	}
+
+	#[must_use]
+	/// Read the key store file at `path` and decrypt the entries in it.
+	pub fn load<P: AsRef<Path>>(path: P, password: &Password) -> Result<Self, UnknownCryptoError> {
+		let key_store = fs::read(path).map_err(|_| UnknownCryptoError)?;
+		Self::open(&key_store, password)
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	fn password() -> Password { Password::from_slice(b"Secret password").unwrap() }
+
+	/// Low iteration count, to keep the tests fast.
+	fn params() -> Params { Params::new(1000).unwrap() }
+
+	mod test_seal_open {
+		use super::*;
+
+		#[test]
+		fn test_seal_open() {
+			let secret_key = SecretKey::generate(64).unwrap();
+			let key_file = seal(&secret_key, &password(), &params()).unwrap();
+
+			assert_eq!(&key_file[..8], b"ORIONKEY");
+			assert_eq!(key_file[8], KEY_FILE_FORMAT_V1);
+			assert!(open(&key_file, &password()).unwrap() == secret_key);
+		}
+
+		#[test]
+		fn test_wrong_password_err() {
+			let key_file = seal(&SecretKey::default(), &password(), &params()).unwrap();
+			let wrong = Password::from_slice(b"Wrong password").unwrap();
+			assert!(open(&key_file, &wrong).is_err());
+		}
+
+		#[test]
+		fn test_modified_err() {
+			let key_file = seal(&SecretKey::default(), &password(), &params()).unwrap();
+
+			let mut bad_magic = key_file.clone();
+			bad_magic[0] ^= 1;
+			assert!(open(&bad_magic, &password()).is_err());
+
+			let mut bad_version = key_file.clone();
+			bad_version[8] = 2;
+			assert!(open(&bad_version, &password()).is_err());
+
+			let mut bad_ciphertext = key_file.clone();
+			let last = bad_ciphertext.len() - 1;
+			bad_ciphertext[last] ^= 1;
+			assert!(open(&bad_ciphertext, &password()).is_err());
+
+			assert!(open(&key_file[..key_file.len() - 1], &password()).is_err());
+			assert!(open(&key_file[..KEY_FILE_HEADER_SIZE], &password()).is_err());
+			assert!(open(&[0u8; 0], &password()).is_err());
+		}
+	}
+
+	mod test_store_load {
+		use super::*;
+
+		#[test]
+		fn test_store_load() {
+			let path = std::env::temp_dir().join("orion_test_keyfile_store_load");
+			let secret_key = SecretKey::default();
+
+			store(&path, &secret_key, &password(), &params()).unwrap();
+			let loaded = load(&path, &password());
+
+			// Storing again replaces the file.
+			let new_key = SecretKey::default();
+			store(&path, &new_key, &password(), &params()).unwrap();
+			let reloaded = load(&path, &password());
+
+			#[cfg(unix)]
+			let mode = {
+				use std::os::unix::fs::PermissionsExt;
+				fs::metadata(&path).unwrap().permissions().mode() & 0o777
+			};
+			fs::remove_file(&path).unwrap();
+
+			assert!(loaded.unwrap() == secret_key);
+			assert!(reloaded.unwrap() == new_key);
+			#[cfg(unix)]
+			assert_eq!(mode, 0o600);
+		}
+
+		#[test]
+		fn test_load_not_found_err() {
+			let path = std::env::temp_dir().join("orion_test_keyfile_not_found");
+			assert!(load(&path, &password()).is_err());
+		}
+	}
+
+	mod test_key_store {
+		use super::*;
+
+		fn key_store() -> KeyStore {
+			let mut key_store = KeyStore::new();
+			key_store
+				.insert_at(
+					"signing",
+					"hmac-sha512",
+					SecretKey::generate(64).unwrap(),
+					2,
+				)
+				.unwrap();
+			key_store
+				.insert_at("backup", "xchacha20poly1305", SecretKey::default(), 1)
+				.unwrap();
+			key_store
+		}
+
+		#[test]
+		fn test_insert_get_remove() {
+			let mut key_store = KeyStore::new();
+			assert!(key_store.is_empty());
+
+			let secret_key = SecretKey::default();
+			let key_id = secret_key.id("orion-keystore").unwrap();
+			let entry = key_store
+				.insert("backup", "xchacha20poly1305", secret_key.duplicate())
+				.unwrap();
+			assert_eq!(entry.name(), "backup");
+			assert_eq!(entry.algorithm(), "xchacha20poly1305");
+			assert!(entry.created_at() > 0);
+			assert_eq!(entry.key_id(), key_id);
+			assert!(entry.secret_key() == &secret_key);
+			assert_eq!(key_store.len(), 1);
+
+			assert!(key_store.get("backup").is_some());
+			assert!(key_store.get("other").is_none());
+			assert!(key_store.remove("backup").unwrap().secret_key() == &secret_key);
+			assert!(key_store.remove("backup").is_err());
+			assert!(key_store.is_empty());
+		}
+
+		#[test]
+		fn test_insert_err() {
+			let mut key_store = key_store();
+			let long = "a".repeat(usize::from(u16::MAX) + 1);
+
+			assert!(key_store
+				.insert("backup", "aead", SecretKey::default())
+				.is_err());
+			assert!(key_store.insert("", "aead", SecretKey::default()).is_err());
+			assert!(key_store.insert("new", "", SecretKey::default()).is_err());
+			assert!(key_store
+				.insert(&long, "aead", SecretKey::default())
+				.is_err());
+			assert!(key_store
+				.insert("new", &long, SecretKey::default())
+				.is_err());
+			assert_eq!(key_store.len(), 2);
+		}
+
+		#[test]
+		fn test_entries_ordered_by_name() {
+			let key_store = key_store();
+			let names: Vec<&str> = key_store.entries().map(|e| e.name()).collect();
+			assert_eq!(names, ["backup", "signing"]);
+		}
+
+		#[test]
+		fn test_encode_deterministic() {
+			let mut key_store = KeyStore::new();
+			key_store
+				.insert_at("b", "x", SecretKey::from_slice(&[1u8; 32]).unwrap(), 7)
+				.unwrap();
+			key_store
+				.insert_at("a", "y", SecretKey::from_slice(&[2u8; 32]).unwrap(), 8)
+				.unwrap();
+
+			let encoded = key_store.encode();
+			assert_eq!(&encoded[..4], &[2, 0, 0, 0]);
+			assert_eq!(&encoded[4..10], &[1, 0, b'a', 1, 0, b'y']);
+			assert_eq!(&encoded[10..18], &8u64.to_le_bytes());
+			assert_eq!(KeyStore::decode(&encoded).unwrap().encode(), encoded);
+		}
+
+		#[test]
+		fn test_decode_err() {
+			let encoded = key_store().encode();
+			assert!(KeyStore::decode(&encoded).is_ok());
+			assert!(KeyStore::decode(&encoded[..encoded.len() - 1]).is_err());
+			assert!(KeyStore::decode(&[0u8; 3]).is_err());
+
+			let mut trailing = encoded.clone();
+			trailing.push(0);
+			assert!(KeyStore::decode(&trailing).is_err());
+
+			// Wrong KeyId.
+			let mut bad_id = encoded.clone();
+			let id_pos = 4 + 2 + 6 + 2 + 17 + 8;
+			bad_id[id_pos] ^= 1;
+			assert!(KeyStore::decode(&bad_id).is_err());
+
+			// Entries out of order.
+			let mut single = KeyStore::new();
+			single.insert_at("b", "x", SecretKey::default(), 0).unwrap();
+			let mut other = KeyStore::new();
+			other.insert_at("a", "x", SecretKey::default(), 0).unwrap();
+			let mut unordered = vec![2, 0, 0, 0];
+			unordered.extend_from_slice(&single.encode()[4..]);
+			unordered.extend_from_slice(&other.encode()[4..]);
+			assert!(KeyStore::decode(&unordered).is_err());
+
+			// Duplicate names.
+			let mut duplicate = vec![2, 0, 0, 0];
+			duplicate.extend_from_slice(&single.encode()[4..]);
+			duplicate.extend_from_slice(&single.encode()[4..]);
+			assert!(KeyStore::decode(&duplicate).is_err());
+
+			// Invalid UTF-8 in a name.
+			let mut bad_utf8 = single.encode();
+			bad_utf8[6] = 0xff;
+			assert!(KeyStore::decode(&bad_utf8).is_err());
+		}
+
+		#[test]
+		fn test_seal_open() {
+			let key_store = key_store();
+			let sealed = key_store.seal(&password(), &params()).unwrap();
+			assert_eq!(&sealed[..8], b"ORIONKST");
+			assert_eq!(sealed[8], KEY_FILE_FORMAT_V1);
+
+			let opened = KeyStore::open(&sealed, &password()).unwrap();
+			assert_eq!(opened.encode(), key_store.encode());
+			assert_eq!(opened.get("backup").unwrap().created_at(), 1);
+
+			let empty = KeyStore::new().seal(&password(), &params()).unwrap();
+			assert!(KeyStore::open(&empty, &password()).unwrap().is_empty());
+		}
+
+		#[test]
+		fn test_open_err() {
+			let sealed = key_store().seal(&password(), &params()).unwrap();
+			let wrong = Password::from_slice(b"Wrong password").unwrap();
+			assert!(KeyStore::open(&sealed, &wrong).is_err());
+
+			let mut bad_ciphertext = sealed.clone();
+			bad_ciphertext[KEY_FILE_HEADER_SIZE + 40] ^= 1;
+			assert!(KeyStore::open(&bad_ciphertext, &password()).is_err());
+
+			let mut bad_version = sealed.clone();
+			bad_version[8] = 2;
+			assert!(KeyStore::open(&bad_version, &password()).is_err());
+
+			// Key files and key store files are not interchangeable.
+			let key_file = seal(&SecretKey::default(), &password(), &params()).unwrap();
+			assert!(KeyStore::open(&key_file, &password()).is_err());
+			assert!(open(&sealed, &password()).is_err());
+		}
+
+		#[test]
+		fn test_store_load() {
+			let path = std::env::temp_dir().join("orion_test_keyfile_key_store");
+			let key_store = key_store();
+
+			key_store.store(&path, &password(), &params()).unwrap();
+			let loaded = KeyStore::load(&path, &password());
+			fs::remove_file(&path).unwrap();
+
+			assert_eq!(loaded.unwrap().encode(), key_store.encode());
+		}
+	}
+}
@@ -0,0 +1,319 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Authenticated secret-key encryption with multiple keys and key rotation.
+//!
+//! # Use case:
+//! `orion::keyring` can be used to encrypt stored data in a way that allows
+//! the key to be rotated, while data that was encrypted with earlier keys can
+//! still be decrypted.
+//!
+//! An example of this could be a storage service that periodically replaces
+//! its encryption key, and re-encrypts old records over time.
+//!
+//! # About:
+//! - A `KeyRing` holds one or more keys, one of which is the primary key.
+//! - `seal()` encrypts with the primary key using `orion::aead`, and prefixes
+//!   the ciphertext with the 16-byte `KeyId` of the primary key.
+//! - `open()` selects the key with the `KeyId` found in the ciphertext.
+//! - `rotate()` generates a new primary key. Earlier keys are kept for
+//!   decryption until they are removed with `remove()`.
+//! - The `KeyId` of a key is `SecretKey::id("orion-keyring")`.
+//!
+//! # Parameters:
+//! - `secret_key`: A secret key to add to the `KeyRing`.
+//! - `key_id`: The `KeyId` of a key in the `KeyRing`.
+//! - `plaintext`: The data to be encrypted.
+//! - `ciphertext_with_key_id`: The data to be decrypted, with the first 16
+//!   bytes being the `KeyId` of the key it was encrypted with.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is not 32 bytes.
+//! - `secret_key` is already in the `KeyRing`.
+//! - `key_id` is not in the `KeyRing`, or is the primary key when removing.
+//! - `ciphertext_with_key_id` was encrypted with a key that is not in the
+//!   `KeyRing`.
+//! - Any of the errors of `aead::seal()` and `aead::open()` occur.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - The `KeyId` of the key used is stored unencrypted in the ciphertext, so
+//!   an observer can tell which ciphertexts were encrypted with the same key.
+//! - Removing a key makes all data encrypted with it undecryptable. Make sure
+//!   that data has been re-encrypted with a newer key first.
+//!
+//! # Example:
+//! ```
+//! use orion::keyring::KeyRing;
+//!
+//! let mut keyring = KeyRing::generate().unwrap();
+//! let old_ciphertext = keyring.seal(b"Secret message").unwrap();
+//!
+//! let old_key_id = keyring.primary_key_id();
+//! keyring.rotate().unwrap();
+//! let new_ciphertext = keyring.seal(b"Secret message").unwrap();
+//!
+//! assert_eq!(keyring.open(&old_ciphertext).unwrap(), b"Secret message");
+//! assert_eq!(keyring.open(&new_ciphertext).unwrap(), b"Secret message");
+//!
+//! // Once old data has been re-encrypted, the old key can be removed.
+//! keyring.remove(&old_key_id).unwrap();
+//! assert!(keyring.open(&old_ciphertext).is_err());
+//! ```
+
+pub use crate::hltypes::{KeyId, SecretKey};
+use crate::{aead, errors::UnknownCryptoError};
+
+/// The context used to derive the `KeyId` of keys in a `KeyRing`.
+const KEY_ID_CONTEXT: &str = "orion-keyring";
+
+/// The size of the `KeyId` that prefixes a ciphertext.
+const KEY_ID_SIZE: usize = 16;
+
+/// The length of keys in a `KeyRing`.
+const KEY_SIZE: usize = 32;
+
+#[must_use]
+#[derive(Debug)]
+/// A set of keys, one of which is used for encryption, that can all be used
+/// for decryption.
+pub struct KeyRing {
+	/// The keys and their `KeyId`s. The last key is the primary key.
+	keys: Vec<(KeyId, SecretKey)>,
+}
+
+impl KeyRing {
+	#[must_use]
+	/// Make a new `KeyRing` with `secret_key` as the primary key.
+	pub fn new(secret_key: SecretKey) -> Result<KeyRing, UnknownCryptoError> {
+		let mut keyring = KeyRing { keys: Vec::new() };
+		let _ = keyring.push_key(secret_key)?;
+
+		Ok(keyring)
+	}
+
+	#[must_use]
+	/// Make a new `KeyRing` with a randomly generated primary key.
+	pub fn generate() -> Result<KeyRing, UnknownCryptoError> {
+		KeyRing::new(SecretKey::generate(KEY_SIZE)?)
+	}
+
+	/// Add `secret_key` to the `KeyRing` and return its `KeyId`, checking
+	/// that it is valid and not already present.
+	fn push_key(&mut self, secret_key: SecretKey) -> Result<KeyId, UnknownCryptoError> {
+		if secret_key.get_length() != KEY_SIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let key_id = secret_key.id(KEY_ID_CONTEXT)?;
+		if self.find(&key_id).is_some() {
+			return Err(UnknownCryptoError);
+		}
+
+		self.keys.push((key_id, secret_key));
+
+		Ok(key_id)
+	}
+
+	/// Return the index of the key with `key_id`.
+	fn find(&self, key_id: &KeyId) -> Option<usize> {
+		self.keys.iter().position(|(id, _)| id == key_id)
+	}
+
+	/// Return the `KeyId` of the primary key.
+	pub fn primary_key_id(&self) -> KeyId {
+		// .unwrap() cannot panic since a `KeyRing` always has a primary key
+		self.keys.last().unwrap().0
+	}
+
+	/// Return the `KeyId`s of all keys, from the oldest to the primary key.
+	pub fn key_ids(&self) -> Vec<KeyId> { self.keys.iter().map(|(id, _)| *id).collect() }
+
+	#[must_use]
+	/// Make `secret_key` the new primary key and return its `KeyId`. The
+	/// previous primary key is kept for decryption.
+	pub fn rotate_to(&mut self, secret_key: SecretKey) -> Result<KeyId, UnknownCryptoError> {
+		self.push_key(secret_key)
+	}
+
+	#[must_use]
+	/// Generate a new primary key and return its `KeyId`. The previous
+	/// primary key is kept for decryption.
+	pub fn rotate(&mut self) -> Result<KeyId, UnknownCryptoError> {
+		self.rotate_to(SecretKey::generate(KEY_SIZE)?)
+	}
+
+	#[must_use]
+	/// Remove the key with `key_id`. The primary key cannot be removed.
+	pub fn remove(&mut self, key_id: &KeyId) -> Result<(), UnknownCryptoError> {
+		match self.find(key_id) {
+			Some(idx) if idx != self.keys.len() - 1 => {
+				drop(self.keys.remove(idx));
+				Ok(())
+			}
+			_ => Err(UnknownCryptoError),
+		}
+	}
+
+	#[must_use]
+	/// Authenticated encryption using the primary key.
+	pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+		// .unwrap() cannot panic since a `KeyRing` always has a primary key
+		let (key_id, secret_key) = self.keys.last().unwrap();
+
+		let mut ciphertext_with_key_id = key_id.as_bytes().to_vec();
+		ciphertext_with_key_id.extend_from_slice(&aead::seal(secret_key, plaintext)?);
+
+		Ok(ciphertext_with_key_id)
+	}
+
+	#[must_use]
+	/// Authenticated decryption using the key that `ciphertext_with_key_id`
+	/// was encrypted with.
+	pub fn open(&self, ciphertext_with_key_id: &[u8]) -> Result<Vec<u8>, UnknownCryptoError> {
+		if ciphertext_with_key_id.len() < KEY_ID_SIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let key_id = KeyId::from_slice(&ciphertext_with_key_id[..KEY_ID_SIZE])?;
+		let idx = self.find(&key_id).ok_or(UnknownCryptoError)?;
+
+		aead::open(&self.keys[idx].1, &ciphertext_with_key_id[KEY_ID_SIZE..])
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_new {
+		use super::*;
+
+		#[test]
+		fn test_key_length() {
+			assert!(KeyRing::new(SecretKey::generate(32).unwrap()).is_ok());
+			assert!(KeyRing::new(SecretKey::generate(31).unwrap()).is_err());
+			assert!(KeyRing::new(SecretKey::generate(64).unwrap()).is_err());
+		}
+
+		#[test]
+		fn test_primary_key_id() {
+			let secret_key = SecretKey::default();
+			let key_id = secret_key.id("orion-keyring").unwrap();
+			let keyring = KeyRing::new(secret_key).unwrap();
+
+			assert!(keyring.primary_key_id() == key_id);
+			assert_eq!(keyring.key_ids(), vec![key_id]);
+		}
+	}
+
+	mod test_rotate {
+		use super::*;
+
+		#[test]
+		fn test_rotate_changes_primary() {
+			let mut keyring = KeyRing::generate().unwrap();
+			let first = keyring.primary_key_id();
+			let second = keyring.rotate().unwrap();
+
+			assert!(first != second);
+			assert!(keyring.primary_key_id() == second);
+			assert_eq!(keyring.key_ids(), vec![first, second]);
+		}
+
+		#[test]
+		fn test_rotate_to_existing_key_err() {
+			let key = SecretKey::from_slice(&[1u8; 32]).unwrap();
+			let mut keyring = KeyRing::new(key.duplicate()).unwrap();
+			assert!(keyring.rotate().is_ok());
+
+			assert!(keyring.rotate_to(key).is_err());
+			assert!(keyring.rotate_to(SecretKey::generate(16).unwrap()).is_err());
+			assert_eq!(keyring.key_ids().len(), 2);
+		}
+	}
+
+	mod test_remove {
+		use super::*;
+
+		#[test]
+		fn test_remove() {
+			let mut keyring = KeyRing::generate().unwrap();
+			let first = keyring.primary_key_id();
+			let second = keyring.rotate().unwrap();
+
+			// The primary key cannot be removed.
+			assert!(keyring.remove(&second).is_err());
+			assert!(keyring.remove(&first).is_ok());
+			assert!(keyring.remove(&first).is_err());
+			assert_eq!(keyring.key_ids(), vec![second]);
+		}
+	}
+
+	mod test_seal_open {
+		use super::*;
+
+		#[test]
+		fn test_seal_open_after_rotation() {
+			let mut keyring = KeyRing::generate().unwrap();
+			let first = keyring.seal(b"Data").unwrap();
+			assert!(keyring.rotate().is_ok());
+			let second = keyring.seal(b"Data").unwrap();
+
+			assert_eq!(&first[..KEY_ID_SIZE], keyring.key_ids()[0].as_bytes());
+			assert_eq!(&second[..KEY_ID_SIZE], keyring.primary_key_id().as_bytes());
+			assert_eq!(keyring.open(&first).unwrap(), b"Data");
+			assert_eq!(keyring.open(&second).unwrap(), b"Data");
+		}
+
+		#[test]
+		fn test_open_unknown_key_err() {
+			let keyring = KeyRing::generate().unwrap();
+			let ciphertext = KeyRing::generate().unwrap().seal(b"Data").unwrap();
+			assert!(keyring.open(&ciphertext).is_err());
+		}
+
+		#[test]
+		fn test_open_modified_err() {
+			let keyring = KeyRing::generate().unwrap();
+			let mut ciphertext = keyring.seal(b"Data").unwrap();
+
+			assert!(keyring.open(&ciphertext[..KEY_ID_SIZE - 1]).is_err());
+			assert!(keyring.open(&ciphertext[..KEY_ID_SIZE]).is_err());
+
+			for idx in 0..ciphertext.len() {
+				ciphertext[idx] ^= 1;
+				assert!(keyring.open(&ciphertext).is_err());
+				ciphertext[idx] ^= 1;
+			}
+			assert_eq!(keyring.open(&ciphertext).unwrap(), b"Data");
+		}
+
+		#[test]
+		fn test_seal_empty_err() {
+			assert!(KeyRing::generate().unwrap().seal(b"").is_err());
+		}
+	}
+}
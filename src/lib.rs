@@ -26,6 +26,18 @@
 //! `orion::aead` offers authenticated secret-key encryption using
 //! XChaCha20Poly1305.
 //!
+//! ## Key rotation
+//! `orion::keyring` offers authenticated secret-key encryption with multiple
+//! keys, so that keys can be rotated.
+//!
+//! ## Envelope encryption
+//! `orion::envelope` encrypts data with a fresh data-encryption key, which is
+//! itself encrypted with a key-encryption key.
+//!
+//! ## Commitments
+//! `orion::commit` offers hash-based commitments to messages that can be
+//! revealed and verified later.
+//!
 //! ## Password hashing and verification
 //! `orion::pwhash` offers password hashing and verification using PBKDF2.
 //!
@@ -33,11 +45,57 @@
 //! `orion::kdf` offers key derivation using PBKDF2.
 //!
 //! ## Message authentication
-//! `orion::auth` offers message authentication and verification using HMAC.
+//! `orion::auth` offers message authentication and verification using HMAC,
+//! or keyed BLAKE2b with `orion::auth::blake2b`.
+//!
+//! ## Key exchange
+//! `orion::kex` derives a pair of session keys from a Diffie-Hellman shared
+//! secret, bound to both public keys and a transcript.
+//!
+//! ## Key files
+//! `orion::keyfile` stores a `SecretKey`, or a `KeyStore` of named keys with
+//! their metadata, on disk encrypted under a password, with the `keyfile`
+//! feature.
 //!
 //! ## Hashing
 //! `orion::hash` offers hashing using BLAKE2b.
 //!
+//! ## Tokens
+//! `orion::token` offers encrypted tokens with an expiry time, such as session
+//! cookies.
+//! `orion::paseto` offers PASETO v4.local tokens, with the `paseto` feature.
+//! `orion::jose` offers JWS tokens authenticated with HS256 or HS512, with the
+//! `jose` feature.
+//! `orion::cose` offers `COSE_Mac0` and `COSE_Encrypt0` messages, with the
+//! `cose` feature.
+//!
+//! ## Throttling
+//! `orion::throttle` pads verification calls to a minimum duration.
+//!
+//! ## WebAuthn
+//! `orion::webauthn` offers server-side checks of WebAuthn assertions, with the
+//! `webauthn` feature.
+//!
+//! ## Mnemonics
+//! `orion::mnemonic` offers BIP39 mnemonic encoding of secret keys, with the
+//! `mnemonic` feature.
+//!
+//! ## Self-tests
+//! `orion::self_test()` runs known-answer tests of the primitives, with the
+//! `self_test` feature.
+//!
+//! ## Test vectors
+//! `orion::test_framework` runs Wycheproof and NIST CAVP test vectors against
+//! constructions built on orion, with the `test_framework` feature.
+//!
+//! ## Timing tests
+//! `orion::timing` looks for timing leaks in constructions built on orion,
+//! with the `timing_tests` feature.
+//!
+//! ## Benchmarks
+//! `orion::bench` measures the throughput of orion's primitives at runtime,
+//! with the `bench` feature.
+//!
 //! ### A note on `no_std`:
 //! When orion is used in a `no_std` context, access to nearly all functionality
 //! outside of `hazardous`, is not available.
@@ -92,5 +150,62 @@ pub mod pwhash;
 #[cfg(feature = "safe_api")]
 pub mod kdf;
 
+#[cfg(feature = "safe_api")]
+pub mod keyring;
+
+#[cfg(feature = "safe_api")]
+pub mod kex;
+
+#[cfg(feature = "safe_api")]
+pub mod envelope;
+
+#[cfg(feature = "safe_api")]
+pub mod commit;
+
+#[cfg(feature = "safe_api")]
+pub mod token;
+
+#[cfg(feature = "safe_api")]
+pub mod throttle;
+
 #[cfg(feature = "safe_api")]
 mod hltypes;
+
+/// Constant-time Base64 and hex encoding and decoding.
+mod encoding;
+
+#[cfg(feature = "interop")]
+pub mod interop;
+
+#[cfg(feature = "paseto")]
+pub mod paseto;
+
+#[cfg(feature = "jose")]
+pub mod jose;
+
+#[cfg(feature = "keyfile")]
+pub mod keyfile;
+
+#[cfg(feature = "cose")]
+pub mod cose;
+
+#[cfg(feature = "webauthn")]
+pub mod webauthn;
+
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+
+#[cfg(feature = "self_test")]
+pub mod self_test;
+
+#[cfg(feature = "self_test")]
+pub use crate::self_test::self_test;
+
+#[cfg(feature = "test_framework")]
+pub mod test_framework;
+
+#[cfg(feature = "timing_tests")]
+pub mod timing;
+
+#[cfg(feature = "bench")]
+pub mod bench;
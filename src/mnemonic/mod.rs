@@ -0,0 +1,358 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Mnemonic encoding of secret keys.
+//!
+//! # Use case:
+//! `orion::mnemonic` can be used to turn a `SecretKey` into a list of words
+//! that can be written down by hand, and to turn those words back into the
+//! `SecretKey`.
+//!
+//! An example of this could be a backup key that is printed or written on
+//! paper, and stored offline.
+//!
+//! # About:
+//! - The encoding is the one specified in
+//!   [BIP39](https://github.com/bitcoin/bips/blob/master/bip-0039.mediawiki):
+//!   a checksum of the first `length / 4` bits of SHA256 of the key is
+//!   appended to it, and every 11 bits select a word from a list of 2048.
+//! - A `SecretKey` of 16, 20, 24, 28 or 32 bytes gives 12, 15, 18, 21 or 24
+//!   words, respectively.
+//! - Only the mnemonic encoding of BIP39 is provided. Seeds are not derived
+//!   from the words with a passphrase.
+//! - The word lists are available as `Language`s. Currently only English is
+//!   supported.
+//! - Words in a phrase given to `decode()` can be separated by any amount of
+//!   whitespace, but must otherwise match the word list exactly.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key to encode.
+//! - `phrase`: The words to decode, separated by whitespace.
+//! - `language`: The word list to use.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is not 16, 20, 24, 28 or 32 bytes.
+//! - `phrase` does not consist of 12, 15, 18, 21 or 24 words.
+//! - A word in `phrase` is not in the word list of `language`.
+//! - The checksum in `phrase` does not match the decoded key.
+//!
+//! # Security:
+//! - A `Mnemonic` is as sensitive as the `SecretKey` it encodes. It is zeroed
+//!   out when dropped, but the caller is responsible for any copies made of
+//!   `unprotected_as_str()`, and for the `phrase` passed to `decode()`.
+//! - The table lookups when encoding and decoding depend on the key, and are
+//!   not done in constant time. The encoding is meant for backing up keys, and
+//!   should not be done where an attacker can measure its timing.
+//! - The checksum only detects mistakes when copying the words. It gives no
+//!   protection against deliberate modification.
+//!
+//! # Example:
+//! ```
+//! use orion::mnemonic::{self, Language, SecretKey};
+//!
+//! let secret_key = SecretKey::default();
+//! let words = mnemonic::encode(&secret_key, Language::English).unwrap();
+//! assert_eq!(words.word_count(), 24);
+//!
+//! let decoded = mnemonic::decode(words.unprotected_as_str(), Language::English).unwrap();
+//! assert!(decoded == secret_key);
+//! ```
+
+/// The BIP39 English word list.
+mod english;
+
+pub use crate::hltypes::SecretKey;
+use crate::{errors::UnknownCryptoError, hazardous::hash::sha256, util};
+use zeroize::Zeroize;
+
+/// The number of bits encoded by each word.
+const WORD_BITS: usize = 11;
+
+/// The maximum number of bytes of a `SecretKey` and its checksum.
+const MAX_DATA_SIZE: usize = 33;
+
+/// The maximum number of words in a `Mnemonic`.
+const MAX_WORD_COUNT: usize = 24;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The languages of the word lists that a `Mnemonic` can be encoded with.
+pub enum Language {
+	/// The BIP39 English word list.
+	English,
+}
+
+impl Language {
+	/// Return the word list of the language.
+	fn words(self) -> &'static [&'static str; 2048] {
+		match self {
+			Language::English => &english::WORDS,
+		}
+	}
+}
+
+/// A type to represent the words of a `Mnemonic`, separated by single spaces.
+///
+/// # Security:
+/// - __**Avoid using**__ `unprotected_as_str()` whenever possible, as it
+///   breaks all protections that the type implements.
+pub struct Mnemonic {
+	value: Vec<u8>,
+}
+
+impl_omitted_debug_trait!(Mnemonic);
+impl_drop_trait!(Mnemonic);
+
+impl Mnemonic {
+	/// Return the words as a string slice. __**Warning**__: Should not be used
+	/// unless strictly needed. This __**breaks protections**__ that the type
+	/// implements.
+	pub fn unprotected_as_str(&self) -> &str {
+		// .unwrap() cannot panic since `value` is built from whole `&str`s.
+		core::str::from_utf8(&self.value).unwrap()
+	}
+
+	/// Return the number of words.
+	pub fn word_count(&self) -> usize { self.value.iter().filter(|b| **b == b' ').count() + 1 }
+}
+
+/// Return the `WORD_BITS` bits of `data` starting at bit `start`.
+fn read_bits(data: &[u8], start: usize) -> usize {
+	let mut bits = 0usize;
+	for bit in start..start + WORD_BITS {
+		bits = (bits << 1) | usize::from((data[bit / 8] >> (7 - bit % 8)) & 1);
+	}
+
+	bits
+}
+
+/// Write the `WORD_BITS` lowest bits of `bits` to `data`, starting at bit
+/// `start`.
+fn write_bits(data: &mut [u8], start: usize, bits: usize) {
+	for (idx, bit) in (start..start + WORD_BITS).enumerate() {
+		let value = ((bits >> (WORD_BITS - 1 - idx)) & 1) as u8;
+		data[bit / 8] |= value << (7 - bit % 8);
+	}
+}
+
+/// Return the mask for the checksum bits of a key of `length` bytes.
+fn checksum_mask(length: usize) -> u8 { 0xFFu8 << (8 - length / 4) }
+
+#[must_use]
+/// Encode `secret_key` as a `Mnemonic` using the word list of `language`.
+pub fn encode(secret_key: &SecretKey, language: Language) -> Result<Mnemonic, UnknownCryptoError> {
+	let length = secret_key.get_length();
+	if length < 16 || length > 32 || length % 4 != 0 {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut data = [0u8; MAX_DATA_SIZE];
+	data[..length].copy_from_slice(secret_key.unprotected_as_bytes());
+	data[length] = sha256::digest(secret_key.unprotected_as_bytes())?.as_bytes()[0];
+
+	let words = language.words();
+	let word_count = (length * 8 + length / 4) / WORD_BITS;
+	let mut value = Vec::with_capacity(word_count * 9);
+	for idx in 0..word_count {
+		if idx > 0 {
+			value.push(b' ');
+		}
+		value.extend_from_slice(words[read_bits(&data, idx * WORD_BITS)].as_bytes());
+	}
+	data.zeroize();
+
+	Ok(Mnemonic { value })
+}
+
+/// Decode the words of `phrase` into `data`, returning the length of the key.
+fn decode_into(
+	phrase: &str,
+	language: Language,
+	data: &mut [u8; MAX_DATA_SIZE],
+) -> Result<usize, UnknownCryptoError> {
+	let words = language.words();
+	let mut word_count = 0;
+
+	for word in phrase.split_whitespace() {
+		if word_count == MAX_WORD_COUNT {
+			return Err(UnknownCryptoError);
+		}
+		let bits = words.iter().position(|w| *w == word).ok_or(UnknownCryptoError)?;
+		write_bits(data, word_count * WORD_BITS, bits);
+		word_count += 1;
+	}
+
+	if word_count < 12 || word_count % 3 != 0 {
+		return Err(UnknownCryptoError);
+	}
+
+	let length = word_count * 4 / 3;
+	let mask = checksum_mask(length);
+	let checksum = sha256::digest(&data[..length])?.as_bytes()[0] & mask;
+	util::secure_cmp(&[data[length] & mask], &[checksum])?;
+
+	Ok(length)
+}
+
+#[must_use]
+/// Decode the words of `phrase` into a `SecretKey` using the word list of
+/// `language`.
+pub fn decode(phrase: &str, language: Language) -> Result<SecretKey, UnknownCryptoError> {
+	let mut data = [0u8; MAX_DATA_SIZE];
+	let secret_key = match decode_into(phrase, language, &mut data) {
+		Ok(length) => SecretKey::from_slice(&data[..length]),
+		Err(err) => Err(err),
+	};
+	data.zeroize();
+
+	secret_key
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	/// Test vectors from the BIP39 reference implementation.
+	const VECTORS: [(&str, &str); 5] = [
+		(
+			"00000000000000000000000000000000",
+			"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+			 abandon about",
+		),
+		(
+			"7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+			"legal winner thank year wave sausage worth useful legal winner thank yellow",
+		),
+		(
+			"ffffffffffffffffffffffffffffffff",
+			"zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+		),
+		(
+			"808080808080808080808080808080808080808080808080",
+			"letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd \
+			 amount doctor acoustic avoid letter always",
+		),
+		(
+			"9e885d952ad362caeb4efe34a8e91bd2",
+			"ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic",
+		),
+	];
+
+	fn key_from_hex(hex: &str) -> SecretKey {
+		let bytes: Vec<u8> = (0..hex.len())
+			.step_by(2)
+			.map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+			.collect();
+
+		SecretKey::from_slice(&bytes).unwrap()
+	}
+
+	// One function tested per submodule.
+	mod test_encode {
+		use super::*;
+
+		#[test]
+		fn test_vectors() {
+			for (key, phrase) in VECTORS.iter() {
+				let mnemonic = encode(&key_from_hex(key), Language::English).unwrap();
+				assert_eq!(mnemonic.unprotected_as_str(), *phrase);
+			}
+		}
+
+		#[test]
+		fn test_key_lengths() {
+			for length in 0..40 {
+				let secret_key = SecretKey::generate(length.max(1)).unwrap();
+				let mnemonic = encode(&secret_key, Language::English);
+				if length >= 16 && length <= 32 && length % 4 == 0 {
+					assert_eq!(mnemonic.unwrap().word_count(), length * 3 / 4);
+				} else {
+					assert!(mnemonic.is_err());
+				}
+			}
+		}
+
+		#[test]
+		fn test_debug_omitted() {
+			let mnemonic = encode(&SecretKey::default(), Language::English).unwrap();
+			assert_eq!(format!("{:?}", mnemonic), "Mnemonic {***OMITTED***}");
+		}
+	}
+
+	mod test_decode {
+		use super::*;
+
+		#[test]
+		fn test_vectors() {
+			for (key, phrase) in VECTORS.iter() {
+				assert!(decode(phrase, Language::English).unwrap() == key_from_hex(key));
+			}
+		}
+
+		#[test]
+		fn test_roundtrip() {
+			for length in [16, 20, 24, 28, 32].iter() {
+				let secret_key = SecretKey::generate(*length).unwrap();
+				let mnemonic = encode(&secret_key, Language::English).unwrap();
+				let decoded = decode(mnemonic.unprotected_as_str(), Language::English).unwrap();
+				assert!(decoded == secret_key);
+			}
+		}
+
+		#[test]
+		fn test_whitespace() {
+			let phrase = "  zoo zoo\tzoo zoo zoo zoo\nzoo zoo zoo zoo  zoo wrong\n";
+			assert!(decode(phrase, Language::English).is_ok());
+		}
+
+		#[test]
+		fn test_bad_checksum_err() {
+			let phrase = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo";
+			assert!(decode(phrase, Language::English).is_err());
+			let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+			              abandon abandon abandon";
+			assert!(decode(phrase, Language::English).is_err());
+		}
+
+		#[test]
+		fn test_unknown_word_err() {
+			let phrase = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo Zoo wrong";
+			assert!(decode(phrase, Language::English).is_err());
+			let phrase = "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zo wrong";
+			assert!(decode(phrase, Language::English).is_err());
+		}
+
+		#[test]
+		fn test_word_count_err() {
+			assert!(decode("", Language::English).is_err());
+			let phrase = "zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong";
+			assert!(decode(phrase, Language::English).is_err());
+
+			let mnemonic = encode(&SecretKey::default(), Language::English).unwrap();
+			let too_long = format!("{} zoo", mnemonic.unprotected_as_str());
+			assert!(decode(&too_long, Language::English).is_err());
+			let too_long = format!("{} zoo zoo zoo", mnemonic.unprotected_as_str());
+			assert!(decode(&too_long, Language::English).is_err());
+		}
+	}
+}
@@ -0,0 +1,99 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! PASETO (Platform-Agnostic Security Tokens).
+//!
+//! # Use case:
+//! `orion::paseto` can be used to create tamper-proof tokens, such as session
+//! or API tokens, where the payload is either encrypted or signed.
+//!
+//! # About:
+//! - Implements [PASETO version 4](https://github.com/paseto-standard/paseto-spec/blob/master/docs/01-Protocol-Versions/Version4.md).
+//! - Only the token format is handled here. The payload and footer are
+//!   passed as bytes, and parsing and validating claims is left to the caller.
+//! - This module is only available with the `paseto` feature, which requires
+//!   `safe_api`.
+
+/// PASETO v4.local tokens, encrypted with XChaCha20 and authenticated with
+/// keyed BLAKE2b.
+pub mod v4_local;
+
+/// Encode `n` as 64-bit unsigned little-endian integer, with the most
+/// significant bit cleared.
+fn le64(n: u64) -> [u8; 8] {
+	let mut out = [0u8; 8];
+	crate::endianness::store_u64_into_le(&[n & 0x7fff_ffff_ffff_ffff], &mut out);
+
+	out
+}
+
+/// Pre-Authentication Encoding (PAE) of `pieces` as defined in the PASETO
+/// specification.
+pub(crate) fn pae(pieces: &[&[u8]]) -> Vec<u8> {
+	let mut encoded = Vec::new();
+	encoded.extend_from_slice(&le64(pieces.len() as u64));
+
+	for piece in pieces.iter() {
+		encoded.extend_from_slice(&le64(piece.len() as u64));
+		encoded.extend_from_slice(piece);
+	}
+
+	encoded
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_pae {
+		use super::*;
+
+		#[test]
+		fn test_spec_examples() {
+			assert_eq!(pae(&[]), [0u8; 8]);
+			assert_eq!(
+				pae(&[b""]),
+				[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+			);
+			assert_eq!(
+				pae(&[b"test"]),
+				[1, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, b't', b'e', b's', b't']
+			);
+		}
+	}
+
+	mod test_le64 {
+		use super::*;
+
+		#[test]
+		fn test_msb_cleared() {
+			assert_eq!(le64(0), [0u8; 8]);
+			assert_eq!(le64(1), [1, 0, 0, 0, 0, 0, 0, 0]);
+			assert_eq!(
+				le64(u64::max_value()),
+				[255, 255, 255, 255, 255, 255, 255, 127]
+			);
+		}
+	}
+}
@@ -0,0 +1,406 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! PASETO v4.local tokens.
+//!
+//! # Use case:
+//! `orion::paseto::v4_local` can be used to create tokens whose payload is
+//! encrypted and authenticated with a symmetric key, so that it can only be
+//! read and issued by parties that hold that key.
+//!
+//! # About:
+//! - The payload is encrypted with XChaCha20 and the token is authenticated
+//!   with keyed BLAKE2b, as specified for
+//!   [v4.local](https://github.com/paseto-standard/paseto-spec/blob/master/docs/01-Protocol-Versions/Version4.md#encrypt).
+//! - A random 32-byte nonce is generated for each token.
+//! - The footer is stored unencrypted, but authenticated, in the token.
+//! - The implicit assertion is authenticated, but not stored in the token.
+//!   The same implicit assertion must be given when decrypting.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `message`: The payload to encrypt.
+//! - `token`: The token to decrypt.
+//! - `footer`: The footer to store in the token. When decrypting, the footer
+//!   that is expected to be in the token.
+//! - `implicit_assertion`: Additional data that is authenticated, but not
+//!   stored in the token.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is not 32 bytes.
+//! - `token` does not start with `v4.local.`.
+//! - `token` is not validly Base64 encoded or is too short.
+//! - The footer in `token` does not match `footer`.
+//! - The token fails authentication, for example because it was modified, or
+//!   because `secret_key` or `implicit_assertion` is not the one used when
+//!   encrypting.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - The secret key should always be generated using a CSPRNG.
+//!   `SecretKey::default()` can be used for this.
+//! - The `secret_key` must only be used for v4.local tokens, and not with
+//!   other algorithms.
+//! - Tokens have no expiry by themselves. Expiry and other claims in the
+//!   payload must be validated by the caller after decryption.
+//!
+//! # Example:
+//! ```
+//! use orion::paseto::v4_local;
+//!
+//! let key = v4_local::SecretKey::default();
+//! let token = v4_local::encrypt(&key, b"{\"sub\":\"user\"}", b"key-id", b"").unwrap();
+//!
+//! let payload = v4_local::decrypt(&key, &token, b"key-id", b"").unwrap();
+//! assert_eq!(payload, b"{\"sub\":\"user\"}");
+//! ```
+
+pub use crate::hltypes::SecretKey;
+use crate::{
+	encoding::{base64_decode, base64_encode},
+	errors::UnknownCryptoError,
+	hazardous::{
+		hash::blake2b,
+		stream::{chacha20, xchacha20},
+	},
+	paseto::pae,
+	util,
+};
+
+/// The header of v4.local tokens.
+const HEADER: &str = "v4.local.";
+
+/// The size of the random nonce stored in a token.
+const NONCE_SIZE: usize = 32;
+
+/// The size of the authentication tag stored in a token.
+const TAG_SIZE: usize = 32;
+
+/// The keys derived from the secret key and nonce of a token.
+struct TokenKeys {
+	encryption_key: chacha20::SecretKey,
+	encryption_nonce: xchacha20::Nonce,
+	authentication_key: blake2b::SecretKey,
+}
+
+/// Derive the encryption key, encryption nonce and authentication key from
+/// `secret_key` and `nonce`.
+fn derive_keys(secret_key: &SecretKey, nonce: &[u8]) -> Result<TokenKeys, UnknownCryptoError> {
+	if secret_key.get_length() != 32 {
+		return Err(UnknownCryptoError);
+	}
+	let key = blake2b::SecretKey::from_slice(secret_key.unprotected_as_bytes())?;
+
+	let mut state = blake2b::init(Some(&key), 56)?;
+	state.update(b"paseto-encryption-key")?;
+	state.update(nonce)?;
+	let tmp = state.finalize()?;
+
+	let mut state = blake2b::init(Some(&key), 32)?;
+	state.update(b"paseto-auth-key-for-aead")?;
+	state.update(nonce)?;
+	let authentication_key = state.finalize()?;
+
+	Ok(TokenKeys {
+		encryption_key: chacha20::SecretKey::from_slice(&tmp.as_bytes()[..32])?,
+		encryption_nonce: xchacha20::Nonce::from_slice(&tmp.as_bytes()[32..])?,
+		authentication_key: blake2b::SecretKey::from_slice(authentication_key.as_bytes())?,
+	})
+}
+
+/// Calculate the authentication tag of a token.
+fn authenticate(
+	keys: &TokenKeys,
+	nonce: &[u8],
+	ciphertext: &[u8],
+	footer: &[u8],
+	implicit_assertion: &[u8],
+) -> Result<blake2b::Digest, UnknownCryptoError> {
+	let pre_auth = pae(&[
+		HEADER.as_bytes(),
+		nonce,
+		ciphertext,
+		footer,
+		implicit_assertion,
+	]);
+
+	let mut state = blake2b::init(Some(&keys.authentication_key), TAG_SIZE)?;
+	state.update(&pre_auth)?;
+
+	Ok(state.finalize()?)
+}
+
+/// XOR `src` with the XChaCha20 keystream into `dst_out`. Empty input is
+/// passed through.
+fn xor_keystream(
+	keys: &TokenKeys,
+	src: &[u8],
+	dst_out: &mut [u8],
+) -> Result<(), UnknownCryptoError> {
+	if src.is_empty() {
+		return Ok(());
+	}
+
+	xchacha20::encrypt(
+		&keys.encryption_key,
+		&keys.encryption_nonce,
+		0,
+		src,
+		dst_out,
+	)
+}
+
+/// Create a v4.local token using a given `nonce`.
+fn encrypt_with_nonce(
+	secret_key: &SecretKey,
+	nonce: &[u8],
+	message: &[u8],
+	footer: &[u8],
+	implicit_assertion: &[u8],
+) -> Result<String, UnknownCryptoError> {
+	let keys = derive_keys(secret_key, nonce)?;
+
+	let mut payload = vec![0u8; NONCE_SIZE + message.len() + TAG_SIZE];
+	payload[..NONCE_SIZE].copy_from_slice(nonce);
+	xor_keystream(
+		&keys,
+		message,
+		&mut payload[NONCE_SIZE..NONCE_SIZE + message.len()],
+	)?;
+
+	let tag = authenticate(
+		&keys,
+		nonce,
+		&payload[NONCE_SIZE..NONCE_SIZE + message.len()],
+		footer,
+		implicit_assertion,
+	)?;
+	payload[NONCE_SIZE + message.len()..].copy_from_slice(tag.as_bytes());
+
+	let mut token = String::from(HEADER);
+	token.push_str(&base64_encode(&payload, true));
+	if !footer.is_empty() {
+		token.push('.');
+		token.push_str(&base64_encode(footer, true));
+	}
+
+	Ok(token)
+}
+
+#[must_use]
+/// Encrypt `message` into a v4.local token.
+pub fn encrypt(
+	secret_key: &SecretKey,
+	message: &[u8],
+	footer: &[u8],
+	implicit_assertion: &[u8],
+) -> Result<String, UnknownCryptoError> {
+	let mut nonce = [0u8; NONCE_SIZE];
+	util::secure_rand_bytes(&mut nonce)?;
+
+	encrypt_with_nonce(secret_key, &nonce, message, footer, implicit_assertion)
+}
+
+#[must_use]
+/// Authenticate and decrypt a v4.local `token`, returning its payload.
+pub fn decrypt(
+	secret_key: &SecretKey,
+	token: &str,
+	footer: &[u8],
+	implicit_assertion: &[u8],
+) -> Result<Vec<u8>, UnknownCryptoError> {
+	if !token.starts_with(HEADER) {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut parts = token[HEADER.len()..].split('.');
+	let payload = base64_decode(parts.next().ok_or(UnknownCryptoError)?, true)?;
+	let token_footer = match parts.next() {
+		Some(encoded) if !encoded.is_empty() => base64_decode(encoded, true)?,
+		Some(_) => return Err(UnknownCryptoError),
+		None => Vec::new(),
+	};
+	if parts.next().is_some() || token_footer.len() != footer.len() {
+		return Err(UnknownCryptoError);
+	}
+	if !footer.is_empty() {
+		util::secure_cmp(&token_footer, footer)?;
+	}
+
+	if payload.len() < NONCE_SIZE + TAG_SIZE {
+		return Err(UnknownCryptoError);
+	}
+	let (nonce, rest) = payload.split_at(NONCE_SIZE);
+	let (ciphertext, tag) = rest.split_at(rest.len() - TAG_SIZE);
+
+	let keys = derive_keys(secret_key, nonce)?;
+	let expected_tag = authenticate(&keys, nonce, ciphertext, footer, implicit_assertion)?;
+	util::secure_cmp(expected_tag.as_bytes(), tag)?;
+
+	let mut message = vec![0u8; ciphertext.len()];
+	xor_keystream(&keys, ciphertext, &mut message)?;
+
+	Ok(message)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_encrypt_decrypt {
+		use super::*;
+
+		#[test]
+		fn test_roundtrip() {
+			let key = SecretKey::default();
+
+			let token = encrypt(&key, b"Message", b"", b"").unwrap();
+			assert!(token.starts_with("v4.local."));
+			assert_eq!(token.matches('.').count(), 2);
+			assert_eq!(decrypt(&key, &token, b"", b"").unwrap(), b"Message");
+
+			let token = encrypt(&key, b"Message", b"Footer", b"Assertion").unwrap();
+			assert_eq!(token.matches('.').count(), 3);
+			assert_eq!(
+				decrypt(&key, &token, b"Footer", b"Assertion").unwrap(),
+				b"Message"
+			);
+		}
+
+		#[test]
+		fn test_empty_message() {
+			let key = SecretKey::default();
+			let token = encrypt(&key, b"", b"", b"").unwrap();
+			assert!(decrypt(&key, &token, b"", b"").unwrap().is_empty());
+		}
+
+		#[test]
+		fn test_random_nonce() {
+			let key = SecretKey::default();
+			assert_ne!(
+				encrypt(&key, b"Message", b"", b"").unwrap(),
+				encrypt(&key, b"Message", b"", b"").unwrap()
+			);
+		}
+
+		#[test]
+		fn test_bad_key_length_err() {
+			let key = SecretKey::generate(64).unwrap();
+			assert!(encrypt(&key, b"Message", b"", b"").is_err());
+
+			let token = encrypt(&SecretKey::default(), b"Message", b"", b"").unwrap();
+			assert!(decrypt(&key, &token, b"", b"").is_err());
+		}
+
+		#[test]
+		fn test_mismatch_err() {
+			let key = SecretKey::default();
+			let token = encrypt(&key, b"Message", b"Footer", b"Assertion").unwrap();
+
+			assert!(decrypt(&SecretKey::default(), &token, b"Footer", b"Assertion").is_err());
+			assert!(decrypt(&key, &token, b"Other", b"Assertion").is_err());
+			assert!(decrypt(&key, &token, b"", b"Assertion").is_err());
+			assert!(decrypt(&key, &token, b"Footer", b"").is_err());
+		}
+
+		#[test]
+		fn test_malformed_token_err() {
+			let key = SecretKey::default();
+			let token = encrypt(&key, b"Message", b"", b"").unwrap();
+
+			assert!(decrypt(&key, &token.replace("v4.local.", "v4.public."), b"", b"").is_err());
+			assert!(decrypt(&key, &token.replace("v4.local.", "v3.local."), b"", b"").is_err());
+			assert!(decrypt(&key, &format!("{}.", token), b"", b"").is_err());
+			assert!(decrypt(&key, &format!("{}.Zm9v.Zm9v", token), b"", b"").is_err());
+			assert!(decrypt(&key, &token[..token.len() - 1], b"", b"").is_err());
+			assert!(decrypt(&key, "v4.local.", b"", b"").is_err());
+			assert!(decrypt(&key, "v4.local.AAAA", b"", b"").is_err());
+		}
+
+		#[test]
+		fn test_modified_token_err() {
+			let key = SecretKey::default();
+			let token = encrypt(&key, b"Message", b"", b"").unwrap();
+
+			let mut payload = base64_decode(&token[HEADER.len()..], true).unwrap();
+			for idx in 0..payload.len() {
+				payload[idx] ^= 1;
+				let modified = format!("{}{}", HEADER, base64_encode(&payload, true));
+				assert!(decrypt(&key, &modified, b"", b"").is_err());
+				payload[idx] ^= 1;
+			}
+		}
+	}
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_encrypt_with_nonce {
+		use super::*;
+
+		const NONCE: [u8; NONCE_SIZE] = [
+			0xdf, 0x65, 0x48, 0x12, 0xba, 0xc4, 0x92, 0x66, 0x38, 0x25, 0x52, 0x0b, 0xa2, 0xf6,
+			0xe6, 0x7c, 0xf5, 0xca, 0x5b, 0xdc, 0x13, 0xd4, 0xe7, 0x50, 0x7a, 0x98, 0xcc, 0x4c,
+			0x2f, 0xcc, 0x3a, 0xd8,
+		];
+
+		fn spec_key() -> SecretKey {
+			SecretKey::from_slice(&(0x70u8..0x90).collect::<Vec<u8>>()).unwrap()
+		}
+
+		#[test]
+		fn test_spec_vector_4_e_3() {
+			assert_eq!(
+				encrypt_with_nonce(
+					&spec_key(),
+					&NONCE,
+					b"{\"data\":\"this is a secret message\",\"exp\":\"2022-01-01T00:00:00+00:00\"}",
+					b"",
+					b""
+				)
+				.unwrap(),
+				"v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WkwMsYXw6FSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t6-tyebyWG6Ov7kKvBdkrrAJ837lKP3iDag2hzUPHuMKA"
+			);
+		}
+
+		#[test]
+		fn test_spec_vector_4_e_7() {
+			assert_eq!(
+				encrypt_with_nonce(
+					&spec_key(),
+					&NONCE,
+					b"{\"data\":\"this is a secret message\",\"exp\":\"2022-01-01T00:00:00+00:00\"}",
+					b"{\"kid\":\"zVhMiPBP9fRf2snEcT7gFTioeA9COcNy9DfgL1W60haN\"}",
+					b"{\"test-vector\":\"4-E-7\"}"
+				)
+				.unwrap(),
+				"v4.local.32VIErrEkmY4JVILovbmfPXKW9wT1OdQepjMTC_MOtjA4kiqw7_tcaOM5GNEcnTxl60WkwMsYXw6FSNb_UdJPXjpzm0KW9ojM5f4O2mRvE2IcweP-PRdoHjd5-RHCiExR1IK6t40KCCWLA7GYL9KFHzKlwY9_RnIfRrMQpueydLEAZGGcA.eyJraWQiOiJ6VmhNaVBCUDlmUmYyc25FY1Q3Z0ZUaW9lQTlDT2NOeTlEZmdMMVc2MGhhTiJ9"
+			);
+		}
+	}
+}
@@ -31,34 +31,82 @@
 //! in the user's actual passwords being disclosed as well.
 //!
 //! # About:
-//! - Uses PBKDF2-HMAC-SHA512.
-//! - A salt of 64 bytes is automatically generated.
+//! - Uses PBKDF2-HMAC-SHA512 by default.
+//! - A salt of 16 bytes is automatically generated.
 //! - The password hash length is set to 64.
 //!
-//! The first 64 bytes of the `PasswordHash` returned by `pwhash::hash_password`
-//! is the salt used to hash the password and the last 64 bytes is the actual
-//! hashed password. When using this function with
-//! `pwhash::hash_password_verify()`, then the separation of the salt and the
-//! password hash is automatically handled.
+//! The `PasswordHash` returned by `pwhash::hash_password` is encoded in the
+//! [PHC string format](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md),
+//! which contains the algorithm, the iteration count, the salt and the actual
+//! hashed password:
+//! ```text
+//! $pbkdf2-sha512$i=100000,l=64$<salt>$<hash>
+//! ```
+//! The salt and hash are encoded as Base64 without padding. This encoded form
+//! is what should be stored, and it can be turned back into a `PasswordHash`
+//! with `PasswordHash::from_encoded()`.
+//!
+//! Password hashes produced by earlier versions, which are the 64-byte salt
+//! followed by the 64-byte password hash and do not store the iteration
+//! count, can be verified with `PasswordHash::from_legacy_bytes()`.
+//! `PasswordHash::from_stored()` accepts either encoding, so a database that
+//! holds both can be read without knowing which one each entry uses.
+//!
+//! Because the algorithm and parameters are part of the encoding,
+//! `pwhash::hash_password_verify()` always verifies with the ones a
+//! `PasswordHash` was computed with. Stored password hashes therefore keep
+//! verifying when the default algorithm or parameters change.
+//! `pwhash::hash_password_with_params()` hashes with a specific `Algorithm`.
+//!
+//! `pwhash::Params::calibrate()` can be used to find an iteration count that
+//! makes hashing take a given amount of time on the current machine.
+//!
+//! When the iteration count is increased over time, `PasswordHash::needs_rehash()`
+//! tells whether a stored password hash was computed with outdated parameters.
+//! `pwhash::verify_and_upgrade()` verifies a password and, if it is correct but
+//! the stored hash is outdated, returns a new `PasswordHash` to store in its place.
+//! `pwhash::migrate()` verifies a password against a stored password hash in
+//! either encoding and always returns a new `PasswordHash` computed with the
+//! given parameters.
 //!
 //! # Parameters:
 //! - `password`: The password to be hashed.
-//! - `expected_with_salt`: The expected password hash with the corresponding
-//!   salt prepended.
+//! - `expected`: The expected password hash.
 //! - `iterations`: The number of iterations performed by PBKDF2, i.e. the cost
 //!   parameter.
+//! - `params`: The parameters to hash with, or that `expected` is checked
+//!   against. `Params::interactive()`, `Params::moderate()` and
+//!   `Params::sensitive()` are recommended presets.
 //!
 //! # Errors:
 //! An error will be returned if:
-//! - `iterations` is 0.
+//! - `iterations` is 0 or greater than `u32::max_value()`.
 //! - The `OsRng` fails to initialize or read from its source.
-//! - The `expected_with_salt` is not constructed exactly as in
-//!   `pwhash::hash_password`.
-//! - The password hash does not match `expected_with_salt`.
+//! - The password hash does not match `expected`.
+//! - The output length of `params` is less than 16 or greater than 64.
+//!
+//! An error will be returned by `PasswordHash::from_encoded()` if:
+//! - The algorithm is not a supported `Algorithm`.
+//! - The iteration count is 0 or greater than `u32::max_value()`.
+//! - The salt is less than 16 or greater than 64 bytes.
+//! - The hash is less than 16 or greater than 64 bytes.
+//! - The encoded string is malformed in any other way.
+//!
+//! An error will be returned by `PasswordHash::from_legacy_bytes()` if:
+//! - The password hash is not exactly 128 bytes.
+//! - `iterations` is 0 or greater than `u32::max_value()`.
+//!
+//! An error will be returned by `PasswordHash::from_stored()` if it is neither
+//! a valid encoding in the PHC string format nor a valid legacy password hash.
 //!
 //! # Security:
 //! - The iteration count should be set as high as feasible. The recommended
 //!   minimum is 100000.
+//! - A password is hashed as the exact bytes it is made from. A password
+//!   typed on platforms that produce different Unicode forms, such as
+//!   precomposed and decomposed accents, will not verify unless it is
+//!   normalized first, with `Password::from_str_normalized()` or
+//!   `pwhash::saslprep::prepare()` and the `password_normalization` feature.
 //!
 //! # Example:
 //! ```
@@ -67,67 +115,553 @@
 //! let password = pwhash::Password::from_slice(b"Secret password").unwrap();
 //!
 //! let hash = pwhash::hash_password(&password, 100000).unwrap();
-//! assert!(pwhash::hash_password_verify(&hash, &password, 100000).unwrap());
+//! assert!(pwhash::hash_password_verify(&hash, &password).unwrap());
+//!
+//! // Store the encoded password hash and verify against it later.
+//! let stored: String = hash.unprotected_as_encoded().to_string();
+//! let from_storage = pwhash::PasswordHash::from_encoded(&stored).unwrap();
+//! assert!(pwhash::hash_password_verify(&from_storage, &password).unwrap());
+//!
+//! // Later, the iteration count is increased.
+//! let params = pwhash::Params::new(200000).unwrap();
+//! if let Some(upgraded) = pwhash::verify_and_upgrade(&from_storage, &password, &params).unwrap() {
+//! 	// Replace the stored password hash with `upgraded`.
+//! 	assert!(!upgraded.needs_rehash(&params));
+//! }
 //! ```
 
-pub use crate::hltypes::{Password, PasswordHash};
+#[cfg(feature = "password_normalization")]
+pub use crate::hltypes::Normalization;
+pub use crate::hltypes::{Algorithm, Params, Password};
 use crate::{
+	encoding::{base64_decode, base64_encode},
 	errors::{UnknownCryptoError, ValidationCryptoError},
-	hazardous::kdf::pbkdf2,
 	util,
 };
 use zeroize::Zeroize;
 
+#[cfg(feature = "password_normalization")]
+/// SASLprep normalization of passwords.
+pub mod saslprep;
+
+/// The length of the salt generated by `hash_password()`.
+const SALT_LENGTH: usize = 16;
+
+/// The minimum length of a salt or password hash in an encoded `PasswordHash`.
+const MIN_ENCODED_LENGTH: usize = 16;
+
+/// The maximum length of a salt or password hash in an encoded `PasswordHash`.
+const MAX_ENCODED_LENGTH: usize = 64;
+
+/// The length of the salt in a password hash produced before the PHC string
+/// format was used.
+const LEGACY_SALT_LENGTH: usize = 64;
+
+/// The length of a password hash produced before the PHC string format was
+/// used, including the salt.
+const LEGACY_LENGTH: usize = 128;
+
+#[must_use]
+/// A type to represent the `PasswordHash` that is returned when used for password hashing.
+///
+/// The `PasswordHash` holds the encoding in the PHC string format, as well as the parameters,
+/// salt and the actual password hash decoded from it.
+///
+/// # Exceptions:
+/// An exception will be thrown if:
+/// - The encoded password hash is not a valid PHC string of a supported `Algorithm`.
+///
+/// # Security:
+/// - __**Avoid using**__ `unprotected_as_bytes()` and `unprotected_as_encoded()` whenever
+///   possible, as they break all protections that the type implements.
+pub struct PasswordHash {
+	encoded_password_hash: String,
+	algorithm: Algorithm,
+	password_hash: Vec<u8>,
+	salt: Vec<u8>,
+	iterations: usize,
+}
+
+impl core::fmt::Debug for PasswordHash {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		write!(f, "PasswordHash {{***OMITTED***}}")
+	}
+}
+
+impl PartialEq for PasswordHash {
+	fn eq(&self, other: &PasswordHash) -> bool {
+		use subtle::ConstantTimeEq;
+		self.unprotected_as_encoded()
+			.as_bytes()
+			.ct_eq(other.unprotected_as_encoded().as_bytes())
+			.unwrap_u8()
+			== 1
+	}
+}
+
+impl Drop for PasswordHash {
+	fn drop(&mut self) {
+		let encoded = core::mem::replace(&mut self.encoded_password_hash, String::new());
+		encoded.into_bytes().zeroize();
+		self.password_hash.zeroize();
+		self.salt.zeroize();
+	}
+}
+
+impl PasswordHash {
+	/// Construct a `PasswordHash` and its encoding from the parameters, salt
+	/// and actual password hash.
+	fn from_parts(
+		algorithm: Algorithm,
+		iterations: usize,
+		salt: &[u8],
+		password_hash: &[u8],
+	) -> Result<PasswordHash, UnknownCryptoError> {
+		if iterations < 1 || iterations > (u32::max_value() as usize) {
+			return Err(UnknownCryptoError);
+		}
+		if salt.len() < MIN_ENCODED_LENGTH || salt.len() > MAX_ENCODED_LENGTH {
+			return Err(UnknownCryptoError);
+		}
+		if password_hash.len() < MIN_ENCODED_LENGTH || password_hash.len() > MAX_ENCODED_LENGTH {
+			return Err(UnknownCryptoError);
+		}
+
+		let encoded_password_hash = format!(
+			"${}$i={},l={}${}${}",
+			algorithm.identifier(),
+			iterations,
+			password_hash.len(),
+			base64_encode(salt, false),
+			base64_encode(password_hash, false)
+		);
+
+		Ok(PasswordHash {
+			encoded_password_hash,
+			algorithm,
+			password_hash: password_hash.to_vec(),
+			salt: salt.to_vec(),
+			iterations,
+		})
+	}
+
+	#[must_use]
+	/// Construct a `PasswordHash` from its encoding in the PHC string format.
+	pub fn from_encoded(password_hash: &str) -> Result<PasswordHash, UnknownCryptoError> {
+		let parts: Vec<&str> = password_hash.split('$').collect();
+		if parts.len() != 5 || !parts[0].is_empty() {
+			return Err(UnknownCryptoError);
+		}
+		let algorithm = Algorithm::from_identifier(parts[1])?;
+
+		let mut iterations: Option<usize> = None;
+		let mut length: Option<usize> = None;
+		for param in parts[2].split(',') {
+			let (name, value) = match param.find('=') {
+				Some(idx) => (&param[..idx], parse_decimal(&param[(idx + 1)..])?),
+				None => return Err(UnknownCryptoError),
+			};
+
+			match name {
+				"i" if iterations.is_none() => iterations = Some(value),
+				"l" if length.is_none() => length = Some(value),
+				_ => return Err(UnknownCryptoError),
+			}
+		}
+
+		let salt = base64_decode(parts[3], false)?;
+		let hash = base64_decode(parts[4], false)?;
+		let iterations = match (iterations, length) {
+			(Some(iterations), Some(length)) if length == hash.len() => iterations,
+			(Some(iterations), None) => iterations,
+			_ => return Err(UnknownCryptoError),
+		};
+
+		let mut decoded = PasswordHash::from_parts(algorithm, iterations, &salt, &hash)?;
+		// Keep the encoding as it was given, since the length parameter is optional.
+		decoded.encoded_password_hash = password_hash.to_string();
+
+		Ok(decoded)
+	}
+
+	#[must_use]
+	/// Construct a `PasswordHash` from the 128 bytes that `hash_password()`
+	/// returned before the PHC string format was used: the 64-byte salt
+	/// followed by the 64-byte PBKDF2-HMAC-SHA512 password hash. These do not
+	/// contain the iteration count, so it must be given as `iterations`.
+	///
+	/// The returned `PasswordHash` is encoded in the PHC string format, and
+	/// `needs_rehash()` is always true for it.
+	pub fn from_legacy_bytes(
+		password_hash: &[u8],
+		iterations: usize,
+	) -> Result<PasswordHash, UnknownCryptoError> {
+		if password_hash.len() != LEGACY_LENGTH {
+			return Err(UnknownCryptoError);
+		}
+
+		PasswordHash::from_parts(
+			Algorithm::Pbkdf2Sha512,
+			iterations,
+			&password_hash[..LEGACY_SALT_LENGTH],
+			&password_hash[LEGACY_SALT_LENGTH..],
+		)
+	}
+
+	#[must_use]
+	/// Construct a `PasswordHash` from a stored password hash that is either
+	/// encoded in the PHC string format or, if that fails, the 128 bytes
+	/// accepted by `from_legacy_bytes()`. `legacy_iterations` is only used for
+	/// the latter, which does not contain the iteration count.
+	pub fn from_stored(
+		stored: &[u8],
+		legacy_iterations: usize,
+	) -> Result<PasswordHash, UnknownCryptoError> {
+		if let Ok(encoded) = core::str::from_utf8(stored) {
+			if let Ok(password_hash) = PasswordHash::from_encoded(encoded) {
+				return Ok(password_hash);
+			}
+		}
+
+		PasswordHash::from_legacy_bytes(stored, legacy_iterations)
+	}
+
+	#[must_use]
+	/// Return the encoding of the `PasswordHash` in the PHC string format.
+	/// __**Warning**__: Should not be used unless strictly needed, such as when
+	/// storing the password hash. This __**breaks protections**__ that the type
+	/// implements.
+	pub fn unprotected_as_encoded(&self) -> &str { &self.encoded_password_hash }
+
+	#[must_use]
+	/// Return the actual password hash as byte slice, without the parameters
+	/// and salt. __**Warning**__: Should not be used unless strictly needed.
+	/// This __**breaks protections**__ that the type implements.
+	pub fn unprotected_as_bytes(&self) -> &[u8] { &self.password_hash }
+
+	/// Return the length of the actual password hash.
+	pub fn get_length(&self) -> usize { self.password_hash.len() }
+
+	/// Return the algorithm that was used to compute the password hash.
+	pub fn algorithm(&self) -> Algorithm { self.algorithm }
+
+	/// Return the iteration count that was used to compute the password hash.
+	pub fn iterations(&self) -> usize { self.iterations }
+
+	/// Check whether the `PasswordHash` was computed with parameters other than
+	/// `params`, or with a salt length other than what `hash_password()`
+	/// produces, in which case the password should be hashed again.
+	pub fn needs_rehash(&self, params: &Params) -> bool {
+		self.algorithm != params.algorithm()
+			|| self.iterations != params.iterations()
+			|| self.salt.len() != SALT_LENGTH
+			|| self.password_hash.len() != params.output_length()
+	}
+}
+
+/// Parse a decimal parameter value in the PHC string format, which must not
+/// have leading zeroes or a sign.
+fn parse_decimal(value: &str) -> Result<usize, UnknownCryptoError> {
+	if value.is_empty()
+		|| value.len() > 10
+		|| (value.len() > 1 && value.starts_with('0'))
+		|| !value.bytes().all(|chr| chr.is_ascii_digit())
+	{
+		return Err(UnknownCryptoError);
+	}
+
+	value.parse::<usize>().map_err(|_| UnknownCryptoError)
+}
+
 #[must_use]
-/// Hash a password using PBKDF2-HMAC-SHA512.
+/// Hash a password using the default algorithm, PBKDF2-HMAC-SHA512.
 pub fn hash_password(
 	password: &Password,
 	iterations: usize,
 ) -> Result<PasswordHash, UnknownCryptoError> {
-	let mut buffer = [0u8; 128];
-	let mut salt = [0u8; 64];
+	hash_password_with_params(password, &Params::new(iterations)?)
+}
+
+#[must_use]
+/// Hash a password using the algorithm, iteration count and output length of
+/// `params`.
+pub fn hash_password_with_params(
+	password: &Password,
+	params: &Params,
+) -> Result<PasswordHash, UnknownCryptoError> {
+	let mut salt = [0u8; SALT_LENGTH];
 	util::secure_rand_bytes(&mut salt)?;
+	let mut buffer = vec![0u8; params.output_length()];
 
-	buffer[..64].copy_from_slice(&salt);
-	pbkdf2::derive_key(
-		&pbkdf2::Password::from_slice(password.unprotected_as_bytes())?,
-		&salt,
-		iterations,
-		&mut buffer[64..],
-	)?;
+	params
+		.algorithm()
+		.derive(password, &salt, params.iterations(), &mut buffer)?;
 
-	let dk = PasswordHash::from_slice(&buffer)?;
+	let password_hash =
+		PasswordHash::from_parts(params.algorithm(), params.iterations(), &salt, &buffer);
 	buffer.zeroize();
 
-	Ok(dk)
+	password_hash
 }
 
 #[must_use]
-/// Hash and verify a password using PBKDF2-HMAC-SHA512.
+/// Hash and verify a password using the algorithm that `expected` was computed with.
 pub fn hash_password_verify(
-	expected_with_salt: &PasswordHash,
+	expected: &PasswordHash,
 	password: &Password,
-	iterations: usize,
 ) -> Result<bool, ValidationCryptoError> {
-	let mut dk = [0u8; 64];
+	let mut dk = vec![0u8; expected.get_length()];
 
-	let is_good = pbkdf2::verify(
-		&expected_with_salt.unprotected_as_bytes()[64..],
-		&pbkdf2::Password::from_slice(password.unprotected_as_bytes())?,
-		&expected_with_salt.unprotected_as_bytes()[..64],
-		iterations,
+	let is_good = expected.algorithm().verify(
+		expected.unprotected_as_bytes(),
+		password,
+		&expected.salt,
+		expected.iterations,
 		&mut dk,
-	)?;
+	);
 
 	dk.zeroize();
 
-	Ok(is_good)
+	is_good
+}
+
+#[must_use]
+/// Verify a password using the algorithm that `expected` was computed with
+/// and, if it is correct but the `PasswordHash` needs a rehash with `params`,
+/// hash it again.
+///
+/// Returns `Ok(None)` if the password was correct and `expected` is up to
+/// date, `Ok(Some(password_hash))` if the password was correct and should be
+/// stored as `password_hash` instead, and an error otherwise.
+pub fn verify_and_upgrade(
+	expected: &PasswordHash,
+	password: &Password,
+	params: &Params,
+) -> Result<Option<PasswordHash>, ValidationCryptoError> {
+	hash_password_verify(expected, password)?;
+
+	if expected.needs_rehash(params) {
+		Ok(Some(hash_password_with_params(password, params)?))
+	} else {
+		Ok(None)
+	}
+}
+
+#[must_use]
+/// Verify a password against a stored password hash and return a new
+/// `PasswordHash` computed with `new_params`.
+///
+/// # About:
+/// `old_hash` is decoded with `PasswordHash::from_stored()`, so it can be
+/// encoded in the PHC string format or be a legacy 128-byte password hash, in
+/// which case it is verified with `legacy_iterations`.
+///
+/// Unlike `verify_and_upgrade()`, this always hashes the password again, even
+/// if `old_hash` was already computed with `new_params`. This is useful when
+/// moving every stored password hash to new parameters in one pass, such as
+/// at the next successful login of each user.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - `old_hash` is neither a valid encoding of a supported `Algorithm` nor a
+///   valid legacy password hash.
+/// - `password` does not match `old_hash`.
+/// - Hashing `password` with `new_params` fails.
+///
+/// # Security:
+/// - The errors returned do not reveal whether `old_hash` could not be decoded
+///   or the password was wrong, and neither the password nor the hash is ever
+///   part of an error.
+///
+/// # Example:
+/// ```rust
+/// use orion::pwhash;
+///
+/// let password = pwhash::Password::from_slice(b"Secret password")?;
+/// let old_hash = pwhash::hash_password(&password, 1000)?;
+///
+/// let new_params = pwhash::Params::new(10000)?;
+/// let stored = old_hash.unprotected_as_encoded().as_bytes();
+/// let new_hash = pwhash::migrate(stored, 1000, &password, &new_params)?;
+/// assert!(!new_hash.needs_rehash(&new_params));
+/// # Ok::<(), orion::errors::ValidationCryptoError>(())
+/// ```
+pub fn migrate(
+	old_hash: &[u8],
+	legacy_iterations: usize,
+	password: &Password,
+	new_params: &Params,
+) -> Result<PasswordHash, ValidationCryptoError> {
+	let expected = PasswordHash::from_stored(old_hash, legacy_iterations)?;
+	hash_password_verify(&expected, password)?;
+
+	Ok(hash_password_with_params(password, new_params)?)
 }
 
 // Testing public functions in the module.
 #[cfg(test)]
 mod public {
 	use super::*;
+	use crate::hltypes::DEFAULT_ALGORITHM;
+	use std::time::Duration;
+
+	mod test_password_hash {
+		use super::*;
+		use crate::hazardous::kdf::pbkdf2;
+
+		#[test]
+		fn test_encoding_roundtrip() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let pbkdf2_dk = hash_password(&password, 100).unwrap();
+
+			let encoded = pbkdf2_dk.unprotected_as_encoded();
+			assert!(encoded.starts_with("$pbkdf2-sha512$i=100,l=64$"));
+			let decoded = PasswordHash::from_encoded(encoded).unwrap();
+			assert_eq!(decoded, pbkdf2_dk);
+			assert_eq!(
+				decoded.unprotected_as_bytes(),
+				pbkdf2_dk.unprotected_as_bytes()
+			);
+			assert_eq!(decoded.iterations(), 100);
+			assert_eq!(decoded.get_length(), 64);
+		}
+
+		#[test]
+		fn test_verify_known_encoding() {
+			// Computed with Python's hashlib.pbkdf2_hmac().
+			let salt = "c2FsdHNhbHRzYWx0c2FsdA";
+			let hash = "715rqIr5dXOVPpBhqqsugl037zT5bWJTWYmZtIcK8hBnisKpwfY7kokvwjDrNHqHhF50Pb7MD6HvkJwiDQw4ww";
+			let encoded = format!("$pbkdf2-sha512$i=1000,l=64${}${}", salt, hash);
+			let password_hash = PasswordHash::from_encoded(&encoded).unwrap();
+			let password = Password::from_slice(b"password").unwrap();
+			assert!(hash_password_verify(&password_hash, &password).unwrap());
+
+			// Without the optional length parameter.
+			let encoded = format!("$pbkdf2-sha512$i=1000${}${}", salt, hash);
+			let password_hash = PasswordHash::from_encoded(&encoded).unwrap();
+			assert!(hash_password_verify(&password_hash, &password).unwrap());
+		}
+
+		#[test]
+		fn test_from_encoded_err() {
+			let salt = "AAAAAAAAAAAAAAAAAAAAAA";
+			let hash = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+			let valid = format!("$pbkdf2-sha512$i=1${}${}", salt, hash);
+			assert!(PasswordHash::from_encoded(&valid).is_ok());
+
+			let invalid = [
+				format!("$argon2id$i=1${}${}", salt, hash),
+				format!("pbkdf2-sha512$i=1${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i=1${}${}$", salt, hash),
+				format!("$pbkdf2-sha512$i=1${}", salt),
+				format!("$pbkdf2-sha512$i=0${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i=01${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i=+1${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i=4294967296${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i=1,i=1${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i=1,l=31${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i=1,x=1${}${}", salt, hash),
+				format!("$pbkdf2-sha512$l=32${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i${}${}", salt, hash),
+				format!("$pbkdf2-sha512$i=1$AAAAAAAAAAAAAAAAAAAA${}", hash),
+				format!("$pbkdf2-sha512$i=1${}$AAAAAAAAAAAAAAAAAAAA", salt),
+				format!("$pbkdf2-sha512$i=1${}=${}", salt, hash),
+				format!("$pbkdf2-sha512$i=1${}${}", "A".repeat(87), hash),
+			];
+
+			for encoded in invalid.iter() {
+				assert!(PasswordHash::from_encoded(encoded).is_err(), "{}", encoded);
+			}
+		}
+
+		#[test]
+		fn test_from_legacy_bytes() {
+			// Made as by `hash_password()` before the PHC string format was used.
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let mut legacy = [0u8; 128];
+			util::secure_rand_bytes(&mut legacy[..64]).unwrap();
+			let (salt, hash) = legacy.split_at_mut(64);
+			let pbkdf2_password =
+				pbkdf2::Password::from_slice(password.unprotected_as_bytes()).unwrap();
+			pbkdf2::derive_key(&pbkdf2_password, salt, 100, hash).unwrap();
+
+			let password_hash = PasswordHash::from_legacy_bytes(&legacy, 100).unwrap();
+			assert_eq!(password_hash.unprotected_as_bytes(), &legacy[64..]);
+			assert_eq!(password_hash.iterations(), 100);
+			assert!(password_hash.needs_rehash(&Params::new(100).unwrap()));
+			assert!(hash_password_verify(&password_hash, &password).unwrap());
+
+			let wrong_iterations = PasswordHash::from_legacy_bytes(&legacy, 101).unwrap();
+			assert!(hash_password_verify(&wrong_iterations, &password).is_err());
+
+			// The PHC string encoding is valid and verifies the same way.
+			let encoded = password_hash.unprotected_as_encoded();
+			let decoded = PasswordHash::from_encoded(encoded).unwrap();
+			assert!(hash_password_verify(&decoded, &password).unwrap());
+		}
+
+		#[test]
+		fn test_from_legacy_bytes_err() {
+			assert!(PasswordHash::from_legacy_bytes(&[0u8; 128], 1).is_ok());
+			assert!(PasswordHash::from_legacy_bytes(&[0u8; 127], 1).is_err());
+			assert!(PasswordHash::from_legacy_bytes(&[0u8; 129], 1).is_err());
+			assert!(PasswordHash::from_legacy_bytes(&[0u8; 64], 1).is_err());
+			assert!(PasswordHash::from_legacy_bytes(&[0u8; 128], 0).is_err());
+		}
+
+		#[test]
+		fn test_from_stored_legacy_known() {
+			// As returned by `hash_password(&password, 1000)` before the PHC string
+			// format was used, with a salt of 64 0x5a bytes. The hash was computed
+			// with Python's hashlib.pbkdf2_hmac().
+			let mut stored = [0x5au8; 128];
+			stored[64..].copy_from_slice(&[
+				0x4e, 0x01, 0xab, 0x95, 0x48, 0x2f, 0x5b, 0x3b, 0x3c, 0x07, 0x50, 0xa8, 0x1b, 0x2d,
+				0x43, 0x20, 0xd1, 0xe6, 0x7e, 0xf1, 0x66, 0x59, 0x84, 0x07, 0xc9, 0xf0, 0x62, 0x41,
+				0x93, 0x5f, 0xeb, 0x27, 0x42, 0x10, 0x34, 0x33, 0x72, 0x7f, 0x3f, 0xc0, 0xfd, 0x9e,
+				0x50, 0x26, 0x08, 0x95, 0x0e, 0x41, 0xf7, 0x03, 0x32, 0x01, 0x75, 0xef, 0xa4, 0x8d,
+				0xb7, 0x93, 0xf9, 0x6a, 0xd2, 0xac, 0x36, 0x93,
+			]);
+			let password = Password::from_slice(b"password").unwrap();
+
+			let password_hash = PasswordHash::from_stored(&stored, 1000).unwrap();
+			assert_eq!(password_hash.algorithm(), Algorithm::Pbkdf2Sha512);
+			assert_eq!(password_hash.iterations(), 1000);
+			assert!(hash_password_verify(&password_hash, &password).unwrap());
+
+			let bad_password = Password::from_slice(b"Password").unwrap();
+			assert!(hash_password_verify(&password_hash, &bad_password).is_err());
+		}
+
+		#[test]
+		fn test_from_stored() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let pbkdf2_dk = hash_password(&password, 100).unwrap();
+
+			// The iteration count of a PHC string is used, not `legacy_iterations`.
+			let encoded = pbkdf2_dk.unprotected_as_encoded().as_bytes();
+			let decoded = PasswordHash::from_stored(encoded, 1).unwrap();
+			assert_eq!(decoded, pbkdf2_dk);
+			assert_eq!(decoded.iterations(), 100);
+
+			// A legacy password hash that starts like a PHC string.
+			let mut legacy = [0u8; 128];
+			legacy[0] = b'$';
+			assert!(PasswordHash::from_stored(&legacy, 1).is_ok());
+
+			assert!(PasswordHash::from_stored(b"", 1).is_err());
+			assert!(PasswordHash::from_stored(&[0u8; 127], 1).is_err());
+			assert!(PasswordHash::from_stored(&encoded[1..], 1).is_err());
+		}
+
+		#[test]
+		fn test_omitted_debug() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let pbkdf2_dk = hash_password(&password, 100).unwrap();
+			let debug = format!("{:?}", pbkdf2_dk);
+			assert!(!debug.contains(pbkdf2_dk.unprotected_as_encoded()));
+		}
+	}
 
 	mod test_pwhash_and_verify {
 		use super::*;
@@ -138,10 +672,7 @@ mod public {
 
 			let pbkdf2_dk = hash_password(&password, 100).unwrap();
 
-			assert_eq!(
-				hash_password_verify(&pbkdf2_dk, &password, 100).unwrap(),
-				true
-			);
+			assert_eq!(hash_password_verify(&pbkdf2_dk, &password).unwrap(), true);
 		}
 
 		#[test]
@@ -149,11 +680,11 @@ mod public {
 			let password = Password::from_slice(&[0u8; 64]).unwrap();
 
 			let pbkdf2_dk = hash_password(&password, 100).unwrap();
-			let mut pwd_mod = pbkdf2_dk.unprotected_as_bytes().to_vec();
-			pwd_mod[0..32].copy_from_slice(&[0u8; 32]);
-			let modified = PasswordHash::from_slice(&pwd_mod).unwrap();
+			let pwd = pbkdf2_dk.unprotected_as_bytes();
+			let modified =
+				PasswordHash::from_parts(DEFAULT_ALGORITHM, 100, &[1u8; 16], pwd).unwrap();
 
-			assert!(hash_password_verify(&modified, &password, 100).is_err());
+			assert!(hash_password_verify(&modified, &password).is_err());
 		}
 
 		#[test]
@@ -162,22 +693,24 @@ mod public {
 
 			let pbkdf2_dk = hash_password(&password, 100).unwrap();
 			let mut pwd_mod = pbkdf2_dk.unprotected_as_bytes().to_vec();
-			pwd_mod[120..128].copy_from_slice(&[0u8; 8]);
-			let modified = PasswordHash::from_slice(&pwd_mod).unwrap();
+			pwd_mod[56..64].copy_from_slice(&[0u8; 8]);
+			let salt = &pbkdf2_dk.salt;
+			let modified =
+				PasswordHash::from_parts(DEFAULT_ALGORITHM, 100, salt, &pwd_mod).unwrap();
 
-			assert!(hash_password_verify(&modified, &password, 100).is_err());
+			assert!(hash_password_verify(&modified, &password).is_err());
 		}
 
 		#[test]
-		fn test_pbkdf2_verify_err_modified_salt_and_password() {
+		fn test_pbkdf2_verify_err_modified_iterations() {
 			let password = Password::from_slice(&[0u8; 64]).unwrap();
 
 			let pbkdf2_dk = hash_password(&password, 100).unwrap();
-			let mut pwd_mod = pbkdf2_dk.unprotected_as_bytes().to_vec();
-			pwd_mod[64..96].copy_from_slice(&[0u8; 32]);
-			let modified = PasswordHash::from_slice(&pwd_mod).unwrap();
+			let pwd = pbkdf2_dk.unprotected_as_bytes();
+			let modified =
+				PasswordHash::from_parts(DEFAULT_ALGORITHM, 101, &pbkdf2_dk.salt, pwd).unwrap();
 
-			assert!(hash_password_verify(&modified, &password, 100).is_err());
+			assert!(hash_password_verify(&modified, &password).is_err());
 		}
 
 		#[test]
@@ -188,6 +721,281 @@ mod public {
 		}
 	}
 
+	mod test_algorithm {
+		use super::*;
+
+		#[test]
+		fn test_identifier_roundtrip() {
+			let algorithm = Algorithm::Pbkdf2Sha512;
+			assert_eq!(algorithm.identifier(), "pbkdf2-sha512");
+			assert_eq!(
+				Algorithm::from_identifier(algorithm.identifier()).unwrap(),
+				algorithm
+			);
+		}
+
+		#[test]
+		fn test_unknown_identifier() {
+			assert!(Algorithm::from_identifier("").is_err());
+			assert!(Algorithm::from_identifier("pbkdf2-sha256").is_err());
+			assert!(Algorithm::from_identifier("PBKDF2-SHA512").is_err());
+			assert!(Algorithm::from_identifier("argon2id").is_err());
+		}
+
+		#[test]
+		fn test_hash_with_params() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let params = Params::with_algorithm(Algorithm::Pbkdf2Sha512, 100).unwrap();
+			let password_hash = hash_password_with_params(&password, &params).unwrap();
+
+			assert_eq!(password_hash.algorithm(), Algorithm::Pbkdf2Sha512);
+			assert_eq!(password_hash.iterations(), 100);
+			assert!(!password_hash.needs_rehash(&params));
+			assert!(hash_password_verify(&password_hash, &password).unwrap());
+
+			let decoded = PasswordHash::from_encoded(password_hash.unprotected_as_encoded());
+			assert_eq!(decoded.unwrap().algorithm(), Algorithm::Pbkdf2Sha512);
+		}
+
+		#[test]
+		fn test_default_algorithm() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let password_hash = hash_password(&password, 100).unwrap();
+
+			assert_eq!(password_hash.algorithm(), DEFAULT_ALGORITHM);
+			assert_eq!(Params::new(100).unwrap().algorithm(), DEFAULT_ALGORITHM);
+		}
+	}
+
+	mod test_params {
+		use super::*;
+
+		#[test]
+		fn test_params_presets() {
+			assert_eq!(Params::interactive(), Params::new(100_000).unwrap());
+			assert_eq!(Params::moderate(), Params::new(300_000).unwrap());
+			assert_eq!(Params::sensitive(), Params::new(1_000_000).unwrap());
+			assert_eq!(Params::interactive().output_length(), 64);
+		}
+
+		#[test]
+		fn test_params_output_length() {
+			let params = Params::new(100).unwrap();
+			assert_eq!(params.output_length(), 64);
+			assert!(params.with_output_length(0).is_err());
+			assert!(params.with_output_length(1).is_ok());
+			assert!(params
+				.with_output_length(u32::max_value() as usize)
+				.is_err());
+
+			let params = params.with_output_length(32).unwrap();
+			assert_eq!(params.output_length(), 32);
+			assert_eq!(params.iterations(), 100);
+		}
+
+		#[test]
+		fn test_hash_with_output_length() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let params = Params::new(100).unwrap();
+
+			for length in [16, 32, 64].iter() {
+				let params = params.with_output_length(*length).unwrap();
+				let password_hash = hash_password_with_params(&password, &params).unwrap();
+				assert_eq!(password_hash.get_length(), *length);
+				assert!(!password_hash.needs_rehash(&params));
+				assert!(hash_password_verify(&password_hash, &password).unwrap());
+			}
+
+			for length in [1, 15, 65].iter() {
+				let params = params.with_output_length(*length).unwrap();
+				assert!(hash_password_with_params(&password, &params).is_err());
+			}
+		}
+
+		#[test]
+		fn test_params_new() {
+			assert!(Params::new(0).is_err());
+			assert!(Params::new(1).is_ok());
+			assert!(Params::new(u32::max_value() as usize).is_ok());
+			assert!(Params::new((u32::max_value() as usize) + 1).is_err());
+			assert_eq!(Params::new(100).unwrap().iterations(), 100);
+		}
+	}
+
+	mod test_calibrate {
+		use super::*;
+
+		#[test]
+		fn test_calibrate() {
+			let params = Params::calibrate(Duration::from_millis(10), 0).unwrap();
+			assert_eq!(params.algorithm(), DEFAULT_ALGORITHM);
+			assert!(params.iterations() >= 1);
+			assert!(params.iterations() <= u32::max_value() as usize);
+		}
+
+		#[test]
+		fn test_calibrate_max_memory_ignored_for_pbkdf2() {
+			assert!(Params::calibrate(Duration::from_millis(1), 0).is_ok());
+			assert!(Params::calibrate(Duration::from_millis(1), usize::max_value()).is_ok());
+		}
+
+		#[test]
+		fn test_calibrate_zero_duration() {
+			assert!(Params::calibrate(Duration::from_secs(0), 0).is_err());
+			assert!(Params::calibrate(Duration::from_nanos(1), 0).is_ok());
+		}
+
+		#[test]
+		fn test_calibrate_long_duration() {
+			// Should saturate instead of overflowing the iteration count.
+			let params = Params::calibrate(Duration::from_secs(u64::max_value()), 0).unwrap();
+			assert_eq!(params.iterations(), u32::max_value() as usize);
+		}
+	}
+
+	mod test_needs_rehash {
+		use super::*;
+
+		#[test]
+		fn test_needs_rehash() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let pbkdf2_dk = hash_password(&password, 100).unwrap();
+
+			assert!(!pbkdf2_dk.needs_rehash(&Params::new(100).unwrap()));
+			assert!(pbkdf2_dk.needs_rehash(&Params::new(101).unwrap()));
+			assert!(pbkdf2_dk.needs_rehash(&Params::new(99).unwrap()));
+		}
+
+		#[test]
+		fn test_needs_rehash_lengths() {
+			let params = Params::new(100).unwrap();
+
+			let short_salt =
+				PasswordHash::from_parts(DEFAULT_ALGORITHM, 100, &[0u8; 32], &[0u8; 64]).unwrap();
+			assert!(short_salt.needs_rehash(&params));
+			let short_hash =
+				PasswordHash::from_parts(DEFAULT_ALGORITHM, 100, &[0u8; 16], &[0u8; 32]).unwrap();
+			assert!(short_hash.needs_rehash(&params));
+			let current =
+				PasswordHash::from_parts(DEFAULT_ALGORITHM, 100, &[0u8; 16], &[0u8; 64]).unwrap();
+			assert!(!current.needs_rehash(&params));
+		}
+	}
+
+	mod test_verify_and_upgrade {
+		use super::*;
+
+		#[test]
+		fn test_no_upgrade() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let pbkdf2_dk = hash_password(&password, 100).unwrap();
+
+			let res = verify_and_upgrade(&pbkdf2_dk, &password, &Params::new(100).unwrap());
+			assert!(res.unwrap().is_none());
+		}
+
+		#[test]
+		fn test_upgrade() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let pbkdf2_dk = hash_password(&password, 100).unwrap();
+			let params = Params::new(200).unwrap();
+
+			let upgraded = verify_and_upgrade(&pbkdf2_dk, &password, &params)
+				.unwrap()
+				.unwrap();
+			assert_eq!(upgraded.iterations(), 200);
+			assert!(!upgraded.needs_rehash(&params));
+			assert!(hash_password_verify(&upgraded, &password).unwrap());
+		}
+
+		#[test]
+		fn test_wrong_password_no_upgrade() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let bad_password = Password::from_slice(&[1u8; 64]).unwrap();
+			let pbkdf2_dk = hash_password(&password, 100).unwrap();
+
+			let current = Params::new(100).unwrap();
+			let upgrade = Params::new(200).unwrap();
+
+			assert!(verify_and_upgrade(&pbkdf2_dk, &bad_password, &current).is_err());
+			assert!(verify_and_upgrade(&pbkdf2_dk, &bad_password, &upgrade).is_err());
+		}
+	}
+
+	mod test_migrate {
+		use super::*;
+		use crate::hazardous::kdf::pbkdf2;
+
+		#[test]
+		fn test_migrate() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let old_hash = hash_password(&password, 100).unwrap();
+			let params = Params::new(200).unwrap();
+
+			let stored = old_hash.unprotected_as_encoded().as_bytes();
+			let new_hash = migrate(stored, 100, &password, &params).unwrap();
+			assert_eq!(new_hash.iterations(), 200);
+			assert!(!new_hash.needs_rehash(&params));
+			assert!(hash_password_verify(&new_hash, &password).unwrap());
+		}
+
+		#[test]
+		fn test_migrate_same_params_rehashes() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let old_hash = hash_password(&password, 100).unwrap();
+			let params = Params::new(100).unwrap();
+
+			let stored = old_hash.unprotected_as_encoded().as_bytes();
+			let new_hash = migrate(stored, 100, &password, &params).unwrap();
+			assert!(new_hash != old_hash);
+			assert!(hash_password_verify(&new_hash, &password).unwrap());
+		}
+
+		#[test]
+		fn test_migrate_wrong_password() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let bad_password = Password::from_slice(&[1u8; 64]).unwrap();
+			let old_hash = hash_password(&password, 100).unwrap();
+			let params = Params::new(200).unwrap();
+
+			let stored = old_hash.unprotected_as_encoded().as_bytes();
+			assert!(migrate(stored, 100, &bad_password, &params).is_err());
+		}
+
+		#[test]
+		fn test_migrate_bad_encoding() {
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let params = Params::new(200).unwrap();
+
+			assert!(migrate(b"", 100, &password, &params).is_err());
+			let scrypt = b"$scrypt$ln=15,r=8,p=1$c2FsdA$aGFzaA";
+			assert!(migrate(scrypt, 100, &password, &params).is_err());
+			let bcrypt = b"$2b$12$invalidbcrypthashvalue";
+			assert!(migrate(bcrypt, 100, &password, &params).is_err());
+		}
+
+		#[test]
+		fn test_migrate_legacy() {
+			// Made as by `hash_password()` before the PHC string format was used.
+			let password = Password::from_slice(&[0u8; 64]).unwrap();
+			let mut legacy = [0u8; 128];
+			util::secure_rand_bytes(&mut legacy[..64]).unwrap();
+			let (salt, hash) = legacy.split_at_mut(64);
+			let pbkdf2_password =
+				pbkdf2::Password::from_slice(password.unprotected_as_bytes()).unwrap();
+			pbkdf2::derive_key(&pbkdf2_password, salt, 100, hash).unwrap();
+			let params = Params::new(200).unwrap();
+
+			let new_hash = migrate(&legacy, 100, &password, &params).unwrap();
+			assert!(!new_hash.needs_rehash(&params));
+			assert!(hash_password_verify(&new_hash, &password).unwrap());
+
+			assert!(migrate(&legacy, 101, &password, &params).is_err());
+			let bad_password = Password::from_slice(&[1u8; 64]).unwrap();
+			assert!(migrate(&legacy, 100, &bad_password, &params).is_err());
+		}
+	}
+
 	// Proptests. Only exectued when NOT testing no_std.
 	#[cfg(feature = "safe_api")]
 	mod proptest {
@@ -205,7 +1013,7 @@ mod public {
 				let pass = Password::from_slice(&passin[..]).unwrap();
 				let pass_hash = hash_password(&pass, 100).unwrap();
 
-				if hash_password_verify(&pass_hash, &pass, 100).is_ok() {
+				if hash_password_verify(&pass_hash, &pass).is_ok() {
 					true
 				} else {
 					false
@@ -226,12 +1034,30 @@ mod public {
 				let pass_hash = hash_password(&pass, 100).unwrap();
 				let bad_pass = Password::generate(32).unwrap();
 
-				if hash_password_verify(&pass_hash, &bad_pass, 100).is_err() {
+				if hash_password_verify(&pass_hash, &bad_pass).is_err() {
 					true
 				} else {
 					false
 				}
 			}
 		}
+
+		quickcheck! {
+			/// Decoding an encoded password hash should always give the same password hash.
+			fn prop_pwhash_encoding(input: Vec<u8>) -> bool {
+				let passin = if input.is_empty() {
+					vec![1u8; 10]
+				} else {
+					input
+				};
+
+				let pass = Password::from_slice(&passin[..]).unwrap();
+				let pass_hash = hash_password(&pass, 100).unwrap();
+				let encoded = pass_hash.unprotected_as_encoded();
+				let decoded = PasswordHash::from_encoded(encoded).unwrap();
+
+				decoded == pass_hash && hash_password_verify(&decoded, &pass).is_ok()
+			}
+		}
 	}
 }
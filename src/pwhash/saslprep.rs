@@ -0,0 +1,248 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! SASLprep normalization of passwords.
+//!
+//! # Use case:
+//! `orion::pwhash::saslprep` can be used to prepare a password before it is
+//! hashed, so that it verifies no matter which platform it was typed on.
+//!
+//! An example of this could be a password set from a phone, which sends the
+//! decomposed form of accented characters, and later typed on a desktop, which
+//! sends the precomposed form.
+//!
+//! # About:
+//! - Implements SASLprep as specified in [RFC 4013](https://tools.ietf.org/html/rfc4013),
+//!   the [RFC 3454](https://tools.ietf.org/html/rfc3454) stringprep profile
+//!   for user names and passwords, for stored strings.
+//! - Non-ASCII space characters are mapped to the ASCII space, and characters
+//!   that are commonly mapped to nothing, such as the soft hyphen, are
+//!   removed.
+//! - The result is normalized to Unicode NFKC.
+//! - Prohibited characters, code points unassigned in Unicode 3.2, and strings
+//!   that mix right-to-left and left-to-right characters in a way that RFC
+//!   3454 section 6 does not allow, are rejected.
+//! - The prepared password is returned as a `Password`, so that no
+//!   unprotected copy of it is left behind. Buffers that orion allocates while
+//!   preparing it are zeroed.
+//!
+//! # Parameters:
+//! - `password`: The password to be prepared.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `password` is empty, or nothing is left of it after mapping.
+//! - `password` contains a prohibited or unassigned character after mapping
+//!   and normalization.
+//! - `password` contains both right-to-left and left-to-right characters, or
+//!   contains right-to-left characters but does not start and end with one.
+//!
+//! # Security:
+//! - Every password must be prepared the same way before it is hashed and
+//!   before it is verified. Changing whether or how passwords are prepared
+//!   makes existing password hashes stop verifying for passwords that
+//!   normalization changes.
+//! - The PRECIS `OpaqueString` profile of [RFC 8265](https://tools.ietf.org/html/rfc8265),
+//!   which replaces SASLprep, uses NFC rather than NFKC. The two agree on most
+//!   passwords, but not on compatibility characters such as full-width
+//!   letters. `Password::from_str_normalized()` with `Normalization::Nfc` can
+//!   be used where only normalization is needed.
+//!
+//! # Example:
+//! ```
+//! use orion::pwhash;
+//!
+//! let password = pwhash::saslprep::prepare("Secret\u{a0}password").unwrap();
+//! let hash = pwhash::hash_password(&password, 100000).unwrap();
+//!
+//! let typed = pwhash::saslprep::prepare("Secret password").unwrap();
+//! assert!(pwhash::hash_password_verify(&hash, &typed).unwrap());
+//! ```
+
+use super::Password;
+use crate::{errors::UnknownCryptoError, hltypes::password_from_chars};
+use unicode_normalization::UnicodeNormalization;
+
+mod tables;
+
+/// Return true if `c` is in one of the inclusive, sorted `ranges`.
+fn in_table(ranges: &[(u32, u32)], c: char) -> bool {
+	let c = c as u32;
+	ranges
+		.binary_search_by(|&(start, end)| {
+			if end < c {
+				core::cmp::Ordering::Less
+			} else if start > c {
+				core::cmp::Ordering::Greater
+			} else {
+				core::cmp::Ordering::Equal
+			}
+		})
+		.is_ok()
+}
+
+/// Check `prepared` against the prohibited, unassigned and
+/// bidirectional rules.
+fn check_prepared(prepared: &str) -> Result<(), UnknownCryptoError> {
+	let prohibited = |c| in_table(tables::PROHIBITED, c) || in_table(tables::UNASSIGNED, c);
+	if prepared.chars().any(prohibited) {
+		return Err(UnknownCryptoError);
+	}
+
+	if prepared.chars().any(|c| in_table(tables::RAND_AL_CAT, c)) {
+		let is_rand_al = |c: Option<char>| c.map_or(false, |c| in_table(tables::RAND_AL_CAT, c));
+		if prepared.chars().any(|c| in_table(tables::L_CAT, c))
+			|| !is_rand_al(prepared.chars().next())
+			|| !is_rand_al(prepared.chars().next_back())
+		{
+			return Err(UnknownCryptoError);
+		}
+	}
+
+	Ok(())
+}
+
+#[must_use]
+/// Prepare `password` with SASLprep, for hashing or verifying.
+pub fn prepare(password: &str) -> Result<Password, UnknownCryptoError> {
+	let mapped = password
+		.chars()
+		.filter(|&c| !in_table(tables::MAPPED_TO_NOTHING, c))
+		.map(|c| {
+			if in_table(tables::NON_ASCII_SPACE, c) {
+				' '
+			} else {
+				c
+			}
+		});
+	let prepared = password_from_chars(mapped.nfkc(), password.len())?;
+
+	// `prepared` was encoded from `char`s, so it is valid UTF-8.
+	let prepared_str =
+		core::str::from_utf8(prepared.unprotected_as_bytes()).map_err(|_| UnknownCryptoError)?;
+	check_prepared(prepared_str)?;
+
+	Ok(prepared)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_prepare {
+		use super::*;
+
+		fn prepared(password: &str) -> Vec<u8> {
+			prepare(password).unwrap().unprotected_as_bytes().to_vec()
+		}
+
+		#[test]
+		fn test_rfc4013_examples() {
+			// Soft hyphen mapped to nothing.
+			assert_eq!(prepared("I\u{ad}X"), b"IX");
+			// No transformation.
+			assert_eq!(prepared("user"), b"user");
+			// Case preserved.
+			assert_eq!(prepared("USER"), b"USER");
+			// Output is NFKC.
+			assert_eq!(prepared("\u{aa}"), b"a");
+			assert_eq!(prepared("\u{2168}"), b"IX");
+			// Prohibited character.
+			assert!(prepare("\u{7}").is_err());
+			// Bidirectional check.
+			assert!(prepare("\u{627}1").is_err());
+		}
+
+		#[test]
+		fn test_non_ascii_space() {
+			assert_eq!(prepared("a\u{a0}b"), b"a b");
+			assert_eq!(prepared("a\u{3000}b"), b"a b");
+		}
+
+		#[test]
+		fn test_composed_and_decomposed_match() {
+			assert_eq!(prepared("cafe\u{301}"), prepared("caf\u{e9}"));
+			assert_eq!(prepared("caf\u{e9}"), "caf\u{e9}".as_bytes());
+		}
+
+		#[test]
+		fn test_right_to_left() {
+			// Only right-to-left characters.
+			assert!(prepare("\u{5d0}\u{5d1}").is_ok());
+			// Right-to-left characters around a neutral one.
+			assert!(prepare("\u{627}1\u{628}").is_ok());
+			// Mixed with left-to-right characters.
+			assert!(prepare("\u{5d0}a\u{5d1}").is_err());
+			// Not ending with a right-to-left character.
+			assert!(prepare("\u{5d0}1").is_err());
+		}
+
+		#[test]
+		fn test_prohibited_and_unassigned_err() {
+			// Private use.
+			assert!(prepare("a\u{e000}").is_err());
+			// Non-character.
+			assert!(prepare("a\u{fdd0}").is_err());
+			// Tagging character.
+			assert!(prepare("a\u{e0001}").is_err());
+			// Unassigned in Unicode 3.2.
+			assert!(prepare("a\u{221}").is_err());
+		}
+
+		#[test]
+		fn test_empty_err() {
+			assert!(prepare("").is_err());
+			assert!(prepare("\u{ad}").is_err());
+		}
+	}
+
+	mod test_in_table {
+		use super::*;
+
+		#[test]
+		fn test_table_bounds() {
+			let table = &[(0x10, 0x20), (0x30, 0x30)];
+			assert!(!in_table(table, '\u{f}'));
+			assert!(in_table(table, '\u{10}'));
+			assert!(in_table(table, '\u{20}'));
+			assert!(!in_table(table, '\u{21}'));
+			assert!(in_table(table, '\u{30}'));
+			assert!(!in_table(table, '\u{31}'));
+		}
+
+		#[test]
+		fn test_tables_sorted() {
+			for table in &[
+				tables::UNASSIGNED,
+				tables::MAPPED_TO_NOTHING,
+				tables::NON_ASCII_SPACE,
+				tables::PROHIBITED,
+				tables::RAND_AL_CAT,
+				tables::L_CAT,
+			] {
+				assert!(table.iter().all(|&(start, end)| start <= end));
+				assert!(table.windows(2).all(|pair| pair[0].1 < pair[1].0));
+			}
+		}
+	}
+}
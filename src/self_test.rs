@@ -0,0 +1,318 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Known-answer self-tests of the primitives in orion.
+//!
+//! # Use case:
+//! `orion::self_test()` can be called when an application starts, to check
+//! that each primitive produces the expected output on the current platform
+//! before it is used. This is required in some regulated environments, in the
+//! same way as the power-on self-tests of FIPS 140.
+//!
+//! # About:
+//! - Each primitive is run on a compiled-in test vector, and its output is
+//!   compared to the known answer. Decryption is checked as well for the AEADs.
+//! - The vectors are only compiled in with the `self_test` feature.
+//! - `self_test()` does not need the standard library, and is also available
+//!   in `no_std` contexts.
+//! - A `SelfTestReport` is returned with the result of every primitive, so
+//!   that failures can be logged before the application refuses to start.
+//!
+//! # Security:
+//! - A self-test can only detect a primitive that is broken on the current
+//!   platform, such as from a miscompilation. It does not make a primitive
+//!   more secure to use.
+//!
+//! # Example:
+//! ```
+//! let report = orion::self_test();
+//! for primitive in report.failures() {
+//!     eprintln!("Self-test of {:?} failed", primitive);
+//! }
+//! assert!(report.passed());
+//! ```
+
+use crate::{
+	encoding::hex_decode_into,
+	errors::UnknownCryptoError,
+	hazardous::{
+		aead::{chacha20poly1305, xchacha20poly1305},
+		hash::{blake2b, sha256, sha512},
+		kdf::{hkdf, pbkdf2},
+		mac::{hmac, poly1305},
+		stream::{chacha20, xchacha20},
+	},
+};
+
+/// The number of primitives that are self-tested.
+const PRIMITIVE_COUNT: usize = 9;
+
+/// The largest known answer, in bytes.
+const MAX_ANSWER_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// The primitives that are self-tested.
+pub enum Primitive {
+	/// SHA256.
+	Sha256,
+	/// SHA512.
+	Sha512,
+	/// BLAKE2b-512.
+	Blake2b,
+	/// HMAC-SHA512.
+	HmacSha512,
+	/// Poly1305.
+	Poly1305,
+	/// ChaCha20Poly1305.
+	ChaCha20Poly1305,
+	/// XChaCha20Poly1305.
+	XChaCha20Poly1305,
+	/// HKDF-HMAC-SHA512.
+	HkdfSha512,
+	/// PBKDF2-HMAC-SHA512.
+	Pbkdf2Sha512,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The results of `self_test()`, with whether the self-test of each
+/// primitive passed.
+pub struct SelfTestReport {
+	results: [(Primitive, bool); PRIMITIVE_COUNT],
+}
+
+impl SelfTestReport {
+	/// Return true if the self-tests of all primitives passed.
+	pub fn passed(&self) -> bool { self.results.iter().all(|(_, passed)| *passed) }
+
+	/// Return each primitive with whether its self-test passed.
+	pub fn results(&self) -> &[(Primitive, bool)] { &self.results }
+
+	/// Return the primitives whose self-test failed.
+	pub fn failures(&self) -> impl Iterator<Item = Primitive> + '_ {
+		self.results.iter().filter(|(_, passed)| !passed).map(|(primitive, _)| *primitive)
+	}
+}
+
+/// Compare `actual` to the hex-encoded `expected`.
+fn check(expected: &str, actual: &[u8]) -> Result<bool, UnknownCryptoError> {
+	let mut answer = [0u8; MAX_ANSWER_SIZE];
+	if actual.len() > MAX_ANSWER_SIZE {
+		return Err(UnknownCryptoError);
+	}
+	hex_decode_into(expected, &mut answer[..actual.len()])?;
+
+	Ok(answer[..actual.len()] == *actual)
+}
+
+/// The SHA256 test vector "abc" from FIPS 180-2.
+fn kat_sha256() -> Result<bool, UnknownCryptoError> {
+	check(
+		"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+		sha256::digest(b"abc")?.as_bytes(),
+	)
+}
+
+/// The SHA512 test vector "abc" from FIPS 180-2.
+fn kat_sha512() -> Result<bool, UnknownCryptoError> {
+	check(
+		"ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+		 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+		sha512::digest(b"abc")?.as_bytes(),
+	)
+}
+
+/// The BLAKE2b-512 test vector "abc" from RFC 7693.
+fn kat_blake2b() -> Result<bool, UnknownCryptoError> {
+	let mut state = blake2b::init(None, 64)?;
+	state.update(b"abc")?;
+
+	check(
+		"ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+		 7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+		state.finalize()?.as_bytes(),
+	)
+}
+
+/// Test case 1 for HMAC-SHA512 from RFC 4231.
+fn kat_hmac_sha512() -> Result<bool, UnknownCryptoError> {
+	let secret_key = hmac::SecretKey::from_slice(&[0x0b; 20])?;
+
+	check(
+		"87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+		 daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+		hmac::hmac(&secret_key, b"Hi There")?.unprotected_as_bytes(),
+	)
+}
+
+/// The Poly1305 test vector from RFC 8439, section 2.5.2.
+fn kat_poly1305() -> Result<bool, UnknownCryptoError> {
+	let mut key = [0u8; 32];
+	hex_decode_into(
+		"85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b",
+		&mut key,
+	)?;
+	let one_time_key = poly1305::OneTimeKey::from_slice(&key)?;
+
+	check(
+		"a8061dc1305136c6c22b8baf0c0127a9",
+		poly1305::poly1305(&one_time_key, b"Cryptographic Forum Research Group")?
+			.unprotected_as_bytes(),
+	)
+}
+
+/// The key, plaintext and additional data of the AEAD self-tests.
+const AEAD_KEY: [u8; 32] = [
+	0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+	0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+];
+const AEAD_PLAINTEXT: &[u8] = b"orion self-test";
+const AEAD_AD: &[u8] = b"orion";
+const AEAD_NONCE: [u8; 24] = [
+	0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f,
+	0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57,
+];
+
+/// A ChaCha20Poly1305 test vector, checked against an independent
+/// implementation.
+fn kat_chacha20poly1305() -> Result<bool, UnknownCryptoError> {
+	let secret_key = chacha20::SecretKey::from_slice(&AEAD_KEY)?;
+	let nonce = chacha20::Nonce::from_slice(&AEAD_NONCE[..12])?;
+	let mut ciphertext = [0u8; 31];
+	let mut plaintext = [0u8; 15];
+
+	chacha20poly1305::seal(&secret_key, &nonce, AEAD_PLAINTEXT, Some(AEAD_AD), &mut ciphertext)?;
+	chacha20poly1305::open(&secret_key, &nonce, &ciphertext, Some(AEAD_AD), &mut plaintext)?;
+
+	Ok(check(
+		"972615ee1c6a9d6434a9db74de9c19a80ba796b362e740315365e5c6b27a13",
+		&ciphertext,
+	)? && plaintext == AEAD_PLAINTEXT)
+}
+
+/// An XChaCha20Poly1305 test vector, checked against an independent
+/// implementation.
+fn kat_xchacha20poly1305() -> Result<bool, UnknownCryptoError> {
+	let secret_key = chacha20::SecretKey::from_slice(&AEAD_KEY)?;
+	let nonce = xchacha20::Nonce::from_slice(&AEAD_NONCE)?;
+	let mut ciphertext = [0u8; 31];
+	let mut plaintext = [0u8; 15];
+
+	xchacha20poly1305::seal(&secret_key, &nonce, AEAD_PLAINTEXT, Some(AEAD_AD), &mut ciphertext)?;
+	xchacha20poly1305::open(&secret_key, &nonce, &ciphertext, Some(AEAD_AD), &mut plaintext)?;
+
+	Ok(check(
+		"bb4b6c1fbec00a73e392aacacaef11dbdaff0d2529642be560b8f0a30b70b9",
+		&ciphertext,
+	)? && plaintext == AEAD_PLAINTEXT)
+}
+
+/// An HKDF-HMAC-SHA512 test vector, checked against an independent
+/// implementation.
+fn kat_hkdf_sha512() -> Result<bool, UnknownCryptoError> {
+	let mut okm = [0u8; 32];
+	hkdf::derive_key(b"salt", b"input key material", Some(b"info"), &mut okm)?;
+
+	check(
+		"57026b6a13014b870f39e8b46105c12f296eb0515a81afd6fb419b0e63c5b577",
+		&okm,
+	)
+}
+
+/// A PBKDF2-HMAC-SHA512 test vector, checked against an independent
+/// implementation.
+fn kat_pbkdf2_sha512() -> Result<bool, UnknownCryptoError> {
+	let password = pbkdf2::Password::from_slice(b"password")?;
+	let mut dk = [0u8; 32];
+	pbkdf2::derive_key(&password, b"salt", 2, &mut dk)?;
+
+	check(
+		"e1d9c16aa681708a45f5c7c4e215ceb66e011a2e9f0040713f18aefdb866d53c",
+		&dk,
+	)
+}
+
+#[must_use]
+/// Run the known-answer self-tests of all primitives.
+pub fn self_test() -> SelfTestReport {
+	let run = |kat: fn() -> Result<bool, UnknownCryptoError>| kat().unwrap_or(false);
+
+	SelfTestReport {
+		results: [
+			(Primitive::Sha256, run(kat_sha256)),
+			(Primitive::Sha512, run(kat_sha512)),
+			(Primitive::Blake2b, run(kat_blake2b)),
+			(Primitive::HmacSha512, run(kat_hmac_sha512)),
+			(Primitive::Poly1305, run(kat_poly1305)),
+			(Primitive::ChaCha20Poly1305, run(kat_chacha20poly1305)),
+			(Primitive::XChaCha20Poly1305, run(kat_xchacha20poly1305)),
+			(Primitive::HkdfSha512, run(kat_hkdf_sha512)),
+			(Primitive::Pbkdf2Sha512, run(kat_pbkdf2_sha512)),
+		],
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_self_test {
+		use super::*;
+
+		#[test]
+		fn test_all_pass() {
+			let report = self_test();
+			assert!(report.passed());
+			assert_eq!(report.results().len(), PRIMITIVE_COUNT);
+			assert_eq!(report.failures().count(), 0);
+		}
+
+		#[test]
+		fn test_failures_reported() {
+			let mut report = self_test();
+			report.results[1].1 = false;
+			report.results[4].1 = false;
+
+			assert!(!report.passed());
+			let failures: Vec<Primitive> = report.failures().collect();
+			assert_eq!(failures, vec![Primitive::Sha512, Primitive::Poly1305]);
+		}
+	}
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	mod test_check {
+		use super::*;
+
+		#[test]
+		fn test_check() {
+			assert!(check("00ff", &[0x00, 0xff]).unwrap());
+			assert!(!check("00fe", &[0x00, 0xff]).unwrap());
+			assert!(check("00f", &[0x00, 0xff]).is_err());
+			assert!(check("00", &[0u8; 65]).is_err());
+		}
+	}
+}
@@ -0,0 +1,261 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! - A CAVP response file is read as records of `Name = value` lines separated
+//!   by blank lines. Comments starting with `#` are ignored, and `[L = n]`
+//!   lines set the output length in bytes for the records that follow.
+//! - `run_digest()` runs the records with `Len`, `Msg` and `MD`, such as in
+//!   the SHA `ShortMsg` and `LongMsg` files. Records where `Len` is not a
+//!   multiple of 8 are skipped.
+//! - `run_mac()` runs the records with `Klen`, `Tlen`, `Key`, `Msg` and `Mac`,
+//!   such as in the HMAC file. Records in a section where `L` is not the
+//!   output length of the MAC under test are skipped.
+
+use super::{compute_tag, decode_hex, TestReport};
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{hash::HashFunction, mac::Mac},
+};
+
+/// A record of a CAVP response file, with the `L` of its section.
+struct Record<'a> {
+	output_len: Option<usize>,
+	fields: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Record<'a> {
+	/// Return the value of the field `name`.
+	fn get(&self, name: &str) -> Result<&'a str, UnknownCryptoError> {
+		self.fields
+			.iter()
+			.find(|(field, _)| *field == name)
+			.map(|(_, value)| *value)
+			.ok_or(UnknownCryptoError)
+	}
+
+	/// Return the value of the field `name` as a number.
+	fn get_usize(&self, name: &str) -> Result<usize, UnknownCryptoError> {
+		self.get(name)?.parse().map_err(|_| UnknownCryptoError)
+	}
+
+	/// Return true if the record has the field `name`.
+	fn has(&self, name: &str) -> bool { self.get(name).is_ok() }
+}
+
+/// Parse the records of the CAVP response file `rsp`.
+fn parse(rsp: &str) -> Result<Vec<Record<'_>>, UnknownCryptoError> {
+	let mut records = Vec::new();
+	let mut output_len = None;
+	let mut fields = Vec::new();
+
+	for line in rsp.lines().map(str::trim) {
+		if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+			if !fields.is_empty() {
+				records.push(Record { output_len, fields });
+				fields = Vec::new();
+			}
+			if line.starts_with('[') {
+				output_len = parse_section(line)?;
+			}
+			continue;
+		}
+
+		let mut parts = line.splitn(2, '=');
+		match (parts.next(), parts.next()) {
+			(Some(name), Some(value)) => fields.push((name.trim(), value.trim())),
+			_ => return Err(UnknownCryptoError),
+		}
+	}
+	if !fields.is_empty() {
+		records.push(Record { output_len, fields });
+	}
+
+	Ok(records)
+}
+
+/// Parse the output length of a section header such as `[L = 64]`. Other
+/// section headers are ignored.
+fn parse_section(line: &str) -> Result<Option<usize>, UnknownCryptoError> {
+	let inner = line.trim_start_matches('[').trim_end_matches(']');
+	let mut parts = inner.splitn(2, '=');
+	match (parts.next().map(str::trim), parts.next()) {
+		(Some("L"), Some(value)) => {
+			Ok(Some(value.trim().parse().map_err(|_| UnknownCryptoError)?))
+		}
+		_ => Ok(None),
+	}
+}
+
+/// Return the digest of `message` with the hash function `H`, or `None` if any
+/// step fails.
+fn compute_digest<H: HashFunction>(message: &[u8]) -> Option<Vec<u8>> {
+	let mut state = H::init();
+	state.update(message).ok()?;
+	let mut digest = vec![0u8; H::OUTSIZE];
+	state.finalize_into(&mut digest).ok()?;
+
+	Some(digest)
+}
+
+#[must_use]
+/// Run the hash records of the CAVP response file `rsp` against the hash
+/// function `H`.
+pub fn run_digest<H: HashFunction>(rsp: &str) -> Result<TestReport, UnknownCryptoError> {
+	let mut report = TestReport::default();
+
+	for record in parse(rsp)?.iter().filter(|record| record.has("MD")) {
+		let bit_len = record.get_usize("Len")?;
+		if bit_len % 8 != 0 {
+			continue;
+		}
+
+		let msg = decode_hex(record.get("Msg")?)?;
+		let expected = decode_hex(record.get("MD")?)?;
+		if msg.len() < bit_len / 8 {
+			return Err(UnknownCryptoError);
+		}
+
+		let passed = match compute_digest::<H>(&msg[..bit_len / 8]) {
+			Some(computed) => computed == expected,
+			None => false,
+		};
+		report.record(&format!("Len = {}", bit_len), passed);
+	}
+
+	Ok(report)
+}
+
+#[must_use]
+/// Run the MAC records of the CAVP response file `rsp` against the MAC whose
+/// states are made by `init` from a key. `tag_bytes` returns the bytes of a
+/// `M::Tag`, such as `hmac::Tag::unprotected_as_bytes`.
+pub fn run_mac<M, F, B>(init: F, tag_bytes: B, rsp: &str) -> Result<TestReport, UnknownCryptoError>
+where
+	M: Mac,
+	F: Fn(&[u8]) -> Result<M, UnknownCryptoError>,
+	B: Fn(&M::Tag) -> &[u8],
+{
+	let mut report = TestReport::default();
+
+	for record in parse(rsp)?.iter().filter(|record| record.has("Mac")) {
+		let key = decode_hex(record.get("Key")?)?;
+		let msg = decode_hex(record.get("Msg")?)?;
+		let expected = decode_hex(record.get("Mac")?)?;
+		let tag_len = record.get_usize("Tlen")?;
+		if key.len() != record.get_usize("Klen")? || expected.len() != tag_len {
+			return Err(UnknownCryptoError);
+		}
+
+		let computed = compute_tag(&init, &tag_bytes, &key, &msg);
+		if let (Some(computed), Some(output_len)) = (&computed, record.output_len) {
+			if computed.len() != output_len {
+				continue;
+			}
+		}
+
+		let passed = match computed {
+			Some(computed) => tag_len <= computed.len() && computed[..tag_len] == expected[..],
+			None => false,
+		};
+		let output_len = record.output_len.unwrap_or(0);
+		report.record(&format!("[L={}] Count = {}", output_len, record.get("Count")?), passed);
+	}
+
+	Ok(report)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use crate::hazardous::{
+		hash::{sha256::Sha256, sha512::Sha512},
+		mac::hmac,
+	};
+
+	fn hmac_init(key: &[u8]) -> Result<hmac::Hmac, UnknownCryptoError> {
+		Ok(hmac::init(&hmac::SecretKey::from_slice(key)?))
+	}
+
+	// One function tested per submodule.
+	mod test_run_digest {
+		use super::*;
+
+		const RSP: &str = "#  SHA-256 ShortMsg\n\n[L = 32]\n\nLen = 0\nMsg = 00\nMD = \
+		                   e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855\n\nLen \
+		                   = 24\nMsg = 616263\nMD = \
+		                   ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\n\nLen \
+		                   = 4\nMsg = 60\nMD = 00\n";
+
+		#[test]
+		fn test_report() {
+			let report = run_digest::<Sha256>(RSP).unwrap();
+			assert_eq!(report.run_count(), 2);
+			assert!(report.passed(), "{:?}", report.failures());
+
+			let report = run_digest::<Sha512>(RSP).unwrap();
+			assert_eq!(report.failures(), &["Len = 0".to_string(), "Len = 24".to_string()]);
+		}
+
+		#[test]
+		fn test_malformed_err() {
+			assert!(run_digest::<Sha256>("Len = 8\nMsg = 0\nMD = 00\n").is_err());
+			assert!(run_digest::<Sha256>("Len = 16\nMsg = 00\nMD = 00\n").is_err());
+			assert!(run_digest::<Sha256>("Len = x\nMsg = 00\nMD = 00\n").is_err());
+			assert!(run_digest::<Sha256>("Len 8\n").is_err());
+			assert!(run_digest::<Sha256>("[L = x]\n").is_err());
+		}
+	}
+
+	mod test_run_mac {
+		use super::*;
+
+		// Test case 1 from RFC 4231, in the format of the CAVP HMAC file.
+		const RSP: &str = "[L=20]\n\nCount = 0\nKlen = 1\nTlen = 1\nKey = 00\nMsg = 00\nMac = \
+		                   00\n\n[L=64]\n\nCount = 1\nKlen = 20\nTlen = 16\nKey = \
+		                   0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b\nMsg = 4869205468657265\nMac = \
+		                   87aa7cdea5ef619d4ff0b4241a1d6cb0\n\nCount = 2\nKlen = 20\nTlen = \
+		                   16\nKey = 0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b\nMsg = 00\nMac = \
+		                   87aa7cdea5ef619d4ff0b4241a1d6cb0\n";
+
+		#[test]
+		fn test_report() {
+			let report = run_mac(hmac_init, hmac::Tag::unprotected_as_bytes, RSP).unwrap();
+			assert_eq!(report.run_count(), 2);
+			assert_eq!(report.failures(), &["[L=64] Count = 2".to_string()]);
+		}
+
+		#[test]
+		fn test_malformed_err() {
+			let malformed = [
+				("Klen = 20", "Klen = 21"),
+				("Tlen = 16", "Tlen = 15"),
+				("Count", "Cnt"),
+			];
+			for (from, to) in malformed.iter() {
+				let rsp = RSP.replace(from, to);
+				assert!(run_mac(hmac_init, hmac::Tag::unprotected_as_bytes, &rsp).is_err());
+			}
+		}
+	}
+}
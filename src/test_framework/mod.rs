@@ -0,0 +1,197 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Reusable test-vector harness.
+//!
+//! # Use case:
+//! `orion::test_framework` can be used to run published test vectors against
+//! constructions built on top of orion, with the same tooling orion uses for
+//! its own primitives.
+//!
+//! An example of this could be a crate that wraps orion's AEADs, and wants to
+//! check its wrapper against the Wycheproof vectors.
+//!
+//! # About:
+//! - AEADs are tested through `hazardous::aead::Aead`, MACs through
+//!   `hazardous::mac::Mac` and hash functions through
+//!   `hazardous::hash::HashFunction`, so any type that implements these can
+//!   be tested.
+//! - `wycheproof` runs Wycheproof JSON vectors for AEADs and MACs.
+//! - `cavp` runs NIST CAVP response files (`.rsp`) for hashes and HMAC.
+//! - `streaming` checks that a streaming state gives the same output across
+//!   different usage patterns of `reset()`, `update()` and `finalize()`.
+//! - Each run returns a `TestReport` with the number of test cases that were
+//!   run, and a description of each one that failed.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - The test vectors cannot be parsed.
+//!
+//! # Security:
+//! - This module is only meant for testing. Passing a set of test vectors
+//!   gives no guarantee that a construction is secure.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::aead::chacha20poly1305::ChaCha20Poly1305;
+//! use orion::test_framework::wycheproof;
+//!
+//! let vectors = r#"{
+//!     "testGroups": [{ "tests": [{
+//!         "tcId": 1,
+//!         "key": "cc56b680552eb75008f5484b4cb803fa5063ebd6eab91f6ab6aef4916a766273",
+//!         "iv": "99e23ec48985bccdeeab60f1",
+//!         "aad": "", "msg": "2a", "ct": "3a",
+//!         "tag": "cac27dec0968801e9f6eded69d807522",
+//!         "result": "valid"
+//!     }]}]
+//! }"#;
+//!
+//! let report = wycheproof::run_aead::<ChaCha20Poly1305>(vectors).unwrap();
+//! assert_eq!(report.run_count(), 1);
+//! assert!(report.passed(), "{:?}", report.failures());
+//! ```
+
+/// NIST CAVP response files.
+pub mod cavp;
+
+/// Wycheproof JSON test vectors.
+pub mod wycheproof;
+
+/// Consistency checks for streaming states.
+pub mod streaming;
+
+use crate::{encoding::hex_decode_into, errors::UnknownCryptoError, hazardous::mac::Mac};
+
+#[derive(Debug, Default)]
+/// The result of running a set of test vectors.
+pub struct TestReport {
+	run: usize,
+	failures: Vec<String>,
+}
+
+impl TestReport {
+	/// Record the result of the test case described by `name`.
+	fn record(&mut self, name: &str, passed: bool) {
+		self.run += 1;
+		if !passed {
+			self.failures.push(name.to_string());
+		}
+	}
+
+	/// Return the number of test cases that were run.
+	pub fn run_count(&self) -> usize { self.run }
+
+	/// Return a description of each test case that failed.
+	pub fn failures(&self) -> &[String] { &self.failures }
+
+	/// Return true if all test cases passed.
+	pub fn passed(&self) -> bool { self.failures.is_empty() }
+}
+
+/// Decode the hex string `src`.
+fn decode_hex(src: &str) -> Result<Vec<u8>, UnknownCryptoError> {
+	if src.len() % 2 != 0 {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut dst = vec![0u8; src.len() / 2];
+	hex_decode_into(src, &mut dst)?;
+
+	Ok(dst)
+}
+
+/// Return the bytes of the tag of `message`, with a state made by `init` from
+/// `key`, or `None` if any step fails.
+fn compute_tag<M, F, B>(init: &F, tag_bytes: &B, key: &[u8], message: &[u8]) -> Option<Vec<u8>>
+where
+	M: Mac,
+	F: Fn(&[u8]) -> Result<M, UnknownCryptoError>,
+	B: Fn(&M::Tag) -> &[u8],
+{
+	let mut state = init(key).ok()?;
+	state.update(message).ok()?;
+	let tag = state.finalize().ok()?;
+
+	Some(tag_bytes(&tag).to_vec())
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	mod test_decode_hex {
+		use super::*;
+
+		#[test]
+		fn test_decode_hex() {
+			assert_eq!(decode_hex("").unwrap(), Vec::<u8>::new());
+			assert_eq!(decode_hex("00fF").unwrap(), vec![0x00, 0xff]);
+			assert!(decode_hex("0").is_err());
+			assert!(decode_hex("0g").is_err());
+		}
+	}
+
+	mod test_compute_tag {
+		use super::*;
+		use crate::hazardous::mac::hmac;
+
+		fn hmac_init(key: &[u8]) -> Result<hmac::Hmac, UnknownCryptoError> {
+			Ok(hmac::init(&hmac::SecretKey::from_slice(key)?))
+		}
+
+		#[test]
+		fn test_compute_tag() {
+			let sk = hmac::SecretKey::from_slice(&[0u8; 32]).unwrap();
+			let expected = hmac::hmac(&sk, b"Data").unwrap();
+			assert_eq!(
+				compute_tag(&hmac_init, &hmac::Tag::unprotected_as_bytes, &[0u8; 32], b"Data")
+					.unwrap(),
+				expected.unprotected_as_bytes()
+			);
+		}
+
+		#[test]
+		fn test_init_err() {
+			let init =
+				|_: &[u8]| -> Result<hmac::Hmac, UnknownCryptoError> { Err(UnknownCryptoError) };
+			assert!(compute_tag(&init, &hmac::Tag::unprotected_as_bytes, &[0u8; 32], b"Data")
+				.is_none());
+		}
+	}
+
+	mod test_report {
+		use super::*;
+
+		#[test]
+		fn test_record() {
+			let mut report = TestReport::default();
+			assert!(report.passed());
+			report.record("a", true);
+			report.record("b", false);
+			assert_eq!(report.run_count(), 2);
+			assert!(!report.passed());
+			assert_eq!(report.failures(), &["b".to_string()]);
+		}
+	}
+}
@@ -0,0 +1,384 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! - A streaming state is tested by implementing `StreamingState` for it.
+//!   Implementations for orion's SHA256, SHA512, HMAC and Poly1305 states are
+//!   provided.
+//! - `same_output_across_usage()` checks that the output is the same whether
+//!   the state is fresh, reset before use, reset after an `update()`, or reset
+//!   after a `finalize()`. This is the check orion runs on its own states,
+//!   after [issue #46](https://github.com/brycx/orion/issues/46).
+//! - `incremental_same_as_one_shot()` checks that updating with `data` split
+//!   into chunks, including empty ones, gives the same output as a single
+//!   `update()`.
+//! - `clone_same_as_original()` checks that a clone of a state taken partway
+//!   through the data, such as to branch a stream, gives the same output as
+//!   the original and that updating one does not affect the other.
+//! - The functions return `false` instead of panicking, so they can be used
+//!   directly as `quickcheck` properties.
+//!
+//! # Example:
+//! ```
+//! use orion::hazardous::hash::sha512;
+//! use orion::test_framework::streaming;
+//!
+//! let data = b"Some data to stream";
+//! assert!(streaming::same_output_across_usage(sha512::init, data));
+//! assert!(streaming::incremental_same_as_one_shot(sha512::init, data, 3));
+//! assert!(streaming::clone_same_as_original(sha512::init, data, 7));
+//! ```
+
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{
+		hash::{sha256, sha512},
+		mac::{hmac, poly1305},
+	},
+};
+
+/// A streaming state that can be tested with this module.
+pub trait StreamingState {
+	/// The output of `finalize()`.
+	type Output: PartialEq;
+
+	/// Reset the state to how it was when initialized.
+	fn reset(&mut self) -> Result<(), UnknownCryptoError>;
+
+	/// Update the state with `data`.
+	fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError>;
+
+	/// Return the output of all data the state has been updated with.
+	fn finalize(&mut self) -> Result<Self::Output, UnknownCryptoError>;
+}
+
+/// Implement `StreamingState` for an orion state whose `reset()` cannot fail.
+macro_rules! impl_streaming_state (($state:ty, $output:ty) => (
+	impl StreamingState for $state {
+		type Output = $output;
+
+		fn reset(&mut self) -> Result<(), UnknownCryptoError> {
+			<$state>::reset(self);
+			Ok(())
+		}
+
+		fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+			Ok(<$state>::update(self, data)?)
+		}
+
+		fn finalize(&mut self) -> Result<Self::Output, UnknownCryptoError> {
+			Ok(<$state>::finalize(self)?)
+		}
+	}
+));
+
+impl_streaming_state!(sha256::Sha256, sha256::Digest);
+impl_streaming_state!(sha512::Sha512, sha512::Digest);
+impl_streaming_state!(hmac::Hmac, hmac::Tag);
+impl_streaming_state!(hmac::sha256::Hmac, hmac::sha256::Tag);
+impl_streaming_state!(poly1305::Poly1305, poly1305::Tag);
+
+/// Return the output of the states created by `new`, updated with `data`
+/// in each of the usage patterns.
+fn outputs_across_usage<S, F>(new: F, data: &[u8]) -> Result<[S::Output; 4], UnknownCryptoError>
+where
+	S: StreamingState,
+	F: Fn() -> S,
+{
+	// init(), update(), finalize()
+	let mut state_1 = new();
+	state_1.update(data)?;
+	let res_1 = state_1.finalize()?;
+
+	// init(), reset(), update(), finalize()
+	let mut state_2 = new();
+	state_2.reset()?;
+	state_2.update(data)?;
+	let res_2 = state_2.finalize()?;
+
+	// init(), update(), reset(), update(), finalize()
+	let mut state_3 = new();
+	state_3.update(data)?;
+	state_3.reset()?;
+	state_3.update(data)?;
+	let res_3 = state_3.finalize()?;
+
+	// init(), update(), finalize(), reset(), update(), finalize()
+	let mut state_4 = new();
+	state_4.update(data)?;
+	let _ = state_4.finalize()?;
+	state_4.reset()?;
+	state_4.update(data)?;
+	let res_4 = state_4.finalize()?;
+
+	Ok([res_1, res_2, res_3, res_4])
+}
+
+#[must_use]
+/// Return true if the states created by `new` give the same output for `data`
+/// across different usage patterns of `reset()`, `update()` and `finalize()`.
+pub fn same_output_across_usage<S, F>(new: F, data: &[u8]) -> bool
+where
+	S: StreamingState,
+	F: Fn() -> S,
+{
+	match outputs_across_usage(new, data) {
+		Ok(outputs) => outputs.iter().all(|output| *output == outputs[0]),
+		Err(_) => false,
+	}
+}
+
+#[must_use]
+/// Return true if updating a state created by `new` with `data` split into
+/// chunks of `chunk_size` bytes, with an empty update between each chunk,
+/// gives the same output as a single `update()` with `data`.
+pub fn incremental_same_as_one_shot<S, F>(new: F, data: &[u8], chunk_size: usize) -> bool
+where
+	S: StreamingState,
+	F: Fn() -> S,
+{
+	let incremental = || -> Result<S::Output, UnknownCryptoError> {
+		let mut state = new();
+		for chunk in data.chunks(chunk_size.max(1)) {
+			state.update(chunk)?;
+			state.update(b"")?;
+		}
+		state.finalize()
+	};
+	let one_shot = || -> Result<S::Output, UnknownCryptoError> {
+		let mut state = new();
+		state.update(data)?;
+		state.finalize()
+	};
+
+	match (incremental(), one_shot()) {
+		(Ok(incremental), Ok(one_shot)) => incremental == one_shot,
+		_ => false,
+	}
+}
+
+#[must_use]
+/// Return true if a clone of a state created by `new`, taken after updating
+/// it with the first `split` bytes of `data` and then updated with the rest,
+/// gives the same output as a single `update()` with `data`. The original is
+/// updated with other data, finalized and reset before the clone is used.
+pub fn clone_same_as_original<S, F>(new: F, data: &[u8], split: usize) -> bool
+where
+	S: StreamingState + Clone,
+	F: Fn() -> S,
+{
+	let outputs = || -> Result<bool, UnknownCryptoError> {
+		let (first, rest) = data.split_at(split.min(data.len()));
+
+		let mut one_shot = new();
+		one_shot.update(data)?;
+		let expected = one_shot.finalize()?;
+
+		let mut original = new();
+		original.update(first)?;
+		let mut branch = original.clone();
+
+		// Update and finalize the original with other data before the clone
+		// is used.
+		original.update(b"Diverging data")?;
+		let _ = original.finalize()?;
+		original.reset()?;
+		original.update(data)?;
+
+		branch.update(rest)?;
+		Ok(branch.finalize()? == expected && original.finalize()? == expected)
+	};
+
+	outputs().unwrap_or(false)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	fn hmac_state() -> hmac::Hmac { hmac::init(&hmac::SecretKey::from_slice(&[0x0b; 32]).unwrap()) }
+
+	fn hmac_sha256_state() -> hmac::sha256::Hmac {
+		hmac::sha256::init(&hmac::sha256::SecretKey::from_slice(&[0x0b; 32]).unwrap())
+	}
+
+	fn poly1305_state() -> poly1305::Poly1305 {
+		poly1305::init(&poly1305::OneTimeKey::from_slice(&[0x0b; 32]).unwrap())
+	}
+
+	/// A state that forgets to clear its buffered data on `reset()`.
+	struct BrokenReset {
+		data: Vec<u8>,
+	}
+
+	impl StreamingState for BrokenReset {
+		type Output = Vec<u8>;
+
+		fn reset(&mut self) -> Result<(), UnknownCryptoError> { Ok(()) }
+
+		fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+			self.data.extend_from_slice(data);
+			Ok(())
+		}
+
+		fn finalize(&mut self) -> Result<Vec<u8>, UnknownCryptoError> { Ok(self.data.clone()) }
+	}
+
+	/// A state that only keeps the data of the last `update()`.
+	struct BrokenUpdate {
+		data: Vec<u8>,
+	}
+
+	impl StreamingState for BrokenUpdate {
+		type Output = Vec<u8>;
+
+		fn reset(&mut self) -> Result<(), UnknownCryptoError> {
+			self.data.clear();
+			Ok(())
+		}
+
+		fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+			self.data = data.to_vec();
+			Ok(())
+		}
+
+		fn finalize(&mut self) -> Result<Vec<u8>, UnknownCryptoError> { Ok(self.data.clone()) }
+	}
+
+	/// A state whose clones share their buffered data with the original.
+	#[derive(Clone)]
+	struct BrokenClone {
+		data: std::rc::Rc<std::cell::RefCell<Vec<u8>>>,
+	}
+
+	impl StreamingState for BrokenClone {
+		type Output = Vec<u8>;
+
+		fn reset(&mut self) -> Result<(), UnknownCryptoError> {
+			self.data.borrow_mut().clear();
+			Ok(())
+		}
+
+		fn update(&mut self, data: &[u8]) -> Result<(), UnknownCryptoError> {
+			self.data.borrow_mut().extend_from_slice(data);
+			Ok(())
+		}
+
+		fn finalize(&mut self) -> Result<Vec<u8>, UnknownCryptoError> { Ok(self.data.borrow().clone()) }
+	}
+
+	// One function tested per submodule.
+	mod test_same_output_across_usage {
+		use super::*;
+
+		#[test]
+		fn test_orion_states() {
+			for len in [0, 1, 63, 64, 65, 128, 129, 300].iter() {
+				let data = vec![0xa5u8; *len];
+				assert!(same_output_across_usage(sha256::init, &data));
+				assert!(same_output_across_usage(sha512::init, &data));
+				assert!(same_output_across_usage(hmac_state, &data));
+				assert!(same_output_across_usage(hmac_sha256_state, &data));
+				assert!(same_output_across_usage(poly1305_state, &data));
+			}
+		}
+
+		#[test]
+		fn test_broken_reset_detected() {
+			let new = || BrokenReset { data: Vec::new() };
+			assert!(same_output_across_usage(new, b""));
+			assert!(!same_output_across_usage(new, b"Data"));
+		}
+	}
+
+	mod test_incremental_same_as_one_shot {
+		use super::*;
+
+		#[test]
+		fn test_orion_states() {
+			let data = vec![0xa5u8; 300];
+			for chunk_size in [0, 1, 7, 64, 128, 300, 301].iter() {
+				assert!(incremental_same_as_one_shot(sha256::init, &data, *chunk_size));
+				assert!(incremental_same_as_one_shot(sha512::init, &data, *chunk_size));
+				assert!(incremental_same_as_one_shot(hmac_state, &data, *chunk_size));
+				assert!(incremental_same_as_one_shot(hmac_sha256_state, &data, *chunk_size));
+				assert!(incremental_same_as_one_shot(poly1305_state, &data, *chunk_size));
+			}
+		}
+
+		#[test]
+		fn test_broken_update_detected() {
+			let new = || BrokenUpdate { data: Vec::new() };
+			assert!(incremental_same_as_one_shot(new, b"", 4));
+			assert!(!incremental_same_as_one_shot(new, b"Data", 3));
+			// Caught by the empty update after the only chunk.
+			assert!(!incremental_same_as_one_shot(new, b"Data", 4));
+		}
+	}
+
+	mod test_clone_same_as_original {
+		use super::*;
+
+		#[test]
+		fn test_orion_states() {
+			let data = vec![0xa5u8; 300];
+			for split in [0, 1, 7, 64, 128, 300, 301].iter() {
+				assert!(clone_same_as_original(sha256::init, &data, *split));
+				assert!(clone_same_as_original(sha512::init, &data, *split));
+				assert!(clone_same_as_original(hmac_state, &data, *split));
+				assert!(clone_same_as_original(hmac_sha256_state, &data, *split));
+				assert!(clone_same_as_original(poly1305_state, &data, *split));
+			}
+		}
+
+		#[test]
+		fn test_broken_clone_detected() {
+			let new = || BrokenClone {
+				data: std::rc::Rc::new(std::cell::RefCell::new(Vec::new())),
+			};
+			assert!(!clone_same_as_original(new, b"Data", 2));
+		}
+	}
+
+	// Proptests. Only exectued when NOT testing no_std.
+	mod proptest {
+		use super::*;
+
+		quickcheck! {
+			fn prop_same_output_across_usage(data: Vec<u8>) -> bool {
+				same_output_across_usage(sha512::init, &data[..])
+					&& same_output_across_usage(hmac_state, &data[..])
+			}
+
+			fn prop_incremental_same_as_one_shot(data: Vec<u8>, chunk_size: usize) -> bool {
+				incremental_same_as_one_shot(sha512::init, &data[..], chunk_size % 256)
+					&& incremental_same_as_one_shot(poly1305_state, &data[..], chunk_size % 256)
+			}
+
+			fn prop_clone_same_as_original(data: Vec<u8>, split: usize) -> bool {
+				clone_same_as_original(sha256::init, &data[..], split)
+					&& clone_same_as_original(hmac_state, &data[..], split)
+			}
+		}
+	}
+}
@@ -0,0 +1,260 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! # About:
+//! - The `testGroups` of a Wycheproof JSON file are run in order, and each
+//!   test case is described in the `TestReport` by its `tcId`.
+//! - For a MAC, the tag is truncated to the `tagSize` of its test group.
+//! - A test case with the result `valid` passes if the computed output
+//!   matches. For an AEAD, the ciphertext must also decrypt to the message.
+//! - A test case with the result `invalid` passes if the output does not
+//!   match, or an error is returned. For an AEAD, decryption must fail.
+//! - A test case with the result `acceptable` always passes.
+
+use super::{compute_tag, decode_hex, TestReport};
+use crate::{
+	errors::UnknownCryptoError,
+	hazardous::{aead::Aead, mac::Mac},
+};
+use core::convert::TryFrom;
+use serde_json::Value;
+
+/// The expected result of a Wycheproof test case.
+enum Expected {
+	Valid,
+	Invalid,
+	Acceptable,
+}
+
+/// Return the string field `name` of `value`.
+fn get_str<'a>(value: &'a Value, name: &str) -> Result<&'a str, UnknownCryptoError> {
+	value.get(name).and_then(Value::as_str).ok_or(UnknownCryptoError)
+}
+
+/// Return the hex-decoded field `name` of `value`.
+fn get_hex(value: &Value, name: &str) -> Result<Vec<u8>, UnknownCryptoError> {
+	decode_hex(get_str(value, name)?)
+}
+
+/// Return the description of `test_case` used in a `TestReport`.
+fn describe(test_case: &Value) -> Result<String, UnknownCryptoError> {
+	let tc_id = test_case.get("tcId").and_then(Value::as_u64).ok_or(UnknownCryptoError)?;
+
+	Ok(format!("tcId {}", tc_id))
+}
+
+/// Return the expected result of `test_case`.
+fn expected(test_case: &Value) -> Result<Expected, UnknownCryptoError> {
+	match get_str(test_case, "result")? {
+		"valid" => Ok(Expected::Valid),
+		"invalid" => Ok(Expected::Invalid),
+		"acceptable" => Ok(Expected::Acceptable),
+		_ => Err(UnknownCryptoError),
+	}
+}
+
+/// Call `run` with each test group and each of its test cases in `vectors`.
+fn for_each_test<F>(vectors: &str, mut run: F) -> Result<TestReport, UnknownCryptoError>
+where
+	F: FnMut(&Value, &Value) -> Result<bool, UnknownCryptoError>,
+{
+	let vectors: Value = serde_json::from_str(vectors).map_err(|_| UnknownCryptoError)?;
+	let test_groups = vectors
+		.get("testGroups")
+		.and_then(Value::as_array)
+		.ok_or(UnknownCryptoError)?;
+
+	let mut report = TestReport::default();
+	for test_group in test_groups.iter() {
+		let tests = test_group.get("tests").and_then(Value::as_array).ok_or(UnknownCryptoError)?;
+		for test_case in tests.iter() {
+			let passed = match expected(test_case)? {
+				Expected::Acceptable => true,
+				Expected::Valid => run(test_group, test_case)?,
+				Expected::Invalid => !run(test_group, test_case)?,
+			};
+			report.record(&describe(test_case)?, passed);
+		}
+	}
+
+	Ok(report)
+}
+
+#[must_use]
+/// Run the Wycheproof `AeadTest` vectors in `vectors` against the AEAD `A`.
+/// A test case with a key or nonce that `A` does not accept fails.
+pub fn run_aead<A>(vectors: &str) -> Result<TestReport, UnknownCryptoError>
+where
+	A: Aead,
+	A::SecretKey: for<'a> TryFrom<&'a [u8]>,
+	A::Nonce: for<'a> TryFrom<&'a [u8]>,
+{
+	for_each_test(vectors, |_, test_case| {
+		let key = get_hex(test_case, "key")?;
+		let iv = get_hex(test_case, "iv")?;
+		let ad = get_hex(test_case, "aad")?;
+		let msg = get_hex(test_case, "msg")?;
+		let ct = get_hex(test_case, "ct")?;
+		let mut ciphertext_with_tag = ct.clone();
+		ciphertext_with_tag.extend_from_slice(&get_hex(test_case, "tag")?);
+
+		let secret_key = A::SecretKey::try_from(&key[..]);
+		let nonce = A::Nonce::try_from(&iv[..]);
+		let (secret_key, nonce) = match (secret_key, nonce) {
+			(Ok(secret_key), Ok(nonce)) => (secret_key, nonce),
+			_ => return Ok(false),
+		};
+
+		let mut dst_out_ct = vec![0u8; ciphertext_with_tag.len()];
+		let sealed = A::seal(&secret_key, &nonce, &msg, Some(&ad[..]), &mut dst_out_ct)
+			.map(|_| dst_out_ct == ciphertext_with_tag);
+		let mut dst_out_pt = vec![0u8; ct.len()];
+		let opened = A::open(
+			&secret_key,
+			&nonce,
+			&ciphertext_with_tag,
+			Some(&ad[..]),
+			&mut dst_out_pt,
+		)
+		.map(|_| dst_out_pt == msg);
+
+		Ok(match expected(test_case)? {
+			// An invalid ciphertext must be rejected, regardless of whether
+			// sealing would have produced it.
+			Expected::Invalid => opened.unwrap_or(false),
+			_ => sealed.unwrap_or(false) && opened.unwrap_or(false),
+		})
+	})
+}
+
+#[must_use]
+/// Run the Wycheproof `MacTest` vectors in `vectors` against the MAC whose
+/// states are made by `init` from a key. `tag_bytes` returns the bytes of a
+/// `M::Tag`, such as `hmac::Tag::unprotected_as_bytes`.
+pub fn run_mac<M, F, B>(
+	init: F,
+	tag_bytes: B,
+	vectors: &str,
+) -> Result<TestReport, UnknownCryptoError>
+where
+	M: Mac,
+	F: Fn(&[u8]) -> Result<M, UnknownCryptoError>,
+	B: Fn(&M::Tag) -> &[u8],
+{
+	for_each_test(vectors, |test_group, test_case| {
+		let tag_size = test_group.get("tagSize").and_then(Value::as_u64).ok_or(UnknownCryptoError)?;
+		let key = get_hex(test_case, "key")?;
+		let msg = get_hex(test_case, "msg")?;
+		let tag = get_hex(test_case, "tag")?;
+
+		Ok(match compute_tag(&init, &tag_bytes, &key, &msg) {
+			Some(computed) => {
+				let tag_len = (tag_size / 8) as usize;
+				tag_len <= computed.len() && computed[..tag_len] == tag[..]
+			}
+			None => false,
+		})
+	})
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use crate::hazardous::{aead::chacha20poly1305::ChaCha20Poly1305, mac::hmac};
+
+	fn hmac_init(key: &[u8]) -> Result<hmac::Hmac, UnknownCryptoError> {
+		Ok(hmac::init(&hmac::SecretKey::from_slice(key)?))
+	}
+
+	// One function tested per submodule.
+	mod test_run_aead {
+		use super::*;
+
+		const VECTORS: &str = r#"{ "testGroups": [{ "tests": [
+			{ "tcId": 4, "result": "valid",
+			  "key": "cc56b680552eb75008f5484b4cb803fa5063ebd6eab91f6ab6aef4916a766273",
+			  "iv": "99e23ec48985bccdeeab60f1", "aad": "", "msg": "2a", "ct": "3a",
+			  "tag": "cac27dec0968801e9f6eded69d807522" },
+			{ "tcId": 5, "result": "invalid",
+			  "key": "cc56b680552eb75008f5484b4cb803fa5063ebd6eab91f6ab6aef4916a766273",
+			  "iv": "99e23ec48985bccdeeab60f1", "aad": "", "msg": "2a", "ct": "3a",
+			  "tag": "cac27dec0968801e9f6eded69d807523" },
+			{ "tcId": 6, "result": "valid",
+			  "key": "cc56b680552eb75008f5484b4cb803fa5063ebd6eab91f6ab6aef4916a766273",
+			  "iv": "99e23ec48985bccdeeab60f1", "aad": "", "msg": "2b", "ct": "3a",
+			  "tag": "cac27dec0968801e9f6eded69d807522" },
+			{ "tcId": 7, "result": "acceptable",
+			  "key": "", "iv": "", "aad": "", "msg": "", "ct": "", "tag": "" }
+		]}]}"#;
+
+		#[test]
+		fn test_report() {
+			let report = run_aead::<ChaCha20Poly1305>(VECTORS).unwrap();
+			assert_eq!(report.run_count(), 4);
+			assert_eq!(report.failures(), &["tcId 6".to_string()]);
+		}
+
+		#[test]
+		fn test_malformed_err() {
+			assert!(run_aead::<ChaCha20Poly1305>("").is_err());
+			assert!(run_aead::<ChaCha20Poly1305>("{}").is_err());
+			assert!(run_aead::<ChaCha20Poly1305>(VECTORS.replace("\"valid\"", "\"?\"").as_str())
+				.is_err());
+			assert!(run_aead::<ChaCha20Poly1305>(VECTORS.replace("3a", "3").as_str()).is_err());
+		}
+	}
+
+	mod test_run_mac {
+		use super::*;
+
+		// Test case 1 from RFC 4231, with the tag truncated to 256 and 128 bits.
+		const VECTORS: &str = r#"{ "testGroups": [
+			{ "tagSize": 256, "tests": [
+				{ "tcId": 1, "result": "valid",
+				  "key": "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b", "msg": "4869205468657265",
+				  "tag": "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde" }
+			]},
+			{ "tagSize": 128, "tests": [
+				{ "tcId": 2, "result": "valid",
+				  "key": "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b", "msg": "4869205468657265",
+				  "tag": "87aa7cdea5ef619d4ff0b4241a1d6cb0" },
+				{ "tcId": 3, "result": "invalid",
+				  "key": "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b", "msg": "4869205468657266",
+				  "tag": "87aa7cdea5ef619d4ff0b4241a1d6cb0" }
+			]}
+		]}"#;
+
+		#[test]
+		fn test_report() {
+			let report = run_mac(hmac_init, hmac::Tag::unprotected_as_bytes, VECTORS).unwrap();
+			assert_eq!(report.run_count(), 3);
+			assert!(report.passed(), "{:?}", report.failures());
+		}
+
+		#[test]
+		fn test_missing_tag_size_err() {
+			let vectors = VECTORS.replace("\"tagSize\"", "\"size\"");
+			assert!(run_mac(hmac_init, hmac::Tag::unprotected_as_bytes, &vectors).is_err());
+		}
+	}
+}
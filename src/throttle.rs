@@ -0,0 +1,167 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Minimum-duration padding of verification calls.
+//!
+//! # Use case:
+//! `orion::throttle` can be used to make checks of passwords, tokens or tags
+//! that are reachable over a network always take at least a fixed amount of
+//! time, whether they succeed or fail.
+//!
+//! # About:
+//! - `Throttle::run()` calls the given closure and then sleeps the current
+//!   thread until at least `floor` has passed since the call started.
+//! - If the closure takes longer than `floor`, `run()` returns as soon as it
+//!   is done.
+//! - The result of the closure is returned unchanged.
+//!
+//! # Parameters:
+//! - `floor`: The minimum duration of a call to `run()`.
+//! - `verification`: The closure that performs the verification.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `floor` is zero.
+//!
+//! # Security:
+//! - `floor` should be higher than the slowest time the verification takes
+//!   in practice. Otherwise, the timing of slow calls is still observable.
+//! - The padding hides differences in timing between a successful and a
+//!   failed verification, such as a user that does not exist returning
+//!   earlier. It does not replace constant-time comparison, which orion's
+//!   verification functions already use.
+//! - Padding slows down brute-force attempts from a single client, but an
+//!   attacker can make many calls in parallel. Limiting the number of
+//!   attempts per account or per client must still be done by the caller.
+//! - Sleeping blocks the current thread. In an asynchronous server, `run()`
+//!   should be called from a thread where blocking is allowed.
+//!
+//! # Example:
+//! ```
+//! use orion::{pwhash, throttle::Throttle};
+//! use std::time::Duration;
+//!
+//! let throttle = Throttle::new(Duration::from_millis(10)).unwrap();
+//!
+//! let password = pwhash::Password::from_slice(b"Secret password").unwrap();
+//! let hash = pwhash::hash_password(&password, 10000).unwrap();
+//!
+//! let verified = throttle.run(|| pwhash::hash_password_verify(&hash, &password));
+//! assert!(verified.unwrap());
+//! ```
+
+use crate::errors::UnknownCryptoError;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Pads calls to a minimum duration.
+pub struct Throttle {
+	floor: Duration,
+}
+
+impl Throttle {
+	#[must_use]
+	/// Create a `Throttle` that makes each call to `run()` take at least
+	/// `floor`.
+	pub fn new(floor: Duration) -> Result<Self, UnknownCryptoError> {
+		if floor == Duration::from_secs(0) {
+			return Err(UnknownCryptoError);
+		}
+
+		Ok(Self { floor })
+	}
+
+	#[must_use]
+	/// Return the minimum duration of a call to `run()`.
+	pub fn floor(&self) -> Duration { self.floor }
+
+	#[must_use]
+	/// Call `verification` and return its result once at least `floor` has
+	/// passed since the call started.
+	pub fn run<T, F>(&self, verification: F) -> T
+	where
+		F: FnOnce() -> T,
+	{
+		let start = Instant::now();
+		let result = verification();
+
+		let elapsed = start.elapsed();
+		if elapsed < self.floor {
+			std::thread::sleep(self.floor - elapsed);
+		}
+
+		result
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_new {
+		use super::*;
+
+		#[test]
+		fn test_zero_floor_err() {
+			assert!(Throttle::new(Duration::from_secs(0)).is_err());
+			assert!(Throttle::new(Duration::from_nanos(1)).is_ok());
+		}
+
+		#[test]
+		fn test_floor() {
+			let throttle = Throttle::new(Duration::from_millis(25)).unwrap();
+			assert_eq!(throttle.floor(), Duration::from_millis(25));
+		}
+	}
+
+	mod test_run {
+		use super::*;
+
+		#[test]
+		fn test_run_is_padded() {
+			let throttle = Throttle::new(Duration::from_millis(20)).unwrap();
+
+			let start = Instant::now();
+			let result: Result<bool, ()> = throttle.run(|| Err(()));
+			assert!(start.elapsed() >= Duration::from_millis(20));
+			assert!(result.is_err());
+
+			let start = Instant::now();
+			assert_eq!(throttle.run(|| 42), 42);
+			assert!(start.elapsed() >= Duration::from_millis(20));
+		}
+
+		#[test]
+		fn test_run_slower_than_floor() {
+			let throttle = Throttle::new(Duration::from_millis(1)).unwrap();
+
+			let start = Instant::now();
+			let value = throttle.run(|| {
+				std::thread::sleep(Duration::from_millis(20));
+				42
+			});
+			assert_eq!(value, 42);
+			assert!(start.elapsed() >= Duration::from_millis(20));
+		}
+	}
+}
@@ -0,0 +1,446 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Timing leak detection.
+//!
+//! # Use case:
+//! `orion::timing` can be used to look for timing leaks in constructions
+//! built on top of orion, with the same harness orion uses for its own
+//! constant-time checks.
+//!
+//! An example of this could be a crate that compares secret tokens, and wants
+//! to check that the comparison takes the same time whether the tokens match
+//! or not.
+//!
+//! # About:
+//! - This follows [dudect](https://eprint.iacr.org/2016/1123.pdf). The
+//!   operation is run on inputs of two classes, `Class::Fixed` and
+//!   `Class::Random`, picked at random for each measurement. The execution
+//!   times of the two classes are then compared with Welch's t-test.
+//! - An operation is tested by implementing `TimingUnderTest` for it.
+//!   Implementations for some of orion's own primitives are provided.
+//! - All inputs are prepared before any measurement is taken, so only
+//!   `TimingUnderTest::run()` is timed.
+//! - Besides all measurements, the measurements below the 50th, 75th, 90th,
+//!   95th and 99th percentile are tested on their own, since a leak may only
+//!   be visible without the slowest runs. The largest t-statistic is
+//!   reported.
+//! - A t-statistic above `T_THRESHOLD` is taken as evidence of a timing leak.
+//!
+//! # Parameters:
+//! - `target`: The operation under test.
+//! - `measurements`: The number of times the operation is timed.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `measurements` is less than 1000.
+//! - `target` fails to prepare an input.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - This module is only meant for testing. Not finding a leak gives no
+//!   guarantee that an operation runs in constant time; a leak may be too
+//!   small to detect with the number of measurements taken, or only show on
+//!   other hardware.
+//! - Results depend on the compiler and its optimization level. Run the tests
+//!   in release mode, such as `cargo test --release --features timing_tests
+//!   -- --ignored`, on an otherwise idle machine.
+//! - `Class::Fixed` should be the input that is most likely to make the
+//!   operation behave differently, such as a tag that matches the expected
+//!   one.
+//!
+//! # Example:
+//! ```
+//! use orion::timing::{self, SecureCmp};
+//!
+//! let report = timing::measure(&mut SecureCmp::new(64).unwrap(), 10000).unwrap();
+//! assert_eq!(report.measurements(), 10000);
+//! println!("max |t|: {}, leaks: {}", report.max_t(), report.leaks());
+//! ```
+//!
+//! A downstream construction implements `TimingUnderTest`:
+//! ```
+//! use orion::{errors::UnknownCryptoError, timing::{self, Class, TimingUnderTest}};
+//!
+//! struct TokenCheck {
+//!     stored: Vec<u8>,
+//! }
+//!
+//! impl TimingUnderTest for TokenCheck {
+//!     type Input = Vec<u8>;
+//!
+//!     fn prepare_input(&mut self, class: Class) -> Result<Vec<u8>, UnknownCryptoError> {
+//!         match class {
+//!             Class::Fixed => Ok(self.stored.clone()),
+//!             Class::Random => {
+//!                 let mut token = vec![0u8; self.stored.len()];
+//!                 orion::util::secure_rand_bytes(&mut token)?;
+//!                 Ok(token)
+//!             }
+//!         }
+//!     }
+//!
+//!     fn run(&mut self, input: &Vec<u8>) {
+//!         let _ = orion::util::secure_cmp(&self.stored, input);
+//!     }
+//! }
+//!
+//! let mut target = TokenCheck { stored: vec![0x42; 32] };
+//! let report = timing::measure(&mut target, 10000).unwrap();
+//! assert_eq!(report.measurements(), 10000);
+//! ```
+
+use crate::{errors::UnknownCryptoError, hazardous::mac::hmac, util};
+use std::time::Instant;
+
+/// The t-statistic above which a timing leak is reported.
+pub const T_THRESHOLD: f64 = 4.5;
+
+/// The minimum number of measurements `measure()` accepts.
+const MIN_MEASUREMENTS: usize = 1000;
+
+/// The percentiles below which measurements are also tested on their own.
+const CROP_PERCENTILES: [f64; 5] = [0.50, 0.75, 0.90, 0.95, 0.99];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The class of an input.
+pub enum Class {
+	/// The same input every time.
+	Fixed,
+	/// A new random input every time.
+	Random,
+}
+
+/// An operation whose execution time can be tested with `measure()`.
+pub trait TimingUnderTest {
+	/// The input that is passed to `run()`.
+	type Input;
+
+	/// Return an input of `class`. This is not timed.
+	fn prepare_input(&mut self, class: Class) -> Result<Self::Input, UnknownCryptoError>;
+
+	/// Run the operation on `input`. This is timed.
+	fn run(&mut self, input: &Self::Input);
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// Welch's t-test between two classes, with the mean and variance of each
+/// class updated one measurement at a time.
+struct WelchTTest {
+	count: [f64; 2],
+	mean: [f64; 2],
+	sum_squares: [f64; 2],
+}
+
+impl WelchTTest {
+	/// Add `value` to the class with index `class`.
+	fn push(&mut self, class: usize, value: f64) {
+		self.count[class] += 1.0;
+		let delta = value - self.mean[class];
+		self.mean[class] += delta / self.count[class];
+		self.sum_squares[class] += delta * (value - self.mean[class]);
+	}
+
+	/// Return the t-statistic, or 0 if there are too few measurements.
+	fn t_statistic(&self) -> f64 {
+		if self.count[0] < 2.0 || self.count[1] < 2.0 {
+			return 0.0;
+		}
+
+		let variance_0 = self.sum_squares[0] / (self.count[0] - 1.0);
+		let variance_1 = self.sum_squares[1] / (self.count[1] - 1.0);
+		let denominator = (variance_0 / self.count[0] + variance_1 / self.count[1]).sqrt();
+
+		if denominator == 0.0 {
+			0.0
+		} else {
+			(self.mean[0] - self.mean[1]) / denominator
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The result of `measure()`.
+pub struct TimingReport {
+	measurements: usize,
+	max_t: f64,
+}
+
+impl TimingReport {
+	/// Return the number of measurements taken.
+	pub fn measurements(&self) -> usize { self.measurements }
+
+	/// Return the largest absolute t-statistic of the tests.
+	pub fn max_t(&self) -> f64 { self.max_t }
+
+	/// Return true if the largest t-statistic is above `T_THRESHOLD`.
+	pub fn leaks(&self) -> bool { self.max_t > T_THRESHOLD }
+}
+
+#[must_use]
+/// Time `target` on `measurements` inputs of random classes, and test whether
+/// the execution times of the classes differ.
+pub fn measure<T: TimingUnderTest>(
+	target: &mut T,
+	measurements: usize,
+) -> Result<TimingReport, UnknownCryptoError> {
+	if measurements < MIN_MEASUREMENTS {
+		return Err(UnknownCryptoError);
+	}
+
+	let mut class_bytes = vec![0u8; measurements];
+	util::secure_rand_bytes(&mut class_bytes)?;
+	let classes: Vec<usize> = class_bytes.iter().map(|b| (b & 1) as usize).collect();
+
+	let mut inputs = Vec::with_capacity(measurements);
+	for class in classes.iter() {
+		let class = if *class == 0 {
+			Class::Fixed
+		} else {
+			Class::Random
+		};
+		inputs.push(target.prepare_input(class)?);
+	}
+
+	let mut times = Vec::with_capacity(measurements);
+	for input in inputs.iter() {
+		let start = Instant::now();
+		target.run(input);
+		times.push(start.elapsed().as_nanos() as f64);
+	}
+
+	let mut sorted = times.clone();
+	sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+	let thresholds: Vec<f64> = CROP_PERCENTILES
+		.iter()
+		.map(|p| sorted[((measurements as f64) * p) as usize])
+		.collect();
+
+	let mut tests = vec![WelchTTest::default(); 1 + thresholds.len()];
+	for (class, time) in classes.iter().zip(times.iter()) {
+		tests[0].push(*class, *time);
+		for (test, threshold) in tests[1..].iter_mut().zip(thresholds.iter()) {
+			if time < threshold {
+				test.push(*class, *time);
+			}
+		}
+	}
+
+	let max_t = tests
+		.iter()
+		.map(|test| test.t_statistic().abs())
+		.fold(0.0, f64::max);
+
+	Ok(TimingReport {
+		measurements,
+		max_t,
+	})
+}
+
+/// Return `len` random bytes.
+fn random_bytes(len: usize) -> Result<Vec<u8>, UnknownCryptoError> {
+	let mut bytes = vec![0u8; len];
+	util::secure_rand_bytes(&mut bytes)?;
+
+	Ok(bytes)
+}
+
+#[derive(Debug)]
+/// `TimingUnderTest` for `util::secure_cmp()`. `Class::Fixed` compares two
+/// equal buffers, `Class::Random` a buffer against random bytes.
+pub struct SecureCmp {
+	reference: Vec<u8>,
+}
+
+impl SecureCmp {
+	#[must_use]
+	/// Compare buffers of `len` bytes.
+	pub fn new(len: usize) -> Result<Self, UnknownCryptoError> {
+		Ok(Self {
+			reference: random_bytes(len)?,
+		})
+	}
+}
+
+impl TimingUnderTest for SecureCmp {
+	type Input = Vec<u8>;
+
+	fn prepare_input(&mut self, class: Class) -> Result<Vec<u8>, UnknownCryptoError> {
+		match class {
+			Class::Fixed => Ok(self.reference.clone()),
+			Class::Random => random_bytes(self.reference.len()),
+		}
+	}
+
+	fn run(&mut self, input: &Vec<u8>) {
+		let _ = util::secure_cmp(&self.reference, input);
+	}
+}
+
+#[derive(Debug)]
+/// `TimingUnderTest` for `hmac::verify()`. `Class::Fixed` verifies the
+/// correct tag, `Class::Random` a random tag.
+pub struct HmacVerify {
+	secret_key: hmac::SecretKey,
+	message: Vec<u8>,
+}
+
+impl HmacVerify {
+	#[must_use]
+	/// Verify tags of a random message of `message_len` bytes.
+	pub fn new(message_len: usize) -> Result<Self, UnknownCryptoError> {
+		Ok(Self {
+			secret_key: hmac::SecretKey::generate()?,
+			message: random_bytes(message_len)?,
+		})
+	}
+}
+
+impl TimingUnderTest for HmacVerify {
+	type Input = hmac::Tag;
+
+	fn prepare_input(&mut self, class: Class) -> Result<hmac::Tag, UnknownCryptoError> {
+		match class {
+			Class::Fixed => hmac::hmac(&self.secret_key, &self.message),
+			Class::Random => hmac::Tag::from_slice(&random_bytes(64)?),
+		}
+	}
+
+	fn run(&mut self, input: &hmac::Tag) {
+		let _ = hmac::verify(input, &self.secret_key, &self.message);
+	}
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	/// A comparison that returns at the first differing byte.
+	struct EarlyExitCmp {
+		reference: Vec<u8>,
+	}
+
+	impl TimingUnderTest for EarlyExitCmp {
+		type Input = Vec<u8>;
+
+		fn prepare_input(&mut self, class: Class) -> Result<Vec<u8>, UnknownCryptoError> {
+			match class {
+				Class::Fixed => Ok(self.reference.clone()),
+				Class::Random => random_bytes(self.reference.len()),
+			}
+		}
+
+		fn run(&mut self, input: &Vec<u8>) {
+			for (a, b) in self.reference.iter().zip(input.iter()) {
+				if a != b {
+					return;
+				}
+			}
+		}
+	}
+
+	mod test_measure {
+		use super::*;
+
+		#[test]
+		fn test_too_few_measurements_err() {
+			let mut target = SecureCmp::new(32).unwrap();
+			assert!(measure(&mut target, MIN_MEASUREMENTS - 1).is_err());
+			assert!(measure(&mut target, MIN_MEASUREMENTS).is_ok());
+		}
+
+		#[test]
+		fn test_report() {
+			let report = measure(&mut HmacVerify::new(64).unwrap(), 2000).unwrap();
+			assert_eq!(report.measurements(), 2000);
+			assert!(report.max_t() >= 0.0);
+			assert_eq!(report.leaks(), report.max_t() > T_THRESHOLD);
+		}
+
+		#[test]
+		#[ignore]
+		// Timing tests are slow and sensitive to noise, so they are only run
+		// locally, in release mode.
+		fn test_secure_cmp_no_leak() {
+			let report = measure(&mut SecureCmp::new(4096).unwrap(), 200_000).unwrap();
+			assert!(!report.leaks(), "max |t|: {}", report.max_t());
+		}
+
+		#[test]
+		#[ignore]
+		fn test_hmac_verify_no_leak() {
+			let report = measure(&mut HmacVerify::new(64).unwrap(), 200_000).unwrap();
+			assert!(!report.leaks(), "max |t|: {}", report.max_t());
+		}
+
+		#[test]
+		#[ignore]
+		fn test_early_exit_leaks() {
+			let mut target = EarlyExitCmp {
+				reference: random_bytes(4096).unwrap(),
+			};
+			let report = measure(&mut target, 200_000).unwrap();
+			assert!(report.leaks(), "max |t|: {}", report.max_t());
+		}
+	}
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	mod test_welch_t_test {
+		use super::*;
+
+		#[test]
+		fn test_t_statistic() {
+			let mut test = WelchTTest::default();
+			for value in [1.0, 2.0, 3.0, 4.0].iter() {
+				test.push(0, *value);
+			}
+			for value in [2.0, 4.0, 6.0, 8.0].iter() {
+				test.push(1, *value);
+			}
+
+			// Means 2.5 and 5, variances 5/3 and 20/3.
+			let expected = -2.5 / (5.0f64 / 12.0 + 20.0 / 12.0).sqrt();
+			assert!((test.t_statistic() - expected).abs() < 1e-12);
+		}
+
+		#[test]
+		fn test_t_statistic_degenerate() {
+			let mut test = WelchTTest::default();
+			assert_eq!(test.t_statistic(), 0.0);
+
+			test.push(0, 1.0);
+			test.push(1, 1.0);
+			assert_eq!(test.t_statistic(), 0.0);
+
+			test.push(0, 1.0);
+			test.push(1, 1.0);
+			// Zero variance.
+			assert_eq!(test.t_statistic(), 0.0);
+		}
+	}
+}
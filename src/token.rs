@@ -0,0 +1,302 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Encrypted tokens with an expiry time.
+//!
+//! # Use case:
+//! `orion::token` can be used to create short-lived tokens that only the
+//! issuer can read, such as encrypted session cookies.
+//!
+//! # About:
+//! - A token holds a format version byte, the expiry time in seconds since
+//!   the Unix epoch as a big-endian 64-bit integer, a random 24-byte nonce
+//!   and the payload encrypted with XChaCha20Poly1305. The version and expiry
+//!   time are authenticated as additional data.
+//! - The token is Base64url encoded without padding, so it can be used in
+//!   cookies and URLs as-is.
+//! - The expiry time is the time of `seal()` plus `ttl`, taken from the
+//!   system clock. `open()` rejects a token once the system clock has
+//!   reached its expiry time.
+//!
+//! # Parameters:
+//! - `secret_key`: The secret key.
+//! - `payload`: The data to encrypt into the token.
+//! - `ttl`: How long the token is valid for. Only whole seconds are used.
+//! - `token`: The token to open.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `secret_key` is not 32 bytes.
+//! - `payload` is empty.
+//! - The expiry time does not fit in a 64-bit integer.
+//! - The system clock is set before the Unix epoch.
+//! - `token` is not validly Base64url encoded, is too short or has an unknown
+//!   format version.
+//! - The token fails authentication, for example because it was modified or
+//!   because `secret_key` is not the one used when sealing.
+//! - The token has expired.
+//! - The `OsRng` fails to initialize or read from its source.
+//!
+//! # Security:
+//! - The secret key should always be generated using a CSPRNG.
+//!   `SecretKey::default()` can be used for this.
+//! - A token can be used any number of times until it expires. If tokens must
+//!   be revocable before that, the payload should identify a server-side
+//!   session that can be invalidated.
+//! - The expiry time is readable by anyone who holds the token, but cannot
+//!   be changed without `secret_key`.
+//! - The length of the payload is not hidden.
+//!
+//! # Example:
+//! ```
+//! use orion::token;
+//! use std::time::Duration;
+//!
+//! let secret_key = token::SecretKey::default();
+//! let cookie = token::seal(&secret_key, b"session-id=42", Duration::from_secs(3600)).unwrap();
+//!
+//! let payload = token::open(&secret_key, &cookie).unwrap();
+//! assert_eq!(payload, b"session-id=42");
+//! ```
+
+pub use crate::hltypes::SecretKey;
+use crate::{
+	encoding::{base64_decode, base64_encode},
+	errors::UnknownCryptoError,
+	hazardous::{
+		aead,
+		constants::{POLY1305_OUTSIZE, XCHACHA_NONCESIZE},
+		stream::{chacha20, xchacha20::Nonce},
+	},
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The format version of tokens that use XChaCha20Poly1305.
+const TOKEN_FORMAT_V1: u8 = 1;
+
+/// The size of the header of a token: the format version and the expiry
+/// time.
+const TOKEN_HEADER_SIZE: usize = 1 + 8;
+
+#[must_use]
+/// The current time in seconds since the Unix epoch.
+fn now() -> Result<u64, UnknownCryptoError> {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|since_epoch| since_epoch.as_secs())
+		.map_err(|_| UnknownCryptoError)
+}
+
+#[must_use]
+/// Create a token that expires `ttl` after `now`.
+fn seal_at(
+	secret_key: &SecretKey,
+	payload: &[u8],
+	ttl: Duration,
+	now: u64,
+) -> Result<String, UnknownCryptoError> {
+	if payload.is_empty() {
+		return Err(UnknownCryptoError);
+	}
+
+	let expiry = now.checked_add(ttl.as_secs()).ok_or(UnknownCryptoError)?;
+	let mut header = [0u8; TOKEN_HEADER_SIZE];
+	header[0] = TOKEN_FORMAT_V1;
+	header[1..].copy_from_slice(&expiry.to_be_bytes());
+
+	let nonce = Nonce::generate()?;
+	let mut token =
+		vec![0u8; TOKEN_HEADER_SIZE + XCHACHA_NONCESIZE + payload.len() + POLY1305_OUTSIZE];
+	token[..TOKEN_HEADER_SIZE].copy_from_slice(&header);
+	token[TOKEN_HEADER_SIZE..(TOKEN_HEADER_SIZE + XCHACHA_NONCESIZE)]
+		.copy_from_slice(&nonce.as_bytes());
+
+	aead::xchacha20poly1305::seal(
+		&chacha20::SecretKey::from_slice(&secret_key.unprotected_as_bytes())?,
+		&nonce,
+		payload,
+		Some(&header),
+		&mut token[(TOKEN_HEADER_SIZE + XCHACHA_NONCESIZE)..],
+	)?;
+
+	Ok(base64_encode(&token, true))
+}
+
+#[must_use]
+/// Open a token, which must not have expired at `now`.
+fn open_at(secret_key: &SecretKey, token: &str, now: u64) -> Result<Vec<u8>, UnknownCryptoError> {
+	let token = base64_decode(token, true)?;
+	// `+ 1` to avoid empty ciphertexts
+	if token.len() < (TOKEN_HEADER_SIZE + XCHACHA_NONCESIZE + POLY1305_OUTSIZE + 1) {
+		return Err(UnknownCryptoError);
+	}
+	if token[0] != TOKEN_FORMAT_V1 {
+		return Err(UnknownCryptoError);
+	}
+
+	let (header, ciphertext_with_tag_and_nonce) = token.split_at(TOKEN_HEADER_SIZE);
+	let mut payload =
+		vec![0u8; ciphertext_with_tag_and_nonce.len() - (XCHACHA_NONCESIZE + POLY1305_OUTSIZE)];
+
+	aead::xchacha20poly1305::open(
+		&chacha20::SecretKey::from_slice(&secret_key.unprotected_as_bytes())?,
+		&Nonce::from_slice(&ciphertext_with_tag_and_nonce[..XCHACHA_NONCESIZE])?,
+		&ciphertext_with_tag_and_nonce[XCHACHA_NONCESIZE..],
+		Some(header),
+		&mut payload,
+	)?;
+
+	let mut expiry = [0u8; 8];
+	expiry.copy_from_slice(&header[1..]);
+	if now >= u64::from_be_bytes(expiry) {
+		return Err(UnknownCryptoError);
+	}
+
+	Ok(payload)
+}
+
+#[must_use]
+/// Encrypt `payload` into a token that expires after `ttl`.
+pub fn seal(
+	secret_key: &SecretKey,
+	payload: &[u8],
+	ttl: Duration,
+) -> Result<String, UnknownCryptoError> {
+	seal_at(secret_key, payload, ttl, now()?)
+}
+
+#[must_use]
+/// Authenticate and decrypt a `token` that has not expired, returning its
+/// payload.
+pub fn open(secret_key: &SecretKey, token: &str) -> Result<Vec<u8>, UnknownCryptoError> {
+	open_at(secret_key, token, now()?)
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+
+	mod test_seal_open {
+		use super::*;
+
+		#[test]
+		fn test_roundtrip() {
+			let secret_key = SecretKey::default();
+			let token = seal(&secret_key, b"Payload", Duration::from_secs(60)).unwrap();
+			assert_eq!(open(&secret_key, &token).unwrap(), b"Payload");
+		}
+
+		#[test]
+		fn test_subsecond_ttl_expired() {
+			let secret_key = SecretKey::default();
+			let token = seal(&secret_key, b"Payload", Duration::from_millis(999)).unwrap();
+			assert!(open(&secret_key, &token).is_err());
+		}
+
+		#[test]
+		fn test_url_safe() {
+			let secret_key = SecretKey::default();
+			let token = seal(&secret_key, &[0xff; 64], Duration::from_secs(60)).unwrap();
+			assert!(token
+				.bytes()
+				.all(|chr| chr.is_ascii_alphanumeric() || chr == b'-' || chr == b'_'));
+		}
+
+		#[test]
+		fn test_bad_input_err() {
+			let secret_key = SecretKey::default();
+			assert!(seal(&secret_key, b"", Duration::from_secs(60)).is_err());
+			assert!(seal(
+				&SecretKey::generate(64).unwrap(),
+				b"Payload",
+				Duration::from_secs(60)
+			)
+			.is_err());
+			assert!(seal(&secret_key, b"Payload", Duration::from_secs(u64::MAX)).is_err());
+
+			assert!(open(&secret_key, "").is_err());
+			assert!(open(&secret_key, "not a token").is_err());
+			assert!(open(&secret_key, &base64_encode(&[1u8; 49], true)).is_err());
+		}
+
+		#[test]
+		fn test_wrong_key_err() {
+			let token = seal(&SecretKey::default(), b"Payload", Duration::from_secs(60)).unwrap();
+			assert!(open(&SecretKey::default(), &token).is_err());
+		}
+	}
+}
+
+// Testing private functions in the module.
+#[cfg(test)]
+mod private {
+	use super::*;
+
+	// One function tested per submodule.
+	mod test_open_at {
+		use super::*;
+
+		const NOW: u64 = 1_600_000_000;
+
+		#[test]
+		fn test_expiry() {
+			let secret_key = SecretKey::default();
+			let token = seal_at(&secret_key, b"Payload", Duration::from_secs(60), NOW).unwrap();
+
+			assert_eq!(open_at(&secret_key, &token, NOW).unwrap(), b"Payload");
+			assert_eq!(open_at(&secret_key, &token, NOW + 59).unwrap(), b"Payload");
+			assert!(open_at(&secret_key, &token, NOW + 60).is_err());
+			assert!(open_at(&secret_key, &token, u64::MAX).is_err());
+		}
+
+		#[test]
+		fn test_header() {
+			let secret_key = SecretKey::default();
+			let token = seal_at(&secret_key, b"Payload", Duration::from_secs(60), NOW).unwrap();
+			let decoded = base64_decode(&token, true).unwrap();
+
+			assert_eq!(decoded[0], TOKEN_FORMAT_V1);
+			assert_eq!(decoded[1..9], (NOW + 60).to_be_bytes());
+			assert_eq!(decoded.len(), 9 + 24 + 7 + 16);
+		}
+
+		#[test]
+		fn test_modified_token_err() {
+			let secret_key = SecretKey::default();
+			let token = seal_at(&secret_key, b"Payload", Duration::from_secs(60), NOW).unwrap();
+			let mut decoded = base64_decode(&token, true).unwrap();
+
+			for idx in 0..decoded.len() {
+				decoded[idx] ^= 1;
+				let modified = base64_encode(&decoded, true);
+				assert!(open_at(&secret_key, &modified, NOW).is_err());
+				decoded[idx] ^= 1;
+			}
+
+			// Extending the expiry time fails authentication.
+			decoded[8] = decoded[8].wrapping_add(1);
+			let extended = base64_encode(&decoded, true);
+			assert!(open_at(&secret_key, &extended, NOW + 60).is_err());
+		}
+	}
+}
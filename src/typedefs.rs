@@ -100,6 +100,125 @@ macro_rules! impl_drop_trait (($name:ident) => (
     }
 ));
 
+/// Macro that implements the `Zeroize` trait on a object called `$name` which
+/// has a field `value`. Only available with the `secrecy_interop` feature.
+macro_rules! impl_zeroize_trait (($name:ident) => (
+    #[cfg(feature = "secrecy_interop")]
+    impl zeroize::Zeroize for $name {
+        fn zeroize(&mut self) {
+            self.value.zeroize();
+        }
+    }
+));
+
+/// Macro that implements `TryFrom<&[u8]>` on a object called `$name` that
+/// also implements `from_slice()`. The conversion has the same requirements
+/// as `from_slice()`.
+macro_rules! impl_try_from_trait (($name:ident) => (
+    impl core::convert::TryFrom<&[u8]> for $name {
+        type Error = UnknownCryptoError;
+
+        fn try_from(slice: &[u8]) -> Result<$name, UnknownCryptoError> {
+            $name::from_slice(slice)
+        }
+    }
+));
+
+/// Macro that implements `From<[u8; $size]>` on a object called `$name` which
+/// has a field `value` of type `[u8; $size]`.
+macro_rules! impl_from_array_trait (($name:ident, $size:expr) => (
+    impl From<[u8; $size]> for $name {
+        fn from(bytes: [u8; $size]) -> $name {
+            $name { value: bytes }
+        }
+    }
+));
+
+/// Macro that implements the `Eq`, `PartialOrd`, `Ord` and `Hash` traits on a
+/// object called `$name`, using the bytes returned by `$bytes()`. These
+/// comparisons will NOT perform in constant time.
+macro_rules! impl_ord_and_hash_traits (($name:ident, $bytes:ident) => (
+    impl Eq for $name {}
+
+    impl PartialOrd for $name {
+        fn partial_cmp(&self, other: &$name) -> Option<core::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for $name {
+        fn cmp(&self, other: &$name) -> core::cmp::Ordering {
+            self.$bytes().cmp(other.$bytes())
+        }
+    }
+
+    impl core::hash::Hash for $name {
+        fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+            self.$bytes().hash(state);
+        }
+    }
+));
+
+/// Macro that implements the `Display` trait on a object called `$name` that
+/// also implements `as_bytes()`. The object is displayed as lowercase hex.
+macro_rules! impl_display_hex_trait (($name:ident) => (
+    impl core::fmt::Display for $name {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            crate::encoding::hex_encode_fmt(self.as_bytes(), f)
+        }
+    }
+));
+
+/// Macro that implements the `FromStr` trait on a object called `$name` that
+/// also implements `from_slice()`. The string is decoded as hex into a buffer
+/// of at most `$size` bytes, which is then passed to `from_slice()`.
+macro_rules! impl_fromstr_hex_trait (($name:ident, $size:expr) => (
+    impl core::str::FromStr for $name {
+        type Err = UnknownCryptoError;
+
+        fn from_str(s: &str) -> Result<$name, UnknownCryptoError> {
+            if s.len() % 2 != 0 || s.len() > $size * 2 {
+                return Err(UnknownCryptoError);
+            }
+
+            let mut value = [0u8; $size];
+            crate::encoding::hex_decode_into(s, &mut value[..s.len() / 2])?;
+
+            $name::from_slice(&value[..s.len() / 2])
+        }
+    }
+));
+
+/// Macro that implements the `Arbitrary` trait from the `arbitrary` crate on a
+/// object called `$name` that also implements `from_slice()`. The object is
+/// made from the next `$size` bytes of the input.
+macro_rules! impl_arbitrary_trait (($name:ident, $size:expr) => (
+    #[cfg(feature = "arbitrary")]
+    impl<'a> arbitrary::Arbitrary<'a> for $name {
+        fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+            $name::from_slice(u.bytes($size)?).map_err(|_| arbitrary::Error::IncorrectFormat)
+        }
+
+        fn size_hint(_depth: usize) -> (usize, Option<usize>) { ($size, Some($size)) }
+    }
+));
+
+#[cfg(feature = "safe_api")]
+/// Macro that implements the `Arbitrary` trait from the `arbitrary` crate on a
+/// variable-sized object called `$name` that also implements `from_slice()`.
+/// The object is made from an arbitrary, non-zero number of bytes of the input.
+macro_rules! impl_arbitrary_variable_size_trait (($name:ident) => (
+    #[cfg(feature = "arbitrary")]
+    impl<'a> arbitrary::Arbitrary<'a> for $name {
+        fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+            let length = u.arbitrary_len::<u8>()?.max(1);
+            $name::from_slice(u.bytes(length)?).map_err(|_| arbitrary::Error::IncorrectFormat)
+        }
+
+        fn size_hint(_depth: usize) -> (usize, Option<usize>) { (1, None) }
+    }
+));
+
 /// Macro to implement a `from_slice()` function. Returns `UnknownCryptoError`
 /// if the slice is not of length `$size`.
 macro_rules! func_from_slice (($name:ident, $size:expr) => (
@@ -155,6 +274,18 @@ macro_rules! func_as_bytes (() => (
     }
 ));
 
+/// Macro to implement a `duplicate()` function for objects containing
+/// sensitive data in a field `value`.
+macro_rules! func_duplicate (() => (
+    #[must_use]
+    /// Return a copy of the object. Both copies are zeroed out when dropped.
+    /// __**Warning**__: Should not be used unless strictly needed, as every
+    /// copy is another place in memory that holds the secret data.
+    pub fn duplicate(&self) -> Self {
+        Self { value: self.value.clone() }
+    }
+));
+
 /// Macro to implement a `get_length()` function which will return the objects'
 /// length of field `value`.
 macro_rules! func_get_length (() => (
@@ -199,112 +330,6 @@ macro_rules! func_generate_variable_size (($name:ident) => (
     }
 ));
 
-/// Macro to construct a type containing sensitive data, using a fixed-size
-/// array.
-macro_rules! construct_secret_key {
-    ($(#[$meta:meta])*
-    ($name:ident, $size:expr)) => (
-        #[must_use]
-        $(#[$meta])*
-        ///
-        /// # Security:
-        /// - __**Avoid using**__ `unprotected_as_bytes()` whenever possible, as it breaks all protections
-        /// that the type implements.
-        pub struct $name { value: [u8; $size] }
-
-        impl_omitted_debug_trait!($name);
-        impl_drop_trait!($name);
-        impl_ct_partialeq_trait!($name);
-
-        impl $name {
-            func_from_slice!($name, $size);
-            func_unprotected_as_bytes!();
-            func_generate!($name, $size);
-            func_get_length!();
-        }
-
-        #[test]
-        fn test_key_size() {
-            assert!($name::from_slice(&[0u8; $size]).is_ok());
-            assert!($name::from_slice(&[0u8; 0]).is_err());
-            assert!($name::from_slice(&[0u8; $size - 1]).is_err());
-            assert!($name::from_slice(&[0u8; $size + 1]).is_err());
-        }
-        #[test]
-        fn test_unprotected_as_bytes_secret_key() {
-            let test = $name::from_slice(&[0u8; $size]).unwrap();
-            assert!(test.unprotected_as_bytes() == &[0u8; $size]);
-            assert!(test.unprotected_as_bytes().len() == $size);
-        }
-
-        #[test]
-        fn test_get_length_secret_key() {
-            let test = $name::from_slice(&[0u8; $size]).unwrap();
-            assert!(test.unprotected_as_bytes().len() == test.get_length());
-            assert!($size == test.get_length());
-        }
-
-        #[test]
-        #[cfg(feature = "safe_api")]
-        fn test_generate_secret_key() {
-            let test_zero = $name::from_slice(&[0u8; $size]).unwrap();
-            // A random one should never be all 0's.
-            let test_rand = $name::generate().unwrap();
-            assert!(test_zero != test_rand);
-            // A random generated one should always be $size in length.
-            assert!(test_rand.get_length() == $size);
-        }
-
-        #[test]
-        #[cfg(feature = "safe_api")]
-        // format! is only available with std
-        fn test_omitted_debug_secret_key() {
-            let secret = format!("{:?}", [0u8; $size].as_ref());
-            let test_debug_contents = format!("{:?}", $name::from_slice(&[0u8; $size]).unwrap());
-            assert_eq!(test_debug_contents.contains(&secret), false);
-        }
-    );
-}
-
-/// Macro to construct a nonce where a random generator is not applicable.
-macro_rules! construct_nonce_no_generator {
-    ($(#[$meta:meta])*
-    ($name:ident, $size:expr)) => (
-        #[must_use]
-        $(#[$meta])*
-        pub struct $name { value: [u8; $size] }
-
-        impl_normal_debug_trait!($name);
-        impl_normal_partialeq_trait!($name);
-
-        impl $name {
-            func_from_slice!($name, $size);
-            func_as_bytes!();
-            func_get_length!();
-        }
-
-        #[test]
-        fn test_nonce_size() {
-            assert!($name::from_slice(&[0u8; $size]).is_ok());
-            assert!($name::from_slice(&[0u8; $size - $size]).is_err());
-            assert!($name::from_slice(&[0u8; $size + 1]).is_err());
-        }
-        #[test]
-        fn test_as_bytes_nonce_no_gen() {
-            let test = $name::from_slice(&[0u8; $size]).unwrap();
-            assert!(test.as_bytes().len() == $size);
-            assert!(test.as_bytes() == [0u8; $size].as_ref());
-        }
-
-        #[test]
-        fn test_get_length_nonce_no_gen() {
-            let test = $name::from_slice(&[0u8; $size]).unwrap();
-            assert!(test.as_bytes().len() == test.get_length());
-            assert!($size == test.get_length());
-        }
-    );
-}
-
 /// Macro to construct a nonce where a random generator is applicable.
 macro_rules! construct_nonce_with_generator {
     ($(#[$meta:meta])*
@@ -315,6 +340,12 @@ macro_rules! construct_nonce_with_generator {
 
         impl_normal_debug_trait!($name);
         impl_normal_partialeq_trait!($name);
+        impl_try_from_trait!($name);
+        impl_from_array_trait!($name, $size);
+        impl_ord_and_hash_traits!($name, as_bytes);
+        impl_display_hex_trait!($name);
+        impl_fromstr_hex_trait!($name, $size);
+        impl_arbitrary_trait!($name, $size);
 
         impl $name {
             func_from_slice!($name, $size);
@@ -323,6 +354,16 @@ macro_rules! construct_nonce_with_generator {
             func_get_length!();
         }
 
+        #[test]
+        #[cfg(feature = "arbitrary")]
+        fn test_arbitrary_nonce_with_gen() {
+            use arbitrary::{Arbitrary, Unstructured};
+            let data = [0xabu8; $size + 1];
+            let mut u = Unstructured::new(&data);
+            assert!($name::arbitrary(&mut u).unwrap() == $name::from_slice(&[0xab; $size]).unwrap());
+            assert!($name::arbitrary(&mut u).is_err());
+        }
+
         #[test]
         fn test_nonce_size() {
             assert!($name::from_slice(&[0u8; $size]).is_ok());
@@ -353,6 +394,48 @@ macro_rules! construct_nonce_with_generator {
             // A random generated one should always be $size in length.
             assert!(test_rand.get_length() == $size);
         }
+
+        #[test]
+        fn test_try_from_and_from_array_nonce_with_gen() {
+            use core::convert::TryFrom;
+            assert!($name::try_from(&[0u8; $size][..]).is_ok());
+            assert!($name::try_from(&[0u8; $size + 1][..]).is_err());
+            assert!($name::from([1u8; $size]) == $name::from_slice(&[1u8; $size]).unwrap());
+        }
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_ord_and_hash_nonce_with_gen() {
+            use std::collections::{BTreeSet, HashSet};
+            let a = $name::from_slice(&[0u8; $size]).unwrap();
+            let b = $name::from_slice(&[1u8; $size]).unwrap();
+            assert!(a < b);
+            assert_eq!(a.cmp(&$name::from_slice(&[0u8; $size]).unwrap()), core::cmp::Ordering::Equal);
+
+            let mut hash_set = HashSet::new();
+            assert!(hash_set.insert($name::from_slice(&[0u8; $size]).unwrap()));
+            assert!(!hash_set.insert($name::from_slice(&[0u8; $size]).unwrap()));
+            assert!(hash_set.insert($name::from_slice(&[1u8; $size]).unwrap()));
+
+            let mut tree_set = BTreeSet::new();
+            tree_set.insert(b);
+            tree_set.insert(a);
+            assert_eq!(tree_set.len(), 2);
+        }
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_display_from_str_nonce_with_gen() {
+            use core::str::FromStr;
+            let test = $name::from_slice(&[0xab; $size]).unwrap();
+            assert_eq!(test.to_string(), "ab".repeat($size));
+            assert!($name::from_str(&test.to_string()).unwrap() == test);
+            assert!($name::from_str(&"AB".repeat($size)).unwrap() == test);
+            assert!($name::from_str(&"ab".repeat($size + 1)).is_err());
+            assert!($name::from_str("abc").is_err());
+            assert!($name::from_str("zz").is_err());
+            assert!($name::from_str("").is_err());
+        }
     );
 }
 
@@ -367,10 +450,14 @@ macro_rules! construct_tag {
         /// # Security:
         /// - __**Avoid using**__ `unprotected_as_bytes()` whenever possible, as it breaks all protections
         /// that the type implements.
+        /// - It does not implement `Ord` or `Hash`, as those would not compare tags in constant
+        /// time.
         pub struct $name { value: [u8; $size] }
 
         impl_omitted_debug_trait!($name);
         impl_ct_partialeq_trait!($name);
+        impl_try_from_trait!($name);
+        impl_from_array_trait!($name, $size);
 
         impl $name {
             func_from_slice!($name, $size);
@@ -406,6 +493,14 @@ macro_rules! construct_tag {
             let test_debug_contents = format!("{:?}", $name::from_slice(&[0u8; $size]).unwrap());
             assert_eq!(test_debug_contents.contains(&secret), false);
         }
+
+        #[test]
+        fn test_try_from_and_from_array_tag() {
+            use core::convert::TryFrom;
+            assert!($name::try_from(&[0u8; $size][..]).is_ok());
+            assert!($name::try_from(&[0u8; $size + 1][..]).is_err());
+            assert!($name::from([1u8; $size]) == $name::from_slice(&[1u8; $size]).unwrap());
+        }
     );
 }
 
@@ -414,6 +509,13 @@ macro_rules! construct_tag {
 macro_rules! construct_hmac_key {
     ($(#[$meta:meta])*
     ($name:ident, $size:expr)) => (
+        construct_hmac_key! {
+            $(#[$meta])*
+            ($name, $size, sha512, SHA512_OUTSIZE)
+        }
+    );
+    ($(#[$meta:meta])*
+    ($name:ident, $size:expr, $hash:ident, $outsize:ident)) => (
         #[must_use]
         $(#[$meta])*
         ///
@@ -424,21 +526,24 @@ macro_rules! construct_hmac_key {
 
         impl_omitted_debug_trait!($name);
         impl_drop_trait!($name);
+        impl_zeroize_trait!($name);
         impl_ct_partialeq_trait!($name);
+        impl_try_from_trait!($name);
+        impl_from_array_trait!($name, $size);
 
         impl $name {
             #[must_use]
             /// Make an object from a given byte slice.
             pub fn from_slice(slice: &[u8]) -> Result<$name, UnknownCryptoError> {
-                use crate::hazardous::hash::sha512;
-                use crate::hazardous::constants::SHA512_OUTSIZE;
+                use crate::hazardous::hash::$hash;
+                use crate::hazardous::constants::$outsize;
 
                 let mut secret_key = [0u8; $size];
 
                 let slice_len = slice.len();
 
                 if slice_len > $size {
-                    secret_key[..SHA512_OUTSIZE].copy_from_slice(&sha512::digest(slice)?.as_bytes());
+                    secret_key[..$outsize].copy_from_slice(&$hash::digest(slice)?.as_bytes());
                 } else {
                     secret_key[..slice_len].copy_from_slice(slice);
                 }
@@ -447,6 +552,7 @@ macro_rules! construct_hmac_key {
             }
 
             func_unprotected_as_bytes!();
+            func_duplicate!();
             func_generate!($name, $size);
             func_get_length!();
         }
@@ -491,6 +597,22 @@ macro_rules! construct_hmac_key {
             let test_debug_contents = format!("{:?}", $name::from_slice(&[0u8; $size]).unwrap());
             assert_eq!(test_debug_contents.contains(&secret), false);
         }
+
+        #[test]
+        fn test_try_from_and_from_array_hmac_key() {
+            use core::convert::TryFrom;
+            assert!($name::try_from(&[0u8; $size][..]).is_ok());
+            assert!($name::try_from(&[0u8; $size + 1][..]).is_ok());
+            assert!($name::from([1u8; $size]) == $name::from_slice(&[1u8; $size]).unwrap());
+        }
+
+        #[test]
+        fn test_duplicate_hmac_key() {
+            let test = $name::from_slice(&[1u8; $size]).unwrap();
+            let duplicate = test.duplicate();
+            assert!(test == duplicate);
+            assert_eq!(test.unprotected_as_bytes(), duplicate.unprotected_as_bytes());
+        }
     );
 }
 
@@ -513,7 +635,9 @@ macro_rules! construct_blake2b_key {
 
         impl_omitted_debug_trait!($name);
         impl_drop_trait!($name);
+        impl_zeroize_trait!($name);
         impl_ct_partialeq_trait!($name);
+        impl_try_from_trait!($name);
 
         impl $name {
             #[must_use]
@@ -538,6 +662,17 @@ macro_rules! construct_blake2b_key {
                 self.original_size
             }
 
+            #[must_use]
+            /// Return a copy of the object. Both copies are zeroed out when dropped.
+            /// __**Warning**__: Should not be used unless strictly needed, as every
+            /// copy is another place in memory that holds the secret data.
+            pub fn duplicate(&self) -> Self {
+                Self {
+                    value: self.value,
+                    original_size: self.original_size,
+                }
+            }
+
             #[must_use]
             #[cfg(feature = "safe_api")]
             /// Randomly generate using a CSPRNG. Not available in `no_std` context.
@@ -620,6 +755,22 @@ macro_rules! construct_blake2b_key {
             let test_debug_contents = format!("{:?}", $name::from_slice(&[0u8; 64]).unwrap());
             assert_eq!(test_debug_contents.contains(&secret), false);
         }
+
+        #[test]
+        fn test_try_from_blake2b_key() {
+            use core::convert::TryFrom;
+            assert!($name::try_from(&[1u8; 64][..]).unwrap() == $name::from_slice(&[1u8; 64]).unwrap());
+            assert!($name::try_from(&[0u8; 65][..]).is_err());
+        }
+
+        #[test]
+        fn test_duplicate_blake2b_key() {
+            let test = $name::from_slice(&[1u8; 32]).unwrap();
+            let duplicate = test.duplicate();
+            assert!(test == duplicate);
+            assert_eq!(test.unprotected_as_bytes(), duplicate.unprotected_as_bytes());
+            assert_eq!(test.get_original_length(), duplicate.get_original_length());
+        }
     );
 }
 
@@ -674,6 +825,11 @@ macro_rules! construct_digest {
             }
         }
 
+        impl_try_from_trait!($name);
+        impl_ord_and_hash_traits!($name, as_bytes);
+        impl_display_hex_trait!($name);
+        impl_fromstr_hex_trait!($name, $size);
+
         impl core::fmt::Debug for $name {
             fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
                 write!(f, "{} value: {:?}, digest_size: {:?}", stringify!($name), &self.value[..], &self.digest_size)
@@ -705,6 +861,47 @@ macro_rules! construct_digest {
             assert!(test.as_bytes().len() == test.get_length());
             assert!(32 == test.get_length());
         }
+
+        #[test]
+        fn test_try_from_blake2b_digest() {
+            use core::convert::TryFrom;
+            assert!($name::try_from(&[1u8; 32][..]).unwrap() == $name::from_slice(&[1u8; 32]).unwrap());
+            assert!($name::try_from(&[0u8; 65][..]).is_err());
+        }
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_ord_and_hash_blake2b_digest() {
+            use std::collections::{BTreeSet, HashSet};
+            let a = $name::from_slice(&[0u8; 32]).unwrap();
+            let b = $name::from_slice(&[1u8; 32]).unwrap();
+            assert!(a < b);
+            assert_eq!(a.cmp(&$name::from_slice(&[0u8; 32]).unwrap()), core::cmp::Ordering::Equal);
+
+            let mut hash_set = HashSet::new();
+            assert!(hash_set.insert($name::from_slice(&[0u8; 32]).unwrap()));
+            assert!(!hash_set.insert($name::from_slice(&[0u8; 32]).unwrap()));
+            assert!(hash_set.insert($name::from_slice(&[1u8; 32]).unwrap()));
+
+            let mut tree_set = BTreeSet::new();
+            tree_set.insert(b);
+            tree_set.insert(a);
+            assert_eq!(tree_set.len(), 2);
+        }
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_display_from_str_blake2b_digest() {
+            use core::str::FromStr;
+            let test = $name::from_slice(&[0xab; 32]).unwrap();
+            assert_eq!(test.to_string(), "ab".repeat(32));
+            assert!($name::from_str(&test.to_string()).unwrap() == test);
+            assert!($name::from_str(&"AB".repeat(32)).unwrap() == test);
+            assert!($name::from_str(&"ab".repeat(65)).is_err());
+            assert!($name::from_str("abc").is_err());
+            assert!($name::from_str("zz").is_err());
+            assert!($name::from_str("").is_err());
+        }
     );
 }
 
@@ -725,12 +922,15 @@ macro_rules! construct_secret_key_variable_size {
 
         impl_omitted_debug_trait!($name);
         impl_drop_trait!($name);
+        impl_zeroize_trait!($name);
         impl_ct_partialeq_trait!($name);
         impl_default_trait!($name, $size);
+        impl_try_from_trait!($name);
 
         impl $name {
             func_from_slice_variable_size!($name);
             func_unprotected_as_bytes!();
+            func_duplicate!();
             func_get_length!();
             func_generate_variable_size!($name);
         }
@@ -774,6 +974,21 @@ macro_rules! construct_secret_key_variable_size {
             let test_debug_contents = format!("{:?}", $name::from_slice(&[0u8; $size]).unwrap());
             assert_eq!(test_debug_contents.contains(&secret), false);
         }
+
+        #[test]
+        fn test_try_from_secret_key_variable_size() {
+            use core::convert::TryFrom;
+            assert!($name::try_from(&[1u8; 64][..]).unwrap() == $name::from_slice(&[1u8; 64]).unwrap());
+            assert!($name::try_from(&[0u8; 0][..]).is_err());
+        }
+
+        #[test]
+        fn test_duplicate_secret_key_variable_size() {
+            let test = $name::from_slice(&[1u8; 64]).unwrap();
+            let duplicate = test.duplicate();
+            assert!(test == duplicate);
+            assert_eq!(test.unprotected_as_bytes(), duplicate.unprotected_as_bytes());
+        }
     );
 }
 
@@ -792,6 +1007,32 @@ macro_rules! construct_salt_variable_size {
         impl_normal_debug_trait!($name);
         impl_default_trait!($name, $size);
         impl_normal_partialeq_trait!($name);
+        impl_try_from_trait!($name);
+        impl_ord_and_hash_traits!($name, as_bytes);
+        impl_display_hex_trait!($name);
+        impl_arbitrary_variable_size_trait!($name);
+
+        impl core::str::FromStr for $name {
+            type Err = UnknownCryptoError;
+
+            fn from_str(s: &str) -> Result<$name, UnknownCryptoError> {
+                let mut value = vec![0u8; s.len() / 2];
+                crate::encoding::hex_decode_into(s, &mut value)?;
+
+                $name::from_slice(&value)
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "arbitrary")]
+        fn test_arbitrary_salt() {
+            use arbitrary::{Arbitrary, Unstructured};
+            let data = [0xabu8; 128];
+            let salt = $name::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            assert!(salt.get_length() >= 1);
+            assert!(salt.as_bytes().iter().all(|byte| *byte == 0xab));
+            assert!($name::arbitrary(&mut Unstructured::new(&[])).is_err());
+        }
 
         impl $name {
             func_from_slice_variable_size!($name);
@@ -830,6 +1071,46 @@ macro_rules! construct_salt_variable_size {
             // A random generated one should always be $size in length.
             assert!(test_rand.get_length() == 128);
         }
+
+        #[test]
+        fn test_try_from_salt() {
+            use core::convert::TryFrom;
+            assert!($name::try_from(&[1u8; 64][..]).unwrap() == $name::from_slice(&[1u8; 64]).unwrap());
+            assert!($name::try_from(&[0u8; 0][..]).is_err());
+        }
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_ord_and_hash_salt() {
+            use std::collections::{BTreeSet, HashSet};
+            let a = $name::from_slice(&[0u8; 16]).unwrap();
+            let b = $name::from_slice(&[1u8; 16]).unwrap();
+            assert!(a < b);
+            assert_eq!(a.cmp(&$name::from_slice(&[0u8; 16]).unwrap()), core::cmp::Ordering::Equal);
+
+            let mut hash_set = HashSet::new();
+            assert!(hash_set.insert($name::from_slice(&[0u8; 16]).unwrap()));
+            assert!(!hash_set.insert($name::from_slice(&[0u8; 16]).unwrap()));
+            assert!(hash_set.insert($name::from_slice(&[1u8; 16]).unwrap()));
+
+            let mut tree_set = BTreeSet::new();
+            tree_set.insert(b);
+            tree_set.insert(a);
+            assert_eq!(tree_set.len(), 2);
+        }
+
+        #[test]
+        #[cfg(feature = "safe_api")]
+        fn test_display_from_str_salt() {
+            use core::str::FromStr;
+            let test = $name::from_slice(&[0xab; 16]).unwrap();
+            assert_eq!(test.to_string(), "ab".repeat(16));
+            assert!($name::from_str(&test.to_string()).unwrap() == test);
+            assert!($name::from_str(&"AB".repeat(16)).unwrap() == test);
+            assert!($name::from_str("abc").is_err());
+            assert!($name::from_str("zz").is_err());
+            assert!($name::from_str("").is_err());
+        }
     );
 }
 
@@ -849,7 +1130,9 @@ macro_rules! construct_password_variable_size {
 
         impl_omitted_debug_trait!($name);
         impl_drop_trait!($name);
+        impl_zeroize_trait!($name);
         impl_ct_partialeq_trait!($name);
+        impl_try_from_trait!($name);
 
         impl $name {
             func_from_slice_variable_size!($name);
@@ -896,5 +1179,12 @@ macro_rules! construct_password_variable_size {
             let test_debug_contents = format!("{:?}", $name::from_slice(&[0u8; 64]).unwrap());
             assert_eq!(test_debug_contents.contains(&secret), false);
         }
+
+        #[test]
+        fn test_try_from_password() {
+            use core::convert::TryFrom;
+            assert!($name::try_from(&[1u8; 64][..]).unwrap() == $name::from_slice(&[1u8; 64]).unwrap());
+            assert!($name::try_from(&[0u8; 0][..]).is_err());
+        }
     );
 }
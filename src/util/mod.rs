@@ -25,8 +25,177 @@ use crate::errors;
 use rand_os::rand_core::RngCore;
 #[cfg(feature = "safe_api")]
 use rand_os::OsRng;
+#[cfg(any(feature = "deterministic_rng", feature = "nonce_reuse_detection"))]
+use std::cell::RefCell;
+#[cfg(feature = "nonce_reuse_detection")]
+use std::collections::VecDeque;
 use subtle::ConstantTimeEq;
 
+#[cfg(feature = "deterministic_rng")]
+/// A caller-supplied source of bytes that replaces the CSPRNG.
+type DeterministicSource = Box<dyn FnMut(&mut [u8])>;
+
+#[cfg(feature = "deterministic_rng")]
+thread_local! {
+	/// The source set with `set_deterministic_rng()` on the current thread.
+	static DETERMINISTIC_RNG: RefCell<Option<DeterministicSource>> = RefCell::new(None);
+}
+
+#[cfg(feature = "deterministic_rng")]
+/// Replace the CSPRNG used by `secure_rand_bytes()` on the current thread with
+/// `source`. Only available with the `deterministic_rng` feature.
+///
+/// # About:
+/// All randomness in orion comes from `secure_rand_bytes()`, so this also
+/// applies to every `generate()` and `default()` function and to nonces and
+/// salts generated by the high-level API, such as in `aead::seal()`. This
+/// makes it possible to write byte-exact tests and generate known-answer tests
+/// against the high-level API.
+///
+/// `source` is called with the buffer to fill, and is used until
+/// `clear_deterministic_rng()` is called. Other threads are not affected.
+///
+/// # Security:
+/// - __**This is for testing only.**__ Everything generated while `source` is
+///   set is as predictable as `source`, including secret keys.
+/// - The `deterministic_rng` feature should only ever be enabled in
+///   `[dev-dependencies]`.
+/// - `source` must not call any function that generates random bytes, as this
+///   panics.
+///
+/// # Example:
+/// ```
+/// use orion::{aead, util};
+///
+/// let mut counter = 0u8;
+/// util::set_deterministic_rng(move |dst: &mut [u8]| {
+/// 	for byte in dst.iter_mut() {
+/// 		*byte = counter;
+/// 		counter = counter.wrapping_add(1);
+/// 	}
+/// });
+///
+/// let secret_key = aead::SecretKey::from_slice(&[0u8; 32]).unwrap();
+/// let ciphertext = aead::seal(&secret_key, b"Secret message").unwrap();
+/// // The nonce came from the deterministic source.
+/// assert_eq!(ciphertext[..24], (0u8..24).collect::<Vec<u8>>()[..]);
+///
+/// util::clear_deterministic_rng();
+/// ```
+pub fn set_deterministic_rng<F: FnMut(&mut [u8]) + 'static>(source: F) {
+	DETERMINISTIC_RNG.with(|rng| *rng.borrow_mut() = Some(Box::new(source)));
+}
+
+#[cfg(feature = "deterministic_rng")]
+/// Remove the source set with `set_deterministic_rng()` on the current thread,
+/// so that `secure_rand_bytes()` uses the CSPRNG again.
+pub fn clear_deterministic_rng() { DETERMINISTIC_RNG.with(|rng| *rng.borrow_mut() = None); }
+
+#[cfg(feature = "nonce_reuse_detection")]
+/// The number of (key, nonce) pairs remembered on each thread.
+const NONCE_HISTORY_SIZE: usize = 4096;
+
+#[cfg(feature = "nonce_reuse_detection")]
+/// The domain separation string hashed before a key, to identify it in the
+/// nonce history without storing it.
+const NONCE_HISTORY_DOMAIN: &[u8] = b"orion-nonce-history-v1";
+
+#[cfg(feature = "nonce_reuse_detection")]
+/// A short identifier of a key, followed by a nonce used with it.
+type NonceUse = ([u8; 16], Vec<u8>);
+
+#[cfg(feature = "nonce_reuse_detection")]
+thread_local! {
+	/// The nonces used on the current thread since
+	/// `start_nonce_reuse_detection()`, or `None` if it has not been called.
+	static NONCE_HISTORY: RefCell<Option<VecDeque<NonceUse>>> = RefCell::new(None);
+}
+
+#[cfg(feature = "nonce_reuse_detection")]
+/// Start recording the (key, nonce) pairs used for encryption on the current
+/// thread, and panic if one is used twice. Only available with the
+/// `nonce_reuse_detection` feature.
+///
+/// # About:
+/// Every encryption with ChaCha20Poly1305 is recorded, which includes
+/// XChaCha20Poly1305, the legacy ChaCha20Poly1305, `orion::aead` and the
+/// `interop` types. Keys are not
+/// stored, only a BLAKE2b-128 hash of them. The last 4096 pairs are
+/// remembered, so reuse further apart than that is not detected.
+///
+/// Recording continues until `stop_nonce_reuse_detection()` is called. Other
+/// threads are not affected.
+///
+/// # Security:
+/// - __**This is for testing only.**__ It catches nonce reuse in tests, but is
+///   no guarantee against it, and the history slows down encryption.
+///
+/// # Example:
+/// ```
+/// use orion::hazardous::aead::chacha20poly1305;
+/// use orion::util;
+///
+/// let secret_key = chacha20poly1305::SecretKey::from_slice(&[0u8; 32]).unwrap();
+/// let nonce = chacha20poly1305::Nonce::from_slice(&[0u8; 12]).unwrap();
+/// let mut dst_out = [0u8; 16 + 16];
+///
+/// util::start_nonce_reuse_detection();
+/// chacha20poly1305::seal(&secret_key, &nonce, &[0u8; 16], None, &mut dst_out).unwrap();
+/// let reused = std::panic::catch_unwind(move || {
+/// 	let mut dst_out = [0u8; 16 + 16];
+/// 	chacha20poly1305::seal(&secret_key, &nonce, &[1u8; 16], None, &mut dst_out)
+/// });
+/// assert!(reused.is_err());
+/// util::stop_nonce_reuse_detection();
+/// ```
+pub fn start_nonce_reuse_detection() {
+	NONCE_HISTORY.with(|history| *history.borrow_mut() = Some(VecDeque::new()));
+}
+
+#[cfg(feature = "nonce_reuse_detection")]
+/// Stop recording nonces on the current thread and forget the ones recorded.
+pub fn stop_nonce_reuse_detection() { NONCE_HISTORY.with(|history| *history.borrow_mut() = None); }
+
+#[cfg(feature = "nonce_reuse_detection")]
+/// Record that `nonce` is used to encrypt with `secret_key`, if
+/// `start_nonce_reuse_detection()` has been called on the current thread.
+///
+/// # Panics:
+/// A panic will occur if:
+/// - `nonce` has already been recorded for `secret_key`.
+pub(crate) fn record_nonce_use(secret_key: &[u8], nonce: &[u8]) {
+	use crate::hazardous::hash::blake2b;
+
+	let key_id = || -> Result<[u8; 16], errors::UnknownCryptoError> {
+		let mut state = blake2b::init(None, 16)?;
+		state.update(NONCE_HISTORY_DOMAIN)?;
+		state.update(secret_key)?;
+		let mut key_id = [0u8; 16];
+		key_id.copy_from_slice(state.finalize()?.as_bytes());
+
+		Ok(key_id)
+	};
+	let to_hex = |bytes: &[u8]| -> String { bytes.iter().map(|b| format!("{:02x}", b)).collect() };
+
+	NONCE_HISTORY.with(|history| {
+		if let Some(history) = history.borrow_mut().as_mut() {
+			// BLAKE2b with a 16-byte output cannot fail.
+			let key_id = key_id().unwrap();
+			if history.iter().any(|(id, used)| *id == key_id && used[..] == *nonce) {
+				panic!(
+					"orion: nonce {} reused with key {}",
+					to_hex(nonce),
+					to_hex(&key_id)
+				);
+			}
+			if history.len() == NONCE_HISTORY_SIZE {
+				history.pop_front();
+			}
+			history.push_back((key_id, nonce.to_vec()));
+		}
+	});
+}
+
 #[must_use]
 #[cfg(feature = "safe_api")]
 /// Generate random bytes using a CSPRNG. Not available in `no_std` context.
@@ -37,7 +206,8 @@ use subtle::ConstantTimeEq;
 /// types used throughout orion, implement their own `generate()` function and
 /// it is strongly preferred to use those, compared to `secure_rand_bytes()`.
 ///
-/// This uses rand_os's [OsRng](https://docs.rs/rand_os/).
+/// This uses rand_os's [OsRng](https://docs.rs/rand_os/), unless a
+/// deterministic source has been set with `set_deterministic_rng()`.
 ///
 /// # Parameters:
 /// - `dst`: Destination buffer for the randomly generated bytes. The amount of
@@ -62,12 +232,371 @@ pub fn secure_rand_bytes(dst: &mut [u8]) -> Result<(), errors::UnknownCryptoErro
 		return Err(errors::UnknownCryptoError);
 	}
 
+	#[cfg(feature = "deterministic_rng")]
+	{
+		let is_deterministic = DETERMINISTIC_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+			Some(source) => {
+				source(dst);
+				true
+			}
+			None => false,
+		});
+		if is_deterministic {
+			return Ok(());
+		}
+	}
+
 	let mut generator = OsRng::new()?;
 	generator.try_fill_bytes(dst)?;
 
 	Ok(())
 }
 
+#[cfg(feature = "safe_api")]
+/// The EFF large wordlist, with one word per line.
+const EFF_LARGE_WORDLIST: &str = include_str!("eff_large_wordlist.txt");
+
+#[must_use]
+#[cfg(feature = "safe_api")]
+/// Generate a uniformly distributed random number in the range `0..upper`,
+/// using rejection sampling so that there is no modulo bias.
+fn uniform_below(upper: u64) -> Result<u64, errors::UnknownCryptoError> {
+	if upper == 0 {
+		return Err(errors::UnknownCryptoError);
+	}
+
+	// The largest multiple of `upper` that fits in 2^64. Values at or
+	// above it would make the lower values more likely, so they are rejected.
+	let zone = (1u128 << 64) - ((1u128 << 64) % u128::from(upper));
+	loop {
+		let value = u128::from(random_u64()?);
+		if value < zone {
+			return Ok((value % u128::from(upper)) as u64);
+		}
+	}
+}
+
+#[must_use]
+#[cfg(feature = "safe_api")]
+/// Generate a random `u64` using a CSPRNG. Not available in `no_std` context.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The `OsRng` fails to initialize or read from its source.
+///
+/// # Example:
+/// ```
+/// use orion::util;
+///
+/// let jitter_ms = util::random_u64().unwrap() % 1000;
+/// ```
+pub fn random_u64() -> Result<u64, errors::UnknownCryptoError> {
+	let mut buf = [0u8; 8];
+	secure_rand_bytes(&mut buf)?;
+
+	Ok(u64::from_le_bytes(buf))
+}
+
+#[must_use]
+#[cfg(feature = "safe_api")]
+/// Generate a uniformly distributed random number in `range` using a CSPRNG.
+/// Not available in `no_std` context.
+///
+/// # About:
+/// Rejection sampling is used, so that there is no modulo bias and every
+/// number in `range` is equally likely.
+///
+/// # Parameters:
+/// - `range`: The range to generate a number in. The end is exclusive.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The `OsRng` fails to initialize or read from its source.
+/// - `range` is empty.
+///
+/// # Example:
+/// ```
+/// use orion::util;
+///
+/// let backoff_ms = util::random_range(100..200).unwrap();
+/// assert!(backoff_ms >= 100 && backoff_ms < 200);
+/// ```
+pub fn random_range(range: core::ops::Range<u64>) -> Result<u64, errors::UnknownCryptoError> {
+	if range.start >= range.end {
+		return Err(errors::UnknownCryptoError);
+	}
+
+	Ok(range.start + uniform_below(range.end - range.start)?)
+}
+
+#[must_use]
+#[cfg(feature = "safe_api")]
+/// Choose an element of `items` uniformly at random using a CSPRNG. Not
+/// available in `no_std` context.
+///
+/// # Parameters:
+/// - `items`: The elements to choose from.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The `OsRng` fails to initialize or read from its source.
+/// - `items` is empty.
+///
+/// # Example:
+/// ```
+/// use orion::util;
+///
+/// let servers = ["a.example.com", "b.example.com", "c.example.com"];
+/// let server = util::random_choice(&servers).unwrap();
+/// assert!(servers.contains(server));
+/// ```
+pub fn random_choice<T>(items: &[T]) -> Result<&T, errors::UnknownCryptoError> {
+	if items.is_empty() {
+		return Err(errors::UnknownCryptoError);
+	}
+
+	Ok(&items[uniform_below(items.len() as u64)? as usize])
+}
+
+#[must_use]
+#[cfg(feature = "safe_api")]
+/// Generate a random passphrase using the EFF large wordlist. Not available in
+/// `no_std` context.
+///
+/// # About:
+/// The passphrase consists of `wordcount` words, separated by a single space,
+/// that are chosen uniformly at random from the 7776 words of the
+/// [EFF large wordlist](https://www.eff.org/deeplinks/2016/07/new-wordlists-random-passphrases).
+/// Each word adds about 12.9 bits of entropy.
+///
+/// The EFF large wordlist is made available by the Electronic Frontier
+/// Foundation under the [CC BY 3.0 US](https://creativecommons.org/licenses/by/3.0/us/) license.
+///
+/// # Parameters:
+/// - `wordcount`: The number of words in the passphrase.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The `OsRng` fails to initialize or read from its source.
+/// - `wordcount` is 0.
+///
+/// # Security:
+/// - A `wordcount` of at least 6 is recommended.
+/// - The returned `String` is not zeroized when dropped.
+///
+/// # Example:
+/// ```
+/// use orion::util;
+///
+/// let passphrase = util::generate_passphrase(6).unwrap();
+/// assert_eq!(passphrase.split(' ').count(), 6);
+/// ```
+pub fn generate_passphrase(wordcount: usize) -> Result<String, errors::UnknownCryptoError> {
+	if wordcount == 0 {
+		return Err(errors::UnknownCryptoError);
+	}
+
+	let words: Vec<&str> = EFF_LARGE_WORDLIST.lines().collect();
+	let mut passphrase = String::new();
+	for idx in 0..wordcount {
+		if idx > 0 {
+			passphrase.push(' ');
+		}
+		passphrase.push_str(random_choice(&words)?);
+	}
+
+	Ok(passphrase)
+}
+
+#[must_use]
+#[cfg(feature = "safe_api")]
+/// Generate a random password from a set of characters. Not available in
+/// `no_std` context.
+///
+/// # About:
+/// Each character of the password is chosen uniformly at random from
+/// `charset`, using rejection sampling, so that no character is more likely
+/// than the others.
+///
+/// # Parameters:
+/// - `len`: The number of characters in the password.
+/// - `charset`: The characters to choose from.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - The `OsRng` fails to initialize or read from its source.
+/// - `len` is 0.
+/// - `charset` is empty.
+///
+/// # Security:
+/// - If a character appears more than once in `charset`, it will be
+///   proportionally more likely to be chosen.
+/// - The returned `String` is not zeroized when dropped.
+///
+/// # Example:
+/// ```
+/// use orion::util;
+///
+/// let charset = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+/// let password = util::generate_password(20, charset).unwrap();
+/// assert_eq!(password.chars().count(), 20);
+/// ```
+pub fn generate_password(len: usize, charset: &str) -> Result<String, errors::UnknownCryptoError> {
+	let chars: Vec<char> = charset.chars().collect();
+	if len == 0 || chars.is_empty() {
+		return Err(errors::UnknownCryptoError);
+	}
+
+	let mut password = String::with_capacity(len);
+	for _ in 0..len {
+		password.push(*random_choice(&chars)?);
+	}
+
+	Ok(password)
+}
+
+/// Securely erase the contents of a buffer.
+///
+/// # About:
+/// Overwrite `buf` with zeroes, in a way that the compiler will not optimize
+/// away, using the [zeroize](https://crates.io/crates/zeroize) crate, followed by a compiler
+/// fence. This is the same mechanism orion uses to wipe its own secret values,
+/// and can be used on buffers that the caller has filled with secrets, such
+/// as passwords read from stdin.
+///
+/// # Parameters:
+/// - `buf`: The buffer to be erased.
+///
+/// # Example:
+/// ```
+/// use orion::util;
+///
+/// let mut password = *b"Secret password";
+/// // Use the password...
+/// util::secure_erase(&mut password);
+/// assert_eq!(password, [0u8; 15]);
+/// ```
+pub fn secure_erase(buf: &mut [u8]) {
+	use zeroize::Zeroize;
+	buf.zeroize();
+	core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+#[must_use]
+/// Pad a buffer to a multiple of a block size, using ISO/IEC 7816-4 padding.
+///
+/// # About:
+/// The first `unpadded_len` bytes of `buf` are the data to pad. A `0x80` byte
+/// is appended, followed by as many zero bytes as needed to make the padded
+/// length a multiple of `blocksize`. At least one byte of padding is always
+/// added. This is compatible with libsodium's `sodium_pad()`.
+///
+/// Padding a message before encrypting it hides its exact length, which is
+/// otherwise revealed by the length of the ciphertext.
+///
+/// # Parameters:
+/// - `buf`: The buffer holding the data, with room for the padding.
+/// - `unpadded_len`: The length of the data in `buf`.
+/// - `blocksize`: The block size to pad to.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - `blocksize` is 0.
+/// - `unpadded_len` is greater than the length of `buf`.
+/// - `buf` is too short to hold the padded data.
+///
+/// # Example:
+/// ```
+/// use orion::util;
+///
+/// let mut buf = [0u8; 64];
+/// buf[..5].copy_from_slice(b"Hello");
+///
+/// let padded_len = util::pad(&mut buf, 5, 16).unwrap();
+/// assert_eq!(padded_len, 16);
+/// assert_eq!(util::unpad(&buf[..padded_len], 16).unwrap(), 5);
+/// ```
+pub fn pad(
+	buf: &mut [u8],
+	unpadded_len: usize,
+	blocksize: usize,
+) -> Result<usize, errors::UnknownCryptoError> {
+	if blocksize == 0 || unpadded_len > buf.len() {
+		return Err(errors::UnknownCryptoError);
+	}
+
+	let padded_len = match unpadded_len.checked_add(blocksize - (unpadded_len % blocksize)) {
+		Some(padded_len) if padded_len <= buf.len() => padded_len,
+		_ => return Err(errors::UnknownCryptoError),
+	};
+
+	buf[unpadded_len] = 0x80;
+	for byte in buf[(unpadded_len + 1)..padded_len].iter_mut() {
+		*byte = 0;
+	}
+
+	Ok(padded_len)
+}
+
+#[must_use]
+/// Find the length of data padded with `pad()`, in constant time.
+///
+/// # About:
+/// The last `blocksize` bytes of `buf` are inspected in constant time, so that
+/// the position of the padding is not leaked through timing. Returns the
+/// length of the data without the padding. This is compatible with libsodium's
+/// `sodium_unpad()`.
+///
+/// # Parameters:
+/// - `buf`: The padded data.
+/// - `blocksize`: The block size that was used when padding.
+///
+/// # Errors:
+/// An error will be returned if:
+/// - `blocksize` is 0.
+/// - The length of `buf` is 0 or not a multiple of `blocksize`.
+/// - `buf` does not end with valid padding.
+///
+/// # Example:
+/// ```
+/// use orion::util;
+///
+/// let mut buf = [0u8; 16];
+/// buf[..5].copy_from_slice(b"Hello");
+/// let padded_len = util::pad(&mut buf, 5, 16).unwrap();
+///
+/// let unpadded_len = util::unpad(&buf[..padded_len], 16).unwrap();
+/// assert_eq!(&buf[..unpadded_len], b"Hello");
+/// ```
+pub fn unpad(buf: &[u8], blocksize: usize) -> Result<usize, errors::UnknownCryptoError> {
+	if blocksize == 0 || buf.is_empty() || buf.len() % blocksize != 0 {
+		return Err(errors::UnknownCryptoError);
+	}
+
+	let padded_len = buf.len();
+	let mut acc = 0usize;
+	let mut pad_len = 0usize;
+	let mut valid = 0usize;
+
+	for idx in 0..blocksize {
+		let chr = buf[padded_len - 1 - idx] as usize;
+		// 1 if this is the first 0x80 byte seen with only zeroes after it, 0
+		// otherwise.
+		let is_barrier =
+			((acc.wrapping_sub(1) & pad_len.wrapping_sub(1) & (chr ^ 0x80).wrapping_sub(1)) >> 8)
+				& 1;
+		acc |= chr;
+		pad_len |= idx & 1usize.wrapping_add(!is_barrier);
+		valid |= is_barrier;
+	}
+
+	if valid == 0 {
+		return Err(errors::UnknownCryptoError);
+	}
+
+	Ok(padded_len - 1 - pad_len)
+}
+
 #[must_use]
 /// Compare two equal length slices in constant time.
 ///
@@ -124,6 +653,132 @@ fn rand_key_len_error() {
 	assert_eq!(err, errors::UnknownCryptoError);
 }
 
+#[cfg(feature = "nonce_reuse_detection")]
+#[test]
+fn test_nonce_reuse_detection() {
+	use crate::hazardous::aead::{chacha20poly1305, xchacha20poly1305};
+
+	let seal = |key: u8, nonce: u8| {
+		let secret_key = chacha20poly1305::SecretKey::from_slice(&[key; 32]).unwrap();
+		let nonce = chacha20poly1305::Nonce::from_slice(&[nonce; 12]).unwrap();
+		let mut dst_out = [0u8; 16 + 16];
+		chacha20poly1305::seal(&secret_key, &nonce, &[0u8; 16], None, &mut dst_out).unwrap();
+	};
+	let is_reuse = |key: u8, nonce: u8| std::panic::catch_unwind(move || seal(key, nonce)).is_err();
+
+	// Nothing is recorded before it is started.
+	seal(0, 0);
+	seal(0, 0);
+
+	start_nonce_reuse_detection();
+	seal(0, 0);
+	seal(0, 1);
+	seal(1, 0);
+	assert!(is_reuse(0, 0));
+	assert!(is_reuse(1, 0));
+
+	// Other threads are not affected.
+	std::thread::spawn(move || {
+		seal(0, 0);
+		seal(0, 0);
+	})
+	.join()
+	.unwrap();
+
+	// XChaCha20Poly1305 is recorded too.
+	let secret_key = xchacha20poly1305::SecretKey::from_slice(&[2u8; 32]).unwrap();
+	let nonce = xchacha20poly1305::Nonce::from_slice(&[0u8; 24]).unwrap();
+	let mut dst_out = [0u8; 16 + 16];
+	xchacha20poly1305::seal(&secret_key, &nonce, &[0u8; 16], None, &mut dst_out).unwrap();
+	assert!(std::panic::catch_unwind(move || {
+		let mut dst_out = [0u8; 16 + 16];
+		xchacha20poly1305::seal(&secret_key, &nonce, &[0u8; 16], None, &mut dst_out)
+	})
+	.is_err());
+
+	stop_nonce_reuse_detection();
+	seal(0, 0);
+}
+
+#[cfg(feature = "nonce_reuse_detection")]
+#[test]
+fn test_nonce_reuse_detection_bounded() {
+	use crate::hazardous::aead::chacha20poly1305;
+
+	let secret_key = chacha20poly1305::SecretKey::from_slice(&[0u8; 32]).unwrap();
+	let mut dst_out = [0u8; 1 + 16];
+	let mut seal = |counter: u32| {
+		let mut nonce = [0u8; 12];
+		nonce[..4].copy_from_slice(&counter.to_le_bytes());
+		let nonce = chacha20poly1305::Nonce::from_slice(&nonce).unwrap();
+		chacha20poly1305::seal(&secret_key, &nonce, &[0u8; 1], None, &mut dst_out).unwrap();
+	};
+
+	start_nonce_reuse_detection();
+	for counter in 0..=(NONCE_HISTORY_SIZE as u32) {
+		seal(counter);
+	}
+	// The first nonce has been forgotten.
+	seal(0);
+	stop_nonce_reuse_detection();
+}
+
+#[cfg(feature = "deterministic_rng")]
+#[test]
+fn test_deterministic_rng() {
+	set_deterministic_rng(|dst: &mut [u8]| {
+		for byte in dst.iter_mut() {
+			*byte = 0xa5;
+		}
+	});
+	let mut dst = [0u8; 64];
+	secure_rand_bytes(&mut dst).unwrap();
+	assert_eq!(dst[..], [0xa5u8; 64][..]);
+
+	// Still rejected before the source is used.
+	assert!(secure_rand_bytes(&mut [0u8; 0]).is_err());
+
+	// Other threads are not affected.
+	std::thread::spawn(|| {
+		let mut dst = [0u8; 64];
+		secure_rand_bytes(&mut dst).unwrap();
+		assert_ne!(dst[..], [0xa5u8; 64][..]);
+	})
+	.join()
+	.unwrap();
+
+	clear_deterministic_rng();
+	let mut dst = [0u8; 64];
+	secure_rand_bytes(&mut dst).unwrap();
+	assert_ne!(dst[..], [0xa5u8; 64][..]);
+}
+
+#[cfg(feature = "deterministic_rng")]
+#[test]
+fn test_deterministic_rng_reproducible_seal() {
+	use crate::aead;
+
+	let counter_source = || {
+		let mut counter = 0u8;
+		move |dst: &mut [u8]| {
+			for byte in dst.iter_mut() {
+				*byte = counter;
+				counter = counter.wrapping_add(1);
+			}
+		}
+	};
+	let secret_key = aead::SecretKey::from_slice(&[0u8; 32]).unwrap();
+
+	set_deterministic_rng(counter_source());
+	let first = aead::seal(&secret_key, b"Secret message").unwrap();
+	set_deterministic_rng(counter_source());
+	let second = aead::seal(&secret_key, b"Secret message").unwrap();
+	clear_deterministic_rng();
+
+	assert_eq!(first, second);
+	assert_eq!(first[..24], (0u8..24).collect::<Vec<u8>>()[..]);
+}
+
 #[cfg(feature = "safe_api")]
 #[test]
 fn test_ct_eq_ok() {
@@ -134,6 +789,62 @@ fn test_ct_eq_ok() {
 	assert_eq!(secure_cmp(&buf_2, &buf_1).unwrap(), true);
 }
 
+#[test]
+fn test_secure_erase() {
+	let mut buf = [0xffu8; 64];
+	secure_erase(&mut buf);
+	assert_eq!(buf[..], [0u8; 64][..]);
+
+	let mut empty = [0u8; 0];
+	secure_erase(&mut empty);
+}
+
+#[test]
+fn test_pad_unpad() {
+	for blocksize in [1, 2, 15, 16, 17, 300].iter() {
+		for unpadded_len in 0..40 {
+			let mut buf = [0xffu8; 400];
+			let padded_len = pad(&mut buf, unpadded_len, *blocksize).unwrap();
+
+			assert!(padded_len > unpadded_len);
+			assert!(padded_len - unpadded_len <= *blocksize);
+			assert_eq!(padded_len % *blocksize, 0);
+			assert_eq!(buf[unpadded_len], 0x80);
+			assert!(buf[(unpadded_len + 1)..padded_len].iter().all(|byte| *byte == 0));
+			assert_eq!(unpad(&buf[..padded_len], *blocksize).unwrap(), unpadded_len);
+		}
+	}
+}
+
+#[test]
+fn test_pad_err() {
+	let mut buf = [0u8; 16];
+	assert!(pad(&mut buf, 0, 0).is_err());
+	assert!(pad(&mut buf, 17, 4).is_err());
+	assert!(pad(&mut buf, 16, 16).is_err());
+	assert!(pad(&mut buf, 15, 16).is_ok());
+	assert!(pad(&mut buf, 4, 4).is_ok());
+	assert!(pad(&mut buf, 0, usize::max_value()).is_err());
+}
+
+#[test]
+fn test_unpad_err() {
+	assert!(unpad(&[0x80], 0).is_err());
+	assert!(unpad(&[], 4).is_err());
+	assert!(unpad(&[0x80, 0, 0], 4).is_err());
+	// No 0x80 byte.
+	assert!(unpad(&[0, 0, 0, 0], 4).is_err());
+	// Non-zero byte after the 0x80 byte.
+	assert!(unpad(&[0x80, 0, 1, 0], 4).is_err());
+	assert!(unpad(&[0, 0, 0, 0x81], 4).is_err());
+	// The 0x80 byte is outside the last block.
+	assert!(unpad(&[0x80, 0, 0, 0, 0, 0, 0, 0], 4).is_err());
+
+	assert_eq!(unpad(&[0x80, 0, 0, 0], 4).unwrap(), 0);
+	assert_eq!(unpad(&[0x80, 0x80, 0, 0], 4).unwrap(), 1);
+	assert_eq!(unpad(&[0, 0, 0, 0x80], 4).unwrap(), 3);
+}
+
 #[test]
 fn test_ct_eq_diff_len() {
 	let buf_1 = [0x06; 10];
@@ -157,3 +868,106 @@ fn test_ct_ne_reg() {
 	assert!(secure_cmp(&[0], &[0, 1]).is_err());
 	assert!(secure_cmp(&[0, 1], &[0]).is_err());
 }
+
+#[cfg(feature = "safe_api")]
+#[test]
+fn test_uniform_below() {
+	assert!(uniform_below(0).is_err());
+	assert_eq!(uniform_below(1).unwrap(), 0);
+	for _ in 0..100 {
+		assert!(uniform_below(3).unwrap() < 3);
+		assert!(uniform_below(u64::max_value()).unwrap() < u64::max_value());
+		assert!(uniform_below((1 << 63) + 1).unwrap() < (1 << 63) + 1);
+	}
+}
+
+#[cfg(feature = "safe_api")]
+#[test]
+fn test_uniform_below_all_values() {
+	let mut seen = [false; 6];
+	for _ in 0..1000 {
+		seen[uniform_below(6).unwrap() as usize] = true;
+	}
+
+	assert!(seen.iter().all(|value| *value));
+}
+
+#[cfg(feature = "safe_api")]
+#[test]
+fn test_eff_large_wordlist() {
+	let words: Vec<&str> = EFF_LARGE_WORDLIST.lines().collect();
+	assert_eq!(words.len(), 7776);
+	assert_eq!(words[0], "abacus");
+	assert_eq!(words[7775], "zoom");
+	assert!(words.iter().all(|word| !word.is_empty() && !word.contains(' ')));
+}
+
+#[cfg(feature = "safe_api")]
+#[test]
+fn test_generate_passphrase() {
+	assert!(generate_passphrase(0).is_err());
+
+	let words: Vec<&str> = EFF_LARGE_WORDLIST.lines().collect();
+	for wordcount in 1..10 {
+		let passphrase = generate_passphrase(wordcount).unwrap();
+		assert_eq!(passphrase.split(' ').count(), wordcount);
+		assert!(passphrase.split(' ').all(|word| words.contains(&word)));
+	}
+
+	assert_ne!(generate_passphrase(6).unwrap(), generate_passphrase(6).unwrap());
+}
+
+#[cfg(feature = "safe_api")]
+#[test]
+fn test_generate_password() {
+	assert!(generate_password(0, "abc").is_err());
+	assert!(generate_password(10, "").is_err());
+
+	let password = generate_password(1, "a").unwrap();
+	assert_eq!(password, "a");
+
+	let password = generate_password(64, "abcæøå").unwrap();
+	assert_eq!(password.chars().count(), 64);
+	assert!(password.chars().all(|chr| "abcæøå".contains(chr)));
+}
+
+#[cfg(feature = "safe_api")]
+#[test]
+fn test_random_u64() {
+	// The chance of this failing is 2^-64.
+	assert_ne!(random_u64().unwrap(), random_u64().unwrap());
+}
+
+#[cfg(feature = "safe_api")]
+#[test]
+fn test_random_range() {
+	assert!(random_range(0..0).is_err());
+	assert!(random_range(5..5).is_err());
+	#[allow(clippy::reversed_empty_ranges)]
+	let reversed = 6..5;
+	assert!(random_range(reversed).is_err());
+
+	assert_eq!(random_range(5..6).unwrap(), 5);
+	assert_eq!(random_range(u64::max_value() - 1..u64::max_value()).unwrap(), u64::max_value() - 1);
+	for _ in 0..100 {
+		let value = random_range(10..20).unwrap();
+		assert!(value >= 10 && value < 20);
+		assert!(random_range(0..u64::max_value()).unwrap() < u64::max_value());
+	}
+}
+
+#[cfg(feature = "safe_api")]
+#[test]
+fn test_random_choice() {
+	let empty: [u8; 0] = [];
+	assert!(random_choice(&empty).is_err());
+	assert_eq!(*random_choice(&[7u8]).unwrap(), 7);
+
+	let items = [1, 2, 3];
+	let mut seen = [false; 3];
+	for _ in 0..1000 {
+		seen[*random_choice(&items).unwrap() - 1] = true;
+	}
+
+	assert!(seen.iter().all(|value| *value));
+}
@@ -0,0 +1,448 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Server-side checks of WebAuthn assertions.
+//!
+//! # Use case:
+//! `orion::webauthn` can be used by a relying party to check a WebAuthn
+//! authentication ceremony, before verifying the signature of the
+//! authenticator.
+//!
+//! # About:
+//! - Implements steps of [Verifying an Authentication Assertion](https://www.w3.org/TR/webauthn-2/#sctn-verifying-assertion)
+//!   from WebAuthn Level 2: parsing the authenticator data and client data,
+//!   and checking the type, challenge, origin, RP ID hash, flags and
+//!   signature counter.
+//! - The signature is not verified here, since orion has no ES256 or Ed25519
+//!   signatures. Once `check_assertion()` succeeds, the signature must be
+//!   verified over `Assertion::signed_data()` with the public key stored for
+//!   the credential.
+//! - Only assertions are supported. Authenticator data with attested
+//!   credential data, which is only present during registration, is
+//!   rejected.
+//! - This module is only available with the `webauthn` feature, which
+//!   requires `safe_api`.
+//!
+//! # Parameters:
+//! - `authenticator_data`: The `authenticatorData` of the assertion response.
+//! - `client_data_json`: The `clientDataJSON` of the assertion response.
+//! - `rp_id`: The RP ID of the relying party.
+//! - `origin`: The origin that the ceremony is expected to come from.
+//! - `challenge`: The challenge that was sent to the client.
+//! - `user_verification_required`: Whether the UV flag must be set.
+//! - `stored_sign_count`: The signature counter stored for the credential.
+//!
+//! # Errors:
+//! An error will be returned if:
+//! - `authenticator_data` is shorter than 37 bytes, or contains attested
+//!   credential data.
+//! - `client_data_json` is not a JSON object.
+//! - The `type` of the client data is not `webauthn.get`.
+//! - The `challenge` of the client data is not `challenge`, Base64url encoded.
+//! - The `origin` of the client data is not `origin`.
+//! - The RP ID hash of `authenticator_data` is not the SHA256 hash of `rp_id`.
+//! - The UP flag is not set, or the UV flag is not set when
+//!   `user_verification_required` is `true`.
+//! - The signature counter is not greater than `stored_sign_count`, unless
+//!   both are zero.
+//!
+//! # Security:
+//! - __**An `Assertion` is not authenticated**__ until its signature has been
+//!   verified.
+//! - The `challenge` must be generated using a CSPRNG for each ceremony, and
+//!   only be accepted once. `util::secure_rand_bytes()` can be used for this.
+//! - A failing signature counter check may indicate a cloned authenticator.
+//!   Store the new counter from `Assertion::sign_count()` only after the
+//!   signature is verified.
+//!
+//! # Example:
+//! ```
+//! use orion::webauthn;
+//!
+//! # let mut authenticator_data = orion::hazardous::hash::sha256::digest(b"example.com")
+//! # 	.unwrap()
+//! # 	.as_bytes()
+//! # 	.to_vec();
+//! # authenticator_data.extend_from_slice(&[0x05, 0, 0, 0, 1]);
+//! # let client_data_json = b"{\"type\":\"webauthn.get\",\"challenge\":\"AAECAw\",\"origin\":\"https://example.com\"}";
+//! let challenge = [0, 1, 2, 3];
+//! let assertion = webauthn::check_assertion(
+//! 	&authenticator_data,
+//! 	client_data_json,
+//! 	"example.com",
+//! 	"https://example.com",
+//! 	&challenge,
+//! 	true,
+//! 	0,
+//! )
+//! .unwrap();
+//!
+//! // Verify the signature over `assertion.signed_data()` with the credential
+//! // public key, then store the new signature counter.
+//! assert_eq!(assertion.sign_count(), 1);
+//! ```
+
+use crate::{
+	encoding::base64_decode,
+	errors::UnknownCryptoError,
+	hazardous::{constants::SHA256_OUTSIZE, hash::sha256},
+	util,
+};
+use serde_json::Value;
+
+/// The flag that is set when the user was present.
+const FLAG_USER_PRESENT: u8 = 0x01;
+/// The flag that is set when the user was verified.
+const FLAG_USER_VERIFIED: u8 = 0x04;
+/// The flag that is set when the credential can be backed up.
+const FLAG_BACKUP_ELIGIBLE: u8 = 0x08;
+/// The flag that is set when the credential is backed up.
+const FLAG_BACKUP_STATE: u8 = 0x10;
+/// The flag that is set when attested credential data is included.
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+/// The flag that is set when extensions are included.
+const FLAG_EXTENSION_DATA: u8 = 0x80;
+
+/// The size of authenticator data without optional fields.
+const AUTHENTICATOR_DATA_MIN_SIZE: usize = SHA256_OUTSIZE + 1 + 4;
+
+#[derive(Debug, Clone, PartialEq)]
+/// Parsed authenticator data of an assertion.
+pub struct AuthenticatorData {
+	rp_id_hash: sha256::Digest,
+	flags: u8,
+	sign_count: u32,
+	extensions: Option<Vec<u8>>,
+}
+
+impl AuthenticatorData {
+	#[must_use]
+	/// Parse the authenticator data of an assertion.
+	pub fn from_slice(bytes: &[u8]) -> Result<Self, UnknownCryptoError> {
+		if bytes.len() < AUTHENTICATOR_DATA_MIN_SIZE {
+			return Err(UnknownCryptoError);
+		}
+
+		let flags = bytes[SHA256_OUTSIZE];
+		if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+			return Err(UnknownCryptoError);
+		}
+
+		let rest = &bytes[AUTHENTICATOR_DATA_MIN_SIZE..];
+		let extensions = match (flags & FLAG_EXTENSION_DATA != 0, rest.is_empty()) {
+			(true, false) => Some(rest.to_vec()),
+			(false, true) => None,
+			_ => return Err(UnknownCryptoError),
+		};
+
+		let mut sign_count = [0u8; 4];
+		sign_count.copy_from_slice(&bytes[(SHA256_OUTSIZE + 1)..AUTHENTICATOR_DATA_MIN_SIZE]);
+
+		Ok(Self {
+			rp_id_hash: sha256::Digest::from_slice(&bytes[..SHA256_OUTSIZE])?,
+			flags,
+			sign_count: u32::from_be_bytes(sign_count),
+			extensions,
+		})
+	}
+
+	/// The SHA256 hash of the RP ID that the credential is scoped to.
+	pub fn rp_id_hash(&self) -> &sha256::Digest { &self.rp_id_hash }
+
+	/// Whether the UP flag is set.
+	pub fn user_present(&self) -> bool { self.flags & FLAG_USER_PRESENT != 0 }
+
+	/// Whether the UV flag is set.
+	pub fn user_verified(&self) -> bool { self.flags & FLAG_USER_VERIFIED != 0 }
+
+	/// Whether the BE flag is set.
+	pub fn backup_eligible(&self) -> bool { self.flags & FLAG_BACKUP_ELIGIBLE != 0 }
+
+	/// Whether the BS flag is set.
+	pub fn backed_up(&self) -> bool { self.flags & FLAG_BACKUP_STATE != 0 }
+
+	/// The signature counter.
+	pub fn sign_count(&self) -> u32 { self.sign_count }
+
+	/// The CBOR encoded extension outputs, if any.
+	pub fn extensions(&self) -> Option<&[u8]> { self.extensions.as_deref() }
+}
+
+#[must_use]
+/// Return the SHA256 hash of `client_data_json`, which is what the
+/// authenticator signs along with the authenticator data.
+pub fn client_data_hash(client_data_json: &[u8]) -> Result<sha256::Digest, UnknownCryptoError> {
+	sha256::digest(client_data_json)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// An assertion whose contents have been checked, but whose signature has not
+/// been verified.
+pub struct Assertion {
+	authenticator_data: AuthenticatorData,
+	signed_data: Vec<u8>,
+}
+
+impl Assertion {
+	/// The parsed authenticator data.
+	pub fn authenticator_data(&self) -> &AuthenticatorData { &self.authenticator_data }
+
+	/// The signature counter, to store once the signature is verified.
+	pub fn sign_count(&self) -> u32 { self.authenticator_data.sign_count }
+
+	/// The data that the signature of the assertion must be verified over,
+	/// which is the authenticator data followed by the client data hash.
+	pub fn signed_data(&self) -> &[u8] { &self.signed_data }
+}
+
+/// Return the string member `name` of the JSON object `object`.
+fn json_str<'a>(object: &'a Value, name: &str) -> Result<&'a str, UnknownCryptoError> {
+	object
+		.get(name)
+		.and_then(Value::as_str)
+		.ok_or(UnknownCryptoError)
+}
+
+#[must_use]
+/// Parse and check a WebAuthn assertion, except for its signature.
+pub fn check_assertion(
+	authenticator_data: &[u8],
+	client_data_json: &[u8],
+	rp_id: &str,
+	origin: &str,
+	challenge: &[u8],
+	user_verification_required: bool,
+	stored_sign_count: u32,
+) -> Result<Assertion, UnknownCryptoError> {
+	let client_data: Value =
+		serde_json::from_slice(client_data_json).map_err(|_| UnknownCryptoError)?;
+	if !client_data.is_object() {
+		return Err(UnknownCryptoError);
+	}
+	if json_str(&client_data, "type")? != "webauthn.get" {
+		return Err(UnknownCryptoError);
+	}
+	let received_challenge = base64_decode(json_str(&client_data, "challenge")?, true)?;
+	util::secure_cmp(&received_challenge, challenge)?;
+	if json_str(&client_data, "origin")? != origin {
+		return Err(UnknownCryptoError);
+	}
+
+	let parsed = AuthenticatorData::from_slice(authenticator_data)?;
+	util::secure_cmp(
+		parsed.rp_id_hash.as_bytes(),
+		sha256::digest(rp_id.as_bytes())?.as_bytes(),
+	)?;
+	if !parsed.user_present() {
+		return Err(UnknownCryptoError);
+	}
+	if user_verification_required && !parsed.user_verified() {
+		return Err(UnknownCryptoError);
+	}
+	if (parsed.sign_count != 0 || stored_sign_count != 0) && parsed.sign_count <= stored_sign_count
+	{
+		return Err(UnknownCryptoError);
+	}
+
+	let mut signed_data = authenticator_data.to_vec();
+	signed_data.extend_from_slice(client_data_hash(client_data_json)?.as_bytes());
+
+	Ok(Assertion {
+		authenticator_data: parsed,
+		signed_data,
+	})
+}
+
+// Testing public functions in the module.
+#[cfg(test)]
+mod public {
+	use super::*;
+	use crate::encoding::hex_decode_into;
+
+	// SHA256("example.com").
+	const RP_ID_HASH: &str = "a379a6f6eeafb9a55e378c118034e2751e682fab9f2d30ab13d2125586ce1947";
+	const ORIGIN: &str = "https://example.com";
+	const CHALLENGE: [u8; 4] = [0, 1, 2, 3];
+
+	fn authenticator_data(flags: u8, sign_count: u32) -> Vec<u8> {
+		let mut data = vec![0u8; SHA256_OUTSIZE];
+		hex_decode_into(RP_ID_HASH, &mut data).unwrap();
+		data.push(flags);
+		data.extend_from_slice(&sign_count.to_be_bytes());
+		data
+	}
+
+	fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+		format!(
+			"{{\"type\":\"{}\",\"challenge\":\"{}\",\"origin\":\"{}\",\"crossOrigin\":false}}",
+			kind, challenge, origin
+		)
+		.into_bytes()
+	}
+
+	fn check(
+		authenticator_data: &[u8],
+		client_data_json: &[u8],
+	) -> Result<Assertion, UnknownCryptoError> {
+		check_assertion(
+			authenticator_data,
+			client_data_json,
+			"example.com",
+			ORIGIN,
+			&CHALLENGE,
+			false,
+			5,
+		)
+	}
+
+	mod test_authenticator_data {
+		use super::*;
+
+		#[test]
+		fn test_parse() {
+			let parsed =
+				AuthenticatorData::from_slice(&authenticator_data(0x1d, 0x0102_0304)).unwrap();
+			assert_eq!(parsed.rp_id_hash().to_string(), RP_ID_HASH);
+			assert!(parsed.user_present());
+			assert!(parsed.user_verified());
+			assert!(parsed.backup_eligible());
+			assert!(parsed.backed_up());
+			assert_eq!(parsed.sign_count(), 0x0102_0304);
+			assert_eq!(parsed.extensions(), None);
+
+			let parsed = AuthenticatorData::from_slice(&authenticator_data(0x00, 0)).unwrap();
+			assert!(!parsed.user_present());
+			assert!(!parsed.user_verified());
+			assert!(!parsed.backup_eligible());
+			assert!(!parsed.backed_up());
+		}
+
+		#[test]
+		fn test_extensions() {
+			let mut data = authenticator_data(0x81, 1);
+			data.extend_from_slice(&[0xa1, 0x61, 0x78, 0xf5]);
+			let parsed = AuthenticatorData::from_slice(&data).unwrap();
+			assert_eq!(parsed.extensions(), Some(&[0xa1, 0x61, 0x78, 0xf5][..]));
+
+			// Extension data without the ED flag, and the ED flag without data.
+			data[SHA256_OUTSIZE] = 0x01;
+			assert!(AuthenticatorData::from_slice(&data).is_err());
+			assert!(AuthenticatorData::from_slice(&authenticator_data(0x81, 1)).is_err());
+		}
+
+		#[test]
+		fn test_bad_input_err() {
+			let data = authenticator_data(0x01, 1);
+			assert!(AuthenticatorData::from_slice(&data[..36]).is_err());
+			assert!(AuthenticatorData::from_slice(&[]).is_err());
+			assert!(AuthenticatorData::from_slice(&authenticator_data(0x41, 1)).is_err());
+		}
+	}
+
+	mod test_check_assertion {
+		use super::*;
+
+		#[test]
+		fn test_valid_assertion() {
+			let data = authenticator_data(0x05, 6);
+			let client_data_json = client_data("webauthn.get", "AAECAw", ORIGIN);
+			let assertion = check(&data, &client_data_json).unwrap();
+
+			assert_eq!(assertion.sign_count(), 6);
+			assert!(assertion.authenticator_data().user_verified());
+			assert_eq!(assertion.signed_data()[..37], data[..]);
+			assert_eq!(
+				assertion.signed_data()[37..],
+				*client_data_hash(&client_data_json).unwrap().as_bytes()
+			);
+		}
+
+		#[test]
+		fn test_client_data_mismatch_err() {
+			let data = authenticator_data(0x01, 6);
+			assert!(check(&data, &client_data("webauthn.get", "AAECAw", ORIGIN)).is_ok());
+			assert!(check(&data, &client_data("webauthn.create", "AAECAw", ORIGIN)).is_err());
+			assert!(check(&data, &client_data("webauthn.get", "AAECBA", ORIGIN)).is_err());
+			assert!(check(&data, &client_data("webauthn.get", "AAECAwQ", ORIGIN)).is_err());
+			assert!(check(&data, &client_data("webauthn.get", "AAECAw==", ORIGIN)).is_err());
+			assert!(check(
+				&data,
+				&client_data("webauthn.get", "AAECAw", "https://evil.com")
+			)
+			.is_err());
+			assert!(check(&data, b"[]").is_err());
+			assert!(check(&data, b"{\"type\":\"webauthn.get\"}").is_err());
+			assert!(check(&data, b"").is_err());
+		}
+
+		#[test]
+		fn test_rp_id_mismatch_err() {
+			let mut data = authenticator_data(0x01, 6);
+			data[0] ^= 1;
+			assert!(check(&data, &client_data("webauthn.get", "AAECAw", ORIGIN)).is_err());
+		}
+
+		#[test]
+		fn test_flags() {
+			let client_data_json = client_data("webauthn.get", "AAECAw", ORIGIN);
+			let check_uv = |flags: u8| {
+				check_assertion(
+					&authenticator_data(flags, 6),
+					&client_data_json,
+					"example.com",
+					ORIGIN,
+					&CHALLENGE,
+					true,
+					5,
+				)
+			};
+
+			assert!(check(&authenticator_data(0x00, 6), &client_data_json).is_err());
+			assert!(check(&authenticator_data(0x04, 6), &client_data_json).is_err());
+			assert!(check_uv(0x01).is_err());
+			assert!(check_uv(0x05).is_ok());
+		}
+
+		#[test]
+		fn test_sign_count() {
+			let client_data_json = client_data("webauthn.get", "AAECAw", ORIGIN);
+			let check_count = |sign_count: u32, stored_sign_count: u32| {
+				check_assertion(
+					&authenticator_data(0x01, sign_count),
+					&client_data_json,
+					"example.com",
+					ORIGIN,
+					&CHALLENGE,
+					false,
+					stored_sign_count,
+				)
+			};
+
+			assert!(check_count(0, 0).is_ok());
+			assert!(check_count(1, 0).is_ok());
+			assert!(check_count(6, 5).is_ok());
+			assert!(check_count(5, 5).is_err());
+			assert!(check_count(4, 5).is_err());
+			assert!(check_count(0, 5).is_err());
+		}
+	}
+}
@@ -1,15 +1,18 @@
 pub mod boringssl_chacha20_poly1305;
 pub mod boringssl_xchacha20_poly1305;
+pub mod openssl_aes_siv;
 pub mod other_aead_xchacha20_poly1305;
 pub mod rfc_aead_chacha20_poly1305;
 pub mod wycheproof_chacha20_poly1305;
 
+extern crate hex;
 extern crate orion;
 use self::{
 	aead::{
 		chacha20poly1305::{self, SecretKey},
 		xchacha20poly1305,
 	},
+	hex::decode,
 	orion::{
 		errors::UnknownCryptoError,
 		hazardous::{aead, constants},
@@ -197,3 +200,18 @@ fn wycheproof_test_runner(
 
 	Ok(())
 }
+
+pub fn aes_siv_test_runner(key: &str, plaintext: &str, ad: Option<&str>, expected: &str) {
+	let secret_key = aead::aes_siv::SecretKey::from_slice(&decode(key).unwrap()).unwrap();
+	let plaintext = decode(plaintext).unwrap();
+	let ad = ad.map(|ad| decode(ad).unwrap());
+	let expected = decode(expected).unwrap();
+
+	let mut dst_ct_out = vec![0u8; plaintext.len() + 16];
+	aead::aes_siv::seal(&secret_key, &plaintext, ad.as_deref(), &mut dst_ct_out).unwrap();
+	assert_eq!(dst_ct_out, expected);
+
+	let mut dst_pt_out = vec![0u8; plaintext.len()];
+	aead::aes_siv::open(&secret_key, &expected, ad.as_deref(), &mut dst_pt_out).unwrap();
+	assert_eq!(dst_pt_out, plaintext);
+}
@@ -0,0 +1,59 @@
+// Testing against AEAD_AES_SIV_CMAC_512 test vectors generated with OpenSSL.
+// The key is the bytes 0x00..0x3f.
+#[cfg(test)]
+mod openssl_aes_siv {
+
+	use crate::aead::aes_siv_test_runner;
+
+	const KEY: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f";
+
+	#[test]
+	fn empty_plaintext_with_ad() {
+		aes_siv_test_runner(
+			KEY,
+			"",
+			Some("7573657273"),
+			"ccb96169513fe26f6a460c6b42e56387",
+		);
+	}
+
+	#[test]
+	fn short_plaintext_no_ad() {
+		aes_siv_test_runner(
+			KEY,
+			"112233445566778899aabbccddee",
+			None,
+			"c561d53546fd103cd5a6e4d39112abddadc39943b2f0cf1517c3fd6efa3d",
+		);
+	}
+
+	#[test]
+	fn short_plaintext_empty_ad() {
+		aes_siv_test_runner(
+			KEY,
+			"112233445566778899aabbccddee",
+			Some(""),
+			"956c2bf56e4501728ae2079d6b73ce05df466d1acd665bfab51b4057cd83",
+		);
+	}
+
+	#[test]
+	fn block_plaintext_with_ad() {
+		aes_siv_test_runner(
+			KEY,
+			"00112233445566778899aabbccddeeff",
+			Some("101112131415161718191a1b1c1d1e1f2021222324252627"),
+			"55c2d980fd36bc9508eb5ef90414fb29bb458cf8c8ab0f742e6254ad5ae0e2f7",
+		);
+	}
+
+	#[test]
+	fn long_plaintext_with_ad() {
+		aes_siv_test_runner(
+			KEY,
+			"616c696365406578616d706c652e636f6d206973206120626c696e6420696e6465782076616c7565",
+			Some("75736572732e656d61696c"),
+			"813674c888ccef7ecd97d5613bb99344f58d75a3e32c4bdf24e46f010c6bc95ce4d9b7548d8cbff24a71cdcd77d058b00b52d9839658ccea",
+		);
+	}
+}
@@ -0,0 +1,76 @@
+// Testing against FIPS 197 and NIST SP 800-38A test vectors
+#[cfg(test)]
+mod fips197_aes {
+
+	use crate::cipher::aes_test_runner;
+
+	#[test]
+	// FIPS 197, Appendix B
+	fn aes128_cipher_example() {
+		aes_test_runner(
+			"2b7e151628aed2a6abf7158809cf4f3c",
+			"3243f6a8885a308d313198a2e0370734",
+			"3925841d02dc09fbdc118597196a0b32",
+		);
+	}
+
+	#[test]
+	// FIPS 197, Appendix C.1
+	fn aes128_example_vector() {
+		aes_test_runner(
+			"000102030405060708090a0b0c0d0e0f",
+			"00112233445566778899aabbccddeeff",
+			"69c4e0d86a7b0430d8cdb78070b4c55a",
+		);
+	}
+
+	#[test]
+	// FIPS 197, Appendix C.2
+	fn aes192_example_vector() {
+		aes_test_runner(
+			"000102030405060708090a0b0c0d0e0f1011121314151617",
+			"00112233445566778899aabbccddeeff",
+			"dda97ca4864cdfe06eaf70a0ec0d7191",
+		);
+	}
+
+	#[test]
+	// FIPS 197, Appendix C.3
+	fn aes256_example_vector() {
+		aes_test_runner(
+			"000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+			"00112233445566778899aabbccddeeff",
+			"8ea2b7ca516745bfeafc49904b496089",
+		);
+	}
+
+	#[test]
+	// NIST SP 800-38A, F.1.1 ECB-AES128.Encrypt, block #1
+	fn aes128_ecb_block_1() {
+		aes_test_runner(
+			"2b7e151628aed2a6abf7158809cf4f3c",
+			"6bc1bee22e409f96e93d7e117393172a",
+			"3ad77bb40d7a3660a89ecaf32466ef97",
+		);
+	}
+
+	#[test]
+	// NIST SP 800-38A, F.1.3 ECB-AES192.Encrypt, block #1
+	fn aes192_ecb_block_1() {
+		aes_test_runner(
+			"8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b",
+			"6bc1bee22e409f96e93d7e117393172a",
+			"bd334f1d6e45f25ff712a214571fa5cc",
+		);
+	}
+
+	#[test]
+	// NIST SP 800-38A, F.1.5 ECB-AES256.Encrypt, block #1
+	fn aes256_ecb_block_1() {
+		aes_test_runner(
+			"603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4",
+			"6bc1bee22e409f96e93d7e117393172a",
+			"f3eed1bdb5d2a03c064b5a7e3db181f8",
+		);
+	}
+}
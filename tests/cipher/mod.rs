@@ -0,0 +1,67 @@
+pub mod fips197_aes;
+pub mod ieee1619_xts;
+
+extern crate hex;
+extern crate orion;
+
+use self::{
+	hex::decode,
+	orion::hazardous::cipher::{
+		aes::{Aes, SecretKey128, SecretKey192, SecretKey256},
+		xts::Xts,
+	},
+};
+
+pub fn aes_test_runner(key: &str, plaintext: &str, expected: &str) {
+	let key = decode(key).unwrap();
+	let cipher = match key.len() {
+		16 => Aes::new(&SecretKey128::from_slice(&key).unwrap()).unwrap(),
+		24 => Aes::new(&SecretKey192::from_slice(&key).unwrap()).unwrap(),
+		32 => Aes::new(&SecretKey256::from_slice(&key).unwrap()).unwrap(),
+		_ => panic!("Unsupported AES key size"),
+	};
+
+	let plaintext = decode(plaintext).unwrap();
+	let expected = decode(expected).unwrap();
+
+	let mut block = plaintext.clone();
+	cipher.encrypt_block(&mut block).unwrap();
+	assert_eq!(block, expected);
+
+	cipher.decrypt_block(&mut block).unwrap();
+	assert_eq!(block, plaintext);
+}
+
+pub fn xts_test_runner(
+	data_key: &str,
+	tweak_key: &str,
+	sector: u128,
+	plaintext: &str,
+	expected: &str,
+) {
+	let data_key = decode(data_key).unwrap();
+	let tweak_key = decode(tweak_key).unwrap();
+	let cipher = match data_key.len() {
+		16 => Xts::new(
+			&SecretKey128::from_slice(&data_key).unwrap(),
+			&SecretKey128::from_slice(&tweak_key).unwrap(),
+		)
+		.unwrap(),
+		32 => Xts::new(
+			&SecretKey256::from_slice(&data_key).unwrap(),
+			&SecretKey256::from_slice(&tweak_key).unwrap(),
+		)
+		.unwrap(),
+		_ => panic!("Unsupported XTS key size"),
+	};
+
+	let plaintext = decode(plaintext).unwrap();
+	let expected = decode(expected).unwrap();
+
+	let mut data = plaintext.clone();
+	cipher.encrypt_sector(sector, &mut data).unwrap();
+	assert_eq!(data, expected);
+
+	cipher.decrypt_sector(sector, &mut data).unwrap();
+	assert_eq!(data, plaintext);
+}
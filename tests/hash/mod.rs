@@ -1,9 +1,10 @@
 pub mod blake2b_kat;
 pub mod other_blake2b;
+pub mod other_sha256;
 pub mod sha512_nist_cavp;
 
 extern crate orion;
-use self::orion::hazardous::hash::{blake2b, sha512};
+use self::orion::hazardous::hash::{blake2b, sha256, sha512};
 
 fn blake2b_test_runner(input: &[u8], key: &[u8], output: &[u8]) {
 	// Only make SecretKey if test case key value is not empty, otherwise it will be
@@ -33,3 +34,15 @@ fn sha512_test_runner(data: &[u8], output: &[u8]) {
 	assert!(digest.as_bytes() == digest_one_shot.as_bytes());
 	assert!(digest.as_bytes() == output);
 }
+
+fn sha256_test_runner(data: &[u8], output: &[u8]) {
+	// Test streaming
+	let mut state = sha256::init();
+	state.update(data).unwrap();
+	let digest = state.finalize().unwrap();
+	// Test one-shot function
+	let digest_one_shot = sha256::digest(data).unwrap();
+
+	assert!(digest.as_bytes() == digest_one_shot.as_bytes());
+	assert!(digest.as_bytes() == output);
+}
@@ -1,14 +1,22 @@
 #[cfg(test)]
 pub mod aead;
 #[cfg(test)]
+pub mod cipher;
+#[cfg(test)]
 pub mod kdf;
 #[cfg(test)]
 pub mod mac;
 #[cfg(test)]
+#[cfg(feature = "paseto")]
+pub mod paseto;
+#[cfg(test)]
 pub mod stream;
 // See: https://github.com/brycx/orion/issues/15
 #[cfg(test)]
 pub mod hash;
 #[cfg(test)]
+#[cfg(feature = "test_framework")]
+pub mod test_framework;
+#[cfg(test)]
 #[cfg(target_endian = "little")]
 pub mod xof;
@@ -0,0 +1,20 @@
+pub mod paseto_v4;
+
+extern crate orion;
+use self::orion::paseto::v4_local;
+
+fn v4_local_test_runner(
+	key: &[u8],
+	token: &str,
+	footer: &[u8],
+	implicit_assertion: &[u8],
+	payload: Option<&[u8]>,
+) {
+	let secret_key = v4_local::SecretKey::from_slice(key).unwrap();
+	let result = v4_local::decrypt(&secret_key, token, footer, implicit_assertion);
+
+	match payload {
+		Some(expected) => assert_eq!(result.unwrap(), expected),
+		None => assert!(result.is_err()),
+	}
+}
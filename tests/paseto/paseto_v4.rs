@@ -0,0 +1,54 @@
+// Testing against the PASETO v4 test vectors.
+// Test vectors were pulled from: https://github.com/paseto-standard/test-vectors/blob/master/v4.json
+extern crate hex;
+extern crate serde_json;
+
+use self::hex::decode;
+
+use self::serde_json::{Deserializer, Value};
+use crate::paseto::v4_local_test_runner;
+use std::{fs::File, io::BufReader};
+
+#[test]
+fn test_paseto_v4_local() {
+	let file = File::open("./tests/test_data/original/paseto_v4.json").unwrap();
+	let reader = BufReader::new(file);
+	let stream = Deserializer::from_reader(reader).into_iter::<Value>();
+
+	for test_file in stream {
+		for test_vectors in test_file.unwrap().get("tests").unwrap().as_array() {
+			for test_case in test_vectors {
+				// Tokens that are expected to fail are decrypted with a v4.local
+				// key if they have one, and otherwise with the 32-byte seed of
+				// their v4.public key.
+				let key = match test_case.get("key") {
+					Some(key) => key,
+					None if test_case.get("expect-fail").unwrap().as_bool().unwrap() => {
+						test_case.get("secret-key-seed").unwrap()
+					}
+					// Valid v4.public tokens.
+					None => continue,
+				};
+
+				let payload = test_case.get("payload").unwrap().as_str();
+				v4_local_test_runner(
+					&decode(key.as_str().unwrap()).unwrap(),
+					test_case.get("token").unwrap().as_str().unwrap(),
+					test_case
+						.get("footer")
+						.unwrap()
+						.as_str()
+						.unwrap()
+						.as_bytes(),
+					test_case
+						.get("implicit-assertion")
+						.unwrap()
+						.as_str()
+						.unwrap()
+						.as_bytes(),
+					payload.map(|p| p.as_bytes()),
+				);
+			}
+		}
+	}
+}
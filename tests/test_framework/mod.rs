@@ -0,0 +1,95 @@
+// MIT License
+
+// Copyright (c) 2018-2019 The orion Developers
+
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+extern crate orion;
+extern crate serde_json;
+
+use self::{
+	orion::{
+		errors::UnknownCryptoError,
+		hazardous::{
+			aead::{chacha20poly1305::ChaCha20Poly1305, xchacha20poly1305::XChaCha20Poly1305},
+			hash::sha512::Sha512,
+			mac::hmac,
+		},
+		test_framework::{cavp, wycheproof},
+	},
+	serde_json::Value,
+};
+use std::fs;
+
+#[test]
+fn test_wycheproof_chacha20_poly1305() {
+	let vectors =
+		fs::read_to_string("./tests/test_data/original/Wycheproof_ChaCha20_Poly1305.json").unwrap();
+	let report = wycheproof::run_aead::<ChaCha20Poly1305>(&vectors).unwrap();
+
+	// orion does not accept empty plaintexts, so the valid test cases with an
+	// empty message are the only ones expected to fail.
+	let parsed: Value = serde_json::from_str(&vectors).unwrap();
+	let mut expected_failures = Vec::new();
+	for test_group in parsed["testGroups"].as_array().unwrap() {
+		for test_case in test_group["tests"].as_array().unwrap() {
+			if test_case["result"] == "valid" && test_case["msg"] == "" {
+				expected_failures.push(format!("tcId {}", test_case["tcId"]));
+			}
+		}
+	}
+
+	assert_eq!(report.run_count(), 151);
+	assert!(!expected_failures.is_empty());
+	assert_eq!(report.failures(), &expected_failures[..]);
+}
+
+#[test]
+fn test_wycheproof_xchacha20_poly1305_rejects_ietf_nonces() {
+	// All valid test cases use 96-bit nonces, which XChaCha20Poly1305 rejects.
+	let vectors =
+		fs::read_to_string("./tests/test_data/original/Wycheproof_ChaCha20_Poly1305.json").unwrap();
+	let report = wycheproof::run_aead::<XChaCha20Poly1305>(&vectors).unwrap();
+
+	assert_eq!(report.run_count(), 151);
+	assert!(!report.passed());
+}
+
+#[test]
+fn test_cavp_sha512() {
+	for (file, count) in [("SHA512ShortMsg.rsp", 129), ("SHA512LongMsg.rsp", 128)].iter() {
+		let rsp = fs::read_to_string(format!("./tests/test_data/original/{}", file)).unwrap();
+		let report = cavp::run_digest::<Sha512>(&rsp).unwrap();
+
+		assert_eq!(report.run_count(), *count);
+		assert!(report.passed(), "{:?}", report.failures());
+	}
+}
+
+#[test]
+fn test_cavp_hmac_sha512() {
+	let rsp = fs::read_to_string("./tests/test_data/original/HMAC.rsp").unwrap();
+	let hmac_init = |key: &[u8]| -> Result<hmac::Hmac, UnknownCryptoError> {
+		Ok(hmac::init(&hmac::SecretKey::from_slice(key)?))
+	};
+	let report = cavp::run_mac(hmac_init, hmac::Tag::unprotected_as_bytes, &rsp).unwrap();
+
+	assert!(report.run_count() > 0);
+	assert!(report.passed(), "{:?}", report.failures());
+}